@@ -10,6 +10,9 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tokio::fs;
 
+use crate::buffer;
+use crate::message::Category;
+
 const DEFAULT_THEME_NAME: &str = "Ferra";
 const DEFAULT_THEME_CONTENT: &str =
     include_str!("../../../assets/themes/ferra.toml");
@@ -80,6 +83,111 @@ pub enum Error {
     Write(#[from] std::io::Error),
     #[error("Failed to decode base64 theme string: {0}")]
     Base64Decode(#[from] base64::DecodeError),
+    #[error("Failed to read theme file: {0}")]
+    Read(std::io::Error),
+    #[error("Failed to parse theme file: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("Theme file uses the old format, which is no longer supported")]
+    Outdated,
+    #[error("No theme named {0}")]
+    NotFound(String),
+}
+
+/// Parses the colors of a theme file
+pub fn parse(content: &str) -> Result<Colors, Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Data {
+        V1 {
+            #[serde(rename = "name")]
+            _name: String,
+        },
+        V2(Colors),
+    }
+
+    match toml::from_str(content)? {
+        Data::V1 { .. } => Err(Error::Outdated),
+        Data::V2(colors) => Ok(colors),
+    }
+}
+
+/// Key colors of a theme, for showing it without loading all of it
+#[derive(Debug, Clone)]
+pub struct Preview {
+    pub name: String,
+    pub background: Color,
+    /// Primary and secondary text, nicknames and URLs
+    pub swatches: [Color; 4],
+}
+
+impl Preview {
+    pub fn default_theme() -> Self {
+        let colors = Colors::default();
+
+        Self {
+            name: DEFAULT_THEME_NAME.to_string(),
+            background: colors.general.background,
+            swatches: [
+                colors.text.primary,
+                colors.text.secondary,
+                colors.buffer.nickname,
+                colors.buffer.url,
+            ],
+        }
+    }
+}
+
+/// Reads only the colors of a theme file which are shown in its [`Preview`]
+pub fn peek(name: String, content: &str) -> Result<Preview, Error> {
+    #[derive(Deserialize)]
+    struct Peek {
+        name: Option<serde::de::IgnoredAny>,
+        #[serde(default)]
+        general: PeekGeneral,
+        #[serde(default)]
+        text: PeekText,
+        #[serde(default)]
+        buffer: PeekBuffer,
+    }
+
+    #[derive(Deserialize, Default)]
+    struct PeekGeneral {
+        #[serde(default = "default_transparent", with = "color_serde")]
+        background: Color,
+    }
+
+    #[derive(Deserialize, Default)]
+    struct PeekText {
+        #[serde(default = "default_transparent", with = "color_serde")]
+        primary: Color,
+        #[serde(default = "default_transparent", with = "color_serde")]
+        secondary: Color,
+    }
+
+    #[derive(Deserialize, Default)]
+    struct PeekBuffer {
+        #[serde(default = "default_transparent", with = "color_serde")]
+        nickname: Color,
+        #[serde(default = "default_transparent", with = "color_serde")]
+        url: Color,
+    }
+
+    let peek: Peek = toml::from_str(content)?;
+
+    if peek.name.is_some() {
+        return Err(Error::Outdated);
+    }
+
+    Ok(Preview {
+        name,
+        background: peek.general.background,
+        swatches: [
+            peek.text.primary,
+            peek.text.secondary,
+            peek.buffer.nickname,
+            peek.buffer.url,
+        ],
+    })
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
@@ -132,8 +240,12 @@ pub struct Buffer {
     pub code: Color,
     #[serde(default = "default_transparent", with = "color_serde")]
     pub highlight: Color,
+    #[serde(default, with = "color_serde_maybe")]
+    pub mention: Option<Color>,
     #[serde(default = "default_transparent", with = "color_serde")]
     pub nickname: Color,
+    #[serde(default)]
+    pub row_background: RowBackground,
     #[serde(default = "default_transparent", with = "color_serde")]
     pub selection: Color,
     #[serde(default)]
@@ -170,10 +282,44 @@ pub struct ServerMessages {
     pub standard_reply_note: Option<Color>,
     #[serde(default, with = "color_serde_maybe")]
     pub wallops: Option<Color>,
+    #[serde(default, with = "color_serde_maybe")]
+    pub ctcp: Option<Color>,
+    #[serde(default, with = "color_serde_maybe")]
+    pub notice: Option<Color>,
     #[serde(default = "default_transparent", with = "color_serde")]
     pub default: Color,
 }
 
+/// Backgrounds of message rows, used with `buffer.row_styling`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct RowBackground {
+    #[serde(default, with = "color_serde_maybe")]
+    pub own: Option<Color>,
+    #[serde(default, with = "color_serde_maybe")]
+    pub highlight: Option<Color>,
+    #[serde(default, with = "color_serde_maybe")]
+    pub action: Option<Color>,
+    #[serde(default, with = "color_serde_maybe")]
+    pub server: Option<Color>,
+    #[serde(default, with = "color_serde_maybe")]
+    pub error: Option<Color>,
+    /// Shading of every other row
+    #[serde(default, with = "color_serde_maybe")]
+    pub alternate: Option<Color>,
+}
+
+impl RowBackground {
+    pub fn category(&self, category: Category) -> Option<Color> {
+        match category {
+            Category::Own => self.own,
+            Category::Highlight => self.highlight,
+            Category::Action => self.action,
+            Category::Server => self.server,
+            Category::Error => self.error,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
 pub struct Text {
     #[serde(default = "default_transparent", with = "color_serde")]
@@ -279,6 +425,52 @@ pub fn randomize_color(original_color: Color, seed: &str) -> Color {
     from_hsl(randomized_hsl)
 }
 
+/// Colors of the default palette, spread over the hues
+const PALETTE_SIZE: usize = 12;
+
+/// Color of the nickname with `seed`, derived from the theme's nickname color
+/// `base`. Colors of the palette and colors shared by users keep their hue
+/// and saturation, but take the lightness of `base`, so they're as readable
+/// on the theme's background.
+pub fn nickname_color(
+    base: Color,
+    seed: &str,
+    kind: buffer::Color,
+    palette: &[Color],
+    shared: Option<Color>,
+) -> Color {
+    let base_hsl = to_hsl(base);
+    let with_lightness = |color| {
+        let hsl = to_hsl(color);
+
+        from_hsl(Okhsl::new(hsl.hue, hsl.saturation, base_hsl.lightness))
+    };
+
+    match (kind, shared) {
+        (buffer::Color::Solid, _) => base,
+        (buffer::Color::Unique | buffer::Color::Palette, Some(shared)) => {
+            with_lightness(shared)
+        }
+        (buffer::Color::Unique, None) => randomize_color(base, seed),
+        (buffer::Color::Palette, None) => {
+            let hash = seahash::hash(seed.as_bytes());
+
+            if palette.is_empty() {
+                let index = hash % PALETTE_SIZE as u64;
+                let hue = 360.0 * index as f32 / PALETTE_SIZE as f32;
+
+                from_hsl(Okhsl::new(
+                    hue,
+                    base_hsl.saturation,
+                    base_hsl.lightness,
+                ))
+            } else {
+                with_lightness(palette[(hash % palette.len() as u64) as usize])
+            }
+        }
+    }
+}
+
 pub fn to_hsl(color: Color) -> Okhsl {
     let mut hsl = Okhsl::from_color(to_rgb(color));
     if hsl.saturation.is_nan() {
@@ -479,6 +671,15 @@ mod binary {
         BufferServerMessagesStandardReplyWarn = 39,
         BufferServerMessagesStandardReplyNote = 40,
         BufferServerMessagesWallops = 41,
+        BufferServerMessagesCtcp = 42,
+        BufferRowBackgroundOwn = 43,
+        BufferRowBackgroundHighlight = 44,
+        BufferRowBackgroundAction = 45,
+        BufferRowBackgroundServer = 46,
+        BufferRowBackgroundError = 47,
+        BufferRowBackgroundAlternate = 48,
+        BufferMention = 49,
+        BufferServerMessagesNotice = 50,
     }
 
     impl Tag {
@@ -505,6 +706,7 @@ mod binary {
                 Tag::BufferBorderSelected => colors.buffer.border_selected,
                 Tag::BufferCode => colors.buffer.code,
                 Tag::BufferHighlight => colors.buffer.highlight,
+                Tag::BufferMention => colors.buffer.mention?,
                 Tag::BufferNickname => colors.buffer.nickname,
                 Tag::BufferSelection => colors.buffer.selection,
                 Tag::BufferTimestamp => colors.buffer.timestamp,
@@ -543,9 +745,33 @@ mod binary {
                 Tag::BufferServerMessagesWallops => {
                     colors.buffer.server_messages.wallops?
                 }
+                Tag::BufferServerMessagesCtcp => {
+                    colors.buffer.server_messages.ctcp?
+                }
+                Tag::BufferServerMessagesNotice => {
+                    colors.buffer.server_messages.notice?
+                }
                 Tag::BufferServerMessagesDefault => {
                     colors.buffer.server_messages.default
                 }
+                Tag::BufferRowBackgroundOwn => {
+                    colors.buffer.row_background.own?
+                }
+                Tag::BufferRowBackgroundHighlight => {
+                    colors.buffer.row_background.highlight?
+                }
+                Tag::BufferRowBackgroundAction => {
+                    colors.buffer.row_background.action?
+                }
+                Tag::BufferRowBackgroundServer => {
+                    colors.buffer.row_background.server?
+                }
+                Tag::BufferRowBackgroundError => {
+                    colors.buffer.row_background.error?
+                }
+                Tag::BufferRowBackgroundAlternate => {
+                    colors.buffer.row_background.alternate?
+                }
                 Tag::ButtonsPrimaryBackground => {
                     colors.buttons.primary.background
                 }
@@ -604,6 +830,7 @@ mod binary {
                 }
                 Tag::BufferCode => colors.buffer.code = color,
                 Tag::BufferHighlight => colors.buffer.highlight = color,
+                Tag::BufferMention => colors.buffer.mention = Some(color),
                 Tag::BufferNickname => colors.buffer.nickname = color,
                 Tag::BufferSelection => colors.buffer.selection = color,
                 Tag::BufferTimestamp => colors.buffer.timestamp = color,
@@ -647,9 +874,33 @@ mod binary {
                 Tag::BufferServerMessagesWallops => {
                     colors.buffer.server_messages.wallops = Some(color);
                 }
+                Tag::BufferServerMessagesCtcp => {
+                    colors.buffer.server_messages.ctcp = Some(color);
+                }
+                Tag::BufferServerMessagesNotice => {
+                    colors.buffer.server_messages.notice = Some(color);
+                }
                 Tag::BufferServerMessagesDefault => {
                     colors.buffer.server_messages.default = color;
                 }
+                Tag::BufferRowBackgroundOwn => {
+                    colors.buffer.row_background.own = Some(color);
+                }
+                Tag::BufferRowBackgroundHighlight => {
+                    colors.buffer.row_background.highlight = Some(color);
+                }
+                Tag::BufferRowBackgroundAction => {
+                    colors.buffer.row_background.action = Some(color);
+                }
+                Tag::BufferRowBackgroundServer => {
+                    colors.buffer.row_background.server = Some(color);
+                }
+                Tag::BufferRowBackgroundError => {
+                    colors.buffer.row_background.error = Some(color);
+                }
+                Tag::BufferRowBackgroundAlternate => {
+                    colors.buffer.row_background.alternate = Some(color);
+                }
                 Tag::ButtonsPrimaryBackground => {
                     colors.buttons.primary.background = color;
                 }
@@ -678,3 +929,83 @@ mod binary {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peek_matches_parse() {
+        let preview = peek("ferra".to_string(), DEFAULT_THEME_CONTENT)
+            .expect("peek default theme");
+        let colors = parse(DEFAULT_THEME_CONTENT).expect("parse default theme");
+
+        assert_eq!(preview.background, colors.general.background);
+        assert_eq!(
+            preview.swatches,
+            [
+                colors.text.primary,
+                colors.text.secondary,
+                colors.buffer.nickname,
+                colors.buffer.url,
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_themes() {
+        let outdated = "name = \"old\"\n[palette]\nbackground = \"#000000\"";
+
+        assert!(matches!(parse(outdated), Err(Error::Outdated)));
+        assert!(matches!(
+            peek("old".to_string(), outdated),
+            Err(Error::Outdated)
+        ));
+
+        let unparsable = "[general\nbackground = \"#000000\"";
+
+        assert!(matches!(parse(unparsable), Err(Error::Parse(_))));
+        assert!(matches!(
+            peek("broken".to_string(), unparsable),
+            Err(Error::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn nickname_colors() {
+        // Dark, as on a light theme
+        let base = hex_to_color("#6b3a1f").unwrap();
+        let yellow = hex_to_color("#ffff00").unwrap();
+        let palette = [yellow, hex_to_color("#0000ff").unwrap()];
+        let lightness = |color| to_hsl(color).lightness;
+        let color = |kind, palette: &[Color], shared| {
+            nickname_color(base, "alice", kind, palette, shared)
+        };
+
+        assert_eq!(color(buffer::Color::Solid, &palette, Some(yellow)), base);
+
+        // The same for the same nickname
+        let unique = color(buffer::Color::Unique, &[], None);
+        assert_eq!(unique, color(buffer::Color::Unique, &[], None));
+        assert_ne!(unique, base);
+
+        for palette in [&palette[..], &[]] {
+            let picked = color(buffer::Color::Palette, palette, None);
+
+            assert_eq!(picked, color(buffer::Color::Palette, palette, None));
+            assert!((lightness(picked) - lightness(base)).abs() < 0.01);
+        }
+
+        // Shared colors are used, but as readable as the theme's
+        for kind in [buffer::Color::Unique, buffer::Color::Palette] {
+            let shared = color(kind, &palette, Some(yellow));
+
+            assert!((lightness(shared) - lightness(base)).abs() < 0.01);
+            assert!(lightness(shared) < lightness(yellow));
+            assert!(
+                (to_hsl(shared).hue.into_positive_degrees() - 110.0).abs()
+                    < 5.0
+            );
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Services of bouncers, like ZNC's `*status` and soju's `BouncerServ`, whose
+//! messages are shown in the server buffer rather than in queries
+
+use std::fmt;
+
+use crate::ignore::wildcard_match;
+use crate::isupport::CaseMap;
+use crate::message::{self, Source, source};
+use crate::user::Nick;
+
+/// Services known by name. ZNC's are told apart by their `*` prefix, which
+/// isn't allowed in the nicknames of users.
+const SERVICES: &[&str] = &["BouncerServ"];
+
+/// Whether `nick` is a bouncer service, `patterns` being those added in the
+/// config which can use `*` and `?` wildcards. Nicks are compared with the
+/// server's `casemapping`.
+pub fn is_service(
+    nick: &str,
+    patterns: &[String],
+    casemapping: CaseMap,
+) -> bool {
+    let nick = casemapping.normalize(nick);
+
+    nick.starts_with('*')
+        || SERVICES
+            .iter()
+            .any(|service| casemapping.normalize(service) == nick)
+        || patterns.iter().any(|pattern| {
+            wildcard_match(&casemapping.normalize(pattern), &nick)
+        })
+}
+
+/// Target of messages from or to the bouncer service `nick`
+pub fn target(nick: Nick) -> message::Target {
+    message::Target::Server {
+        source: Source::Server(Some(source::Server::new(
+            source::server::Kind::Bouncer,
+            Some(nick),
+        ))),
+    }
+}
+
+/// Another client of the same account attaching to or detaching from the
+/// bouncer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Attachment {
+    Attached,
+    Detached,
+}
+
+impl Attachment {
+    /// Parses the notice a bouncer service sends when another client attaches
+    /// or detaches, e.g. from ZNC's `clientnotify` module
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.to_lowercase();
+
+        if text.contains("authenticated as your user")
+            || text.contains("client attached")
+        {
+            Some(Attachment::Attached)
+        } else if text.contains("disconnected from your user")
+            || text.contains("client detached")
+        {
+            Some(Attachment::Detached)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for Attachment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Attachment::Attached => write!(f, "another client attached"),
+            Attachment::Detached => write!(f, "another client detached"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn services() {
+        let patterns = vec!["bnc-*".to_string()];
+
+        for (nick, expected) in [
+            ("*status", true),
+            ("*clientnotify", true),
+            ("BouncerServ", true),
+            ("bouncerserv", true),
+            ("BNC-Control", true),
+            ("alice", false),
+            ("status", false),
+        ] {
+            assert_eq!(
+                is_service(nick, &patterns, CaseMap::default()),
+                expected,
+                "{nick}"
+            );
+        }
+
+        // Compared with the server's casemapping
+        let patterns = vec!["bnc[*".to_string()];
+        assert!(is_service("BNC{1", &patterns, CaseMap::RFC1459));
+        assert!(!is_service("BNC{1", &patterns, CaseMap::ASCII));
+    }
+
+    #[test]
+    fn attachments() {
+        for (text, expected) in [
+            (
+                "Another client (10.0.0.2) authenticated as your user. Use \
+                 the 'ListClients' command to see all 2 clients.",
+                Some(Attachment::Attached),
+            ),
+            (
+                "A client from 10.0.0.2 disconnected from your user. Use the \
+                 'ListClients' command to see the 1 remaining client.",
+                Some(Attachment::Detached),
+            ),
+            ("Client detached: laptop", Some(Attachment::Detached)),
+            ("You are currently disconnected from IRC.", None),
+        ] {
+            assert_eq!(Attachment::parse(text), expected, "{text}");
+        }
+    }
+}
@@ -2,10 +2,11 @@ use core::fmt;
 
 use serde::{Deserialize, Serialize};
 
+use crate::appearance::theme;
 use crate::config::buffer::NicknameClickAction;
 use crate::serde::default_bool_true;
 use crate::target::{self, Target};
-use crate::{Server, channel, config, message};
+use crate::{Server, channel, config, input, message};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(untagged)]
@@ -47,6 +48,15 @@ impl Buffer {
         }
     }
 
+    /// Key of the buffer as it was saved before channels and queries were
+    /// keyed by their normalized name
+    pub fn legacy_key(&self) -> String {
+        match self {
+            Buffer::Upstream(upstream) => upstream.legacy_key(),
+            Buffer::Internal(internal) => internal.key(),
+        }
+    }
+
     pub fn upstream(&self) -> Option<&Upstream> {
         if let Self::Upstream(upstream) = self {
             Some(upstream)
@@ -65,7 +75,23 @@ impl Buffer {
 }
 
 impl Upstream {
+    /// Key of the buffer in saved settings and orders, the same however
+    /// its channel or query is cased
     pub fn key(&self) -> String {
+        match self {
+            Upstream::Server(server) => format!("server:{server}"),
+            Upstream::Channel(server, channel) => {
+                format!("server:{server}:{}", channel.as_normalized_str())
+            }
+            Upstream::Query(server, query) => {
+                format!("server:{server}:{}", query.as_normalized_str())
+            }
+        }
+    }
+
+    /// Key of the buffer as it was saved before channels and queries were
+    /// keyed by their normalized name
+    pub fn legacy_key(&self) -> String {
         match self {
             Upstream::Server(server) => format!("server:{server}"),
             Upstream::Channel(server, channel) => {
@@ -137,16 +163,36 @@ impl Internal {
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Settings {
     pub channel: channel::Settings,
+    /// Overrides the `notifications` config for this buffer
+    #[serde(default)]
+    pub notifications: Option<Notifications>,
 }
 
 impl From<config::Buffer> for Settings {
     fn from(config: config::Buffer) -> Self {
         Self {
             channel: channel::Settings::from(config.channel),
+            notifications: None,
         }
     }
 }
 
+/// Which messages of a channel or query notify
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Notifications {
+    /// Every message, notified like a highlight in channels
+    All,
+    /// Only messages highlighting us
+    Highlights,
+    /// No notifications or highlights, but the buffer still turns unread
+    None,
+}
+
+impl Notifications {
+    pub const ALL: &'static [Self] = &[Self::All, Self::Highlights, Self::None];
+}
+
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct TextInput {
     #[serde(default)]
@@ -155,6 +201,16 @@ pub struct TextInput {
     pub auto_format: AutoFormat,
     #[serde(default)]
     pub autocomplete: Autocomplete,
+    #[serde(default)]
+    pub markdown_links: MarkdownLinks,
+    #[serde(default)]
+    pub preview: bool,
+    #[serde(default)]
+    pub reply_quote: ReplyQuote,
+    #[serde(default)]
+    pub emoji_replacement: bool,
+    #[serde(default)]
+    pub confirm_send: ConfirmSend,
 }
 
 #[derive(Debug, Clone, Copy, Default, Deserialize)]
@@ -193,6 +249,35 @@ impl Default for Autocomplete {
     }
 }
 
+/// When input is confirmed before it's sent, e.g. a pasted stack trace
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConfirmSend {
+    #[serde(default = "default_confirm_send_lines")]
+    pub lines: usize,
+    #[serde(default = "default_confirm_send_bytes")]
+    pub bytes: usize,
+    /// Uploads the input, given on stdin, and prints where it can be read
+    #[serde(default)]
+    pub pastebin_command: Option<String>,
+}
+
+impl ConfirmSend {
+    /// Whether sending `text` has to be confirmed first
+    pub fn is_exceeded_by(&self, text: &str) -> bool {
+        input::lines(text).len() > self.lines || text.len() > self.bytes
+    }
+}
+
+impl Default for ConfirmSend {
+    fn default() -> Self {
+        Self {
+            lines: default_confirm_send_lines(),
+            bytes: default_confirm_send_bytes(),
+            pastebin_command: None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, Default, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum TextInputVisibility {
@@ -210,6 +295,28 @@ pub enum AutoFormat {
     All,
 }
 
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MarkdownLinks {
+    /// `[text](url)` is sent as `text (url)`
+    #[default]
+    Parentheses,
+    /// `[text](url)` is sent as `url`
+    Inline,
+}
+
+/// How a reply quotes the message it replies to, when the server doesn't
+/// support message tags
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReplyQuote {
+    /// The input is prefilled with `nick: `
+    #[default]
+    Nickname,
+    /// A `> nick: quoted text` line is sent before the reply
+    Line,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Timestamp {
     #[serde(default = "default_timestamp")]
@@ -256,6 +363,36 @@ pub struct Nickname {
     pub show_access_levels: bool,
     #[serde(default)]
     pub click: NicknameClickAction,
+    #[serde(default = "default_bool_true")]
+    pub show_display_names: bool,
+    #[serde(default)]
+    pub avatars: Avatars,
+    /// Colors picked from with [`Color::Palette`], spread over the hues of
+    /// the theme's nickname color when empty
+    #[serde(default, deserialize_with = "deserialize_palette")]
+    pub palette: Vec<iced_core::Color>,
+    /// Uses the colors users share, on servers sharing them, in place of
+    /// generated ones
+    #[serde(default = "default_bool_true")]
+    pub shared_colors: bool,
+}
+
+fn deserialize_palette<'de, D>(
+    deserializer: D,
+) -> Result<Vec<iced_core::Color>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .into_iter()
+        .map(|hex| {
+            theme::hex_to_color(&hex).ok_or_else(|| {
+                serde::de::Error::custom(format!(
+                    "invalid palette color {hex:?}, expected \"#rrggbb\""
+                ))
+            })
+        })
+        .collect()
 }
 
 impl Default for Nickname {
@@ -266,10 +403,26 @@ impl Default for Nickname {
             alignment: Alignment::default(),
             show_access_levels: default_bool_true(),
             click: NicknameClickAction::default(),
+            show_display_names: default_bool_true(),
+            avatars: Avatars::default(),
+            palette: vec![],
+            shared_colors: default_bool_true(),
         }
     }
 }
 
+/// Where avatars of users are shown, on servers sharing them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Avatars {
+    Hidden,
+    /// In the header of queries
+    #[default]
+    Query,
+    /// Next to messages too
+    Messages,
+}
+
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct StatusMessagePrefix {
     #[serde(default)]
@@ -288,12 +441,17 @@ impl Brackets {
     }
 }
 
+/// How nicknames are colored, see [`theme::nickname_color`]
 #[derive(Debug, Clone, Copy, Default, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum Color {
+    /// The theme's nickname color
     Solid,
+    /// The theme's nickname color with a hue picked by the nickname
     #[default]
     Unique,
+    /// A color of the palette picked by the nickname
+    Palette,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
@@ -368,3 +526,11 @@ fn default_date_separators() -> String {
 fn default_completion_suffixes() -> [String; 2] {
     [": ".to_string(), " ".to_string()]
 }
+
+fn default_confirm_send_lines() -> usize {
+    3
+}
+
+fn default_confirm_send_bytes() -> usize {
+    1024
+}
@@ -1,11 +1,14 @@
 use serde::{Deserialize, Serialize};
 
-use crate::config;
+use crate::isupport::CaseMap;
+use crate::{User, config, ignore};
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct Settings {
     pub nicklist: Nicklist,
     pub topic: Topic,
+    #[serde(default)]
+    pub actions: Actions,
 }
 
 impl From<config::buffer::Channel> for Settings {
@@ -13,6 +16,7 @@ impl From<config::buffer::Channel> for Settings {
         Self {
             nicklist: Nicklist::from(config.nicklist),
             topic: Topic::from(config.topic),
+            actions: Actions::default(),
         }
     }
 }
@@ -68,3 +72,127 @@ impl Topic {
         self.enabled = !self.enabled;
     }
 }
+
+/// Masks of users whose actions (`/me`) are shown differently, e.g. bots which
+/// send every line as an action. Only how they're shown changes, history keeps
+/// them as actions.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct Actions {
+    /// Shown as messages from the user
+    #[serde(default)]
+    pub plain: Vec<String>,
+    /// Shown as actions, but dimmed
+    #[serde(default)]
+    pub dimmed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ActionStyle {
+    #[default]
+    Action,
+    Plain,
+    Dimmed,
+}
+
+impl Actions {
+    pub fn style(&self, user: &User, casemapping: CaseMap) -> ActionStyle {
+        let matches = |masks: &[String]| {
+            masks
+                .iter()
+                .any(|mask| ignore::mask_matches(mask, user, casemapping))
+        };
+
+        if matches(&self.plain) {
+            ActionStyle::Plain
+        } else if matches(&self.dimmed) {
+            ActionStyle::Dimmed
+        } else {
+            ActionStyle::Action
+        }
+    }
+
+    /// Shows actions of the user as `style`, by their host or by nickname if
+    /// it's unknown. Masks already matching the user are removed.
+    pub fn set_style(
+        &mut self,
+        user: &User,
+        style: ActionStyle,
+        casemapping: CaseMap,
+    ) {
+        for masks in [&mut self.plain, &mut self.dimmed] {
+            masks.retain(|mask| !ignore::mask_matches(mask, user, casemapping));
+        }
+
+        match style {
+            ActionStyle::Action => {}
+            ActionStyle::Plain => self.plain.push(user.ban_mask()),
+            ActionStyle::Dimmed => self.dimmed.push(user.ban_mask()),
+        }
+    }
+
+    pub fn remove(&mut self, mask: &str) {
+        self.plain.retain(|plain| plain != mask);
+        self.dimmed.retain(|dimmed| dimmed != mask);
+    }
+
+    /// Every mask with the style it sets
+    pub fn masks(&self) -> impl Iterator<Item = (&str, ActionStyle)> {
+        self.plain
+            .iter()
+            .map(|mask| (mask.as_str(), ActionStyle::Plain))
+            .chain(
+                self.dimmed
+                    .iter()
+                    .map(|mask| (mask.as_str(), ActionStyle::Dimmed)),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn action_styles() {
+        let bot = User::try_from("Game[Bot]!bot@games.example.com").unwrap();
+        let feed = User::try_from("feeds").unwrap();
+        let user = User::try_from("dan!d@host.example.com").unwrap();
+        let mut actions = Actions::default();
+
+        actions.set_style(&bot, ActionStyle::Plain, CaseMap::RFC1459);
+        actions.set_style(&feed, ActionStyle::Dimmed, CaseMap::RFC1459);
+
+        assert_eq!(
+            actions.masks().collect::<Vec<_>>(),
+            vec![
+                ("*!*@games.example.com", ActionStyle::Plain),
+                ("feeds!*@*", ActionStyle::Dimmed),
+            ]
+        );
+        assert_eq!(actions.style(&bot, CaseMap::RFC1459), ActionStyle::Plain);
+        assert_eq!(actions.style(&feed, CaseMap::RFC1459), ActionStyle::Dimmed);
+        assert_eq!(actions.style(&user, CaseMap::RFC1459), ActionStyle::Action);
+
+        // Masks are compared as the server compares nicknames
+        actions.remove("feeds!*@*");
+        actions.dimmed.push("game{bot}!*@*".to_string());
+        let renamed = User::try_from("Game[Bot]!bot@elsewhere").unwrap();
+        assert_eq!(
+            actions.style(&renamed, CaseMap::RFC1459),
+            ActionStyle::Dimmed
+        );
+        assert_eq!(
+            actions.style(&renamed, CaseMap::ASCII),
+            ActionStyle::Action
+        );
+
+        // Restyling replaces the masks matching the user
+        actions.set_style(&renamed, ActionStyle::Action, CaseMap::RFC1459);
+        assert_eq!(actions.masks().count(), 1);
+        actions.set_style(&bot, ActionStyle::Dimmed, CaseMap::RFC1459);
+        assert_eq!(
+            actions.masks().collect::<Vec<_>>(),
+            vec![("*!*@games.example.com", ActionStyle::Dimmed)]
+        );
+    }
+}
@@ -3,7 +3,7 @@ use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::{fmt, io};
+use std::{fmt, io, iter};
 
 use anyhow::{Context as ErrorContext, Result, anyhow, bail};
 use chrono::{DateTime, Utc};
@@ -14,8 +14,9 @@ use itertools::{Either, Itertools};
 use log::error;
 use tokio::fs;
 
+pub use self::forward::Forwards;
 pub use self::on_connect::on_connect;
-use crate::environment::{SOURCE_WEBSITE, VERSION};
+use crate::environment::SOURCE_WEBSITE;
 use crate::history::ReadMarker;
 use crate::isupport::{
     ChatHistoryState, ChatHistorySubcommand, MessageReference, WhoToken,
@@ -24,23 +25,30 @@ use crate::isupport::{
 use crate::message::{message_id, server_time, source};
 use crate::target::{self, Target};
 use crate::time::Posix;
-use crate::user::{Nick, NickRef};
+use crate::user::{HighlightNicks, Nick, NickRef, PreviousNicks};
 use crate::{
-    Server, User, buffer, compression, config, ctcp, dcc, environment,
+    Server, User, bouncer, buffer, compression, config, ctcp, dcc, environment,
     file_transfer, isupport, message, mode, server,
 };
 
+pub mod forward;
+pub mod metadata;
+pub mod monitor;
 pub mod on_connect;
+pub mod server_info;
 
 const HIGHLIGHT_BLACKOUT_INTERVAL: Duration = Duration::from_secs(5);
 const CLIENT_CHATHISTORY_LIMIT: u16 = 500;
 const CHATHISTORY_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+/// WHO poll intervals after which an unanswered poll is retried
+const WHO_POLL_RETRY: u32 = 5;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Status {
     Unavailable,
     Connected,
     Disconnected,
+    Idle,
 }
 
 impl Status {
@@ -51,6 +59,8 @@ impl Status {
 
 #[derive(Debug)]
 pub enum State {
+    /// Not connected on startup, waiting to be connected by the user
+    Idle,
     Disconnected,
     Ready(Client),
 }
@@ -85,6 +95,26 @@ pub enum Broadcast {
         channels: Vec<target::Channel>,
         sent_time: DateTime<Utc>,
     },
+    UsersResynced {
+        channel: target::Channel,
+        removed: Vec<Nick>,
+        added: Vec<Nick>,
+        sent_time: DateTime<Utc>,
+    },
+    ServerInfo {
+        report: server_info::Report,
+        sent_time: DateTime<Utc>,
+    },
+    Monitored {
+        users: Vec<Nick>,
+        online: bool,
+        sent_time: DateTime<Utc>,
+    },
+    MonitorListTruncated {
+        limit: usize,
+        left_out: Vec<String>,
+        sent_time: DateTime<Utc>,
+    },
 }
 
 #[derive(Debug)]
@@ -106,14 +136,28 @@ pub enum Event {
     WithTarget(message::Encoded, Nick, message::Target),
     Broadcast(Broadcast),
     FileTransferRequest(file_transfer::ReceiveRequest),
+    /// The sender agreed to resume a file transfer
+    FileTransferResumeAccepted(Nick, dcc::Resume),
     UpdateReadMarker(Target, ReadMarker),
+    /// Reaction to the message with the given msgid
+    Reaction(Target, String, message::Reaction),
     JoinedChannel(target::Channel, DateTime<Utc>),
+    /// Joining `from` ended up in `to`, `remembered` being set when the
+    /// forward was newly remembered and should be saved
+    ChannelForwarded {
+        from: target::Channel,
+        to: target::Channel,
+        remembered: bool,
+    },
     LoggedIn(DateTime<Utc>),
     ChatHistoryTargetReceived(Target, DateTime<Utc>),
     ChatHistoryTargetsReceived(DateTime<Utc>),
     DirectMessage(message::Encoded, Nick, User),
     MonitoredOnline(Vec<User>),
     MonitoredOffline(Vec<Nick>),
+    /// Another client attached to or detached from the bouncer, as told by
+    /// the service
+    BouncerAttachment(Nick, bouncer::Attachment, DateTime<Utc>),
     OnConnect(on_connect::Stream),
 }
 
@@ -128,6 +172,10 @@ pub struct Client {
     handle: server::Handle,
     alt_nick: Option<usize>,
     resolved_nick: Option<String>,
+    /// Our username and hostname, which servers prefix our messages with,
+    /// once seen on our joins or host changes
+    mask: Option<(String, String)>,
+    previous_nicks: PreviousNicks,
     chanmap: BTreeMap<target::Channel, Channel>,
     channels: Vec<target::Channel>,
     users: HashMap<target::Channel, Vec<User>>,
@@ -144,14 +192,27 @@ pub struct Client {
     supports_extended_join: bool,
     supports_read_marker: bool,
     supports_chathistory: bool,
+    supports_setname: bool,
+    supports_message_tags: bool,
+    supports_metadata: bool,
     chathistory_requests: HashMap<Target, ChatHistoryRequest>,
     chathistory_exhausted: HashMap<Target, bool>,
     chathistory_targets_request: Option<ChatHistoryRequest>,
     highlight_notification_blackout: HighlightNotificationBlackout,
     registration_required_channels: Vec<target::Channel>,
+    /// Why channels which couldn't be joined were refused, until joined
+    join_failures: HashMap<target::Channel, &'static str>,
+    /// Channels whose modes we requested when joining, until the replies
+    /// are received
+    mode_requests: HashSet<target::Channel>,
     isupport: HashMap<isupport::Kind, isupport::Parameter>,
     who_polls: VecDeque<WhoPoll>,
     who_poll_interval: BackoffInterval,
+    monitor: monitor::Monitor,
+    metadata: metadata::Metadata,
+    forwards: Forwards,
+    resync_users_on_join: bool,
+    server_info_requests: VecDeque<server_info::Request>,
 }
 
 impl fmt::Debug for Client {
@@ -167,9 +228,10 @@ impl Client {
         sender: mpsc::Sender<proto::Message>,
     ) -> Self {
         Self {
-            server,
             handle: sender,
             resolved_nick: None,
+            mask: None,
+            previous_nicks: PreviousNicks::default(),
             alt_nick: None,
             chanmap: BTreeMap::default(),
             channels: vec![],
@@ -187,17 +249,33 @@ impl Client {
             supports_extended_join: false,
             supports_read_marker: false,
             supports_chathistory: false,
+            supports_setname: false,
+            supports_message_tags: false,
+            supports_metadata: false,
             chathistory_requests: HashMap::new(),
             chathistory_exhausted: HashMap::new(),
             chathistory_targets_request: None,
             highlight_notification_blackout:
                 HighlightNotificationBlackout::Blackout(Instant::now()),
             registration_required_channels: vec![],
+            join_failures: HashMap::new(),
+            mode_requests: HashSet::new(),
             isupport: HashMap::new(),
             who_polls: VecDeque::new(),
             who_poll_interval: BackoffInterval::from_duration(
                 config.who_poll_interval,
             ),
+            monitor: monitor::Monitor::new(&config.monitor),
+            metadata: metadata::Metadata::default(),
+            forwards: Forwards::load(&server).unwrap_or_else(|error| {
+                log::warn!(
+                    "[{server}] failed to load channel forwards: {error}"
+                );
+                Forwards::default()
+            }),
+            resync_users_on_join: false,
+            server_info_requests: VecDeque::new(),
+            server,
             config,
         }
     }
@@ -232,8 +310,9 @@ impl Client {
 
     fn join(&mut self, channels: &[target::Channel]) {
         let keys = HashMap::new();
+        let channels = self.forwarded(channels);
 
-        let messages = group_joins(channels, &keys);
+        let messages = group_joins(&channels, &keys);
 
         for message in messages {
             if let Err(e) = self.handle.try_send(message) {
@@ -242,6 +321,91 @@ impl Client {
         }
     }
 
+    /// Requests `NAMES` of `channel` again, replacing its users with the
+    /// reply once it's complete. `requested` is set when the user asked for
+    /// it, so the result is reported even if nothing changed.
+    fn resync_users(&mut self, channel: &target::Channel, requested: bool) {
+        let Some(state) = self.chanmap.get_mut(channel) else {
+            return;
+        };
+
+        if let Some(resync) = &mut state.names_resync {
+            resync.requested |= requested;
+            return;
+        }
+
+        state.names_resync = Some(NamesResync {
+            users: HashSet::new(),
+            requested,
+        });
+
+        if let Err(e) =
+            self.handle.try_send(command!("NAMES", channel.to_string()))
+        {
+            log::warn!("Error sending names: {e}");
+        }
+    }
+
+    /// Requests the ban list of `channel`, unless it's already being received
+    fn request_bans(&mut self, channel: &target::Channel) {
+        let Some(state) = self.chanmap.get_mut(channel) else {
+            return;
+        };
+
+        if state.bans_request.is_some() {
+            return;
+        }
+
+        state.bans_request = Some(vec![]);
+
+        if let Err(e) =
+            self.handle
+                .try_send(command!("MODE", channel.to_string(), "+b"))
+        {
+            log::warn!("Error requesting bans: {e}");
+        }
+    }
+
+    /// Moves the WHO poll of `channel` forward, so what's known about its
+    /// users is refreshed soon
+    fn prioritize_who_poll(&mut self, channel: &target::Channel) {
+        let Some(pos) = self.who_polls.iter().position(|who_poll| {
+            &who_poll.channel == channel
+                && matches!(
+                    who_poll.status,
+                    WhoStatus::Waiting(_) | WhoStatus::Received
+                )
+        }) else {
+            return;
+        };
+
+        if let Some(mut who_poll) = self.who_polls.remove(pos) {
+            who_poll.status = WhoStatus::Joined;
+
+            // Don't hold up a request in progress
+            let in_progress = self.who_polls.front().is_some_and(|who_poll| {
+                matches!(
+                    who_poll.status,
+                    WhoStatus::Requested(..) | WhoStatus::Receiving(..)
+                )
+            });
+
+            self.who_polls.insert(usize::from(in_progress), who_poll);
+        }
+    }
+
+    /// Channels to join in place of `channels`, following remembered
+    /// forwards
+    fn forwarded(&self, channels: &[target::Channel]) -> Vec<target::Channel> {
+        channels
+            .iter()
+            .map(|channel| {
+                self.forwards.resolve(channel).unwrap_or(channel).clone()
+            })
+            .unique()
+            .collect()
+    }
+
     fn start_reroute(&self, command: &Command) -> bool {
         use Command::*;
 
@@ -319,16 +483,38 @@ impl Client {
 
             self.labels.insert(label.clone(), context);
 
-            // IRC: Encode tags
-            message.tags = vec![Tag {
+            // IRC: Encode tags, keeping any already present on the message
+            message.tags.retain(|tag| tag.key != "label");
+            message.tags.push(Tag {
                 key: "label".to_string(),
                 value: Some(label),
-            }];
+            });
         }
 
         self.reroute_responses_to =
             self.start_reroute(&message.command).then(|| buffer.clone());
 
+        if let Command::JOIN(channels, _) = &mut message.command {
+            *channels = channels
+                .split(',')
+                .map(|channel| {
+                    target::Channel::parse(
+                        channel,
+                        self.chantypes(),
+                        self.statusmsg(),
+                        self.casemapping(),
+                    )
+                    .ok()
+                    .and_then(|channel| {
+                        self.forwards
+                            .resolve(&channel)
+                            .map(|forwarded| forwarded.as_str().to_string())
+                    })
+                    .unwrap_or_else(|| channel.to_string())
+                })
+                .join(",");
+        }
+
         if matches!(message.command, Command::WHO(..)) {
             let params = message.command.clone().parameters();
 
@@ -369,6 +555,11 @@ impl Client {
             }
         }
 
+        if let Some(kind) = server_info::Kind::requested_by(&message.command) {
+            self.server_info_requests
+                .push_back(server_info::Request::new(kind, Instant::now()));
+        }
+
         if let Err(e) = self.handle.try_send(message.into()) {
             log::warn!("Error sending message: {e}");
         }
@@ -446,6 +637,36 @@ impl Client {
             };
         }
 
+        if let (Command::Numeric(numeric, args), Some(request)) =
+            (&message.command, self.server_info_requests.front_mut())
+        {
+            match request.collect(*numeric, args) {
+                server_info::Collected::No => {}
+                server_info::Collected::Yes => return Ok(vec![]),
+                server_info::Collected::Ended => {
+                    let report = ok!(self.server_info_requests.pop_front())
+                        .finish(false);
+
+                    return Ok(vec![Event::Broadcast(Broadcast::ServerInfo {
+                        report,
+                        sent_time: server_time(&message),
+                    })]);
+                }
+                // Shown as is, as there's nothing to report
+                server_info::Collected::Failed => {
+                    self.server_info_requests.pop_front();
+                }
+            }
+        }
+
+        // Said in reply to WHOIS and messages to away users, which may be
+        // rerouted below
+        if let Command::Numeric(RPL_AWAY, args) = &message.command {
+            let nick = ok!(args.get(1));
+
+            self.update_user_away(&User::from(Nick::from(nick.as_str())), true);
+        }
+
         match &message.command {
             Command::BATCH(batch, params) => {
                 let mut chars = batch.chars();
@@ -797,6 +1018,31 @@ impl Client {
                     return Ok(events);
                 }
             }
+            // Show standard replies to our own commands in the buffer the
+            // command was sent from
+            Command::FAIL(_, _, _, _)
+            | Command::WARN(_, _, _, _)
+            | Command::NOTE(_, _, _, _)
+                if context.is_some() =>
+            {
+                let reply = ok!(message::standard_reply::Reply::parse(
+                    &message.command
+                ));
+                let source = source::Server::new(
+                    message::Kind::StandardReply(reply.kind),
+                    None,
+                );
+
+                if let Some(target) = context.map(|context| {
+                    context.buffer().server_message_target(Some(source))
+                }) {
+                    return Ok(vec![Event::WithTarget(
+                        message,
+                        self.nickname().to_owned(),
+                        target,
+                    )]);
+                }
+            }
             // Label context whois
             _ if context.as_ref().is_some_and(Context::is_whois) => {
                 if let Some(source) = context
@@ -914,6 +1160,13 @@ impl Client {
                     if contains("setname") {
                         requested.push("setname");
                     }
+                    if self
+                        .listed_caps
+                        .iter()
+                        .any(|cap| metadata::is_capability(cap))
+                    {
+                        requested.push(metadata::CAPABILITY);
+                    }
 
                     if !requested.is_empty() {
                         // Request
@@ -955,6 +1208,20 @@ impl Client {
                 if caps.contains(&"draft/read-marker") {
                     self.supports_read_marker = true;
                 }
+                if caps.contains(&"setname") {
+                    self.supports_setname = true;
+                }
+                if caps.contains(&"message-tags") {
+                    self.supports_message_tags = true;
+                }
+                if caps.contains(&metadata::CAPABILITY) {
+                    self.supports_metadata = true;
+
+                    // Acknowledged after CAP NEW
+                    if self.registration_step == RegistrationStep::Complete {
+                        self.handle.try_send(metadata::Metadata::subscribe())?;
+                    }
+                }
 
                 let supports_sasl = caps.iter().any(|cap| cap.contains("sasl"));
 
@@ -1064,6 +1331,9 @@ impl Client {
                 if newly_contains("setname") {
                     requested.push("setname");
                 }
+                if new_caps.iter().any(|cap| metadata::is_capability(cap)) {
+                    requested.push(metadata::CAPABILITY);
+                }
 
                 if !requested.is_empty() {
                     for message in group_capability_requests(&requested) {
@@ -1101,6 +1371,15 @@ impl Client {
                 if del_caps.contains(&"draft/chathistory") {
                     self.supports_chathistory = false;
                 }
+                if del_caps.contains(&"setname") {
+                    self.supports_setname = false;
+                }
+                if del_caps.contains(&"message-tags") {
+                    self.supports_message_tags = false;
+                }
+                if del_caps.contains(&metadata::CAPABILITY) {
+                    self.supports_metadata = false;
+                }
 
                 self.listed_caps.retain(|cap| {
                     !del_caps.iter().any(|del_cap| del_cap == cap)
@@ -1171,6 +1450,37 @@ impl Client {
                 if let Some(user) = message.user() {
                     let is_echo = user.nickname() == self.nickname();
 
+                    // Bouncer services are shown in the server buffer,
+                    // including what we sent them from another client
+                    let service = if is_echo {
+                        Nick::from(target.as_str())
+                    } else {
+                        user.nickname().to_owned()
+                    };
+
+                    if bouncer::is_service(
+                        service.as_ref(),
+                        &self.config.bouncer_services,
+                        self.casemapping(),
+                    ) {
+                        let attachment = bouncer::Attachment::parse(text)
+                            .filter(|_| !is_echo);
+
+                        if let Some(attachment) = attachment {
+                            return Ok(vec![Event::BouncerAttachment(
+                                service,
+                                attachment,
+                                server_time(&message),
+                            )]);
+                        }
+
+                        return Ok(vec![Event::WithTarget(
+                            message,
+                            self.nickname().to_owned(),
+                            bouncer::target(service),
+                        )]);
+                    }
+
                     let dcc_command = dcc::decode(text);
                     let ctcp_query = ctcp::parse_query(text);
 
@@ -1193,6 +1503,15 @@ impl Client {
                                     },
                                 )]);
                             }
+                            dcc::Command::Accept(accept) => {
+                                log::trace!("DCC Accept => {accept:?}");
+                                return Ok(vec![
+                                    Event::FileTransferResumeAccepted(
+                                        user.nickname().to_owned(),
+                                        accept,
+                                    ),
+                                ]);
+                            }
                             dcc::Command::Unsupported(command) => {
                                 bail!("Unsupported DCC command: {command}",);
                             }
@@ -1219,10 +1538,7 @@ impl Client {
                             }
 
                             // Response to a client sending us a CTCP request
-                            if matches!(
-                                &message.command,
-                                Command::PRIVMSG(_, _)
-                            ) {
+                            if ctcp_config.enabled {
                                 match query.command {
                                     ctcp::Command::Action => (),
                                     ctcp::Command::ClientInfo => {
@@ -1263,9 +1579,10 @@ impl Client {
                                                 ctcp::response_message(
                                                     &query.command,
                                                     user.nickname().to_string(),
-                                                    Some(format!(
-                                                        "Halloy {VERSION}"
-                                                    )),
+                                                    Some(
+                                                        ctcp_config
+                                                            .version_reply(),
+                                                    ),
                                                 ),
                                             )?;
                                         }
@@ -1296,10 +1613,40 @@ impl Client {
                                 }
                             }
 
-                            return Ok(vec![]);
+                            // Let the user see who probed them
+                            let target = message::Target::Server {
+                                source: source::Source::Server(Some(
+                                    source::Server::new(
+                                        source::server::Kind::Ctcp,
+                                        Some(user.nickname().to_owned()),
+                                    ),
+                                )),
+                            };
+
+                            return Ok(vec![Event::WithTarget(
+                                message,
+                                self.nickname().to_owned(),
+                                target,
+                            )]);
                         }
                     }
 
+                    // Private notices are kept out of queries when configured
+                    // to, e.g. for services
+                    if matches!(&message.command, Command::NOTICE(_, _))
+                        && !is_echo
+                        && target == &self.nickname().to_string()
+                        && self.config.private_notices_in_server_buffer
+                    {
+                        return Ok(vec![Event::WithTarget(
+                            message,
+                            self.nickname().to_owned(),
+                            message::Target::notice(Some(
+                                user.nickname().to_owned(),
+                            )),
+                        )]);
+                    }
+
                     // use `target` to confirm the direct message
                     let direct_message = target == &self.nickname().to_string();
 
@@ -1353,10 +1700,18 @@ impl Client {
 
                 if ourself {
                     self.resolved_nick = Some(nick.to_string());
+                    self.previous_nicks
+                        .record(old_user.nickname().to_owned(), Instant::now());
                 }
 
                 let new_nick = Nick::from(nick.as_str());
 
+                self.metadata.rename(
+                    old_user.nickname().as_ref(),
+                    nick,
+                    self.casemapping(),
+                );
+
                 self.chanmap.values_mut().for_each(|channel| {
                     if let Some(user) = channel.users.take(&old_user) {
                         channel
@@ -1412,6 +1767,9 @@ impl Client {
                     channel.users.remove(&user);
                 });
 
+                self.metadata
+                    .remove(user.nickname().as_ref(), self.casemapping());
+
                 let channels = self.user_channels(user.nickname());
 
                 return Ok(vec![Event::Broadcast(Broadcast::Quit {
@@ -1425,12 +1783,15 @@ impl Client {
                 let user = ok!(message.user());
 
                 if user.nickname() == self.nickname() {
-                    self.chanmap.remove(&context!(target::Channel::parse(
+                    let channel = context!(target::Channel::parse(
                         channel,
                         self.chantypes(),
                         self.statusmsg(),
                         self.casemapping(),
-                    )));
+                    ));
+
+                    self.chanmap.remove(&channel);
+                    self.mode_requests.remove(&channel);
                 } else if let Some(channel) =
                     self.chanmap.get_mut(&context!(target::Channel::parse(
                         channel,
@@ -1453,9 +1814,21 @@ impl Client {
                 ));
 
                 if user.nickname() == self.nickname() {
+                    if let (Some(username), Some(hostname)) =
+                        (user.username(), user.hostname())
+                    {
+                        self.mask =
+                            Some((username.to_string(), hostname.to_string()));
+                    }
+
+                    self.join_failures.remove(&target_channel);
                     self.chanmap
                         .insert(target_channel.clone(), Channel::default());
 
+                    // Modes are shown with the topic
+                    self.handle.try_send(command!("MODE", channel))?;
+                    self.mode_requests.insert(target_channel.clone());
+
                     // Add channel to WHO poll queue
                     if !self
                         .who_polls
@@ -1485,7 +1858,9 @@ impl Client {
                         user
                     };
 
-                    channel.users.insert(user);
+                    // Anything known from before they left, like being away,
+                    // is stale
+                    channel.users.replace(user);
                 }
             }
             Command::KICK(channel, victim, _) => {
@@ -1780,39 +2155,23 @@ impl Client {
                 }
             }
             Command::AWAY(args) => {
-                let away = args.is_some();
                 let user = ok!(message.user());
 
-                for channel in self.chanmap.values_mut() {
-                    if let Some(mut user) = channel.users.take(&user) {
-                        user.update_away(away);
-                        channel.users.insert(user);
-                    }
-                }
+                self.update_user_away(&user, args.is_some());
             }
             // RPL_UNAWAY is a reply to "/AWAY" from the server
             // for the client/user itself.
             Command::Numeric(RPL_UNAWAY, _) => {
                 let user = User::from(self.nickname().to_owned());
 
-                for channel in self.chanmap.values_mut() {
-                    if let Some(mut user) = channel.users.take(&user) {
-                        user.update_away(false);
-                        channel.users.insert(user);
-                    }
-                }
+                self.update_user_away(&user, false);
             }
             // RPL_UNAWAY is a reply to "/AWAY <msg>" from the server
             // for the client/user itself.
             Command::Numeric(RPL_NOWAWAY, _) => {
                 let user = User::from(self.nickname().to_owned());
 
-                for channel in self.chanmap.values_mut() {
-                    if let Some(mut user) = channel.users.take(&user) {
-                        user.update_away(true);
-                        channel.users.insert(user);
-                    }
-                }
+                self.update_user_away(&user, true);
             }
             Command::MODE(target, Some(modes), Some(args)) => {
                 match Target::parse(
@@ -1823,26 +2182,22 @@ impl Client {
                 ) {
                     Target::Channel(ref channel) => {
                         if let Some(channel) = self.chanmap.get_mut(channel) {
+                            if let Some(channel_modes) = &mut channel.modes {
+                                channel_modes.apply(modes, args);
+                            }
+
                             let modes =
                                 mode::parse::<mode::Channel>(modes, args);
 
-                            for mode in modes {
-                                if let Some((op, lookup)) =
-                                    mode.operation().zip(mode.arg().map(
-                                        |nick| User::from(Nick::from(nick)),
-                                    ))
-                                {
-                                    if let Some(mut user) =
-                                        channel.users.take(&lookup)
-                                    {
-                                        user.update_access_level(
-                                            op,
-                                            *mode.value(),
-                                        );
-                                        channel.users.insert(user);
-                                    }
-                                }
-                            }
+                            channel.update_bans(
+                                &modes,
+                                message
+                                    .user()
+                                    .map(|user| user.nickname().to_string())
+                                    .as_deref(),
+                                server_time(&message),
+                            );
+                            channel.update_access_levels(modes);
                         }
                     }
                     Target::Query(_) => {
@@ -1888,14 +2243,20 @@ impl Client {
                         self.casemapping(),
                     )))
                 {
+                    let users = match &mut channel.names_resync {
+                        Some(resync) => &mut resync.users,
+                        None => &mut channel.users,
+                    };
+
                     for user in args[3].split(' ') {
                         if let Ok(user) = User::try_from(user) {
-                            channel.users.insert(user);
+                            users.insert(user);
                         }
                     }
 
                     // Don't save to history if names list was triggered by JOIN
-                    if !channel.names_init {
+                    // or a resync
+                    if !channel.names_init || channel.names_resync.is_some() {
                         return Ok(vec![]);
                     }
                 }
@@ -1911,9 +2272,34 @@ impl Client {
                 ));
 
                 if let Some(channel) = self.chanmap.get_mut(&target_channel) {
+                    if let Some(resync) = channel.names_resync.take() {
+                        let resynced = channel.resync_users(resync.users);
+
+                        self.prioritize_who_poll(&target_channel);
+
+                        if !resync.requested && resynced.is_empty() {
+                            return Ok(vec![]);
+                        }
+
+                        return Ok(vec![Event::Broadcast(
+                            Broadcast::UsersResynced {
+                                channel: target_channel,
+                                removed: resynced.removed,
+                                added: resynced.added,
+                                sent_time: server_time(&message),
+                            },
+                        )]);
+                    }
+
                     if !channel.names_init {
                         channel.names_init = true;
 
+                        // Names replayed when the connection is restored,
+                        // e.g. by a bouncer, may have gone stale
+                        if self.resync_users_on_join {
+                            self.resync_users(&target_channel, false);
+                        }
+
                         return Ok(vec![]);
                     }
                 }
@@ -1983,17 +2369,79 @@ impl Client {
                 return Ok(vec![]);
             }
             Command::Numeric(RPL_CHANNELMODEIS, args) => {
-                let channel = ok!(args.get(1));
+                let target_channel = context!(target::Channel::parse(
+                    ok!(args.get(1)),
+                    self.chantypes(),
+                    self.statusmsg(),
+                    self.casemapping(),
+                ));
 
-                if let Some(channel) =
-                    self.chanmap.get_mut(&context!(target::Channel::parse(
-                        channel,
-                        self.chantypes(),
-                        self.statusmsg(),
-                        self.casemapping(),
-                    )))
+                if let Some(channel) = self.chanmap.get_mut(&target_channel) {
+                    channel.modes = args.get(2).map(|modes| {
+                        mode::Modes::parse(modes, args.get(3..).unwrap_or(&[]))
+                    });
+                }
+
+                // Replies to our request when joining aren't shown
+                if self.mode_requests.contains(&target_channel) {
+                    return Ok(vec![]);
+                }
+            }
+            Command::Numeric(RPL_BANLIST, args) => {
+                let target_channel = context!(target::Channel::parse(
+                    ok!(args.get(1)),
+                    self.chantypes(),
+                    self.statusmsg(),
+                    self.casemapping(),
+                ));
+
+                let ban = Ban {
+                    mask: ok!(args.get(2)).to_string(),
+                    who: args.get(3).cloned(),
+                    time: args
+                        .get(4)
+                        .and_then(|time| time.parse::<u64>().ok())
+                        .and_then(|time| Posix::from_seconds(time).datetime()),
+                };
+
+                // Bans of a list we requested are collected, not shown
+                if let Some(bans) = self
+                    .chanmap
+                    .get_mut(&target_channel)
+                    .and_then(|channel| channel.bans_request.as_mut())
                 {
-                    channel.mode = args.get(2).cloned();
+                    bans.push(ban);
+
+                    return Ok(vec![]);
+                }
+            }
+            Command::Numeric(RPL_ENDOFBANLIST, args) => {
+                let target_channel = context!(target::Channel::parse(
+                    ok!(args.get(1)),
+                    self.chantypes(),
+                    self.statusmsg(),
+                    self.casemapping(),
+                ));
+
+                if let Some(channel) = self.chanmap.get_mut(&target_channel) {
+                    if let Some(bans) = channel.bans_request.take() {
+                        channel.bans = Some(bans);
+
+                        return Ok(vec![]);
+                    }
+                }
+            }
+            Command::Numeric(RPL_CREATIONTIME, args) => {
+                let target_channel = context!(target::Channel::parse(
+                    ok!(args.get(1)),
+                    self.chantypes(),
+                    self.statusmsg(),
+                    self.casemapping(),
+                ));
+
+                // Sent after the modes, ending the reply to our request
+                if self.mode_requests.remove(&target_channel) {
+                    return Ok(vec![]);
                 }
             }
             Command::Numeric(ERR_NOCHANMODES, args) => {
@@ -2012,11 +2460,75 @@ impl Client {
                         config_channel == channel.as_str()
                     })
                 {
+                    self.join_failures
+                        .insert(channel.clone(), "registration required");
                     self.registration_required_channels.push(channel.clone());
                 }
             }
+            Command::Numeric(
+                numeric @ (ERR_NOSUCHCHANNEL | ERR_TOOMANYCHANNELS
+                | ERR_CHANNELISFULL | ERR_INVITEONLYCHAN
+                | ERR_BANNEDFROMCHAN | ERR_BADCHANNELKEY),
+                args,
+            ) => {
+                // Also sent for other commands, whose target may not even
+                // be a channel
+                let channel = args
+                    .get(1)
+                    .and_then(|channel| {
+                        target::Channel::parse(
+                            channel,
+                            self.chantypes(),
+                            self.statusmsg(),
+                            self.casemapping(),
+                        )
+                        .ok()
+                    })
+                    .filter(|channel| !self.chanmap.contains_key(channel));
+
+                if let Some(channel) = channel {
+                    let reason = match numeric {
+                        ERR_NOSUCHCHANNEL => "no such channel",
+                        ERR_TOOMANYCHANNELS => "too many channels joined",
+                        ERR_CHANNELISFULL => "channel is full (+l)",
+                        ERR_INVITEONLYCHAN => "invite only (+i)",
+                        ERR_BANNEDFROMCHAN => "banned (+b)",
+                        _ => "wrong key (+k)",
+                    };
+
+                    self.join_failures.insert(channel, reason);
+                }
+            }
+            Command::Numeric(ERR_LINKCHANNEL, args) => {
+                let from = context!(target::Channel::parse(
+                    ok!(args.get(1)),
+                    self.chantypes(),
+                    self.statusmsg(),
+                    self.casemapping(),
+                ));
+                let to = context!(target::Channel::parse(
+                    ok!(args.get(2)),
+                    self.chantypes(),
+                    self.statusmsg(),
+                    self.casemapping(),
+                ));
+
+                let remembered = self.config.remember_channel_forwards
+                    && self.forwards.insert(from.clone(), to.clone());
+
+                return Ok(vec![
+                    Event::Single(message, self.nickname().to_owned()),
+                    Event::ChannelForwarded {
+                        from,
+                        to,
+                        remembered,
+                    },
+                ]);
+            }
             Command::Numeric(RPL_ISUPPORT, args) => {
                 let args_len = args.len();
+                let mut events = vec![];
+
                 for (index, arg) in args.iter().enumerate().skip(1) {
                     let operation = arg.parse::<isupport::Operation>();
 
@@ -2040,15 +2552,12 @@ impl Client {
                                             target_limit,
                                         ) = parameter
                                         {
-                                            let messages = group_monitors(
-                                                &self.config.monitor,
-                                                target_limit,
+                                            events.extend(
+                                                self.register_monitor(
+                                                    target_limit,
+                                                    server_time(&message),
+                                                )?,
                                             );
-
-                                            for message in messages {
-                                                self.handle
-                                                    .try_send(message)?;
-                                            }
                                         }
                                     } else {
                                         log::debug!(
@@ -2083,15 +2592,48 @@ impl Client {
                     }
                 }
 
-                return Ok(vec![]);
-            }
-            Command::TAGMSG(_) => {
-                return Ok(vec![]);
+                return Ok(events);
             }
-            Command::ACCOUNT(accountname) => {
-                let old_user = ok!(message.user());
-
-                self.chanmap.values_mut().for_each(|channel| {
+            Command::TAGMSG(target) => {
+                if let (Some(user), Some(id), Some(text)) = (
+                    message.user(),
+                    message::reply_to(&message),
+                    message::reaction(&message),
+                ) {
+                    // Reactions in queries are sent to us
+                    let target = match Target::parse(
+                        target,
+                        self.chantypes(),
+                        self.statusmsg(),
+                        self.casemapping(),
+                    ) {
+                        Target::Query(_)
+                            if user.nickname() != self.nickname() =>
+                        {
+                            Target::Query(target::Query::from_user(
+                                &user,
+                                self.casemapping(),
+                            ))
+                        }
+                        target => target,
+                    };
+
+                    return Ok(vec![Event::Reaction(
+                        target,
+                        id,
+                        message::Reaction {
+                            nick: user.nickname().to_owned(),
+                            text,
+                        },
+                    )]);
+                }
+
+                return Ok(vec![]);
+            }
+            Command::ACCOUNT(accountname) => {
+                let old_user = ok!(message.user());
+
+                self.chanmap.values_mut().for_each(|channel| {
                     if let Some(user) = channel.users.take(&old_user) {
                         channel
                             .users
@@ -2113,11 +2655,25 @@ impl Client {
                     self.registration_required_channels.clear();
                 }
             }
+            Command::SETNAME(realname) => {
+                let old_user = ok!(message.user());
+
+                self.chanmap.values_mut().for_each(|channel| {
+                    if let Some(user) = channel.users.take(&old_user) {
+                        channel.users.insert(user.with_realname(realname));
+                    }
+                });
+            }
             Command::CHGHOST(new_username, new_hostname) => {
                 let old_user = ok!(message.user());
 
                 let ourself = old_user.nickname() == self.nickname();
 
+                if ourself {
+                    self.mask =
+                        Some((new_username.clone(), new_hostname.clone()));
+                }
+
                 self.chanmap.values_mut().for_each(|channel| {
                     if let Some(user) = channel.users.take(&old_user) {
                         channel.users.insert(user.with_username_and_hostname(
@@ -2145,9 +2701,20 @@ impl Client {
                     .filter_map(|target| User::try_from(target).ok())
                     .collect::<Vec<_>>();
 
+                let changed = self.monitor.update(
+                    targets.iter().map(|user| user.nickname().to_owned()),
+                    monitor::Presence::Online,
+                    self.casemapping(),
+                );
+
                 return Ok(vec![
                     Event::Single(message.clone(), self.nickname().to_owned()),
                     Event::MonitoredOnline(targets),
+                    Event::Broadcast(Broadcast::Monitored {
+                        users: changed,
+                        online: true,
+                        sent_time: server_time(&message),
+                    }),
                 ]);
             }
             Command::Numeric(RPL_MONOFFLINE, args) => {
@@ -2156,14 +2723,76 @@ impl Client {
                     .map(Nick::from)
                     .collect::<Vec<_>>();
 
+                let changed = self.monitor.update(
+                    targets.clone(),
+                    monitor::Presence::Offline,
+                    self.casemapping(),
+                );
+
                 return Ok(vec![
                     Event::Single(message.clone(), self.nickname().to_owned()),
                     Event::MonitoredOffline(targets),
+                    Event::Broadcast(Broadcast::Monitored {
+                        users: changed,
+                        online: false,
+                        sent_time: server_time(&message),
+                    }),
                 ]);
             }
+            Command::Numeric(RPL_ISON, args) => {
+                let online = args.get(1).map_or("", String::as_str);
+
+                // Replies to our polls for servers without MONITOR aren't shown
+                if let Some((online, offline)) =
+                    self.monitor.ison_reply(online, self.casemapping())
+                {
+                    let sent_time = server_time(&message);
+                    let mut events = vec![];
+
+                    if !online.is_empty() {
+                        events.push(Event::MonitoredOnline(
+                            online.iter().cloned().map(User::from).collect(),
+                        ));
+                        events.push(Event::Broadcast(Broadcast::Monitored {
+                            users: online,
+                            online: true,
+                            sent_time,
+                        }));
+                    }
+
+                    if !offline.is_empty() {
+                        events.push(Event::MonitoredOffline(offline.clone()));
+                        events.push(Event::Broadcast(Broadcast::Monitored {
+                            users: offline,
+                            online: false,
+                            sent_time,
+                        }));
+                    }
+
+                    return Ok(events);
+                }
+            }
             Command::Numeric(RPL_ENDOFMONLIST, _) => {
                 return Ok(vec![]);
             }
+            Command::METADATA(..)
+            | Command::Numeric(
+                RPL_KEYVALUE
+                | RPL_KEYNOTSET
+                | RPL_METADATASUBOK
+                | RPL_METADATASYNCLATER,
+                _,
+            ) => {
+                if let Some(reply) = metadata::Reply::parse(&message.command) {
+                    self.metadata.update(
+                        reply,
+                        Instant::now(),
+                        self.casemapping(),
+                    );
+                }
+
+                return Ok(vec![]);
+            }
             Command::MARKREAD(target, Some(timestamp)) => {
                 if let Some(read_marker) = timestamp
                     .strip_prefix("timestamp=")
@@ -2202,6 +2831,13 @@ impl Client {
                                 ));
                             }
                         }
+                        // Shown in the server buffer instead
+                        Target::Query(ref query)
+                            if bouncer::is_service(
+                                query.as_str(),
+                                &self.config.bouncer_services,
+                                self.casemapping(),
+                            ) => {}
                         Target::Query(_) => {
                             events.push(Event::ChatHistoryTargetReceived(
                                 target,
@@ -2260,6 +2896,10 @@ impl Client {
                 if self.registration_step == RegistrationStep::End {
                     self.registration_step = RegistrationStep::Complete;
 
+                    if self.supports_metadata {
+                        self.handle.try_send(metadata::Metadata::subscribe())?;
+                    }
+
                     // Send nick password & ghost
                     if let Some(nick_pass) = self.config.nick_password.as_ref()
                     {
@@ -2346,6 +2986,8 @@ impl Client {
                         })
                         .collect::<Vec<_>>();
 
+                    let channels = self.forwarded(&channels);
+
                     // Send JOIN
                     for message in
                         group_joins(&channels, &self.config.channel_keys)
@@ -2366,6 +3008,32 @@ impl Client {
         Ok(vec![Event::Single(message, self.nickname().to_owned())])
     }
 
+    /// Registers the `monitor` list, cut to the server's limit
+    fn register_monitor(
+        &mut self,
+        target_limit: Option<u16>,
+        sent_time: DateTime<Utc>,
+    ) -> Result<Option<Event>> {
+        for message in group_monitors(&self.config.monitor, target_limit) {
+            self.handle.try_send(message)?;
+        }
+
+        let Some(limit) = target_limit
+            .map(usize::from)
+            .filter(|limit| *limit < self.config.monitor.len())
+        else {
+            return Ok(None);
+        };
+
+        self.monitor.truncate(limit);
+
+        Ok(Some(Event::Broadcast(Broadcast::MonitorListTruncated {
+            limit,
+            left_out: self.config.monitor[limit..].to_vec(),
+            sent_time,
+        })))
+    }
+
     pub fn send_markread(&mut self, target: Target, read_marker: ReadMarker) {
         if self.supports_read_marker {
             if let Err(e) = self.handle.try_send(command!(
@@ -2378,6 +3046,33 @@ impl Client {
         }
     }
 
+    /// Requests the metadata of `target` once, if it's a query or a joined
+    /// channel on a server sharing it
+    pub fn sync_metadata(&mut self, target: &Target) {
+        if !self.supports_metadata
+            || self.registration_step != RegistrationStep::Complete
+        {
+            return;
+        }
+
+        if matches!(
+            target,
+            Target::Channel(channel) if !self.chanmap.contains_key(channel)
+        ) {
+            return;
+        }
+
+        let Some(message) =
+            self.metadata.sync(target.as_str(), self.casemapping())
+        else {
+            return;
+        };
+
+        if let Err(e) = self.handle.try_send(message) {
+            log::warn!("Error sending metadata sync: {e}");
+        }
+    }
+
     fn user_who_request(&self, channel: &target::Channel) -> bool {
         if let Some(who_poll) = self
             .who_polls
@@ -2715,10 +3410,19 @@ impl Client {
         self.chanmap.get(channel).map(|channel| &channel.topic)
     }
 
-    fn mode<'a>(&'a self, channel: &target::Channel) -> Option<&'a String> {
+    fn bans<'a>(&'a self, channel: &target::Channel) -> Option<&'a [Ban]> {
+        self.chanmap
+            .get(channel)
+            .and_then(|channel| channel.bans.as_deref())
+    }
+
+    fn modes<'a>(
+        &'a self,
+        channel: &target::Channel,
+    ) -> Option<&'a mode::Modes> {
         self.chanmap
             .get(channel)
-            .and_then(|channel| channel.mode.as_ref())
+            .and_then(|channel| channel.modes.as_ref())
     }
 
     fn resolve_user_attributes<'a>(
@@ -2750,6 +3454,16 @@ impl Client {
             .collect()
     }
 
+    /// Marks `user` as away, or back, in every channel they share with us
+    fn update_user_away(&mut self, user: &User, away: bool) {
+        for channel in self.chanmap.values_mut() {
+            if let Some(mut user) = channel.users.take(user) {
+                user.update_away(away);
+                channel.users.insert(user);
+            }
+        }
+    }
+
     fn resolve_query<'a>(
         &'a self,
         query: &target::Query,
@@ -2766,6 +3480,63 @@ impl Client {
         )
     }
 
+    /// Ourselves as servers prefix our messages, with our username and
+    /// hostname once known
+    pub fn source(&self) -> User {
+        let user = User::from(self.nickname().to_owned());
+
+        match self.mask.clone() {
+            Some((username, hostname)) => {
+                user.with_username_and_hostname(username, hostname)
+            }
+            None => user,
+        }
+    }
+
+    pub fn highlight_nicks(
+        &self,
+        highlights: &config::Highlights,
+    ) -> HighlightNicks {
+        let grace_period =
+            Duration::from_secs(highlights.nickname.grace_period);
+
+        HighlightNicks::new(
+            iter::once(self.nickname().as_ref())
+                .chain(self.config.alt_nicks.iter().map(String::as_str))
+                .chain(
+                    self.config.highlight_nicknames.iter().map(String::as_str),
+                )
+                .chain(highlights.nicknames.iter().map(String::as_str))
+                .chain(
+                    self.previous_nicks
+                        .active(Instant::now(), grace_period)
+                        .map(Nick::as_ref),
+                ),
+            self.casemapping(),
+        )
+    }
+
+    /// Finishes the requests whose replies didn't end within
+    /// [`server_info::TIMEOUT`] with whatever was collected
+    fn finish_timed_out_server_info(
+        &mut self,
+        now: Instant,
+    ) -> Vec<server_info::Report> {
+        let mut reports = vec![];
+
+        while self
+            .server_info_requests
+            .front()
+            .is_some_and(|request| request.is_timed_out(now))
+        {
+            if let Some(request) = self.server_info_requests.pop_front() {
+                reports.push(request.finish(true));
+            }
+        }
+
+        reports
+    }
+
     pub fn tick(&mut self, now: Instant) -> Result<()> {
         match self.highlight_notification_blackout {
             HighlightNotificationBlackout::Blackout(instant) => {
@@ -2777,101 +3548,186 @@ impl Client {
             HighlightNotificationBlackout::Receiving => {}
         }
 
-        if let Some(who_poll) = self.who_polls.front_mut() {
-            #[derive(Debug)]
-            enum Request {
-                Poll,
-                Retry,
-            }
-
-            let request = match &who_poll.status {
-                WhoStatus::Joined => (self.supports_away_notify
-                    || self.config.who_poll_enabled)
-                    .then_some(Request::Poll),
-                WhoStatus::Waiting(last) => {
-                    if self.supports_away_notify {
-                        self.chanmap.get(&who_poll.channel).and_then(
-                            |channel| {
-                                (!channel.who_init
-                                    && (now.duration_since(*last)
-                                        >= self.who_poll_interval.duration))
-                                    .then_some(Request::Poll)
-                            },
-                        )
-                    } else {
-                        (self.config.who_poll_enabled
+        // Poll with ISON on servers without MONITOR
+        if self.registration_step == RegistrationStep::Complete
+            && !self.isupport.contains_key(&isupport::Kind::MONITOR)
+        {
+            for message in self.monitor.poll(now) {
+                self.handle.try_send(message)?;
+            }
+        }
+
+        if self.supports_metadata {
+            for message in self.metadata.poll(now) {
+                self.handle.try_send(message)?;
+            }
+        }
+
+        self.chathistory_requests.retain(|_, chathistory_request| {
+            now.duration_since(chathistory_request.requested_at)
+                < CHATHISTORY_REQUEST_TIMEOUT
+        });
+
+        Ok(())
+    }
+
+    /// Request due at `now` for the first channel waiting to be polled with
+    /// WHO
+    fn who_poll_request(&self, now: Instant) -> Option<WhoRequest> {
+        let who_poll = self.who_polls.front()?;
+
+        match &who_poll.status {
+            WhoStatus::Joined => (self.supports_away_notify
+                || self.config.who_poll_enabled)
+                .then_some(WhoRequest::Poll),
+            WhoStatus::Waiting(last) => {
+                if self.supports_away_notify {
+                    self.chanmap.get(&who_poll.channel).and_then(|channel| {
+                        (!channel.who_init
                             && (now.duration_since(*last)
                                 >= self.who_poll_interval.duration))
-                            .then_some(Request::Poll)
-                    }
+                            .then_some(WhoRequest::Poll)
+                    })
+                } else {
+                    (self.config.who_poll_enabled
+                        && (now.duration_since(*last)
+                            >= self.who_poll_interval.duration))
+                        .then_some(WhoRequest::Poll)
                 }
-                WhoStatus::Requested(source, requested, _) => {
-                    if matches!(source, WhoSource::Poll)
-                        && !self.config.who_poll_enabled
-                    {
-                        None
-                    } else {
-                        (now.duration_since(*requested)
-                            >= 5 * self.who_poll_interval.duration)
-                            .then_some(Request::Retry)
-                    }
+            }
+            WhoStatus::Requested(source, requested, _) => {
+                if matches!(source, WhoSource::Poll)
+                    && !self.config.who_poll_enabled
+                {
+                    None
+                } else {
+                    (now.duration_since(*requested)
+                        >= WHO_POLL_RETRY * self.who_poll_interval.duration)
+                        .then_some(WhoRequest::Retry)
                 }
-                _ => None,
-            };
+            }
+            _ => None,
+        }
+    }
 
-            if let Some(request) = request {
-                if self.isupport.contains_key(&isupport::Kind::WHOX) {
-                    let whox_params = if self.supports_account_notify {
-                        WhoXPollParameters::WithAccountName
-                    } else {
-                        WhoXPollParameters::Default
-                    };
+    /// Channel whose WHO poll is due at `now`, sent with
+    /// [`Self::send_who_poll`]
+    pub fn who_poll_due(&self, now: Instant) -> Option<target::Channel> {
+        self.who_poll_request(now)?;
 
-                    who_poll.status = WhoStatus::Requested(
-                        WhoSource::Poll,
-                        Instant::now(),
-                        Some(whox_params.token()),
-                    );
+        self.who_polls
+            .front()
+            .map(|who_poll| who_poll.channel.clone())
+    }
 
-                    self.handle.try_send(command!(
-                        "WHO",
-                        who_poll.channel.to_string(),
-                        whox_params.fields().to_string(),
-                        whox_params.token().to_owned()
-                    ))?;
-                } else {
-                    who_poll.status = WhoStatus::Requested(
-                        WhoSource::Poll,
-                        Instant::now(),
-                        None,
-                    );
+    /// Sends the WHO poll of `channel`, if it's still due at `now`
+    pub fn send_who_poll(
+        &mut self,
+        channel: &target::Channel,
+        now: Instant,
+    ) -> Result<()> {
+        let Some(request) = self.who_poll_request(now) else {
+            return Ok(());
+        };
 
-                    self.handle.try_send(command!(
-                        "WHO",
-                        who_poll.channel.to_string()
-                    ))?;
-                }
+        let Some(who_poll) = self
+            .who_polls
+            .front_mut()
+            .filter(|who_poll| &who_poll.channel == channel)
+        else {
+            return Ok(());
+        };
 
-                log::debug!(
-                    "[{}] {} - WHO {}",
-                    self.server,
-                    who_poll.channel,
-                    match request {
-                        Request::Poll => "poll",
-                        Request::Retry => "retry",
-                    }
-                );
-            }
+        if self.isupport.contains_key(&isupport::Kind::WHOX) {
+            let whox_params = if self.supports_account_notify {
+                WhoXPollParameters::WithAccountName
+            } else {
+                WhoXPollParameters::Default
+            };
+
+            who_poll.status = WhoStatus::Requested(
+                WhoSource::Poll,
+                Instant::now(),
+                Some(whox_params.token()),
+            );
+
+            self.handle.try_send(command!(
+                "WHO",
+                who_poll.channel.to_string(),
+                whox_params.fields().to_string(),
+                whox_params.token().to_owned()
+            ))?;
+        } else {
+            who_poll.status =
+                WhoStatus::Requested(WhoSource::Poll, Instant::now(), None);
+
+            self.handle
+                .try_send(command!("WHO", who_poll.channel.to_string()))?;
         }
 
-        self.chathistory_requests.retain(|_, chathistory_request| {
-            now.duration_since(chathistory_request.requested_at)
-                < CHATHISTORY_REQUEST_TIMEOUT
-        });
+        log::debug!(
+            "[{}] {} - WHO {}",
+            self.server,
+            who_poll.channel,
+            match request {
+                WhoRequest::Poll => "poll",
+                WhoRequest::Retry => "retry",
+            }
+        );
 
         Ok(())
     }
 
+    /// Whether the WHO poll of `channel` was sent and is yet to be answered,
+    /// until it's retried
+    pub fn who_poll_pending(
+        &self,
+        channel: &target::Channel,
+        now: Instant,
+    ) -> bool {
+        self.who_polls.iter().any(|who_poll| {
+            &who_poll.channel == channel
+                && match &who_poll.status {
+                    WhoStatus::Requested(WhoSource::Poll, requested, _) => {
+                        now.duration_since(*requested)
+                            < WHO_POLL_RETRY * self.who_poll_interval.duration
+                    }
+                    WhoStatus::Receiving(WhoSource::Poll, _) => true,
+                    _ => false,
+                }
+        })
+    }
+
+    /// Channels in the server's configuration, as they're joined after
+    /// any remembered forwards
+    pub fn configured_channels(&self) -> Vec<target::Channel> {
+        let channels = self
+            .config
+            .channels
+            .iter()
+            .filter_map(|channel| {
+                target::Channel::parse(
+                    channel,
+                    self.chantypes(),
+                    self.statusmsg(),
+                    self.casemapping(),
+                )
+                .ok()
+            })
+            .collect::<Vec<_>>();
+
+        self.forwarded(&channels)
+    }
+
+    /// Why joining `channel` was last refused, if it hasn't been joined
+    /// since
+    pub fn join_failure(
+        &self,
+        channel: &target::Channel,
+    ) -> Option<&'static str> {
+        self.join_failures.get(channel).copied()
+    }
+
     pub fn casemapping(&self) -> isupport::CaseMap {
         isupport::get_casemapping(&self.isupport)
     }
@@ -2983,6 +3839,26 @@ impl Map {
         self.0.len()
     }
 
+    /// Tracks servers which aren't connected on startup as idle
+    pub fn add_idle(&mut self, servers: &server::Map) {
+        for entry in servers.entries() {
+            if !entry.config.autoconnect {
+                self.0.entry(entry.server).or_insert(State::Idle);
+            }
+        }
+    }
+
+    pub fn is_idle(&self, server: &Server) -> bool {
+        matches!(self.0.get(server), Some(State::Idle))
+    }
+
+    /// Leaves the idle state, allowing the server to be connected
+    pub fn connect(&mut self, server: &Server) {
+        if let Some(state @ State::Idle) = self.0.get_mut(server) {
+            *state = State::Disconnected;
+        }
+    }
+
     pub fn disconnected(&mut self, server: Server) {
         self.0.insert(server, State::Disconnected);
     }
@@ -2997,7 +3873,7 @@ impl Map {
 
     pub fn remove(&mut self, server: &Server) -> Option<Client> {
         self.0.remove(server).and_then(|state| match state {
-            State::Disconnected => None,
+            State::Idle | State::Disconnected => None,
             State::Ready(client) => Some(client),
         })
     }
@@ -3022,6 +3898,10 @@ impl Map {
         self.client(server).map(Client::nickname)
     }
 
+    pub fn source(&self, server: &Server) -> Option<User> {
+        self.client(server).map(Client::source)
+    }
+
     pub fn receive(
         &mut self,
         server: &Server,
@@ -3068,6 +3948,45 @@ impl Map {
         }
     }
 
+    pub fn resync_users(&mut self, server: &Server, channel: &target::Channel) {
+        if let Some(client) = self.client_mut(server) {
+            client.resync_users(channel, true);
+        }
+    }
+
+    pub fn request_bans(&mut self, server: &Server, channel: &target::Channel) {
+        if let Some(client) = self.client_mut(server) {
+            client.request_bans(channel);
+        }
+    }
+
+    /// Resyncs the users of channels as they're joined, for a connection
+    /// which was restored
+    pub fn resync_users_on_join(&mut self, server: &Server) {
+        if let Some(client) = self.client_mut(server) {
+            client.resync_users_on_join = true;
+        }
+    }
+
+    pub fn forwards(&self, server: &Server) -> Option<&Forwards> {
+        self.client(server).map(|client| &client.forwards)
+    }
+
+    /// Forgets the forwards ending up in `channel`, returning the remaining
+    /// forwards to be saved if any were forgotten
+    pub fn forget_forwards(
+        &mut self,
+        server: &Server,
+        channel: &target::Channel,
+    ) -> Option<Forwards> {
+        let client = self.client_mut(server)?;
+
+        client
+            .forwards
+            .forget(channel)
+            .then(|| client.forwards.clone())
+    }
+
     pub fn quit(&mut self, server: &Server, reason: Option<String>) {
         if let Some(client) = self.client_mut(server) {
             client.quit(reason);
@@ -3128,13 +4047,23 @@ impl Map {
             .unwrap_or_default()
     }
 
-    pub fn get_channel_mode<'a>(
+    pub fn get_channel_modes<'a>(
         &'a self,
         server: &Server,
         channel: &target::Channel,
-    ) -> Option<&'a String> {
+    ) -> Option<&'a mode::Modes> {
         self.client(server)
-            .map(|client| client.mode(channel))
+            .map(|client| client.modes(channel))
+            .unwrap_or_default()
+    }
+
+    pub fn get_channel_bans<'a>(
+        &'a self,
+        server: &Server,
+        channel: &target::Channel,
+    ) -> Option<&'a [Ban]> {
+        self.client(server)
+            .map(|client| client.bans(channel))
             .unwrap_or_default()
     }
 
@@ -3165,12 +4094,61 @@ impl Map {
             .unwrap_or_default()
     }
 
+    pub fn get_highlight_nicks(
+        &self,
+        server: &Server,
+        highlights: &config::Highlights,
+    ) -> HighlightNicks {
+        self.client(server)
+            .map(|client| client.highlight_nicks(highlights))
+            .unwrap_or_default()
+    }
+
     pub fn get_casemapping(&self, server: &Server) -> isupport::CaseMap {
         self.client(server)
             .map(Client::casemapping)
             .unwrap_or_default()
     }
 
+    pub fn monitored(&self, server: &Server) -> &[monitor::Monitored] {
+        self.client(server)
+            .map(|client| client.monitor.users())
+            .unwrap_or_default()
+    }
+
+    pub fn sync_metadata(&mut self, server: &Server, target: &Target) {
+        if let Some(client) = self.client_mut(server) {
+            client.sync_metadata(target);
+        }
+    }
+
+    /// Metadata shared by users of `server`, if it supports sharing it
+    pub fn get_metadata(&self, server: &Server) -> Option<&metadata::Metadata> {
+        self.client(server)
+            .filter(|client| client.supports_metadata)
+            .map(|client| &client.metadata)
+    }
+
+    pub fn get_user_profile(
+        &self,
+        server: &Server,
+        nick: &str,
+    ) -> Option<&metadata::Profile> {
+        self.get_metadata(server).and_then(|metadata| {
+            metadata.profile(nick, self.get_casemapping(server))
+        })
+    }
+
+    pub fn supports_setname(&self, server: &Server) -> bool {
+        self.client(server)
+            .is_some_and(|client| client.supports_setname)
+    }
+
+    pub fn supports_message_tags(&self, server: &Server) -> bool {
+        self.client(server)
+            .is_some_and(|client| client.supports_message_tags)
+    }
+
     pub fn get_chantypes<'a>(&'a self, server: &Server) -> &'a [char] {
         self.client(server)
             .map(Client::chantypes)
@@ -3199,11 +4177,60 @@ impl Map {
             })
     }
 
+    /// Nicknames of bouncer services added in the config of `server`
+    pub fn get_bouncer_services(&self, server: &Server) -> &[String] {
+        self.client(server)
+            .map(|client| client.config.bouncer_services.as_slice())
+            .unwrap_or_default()
+    }
+
     pub fn get_server_supports_chathistory(&self, server: &Server) -> bool {
         self.client(server)
             .is_some_and(|client| client.supports_chathistory)
     }
 
+    /// WHO polls due at `now` on each server
+    pub fn who_polls_due(
+        &self,
+        now: Instant,
+    ) -> Vec<(Server, target::Channel)> {
+        self.0
+            .iter()
+            .filter_map(|(server, state)| {
+                let State::Ready(client) = state else {
+                    return None;
+                };
+
+                client
+                    .who_poll_due(now)
+                    .map(|channel| (server.clone(), channel))
+            })
+            .collect()
+    }
+
+    pub fn send_who_poll(
+        &mut self,
+        server: &Server,
+        channel: &target::Channel,
+        now: Instant,
+    ) -> Result<()> {
+        if let Some(client) = self.client_mut(server) {
+            client.send_who_poll(channel, now)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn who_poll_pending(
+        &self,
+        server: &Server,
+        channel: &target::Channel,
+        now: Instant,
+    ) -> bool {
+        self.client(server)
+            .is_some_and(|client| client.who_poll_pending(channel, now))
+    }
+
     pub fn get_chathistory_request(
         &self,
         server: &Server,
@@ -3305,6 +4332,7 @@ impl Map {
 
     pub fn status(&self, server: &Server) -> Status {
         self.0.get(server).map_or(Status::Unavailable, |s| match s {
+            State::Idle => Status::Idle,
             State::Disconnected => Status::Disconnected,
             State::Ready(_) => Status::Connected,
         })
@@ -3324,6 +4352,29 @@ impl Map {
         }
         Ok(())
     }
+
+    /// Reports of the LINKS, MAP and ADMIN requests which timed out
+    pub fn finish_timed_out_server_info(
+        &mut self,
+        now: Instant,
+    ) -> Vec<(Server, server_info::Report)> {
+        self.0
+            .iter_mut()
+            .filter_map(|(server, state)| {
+                if let State::Ready(client) = state {
+                    Some((server, client))
+                } else {
+                    None
+                }
+            })
+            .flat_map(|(server, client)| {
+                client
+                    .finish_timed_out_server_info(now)
+                    .into_iter()
+                    .map(|report| (server.clone(), report))
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -3412,10 +4463,134 @@ pub struct Channel {
     pub topic: Topic,
     pub names_init: bool,
     pub who_init: bool,
-    pub mode: Option<String>,
+    /// Known once `RPL_CHANNELMODEIS` is received, and kept up to date by
+    /// MODE lines
+    pub modes: Option<mode::Modes>,
+    /// Known once a ban list requested with `request_bans` is received, and
+    /// kept up to date by MODE lines
+    pub bans: Option<Vec<Ban>>,
+    /// Users received so far while resyncing with `NAMES`
+    names_resync: Option<NamesResync>,
+    /// Bans received so far for a requested ban list
+    bans_request: Option<Vec<Ban>>,
+}
+
+#[derive(Debug)]
+struct NamesResync {
+    users: HashSet<User>,
+    requested: bool,
+}
+
+/// Difference between the users of a channel and a `NAMES` snapshot
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Resync {
+    pub removed: Vec<Nick>,
+    pub added: Vec<Nick>,
+}
+
+impl Resync {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty()
+    }
 }
 
 impl Channel {
+    /// Applies the access levels given and taken by a MODE line, updating
+    /// each user named once however many modes name them
+    pub fn update_access_levels(
+        &mut self,
+        modes: Vec<mode::Mode<mode::Channel>>,
+    ) {
+        let mut updated = Vec::<User>::new();
+
+        for mode in modes {
+            let (Some(operation), Some(nick)) = (mode.operation(), mode.arg())
+            else {
+                continue;
+            };
+            let lookup = User::from(Nick::from(nick));
+
+            let index = match updated.iter().position(|user| *user == lookup) {
+                Some(index) => index,
+                None => {
+                    let Some(user) = self.users.take(&lookup) else {
+                        continue;
+                    };
+
+                    updated.push(user);
+                    updated.len() - 1
+                }
+            };
+
+            updated[index].update_access_level(operation, *mode.value());
+        }
+
+        self.users.extend(updated);
+    }
+
+    /// Replaces the users with a `NAMES` snapshot, keeping what's known
+    /// about the users who remain
+    pub fn resync_users(&mut self, names: HashSet<User>) -> Resync {
+        let resync = Resync {
+            removed: self
+                .users
+                .difference(&names)
+                .map(|user| user.nickname().to_owned())
+                .sorted()
+                .collect(),
+            added: names
+                .difference(&self.users)
+                .map(|user| user.nickname().to_owned())
+                .sorted()
+                .collect(),
+        };
+
+        self.users = names
+            .into_iter()
+            .map(|user| match self.users.get(&user) {
+                Some(known) => user.with_known_details(known),
+                None => user,
+            })
+            .collect();
+
+        resync
+    }
+
+    /// Adds and removes the bans set by a MODE line, if the ban list is known
+    pub fn update_bans(
+        &mut self,
+        modes: &[mode::Mode<mode::Channel>],
+        who: Option<&str>,
+        time: DateTime<Utc>,
+    ) {
+        let Some(bans) = &mut self.bans else {
+            return;
+        };
+
+        for mode in modes {
+            let (mode::Channel::Ban, Some(mask)) = (mode.value(), mode.arg())
+            else {
+                continue;
+            };
+
+            match mode.operation() {
+                Some(mode::Operation::Add) => {
+                    if !bans.iter().any(|ban| ban.mask == mask) {
+                        bans.push(Ban {
+                            mask: mask.to_string(),
+                            who: who.map(ToString::to_string),
+                            time: Some(time),
+                        });
+                    }
+                }
+                Some(mode::Operation::Remove) => {
+                    bans.retain(|ban| ban.mask != mask);
+                }
+                None => {}
+            }
+        }
+    }
+
     pub fn update_user_away(&mut self, user: &str, flags: &str) {
         let user = User::from(Nick::from(user));
 
@@ -3450,6 +4625,14 @@ pub struct Topic {
     pub time: Option<DateTime<Utc>>,
 }
 
+/// Entry of a channel's ban list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ban {
+    pub mask: String,
+    pub who: Option<String>,
+    pub time: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone)]
 pub struct WhoPoll {
     pub channel: target::Channel,
@@ -3471,6 +4654,12 @@ pub enum WhoSource {
     Poll,
 }
 
+#[derive(Debug)]
+enum WhoRequest {
+    Poll,
+    Retry,
+}
+
 pub struct BackoffInterval {
     duration: Duration,
     previous: Duration,
@@ -3639,3 +4828,113 @@ pub enum Error {
     #[error(transparent)]
     Target(#[from] target::ParseError),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::AccessLevel;
+
+    fn names(names: &str) -> HashSet<User> {
+        names
+            .split(' ')
+            .map(|user| User::try_from(user).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn resync_users() {
+        let mut channel = Channel {
+            users: names("@alice bob carol dave"),
+            ..Channel::default()
+        };
+
+        let bob = User::try_from("bob").unwrap();
+        let mut known_bob = User::try_from("bob!b@host.example.com")
+            .unwrap()
+            .with_accountname("bob");
+        known_bob.update_away(true);
+        channel.users.replace(known_bob);
+
+        let resync = channel.resync_users(names("+bob @alice erin"));
+
+        assert_eq!(
+            resync,
+            Resync {
+                removed: vec![Nick::from("carol"), Nick::from("dave")],
+                added: vec![Nick::from("erin")],
+            }
+        );
+        assert_eq!(channel.users, names("alice bob erin"));
+
+        // What's known about remaining users is kept, while access levels
+        // come from the snapshot
+        let bob = channel.users.get(&bob).unwrap();
+        assert_eq!(bob.hostname(), Some("host.example.com"));
+        assert_eq!(bob.accountname(), Some("bob"));
+        assert!(bob.is_away());
+        assert_eq!(bob.highest_access_level(), AccessLevel::Voice);
+
+        assert!(channel.resync_users(names("alice bob erin")).is_empty());
+    }
+
+    #[test]
+    fn update_access_levels() {
+        let mut channel = Channel {
+            users: names("alice @bob carol"),
+            ..Channel::default()
+        };
+        let args =
+            ["alice", "alice", "*!*@mask", "bob", "carol"].map(String::from);
+
+        channel.update_access_levels(mode::parse::<mode::Channel>(
+            "+ov-bo+v", &args,
+        ));
+
+        let level = |nick: &str| {
+            channel
+                .users
+                .get(&User::from(Nick::from(nick)))
+                .map(User::highest_access_level)
+        };
+
+        assert_eq!(level("alice"), Some(AccessLevel::Oper));
+        assert!(
+            channel
+                .users
+                .get(&User::from(Nick::from("alice")))
+                .unwrap()
+                .has_access_level(AccessLevel::Voice)
+        );
+        assert_eq!(level("bob"), Some(AccessLevel::Member));
+        assert_eq!(level("carol"), Some(AccessLevel::Voice));
+        assert_eq!(channel.users.len(), 3);
+    }
+
+    #[test]
+    fn update_bans() {
+        let time = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let ban = |mask: &str| Ban {
+            mask: mask.to_string(),
+            who: Some("alice".to_string()),
+            time: Some(time),
+        };
+        let args = ["*!*@spam", "bob", "*!*@old"].map(String::from);
+        let modes = mode::parse::<mode::Channel>("+bo-b", &args);
+
+        // Unknown until requested
+        let mut channel = Channel::default();
+        channel.update_bans(&modes, Some("alice"), time);
+        assert_eq!(channel.bans, None);
+
+        let mut channel = Channel {
+            bans: Some(vec![ban("*!*@old")]),
+            ..Channel::default()
+        };
+        channel.update_bans(&modes, Some("alice"), time);
+        assert_eq!(channel.bans, Some(vec![ban("*!*@spam")]));
+
+        // Bans already listed aren't repeated
+        channel.update_bans(&modes, Some("alice"), time);
+        assert_eq!(channel.bans, Some(vec![ban("*!*@spam")]));
+    }
+}
@@ -0,0 +1,192 @@
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::{Server, environment, target};
+
+/// Channel forwards (`470`) remembered for a server, so joining a forwarded
+/// channel goes straight to where the server would send us
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Forwards(Vec<Forward>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Forward {
+    pub from: target::Channel,
+    pub to: target::Channel,
+}
+
+impl Forwards {
+    pub fn load(server: &Server) -> Result<Self, Error> {
+        let path = path(server)?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let bytes = std::fs::read(path)?;
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    pub async fn save(self, server: Server) -> Result<(), Error> {
+        let path = path(&server)?;
+
+        let bytes = serde_json::to_vec(&self)?;
+        fs::write(path, &bytes).await?;
+
+        Ok(())
+    }
+
+    /// Remembers that `from` forwards to `to`, replacing any previous
+    /// forward of `from`. Returns whether anything changed.
+    pub fn insert(
+        &mut self,
+        from: target::Channel,
+        to: target::Channel,
+    ) -> bool {
+        if from == to
+            || self
+                .0
+                .iter()
+                .any(|forward| forward.from == from && forward.to == to)
+        {
+            return false;
+        }
+
+        self.0.retain(|forward| forward.from != from);
+        self.0.push(Forward { from, to });
+
+        true
+    }
+
+    /// Forgets every forward ending up in `channel`. Returns whether
+    /// anything changed.
+    pub fn forget(&mut self, channel: &target::Channel) -> bool {
+        let forgotten = self
+            .0
+            .iter()
+            .filter(|forward| self.resolve(&forward.from) == Some(channel))
+            .map(|forward| forward.from.clone())
+            .collect::<Vec<_>>();
+
+        self.0.retain(|forward| !forgotten.contains(&forward.from));
+
+        !forgotten.is_empty()
+    }
+
+    /// Where joining `channel` ends up, following chained forwards.
+    pub fn resolve<'a>(
+        &'a self,
+        channel: &'a target::Channel,
+    ) -> Option<&'a target::Channel> {
+        let mut resolved = None;
+
+        // Bounded so forwards looping back on themselves terminate
+        for _ in 0..self.0.len() {
+            let current = resolved.unwrap_or(channel);
+
+            match self.0.iter().find(|forward| &forward.from == current) {
+                Some(forward) => resolved = Some(&forward.to),
+                None => break,
+            }
+        }
+
+        resolved.filter(|resolved| *resolved != channel)
+    }
+
+    /// Whether joining any channel ends up in `channel`
+    pub fn is_target(&self, channel: &target::Channel) -> bool {
+        self.0.iter().any(|forward| &forward.to == channel)
+    }
+}
+
+fn path(server: &Server) -> Result<PathBuf, Error> {
+    let parent = environment::data_dir().join("forwards");
+
+    if !parent.exists() {
+        std::fs::create_dir_all(&parent)?;
+    }
+
+    let hashed_server = seahash::hash(format!("{server}").as_bytes());
+
+    Ok(parent.join(format!("{hashed_server}.json")))
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Serde(Arc<serde_json::Error>),
+    #[error(transparent)]
+    Io(Arc<io::Error>),
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serde(Arc::new(error))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(Arc::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isupport::CaseMap;
+
+    fn channel(name: &str) -> target::Channel {
+        target::Channel::from_str(name, CaseMap::default())
+    }
+
+    #[test]
+    fn repeated_forwards() {
+        let mut forwards = Forwards::default();
+
+        assert!(forwards.insert(channel("#foo"), channel("##foo-overflow")));
+        assert!(!forwards.insert(channel("#FOO"), channel("##foo-overflow")));
+        assert!(!forwards.insert(channel("#bar"), channel("#BAR")));
+        assert_eq!(
+            forwards.resolve(&channel("#foo")),
+            Some(&channel("##foo-overflow"))
+        );
+        assert_eq!(forwards.resolve(&channel("#bar")), None);
+
+        // A later forward of the same channel replaces the earlier one
+        assert!(
+            forwards.insert(channel("#foo"), channel("##foo-unregistered"))
+        );
+        assert_eq!(
+            forwards.resolve(&channel("#foo")),
+            Some(&channel("##foo-unregistered"))
+        );
+        assert!(!forwards.is_target(&channel("##foo-overflow")));
+        assert!(forwards.is_target(&channel("##foo-unregistered")));
+    }
+
+    #[test]
+    fn chained_forwards() {
+        let mut forwards = Forwards::default();
+
+        forwards.insert(channel("#a"), channel("#b"));
+        forwards.insert(channel("#b"), channel("#c"));
+
+        assert_eq!(forwards.resolve(&channel("#a")), Some(&channel("#c")));
+        assert_eq!(forwards.resolve(&channel("#b")), Some(&channel("#c")));
+
+        // Forwards looping back resolve to the channel itself
+        forwards.insert(channel("#c"), channel("#a"));
+        assert_eq!(forwards.resolve(&channel("#a")), None);
+
+        forwards.insert(channel("#c"), channel("#d"));
+        assert!(forwards.forget(&channel("#d")));
+        assert_eq!(forwards.resolve(&channel("#a")), None);
+        assert_eq!(forwards.resolve(&channel("#b")), None);
+        assert!(!forwards.forget(&channel("#d")));
+    }
+}
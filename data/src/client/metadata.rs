@@ -0,0 +1,448 @@
+//! Avatars, display names and colors of users shared with the
+//! `draft/metadata-2` extension, subscribed to once registered and synced as buffers are focused
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+use irc::proto::command::Numeric;
+use irc::proto::{self, Command};
+use url::Url;
+
+use crate::appearance::theme;
+use crate::isupport;
+
+pub const CAPABILITY: &str = "draft/metadata-2";
+
+const AVATAR: &str = "avatar";
+const DISPLAY_NAME: &str = "display-name";
+const COLOR: &str = "color";
+
+/// How long to wait before syncing again when the server asks to without
+/// saying for how long
+const SYNC_LATER: Duration = Duration::from_secs(5);
+
+/// Whether `cap`, as listed with its value, is the extension
+pub fn is_capability(cap: &str) -> bool {
+    cap == CAPABILITY
+        || cap
+            .strip_prefix(CAPABILITY)
+            .is_some_and(|value| value.starts_with('='))
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub avatar: Option<Url>,
+    pub display_name: Option<String>,
+    /// Hex color, as `#rrggbb`
+    color: Option<String>,
+}
+
+impl Profile {
+    /// Display name, unless it's only the nickname again
+    pub fn display_name(&self, nick: &str) -> Option<&str> {
+        self.display_name
+            .as_deref()
+            .filter(|display_name| !display_name.eq_ignore_ascii_case(nick))
+    }
+
+    /// Color the user picked for their nickname
+    pub fn color(&self) -> Option<iced_core::Color> {
+        self.color.as_deref().and_then(theme::hex_to_color)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.avatar.is_none()
+            && self.display_name.is_none()
+            && self.color.is_none()
+    }
+}
+
+/// Metadata the server told about
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reply {
+    /// `key` of `target` is `value`, or isn't set without one
+    Value {
+        target: String,
+        key: String,
+        value: Option<String>,
+    },
+    /// `target` can't be synced yet, to be tried again after `retry_after`
+    SyncLater {
+        target: String,
+        retry_after: Option<Duration>,
+    },
+    /// Keys which are now subscribed to
+    Subscribed(Vec<String>),
+}
+
+impl Reply {
+    pub fn parse(command: &Command) -> Option<Self> {
+        match command {
+            // <target> <key> <visibility> [<value>]
+            Command::METADATA(target, key, params) => Some(Reply::Value {
+                target: target.clone(),
+                key: key.clone(),
+                value: params.get(1).cloned(),
+            }),
+            // <client> <target> <key> <visibility> [<value>]
+            Command::Numeric(Numeric::RPL_KEYVALUE, args) => {
+                Some(Reply::Value {
+                    target: args.get(1)?.clone(),
+                    key: args.get(2)?.clone(),
+                    value: args.get(4).cloned(),
+                })
+            }
+            // <client> <target> <key> :key not set
+            Command::Numeric(Numeric::RPL_KEYNOTSET, args) => {
+                Some(Reply::Value {
+                    target: args.get(1)?.clone(),
+                    key: args.get(2)?.clone(),
+                    value: None,
+                })
+            }
+            // <client> <target> [<retry after>]
+            Command::Numeric(Numeric::RPL_METADATASYNCLATER, args) => {
+                Some(Reply::SyncLater {
+                    target: args.get(1)?.clone(),
+                    retry_after: args
+                        .get(2)
+                        .and_then(|secs| secs.parse().ok())
+                        .map(Duration::from_secs),
+                })
+            }
+            // <client> <key> [<key>...]
+            Command::Numeric(Numeric::RPL_METADATASUBOK, args) => {
+                Some(Reply::Subscribed(args.iter().skip(1).cloned().collect()))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Metadata {
+    profiles: HashMap<String, Profile>,
+    /// Targets which were synced, by their normalized name
+    synced: HashSet<String>,
+    /// Targets to sync again once the server is ready for them
+    sync_later: Vec<(String, Instant)>,
+}
+
+impl Metadata {
+    /// Subscribes to the keys which are shown
+    pub fn subscribe() -> proto::Message {
+        proto::command(
+            "METADATA",
+            vec![
+                "*".into(),
+                "SUB".into(),
+                AVATAR.into(),
+                DISPLAY_NAME.into(),
+                COLOR.into(),
+            ],
+        )
+    }
+
+    /// Request for the metadata of `target` and, of a channel, its users,
+    /// unless it was already requested
+    pub fn sync(
+        &mut self,
+        target: &str,
+        casemapping: isupport::CaseMap,
+    ) -> Option<proto::Message> {
+        self.synced
+            .insert(casemapping.normalize(target))
+            .then(|| sync(target))
+    }
+
+    /// Syncs the server asked to be sent again, once due
+    pub fn poll(&mut self, now: Instant) -> Vec<proto::Message> {
+        let (due, later) = std::mem::take(&mut self.sync_later)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(_, at)| *at <= now);
+
+        self.sync_later = later;
+
+        due.into_iter().map(|(target, _)| sync(&target)).collect()
+    }
+
+    /// Records `reply`, returning whether a profile changed
+    pub fn update(
+        &mut self,
+        reply: Reply,
+        now: Instant,
+        casemapping: isupport::CaseMap,
+    ) -> bool {
+        match reply {
+            Reply::Value { target, key, value } => {
+                let normalized = casemapping.normalize(&target);
+                let profile =
+                    self.profiles.entry(normalized.clone()).or_default();
+                let before = profile.clone();
+
+                match key.as_str() {
+                    AVATAR => {
+                        profile.avatar = value
+                            .and_then(|value| Url::parse(&value).ok())
+                            .filter(|url| {
+                                matches!(url.scheme(), "http" | "https")
+                            });
+                    }
+                    DISPLAY_NAME => {
+                        profile.display_name = value
+                            .map(|value| {
+                                value
+                                    .chars()
+                                    .filter(|c| !c.is_control())
+                                    .collect::<String>()
+                                    .trim()
+                                    .to_string()
+                            })
+                            .filter(|value| !value.is_empty());
+                    }
+                    COLOR => {
+                        profile.color = value.filter(|value| {
+                            value.len() == 7
+                                && theme::hex_to_color(value).is_some()
+                        });
+                    }
+                    _ => {}
+                }
+
+                let changed = *profile != before;
+
+                if profile.is_empty() {
+                    self.profiles.remove(&normalized);
+                }
+
+                changed
+            }
+            Reply::SyncLater {
+                target,
+                retry_after,
+            } => {
+                self.sync_later
+                    .push((target, now + retry_after.unwrap_or(SYNC_LATER)));
+
+                false
+            }
+            Reply::Subscribed(_) => false,
+        }
+    }
+
+    pub fn profile(
+        &self,
+        target: &str,
+        casemapping: isupport::CaseMap,
+    ) -> Option<&Profile> {
+        self.profiles.get(&casemapping.normalize(target))
+    }
+
+    /// Avatars of every known profile
+    pub fn avatars(&self) -> impl Iterator<Item = &Url> {
+        self.profiles
+            .values()
+            .filter_map(|profile| profile.avatar.as_ref())
+    }
+
+    /// Keeps the profile of a user who changed nicknames
+    pub fn rename(
+        &mut self,
+        old: &str,
+        new: &str,
+        casemapping: isupport::CaseMap,
+    ) {
+        if let Some(profile) = self.profiles.remove(&casemapping.normalize(old))
+        {
+            self.profiles.insert(casemapping.normalize(new), profile);
+        }
+    }
+
+    /// Forgets a user who quit, to be synced again when they're back
+    pub fn remove(&mut self, nick: &str, casemapping: isupport::CaseMap) {
+        let normalized = casemapping.normalize(nick);
+
+        self.profiles.remove(&normalized);
+        self.synced.remove(&normalized);
+    }
+}
+
+fn sync(target: &str) -> proto::Message {
+    proto::command("METADATA", vec![target.into(), "SYNC".into()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Option<Reply> {
+        Reply::parse(&proto::parse::message(line).unwrap().command)
+    }
+
+    #[test]
+    fn replies() {
+        let value = |target: &str, key: &str, value: Option<&str>| {
+            Some(Reply::Value {
+                target: target.into(),
+                key: key.into(),
+                value: value.map(String::from),
+            })
+        };
+
+        assert_eq!(
+            parse(
+                ":irc.example.com METADATA alice display-name * :Alice L\r\n"
+            ),
+            value("alice", "display-name", Some("Alice L"))
+        );
+        // Unset without a value
+        assert_eq!(
+            parse(":irc.example.com METADATA alice avatar *\r\n"),
+            value("alice", "avatar", None)
+        );
+        assert_eq!(
+            parse(
+                ":irc.example.com 761 dan alice avatar * :https://example.com/a.png\r\n"
+            ),
+            value("alice", "avatar", Some("https://example.com/a.png"))
+        );
+        assert_eq!(
+            parse(":irc.example.com 766 dan alice avatar :key not set\r\n"),
+            value("alice", "avatar", None)
+        );
+        assert_eq!(
+            parse(":irc.example.com 774 dan #halloy 30\r\n"),
+            Some(Reply::SyncLater {
+                target: "#halloy".into(),
+                retry_after: Some(Duration::from_secs(30)),
+            })
+        );
+        assert_eq!(
+            parse(":irc.example.com 774 dan #halloy\r\n"),
+            Some(Reply::SyncLater {
+                target: "#halloy".into(),
+                retry_after: None,
+            })
+        );
+        assert_eq!(
+            parse(":irc.example.com 770 dan avatar display-name\r\n"),
+            Some(Reply::Subscribed(vec![
+                "avatar".into(),
+                "display-name".into()
+            ]))
+        );
+        assert_eq!(parse(":irc.example.com 761 dan alice\r\n"), None);
+        assert_eq!(parse(":dan!d@localhost PRIVMSG alice :hi\r\n"), None);
+
+        assert!(is_capability("draft/metadata-2"));
+        assert!(is_capability("draft/metadata-2=max-subs=50"));
+        assert!(!is_capability("draft/metadata"));
+    }
+
+    #[test]
+    fn profiles() {
+        let casemapping = isupport::CaseMap::default();
+        let now = Instant::now();
+        let mut metadata = Metadata::default();
+
+        let update = |metadata: &mut Metadata,
+                      target: &str,
+                      key: &str,
+                      value: Option<&str>| {
+            metadata.update(
+                Reply::Value {
+                    target: target.into(),
+                    key: key.into(),
+                    value: value.map(String::from),
+                },
+                now,
+                casemapping,
+            )
+        };
+
+        assert!(update(
+            &mut metadata,
+            "Alice",
+            "display-name",
+            Some(" Alice\u{7}L ")
+        ));
+        assert!(update(
+            &mut metadata,
+            "Alice",
+            "avatar",
+            Some("https://example.com/a.png")
+        ));
+        // Unchanged, unknown and unsafe values
+        assert!(!update(
+            &mut metadata,
+            "alice",
+            "display-name",
+            Some("AliceL")
+        ));
+        assert!(!update(
+            &mut metadata,
+            "alice",
+            "url",
+            Some("https://example.com")
+        ));
+        assert!(!update(
+            &mut metadata,
+            "bob",
+            "avatar",
+            Some("file:///etc/passwd")
+        ));
+
+        let profile = metadata.profile("ALICE", casemapping).unwrap();
+        assert_eq!(profile.display_name("alice"), Some("AliceL"));
+        assert_eq!(profile.display_name("alicel"), None);
+        assert_eq!(
+            profile.avatar.as_ref().map(Url::as_str),
+            Some("https://example.com/a.png")
+        );
+        assert_eq!(metadata.profile("bob", casemapping), None);
+
+        // Only colors without transparency
+        assert!(!update(&mut metadata, "alice", "color", Some("#ff000080")));
+        assert!(!update(&mut metadata, "alice", "color", Some("red")));
+        assert!(update(&mut metadata, "alice", "color", Some("#ff0000")));
+        assert_eq!(
+            metadata.profile("alice", casemapping).unwrap().color(),
+            Some(iced_core::Color::from_rgb8(255, 0, 0))
+        );
+        assert!(update(&mut metadata, "alice", "color", None));
+
+        metadata.rename("alice", "alice_", casemapping);
+        assert!(metadata.profile("alice", casemapping).is_none());
+        assert_eq!(metadata.avatars().count(), 1);
+
+        // Unset keys remove the profile once it's empty
+        assert!(update(&mut metadata, "alice_", "avatar", None));
+        assert!(update(&mut metadata, "alice_", "display-name", None));
+        assert!(metadata.profile("alice_", casemapping).is_none());
+    }
+
+    #[test]
+    fn sync_later() {
+        let casemapping = isupport::CaseMap::default();
+        let now = Instant::now();
+        let mut metadata = Metadata::default();
+
+        assert!(metadata.sync("#halloy", casemapping).is_some());
+        assert!(metadata.sync("#Halloy", casemapping).is_none());
+
+        metadata.update(
+            Reply::SyncLater {
+                target: "#halloy".into(),
+                retry_after: Some(Duration::from_secs(30)),
+            },
+            now,
+            casemapping,
+        );
+
+        assert!(metadata.poll(now).is_empty());
+        assert_eq!(
+            metadata.poll(now + Duration::from_secs(30)),
+            vec![sync("#halloy")]
+        );
+        assert!(metadata.poll(now + Duration::from_secs(60)).is_empty());
+    }
+}
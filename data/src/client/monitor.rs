@@ -0,0 +1,209 @@
+//! Presence of the users in a server's `monitor` list, followed with MONITOR
+//! or, on servers without it, by polling with ISON
+use std::collections::{HashSet, VecDeque};
+use std::time::{Duration, Instant};
+
+use irc::proto;
+
+use crate::isupport;
+use crate::user::Nick;
+
+/// How often users are polled for with ISON
+const ISON_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presence {
+    Unknown,
+    Online,
+    Offline,
+}
+
+#[derive(Debug, Clone)]
+pub struct Monitored {
+    pub nick: Nick,
+    pub presence: Presence,
+}
+
+#[derive(Debug, Default)]
+pub struct Monitor {
+    users: Vec<Monitored>,
+    /// Users asked about by each ISON still awaiting its reply
+    ison_requests: VecDeque<Vec<Nick>>,
+    last_ison: Option<Instant>,
+}
+
+impl Monitor {
+    pub fn new(nicks: &[String]) -> Self {
+        Self {
+            users: nicks
+                .iter()
+                .map(|nick| Monitored {
+                    nick: Nick::from(nick.as_str()),
+                    presence: Presence::Unknown,
+                })
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn users(&self) -> &[Monitored] {
+        &self.users
+    }
+
+    /// Stops following users past the server's limit
+    pub fn truncate(&mut self, limit: usize) {
+        self.users.truncate(limit);
+    }
+
+    /// Sets the presence of `nicks`, returning those it changed for
+    pub fn update(
+        &mut self,
+        nicks: impl IntoIterator<Item = Nick>,
+        presence: Presence,
+        casemapping: isupport::CaseMap,
+    ) -> Vec<Nick> {
+        nicks
+            .into_iter()
+            .filter(|nick| {
+                let normalized = casemapping.normalize(nick.as_ref());
+
+                self.users
+                    .iter_mut()
+                    .find(|user| {
+                        casemapping.normalize(user.nick.as_ref()) == normalized
+                    })
+                    .is_some_and(|user| {
+                        let changed = user.presence != presence;
+                        user.presence = presence;
+                        changed
+                    })
+            })
+            .collect()
+    }
+
+    /// ISON requests to send, once every interval
+    pub fn poll(&mut self, now: Instant) -> Vec<proto::Message> {
+        const MAX_LEN: usize = proto::format::BYTE_LIMIT - b"ISON \r\n".len();
+
+        if self.users.is_empty()
+            || self
+                .last_ison
+                .is_some_and(|last| now.duration_since(last) < ISON_INTERVAL)
+        {
+            return vec![];
+        }
+
+        self.last_ison = Some(now);
+        // Replies still missing by now aren't coming
+        self.ison_requests.clear();
+
+        let mut len = 0;
+
+        for user in &self.users {
+            // Nick + a space
+            len += user.nick.as_ref().len() + 1;
+
+            match self.ison_requests.back_mut() {
+                Some(nicks) if len <= MAX_LEN => nicks.push(user.nick.clone()),
+                _ => {
+                    len = user.nick.as_ref().len() + 1;
+                    self.ison_requests.push_back(vec![user.nick.clone()]);
+                }
+            }
+        }
+
+        self.ison_requests
+            .iter()
+            .map(|nicks| {
+                proto::command(
+                    "ISON",
+                    nicks.iter().map(ToString::to_string).collect(),
+                )
+            })
+            .collect()
+    }
+
+    /// Users that came online and went offline according to an ISON reply
+    /// listing those online, or `None` if it wasn't requested by us
+    pub fn ison_reply(
+        &mut self,
+        online: &str,
+        casemapping: isupport::CaseMap,
+    ) -> Option<(Vec<Nick>, Vec<Nick>)> {
+        let requested = self.ison_requests.pop_front()?;
+
+        let online = online
+            .split_ascii_whitespace()
+            .map(|nick| casemapping.normalize(nick))
+            .collect::<HashSet<_>>();
+
+        let (online, offline): (Vec<_>, Vec<_>) =
+            requested.into_iter().partition(|nick| {
+                online.contains(&casemapping.normalize(nick.as_ref()))
+            });
+
+        Some((
+            self.update(online, Presence::Online, casemapping),
+            self.update(offline, Presence::Offline, casemapping),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ison() {
+        let casemapping = isupport::CaseMap::default();
+        let now = Instant::now();
+        let mut monitor =
+            Monitor::new(&["Alice".to_string(), "bob".to_string()]);
+
+        let requests = monitor.poll(now);
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].command,
+            proto::Command::new("ISON", vec!["Alice".into(), "bob".into()])
+        );
+
+        // Not polled again until the interval passes
+        assert!(monitor.poll(now + Duration::from_secs(1)).is_empty());
+
+        assert_eq!(
+            monitor.ison_reply("alice", casemapping),
+            Some((vec![Nick::from("Alice")], vec![Nick::from("bob")]))
+        );
+        assert_eq!(monitor.users()[0].presence, Presence::Online);
+        assert_eq!(monitor.users()[1].presence, Presence::Offline);
+
+        // Replies to ISON sent by the user aren't ours
+        assert_eq!(monitor.ison_reply("bob", casemapping), None);
+
+        // Only changes are returned
+        monitor.poll(now + ISON_INTERVAL);
+        assert_eq!(
+            monitor.ison_reply("Alice bob", casemapping),
+            Some((vec![Nick::from("bob")], vec![]))
+        );
+    }
+
+    #[test]
+    fn ison_split() {
+        let nicks =
+            (0..100).map(|i| format!("nickname{i}")).collect::<Vec<_>>();
+        let mut monitor = Monitor::new(&nicks);
+
+        let requests = monitor.poll(Instant::now());
+        assert!(requests.len() > 1);
+
+        for request in &requests {
+            assert!(proto::format::message(request.clone()).len() <= 512);
+        }
+
+        assert_eq!(
+            monitor.ison_requests.iter().map(Vec::len).sum::<usize>(),
+            nicks.len()
+        );
+    }
+}
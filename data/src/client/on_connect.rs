@@ -67,8 +67,14 @@ pub fn on_connect(
                             command::Internal::OpenBuffers(targets) => {
                                 Some(Event::OpenBuffers(targets))
                             }
-                            // We don't handle hop when called from connected.
-                            command::Internal::Hop(_, _) => None,
+                            // We don't handle hop or theme when called from
+                            // connected.
+                            command::Internal::Hop(_, _)
+                            | command::Internal::Theme(_)
+                            | command::Internal::TopicHistory(_, _)
+                            | command::Internal::Schedule(_, _)
+                            | command::Internal::Layout(_)
+                            | command::Internal::BanList(_) => None,
                             command::Internal::Delay(seconds) => {
                                 time::sleep(Duration::from_secs(seconds)).await;
                                 None
@@ -0,0 +1,419 @@
+//! Collects the replies to LINKS, MAP and ADMIN into a single report, rather
+//! than a line in the server buffer for each of them
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use irc::proto::Command;
+use irc::proto::command::Numeric;
+
+/// Requests without an end to their replies within this are reported with
+/// whatever was collected
+pub const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Links,
+    Map,
+    Admin,
+}
+
+impl Kind {
+    /// Kind of report the replies to `command` are collected into
+    pub fn requested_by(command: &Command) -> Option<Self> {
+        match command {
+            Command::LINKS => Some(Kind::Links),
+            Command::ADMIN(_) => Some(Kind::Admin),
+            Command::Unknown(command, _)
+                if command.eq_ignore_ascii_case("MAP") =>
+            {
+                Some(Kind::Map)
+            }
+            _ => None,
+        }
+    }
+
+    fn command(self) -> &'static str {
+        match self {
+            Kind::Links => "LINKS",
+            Kind::Map => "MAP",
+            Kind::Admin => "ADMIN",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Request {
+    kind: Kind,
+    requested_at: Instant,
+    replies: Vec<(Numeric, Vec<String>)>,
+}
+
+/// What became of a reply offered to a [`Request`]
+#[derive(Debug, PartialEq, Eq)]
+pub enum Collected {
+    /// Not a reply to the request
+    No,
+    Yes,
+    /// The last reply to the request
+    Ended,
+    /// The request failed, e.g. for lack of privileges
+    Failed,
+}
+
+impl Request {
+    pub fn new(kind: Kind, requested_at: Instant) -> Self {
+        Self {
+            kind,
+            requested_at,
+            replies: vec![],
+        }
+    }
+
+    pub fn collect(&mut self, numeric: Numeric, args: &[String]) -> Collected {
+        use Numeric::*;
+
+        let collected = match (self.kind, numeric) {
+            (_, ERR_NOSUCHSERVER | ERR_NOPRIVILEGES | ERR_NOPRIVS) => {
+                return Collected::Failed;
+            }
+            (Kind::Map, ERR_UNKNOWNCOMMAND) => {
+                return if args
+                    .get(1)
+                    .is_some_and(|command| command.eq_ignore_ascii_case("MAP"))
+                {
+                    Collected::Failed
+                } else {
+                    Collected::No
+                };
+            }
+            (Kind::Links, RPL_LINKS)
+            | (Kind::Map, RPL_MAP | RPL_MAPIRCU | RPL_MAPMORE)
+            | (Kind::Admin, RPL_ADMINME | RPL_ADMINLOC1 | RPL_ADMINLOC2) => {
+                Collected::Yes
+            }
+            // ADMIN has no end of its own, the email always comes last
+            (Kind::Links, RPL_ENDOFLINKS)
+            | (Kind::Map, RPL_MAPEND | RPL_MAPENDIRCU)
+            | (Kind::Admin, RPL_ADMINEMAIL) => Collected::Ended,
+            _ => return Collected::No,
+        };
+
+        self.replies.push((numeric, args.to_vec()));
+
+        collected
+    }
+
+    pub fn is_timed_out(&self, now: Instant) -> bool {
+        now.duration_since(self.requested_at) >= TIMEOUT
+    }
+
+    pub fn finish(self, timed_out: bool) -> Report {
+        let lines = match self.kind {
+            Kind::Links => links(&self.replies),
+            Kind::Map => self
+                .replies
+                .iter()
+                .filter(|(numeric, _)| {
+                    !matches!(
+                        numeric,
+                        Numeric::RPL_MAPEND | Numeric::RPL_MAPENDIRCU
+                    )
+                })
+                .filter_map(|(_, args)| args.last().cloned())
+                .collect(),
+            Kind::Admin => admin(&self.replies),
+        };
+
+        Report {
+            kind: self.kind,
+            lines,
+            timed_out,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    pub kind: Kind,
+    pub lines: Vec<String>,
+    /// Set when the replies didn't end within [`TIMEOUT`], so some may be
+    /// missing
+    pub timed_out: bool,
+}
+
+impl Report {
+    pub fn title(&self) -> &'static str {
+        match self.kind {
+            Kind::Links => "server links",
+            Kind::Map => "server map",
+            Kind::Admin => "administrative info",
+        }
+    }
+
+    /// Note on the report being incomplete, if it is
+    pub fn note(&self) -> Option<String> {
+        let seconds = TIMEOUT.as_secs();
+        let command = self.kind.command();
+
+        match (self.timed_out, self.lines.is_empty()) {
+            (false, _) => None,
+            (true, true) => {
+                Some(format!("no reply to {command} within {seconds} seconds"))
+            }
+            (true, false) => Some(format!(
+                "incomplete, the reply to {command} didn't end within {seconds} seconds"
+            )),
+        }
+    }
+
+    /// Whether the lines have to be shown in a monospace font to line up
+    pub fn is_monospace(&self) -> bool {
+        matches!(self.kind, Kind::Links | Kind::Map)
+    }
+}
+
+struct Link<'a> {
+    server: &'a str,
+    hub: &'a str,
+    info: &'a str,
+}
+
+/// `<client> <server> <hub> :<hopcount> <server info>` replies as a tree of
+/// servers under the hubs they're linked to
+fn links(replies: &[(Numeric, Vec<String>)]) -> Vec<String> {
+    let links = replies
+        .iter()
+        .filter(|(numeric, _)| *numeric == Numeric::RPL_LINKS)
+        .filter_map(|(_, args)| {
+            let [_, server, hub, trailing] = args.as_slice() else {
+                return None;
+            };
+            let info =
+                trailing.split_once(' ').map_or("", |(_, info)| info.trim());
+
+            Some(Link { server, hub, info })
+        })
+        .collect::<Vec<_>>();
+
+    let mut visited = HashSet::new();
+    let mut lines = vec![];
+
+    // Servers whose hub wasn't listed, usually only the one we're on
+    let roots = (0..links.len())
+        .filter(|index| {
+            let link = &links[*index];
+
+            link.server == link.hub
+                || !links.iter().any(|hub| hub.server == link.hub)
+        })
+        // Anything unreachable from them, should the links form a cycle
+        .chain(0..links.len())
+        .collect::<Vec<_>>();
+
+    for root in roots {
+        walk(&links, root, "", "", &mut visited, &mut lines);
+    }
+
+    lines
+}
+
+/// Adds the line of `links[index]` under `prefix`, followed by the servers
+/// linked to it
+fn walk(
+    links: &[Link<'_>],
+    index: usize,
+    prefix: &str,
+    branch: &str,
+    visited: &mut HashSet<usize>,
+    lines: &mut Vec<String>,
+) {
+    if !visited.insert(index) {
+        return;
+    }
+
+    let link = &links[index];
+
+    lines.push(if link.info.is_empty() {
+        format!("{prefix}{branch}{}", link.server)
+    } else {
+        format!("{prefix}{branch}{} ({})", link.server, link.info)
+    });
+
+    let prefix = match branch {
+        "" => prefix.to_string(),
+        "├─ " => format!("{prefix}│  "),
+        _ => format!("{prefix}   "),
+    };
+
+    let children = links
+        .iter()
+        .enumerate()
+        .filter(|(child, child_link)| {
+            !visited.contains(child)
+                && child_link.server != child_link.hub
+                && child_link.hub == link.server
+        })
+        .map(|(child, _)| child)
+        .collect::<Vec<_>>();
+
+    for (position, child) in children.iter().enumerate() {
+        let branch = if position + 1 == children.len() {
+            "└─ "
+        } else {
+            "├─ "
+        };
+
+        walk(links, *child, &prefix, branch, visited, lines);
+    }
+}
+
+/// `RPL_ADMIN*` replies as a labeled block
+fn admin(replies: &[(Numeric, Vec<String>)]) -> Vec<String> {
+    let fields = replies
+        .iter()
+        .filter_map(|(numeric, args)| {
+            let value = args.last()?.trim();
+
+            let (label, value) = match numeric {
+                // `<client> [<server>] :Administrative info`, naming the
+                // server rather than saying what follows is useful
+                Numeric::RPL_ADMINME => (
+                    "Server",
+                    match args.as_slice() {
+                        [_, server, _] => server.as_str(),
+                        _ => value,
+                    },
+                ),
+                Numeric::RPL_ADMINLOC1 => ("Location", value),
+                Numeric::RPL_ADMINLOC2 => ("Details", value),
+                Numeric::RPL_ADMINEMAIL => ("Email", value),
+                _ => return None,
+            };
+
+            (!value.is_empty()).then_some((label, value))
+        })
+        .collect::<Vec<_>>();
+
+    let width = fields
+        .iter()
+        .map(|(label, _)| label.len() + 1)
+        .max()
+        .unwrap_or_default();
+
+    fields
+        .into_iter()
+        .map(|(label, value)| {
+            format!("{:<width$} {value}", format!("{label}:"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reply(numeric: Numeric, args: &[&str]) -> (Numeric, Vec<String>) {
+        (numeric, args.iter().map(ToString::to_string).collect())
+    }
+
+    fn collect(kind: Kind, replies: &[(Numeric, Vec<String>)]) -> Request {
+        let mut request = Request::new(kind, Instant::now());
+
+        for (numeric, args) in replies {
+            request.collect(*numeric, args);
+        }
+
+        request
+    }
+
+    #[test]
+    fn links_tree() {
+        use Numeric::*;
+
+        let mut request = collect(
+            Kind::Links,
+            &[
+                reply(RPL_LINKS, &["me", "leaf.b", "hub", "2 Leaf B"]),
+                reply(RPL_LINKS, &["me", "leaf.a", "hub", "1 Leaf A"]),
+                reply(RPL_LINKS, &["me", "leaf.c", "leaf.b", "3 Leaf C"]),
+                reply(RPL_LINKS, &["me", "hub", "hub", "0 Hub"]),
+            ],
+        );
+
+        assert_eq!(
+            request.collect(RPL_ENDOFLINKS, &["me".into(), "*".into()]),
+            Collected::Ended
+        );
+
+        let report = request.finish(false);
+
+        assert_eq!(
+            report.lines,
+            vec![
+                "hub (Hub)",
+                "├─ leaf.b (Leaf B)",
+                "│  └─ leaf.c (Leaf C)",
+                "└─ leaf.a (Leaf A)",
+            ]
+        );
+        assert_eq!(report.note(), None);
+    }
+
+    #[test]
+    fn map_and_admin() {
+        use Numeric::*;
+
+        let map = collect(
+            Kind::Map,
+            &[
+                reply(RPL_MAP, &["me", "hub ------ | Users: 3"]),
+                reply(RPL_MAP, &["me", "  `- leaf  | Users: 1"]),
+                reply(RPL_MAPEND, &["me", "End of /MAP"]),
+            ],
+        );
+
+        assert_eq!(
+            map.finish(false).lines,
+            vec!["hub ------ | Users: 3", "  `- leaf  | Users: 1"]
+        );
+
+        let admin = collect(
+            Kind::Admin,
+            &[
+                reply(RPL_ADMINME, &["me", "irc.example.org", "Admin info"]),
+                reply(RPL_ADMINLOC1, &["me", "Somewhere"]),
+                reply(RPL_ADMINLOC2, &["me", ""]),
+            ],
+        )
+        .finish(true);
+
+        assert_eq!(
+            admin.lines,
+            vec!["Server:   irc.example.org", "Location: Somewhere"]
+        );
+        assert!(admin.note().is_some());
+    }
+
+    #[test]
+    fn unrelated_and_failed() {
+        use Numeric::*;
+
+        let mut request = Request::new(Kind::Map, Instant::now());
+
+        assert_eq!(request.collect(RPL_LINKS, &["me".into()]), Collected::No);
+        assert_eq!(
+            request.collect(
+                ERR_UNKNOWNCOMMAND,
+                &["me".into(), "FOO".into(), "Unknown".into()]
+            ),
+            Collected::No
+        );
+        assert_eq!(
+            request.collect(
+                ERR_UNKNOWNCOMMAND,
+                &["me".into(), "MAP".into(), "Unknown".into()]
+            ),
+            Collected::Failed
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::HashMap;
 use std::str::FromStr;
 
+use chrono::{DateTime, Local, Utc};
 use fancy_regex::Regex;
 use irc::proto;
 use itertools::Itertools;
@@ -8,7 +9,12 @@ use itertools::Itertools;
 use crate::buffer::{self, Upstream};
 use crate::isupport::{self, find_target_limit};
 use crate::message::{self, formatting};
-use crate::{Target, ctcp};
+use crate::{Target, ctcp, scheduled, target};
+
+pub mod custom;
+
+/// # topics printed by `/topic -history` without a count
+const DEFAULT_TOPIC_HISTORY: usize = 5;
 
 #[derive(Debug, Clone)]
 pub enum Command {
@@ -25,6 +31,23 @@ pub enum Internal {
     /// - Part message
     Hop(Option<String>, Option<String>),
     Delay(u64),
+    /// Switch to a theme, or pick one if none is given
+    Theme(Option<String>),
+    /// Print the last topics of a channel from its audit log
+    TopicHistory(target::Channel, usize),
+    /// Send a message to the buffer's target later
+    Schedule(DateTime<Utc>, String),
+    /// Save, load or delete a named layout of the panes
+    Layout(Layout),
+    /// Show the ban list of a channel
+    BanList(target::Channel),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Layout {
+    Save(String),
+    Load(String),
+    Delete(String),
 }
 
 #[derive(Debug, Clone)]
@@ -46,6 +69,9 @@ pub enum Irc {
     Raw(String),
     Unknown(String, Vec<String>),
     Ctcp(ctcp::Command, String, Option<String>),
+    Links,
+    Map,
+    Admin(Option<String>),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -69,6 +95,14 @@ enum Kind {
     Notice,
     Delay,
     Raw,
+    Links,
+    Map,
+    Admin,
+    Theme,
+    Schedule,
+    In,
+    Layout,
+    BanList,
 }
 
 impl FromStr for Kind {
@@ -95,6 +129,14 @@ impl FromStr for Kind {
             "ctcp" => Ok(Kind::Ctcp),
             "hop" | "rejoin" => Ok(Kind::Hop),
             "delay" => Ok(Kind::Delay),
+            "links" => Ok(Kind::Links),
+            "map" => Ok(Kind::Map),
+            "admin" => Ok(Kind::Admin),
+            "theme" => Ok(Kind::Theme),
+            "schedule" => Ok(Kind::Schedule),
+            "in" => Ok(Kind::In),
+            "layout" => Ok(Kind::Layout),
+            "banlist" => Ok(Kind::BanList),
             _ => Err(()),
         }
     }
@@ -254,26 +296,12 @@ pub fn parse(
                     }
                 }
 
+                let parsed = parse_targets(&targets, isupport)?;
+
                 if let Some(msg) = msg {
                     Ok(Command::Irc(Irc::Msg(targets, msg)))
                 } else {
-                    let casemapping = isupport::get_casemapping(isupport);
-                    let chantypes = isupport::get_chantypes(isupport);
-                    let statusmsg = isupport::get_statusmsg(isupport);
-
-                    Ok(Command::Internal(Internal::OpenBuffers(
-                        targets
-                            .split(",")
-                            .map(|target| {
-                                Target::parse(
-                                    target,
-                                    chantypes,
-                                    statusmsg,
-                                    casemapping,
-                                )
-                            })
-                            .collect(),
-                    )))
+                    Ok(Command::Internal(Internal::OpenBuffers(parsed)))
                 }
             }),
             Kind::Me => {
@@ -337,22 +365,31 @@ pub fn parse(
                     Ok(Command::Irc(Irc::Part(chanlist, reason)))
                 })
             }
+            Kind::Topic if args.first() == Some(&"-history") => {
+                let Some(Target::Channel(channel)) =
+                    buffer.and_then(Upstream::target)
+                else {
+                    return Ok(unknown());
+                };
+
+                validated::<1, 1, false>(args, |_, [count]| {
+                    let count =
+                        count.map_or(Ok(DEFAULT_TOPIC_HISTORY), |count| {
+                            match count.parse::<usize>() {
+                                Ok(count) if count > 0 => Ok(count),
+                                _ => Err(Error::NotPositiveInteger),
+                            }
+                        })?;
+
+                    Ok(Command::Internal(Internal::TopicHistory(
+                        channel, count,
+                    )))
+                })
+            }
             Kind::Topic => {
                 validated::<1, 1, true>(args, |[channel], [topic]| {
                     if let Some(ref topic) = topic {
-                        if let Some(isupport::Parameter::TOPICLEN(max_len)) =
-                            isupport.get(&isupport::Kind::TOPICLEN)
-                        {
-                            let max_len = *max_len as usize;
-
-                            if topic.len() > max_len {
-                                return Err(Error::ArgTooLong {
-                                    name: "topic",
-                                    len: topic.len(),
-                                    max_len,
-                                });
-                            }
-                        }
+                        validate_topic(topic, isupport)?;
                     }
 
                     Ok(Command::Irc(Irc::Topic(channel, topic)))
@@ -483,26 +520,12 @@ pub fn parse(
                         }
                     }
 
+                    let parsed = parse_targets(&targets, isupport)?;
+
                     if let Some(msg) = msg {
                         Ok(Command::Irc(Irc::Notice(targets, msg)))
                     } else {
-                        let casemapping = isupport::get_casemapping(isupport);
-                        let chantypes = isupport::get_chantypes(isupport);
-                        let statusmsg = isupport::get_statusmsg(isupport);
-
-                        Ok(Command::Internal(Internal::OpenBuffers(
-                            targets
-                                .split(",")
-                                .map(|target| {
-                                    Target::parse(
-                                        target,
-                                        chantypes,
-                                        statusmsg,
-                                        casemapping,
-                                    )
-                                })
-                                .collect(),
-                        )))
+                        Ok(Command::Internal(Internal::OpenBuffers(parsed)))
                     }
                 })
             }
@@ -511,7 +534,11 @@ pub fn parse(
                 if let Some(target) = buffer.and_then(Upstream::target) {
                     Ok(Command::Irc(Irc::Msg(
                         target.to_string(),
-                        formatting::encode(raw, false),
+                        formatting::encode(
+                            raw,
+                            false,
+                            buffer::MarkdownLinks::default(),
+                        ),
                     )))
                 } else {
                     Ok(unknown())
@@ -519,6 +546,16 @@ pub fn parse(
             }
             Kind::Ctcp => {
                 validated::<2, 1, true>(args, |[target, command], [params]| {
+                    // A single nickname or channel, as the request can't be
+                    // split across targets
+                    if target.contains([',', '\u{1}']) {
+                        return Err(Error::InvalidCtcpTarget(target));
+                    }
+
+                    if !command.chars().all(|c| c.is_ascii_alphanumeric()) {
+                        return Err(Error::InvalidCtcpCommand(command));
+                    }
+
                     Ok(Command::Irc(Irc::Ctcp(
                         ctcp::Command::from(command.as_str()),
                         target,
@@ -542,12 +579,84 @@ pub fn parse(
                     Err(Error::NotPositiveInteger)
                 }
             }),
+            Kind::Links => validated::<0, 0, false>(args, |_, _| {
+                Ok(Command::Irc(Irc::Links))
+            }),
+            Kind::Map => validated::<0, 0, false>(args, |_, _| {
+                Ok(Command::Irc(Irc::Map))
+            }),
+            Kind::Admin => validated::<0, 1, false>(args, |_, [server]| {
+                Ok(Command::Irc(Irc::Admin(server)))
+            }),
+            Kind::Theme => validated::<0, 1, true>(args, |_, [name]| {
+                Ok(Command::Internal(Internal::Theme(name)))
+            }),
+            Kind::Schedule | Kind::In => {
+                if buffer.and_then(Upstream::target).is_none() {
+                    return Ok(unknown());
+                }
+
+                validated::<2, 0, true>(args, |[time, text], _| {
+                    let send_at = if let Kind::Schedule = kind {
+                        scheduled::parse_time(&time, Utc::now(), &Local)?
+                    } else {
+                        scheduled::parse_delay(&time, Utc::now())?
+                    };
+
+                    Ok(Command::Internal(Internal::Schedule(send_at, text)))
+                })
+            }
+            Kind::Layout => {
+                validated::<2, 0, true>(args, |[action, name], _| {
+                    let layout = match action.to_lowercase().as_str() {
+                        "save" => Layout::Save(name),
+                        "load" => Layout::Load(name),
+                        "delete" => Layout::Delete(name),
+                        _ => return Err(Error::InvalidLayoutAction(action)),
+                    };
+
+                    Ok(Command::Internal(Internal::Layout(layout)))
+                })
+            }
+            Kind::BanList => {
+                let Some(Target::Channel(channel)) =
+                    buffer.and_then(Upstream::target)
+                else {
+                    return Ok(unknown());
+                };
+
+                validated::<0, 0, false>(args, |_, _| {
+                    Ok(Command::Internal(Internal::BanList(channel)))
+                })
+            }
         },
         Err(()) => Ok(unknown()),
     }
 }
 
 // TODO: Expand `validated` so we can better indicate which parameters is optional.
+/// Checks `topic` against the server's `TOPICLEN`
+pub fn validate_topic(
+    topic: &str,
+    isupport: &HashMap<isupport::Kind, isupport::Parameter>,
+) -> Result<(), Error> {
+    if let Some(isupport::Parameter::TOPICLEN(max_len)) =
+        isupport.get(&isupport::Kind::TOPICLEN)
+    {
+        let max_len = *max_len as usize;
+
+        if topic.len() > max_len {
+            return Err(Error::ArgTooLong {
+                name: "topic",
+                len: topic.len(),
+                max_len,
+            });
+        }
+    }
+
+    Ok(())
+}
+
 fn validated<const EXACT: usize, const OPT: usize, const TEXT: bool>(
     args: Vec<&str>,
     f: impl FnOnce([String; EXACT], [Option<String>; OPT]) -> Result<Command, Error>,
@@ -620,6 +729,9 @@ impl TryFrom<Irc> for proto::Command {
             Irc::Ctcp(command, target, params) => {
                 ctcp::query_command(&command, target, params)
             }
+            Irc::Links => proto::Command::LINKS,
+            Irc::Map => proto::Command::new("MAP", vec![]),
+            Irc::Admin(server) => proto::Command::ADMIN(server),
         })
     }
 }
@@ -662,6 +774,35 @@ pub enum Error {
     },
     #[error("must be a number greater than zero")]
     NotPositiveInteger,
+    #[error("{0} is not a valid CTCP target")]
+    InvalidCtcpTarget(String),
+    #[error("{0} is not a valid CTCP command")]
+    InvalidCtcpCommand(String),
+    #[error(transparent)]
+    InvalidTarget(#[from] target::ParseError),
+    #[error(transparent)]
+    InvalidScheduleTime(#[from] scheduled::TimeError),
+    #[error("{0} isn't one of save, load or delete")]
+    InvalidLayoutAction(String),
+}
+
+/// Parses comma separated `targets`, rejecting any which isn't a channel or
+/// nickname before anything is sent
+fn parse_targets(
+    targets: &str,
+    isupport: &HashMap<isupport::Kind, isupport::Parameter>,
+) -> Result<Vec<Target>, Error> {
+    let casemapping = isupport::get_casemapping(isupport);
+    let chantypes = isupport::get_chantypes(isupport);
+    let statusmsg = isupport::get_statusmsg(isupport);
+
+    targets
+        .split(',')
+        .map(|target| {
+            Target::try_parse(target, chantypes, statusmsg, casemapping)
+                .map_err(Error::from)
+        })
+        .collect()
 }
 
 fn fmt_incorrect_arg_count(min: usize, max: usize, actual: usize) -> String {
@@ -677,3 +818,217 @@ fn fmt_incorrect_arg_count(min: usize, max: usize, actual: usize) -> String {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ctcp() {
+        let isupport = HashMap::new();
+
+        assert!(matches!(
+            parse("/ctcp dan VERSION", None, &isupport),
+            Ok(Command::Irc(Irc::Ctcp(ctcp::Command::Version, target, None)))
+                if target == "dan"
+        ));
+        assert!(matches!(
+            parse("/ctcp dan ping 1234", None, &isupport),
+            Ok(Command::Irc(Irc::Ctcp(ctcp::Command::Ping, _, Some(params))))
+                if params == "1234"
+        ));
+        assert!(matches!(
+            parse("/ctcp dan,bob VERSION", None, &isupport),
+            Err(Error::InvalidCtcpTarget(_))
+        ));
+        assert!(matches!(
+            parse("/ctcp dan VER\u{1}SION", None, &isupport),
+            Err(Error::InvalidCtcpCommand(_))
+        ));
+        assert!(matches!(
+            parse("/ctcp dan", None, &isupport),
+            Err(Error::IncorrectArgCount { .. })
+        ));
+    }
+
+    #[test]
+    fn msg_targets() {
+        let isupport = HashMap::new();
+
+        assert!(matches!(
+            parse("/msg dan,#halloy hi", None, &isupport),
+            Ok(Command::Irc(Irc::Msg(targets, _))) if targets == "dan,#halloy"
+        ));
+        assert!(matches!(
+            parse("/msg dan,#Halloy", None, &isupport),
+            Ok(Command::Internal(Internal::OpenBuffers(targets)))
+                if targets.len() == 2
+                    && targets[1] == Target::parse(
+                        "#halloy",
+                        &['#'],
+                        &[],
+                        isupport::CaseMap::default(),
+                    )
+        ));
+        assert!(matches!(
+            parse("/msg dan!d@host hi", None, &isupport),
+            Err(Error::InvalidTarget(target::ParseError::InvalidTarget(
+                target
+            ))) if target == "dan!d@host"
+        ));
+        assert!(matches!(
+            parse("/notice dan,,bob hi", None, &isupport),
+            Err(Error::InvalidTarget(target::ParseError::InvalidTarget(
+                target
+            ))) if target.is_empty()
+        ));
+    }
+
+    #[test]
+    fn topic() {
+        let isupport = HashMap::from([(
+            isupport::Kind::TOPICLEN,
+            isupport::Parameter::TOPICLEN(10),
+        )]);
+
+        assert!(validate_topic("short", &isupport).is_ok());
+        assert!(validate_topic("ten chars!", &isupport).is_ok());
+        assert!(matches!(
+            validate_topic("eleven chars", &isupport),
+            Err(Error::ArgTooLong {
+                len: 12,
+                max_len: 10,
+                ..
+            })
+        ));
+        assert!(validate_topic("eleven chars", &HashMap::new()).is_ok());
+        assert!(matches!(
+            parse("/topic #halloy eleven chars", None, &isupport),
+            Err(Error::ArgTooLong { .. })
+        ));
+    }
+
+    #[test]
+    fn topic_history() {
+        let isupport = HashMap::new();
+        let buffer = Upstream::Channel(
+            "libera".into(),
+            target::Channel::from_str("#halloy", isupport::CaseMap::default()),
+        );
+
+        assert!(matches!(
+            parse("/topic -history", Some(&buffer), &isupport),
+            Ok(Command::Internal(Internal::TopicHistory(_, 5)))
+        ));
+        assert!(matches!(
+            parse("/topic -history 10", Some(&buffer), &isupport),
+            Ok(Command::Internal(Internal::TopicHistory(_, 10)))
+        ));
+        assert!(matches!(
+            parse("/topic -history 0", Some(&buffer), &isupport),
+            Err(Error::NotPositiveInteger)
+        ));
+        // Only in channels
+        assert!(matches!(
+            parse("/topic -history", None, &isupport),
+            Ok(Command::Irc(Irc::Unknown(..)))
+        ));
+    }
+
+    #[test]
+    fn ban_list() {
+        let isupport = HashMap::new();
+        let buffer = Upstream::Channel(
+            "libera".into(),
+            target::Channel::from_str("#halloy", isupport::CaseMap::default()),
+        );
+
+        assert!(matches!(
+            parse("/banlist", Some(&buffer), &isupport),
+            Ok(Command::Internal(Internal::BanList(channel)))
+                if channel.as_str() == "#halloy"
+        ));
+        assert!(matches!(
+            parse("/banlist #halloy", Some(&buffer), &isupport),
+            Err(Error::IncorrectArgCount { .. })
+        ));
+        // Only in channels
+        assert!(matches!(
+            parse("/banlist", None, &isupport),
+            Ok(Command::Irc(Irc::Unknown(..)))
+        ));
+    }
+
+    #[test]
+    fn schedule() {
+        let isupport = HashMap::new();
+        let buffer = Upstream::Channel(
+            "libera".into(),
+            target::Channel::from_str("#halloy", isupport::CaseMap::default()),
+        );
+
+        assert!(matches!(
+            parse("/in 1h30m see you  all", Some(&buffer), &isupport),
+            Ok(Command::Internal(Internal::Schedule(send_at, text)))
+                if text == "see you all"
+                    && send_at > Utc::now() + chrono::TimeDelta::minutes(89)
+        ));
+        assert!(matches!(
+            parse("/schedule 2999-05-01T09:00Z hello", Some(&buffer), &isupport),
+            Ok(Command::Internal(Internal::Schedule(_, text))) if text == "hello"
+        ));
+        assert!(matches!(
+            parse(
+                "/schedule 2000-05-01T09:00Z hello",
+                Some(&buffer),
+                &isupport
+            ),
+            Err(Error::InvalidScheduleTime(
+                scheduled::TimeError::InPast { .. }
+            ))
+        ));
+        assert!(matches!(
+            parse("/in soon hello", Some(&buffer), &isupport),
+            Err(Error::InvalidScheduleTime(
+                scheduled::TimeError::InvalidDelay(_)
+            ))
+        ));
+        assert!(matches!(
+            parse("/in 2h", Some(&buffer), &isupport),
+            Err(Error::IncorrectArgCount { .. })
+        ));
+        // Only in channels and queries
+        assert!(matches!(
+            parse("/in 2h hello", None, &isupport),
+            Ok(Command::Irc(Irc::Unknown(..)))
+        ));
+    }
+
+    #[test]
+    fn layout() {
+        let isupport = HashMap::new();
+
+        assert!(matches!(
+            parse("/layout save work  stuff", None, &isupport),
+            Ok(Command::Internal(Internal::Layout(Layout::Save(name))))
+                if name == "work stuff"
+        ));
+        assert!(matches!(
+            parse("/layout LOAD social", None, &isupport),
+            Ok(Command::Internal(Internal::Layout(Layout::Load(name))))
+                if name == "social"
+        ));
+        assert!(matches!(
+            parse("/layout delete social", None, &isupport),
+            Ok(Command::Internal(Internal::Layout(Layout::Delete(_))))
+        ));
+        assert!(matches!(
+            parse("/layout rename social", None, &isupport),
+            Err(Error::InvalidLayoutAction(action)) if action == "rename"
+        ));
+        assert!(matches!(
+            parse("/layout save", None, &isupport),
+            Err(Error::IncorrectArgCount { .. })
+        ));
+    }
+}
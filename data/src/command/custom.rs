@@ -0,0 +1,487 @@
+//! Custom commands from `buffer.commands.custom`, which expand into one or
+//! more lines of input that are then parsed like any other input.
+//!
+//! Templates substitute `{1}`, `{2}`, .. with the arguments given, `{2-}`
+//! with the arguments from the second on, and `{channel}`, `{query}`,
+//! `{target}`, `{nick}` and `{server}` with the context they're run in.
+//! `{if channel}..{else}..{end}` includes either part depending on a
+//! condition, `{error ..}` refuses to run the command and `{{` is a literal
+//! brace.
+use std::mem;
+
+use super::Kind;
+use crate::config::buffer::Commands;
+
+/// Custom commands may run other custom commands, but only this deep
+pub const MAX_DEPTH: usize = 8;
+
+/// What a custom command is run in
+#[derive(Debug, Clone, Copy)]
+pub struct Context<'a> {
+    pub server: &'a str,
+    pub channel: Option<&'a str>,
+    pub query: Option<&'a str>,
+    /// Our nickname, once connected
+    pub nick: Option<&'a str>,
+}
+
+/// Lines `input` expands to, or `None` when it doesn't run a custom
+/// command. Built-in commands can't be replaced.
+pub fn expand(
+    commands: &Commands,
+    input: &str,
+    context: Context<'_>,
+) -> Result<Option<Vec<String>>, Error> {
+    expand_at_depth(commands, input, context, 0)
+}
+
+fn expand_at_depth(
+    commands: &Commands,
+    input: &str,
+    context: Context<'_>,
+    depth: usize,
+) -> Result<Option<Vec<String>>, Error> {
+    let Some(rest) = input.strip_prefix('/') else {
+        return Ok(None);
+    };
+
+    let mut split = rest.split_ascii_whitespace();
+
+    let Some(name) = split.next() else {
+        return Ok(None);
+    };
+
+    if name.parse::<Kind>().is_ok() {
+        return Ok(None);
+    }
+
+    let Some((name, command)) = commands
+        .custom
+        .iter()
+        .find(|(custom, _)| custom.eq_ignore_ascii_case(name))
+    else {
+        return Ok(None);
+    };
+
+    if depth == MAX_DEPTH {
+        return Err(Error::TooDeep(name.clone()));
+    }
+
+    let args = split.collect::<Vec<_>>();
+    let mut lines = vec![];
+
+    for template in &command.0 {
+        let mut line = String::new();
+
+        render(&parse(template)?, &args, context, &mut line)?;
+
+        // Lines left empty by a condition aren't sent
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match expand_at_depth(commands, &line, context, depth + 1)? {
+            Some(expanded) => lines.extend(expanded),
+            None => lines.push(line),
+        }
+    }
+
+    Ok(Some(lines))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Node {
+    Text(String),
+    /// Argument at a 1-based position
+    Arg(usize),
+    /// Arguments from a 1-based position on
+    ArgsFrom(usize),
+    Variable(Variable),
+    Error(String),
+    If {
+        condition: Condition,
+        negated: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Variable {
+    Channel,
+    Query,
+    Target,
+    Nick,
+    Server,
+}
+
+impl Variable {
+    fn name(self) -> &'static str {
+        match self {
+            Variable::Channel => "channel",
+            Variable::Query => "query",
+            Variable::Target => "target",
+            Variable::Nick => "nick",
+            Variable::Server => "server",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Condition {
+    Channel,
+    Query,
+    /// An argument is given at a 1-based position
+    Arg(usize),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Token {
+    Text(String),
+    /// Contents of `{..}`
+    Tag(String),
+}
+
+/// How a run of nodes ended
+enum Closing {
+    Eof,
+    Else,
+    End,
+}
+
+fn parse(template: &str) -> Result<Vec<Node>, Error> {
+    let invalid = |reason: &str| Error::Template {
+        template: template.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let mut tokens = tokenize(template).ok_or_else(|| invalid("unclosed {"))?;
+
+    match parse_nodes(&mut tokens).map_err(invalid)? {
+        (nodes, Closing::Eof) => Ok(nodes),
+        (_, Closing::Else) => Err(invalid("{else} outside of {if}")),
+        (_, Closing::End) => Err(invalid("{end} outside of {if}")),
+    }
+}
+
+fn tokenize(template: &str) -> Option<std::vec::IntoIter<Token>> {
+    let mut tokens = vec![];
+    let mut text = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => text.push('{'),
+            '{' => {
+                let mut tag = String::new();
+
+                loop {
+                    match chars.next()? {
+                        '}' => break,
+                        c => tag.push(c),
+                    }
+                }
+
+                if !text.is_empty() {
+                    tokens.push(Token::Text(mem::take(&mut text)));
+                }
+
+                tokens.push(Token::Tag(tag));
+            }
+            c => text.push(c),
+        }
+    }
+
+    if !text.is_empty() {
+        tokens.push(Token::Text(text));
+    }
+
+    Some(tokens.into_iter())
+}
+
+fn parse_nodes(
+    tokens: &mut impl Iterator<Item = Token>,
+) -> Result<(Vec<Node>, Closing), &'static str> {
+    let mut nodes = vec![];
+
+    while let Some(token) = tokens.next() {
+        let tag = match token {
+            Token::Text(text) => {
+                nodes.push(Node::Text(text));
+                continue;
+            }
+            Token::Tag(tag) => tag,
+        };
+
+        match tag.trim().split_once(' ') {
+            Some(("if", condition)) => {
+                let condition = condition.trim();
+                let (negated, condition) = match condition.strip_prefix('!') {
+                    Some(condition) => (true, condition.trim()),
+                    None => (false, condition),
+                };
+
+                let condition = match condition {
+                    "channel" => Condition::Channel,
+                    "query" => Condition::Query,
+                    _ => Condition::Arg(
+                        position(condition).ok_or("unknown condition")?,
+                    ),
+                };
+
+                let (then, otherwise) = match parse_nodes(tokens)? {
+                    (then, Closing::End) => (then, vec![]),
+                    (then, Closing::Else) => match parse_nodes(tokens)? {
+                        (otherwise, Closing::End) => (then, otherwise),
+                        (_, Closing::Else) => return Err("repeated {else}"),
+                        (_, Closing::Eof) => return Err("{if} without {end}"),
+                    },
+                    (_, Closing::Eof) => return Err("{if} without {end}"),
+                };
+
+                nodes.push(Node::If {
+                    condition,
+                    negated,
+                    then,
+                    otherwise,
+                });
+            }
+            Some(("error", message)) => {
+                nodes.push(Node::Error(message.trim().to_string()));
+            }
+            _ => match tag.trim() {
+                "else" => return Ok((nodes, Closing::Else)),
+                "end" => return Ok((nodes, Closing::End)),
+                "channel" => nodes.push(Node::Variable(Variable::Channel)),
+                "query" => nodes.push(Node::Variable(Variable::Query)),
+                "target" => nodes.push(Node::Variable(Variable::Target)),
+                "nick" => nodes.push(Node::Variable(Variable::Nick)),
+                "server" => nodes.push(Node::Variable(Variable::Server)),
+                tag => {
+                    let node = match tag.strip_suffix('-') {
+                        Some(from) => position(from).map(Node::ArgsFrom),
+                        None => position(tag).map(Node::Arg),
+                    };
+
+                    nodes.push(node.ok_or("unknown placeholder")?);
+                }
+            },
+        }
+    }
+
+    Ok((nodes, Closing::Eof))
+}
+
+/// 1-based position of an argument
+fn position(s: &str) -> Option<usize> {
+    s.parse().ok().filter(|position| *position > 0)
+}
+
+fn render(
+    nodes: &[Node],
+    args: &[&str],
+    context: Context<'_>,
+    line: &mut String,
+) -> Result<(), Error> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => line.push_str(text),
+            Node::Arg(position) => {
+                line.push_str(args.get(position - 1).copied().unwrap_or(""));
+            }
+            Node::ArgsFrom(position) => {
+                line.push_str(
+                    &args.get(position - 1..).unwrap_or_default().join(" "),
+                );
+            }
+            Node::Variable(variable) => {
+                let (value, only_in) = match variable {
+                    Variable::Channel => (context.channel, "channel buffers"),
+                    Variable::Query => (context.query, "query buffers"),
+                    Variable::Target => (
+                        context.channel.or(context.query),
+                        "channel and query buffers",
+                    ),
+                    Variable::Nick => (context.nick, "connected servers"),
+                    Variable::Server => (Some(context.server), ""),
+                };
+
+                line.push_str(value.ok_or(Error::Unavailable {
+                    variable: variable.name(),
+                    only_in,
+                })?);
+            }
+            Node::Error(message) => {
+                return Err(Error::Refused(message.clone()));
+            }
+            Node::If {
+                condition,
+                negated,
+                then,
+                otherwise,
+            } => {
+                let holds = match condition {
+                    Condition::Channel => context.channel.is_some(),
+                    Condition::Query => context.query.is_some(),
+                    Condition::Arg(position) => args.len() >= *position,
+                };
+
+                if holds != *negated {
+                    render(then, args, context, line)?;
+                } else {
+                    render(otherwise, args, context, line)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error("invalid template \"{template}\": {reason}")]
+    Template { template: String, reason: String },
+    #[error("{0}")]
+    Refused(String),
+    #[error("{{{variable}}} is only available in {only_in}")]
+    Unavailable {
+        variable: &'static str,
+        only_in: &'static str,
+    },
+    #[error("/{0} runs custom commands more than {MAX_DEPTH} deep")]
+    TooDeep(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::IndexMap;
+
+    use super::*;
+    use crate::config::buffer::CustomCommand;
+
+    const CHANNEL: Context<'static> = Context {
+        server: "libera",
+        channel: Some("#halloy"),
+        query: None,
+        nick: Some("me"),
+    };
+
+    const QUERY: Context<'static> = Context {
+        channel: None,
+        query: Some("friend"),
+        ..CHANNEL
+    };
+
+    fn rendered(template: &str, args: &[&str], context: Context) -> String {
+        let mut line = String::new();
+
+        render(&parse(template).unwrap(), args, context, &mut line).unwrap();
+
+        line
+    }
+
+    fn commands(commands: &[(&str, &[&str])]) -> Commands {
+        Commands {
+            custom: commands
+                .iter()
+                .map(|(name, lines)| {
+                    (
+                        name.to_string(),
+                        CustomCommand(
+                            lines.iter().map(ToString::to_string).collect(),
+                        ),
+                    )
+                })
+                .collect::<IndexMap<_, _>>(),
+            ..Commands::default()
+        }
+    }
+
+    #[test]
+    fn conditionals() {
+        let template =
+            "/msg {if channel}{channel}{else}{1}{end} hi{if 2} {2-}{end}";
+
+        assert_eq!(
+            rendered(template, &["bob", "how", "are", "you"], CHANNEL),
+            "/msg #halloy hi how are you"
+        );
+        assert_eq!(rendered(template, &["bob"], QUERY), "/msg bob hi");
+
+        // Negated and nested
+        let template = "{if !channel}{if 1}{1}{else}none{end}{end}";
+
+        assert_eq!(rendered(template, &[], CHANNEL), "");
+        assert_eq!(rendered(template, &["a"], QUERY), "a");
+        assert_eq!(rendered(template, &[], QUERY), "none");
+
+        assert_eq!(rendered("{{literal}", &[], CHANNEL), "{literal}");
+    }
+
+    #[test]
+    fn invalid_templates() {
+        for template in [
+            "{if channel}unclosed",
+            "{if channel}a{else}b{else}c{end}",
+            "stray{end}",
+            "stray{else}",
+            "{if unknown}{end}",
+            "{0}",
+            "{unknown}",
+            "{1",
+        ] {
+            assert!(
+                matches!(parse(template), Err(Error::Template { .. })),
+                "{template}"
+            );
+        }
+    }
+
+    #[test]
+    fn expansion() {
+        let commands = commands(&[
+            ("cycle", &["/part {channel} {1-}", "/join {channel}"]),
+            (
+                "opme",
+                &[
+                    "{if !channel}{error /opme only works in channels}{end}",
+                    "/msg ChanServ op {channel} {nick}",
+                ],
+            ),
+            ("again", &["/cycle brb", "/me is back"]),
+            ("loop", &["/loop"]),
+            ("join", &["/part {channel}"]),
+        ]);
+
+        assert_eq!(
+            expand(&commands, "/CYCLE see you", CHANNEL).unwrap(),
+            Some(vec![
+                "/part #halloy see you".to_string(),
+                "/join #halloy".to_string(),
+            ])
+        );
+        assert_eq!(
+            expand(&commands, "/again", CHANNEL).unwrap().unwrap().len(),
+            3
+        );
+
+        assert_eq!(
+            expand(&commands, "/opme", QUERY).unwrap_err().to_string(),
+            "/opme only works in channels"
+        );
+        assert_eq!(
+            expand(&commands, "/cycle", QUERY).unwrap_err().to_string(),
+            "{channel} is only available in channel buffers"
+        );
+        assert!(matches!(
+            expand(&commands, "/loop", CHANNEL),
+            Err(Error::TooDeep(_))
+        ));
+
+        // Built-in commands and plain text aren't expanded
+        assert_eq!(expand(&commands, "/join #rust", CHANNEL).unwrap(), None);
+        assert_eq!(expand(&commands, "cycle", CHANNEL).unwrap(), None);
+        assert_eq!(expand(&commands, "/unknown", CHANNEL).unwrap(), None);
+    }
+}
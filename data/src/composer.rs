@@ -0,0 +1,248 @@
+use irc::proto::{self, format};
+use thiserror::Error;
+
+use crate::message;
+
+/// A raw message assembled field by field, used to test protocol
+/// behavior by sending exactly what is composed.
+#[derive(Debug, Clone, Default)]
+pub struct Draft {
+    pub tags: Vec<Tag>,
+    pub command: String,
+    pub params: String,
+    pub trailing: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Tag {
+    pub key: String,
+    pub value: String,
+}
+
+impl Tag {
+    fn proto(&self) -> proto::Tag {
+        proto::Tag {
+            key: self.key.trim().to_string(),
+            value: (!self.value.is_empty()).then(|| self.value.clone()),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), format::TagError> {
+        format::validate_tag(&self.proto())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Size {
+    pub tags: usize,
+    pub message: usize,
+}
+
+impl Draft {
+    pub fn proto(&self) -> proto::Message {
+        let command = self.command.trim().to_uppercase();
+
+        let parameters = self
+            .params
+            .split_whitespace()
+            .map(String::from)
+            .chain(self.trailing.clone())
+            .collect();
+
+        proto::Message {
+            tags: self.tags.iter().map(Tag::proto).collect(),
+            source: None,
+            command: proto::Command::Unknown(command, parameters),
+        }
+    }
+
+    /// The serialized line, exactly as it will be written to the server.
+    pub fn line(&self) -> String {
+        format::message(self.proto())
+    }
+
+    /// Byte count of the tag section and of the remaining message,
+    /// which servers limit separately.
+    pub fn size(&self) -> Size {
+        let line = self.line();
+
+        let tags = if self.tags.is_empty() {
+            0
+        } else {
+            // '@' <tags> <SPACE>
+            format::tags(self.proto().tags).len() + 2
+        };
+
+        Size {
+            tags,
+            message: line.len() - tags,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), Error> {
+        let command = self.command.trim();
+
+        if command.is_empty() {
+            return Err(Error::EmptyCommand);
+        }
+
+        // letter* / 3digit
+        if !(command.chars().all(|c| c.is_ascii_alphabetic())
+            || (command.len() == 3
+                && command.chars().all(|c| c.is_ascii_digit())))
+        {
+            return Err(Error::InvalidCommand(command.to_string()));
+        }
+
+        for tag in &self.tags {
+            tag.validate()?;
+        }
+
+        if let Some(param) = self
+            .params
+            .split_whitespace()
+            .find(|param| param.starts_with(':') || has_line_break(param))
+        {
+            return Err(Error::InvalidParameter(param.to_string()));
+        }
+
+        if let Some(trailing) = self
+            .trailing
+            .as_ref()
+            .filter(|trailing| has_line_break(trailing))
+        {
+            return Err(Error::InvalidParameter(trailing.clone()));
+        }
+
+        let size = self.size();
+
+        if size.tags > format::CLIENT_TAGS_BYTE_LIMIT {
+            return Err(Error::TagsTooLong(size.tags));
+        }
+
+        if size.message > format::BYTE_LIMIT {
+            return Err(Error::MessageTooLong(size.message));
+        }
+
+        Ok(())
+    }
+
+    pub fn encoded(&self) -> Result<message::Encoded, Error> {
+        self.validate()?;
+
+        Ok(message::Encoded::from(self.proto()))
+    }
+}
+
+fn has_line_break(value: &str) -> bool {
+    value.contains(['\0', '\r', '\n'])
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Error {
+    #[error("command is empty")]
+    EmptyCommand,
+    #[error("invalid command {0:?}")]
+    InvalidCommand(String),
+    #[error("invalid parameter {0:?}")]
+    InvalidParameter(String),
+    #[error(transparent)]
+    Tag(#[from] format::TagError),
+    #[error(
+        "tags are {0} bytes, limit is {limit}",
+        limit = format::CLIENT_TAGS_BYTE_LIMIT
+    )]
+    TagsTooLong(usize),
+    #[error("message is {0} bytes, limit is {limit}", limit = format::BYTE_LIMIT)]
+    MessageTooLong(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn draft(
+        tags: &[(&str, &str)],
+        command: &str,
+        params: &str,
+        trailing: Option<&str>,
+    ) -> Draft {
+        Draft {
+            tags: tags
+                .iter()
+                .map(|(key, value)| Tag {
+                    key: key.to_string(),
+                    value: value.to_string(),
+                })
+                .collect(),
+            command: command.to_string(),
+            params: params.to_string(),
+            trailing: trailing.map(String::from),
+        }
+    }
+
+    #[test]
+    fn line() {
+        let tests = [
+            (
+                draft(&[("+typing", "active")], "tagmsg", "#halloy", None),
+                "@+typing=active TAGMSG #halloy\r\n",
+            ),
+            (
+                draft(
+                    &[("+draft/reply", "abc"), ("+example.com/flag", "")],
+                    "PRIVMSG",
+                    "#halloy",
+                    Some("hello there"),
+                ),
+                "@+draft/reply=abc;+example.com/flag PRIVMSG #halloy :hello there\r\n",
+            ),
+            (draft(&[], "NOTICE", "nick", Some("")), "NOTICE nick :\r\n"),
+        ];
+
+        for (draft, expected) in tests {
+            assert_eq!(draft.validate(), Ok(()));
+            assert_eq!(draft.line(), expected);
+        }
+    }
+
+    #[test]
+    fn size() {
+        let draft = draft(&[("+typing", "active")], "TAGMSG", "#halloy", None);
+
+        assert_eq!(
+            draft.size(),
+            Size {
+                tags: "@+typing=active ".len(),
+                message: "TAGMSG #halloy\r\n".len(),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let tests = [
+            (draft(&[], " ", "", None), Error::EmptyCommand),
+            (
+                draft(&[], "PRIV MSG", "", None),
+                Error::InvalidCommand("PRIV MSG".to_string()),
+            ),
+            (
+                draft(&[("bad_key", "")], "TAGMSG", "#halloy", None),
+                Error::Tag(format::TagError::InvalidKey('_')),
+            ),
+            (
+                draft(&[], "PRIVMSG", ":#halloy", Some("hi")),
+                Error::InvalidParameter(":#halloy".to_string()),
+            ),
+            (
+                draft(&[], "PRIVMSG", "#halloy", Some("hi\r\nQUIT")),
+                Error::InvalidParameter("hi\r\nQUIT".to_string()),
+            ),
+        ];
+
+        for (draft, error) in tests {
+            assert_eq!(draft.validate(), Err(error));
+        }
+    }
+}
@@ -1,6 +1,7 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::{str, string};
 
+use chrono::{DateTime, Local};
 use iced_core::font;
 use indexmap::IndexMap;
 use rand::prelude::*;
@@ -11,36 +12,50 @@ use tokio_stream::StreamExt;
 use tokio_stream::wrappers::ReadDirStream;
 
 pub use self::actions::Actions;
+pub use self::background::Background;
 pub use self::buffer::Buffer;
 pub use self::ctcp::Ctcp;
+pub use self::developer::Developer;
 pub use self::file_transfer::FileTransfer;
 pub use self::highlights::Highlights;
+pub use self::history::History;
+pub use self::http::Http;
 pub use self::keys::Keyboard;
+pub use self::matching::Matching;
 pub use self::notification::Notifications;
 pub use self::pane::Pane;
 pub use self::preview::Preview;
 pub use self::proxy::Proxy;
 pub use self::server::Server;
 pub use self::sidebar::Sidebar;
-use crate::appearance::theme::Colors;
-use crate::appearance::{self, Appearance};
+pub use self::startup::Startup;
+pub use self::translation::Translation;
+use crate::appearance::{self, Appearance, theme};
 use crate::audio::{self, Sound};
 use crate::environment::config_dir;
 use crate::server::{Map as ServerMap, Server as ServerName};
 use crate::{Theme, environment};
 
 pub mod actions;
+pub mod background;
 pub mod buffer;
+pub mod check;
 pub mod ctcp;
+pub mod developer;
 pub mod file_transfer;
 pub mod highlights;
+pub mod history;
+pub mod http;
 pub mod keys;
+pub mod matching;
 pub mod notification;
 pub mod pane;
 pub mod preview;
 pub mod proxy;
 pub mod server;
 pub mod sidebar;
+pub mod startup;
+pub mod translation;
 
 const CONFIG_TEMPLATE: &str = include_str!("../../config.toml");
 const DEFAULT_THEME_NAME: &str = "ferra";
@@ -60,9 +75,16 @@ pub struct Config {
     pub file_transfer: FileTransfer,
     pub tooltips: bool,
     pub preview: Preview,
+    pub http: Http,
+    pub background: Background,
     pub highlights: Highlights,
+    pub matching: Matching,
+    pub history: History,
     pub actions: Actions,
     pub ctcp: Ctcp,
+    pub translation: Translation,
+    pub startup: Startup,
+    pub developer: Developer,
 }
 
 #[derive(Debug, Clone, Copy, Deserialize)]
@@ -185,12 +207,61 @@ impl Config {
     }
 
     pub fn path() -> PathBuf {
-        Self::config_dir().join(environment::CONFIG_FILE_NAME)
+        Self::config_dir()
+            .join(environment::config_file_name(environment::profile()))
+    }
+
+    /// Notification level of a channel or query buffer, from the schedules of
+    /// its tags
+    pub fn notification_level(
+        &self,
+        target: &str,
+        now: DateTime<Local>,
+    ) -> notification::Level {
+        self.notifications.level(self.buffer.tags(target), now)
+    }
+
+    /// How much notifications about a channel or query buffer reveal
+    pub fn notification_privacy(&self, target: &str) -> notification::Privacy {
+        self.notifications.privacy_of(self.buffer.tags(target))
     }
 
     pub async fn load() -> Result<Self, Error> {
         use tokio::fs;
 
+        let path = Self::path();
+        if !path.try_exists()? {
+            return Err(Error::ConfigMissing {
+                has_yaml_config: has_yaml_config()?,
+            });
+        }
+        let content = fs::read_to_string(&path)
+            .await
+            .map_err(|e| Error::LoadConfigFile(e.to_string()))?;
+
+        let (config, missing_themes) =
+            Self::parse(&content, &Self::themes_dir()).await?;
+
+        for name in missing_themes {
+            log::warn!("theme {name} not found, using the default");
+        }
+
+        // Kept to start with if a later edit breaks the config
+        if let Err(error) =
+            fs::write(environment::last_good_path(&path), &content).await
+        {
+            log::warn!("failed to keep last known good config: {error}");
+        }
+
+        Ok(config)
+    }
+
+    /// Config from the contents of a config file, along with the themes it
+    /// names which aren't in `themes_dir` and fall back to the default
+    pub async fn parse(
+        content: &str,
+        themes_dir: &Path,
+    ) -> Result<(Self, Vec<String>), Error> {
         #[derive(Deserialize, Debug)]
         #[serde(untagged)]
         pub enum ThemeKeys {
@@ -240,23 +311,27 @@ impl Config {
             #[serde(default)]
             pub preview: Preview,
             #[serde(default)]
+            pub http: Http,
+            #[serde(default)]
+            pub background: Background,
+            #[serde(default)]
             pub highlights: Highlights,
             #[serde(default)]
+            pub matching: Matching,
+            #[serde(default)]
+            pub history: History,
+            #[serde(default)]
             pub actions: Actions,
             #[serde(default)]
             pub ctcp: Ctcp,
+            #[serde(default)]
+            pub translation: Translation,
+            #[serde(default)]
+            pub startup: Startup,
+            #[serde(default)]
+            pub developer: Developer,
         }
 
-        let path = Self::path();
-        if !path.try_exists()? {
-            return Err(Error::ConfigMissing {
-                has_yaml_config: has_yaml_config()?,
-            });
-        }
-        let content = fs::read_to_string(path)
-            .await
-            .map_err(|e| Error::LoadConfigFile(e.to_string()))?;
-
         let Configuration {
             theme,
             mut servers,
@@ -270,12 +345,18 @@ impl Config {
             file_transfer,
             tooltips,
             preview,
+            http,
+            background,
             pane,
             highlights,
+            matching,
+            history,
             actions,
             ctcp,
-        } = toml::from_str(content.as_ref())
-            .map_err(|e| Error::Parse(e.to_string()))?;
+            translation,
+            startup,
+            developer,
+        } = toml::from_str(content).map_err(|e| Error::Parse(e.to_string()))?;
 
         match sidebar.order_by {
             sidebar::OrderBy::Alpha => servers.sort_keys(),
@@ -286,11 +367,14 @@ impl Config {
 
         let loaded_notifications = notifications.load_sounds()?;
 
-        let appearance = Self::load_appearance(theme.keys())
-            .await
-            .unwrap_or_default();
+        let highlights = highlights.compile(matching)?;
+
+        keyboard.validate()?;
 
-        Ok(Config {
+        let (appearance, missing_themes) =
+            Self::load_appearance(theme.keys(), themes_dir).await;
+
+        let config = Config {
             appearance,
             servers,
             font,
@@ -303,72 +387,82 @@ impl Config {
             file_transfer,
             tooltips,
             preview,
+            http,
+            background,
             pane,
             highlights,
+            matching,
+            history,
             actions,
             ctcp,
-        })
+            translation,
+            startup,
+            developer,
+        };
+
+        Ok((config, missing_themes))
     }
 
+    /// Themes of `themes_dir` with those named by `theme_keys` selected, and
+    /// the names which weren't found
     async fn load_appearance(
         theme_keys: (&str, Option<&str>),
-    ) -> Result<Appearance, Error> {
+        themes_dir: &Path,
+    ) -> (Appearance, Vec<String>) {
         use tokio::fs;
 
-        #[derive(Deserialize)]
-        #[serde(untagged)]
-        pub enum Data {
-            V1 {
-                #[serde(rename = "name")]
-                _name: String,
-            },
-            V2(Colors),
-        }
-
         let read_entry = |entry: fs::DirEntry| async move {
             let content = fs::read_to_string(entry.path()).await.ok()?;
 
-            let data: Data = toml::from_str(content.as_ref()).ok()?;
+            let colors = theme::parse(&content).ok()?;
             let name = entry.path().file_stem()?.to_string_lossy().to_string();
 
-            match data {
-                Data::V1 { .. } => None,
-                Data::V2(colors) => Some(Theme::new(name, colors)),
-            }
+            Some(Theme::new(name, colors))
         };
 
         let mut all = vec![];
         let mut first_theme = Theme::default();
         let mut second_theme = theme_keys.1.map(|_| Theme::default());
         let mut has_halloy_theme = false;
-
-        let mut stream =
-            ReadDirStream::new(fs::read_dir(Self::themes_dir()).await?);
-        while let Some(entry) = stream.next().await {
-            let Ok(entry) = entry else {
-                continue;
-            };
-
-            let Some(file_name) = entry.file_name().to_str().map(String::from)
-            else {
-                continue;
-            };
-
-            if let Some(file_name) = file_name.strip_suffix(".toml") {
-                if let Some(theme) = read_entry(entry).await {
-                    if file_name == theme_keys.0 {
-                        first_theme = theme.clone();
-                    }
-
-                    if Some(file_name) == theme_keys.1 {
-                        second_theme = Some(theme.clone());
-                    }
-
-                    if file_name.to_lowercase() == DEFAULT_THEME_NAME {
-                        has_halloy_theme = true;
+        // The bundled default is always found
+        let is_default = |name: &str| {
+            name.is_empty() || name.to_lowercase() == DEFAULT_THEME_NAME
+        };
+        let mut first_found = is_default(theme_keys.0);
+        let mut second_found = theme_keys.1.is_none_or(is_default);
+
+        // Without a themes directory only the bundled default is found
+        if let Ok(entries) = fs::read_dir(themes_dir).await {
+            let mut stream = ReadDirStream::new(entries);
+            while let Some(entry) = stream.next().await {
+                let Ok(entry) = entry else {
+                    continue;
+                };
+
+                let Some(file_name) =
+                    entry.file_name().to_str().map(String::from)
+                else {
+                    continue;
+                };
+
+                if let Some(file_name) = file_name.strip_suffix(".toml") {
+                    if let Some(theme) = read_entry(entry).await {
+                        if file_name == theme_keys.0 {
+                            first_theme = theme.clone();
+                            first_found = true;
+                        }
+
+                        if Some(file_name) == theme_keys.1 {
+                            second_theme = Some(theme.clone());
+                            second_found = true;
+                        }
+
+                        if file_name.to_lowercase() == DEFAULT_THEME_NAME {
+                            has_halloy_theme = true;
+                        }
+
+                        all.push(theme);
                     }
-
-                    all.push(theme);
                 }
             }
         }
@@ -383,7 +477,111 @@ impl Config {
             appearance::Selected::specific(first_theme)
         };
 
-        Ok(Appearance { selected, all })
+        let missing = [
+            (!first_found).then_some(theme_keys.0),
+            if second_found { None } else { theme_keys.1 },
+        ]
+        .into_iter()
+        .flatten()
+        .map(String::from)
+        .collect();
+
+        (Appearance { selected, all }, missing)
+    }
+
+    /// Loads the theme `name` from the themes directory, or the bundled
+    /// default if there's no file for it
+    pub async fn load_theme(name: String) -> Result<Theme, theme::Error> {
+        if name.contains(['/', '\\']) {
+            return Err(theme::Error::NotFound(name));
+        }
+
+        let path = Self::themes_dir().join(format!("{name}.toml"));
+
+        match tokio::fs::read_to_string(path).await {
+            Ok(content) => Ok(Theme::new(name, theme::parse(&content)?)),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                if name.eq_ignore_ascii_case(DEFAULT_THEME_NAME) {
+                    Ok(Theme::default())
+                } else {
+                    Err(theme::Error::NotFound(name))
+                }
+            }
+            Err(error) => Err(theme::Error::Read(error)),
+        }
+    }
+
+    /// Previews of the themes in the themes directory and the bundled
+    /// default, sorted by name. Themes which can't be read are returned
+    /// with their error.
+    pub async fn preview_themes()
+    -> Vec<Result<theme::Preview, (String, theme::Error)>> {
+        let mut previews = vec![];
+
+        if let Ok(entries) = tokio::fs::read_dir(Self::themes_dir()).await {
+            let mut stream = ReadDirStream::new(entries);
+
+            while let Some(Ok(entry)) = stream.next().await {
+                let path = entry.path();
+
+                let Some(name) = path
+                    .extension()
+                    .is_some_and(|extension| extension == "toml")
+                    .then(|| path.file_stem())
+                    .flatten()
+                    .map(|name| name.to_string_lossy().to_string())
+                else {
+                    continue;
+                };
+
+                previews.push(
+                    match tokio::fs::read_to_string(&path).await {
+                        Ok(content) => theme::peek(name.clone(), &content),
+                        Err(error) => Err(theme::Error::Read(error)),
+                    }
+                    .map_err(|error| (name, error)),
+                );
+            }
+        }
+
+        let name =
+            |preview: &Result<theme::Preview, (String, theme::Error)>| {
+                match preview {
+                    Ok(preview) => preview.name.to_lowercase(),
+                    Err((name, _)) => name.to_lowercase(),
+                }
+            };
+
+        if !previews
+            .iter()
+            .any(|preview| name(preview) == DEFAULT_THEME_NAME)
+        {
+            previews.push(Ok(theme::Preview::default_theme()));
+        }
+
+        previews.sort_by_key(name);
+
+        previews
+    }
+
+    /// Sets `theme` in the config file to `name`, replacing any light and
+    /// dark themes and keeping the rest of the file as is
+    pub async fn save_theme(name: String) -> Result<(), Error> {
+        let path = Self::path();
+
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| Error::LoadConfigFile(e.to_string()))?;
+
+        let mut document = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| Error::Parse(e.to_string()))?;
+
+        document["theme"] = toml_edit::value(name);
+
+        tokio::fs::write(path, document.to_string()).await?;
+
+        Ok(())
     }
 
     pub fn create_initial_config() {
@@ -393,18 +591,49 @@ impl Config {
             return;
         }
 
+        let _ = Self::write_initial_config(&config_file);
+    }
+
+    /// Whether a config from the last time it loaded was kept
+    pub fn has_last_good() -> bool {
+        environment::last_good_path(&Self::path()).is_file()
+    }
+
+    /// Sets the config aside with a `.broken` suffix and loads the last known
+    /// good one in its place
+    pub async fn restore_last_good() -> Result<Self, Error> {
+        use tokio::fs;
+
+        let path = Self::path();
+        let last_good = environment::last_good_path(&path);
+
+        if !last_good.is_file() {
+            return Err(Error::LastGoodMissing);
+        }
+
+        if path.try_exists()? {
+            fs::rename(&path, environment::broken_path(&path)).await?;
+        }
+
+        fs::copy(&last_good, &path).await?;
+
+        Self::load().await
+    }
+
+    /// Writes the commented config template to `path`, with a unique nick.
+    pub fn write_initial_config(path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
         // Generate a unique nick
         let rand_nick = random_nickname();
 
         // Replace placeholder nick with unique nick
         let config_string =
             CONFIG_TEMPLATE.replace("__NICKNAME__", rand_nick.as_str());
-        let config_bytes = config_string.as_bytes();
 
-        // Create configuration path.
-        let config_path = Self::config_dir().join("config.toml");
-
-        let _ = std::fs::write(config_path, config_bytes);
+        std::fs::write(path, config_string)
     }
 }
 
@@ -457,6 +686,24 @@ pub enum Error {
         "Exactly one of sasl.plain.password, sasl.plain.password_file or sasl.plain.password_command must be set."
     )]
     DuplicateSaslPassword,
+    #[error("path must be set when connection is unix.")]
+    MissingUnixSocketPath,
+    #[error("Unix domain sockets aren't supported on this platform.")]
+    UnixSocketUnsupported,
+    #[error("invalid highlight regex '{regex}': {error}")]
+    InvalidHighlightRegex { regex: String, error: String },
+    #[error(
+        "keyboard shortcut {key_bind} is set for both {first} and {second}"
+    )]
+    KeyboardConflict {
+        key_bind: String,
+        first: String,
+        second: String,
+    },
+    #[error("theme {0} was not found in the themes directory")]
+    ThemeNotFound(String),
+    #[error("no config was kept from the last time it loaded")]
+    LastGoodMissing,
     #[error("Config does not exist")]
     ConfigMissing { has_yaml_config: bool },
 }
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Local, Utc};
-use serde::Deserialize;
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer};
 
 pub use self::away::Away;
 pub use self::channel::Channel;
@@ -12,7 +13,9 @@ use crate::buffer::{
     DateSeparators, Nickname, SkinTone, StatusMessagePrefix, TextInput,
     Timestamp,
 };
-use crate::message::source;
+use crate::message::{Message, Source, source};
+use crate::scheduled::WhenDisconnected;
+use crate::time;
 
 #[derive(Debug, Default, Clone, Deserialize)]
 pub struct Buffer {
@@ -44,6 +47,28 @@ pub struct Buffer {
     pub mark_as_read: MarkAsRead,
     #[serde(default)]
     pub url: Url,
+    #[serde(default)]
+    pub copy_message: CopyMessage,
+    #[serde(default)]
+    pub scrollbar_markers: ScrollbarMarkers,
+    /// Tags of channel and query buffers, keyed by channel or nickname
+    #[serde(default)]
+    pub tags: IndexMap<String, Vec<String>>,
+    /// Color message rows with the theme's `buffer.row_background`
+    #[serde(default)]
+    pub row_styling: bool,
+}
+
+impl Buffer {
+    pub fn tags<'a>(
+        &'a self,
+        target: &'a str,
+    ) -> impl Iterator<Item = &'a str> {
+        self.tags
+            .iter()
+            .filter(move |(buffer, _)| buffer.eq_ignore_ascii_case(target))
+            .flat_map(|(_, tags)| tags.iter().map(String::as_str))
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -84,6 +109,39 @@ pub struct Url {
     pub prompt_before_open: bool,
 }
 
+/// What "Copy message" in the context menu of a message copies besides its
+/// text, and how timestamps in copied or quoted text are written
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct CopyMessage {
+    #[serde(default)]
+    pub include_timestamp: bool,
+    #[serde(default)]
+    pub include_nickname: bool,
+    /// Zone the timestamps are written in, followed by it. Local time
+    /// without a zone when unset
+    #[serde(default)]
+    pub timestamp_zone: Option<time::Zone>,
+    /// Start the quote sent before a reply with the time of the message
+    #[serde(default)]
+    pub include_timestamp_in_quote: bool,
+}
+
+/// Marks on the scrollbar of a buffer for its highlights, the backlog
+/// divider and the unread messages
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScrollbarMarkers {
+    #[serde(default = "default_bool_true")]
+    pub enabled: bool,
+}
+
+impl Default for ScrollbarMarkers {
+    fn default() -> Self {
+        Self {
+            enabled: default_bool_true(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct MarkAsRead {
     #[serde(default)]
@@ -111,13 +169,51 @@ impl Default for MarkAsRead {
 pub struct Commands {
     #[serde(default = "default_bool_true")]
     pub show_description: bool,
+    /// Commands by name, expanding to the lines of input in their templates
+    #[serde(default)]
+    pub custom: IndexMap<String, CustomCommand>,
+    #[serde(default)]
+    pub schedule: Schedule,
 }
 
 impl Default for Commands {
     fn default() -> Self {
         Self {
             show_description: default_bool_true(),
+            custom: IndexMap::default(),
+            schedule: Schedule::default(),
+        }
+    }
+}
+
+/// Messages sent later with `/schedule` and `/in`
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct Schedule {
+    #[serde(default)]
+    pub when_disconnected: WhenDisconnected,
+}
+
+/// Templates of the lines a custom command expands to, given as a single
+/// string or an array of them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CustomCommand(pub Vec<String>);
+
+impl<'de> Deserialize<'de> for CustomCommand {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum CustomCommandRepr {
+            Line(String),
+            Lines(Vec<String>),
         }
+
+        Ok(match CustomCommandRepr::deserialize(deserializer)? {
+            CustomCommandRepr::Line(line) => CustomCommand(vec![line]),
+            CustomCommandRepr::Lines(lines) => CustomCommand(lines),
+        })
     }
 }
 
@@ -134,6 +230,8 @@ pub struct ServerMessages {
     #[serde(default)]
     pub change_host: ServerMessage,
     #[serde(default)]
+    pub change_nick: ServerMessage,
+    #[serde(default)]
     pub monitored_online: ServerMessage,
     #[serde(default)]
     pub monitored_offline: ServerMessage,
@@ -145,6 +243,19 @@ pub struct ServerMessages {
     pub standard_reply_note: ServerMessage,
     #[serde(default)]
     pub wallops: ServerMessage,
+    #[serde(default)]
+    pub ctcp: ServerMessage,
+    #[serde(default)]
+    pub bouncer: ServerMessage,
+    #[serde(default)]
+    pub notice: ServerMessage,
+    /// Fold runs of joins, parts, quits and nick changes in channels into a
+    /// single line
+    #[serde(default)]
+    pub collapse: bool,
+    /// Seconds since a user last spoke for their events to stay unfolded
+    #[serde(default)]
+    pub collapse_exempt_active: Option<i64>,
 }
 
 impl ServerMessages {
@@ -155,6 +266,8 @@ impl ServerMessages {
             source::server::Kind::Part => Some(&self.part),
             source::server::Kind::Quit => Some(&self.quit),
             source::server::Kind::ChangeHost => Some(&self.change_host),
+            source::server::Kind::ChangeNick => Some(&self.change_nick),
+            source::server::Kind::ChangeMode => None,
             source::server::Kind::MonitoredOnline => {
                 Some(&self.monitored_online)
             }
@@ -171,6 +284,9 @@ impl ServerMessages {
                 source::server::StandardReply::Note,
             ) => Some(&self.standard_reply_note),
             source::server::Kind::Wallops => Some(&self.wallops),
+            source::server::Kind::Ctcp => Some(&self.ctcp),
+            source::server::Kind::Bouncer => Some(&self.bouncer),
+            source::server::Kind::Notice => Some(&self.notice),
         }
     }
 }
@@ -298,8 +414,125 @@ impl Buffer {
             )
         ))
     }
+
+    /// Timestamp in text copied or quoted out of a buffer, followed by its
+    /// zone when one is set, e.g. "[12:34 UTC] "
+    pub fn copied_timestamp(
+        &self,
+        date_time: &DateTime<Utc>,
+    ) -> Option<String> {
+        let Some(zone) = self.copy_message.timestamp_zone else {
+            return self.format_timestamp(date_time);
+        };
+
+        if self.timestamp.format.is_empty() {
+            return None;
+        }
+
+        Some(format!(
+            "{} ",
+            self.timestamp
+                .brackets
+                .format(zone.format(date_time, &self.timestamp.format))
+        ))
+    }
+
+    /// The text "Copy message" copies, without formatting
+    pub fn copied_text(&self, message: &Message) -> String {
+        self.format_copied(
+            message,
+            self.copy_message.include_timestamp,
+            self.copy_message.include_nickname,
+        )
+    }
+
+    /// A line of the text copied from messages selected together, e.g.
+    /// "[12:34] <alice> hi"
+    pub fn copied_line(&self, message: &Message) -> String {
+        self.format_copied(message, true, true)
+    }
+
+    fn format_copied(
+        &self,
+        message: &Message,
+        include_timestamp: bool,
+        include_nickname: bool,
+    ) -> String {
+        let timestamp = include_timestamp
+            .then(|| self.copied_timestamp(&message.server_time))
+            .flatten();
+        let nickname = match message.target.source() {
+            Source::User(user) if include_nickname => Some(format!(
+                "{} ",
+                self.nickname.brackets.format(user.nickname())
+            )),
+            _ => None,
+        };
+
+        format!(
+            "{}{}{}",
+            timestamp.unwrap_or_default(),
+            nickname.unwrap_or_default(),
+            message.text()
+        )
+    }
 }
 
 fn default_characters_to_trigger_picker() -> usize {
     2
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isupport::CaseMap;
+    use crate::message::{Target, plain};
+    use crate::user::{Nick, User};
+    use crate::{buffer, target};
+
+    fn config() -> Buffer {
+        let mut config = Buffer::default();
+        config.timestamp.brackets = buffer::Brackets {
+            left: "[".to_string(),
+            right: "]".to_string(),
+        };
+        config.nickname.brackets = buffer::Brackets {
+            left: "<".to_string(),
+            right: ">".to_string(),
+        };
+        config
+    }
+
+    fn message() -> Message {
+        let user = User::from(Nick::from("alice"));
+
+        Message::test(
+            DateTime::from_timestamp(45_240, 0).unwrap(),
+            Target::Query {
+                query: target::Query::from_user(&user, CaseMap::default()),
+                source: Source::User(user),
+            },
+            plain("hi".to_string()),
+        )
+    }
+
+    #[test]
+    fn copied_line_without_zone() {
+        let config = config();
+        let message = message();
+        let time = message.server_time.with_timezone(&Local).format("%R");
+
+        assert_eq!(
+            config.copied_line(&message),
+            format!("[{time}] <alice> hi")
+        );
+    }
+
+    #[test]
+    fn copied_line_with_zone() {
+        let mut config = config();
+        config.copy_message.timestamp_zone = Some(time::Zone::Utc);
+
+        assert_eq!(config.copied_line(&message()), "[12:34 UTC] <alice> hi");
+    }
+}
@@ -13,6 +13,9 @@ pub struct Channel {
     pub topic: Topic,
     #[serde(default)]
     pub message: Message,
+    /// Reason sent when kicking from the user context menu
+    #[serde(default)]
+    pub kick_reason: Option<String>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize)]
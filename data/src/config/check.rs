@@ -0,0 +1,169 @@
+//! Checking a config file without starting Halloy, by loading it the same way
+//! as at startup
+
+use std::fmt;
+use std::path::Path;
+
+use super::{Config, Error};
+
+/// What a config which passed the check sets up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Summary {
+    pub servers: usize,
+    pub themes: usize,
+    pub keybindings: usize,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} servers, {} themes, {} keybindings OK",
+            self.servers, self.themes, self.keybindings
+        )
+    }
+}
+
+/// Checks the config file at `path`. Themes and sounds are looked up where
+/// Halloy looks for them.
+pub async fn file(path: &Path) -> Result<Summary, Vec<Error>> {
+    let text = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|error| vec![Error::LoadConfigFile(error.to_string())])?;
+
+    content(&text, &Config::themes_dir()).await
+}
+
+/// Checks the contents of a config file. An error which keeps the config from
+/// loading ends the check, problems Halloy would start with anyway are all
+/// listed.
+pub async fn content(
+    content: &str,
+    themes_dir: &Path,
+) -> Result<Summary, Vec<Error>> {
+    let (config, missing_themes) = Config::parse(content, themes_dir)
+        .await
+        .map_err(|error| vec![error])?;
+
+    if !missing_themes.is_empty() {
+        return Err(missing_themes
+            .into_iter()
+            .map(Error::ThemeNotFound)
+            .collect());
+    }
+
+    Ok(Summary {
+        servers: config.servers.keys().count(),
+        themes: config.appearance.all.len(),
+        keybindings: config.keyboard.shortcuts().len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CONFIG_TEMPLATE;
+
+    const THEMES_DIR: &str = "no-themes-here";
+
+    const MINIMAL: &str = r#"
+        theme = "ferra"
+
+        [servers.libera]
+        nickname = "halloy"
+        server = "irc.libera.chat"
+
+        [servers.oftc]
+        nickname = "halloy"
+        server = "irc.oftc.net"
+    "#;
+
+    const MISSING_THEMES: &str = r#"
+        theme = { light = "paper", dark = "ink" }
+
+        [servers.libera]
+        nickname = "halloy"
+        server = "irc.libera.chat"
+    "#;
+
+    const INVALID_SYNTAX: &str = r#"
+        [servers.libera
+        nickname = "halloy"
+    "#;
+
+    const INVALID_KEYBIND: &str = r#"
+        [servers.libera]
+        nickname = "halloy"
+        server = "irc.libera.chat"
+
+        [keyboard]
+        move_up = "hyper+up"
+    "#;
+
+    const INVALID_HIGHLIGHT: &str = r#"
+        [servers.libera]
+        nickname = "halloy"
+        server = "irc.libera.chat"
+
+        [highlights]
+        regexes = ["(unclosed"]
+    "#;
+
+    async fn check(content: &str) -> Result<Summary, Vec<Error>> {
+        super::content(content, Path::new(THEMES_DIR)).await
+    }
+
+    #[tokio::test]
+    async fn good() {
+        let template = CONFIG_TEMPLATE.replace("__NICKNAME__", "halloy");
+
+        assert_eq!(
+            check(&template).await.unwrap(),
+            Summary {
+                servers: 1,
+                themes: 1,
+                keybindings: Config::default().keyboard.shortcuts().len(),
+            }
+        );
+
+        let summary = check(MINIMAL).await.unwrap();
+
+        assert_eq!(summary.servers, 2);
+        assert_eq!(
+            summary.to_string(),
+            format!(
+                "2 servers, 1 themes, {} keybindings OK",
+                summary.keybindings
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn bad() {
+        let errors = check(MISSING_THEMES).await.unwrap_err();
+        assert_eq!(
+            errors.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "theme paper was not found in the themes directory",
+                "theme ink was not found in the themes directory",
+            ]
+        );
+
+        // Parse errors point at where in the file they are
+        let errors = check(INVALID_SYNTAX).await.unwrap_err();
+        assert!(
+            matches!(&errors[..], [Error::Parse(error)] if error.contains("line 2"))
+        );
+
+        let errors = check(INVALID_KEYBIND).await.unwrap_err();
+        assert!(
+            matches!(&errors[..], [Error::Parse(error)] if error.contains("move_up"))
+        );
+
+        let errors = check(INVALID_HIGHLIGHT).await.unwrap_err();
+        assert!(matches!(
+            &errors[..],
+            [Error::InvalidHighlightRegex { regex, .. }] if regex == "(unclosed"
+        ));
+    }
+}
@@ -1,9 +1,12 @@
 use serde::Deserialize;
 
+use crate::environment::VERSION;
 use crate::serde::default_bool_true;
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct Ctcp {
+    #[serde(default = "default_bool_true")]
+    pub enabled: bool,
     #[serde(default = "default_bool_true")]
     pub ping: bool,
     #[serde(default = "default_bool_true")]
@@ -12,15 +15,19 @@ pub struct Ctcp {
     pub time: bool,
     #[serde(default = "default_bool_true")]
     pub version: bool,
+    #[serde(default)]
+    pub version_reply: Option<String>,
 }
 
 impl Default for Ctcp {
     fn default() -> Self {
         Self {
+            enabled: default_bool_true(),
             ping: default_bool_true(),
             source: default_bool_true(),
             time: default_bool_true(),
             version: default_bool_true(),
+            version_reply: None,
         }
     }
 }
@@ -47,4 +54,10 @@ impl Ctcp {
 
         commands.join(" ")
     }
+
+    pub fn version_reply(&self) -> String {
+        self.version_reply
+            .clone()
+            .unwrap_or_else(|| format!("Halloy {VERSION}"))
+    }
 }
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Developer {
+    #[serde(default)]
+    pub raw_composer: bool,
+    #[serde(default)]
+    pub debug_overlay: bool,
+}
@@ -16,6 +16,10 @@ pub struct FileTransfer {
     /// Time in seconds to wait before timing out a transfer waiting to be accepted.
     #[serde(default = "default_timeout")]
     pub timeout: u64,
+    /// If true, continue receiving a file which partially exists instead of
+    /// starting over.
+    #[serde(default = "default_resume")]
+    pub resume: bool,
     pub server: Option<Server>,
 }
 
@@ -25,6 +29,7 @@ impl Default for FileTransfer {
             save_directory: None,
             passive: default_passive(),
             timeout: default_timeout(),
+            resume: default_resume(),
             server: None,
         }
     }
@@ -38,6 +43,10 @@ fn default_timeout() -> u64 {
     60 * 5
 }
 
+fn default_resume() -> bool {
+    true
+}
+
 #[derive(Debug, Clone)]
 pub struct Server {
     /// Address advertised to the remote user to connect to
@@ -53,31 +62,106 @@ impl<'de> Deserialize<'de> for Server {
     where
         D: serde::Deserializer<'de>,
     {
+        #[derive(Deserialize)]
+        struct PortRange {
+            start: NonZeroU16,
+            end: NonZeroU16,
+        }
+
         #[derive(Deserialize)]
         struct Data {
             public_address: IpAddr,
             bind_address: IpAddr,
-            bind_port_first: NonZeroU16,
-            bind_port_last: NonZeroU16,
+            #[serde(default)]
+            port_range: Option<PortRange>,
+            #[serde(default)]
+            bind_port_first: Option<NonZeroU16>,
+            #[serde(default)]
+            bind_port_last: Option<NonZeroU16>,
         }
 
         let Data {
             public_address,
             bind_address,
+            port_range,
             bind_port_first,
             bind_port_last,
         } = Data::deserialize(deserializer)?;
 
-        if bind_port_last < bind_port_first {
-            return Err(serde::de::Error::custom(
-                "`bind_port_last` must be greater than or equal to `bind_port_first`",
-            ));
-        }
+        let (first, last) = match (port_range, bind_port_first, bind_port_last)
+        {
+            (Some(PortRange { start, end }), None, None) => {
+                if end < start {
+                    return Err(serde::de::Error::custom(
+                        "`port_range.end` must be greater than or equal to `port_range.start`",
+                    ));
+                }
+
+                (start, end)
+            }
+            (None, Some(first), Some(last)) => {
+                if last < first {
+                    return Err(serde::de::Error::custom(
+                        "`bind_port_last` must be greater than or equal to `bind_port_first`",
+                    ));
+                }
+
+                (first, last)
+            }
+            (Some(_), _, _) => {
+                return Err(serde::de::Error::custom(
+                    "`port_range` can't be set together with `bind_port_first` or `bind_port_last`",
+                ));
+            }
+            (None, _, _) => {
+                return Err(serde::de::Error::custom(
+                    "`port_range` must be set",
+                ));
+            }
+        };
 
         Ok(Server {
             public_address,
             bind_address,
-            bind_ports: bind_port_first.get()..=bind_port_last.get(),
+            bind_ports: first.get()..=last.get(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server(ports: &str) -> Result<Server, toml::de::Error> {
+        toml::from_str(&format!(
+            r#"
+            public_address = "203.0.113.7"
+            bind_address = "0.0.0.0"
+            {ports}
+            "#
+        ))
+    }
+
+    #[test]
+    fn port_range() {
+        let parsed =
+            server("port_range = { start = 5000, end = 5100 }").unwrap();
+        assert_eq!(parsed.bind_ports, 5000..=5100);
+        assert_eq!(parsed.public_address.to_string(), "203.0.113.7");
+
+        // Still accepted as before `port_range`
+        let parsed =
+            server("bind_port_first = 1024\nbind_port_last = 1030").unwrap();
+        assert_eq!(parsed.bind_ports, 1024..=1030);
+
+        for ports in [
+            "port_range = { start = 5100, end = 5000 }",
+            "port_range = { start = 0, end = 5000 }",
+            "port_range = { start = 5000, end = 5100 }\nbind_port_first = 1024",
+            "bind_port_first = 1024",
+            "",
+        ] {
+            assert!(server(ports).is_err(), "{ports}");
+        }
+    }
+}
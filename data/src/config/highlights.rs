@@ -2,20 +2,123 @@ use fancy_regex::{Regex, RegexBuilder};
 use itertools::Itertools;
 use serde::{Deserialize, Deserializer};
 
+use super::{Error, Matching};
+use crate::{Server, fold};
+
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct Highlights {
     #[serde(default)]
     pub nickname: Nickname,
     #[serde(rename = "match", default)]
     pub matches: Vec<Match>,
+    /// Additional nicknames highlighted like our current nickname
+    #[serde(default)]
+    pub nicknames: Vec<String>,
+    /// Highlighted wherever they appear, regardless of case (and accents with
+    /// `matching.accent_insensitive`)
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// Highlighted wherever they match
+    #[serde(default)]
+    pub regexes: Vec<String>,
+    /// Servers in which `words` and `regexes` aren't highlighted
+    #[serde(default)]
+    pub exclude_servers: Vec<String>,
+    /// Channels in which `words` and `regexes` aren't highlighted
+    #[serde(default)]
+    pub exclude_channels: Vec<String>,
+    /// `words` and `regexes`, compiled by [`Highlights::compile`]
+    #[serde(skip)]
+    pub(crate) patterns: Vec<Pattern>,
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+/// A compiled highlight of `words` or `regexes`
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    pub regex: Regex,
+    /// Matched against text folded by [`fold::Folded`], so accents are
+    /// ignored
+    pub is_folded: bool,
+}
+
+impl Highlights {
+    /// Compiles `words` and `regexes`, failing on the first invalid regex.
+    /// `words` are folded when matching is accent insensitive.
+    pub fn compile(mut self, matching: Matching) -> Result<Self, Error> {
+        let is_folded = matching.accent_insensitive;
+
+        let words = (!self.words.is_empty()).then(|| {
+            let words = self
+                .words
+                .iter()
+                .map(|word| {
+                    if is_folded {
+                        fancy_regex::escape(&fold::fold(word)).into_owned()
+                    } else {
+                        fancy_regex::escape(word).into_owned()
+                    }
+                })
+                .join("|");
+
+            (format!("(?i){words}"), is_folded)
+        });
+
+        self.patterns = words
+            .into_iter()
+            .chain(self.regexes.iter().map(|regex| (regex.clone(), false)))
+            .map(|(regex, is_folded)| {
+                RegexBuilder::new(&regex)
+                    .build()
+                    .map(|regex| Pattern { regex, is_folded })
+                    .map_err(|error| Error::InvalidHighlightRegex {
+                        regex,
+                        error: error.to_string(),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(self)
+    }
+
+    /// Compiled `words` and `regexes` which apply to `target` on `server`
+    pub fn patterns<'a>(
+        &'a self,
+        server: &Server,
+        target: &str,
+    ) -> impl Iterator<Item = &'a Pattern> {
+        let is_excluded = self
+            .exclude_servers
+            .iter()
+            .any(|excluded| excluded == server.as_ref())
+            || !is_target_included(&[], &self.exclude_channels, target);
+
+        self.patterns.iter().filter(move |_| !is_excluded)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
 pub struct Nickname {
     #[serde(default)]
     pub exclude: Vec<String>,
     #[serde(default)]
     pub include: Vec<String>,
+    /// Seconds to keep highlighting a nickname after changing from it
+    #[serde(default = "default_grace_period")]
+    pub grace_period: u64,
+}
+
+impl Default for Nickname {
+    fn default() -> Self {
+        Self {
+            exclude: Vec::default(),
+            include: Vec::default(),
+            grace_period: default_grace_period(),
+        }
+    }
+}
+
+fn default_grace_period() -> u64 {
+    300
 }
 
 impl Nickname {
@@ -0,0 +1,20 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct History {
+    /// What happens to messages received for a buffer which was closed
+    #[serde(default)]
+    pub closed_buffers: ClosedBuffers,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ClosedBuffers {
+    /// Dropped
+    Discard,
+    /// Written to the history on disk without reopening the buffer
+    Store,
+    /// Private messages reopen the buffer, anything else is stored
+    #[default]
+    Reopen,
+}
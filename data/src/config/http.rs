@@ -0,0 +1,100 @@
+use serde::{Deserialize, Deserializer};
+
+use crate::config;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Http {
+    /// Proxy requests are made through
+    #[serde(default)]
+    pub proxy: Proxy,
+    /// Number of requests allowed to be in progress at the same time
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+    /// Minimum number of milliseconds between requests to the same host
+    #[serde(default = "default_host_delay_ms")]
+    pub host_delay_ms: u64,
+    /// Max bytes read from a single response before it's cancelled
+    #[serde(default = "default_max_response_size")]
+    pub max_response_size: usize,
+    /// Number of hours fetched content, like previews, is cached for
+    #[serde(default = "default_cache_ttl_hours")]
+    pub cache_ttl_hours: u64,
+}
+
+impl Default for Http {
+    fn default() -> Self {
+        Self {
+            proxy: Proxy::default(),
+            concurrency: default_concurrency(),
+            host_delay_ms: default_host_delay_ms(),
+            max_response_size: default_max_response_size(),
+            cache_ttl_hours: default_cache_ttl_hours(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub enum Proxy {
+    /// The root `proxy` used for IRC connections
+    #[default]
+    Irc,
+    None,
+    Custom(config::Proxy),
+}
+
+impl<'de> Deserialize<'de> for Proxy {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum ProxyRepr {
+            String(String),
+            Custom(config::Proxy),
+        }
+
+        match ProxyRepr::deserialize(deserializer)? {
+            ProxyRepr::String(s) => match s.as_str() {
+                "irc" => Ok(Proxy::Irc),
+                "none" => Ok(Proxy::None),
+                _ => Err(serde::de::Error::custom(format!(
+                    "unknown proxy: {s}",
+                ))),
+            },
+            ProxyRepr::Custom(proxy) => Ok(Proxy::Custom(proxy)),
+        }
+    }
+}
+
+impl Proxy {
+    /// Proxy to use, given the root `proxy` of the config
+    pub fn resolve<'a>(
+        &'a self,
+        irc: Option<&'a config::Proxy>,
+    ) -> Option<&'a config::Proxy> {
+        match self {
+            Proxy::Irc => irc,
+            Proxy::None => None,
+            Proxy::Custom(proxy) => Some(proxy),
+        }
+    }
+}
+
+fn default_concurrency() -> usize {
+    8
+}
+
+fn default_host_delay_ms() -> u64 {
+    100
+}
+
+fn default_max_response_size() -> usize {
+    // 50 MiB
+    50 * 1024 * 1024
+}
+
+fn default_cache_ttl_hours() -> u64 {
+    // 1 week
+    7 * 24
+}
@@ -1,8 +1,10 @@
 use serde::Deserialize;
 
+use crate::config::Error;
 use crate::shortcut::{KeyBind, Shortcut, shortcut};
 
 #[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Keyboard {
     #[serde(default = "KeyBind::move_up")]
     pub move_up: KeyBind,
@@ -18,16 +20,32 @@ pub struct Keyboard {
     pub maximize_buffer: KeyBind,
     #[serde(default = "KeyBind::restore_buffer")]
     pub restore_buffer: KeyBind,
+    #[serde(default = "KeyBind::split_horizontal")]
+    pub split_horizontal: KeyBind,
+    #[serde(default = "KeyBind::split_vertical")]
+    pub split_vertical: KeyBind,
+    #[serde(default = "KeyBind::move_buffer_up")]
+    pub move_buffer_up: KeyBind,
+    #[serde(default = "KeyBind::move_buffer_down")]
+    pub move_buffer_down: KeyBind,
     #[serde(default = "KeyBind::cycle_next_buffer")]
     pub cycle_next_buffer: KeyBind,
     #[serde(default = "KeyBind::cycle_previous_buffer")]
     pub cycle_previous_buffer: KeyBind,
+    #[serde(default = "KeyBind::cycle_recent_buffer")]
+    pub cycle_recent_buffer: KeyBind,
+    #[serde(default = "KeyBind::cycle_previous_recent_buffer")]
+    pub cycle_previous_recent_buffer: KeyBind,
     #[serde(default = "KeyBind::leave_buffer")]
     pub leave_buffer: KeyBind,
     #[serde(default = "KeyBind::toggle_nick_list")]
     pub toggle_nick_list: KeyBind,
+    #[serde(default = "KeyBind::toggle_nick_list_filter")]
+    pub toggle_nick_list_filter: KeyBind,
     #[serde(default = "KeyBind::toggle_topic")]
     pub toggle_topic: KeyBind,
+    #[serde(default = "KeyBind::toggle_pin")]
+    pub toggle_pin: KeyBind,
     #[serde(default = "KeyBind::toggle_sidebar")]
     pub toggle_sidebar: KeyBind,
     #[serde(default = "KeyBind::toggle_fullscreen")]
@@ -53,14 +71,49 @@ pub struct Keyboard {
     pub scroll_to_top: KeyBind,
     #[serde(default = "KeyBind::scroll_to_bottom")]
     pub scroll_to_bottom: KeyBind,
+    #[serde(default = "KeyBind::scroll_to_focus_marker")]
+    pub scroll_to_focus_marker: KeyBind,
+    #[serde(default = "KeyBind::search_buffer")]
+    pub search_buffer: KeyBind,
+    #[serde(default = "KeyBind::search_history")]
+    pub search_history: KeyBind,
+    #[serde(default = "KeyBind::quick_switcher")]
+    pub quick_switcher: KeyBind,
     #[serde(default = "KeyBind::cycle_next_unread_buffer")]
     pub cycle_next_unread_buffer: KeyBind,
     #[serde(default = "KeyBind::cycle_previous_unread_buffer")]
     pub cycle_previous_unread_buffer: KeyBind,
     #[serde(default = "KeyBind::mark_as_read")]
     pub mark_as_read: KeyBind,
+    #[serde(default = "KeyBind::focus_buffer_1")]
+    pub focus_buffer_1: KeyBind,
+    #[serde(default = "KeyBind::focus_buffer_2")]
+    pub focus_buffer_2: KeyBind,
+    #[serde(default = "KeyBind::focus_buffer_3")]
+    pub focus_buffer_3: KeyBind,
+    #[serde(default = "KeyBind::focus_buffer_4")]
+    pub focus_buffer_4: KeyBind,
+    #[serde(default = "KeyBind::focus_buffer_5")]
+    pub focus_buffer_5: KeyBind,
+    #[serde(default = "KeyBind::focus_buffer_6")]
+    pub focus_buffer_6: KeyBind,
+    #[serde(default = "KeyBind::focus_buffer_7")]
+    pub focus_buffer_7: KeyBind,
+    #[serde(default = "KeyBind::focus_buffer_8")]
+    pub focus_buffer_8: KeyBind,
+    #[serde(default = "KeyBind::focus_buffer_9")]
+    pub focus_buffer_9: KeyBind,
+    #[serde(default = "KeyBind::focus_buffer_10")]
+    pub focus_buffer_10: KeyBind,
+    #[serde(default)]
+    pub focus_buffer_target: FocusBufferTarget,
     #[serde(default)]
     pub quit_application: Option<KeyBind>,
+    #[serde(default)]
+    pub toggle_debug_overlay: Option<KeyBind>,
+    /// System-wide shortcut showing or hiding the main window
+    #[serde(default)]
+    pub global_toggle_window: Option<KeyBind>,
 }
 
 impl Default for Keyboard {
@@ -73,12 +126,21 @@ impl Default for Keyboard {
             close_buffer: KeyBind::close_buffer(),
             maximize_buffer: KeyBind::maximize_buffer(),
             restore_buffer: KeyBind::restore_buffer(),
+            split_horizontal: KeyBind::split_horizontal(),
+            split_vertical: KeyBind::split_vertical(),
+            move_buffer_up: KeyBind::move_buffer_up(),
+            move_buffer_down: KeyBind::move_buffer_down(),
             cycle_next_buffer: KeyBind::cycle_next_buffer(),
             cycle_previous_buffer: KeyBind::cycle_previous_buffer(),
+            cycle_recent_buffer: KeyBind::cycle_recent_buffer(),
+            cycle_previous_recent_buffer: KeyBind::cycle_previous_recent_buffer(
+            ),
             leave_buffer: KeyBind::leave_buffer(),
             toggle_nick_list: KeyBind::toggle_nick_list(),
+            toggle_nick_list_filter: KeyBind::toggle_nick_list_filter(),
             toggle_sidebar: KeyBind::toggle_sidebar(),
             toggle_topic: KeyBind::toggle_topic(),
+            toggle_pin: KeyBind::toggle_pin(),
             toggle_fullscreen: KeyBind::toggle_fullscreen(),
             command_bar: KeyBind::command_bar(),
             reload_configuration: KeyBind::reload_configuration(),
@@ -90,59 +152,439 @@ impl Default for Keyboard {
             scroll_down_page: KeyBind::scroll_down_page(),
             scroll_to_top: KeyBind::scroll_to_top(),
             scroll_to_bottom: KeyBind::scroll_to_bottom(),
+            scroll_to_focus_marker: KeyBind::scroll_to_focus_marker(),
+            search_buffer: KeyBind::search_buffer(),
+            search_history: KeyBind::search_history(),
+            quick_switcher: KeyBind::quick_switcher(),
             cycle_next_unread_buffer: KeyBind::cycle_next_unread_buffer(),
             cycle_previous_unread_buffer: KeyBind::cycle_previous_unread_buffer(
             ),
             mark_as_read: KeyBind::mark_as_read(),
+            focus_buffer_1: KeyBind::focus_buffer_1(),
+            focus_buffer_2: KeyBind::focus_buffer_2(),
+            focus_buffer_3: KeyBind::focus_buffer_3(),
+            focus_buffer_4: KeyBind::focus_buffer_4(),
+            focus_buffer_5: KeyBind::focus_buffer_5(),
+            focus_buffer_6: KeyBind::focus_buffer_6(),
+            focus_buffer_7: KeyBind::focus_buffer_7(),
+            focus_buffer_8: KeyBind::focus_buffer_8(),
+            focus_buffer_9: KeyBind::focus_buffer_9(),
+            focus_buffer_10: KeyBind::focus_buffer_10(),
+            focus_buffer_target: FocusBufferTarget::default(),
             quit_application: None,
+            toggle_debug_overlay: None,
+            global_toggle_window: None,
         }
     }
 }
 
 impl Keyboard {
     pub fn shortcuts(&self) -> Vec<Shortcut> {
+        self.bindings()
+            .into_iter()
+            .map(|(_, shortcut)| shortcut)
+            .collect()
+    }
+
+    /// Errors when a shortcut is set for more than one action by the user
+    pub fn validate(&self) -> Result<(), Error> {
+        let bindings = self.bindings();
+
+        for (index, (action, shortcut)) in bindings.iter().enumerate() {
+            if let Some((other, _)) = bindings[..index]
+                .iter()
+                .find(|(_, other)| other.key_bind() == shortcut.key_bind())
+            {
+                return Err(Error::KeyboardConflict {
+                    key_bind: shortcut.key_bind().to_string(),
+                    first: (*other).to_string(),
+                    second: (*action).to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Shortcuts along with the name of their action. A default shortcut is
+    /// left out when the user set its key bind for another action
+    fn bindings(&self) -> Vec<(&'static str, Shortcut)> {
+        let defaults = Keyboard::default().all_bindings();
+        let bindings = self.all_bindings();
+
+        let is_default = |action: &str, shortcut: &Shortcut| {
+            defaults.iter().any(|(default, default_shortcut)| {
+                *default == action
+                    && default_shortcut.key_bind() == shortcut.key_bind()
+            })
+        };
+        let user_key_binds = bindings
+            .iter()
+            .filter(|(action, shortcut)| !is_default(action, shortcut))
+            .map(|(_, shortcut)| shortcut.key_bind().clone())
+            .collect::<Vec<_>>();
+
+        bindings
+            .into_iter()
+            .filter(|(action, shortcut)| {
+                !is_default(action, shortcut)
+                    || !user_key_binds.contains(shortcut.key_bind())
+            })
+            .collect()
+    }
+
+    /// Shortcuts of every action along with its name
+    fn all_bindings(&self) -> Vec<(&'static str, Shortcut)> {
         use crate::shortcut::Command::*;
 
-        let mut shortcuts = vec![
-            shortcut(self.move_up.clone(), MoveUp),
-            shortcut(self.move_down.clone(), MoveDown),
-            shortcut(self.move_left.clone(), MoveLeft),
-            shortcut(self.move_right.clone(), MoveRight),
-            shortcut(self.close_buffer.clone(), CloseBuffer),
-            shortcut(self.maximize_buffer.clone(), MaximizeBuffer),
-            shortcut(self.restore_buffer.clone(), RestoreBuffer),
-            shortcut(self.cycle_next_buffer.clone(), CycleNextBuffer),
-            shortcut(self.cycle_previous_buffer.clone(), CyclePreviousBuffer),
-            shortcut(self.leave_buffer.clone(), LeaveBuffer),
-            shortcut(self.toggle_nick_list.clone(), ToggleNicklist),
-            shortcut(self.toggle_topic.clone(), ToggleTopic),
-            shortcut(self.toggle_sidebar.clone(), ToggleSidebar),
-            shortcut(self.toggle_fullscreen.clone(), ToggleFullscreen),
-            shortcut(self.command_bar.clone(), CommandBar),
-            shortcut(self.reload_configuration.clone(), ReloadConfiguration),
-            shortcut(self.file_transfers.clone(), FileTransfers),
-            shortcut(self.logs.clone(), Logs),
-            shortcut(self.theme_editor.clone(), ThemeEditor),
-            shortcut(self.scroll_up_page.clone(), ScrollUpPage),
-            shortcut(self.scroll_down_page.clone(), ScrollDownPage),
-            shortcut(self.scroll_to_top.clone(), ScrollToTop),
-            shortcut(self.scroll_to_bottom.clone(), ScrollToBottom),
-            shortcut(self.highlights.clone(), Highlights),
-            shortcut(
-                self.cycle_next_unread_buffer.clone(),
-                CycleNextUnreadBuffer,
-            ),
-            shortcut(
-                self.cycle_previous_unread_buffer.clone(),
-                CyclePreviousUnreadBuffer,
-            ),
-            shortcut(self.mark_as_read.clone(), MarkAsRead),
+        let mut bindings = vec![
+            ("move_up", shortcut(self.move_up.clone(), MoveUp)),
+            ("move_down", shortcut(self.move_down.clone(), MoveDown)),
+            ("move_left", shortcut(self.move_left.clone(), MoveLeft)),
+            ("move_right", shortcut(self.move_right.clone(), MoveRight)),
+            (
+                "close_buffer",
+                shortcut(self.close_buffer.clone(), CloseBuffer),
+            ),
+            (
+                "maximize_buffer",
+                shortcut(self.maximize_buffer.clone(), MaximizeBuffer),
+            ),
+            (
+                "restore_buffer",
+                shortcut(self.restore_buffer.clone(), RestoreBuffer),
+            ),
+            (
+                "split_horizontal",
+                shortcut(self.split_horizontal.clone(), SplitHorizontal),
+            ),
+            (
+                "split_vertical",
+                shortcut(self.split_vertical.clone(), SplitVertical),
+            ),
+            (
+                "move_buffer_up",
+                shortcut(self.move_buffer_up.clone(), MoveBufferUp),
+            ),
+            (
+                "move_buffer_down",
+                shortcut(self.move_buffer_down.clone(), MoveBufferDown),
+            ),
+            (
+                "cycle_next_buffer",
+                shortcut(self.cycle_next_buffer.clone(), CycleNextBuffer),
+            ),
+            (
+                "cycle_previous_buffer",
+                shortcut(
+                    self.cycle_previous_buffer.clone(),
+                    CyclePreviousBuffer,
+                ),
+            ),
+            (
+                "cycle_recent_buffer",
+                shortcut(self.cycle_recent_buffer.clone(), CycleRecentBuffer),
+            ),
+            (
+                "cycle_previous_recent_buffer",
+                shortcut(
+                    self.cycle_previous_recent_buffer.clone(),
+                    CyclePreviousRecentBuffer,
+                ),
+            ),
+            (
+                "leave_buffer",
+                shortcut(self.leave_buffer.clone(), LeaveBuffer),
+            ),
+            (
+                "toggle_nick_list",
+                shortcut(self.toggle_nick_list.clone(), ToggleNicklist),
+            ),
+            (
+                "toggle_nick_list_filter",
+                shortcut(
+                    self.toggle_nick_list_filter.clone(),
+                    ToggleNicklistFilter,
+                ),
+            ),
+            (
+                "toggle_topic",
+                shortcut(self.toggle_topic.clone(), ToggleTopic),
+            ),
+            ("toggle_pin", shortcut(self.toggle_pin.clone(), TogglePin)),
+            (
+                "toggle_sidebar",
+                shortcut(self.toggle_sidebar.clone(), ToggleSidebar),
+            ),
+            (
+                "toggle_fullscreen",
+                shortcut(self.toggle_fullscreen.clone(), ToggleFullscreen),
+            ),
+            (
+                "command_bar",
+                shortcut(self.command_bar.clone(), CommandBar),
+            ),
+            (
+                "reload_configuration",
+                shortcut(
+                    self.reload_configuration.clone(),
+                    ReloadConfiguration,
+                ),
+            ),
+            (
+                "file_transfers",
+                shortcut(self.file_transfers.clone(), FileTransfers),
+            ),
+            ("logs", shortcut(self.logs.clone(), Logs)),
+            (
+                "theme_editor",
+                shortcut(self.theme_editor.clone(), ThemeEditor),
+            ),
+            (
+                "scroll_up_page",
+                shortcut(self.scroll_up_page.clone(), ScrollUpPage),
+            ),
+            (
+                "scroll_down_page",
+                shortcut(self.scroll_down_page.clone(), ScrollDownPage),
+            ),
+            (
+                "scroll_to_top",
+                shortcut(self.scroll_to_top.clone(), ScrollToTop),
+            ),
+            (
+                "scroll_to_bottom",
+                shortcut(self.scroll_to_bottom.clone(), ScrollToBottom),
+            ),
+            (
+                "scroll_to_focus_marker",
+                shortcut(
+                    self.scroll_to_focus_marker.clone(),
+                    ScrollToFocusMarker,
+                ),
+            ),
+            (
+                "search_buffer",
+                shortcut(self.search_buffer.clone(), SearchBuffer),
+            ),
+            (
+                "search_history",
+                shortcut(self.search_history.clone(), SearchHistory),
+            ),
+            (
+                "quick_switcher",
+                shortcut(self.quick_switcher.clone(), QuickSwitcher),
+            ),
+            ("highlights", shortcut(self.highlights.clone(), Highlights)),
+            (
+                "cycle_next_unread_buffer",
+                shortcut(
+                    self.cycle_next_unread_buffer.clone(),
+                    CycleNextUnreadBuffer,
+                ),
+            ),
+            (
+                "cycle_previous_unread_buffer",
+                shortcut(
+                    self.cycle_previous_unread_buffer.clone(),
+                    CyclePreviousUnreadBuffer,
+                ),
+            ),
+            (
+                "mark_as_read",
+                shortcut(self.mark_as_read.clone(), MarkAsRead),
+            ),
+            (
+                "focus_buffer_1",
+                shortcut(self.focus_buffer_1.clone(), FocusBuffer(0)),
+            ),
+            (
+                "focus_buffer_2",
+                shortcut(self.focus_buffer_2.clone(), FocusBuffer(1)),
+            ),
+            (
+                "focus_buffer_3",
+                shortcut(self.focus_buffer_3.clone(), FocusBuffer(2)),
+            ),
+            (
+                "focus_buffer_4",
+                shortcut(self.focus_buffer_4.clone(), FocusBuffer(3)),
+            ),
+            (
+                "focus_buffer_5",
+                shortcut(self.focus_buffer_5.clone(), FocusBuffer(4)),
+            ),
+            (
+                "focus_buffer_6",
+                shortcut(self.focus_buffer_6.clone(), FocusBuffer(5)),
+            ),
+            (
+                "focus_buffer_7",
+                shortcut(self.focus_buffer_7.clone(), FocusBuffer(6)),
+            ),
+            (
+                "focus_buffer_8",
+                shortcut(self.focus_buffer_8.clone(), FocusBuffer(7)),
+            ),
+            (
+                "focus_buffer_9",
+                shortcut(self.focus_buffer_9.clone(), FocusBuffer(8)),
+            ),
+            (
+                "focus_buffer_10",
+                shortcut(self.focus_buffer_10.clone(), FocusBuffer(9)),
+            ),
         ];
 
         if let Some(quit_application) = self.quit_application.clone() {
-            shortcuts.push(shortcut(quit_application, QuitApplication));
+            bindings.push((
+                "quit_application",
+                shortcut(quit_application, QuitApplication),
+            ));
+        }
+
+        if let Some(toggle_debug_overlay) = self.toggle_debug_overlay.clone() {
+            bindings.push((
+                "toggle_debug_overlay",
+                shortcut(toggle_debug_overlay, ToggleDebugOverlay),
+            ));
+        }
+
+        bindings
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FocusBufferTarget {
+    /// Focus the nth buffer in sidebar order
+    #[default]
+    Sidebar,
+    /// Focus the nth pane
+    Pane,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(config: &str) -> Result<Keyboard, String> {
+        toml::from_str::<Keyboard>(config).map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn validate() {
+        assert!(Keyboard::default().validate().is_ok());
+
+        let keyboard = parse(
+            r#"
+            close_buffer = "ctrl+shift+n"
+            command_bar = "alt + k"
+            "#,
+        )
+        .unwrap();
+        assert!(keyboard.validate().is_ok());
+
+        let keyboard = parse(
+            r#"
+            toggle_sidebar = "ctrl+shift+n"
+            scroll_to_bottom = "Ctrl+Shift+N"
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(
+            keyboard.validate(),
+            Err(Error::KeyboardConflict { first, second, .. })
+                if first == "toggle_sidebar" && second == "scroll_to_bottom"
+        ));
+
+        // Displaces the default shortcut of focus_buffer_1
+        let keyboard = parse(r#"mark_as_read = "alt+1""#).unwrap();
+        assert!(keyboard.validate().is_ok());
+        assert!(
+            keyboard
+                .bindings()
+                .iter()
+                .filter(|(_, shortcut)| {
+                    shortcut.key_bind() == &keyboard.mark_as_read
+                })
+                .map(|(action, _)| *action)
+                .eq(["mark_as_read"])
+        );
+
+        assert!(parse(r#"next_buffer = "ctrl+n""#).is_err());
+        assert!(parse(r#"close_buffer = "ctrl+hyper+n""#).is_err());
+        assert!(parse(r#"close_buffer = "ctrl+""#).is_err());
+    }
+
+    /// Names of the fields of `T` as deserialized
+    fn fields<'de, T: Deserialize<'de>>() -> &'static [&'static str] {
+        use serde::de::{self, Visitor};
+
+        struct Fields(&'static [&'static str]);
+
+        impl<'de> de::Deserializer<'de> for &mut Fields {
+            type Error = de::value::Error;
+
+            fn deserialize_any<V: Visitor<'de>>(
+                self,
+                _visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                Err(de::Error::custom("only structs have fields"))
+            }
+
+            fn deserialize_struct<V: Visitor<'de>>(
+                self,
+                _name: &'static str,
+                fields: &'static [&'static str],
+                _visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                self.0 = fields;
+
+                Err(de::Error::custom("only the fields are read"))
+            }
+
+            serde::forward_to_deserialize_any! {
+                bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str
+                string bytes byte_buf option unit unit_struct newtype_struct
+                seq tuple tuple_struct map enum identifier ignored_any
+            }
         }
 
-        shortcuts
+        let mut fields = Fields(&[]);
+        let _ = T::deserialize(&mut fields);
+
+        fields.0
+    }
+
+    #[test]
+    fn bindings_cover_every_action() {
+        let keyboard = Keyboard {
+            quit_application: Some(KeyBind::move_up()),
+            toggle_debug_overlay: Some(KeyBind::move_up()),
+            ..Keyboard::default()
+        };
+        let actions = keyboard
+            .all_bindings()
+            .into_iter()
+            .map(|(action, _)| action)
+            .collect::<Vec<_>>();
+
+        let fields = fields::<Keyboard>();
+
+        for field in fields {
+            // Not shortcuts of the app's window, or an alias
+            if matches!(
+                *field,
+                "focus_buffer_target" | "global_toggle_window" | "highlight"
+            ) {
+                continue;
+            }
+
+            assert!(actions.contains(field), "{field} isn't in bindings()");
+        }
+        for action in actions {
+            assert!(fields.contains(&action), "{action} isn't a field");
+        }
     }
 }
@@ -0,0 +1,9 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+pub struct Matching {
+    /// If true, searching without case sensitivity and highlight `words`
+    /// ignore accents too, so "cafe" matches "café"
+    #[serde(default)]
+    pub accent_insensitive: bool,
+}
@@ -1,6 +1,12 @@
+use chrono::{DateTime, Local};
+use indexmap::IndexMap;
 use serde::Deserialize;
 
+pub use self::schedule::{Level, Schedule};
 use crate::audio::{self, Sound};
+use crate::serde::default_bool_true;
+
+pub mod schedule;
 
 pub type Loaded = Notification<Sound>;
 
@@ -48,8 +54,27 @@ impl<T> Notification<T> {
     }
 }
 
+/// How much notifications about messages reveal
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Privacy {
+    /// Sender and buffer, and the message with `show_content`
+    #[default]
+    Full,
+    /// Only who sent a message and where
+    SenderOnly,
+    /// Only that there's something new
+    CountOnly,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct Notifications<T = String> {
+    /// Plays sounds at all when `true`, regardless of the sounds set for each
+    /// notification
+    #[serde(default = "default_bool_true")]
+    pub sounds: bool,
     #[serde(default)]
     pub connected: Notification<T>,
     #[serde(default)]
@@ -66,11 +91,23 @@ pub struct Notifications<T = String> {
     pub monitored_online: Notification<T>,
     #[serde(default)]
     pub monitored_offline: Notification<T>,
+    #[serde(default)]
+    pub bouncer_attachment: Notification<T>,
+    #[serde(default)]
+    pub scheduled_message: Notification<T>,
+    #[serde(default)]
+    pub schedules: IndexMap<String, Schedule>,
+    #[serde(default)]
+    pub privacy: Privacy,
+    /// Privacy of tagged buffers, keyed by tag
+    #[serde(default)]
+    pub tag_privacy: IndexMap<String, Privacy>,
 }
 
 impl<T> Default for Notifications<T> {
     fn default() -> Self {
         Self {
+            sounds: true,
             connected: Notification::default(),
             disconnected: Notification::default(),
             reconnected: Notification::default(),
@@ -79,10 +116,55 @@ impl<T> Default for Notifications<T> {
             file_transfer_request: Notification::default(),
             monitored_online: Notification::default(),
             monitored_offline: Notification::default(),
+            bouncer_attachment: Notification::default(),
+            scheduled_message: Notification::default(),
+            schedules: IndexMap::default(),
+            privacy: Privacy::default(),
+            tag_privacy: IndexMap::default(),
         }
     }
 }
 
+impl<T> Notifications<T> {
+    /// Tags with a schedule active at `now`
+    pub fn active_schedules(
+        &self,
+        now: DateTime<Local>,
+    ) -> impl Iterator<Item = (&str, Level)> + '_ {
+        self.schedules
+            .iter()
+            .filter(move |(_, schedule)| schedule.is_active(now))
+            .map(|(tag, schedule)| (tag.as_str(), schedule.level))
+    }
+
+    /// Most restrictive level of the active schedules for the given tags
+    pub fn level<'a>(
+        &self,
+        tags: impl IntoIterator<Item = &'a str>,
+        now: DateTime<Local>,
+    ) -> Level {
+        tags.into_iter()
+            .filter_map(|tag| self.schedules.get(tag))
+            .filter(|schedule| schedule.is_active(now))
+            .map(|schedule| schedule.level)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Most restrictive privacy set for the given tags, or `privacy` if none
+    /// of them has one
+    pub fn privacy_of<'a>(
+        &self,
+        tags: impl IntoIterator<Item = &'a str>,
+    ) -> Privacy {
+        tags.into_iter()
+            .filter_map(|tag| self.tag_privacy.get(tag))
+            .copied()
+            .max()
+            .unwrap_or(self.privacy)
+    }
+}
+
 impl Notifications {
     pub fn load_sounds(
         &self,
@@ -99,6 +181,7 @@ impl Notifications {
         };
 
         Ok(Notifications {
+            sounds: self.sounds,
             connected: load(&self.connected)?,
             disconnected: load(&self.disconnected)?,
             reconnected: load(&self.reconnected)?,
@@ -107,6 +190,39 @@ impl Notifications {
             file_transfer_request: load(&self.file_transfer_request)?,
             monitored_online: load(&self.monitored_online)?,
             monitored_offline: load(&self.monitored_offline)?,
+            bouncer_attachment: load(&self.bouncer_attachment)?,
+            scheduled_message: load(&self.scheduled_message)?,
+            schedules: self.schedules.clone(),
+            privacy: self.privacy,
+            tag_privacy: self.tag_privacy.clone(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn privacy_of() {
+        let notifications: Notifications = toml::from_str(
+            r#"
+            privacy = "sender_only"
+
+            [tag_privacy]
+            work = "count_only"
+            personal = "full"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(notifications.privacy_of([]), Privacy::SenderOnly);
+        assert_eq!(notifications.privacy_of(["other"]), Privacy::SenderOnly);
+        // Tags can loosen it too
+        assert_eq!(notifications.privacy_of(["personal"]), Privacy::Full);
+        assert_eq!(
+            notifications.privacy_of(["personal", "work"]),
+            Privacy::CountOnly
+        );
+    }
+}
@@ -0,0 +1,287 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Datelike, Local, Timelike, Weekday};
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+/// Notification level applied to tagged buffers while a schedule is active
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Level {
+    /// Notify as configured
+    #[default]
+    Normal,
+    /// No toasts or sounds
+    Silent,
+    /// No toasts or sounds, and no unread indicator
+    Muted,
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Normal => write!(f, "normal"),
+            Level::Silent => write!(f, "silent"),
+            Level::Muted => write!(f, "muted"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Schedule {
+    pub level: Level,
+    #[serde(default)]
+    pub times: Vec<TimeRange>,
+}
+
+impl Schedule {
+    pub fn is_active(&self, now: DateTime<Local>) -> bool {
+        let minute = (now.hour() * 60 + now.minute()) as u16;
+
+        self.times
+            .iter()
+            .any(|range| range.contains(now.weekday(), minute))
+    }
+}
+
+/// A weekly time range, e.g. `Mon-Fri 09:00-17:00`. Ranges ending before
+/// they start cross midnight and end on the following day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    days: [bool; 7],
+    start: u16,
+    end: u16,
+}
+
+impl TimeRange {
+    fn contains(&self, weekday: Weekday, minute: u16) -> bool {
+        let on = |weekday: Weekday| {
+            self.days[weekday.num_days_from_monday() as usize]
+        };
+
+        if self.start < self.end {
+            on(weekday) && (self.start..self.end).contains(&minute)
+        } else {
+            (on(weekday) && minute >= self.start)
+                || (on(weekday.pred()) && minute < self.end)
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum Error {
+    #[error("empty time range")]
+    Empty,
+    #[error("invalid day: {0}")]
+    InvalidDay(String),
+    #[error("invalid time: {0}")]
+    InvalidTime(String),
+    #[error("time range starts and ends at {0}")]
+    EmptyTimes(String),
+    #[error("unexpected {0}")]
+    Trailing(String),
+}
+
+impl FromStr for TimeRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+
+        let first = parts.next().ok_or(Error::Empty)?;
+
+        // Days may be omitted to mean every day
+        let (days, times) = if first.contains(':') {
+            ([true; 7], Some(first))
+        } else {
+            (parse_days(first)?, parts.next())
+        };
+
+        if let Some(rest) = parts.next() {
+            return Err(Error::Trailing(rest.to_string()));
+        }
+
+        let (start, end) = match times {
+            Some(times) => {
+                let (start, end) = times
+                    .split_once('-')
+                    .ok_or_else(|| Error::InvalidTime(times.to_string()))?;
+
+                let start = parse_time(start)?;
+                let end = parse_time(end)?;
+
+                if start == MINUTES_PER_DAY {
+                    return Err(Error::InvalidTime("24:00".to_string()));
+                } else if start == end {
+                    return Err(Error::EmptyTimes(times.to_string()));
+                }
+
+                (start, end)
+            }
+            None => (0, MINUTES_PER_DAY),
+        };
+
+        Ok(TimeRange { days, start, end })
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeRange {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// Comma separated days or day ranges, e.g. `Mon-Fri` or `Sat,Sun`
+fn parse_days(s: &str) -> Result<[bool; 7], Error> {
+    let day = |s: &str| {
+        Weekday::from_str(s).map_err(|_| Error::InvalidDay(s.to_string()))
+    };
+
+    let mut days = [false; 7];
+
+    for part in s.split(',') {
+        let (first, last) = match part.split_once('-') {
+            Some((first, last)) => (day(first)?, day(last)?),
+            None => (day(part)?, day(part)?),
+        };
+
+        let mut weekday = first;
+
+        loop {
+            days[weekday.num_days_from_monday() as usize] = true;
+
+            if weekday == last {
+                break;
+            }
+
+            weekday = weekday.succ();
+        }
+    }
+
+    Ok(days)
+}
+
+/// `HH:MM` as minutes since midnight, `24:00` is allowed as an end time
+fn parse_time(s: &str) -> Result<u16, Error> {
+    let invalid = || Error::InvalidTime(s.to_string());
+
+    let (hour, minute) = s.split_once(':').ok_or_else(invalid)?;
+
+    let hour = hour.parse::<u16>().map_err(|_| invalid())?;
+    let minute = minute.parse::<u16>().map_err(|_| invalid())?;
+
+    if minute >= 60 || hour > 24 || (hour == 24 && minute > 0) {
+        return Err(invalid());
+    }
+
+    Ok(hour * 60 + minute)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Weekday::*;
+
+    use super::*;
+
+    fn minute(time: &str) -> u16 {
+        parse_time(time).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        let range = "Mon-Fri 09:00-17:00".parse::<TimeRange>().unwrap();
+        assert_eq!(
+            range,
+            TimeRange {
+                days: [true, true, true, true, true, false, false],
+                start: 540,
+                end: 1020,
+            }
+        );
+
+        let range = "sat,sun".parse::<TimeRange>().unwrap();
+        assert_eq!(
+            range,
+            TimeRange {
+                days: [false, false, false, false, false, true, true],
+                start: 0,
+                end: MINUTES_PER_DAY,
+            }
+        );
+
+        let range = "Fri-Mon".parse::<TimeRange>().unwrap();
+        assert_eq!(range.days, [true, false, false, false, true, true, true]);
+
+        let range = "12:00-13:30".parse::<TimeRange>().unwrap();
+        assert_eq!(range.days, [true; 7]);
+        assert_eq!((range.start, range.end), (720, 810));
+
+        let range = "Wed 17:00-24:00".parse::<TimeRange>().unwrap();
+        assert_eq!((range.start, range.end), (1020, MINUTES_PER_DAY));
+
+        for (input, error) in [
+            ("", Error::Empty),
+            ("Someday", Error::InvalidDay("Someday".into())),
+            ("Mon-Fri 9-17", Error::InvalidTime("9".into())),
+            ("Mon 25:00-26:00", Error::InvalidTime("25:00".into())),
+            ("Mon 09:60-10:00", Error::InvalidTime("09:60".into())),
+            ("Mon 10:00-10:00", Error::EmptyTimes("10:00-10:00".into())),
+            ("Mon 24:00-02:00", Error::InvalidTime("24:00".into())),
+            ("Mon 10:00-11:00 12:00", Error::Trailing("12:00".into())),
+        ] {
+            assert_eq!(input.parse::<TimeRange>(), Err(error), "{input}");
+        }
+    }
+
+    #[test]
+    fn contains() {
+        let range = "Mon-Fri 09:00-17:00".parse::<TimeRange>().unwrap();
+
+        assert!(range.contains(Mon, minute("09:00")));
+        assert!(range.contains(Fri, minute("16:59")));
+        assert!(!range.contains(Fri, minute("17:00")));
+        assert!(!range.contains(Tue, minute("08:59")));
+        assert!(!range.contains(Sat, minute("12:00")));
+
+        for range in ["Sun", "Sun 00:00-24:00"] {
+            let range = range.parse::<TimeRange>().unwrap();
+
+            assert!(range.contains(Sun, minute("00:00")));
+            assert!(range.contains(Sun, minute("23:59")));
+            assert!(!range.contains(Mon, minute("00:00")));
+            assert!(!range.contains(Sat, minute("23:59")));
+        }
+    }
+
+    #[test]
+    fn contains_across_midnight() {
+        let range = "Mon-Fri 22:00-02:00".parse::<TimeRange>().unwrap();
+
+        assert!(range.contains(Mon, minute("22:00")));
+        assert!(range.contains(Tue, minute("01:59")));
+        assert!(!range.contains(Tue, minute("02:00")));
+        assert!(!range.contains(Mon, minute("01:00")));
+        assert!(!range.contains(Mon, minute("21:59")));
+        // Friday night ends on Saturday, Sunday night is outside the range
+        assert!(range.contains(Sat, minute("01:00")));
+        assert!(!range.contains(Sat, minute("22:00")));
+        assert!(!range.contains(Sun, minute("23:00")));
+
+        let range = "Sun 18:00-09:00".parse::<TimeRange>().unwrap();
+
+        assert!(range.contains(Sun, minute("23:00")));
+        assert!(range.contains(Mon, minute("08:00")));
+        assert!(!range.contains(Mon, minute("09:00")));
+        assert!(!range.contains(Sat, minute("23:00")));
+    }
+}
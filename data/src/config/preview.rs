@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use url::Url;
 
 use crate::serde::default_bool_true;
 use crate::{Target, isupport};
@@ -103,14 +104,37 @@ impl Card {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Image {
+    #[serde(default = "default_bool_true")]
+    pub enabled: bool,
     #[serde(default)]
     pub action: ImageAction,
     #[serde(default)]
     pub exclude: Vec<String>,
     #[serde(default)]
     pub include: Vec<String>,
+    /// Max height of the inline thumbnail in pixels
+    #[serde(default = "default_image_max_height")]
+    pub max_height: f32,
+    #[serde(default)]
+    pub exclude_domains: Vec<String>,
+    #[serde(default)]
+    pub include_domains: Vec<String>,
+}
+
+impl Default for Image {
+    fn default() -> Self {
+        Self {
+            enabled: default_bool_true(),
+            action: ImageAction::default(),
+            exclude: Vec::default(),
+            include: Vec::default(),
+            max_height: default_image_max_height(),
+            exclude_domains: Vec::default(),
+            include_domains: Vec::default(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
@@ -129,6 +153,32 @@ impl Image {
     ) -> bool {
         is_visible(&self.include, &self.exclude, target, casemapping)
     }
+
+    /// Whether images hosted on the domain of `url` are previewed. A domain
+    /// also covers its subdomains.
+    pub fn domain_visible(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let host = host.to_lowercase();
+
+        let is_domain_filtered = |list: &[String]| -> bool {
+            list.iter().any(|domain| {
+                let domain = domain.to_lowercase();
+
+                domain == "*"
+                    || host == domain
+                    || host
+                        .strip_suffix(domain.as_str())
+                        .is_some_and(|subdomain| subdomain.ends_with('.'))
+            })
+        };
+
+        let domain_included = is_domain_filtered(&self.include_domains);
+        let domain_excluded = is_domain_filtered(&self.exclude_domains);
+
+        domain_included || !domain_excluded
+    }
 }
 
 fn is_visible(
@@ -156,6 +206,10 @@ fn is_visible(
     target_included || !target_excluded
 }
 
+fn default_image_max_height() -> f32 {
+    350.0
+}
+
 fn default_user_agent() -> String {
     "WhatsApp/2".to_string()
 }
@@ -26,12 +26,24 @@ pub struct Server {
     /// Alternative nicknames for the client, if the default is taken.
     #[serde(default)]
     pub alt_nicks: Vec<String>,
+    /// Additional nicknames highlighted like our current nickname.
+    #[serde(default)]
+    pub highlight_nicknames: Vec<String>,
     /// The client's username.
     pub username: Option<String>,
     /// The client's real name.
     pub realname: Option<String>,
-    /// The server to connect to.
+    /// The server to connect to. Only names the server for TLS when
+    /// connecting over a Unix domain socket.
     pub server: String,
+    /// How to connect to the server. Defaults to `tcp`.
+    #[serde(default)]
+    pub connection: Connection,
+    /// The Unix domain socket to connect to when `connection` is `unix`.
+    pub path: Option<PathBuf>,
+    /// Whether to connect to the server on startup. Defaults to `true`.
+    #[serde(default = "default_bool_true")]
+    pub autoconnect: bool,
     /// The port to connect on.
     #[serde(default = "default_tls_port")]
     pub port: u16,
@@ -50,6 +62,10 @@ pub struct Server {
     /// A mapping of channel names to keys for join-on-connect.
     #[serde(default)]
     pub channel_keys: HashMap<String, String>,
+    /// Whether channels the server forwarded us from are joined as their
+    /// forward target from then on.
+    #[serde(default)]
+    pub remember_channel_forwards: bool,
     /// The amount of inactivity in seconds before the client will ping the server.
     #[serde(default = "default_ping_time")]
     pub ping_time: u64,
@@ -73,8 +89,9 @@ pub struct Server {
     pub umodes: Option<String>,
     /// Whether or not to use TLS.
     /// Clients will automatically panic if this is enabled without TLS support.
-    #[serde(default = "default_use_tls")]
-    pub use_tls: bool,
+    /// Defaults to `true`, or `false` over a Unix domain socket.
+    #[serde(default)]
+    use_tls: Option<bool>,
     /// On `true`, all certificate validations are skipped. Defaults to `false`.
     #[serde(default)]
     pub dangerously_accept_invalid_certs: bool,
@@ -99,6 +116,14 @@ pub struct Server {
     pub monitor: Vec<String>,
     #[serde(default = "default_chathistory")]
     pub chathistory: bool,
+    /// Nicknames of bouncer services besides ZNC's and soju's, whose messages
+    /// are shown in the server buffer
+    #[serde(default)]
+    pub bouncer_services: Vec<String>,
+    /// Show every notice sent to us by a user in the server buffer, rather
+    /// than in their query when one is open
+    #[serde(default)]
+    pub private_notices_in_server_buffer: bool,
 }
 
 impl Server {
@@ -118,17 +143,24 @@ impl Server {
                 default_port()
             }),
             channels,
-            use_tls,
+            use_tls: Some(use_tls),
             dangerously_accept_invalid_certs: false,
             ..Default::default()
         }
     }
 
+    pub fn use_tls(&self) -> bool {
+        self.use_tls.unwrap_or(match self.connection {
+            Connection::Tcp => true,
+            Connection::Unix => false,
+        })
+    }
+
     pub fn connection(
         &self,
         proxy: Option<config::Proxy>,
     ) -> connection::Config {
-        let security = if self.use_tls {
+        let security = if self.use_tls() {
             connection::Security::Secured {
                 accept_invalid_certs: self.dangerously_accept_invalid_certs,
                 root_cert_path: self.root_cert_path.as_ref(),
@@ -150,6 +182,10 @@ impl Server {
             port: self.port,
             security,
             proxy: proxy.map(From::from),
+            unix_socket: match self.connection {
+                Connection::Tcp => None,
+                Connection::Unix => self.path.as_deref(),
+            },
         }
     }
 }
@@ -164,9 +200,13 @@ impl Default for Server {
             nick_password_command: Option::default(),
             nick_identify_syntax: Option::default(),
             alt_nicks: Vec::default(),
+            highlight_nicknames: Vec::default(),
             username: Option::default(),
             realname: Option::default(),
             server: String::default(),
+            connection: Connection::default(),
+            path: Option::default(),
+            autoconnect: default_bool_true(),
             port: default_tls_port(),
             password: Option::default(),
             password_file: Option::default(),
@@ -174,13 +214,14 @@ impl Default for Server {
             password_command: Option::default(),
             channels: Vec::default(),
             channel_keys: HashMap::default(),
+            remember_channel_forwards: Default::default(),
             ping_time: default_ping_time(),
             ping_timeout: default_ping_timeout(),
             reconnect_delay: default_reconnect_delay(),
             should_ghost: Default::default(),
             ghost_sequence: default_ghost_sequence(),
             umodes: Option::default(),
-            use_tls: default_use_tls(),
+            use_tls: Option::default(),
             dangerously_accept_invalid_certs: Default::default(),
             root_cert_path: Option::default(),
             sasl: Option::default(),
@@ -189,10 +230,20 @@ impl Default for Server {
             who_poll_interval: default_who_poll_interval(),
             monitor: Vec::default(),
             chathistory: default_chathistory(),
+            bouncer_services: Vec::default(),
+            private_notices_in_server_buffer: Default::default(),
         }
     }
 }
 
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Connection {
+    #[default]
+    Tcp,
+    Unix,
+}
+
 #[derive(PartialEq, Eq, Debug, Clone, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum IdentifySyntax {
@@ -304,10 +355,6 @@ where
     Ok(Duration::from_secs(seconds.clamp(1, 3600)))
 }
 
-fn default_use_tls() -> bool {
-    true
-}
-
 fn default_tls_port() -> u16 {
     6697
 }
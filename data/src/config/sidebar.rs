@@ -22,6 +22,7 @@ pub enum UnreadIndicator {
     #[default]
     Dot,
     Title,
+    Count,
     None,
 }
 
@@ -0,0 +1,30 @@
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Startup {
+    /// Number of milliseconds to wait between starting each server
+    /// connection on startup
+    #[serde(default = "default_delay_ms")]
+    pub delay_ms: u64,
+    /// Number of connections allowed to be in progress at the same time
+    /// on startup
+    #[serde(default = "default_concurrency")]
+    pub concurrency: usize,
+}
+
+impl Default for Startup {
+    fn default() -> Self {
+        Self {
+            delay_ms: default_delay_ms(),
+            concurrency: default_concurrency(),
+        }
+    }
+}
+
+fn default_delay_ms() -> u64 {
+    300
+}
+
+fn default_concurrency() -> usize {
+    3
+}
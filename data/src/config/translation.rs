@@ -0,0 +1,40 @@
+use serde::Deserialize;
+use url::Url;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Translation {
+    /// Language messages are translated into
+    #[serde(default = "default_language")]
+    pub language: String,
+    /// Command given the message on stdin, printing its translation, with
+    /// `{language}` replaced by the language
+    #[serde(default)]
+    pub command: Option<String>,
+    /// LibreTranslate compatible endpoint, used unless there's a command
+    #[serde(default)]
+    pub url: Option<Url>,
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+impl Translation {
+    /// Whether there's a backend to translate with
+    pub fn is_enabled(&self) -> bool {
+        self.command.is_some() || self.url.is_some()
+    }
+}
+
+impl Default for Translation {
+    fn default() -> Self {
+        Self {
+            language: default_language(),
+            command: None,
+            url: None,
+            api_key: None,
+        }
+    }
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
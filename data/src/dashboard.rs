@@ -1,13 +1,16 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
 use crate::buffer::{self, Buffer};
+use crate::history::ReadMarker;
+use crate::message::Target;
 use crate::pane::Pane;
 use crate::serde::fail_as_none;
-use crate::{compression, environment};
+use crate::{Message, Server, channel, compression, environment};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Dashboard {
@@ -18,6 +21,14 @@ pub struct Dashboard {
     pub buffer_settings: BufferSettings,
     #[serde(default, deserialize_with = "fail_as_none")]
     pub focus_buffer: Option<Buffer>,
+    #[serde(default)]
+    pub highlights: Highlights,
+    #[serde(default)]
+    pub sidebar_order: SidebarOrder,
+    #[serde(default)]
+    pub collapsed_servers: CollapsedServers,
+    #[serde(default)]
+    pub layouts: Layouts,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -25,7 +36,9 @@ pub struct BufferSettings(HashMap<String, buffer::Settings>);
 
 impl BufferSettings {
     pub fn get(&self, buffer: &buffer::Buffer) -> Option<&buffer::Settings> {
-        self.0.get(&buffer.key())
+        self.0
+            .get(&buffer.key())
+            .or_else(|| self.0.get(&buffer.legacy_key()))
     }
 
     pub fn entry(
@@ -33,10 +46,266 @@ impl BufferSettings {
         buffer: &buffer::Buffer,
         maybe_default: Option<buffer::Settings>,
     ) -> &mut buffer::Settings {
+        let key = buffer.key();
+
+        // Moves settings saved under the legacy key to the normalized one
+        if !self.0.contains_key(&key) {
+            if let Some(settings) = self.0.remove(&buffer.legacy_key()) {
+                self.0.insert(key.clone(), settings);
+            }
+        }
+
         self.0
-            .entry(buffer.key())
+            .entry(key)
             .or_insert_with(|| maybe_default.unwrap_or_default())
     }
+
+    /// Notification override of `buffer`, if one was set
+    pub fn notifications(
+        &self,
+        buffer: &buffer::Upstream,
+    ) -> Option<buffer::Notifications> {
+        self.get(&Buffer::Upstream(buffer.clone()))
+            .and_then(|settings| settings.notifications)
+    }
+
+    /// Users whose actions are shown differently in `buffer`
+    pub fn actions(
+        &self,
+        buffer: &buffer::Upstream,
+    ) -> Option<&channel::Actions> {
+        self.get(&Buffer::Upstream(buffer.clone()))
+            .map(|settings| &settings.channel.actions)
+    }
+}
+
+/// Arrangements of the panes of the main window saved by name, to switch
+/// between with `/layout`
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct Layouts(BTreeMap<String, Pane>);
+
+impl Layouts {
+    /// Saves `pane` as `name`, replacing the layout saved as it before
+    pub fn save(&mut self, name: String, pane: Pane) {
+        self.0.insert(name, pane);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Pane> {
+        self.0.get(name)
+    }
+
+    /// Deletes the layout saved as `name`, returning whether there was one
+    pub fn delete(&mut self, name: &str) -> bool {
+        self.0.remove(name).is_some()
+    }
+}
+
+/// Order of the channels and queries of each server in the sidebar, as they
+/// were moved. Those which weren't follow in their usual order.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct SidebarOrder(HashMap<String, Vec<String>>);
+
+/// Where a buffer is moved, next to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Before,
+    After,
+}
+
+/// Where a buffer is moved, one place from where it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+}
+
+impl SidebarOrder {
+    /// Sorts `buffers`, channels and queries of `server`, into their order
+    pub fn sort(&self, server: &Server, buffers: &mut [buffer::Upstream]) {
+        let Some(order) = self.0.get(server.as_ref()) else {
+            return;
+        };
+
+        buffers.sort_by_key(|buffer| {
+            let key = buffer.key();
+            let legacy_key = buffer.legacy_key();
+
+            order
+                .iter()
+                .position(|ordered| *ordered == key || *ordered == legacy_key)
+                .unwrap_or(order.len())
+        });
+    }
+
+    /// Moves `buffer` next to `target`, both among `buffers` as they're
+    /// ordered. Returns whether the order changed.
+    pub fn place(
+        &mut self,
+        buffers: &[buffer::Upstream],
+        buffer: &buffer::Upstream,
+        target: &buffer::Upstream,
+        placement: Placement,
+    ) -> bool {
+        if buffer == target {
+            return false;
+        }
+
+        let mut order = ordered(buffers, buffer.server());
+
+        let Some(from) = order.iter().position(|ordered| ordered == buffer)
+        else {
+            return false;
+        };
+        order.remove(from);
+
+        let Some(to) = order.iter().position(|ordered| ordered == target)
+        else {
+            return false;
+        };
+        let to = match placement {
+            Placement::Before => to,
+            Placement::After => to + 1,
+        };
+
+        if to == from {
+            return false;
+        }
+
+        order.insert(to, buffer.clone());
+        self.set(buffer.server(), &order);
+
+        true
+    }
+
+    /// Moves `buffer` one place up or down among `buffers` as they're
+    /// ordered. Returns whether the order changed.
+    pub fn shift(
+        &mut self,
+        buffers: &[buffer::Upstream],
+        buffer: &buffer::Upstream,
+        direction: Direction,
+    ) -> bool {
+        let mut order = ordered(buffers, buffer.server());
+
+        let Some(from) = order.iter().position(|ordered| ordered == buffer)
+        else {
+            return false;
+        };
+        let to = match direction {
+            Direction::Up => from.checked_sub(1),
+            Direction::Down => Some(from + 1).filter(|to| *to < order.len()),
+        };
+        let Some(to) = to else {
+            return false;
+        };
+
+        order.swap(from, to);
+        self.set(buffer.server(), &order);
+
+        true
+    }
+
+    fn set(&mut self, server: &Server, order: &[buffer::Upstream]) {
+        self.0.insert(
+            server.to_string(),
+            order.iter().map(buffer::Upstream::key).collect(),
+        );
+    }
+}
+
+/// Servers whose channels and queries are hidden in the sidebar
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct CollapsedServers(HashSet<String>);
+
+impl CollapsedServers {
+    pub fn contains(&self, server: &Server) -> bool {
+        self.0.contains(server.as_ref())
+    }
+
+    pub fn toggle(&mut self, server: &Server) {
+        if !self.0.remove(server.as_ref()) {
+            self.0.insert(server.to_string());
+        }
+    }
+
+    /// Expands `server`. Returns whether it was collapsed.
+    pub fn expand(&mut self, server: &Server) -> bool {
+        self.0.remove(server.as_ref())
+    }
+}
+
+/// Channels and queries of `server` among `buffers`
+fn ordered(
+    buffers: &[buffer::Upstream],
+    server: &Server,
+) -> Vec<buffer::Upstream> {
+    buffers
+        .iter()
+        .filter(|buffer| {
+            buffer.server() == server
+                && !matches!(buffer, buffer::Upstream::Server(_))
+        })
+        .cloned()
+        .collect()
+}
+
+/// Highlights received since the Highlights buffer was last opened
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Highlights {
+    read_marker: Option<ReadMarker>,
+    #[serde(skip)]
+    unread: usize,
+    #[serde(skip)]
+    unread_by_server: HashMap<Server, usize>,
+}
+
+impl Highlights {
+    pub fn unread(&self) -> usize {
+        self.unread
+    }
+
+    /// Unread highlights from channels of `server`
+    pub fn unread_on(&self, server: &Server) -> usize {
+        self.unread_by_server
+            .get(server)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Counts `message` as unread if it's newer than the read marker
+    pub fn record(&mut self, message: &Message) {
+        if self.is_unread(message) {
+            self.count(message);
+        }
+    }
+
+    /// Counts the unread highlights of the history loaded from disk, which
+    /// doesn't yet have those recorded since
+    pub fn loaded(&mut self, messages: &[Message]) {
+        for message in messages {
+            self.record(message);
+        }
+    }
+
+    pub fn mark_as_read(&mut self) {
+        self.read_marker = Some(ReadMarker::from_date_time(Utc::now()));
+        self.unread = 0;
+        self.unread_by_server.clear();
+    }
+
+    fn count(&mut self, message: &Message) {
+        self.unread += 1;
+
+        if let Target::Highlights { server, .. } = &message.target {
+            *self.unread_by_server.entry(server.clone()).or_default() += 1;
+        }
+    }
+
+    fn is_unread(&self, message: &Message) -> bool {
+        self.read_marker.is_none_or(|read_marker| {
+            message.server_time > read_marker.date_time()
+        })
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, Deserialize, Serialize)]
@@ -56,12 +325,55 @@ pub enum BufferFocusedAction {
 }
 
 impl Dashboard {
-    pub fn load() -> Result<Self, Error> {
-        let path = path()?;
+    /// The saved dashboard, `None` if there isn't one yet
+    pub fn load() -> Result<Option<Self>, Error> {
+        Self::load_from(&path()?)
+    }
+
+    /// Whether a dashboard from the last time it loaded was kept
+    pub fn has_last_good() -> bool {
+        path().is_ok_and(|path| environment::last_good_path(&path).is_file())
+    }
+
+    /// Sets the saved dashboard aside with a `.broken` suffix, so the next
+    /// one starts empty
+    pub fn set_aside_broken() -> Result<(), Error> {
+        set_aside_broken(&path()?)
+    }
+
+    /// Sets the saved dashboard aside and loads the last known good one in
+    /// its place
+    pub fn restore_last_good() -> Result<Self, Error> {
+        Self::restore_last_good_to(&path()?)
+    }
 
-        let bytes = std::fs::read(path)?;
+    fn load_from(path: &Path) -> Result<Option<Self>, Error> {
+        let bytes = match std::fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Ok(None);
+            }
+            Err(error) => return Err(error.into()),
+        };
 
-        Ok(compression::decompress(&bytes)?)
+        let dashboard = compression::decompress(&bytes)?;
+
+        // Kept to fall back on if a later save can't be loaded
+        if let Err(error) =
+            std::fs::write(environment::last_good_path(path), &bytes)
+        {
+            log::warn!("failed to keep last known good dashboard: {error}");
+        }
+
+        Ok(Some(dashboard))
+    }
+
+    fn restore_last_good_to(path: &Path) -> Result<Self, Error> {
+        set_aside_broken(path)?;
+        std::fs::copy(environment::last_good_path(path), path)?;
+
+        Self::load_from(path)?
+            .ok_or_else(|| Error::Io(io::Error::from(io::ErrorKind::NotFound)))
     }
 
     pub async fn save(self) -> Result<(), Error> {
@@ -85,6 +397,14 @@ fn path() -> Result<PathBuf, Error> {
     Ok(parent.join("dashboard.json.gz"))
 }
 
+fn set_aside_broken(path: &Path) -> Result<(), Error> {
+    if path.try_exists()? {
+        std::fs::rename(path, environment::broken_path(path))?;
+    }
+
+    Ok(())
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
@@ -92,3 +412,259 @@ pub enum Error {
     #[error(transparent)]
     Io(#[from] io::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeDelta};
+
+    use super::*;
+    use crate::isupport::CaseMap;
+    use crate::message::{Source, Target, plain};
+    use crate::target;
+
+    fn highlight(server_time: DateTime<Utc>) -> Message {
+        Message::test(
+            server_time,
+            Target::Server {
+                source: Source::Server(None),
+            },
+            plain("halloy: hi".to_string()),
+        )
+    }
+
+    #[test]
+    fn highlights() {
+        let mut highlights = Highlights::default();
+        let before = Utc::now() - TimeDelta::minutes(1);
+
+        highlights.loaded(&[highlight(before), highlight(before)]);
+        highlights.record(&highlight(Utc::now()));
+        assert_eq!(highlights.unread(), 3);
+
+        highlights.mark_as_read();
+        assert_eq!(highlights.unread(), 0);
+
+        highlights.loaded(&[highlight(before)]);
+        highlights.record(&highlight(Utc::now() + TimeDelta::seconds(1)));
+        assert_eq!(highlights.unread(), 1);
+    }
+
+    #[test]
+    fn highlights_by_server() {
+        let libera = Server::from("libera");
+        let oftc = Server::from("oftc");
+        let highlight_on = |server: &Server| Message {
+            target: Target::Highlights {
+                server: server.clone(),
+                channel: target::Channel::from_str(
+                    "#halloy",
+                    CaseMap::default(),
+                ),
+                source: Source::Server(None),
+            },
+            ..highlight(Utc::now())
+        };
+
+        let mut highlights = Highlights::default();
+        highlights.loaded(&[highlight_on(&libera)]);
+        highlights.record(&highlight_on(&libera));
+        highlights.record(&highlight_on(&oftc));
+
+        assert_eq!(highlights.unread(), 3);
+        assert_eq!(highlights.unread_on(&libera), 2);
+        assert_eq!(highlights.unread_on(&oftc), 1);
+
+        highlights.mark_as_read();
+        assert_eq!(highlights.unread_on(&libera), 0);
+    }
+
+    #[test]
+    fn collapsed_servers() {
+        let libera = Server::from("libera");
+        let mut collapsed = CollapsedServers::default();
+
+        assert!(!collapsed.contains(&libera));
+        collapsed.toggle(&libera);
+        assert!(collapsed.contains(&libera));
+
+        assert!(collapsed.expand(&libera));
+        assert!(!collapsed.expand(&libera));
+        assert!(!collapsed.contains(&libera));
+    }
+
+    #[test]
+    fn sidebar_order() {
+        let libera = Server::from("libera");
+        let oftc = Server::from("oftc");
+        let channel = |server: &Server, channel: &str| {
+            buffer::Upstream::Channel(
+                server.clone(),
+                target::Channel::from_str(channel, CaseMap::default()),
+            )
+        };
+        let query = buffer::Upstream::Query(
+            libera.clone(),
+            target::Query::parse("casper", &['#'], &[], CaseMap::default())
+                .unwrap(),
+        );
+
+        let halloy = channel(&libera, "#halloy");
+        let rust = channel(&libera, "#rust");
+        let buffers = vec![
+            buffer::Upstream::Server(libera.clone()),
+            halloy.clone(),
+            rust.clone(),
+            query.clone(),
+            channel(&oftc, "#debian"),
+        ];
+        let sorted = |order: &SidebarOrder| {
+            let mut sorted = buffers[1..4].to_vec();
+            order.sort(&libera, &mut sorted);
+            sorted
+        };
+
+        let mut order = SidebarOrder::default();
+        assert_eq!(sorted(&order), buffers[1..4]);
+
+        assert!(order.place(&buffers, &query, &halloy, Placement::Before));
+        assert_eq!(
+            sorted(&order),
+            vec![query.clone(), halloy.clone(), rust.clone()]
+        );
+
+        let buffers = sorted(&order);
+        // Already there, or somewhere it can't go
+        assert!(!order.place(&buffers, &query, &halloy, Placement::Before));
+        assert!(!order.place(&buffers, &query, &query, Placement::After));
+        assert!(!order.shift(&buffers, &query, Direction::Up));
+        assert!(!order.shift(&buffers, &rust, Direction::Down));
+
+        assert!(order.place(&buffers, &query, &rust, Placement::After));
+        assert!(order.shift(&sorted(&order), &halloy, Direction::Down));
+        assert_eq!(
+            sorted(&order),
+            vec![rust.clone(), halloy.clone(), query.clone()]
+        );
+
+        // Buffers which weren't ordered yet come last
+        let mut with_new =
+            vec![channel(&libera, "#new"), query.clone(), halloy];
+        order.sort(&libera, &mut with_new);
+        assert_eq!(with_new[2], channel(&libera, "#new"));
+
+        // However their channels are cased
+        let mut cased =
+            vec![channel(&libera, "#HALLOY"), channel(&libera, "#Rust")];
+        order.sort(&libera, &mut cased);
+        assert_eq!(
+            cased,
+            vec![channel(&libera, "#rust"), channel(&libera, "#halloy")]
+        );
+
+        // Other servers keep their order
+        let mut debian = vec![channel(&oftc, "#debian")];
+        order.sort(&oftc, &mut debian);
+        assert_eq!(debian, vec![channel(&oftc, "#debian")]);
+    }
+
+    #[test]
+    fn buffer_settings_are_case_insensitive() {
+        let libera = Server::from("libera");
+        let channel = |channel: &str| {
+            Buffer::Upstream(buffer::Upstream::Channel(
+                libera.clone(),
+                target::Channel::from_str(channel, CaseMap::default()),
+            ))
+        };
+
+        let mut settings = BufferSettings::default();
+        settings.entry(&channel("#Foo"), None).notifications =
+            Some(buffer::Notifications::None);
+        assert_eq!(
+            settings.get(&channel("#foo")).unwrap().notifications,
+            Some(buffer::Notifications::None)
+        );
+
+        // Saved under how the channel was cased before
+        let mut settings = BufferSettings(HashMap::from([(
+            "server:libera:#Bar".to_string(),
+            buffer::Settings {
+                notifications: Some(buffer::Notifications::All),
+                ..buffer::Settings::default()
+            },
+        )]));
+        assert!(settings.get(&channel("#Bar")).is_some());
+        settings.entry(&channel("#Bar"), None);
+        assert_eq!(
+            settings.get(&channel("#BAR")).unwrap().notifications,
+            Some(buffer::Notifications::All)
+        );
+    }
+
+    #[test]
+    fn restore_last_good() {
+        let dir = std::env::temp_dir()
+            .join(format!("halloy-dashboard-{}", std::process::id()));
+        // Left from a failed run
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("dashboard.json.gz");
+
+        let mut layouts = Layouts::default();
+        layouts.save("empty".to_string(), Pane::Empty);
+        let dashboard = Dashboard {
+            pane: Pane::Buffer {
+                buffer: Buffer::Upstream(buffer::Upstream::Server(
+                    Server::from("libera"),
+                )),
+                pinned: true,
+            },
+            popout_panes: vec![],
+            buffer_settings: BufferSettings::default(),
+            focus_buffer: None,
+            highlights: Highlights::default(),
+            sidebar_order: SidebarOrder::default(),
+            collapsed_servers: CollapsedServers::default(),
+            layouts,
+        };
+        std::fs::write(&path, compression::compress(&dashboard).unwrap())
+            .unwrap();
+
+        // Loading keeps it as the last known good one
+        assert!(Dashboard::load_from(&path).unwrap().is_some());
+        assert!(environment::last_good_path(&path).is_file());
+
+        // A later save that can't be loaded
+        std::fs::write(&path, b"not a dashboard").unwrap();
+        assert!(matches!(
+            Dashboard::load_from(&path),
+            Err(Error::Compression(_))
+        ));
+
+        let restored = Dashboard::restore_last_good_to(&path).unwrap();
+        assert!(matches!(
+            restored.pane,
+            Pane::Buffer {
+                buffer: Buffer::Upstream(buffer::Upstream::Server(_)),
+                pinned: true,
+            }
+        ));
+        assert!(restored.layouts.get("empty").is_some());
+
+        // The broken one is set aside, and the restored one loads again
+        let broken = dir.join("dashboard.json.gz.broken");
+        assert_eq!(std::fs::read(&broken).unwrap(), b"not a dashboard");
+        assert!(Dashboard::load_from(&path).unwrap().is_some());
+
+        // Breaking again keeps the one set aside before
+        std::fs::write(&path, b"still not a dashboard").unwrap();
+        Dashboard::restore_last_good_to(&path).unwrap();
+        assert_eq!(std::fs::read(&broken).unwrap(), b"not a dashboard");
+        assert_eq!(
+            std::fs::read(dir.join("dashboard.json.gz.broken.2")).unwrap(),
+            b"still not a dashboard"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
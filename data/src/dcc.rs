@@ -17,6 +17,7 @@ pub fn decode(content: &str) -> Option<Command> {
 
     match args.next()?.to_lowercase().as_str() {
         "send" => Send::decode(args).map(Command::Send),
+        "accept" => Resume::decode(args).map(Command::Accept),
         cmd => Some(Command::Unsupported(cmd.to_string())),
     }
 }
@@ -24,6 +25,7 @@ pub fn decode(content: &str) -> Option<Command> {
 #[derive(Debug, Clone)]
 pub enum Command {
     Send(Send),
+    Accept(Resume),
     Unsupported(String),
 }
 
@@ -155,6 +157,101 @@ impl Send {
     }
 }
 
+/// Position to resume a SEND from, as requested with RESUME and as agreed to
+/// with ACCEPT
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Resume {
+    pub filename: String,
+    /// 0 for a reverse SEND
+    pub port: u16,
+    pub position: u64,
+    pub token: Option<String>,
+}
+
+impl Resume {
+    pub fn new(send: &Send, position: u64) -> Self {
+        match send {
+            Send::Reverse {
+                filename, token, ..
+            } => Self {
+                filename: filename.clone(),
+                port: 0,
+                position,
+                token: Some(token.clone()),
+            },
+            Send::Direct { filename, port, .. } => Self {
+                filename: filename.clone(),
+                port: port.get(),
+                position,
+                token: None,
+            },
+        }
+    }
+
+    /// Whether this is about `send`. The filename isn't compared, as some
+    /// clients replace it.
+    pub fn is_for(&self, send: &Send) -> bool {
+        match send {
+            Send::Reverse { token, .. } => {
+                self.port == 0 && self.token.as_ref() == Some(token)
+            }
+            Send::Direct { port, .. } => {
+                self.port == port.get() && self.token.is_none()
+            }
+        }
+    }
+
+    fn decode<'a>(args: impl Iterator<Item = &'a str>) -> Option<Self> {
+        let args = args.collect::<Vec<_>>();
+
+        if args.len() < 3 {
+            return None;
+        }
+
+        // Only a reverse SEND, which has port 0, is followed by a token
+        let has_token = args.len() >= 4
+            && args[args.len() - 3] == "0"
+            && args[args.len() - 2].parse::<u64>().is_ok();
+
+        let (args, token) = if has_token {
+            let (token, args) = args.split_last()?;
+
+            (args, Some(token.to_string()))
+        } else {
+            (&args[..], None)
+        };
+
+        let [filename @ .., port, position] = args else {
+            return None;
+        };
+
+        Some(Self {
+            filename: filename.join(" ").trim_matches('\"').to_string(),
+            port: port.parse().ok()?,
+            position: position.parse().ok()?,
+            token,
+        })
+    }
+
+    /// RESUME request to `target`
+    pub fn encode(self, target: &dyn ToString) -> proto::Message {
+        let Self {
+            filename,
+            port,
+            position,
+            token,
+        } = self;
+
+        let token = token.map(|token| format!(" {token}")).unwrap_or_default();
+
+        ctcp::query_message(
+            &ctcp::Command::DCC,
+            target.to_string(),
+            Some(format!("RESUME {filename} {port} {position}{token}")),
+        )
+    }
+}
+
 fn decode_host(host: &str) -> Option<IpAddr> {
     match host.parse::<u32>() {
         Ok(n) => Some(IpAddr::V4(Ipv4Addr::from(n))),
@@ -256,6 +353,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn accept_decode() {
+        let accept = |args: &str| Resume::decode(args.split_whitespace());
+
+        let direct = Send::Direct {
+            filename: "my file name".to_string(),
+            host: IpAddr::V4(Ipv4Addr::from(1402301083)),
+            port: NonZeroU16::new(12345).unwrap(),
+            size: 1453953495,
+        };
+        let reverse = Send::Reverse {
+            filename: "my file name".to_string(),
+            host: IpAddr::V4(Ipv4Addr::from(1402301083)),
+            port: None,
+            size: 1453953495,
+            token: "7".to_string(),
+        };
+
+        let resume = accept("\"my file name\" 12345 1024").unwrap();
+        assert_eq!(resume, Resume::new(&direct, 1024));
+        assert!(resume.is_for(&direct));
+        assert!(!resume.is_for(&reverse));
+
+        let resume = accept("file.ext 0 1024 7").unwrap();
+        assert_eq!(resume.token.as_deref(), Some("7"));
+        assert_eq!(resume.position, 1024);
+        assert!(resume.is_for(&reverse));
+        assert!(!resume.is_for(&direct));
+
+        // Numbers ending the filename of a direct SEND aren't a token
+        let resume = accept("file 2 12345 1024").unwrap();
+        assert_eq!(resume.filename, "file 2");
+        assert_eq!(resume.token, None);
+
+        assert_eq!(accept("file 12345"), None);
+        assert_eq!(accept("file 12345 many"), None);
+    }
+
     #[test]
     fn send_decode_numeric_filename() {
         // Succeeds because only 4 args so we know to only
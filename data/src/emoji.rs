@@ -0,0 +1,75 @@
+//! Emojis written as `:shortcode:`, from the shortcodes bundled with the
+//! `emojis` crate
+use crate::buffer::SkinTone;
+
+/// The emoji of `shortcode`, in `skin_tone` when it has skin tones
+pub fn from_shortcode(
+    shortcode: &str,
+    skin_tone: SkinTone,
+) -> Option<&'static str> {
+    emojis::get_by_shortcode(shortcode).map(|emoji| {
+        emoji
+            .with_skin_tone(skin_tone.into())
+            .unwrap_or(emoji)
+            .as_str()
+    })
+}
+
+/// The emoji and its variants in other skin tones, if it has any
+pub fn skin_tones(emoji: &str) -> Vec<&'static str> {
+    emojis::get(emoji)
+        .and_then(emojis::Emoji::skin_tones)
+        .map(|skin_tones| skin_tones.map(emojis::Emoji::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Replaces the words of `text` which are a known `:shortcode:` with their
+/// emoji. Shortcodes within words are left alone, so `12:30:45` stays as is.
+pub fn replace_shortcodes(text: &str, skin_tone: SkinTone) -> String {
+    text.split(' ')
+        .map(|word| {
+            word.strip_prefix(':')
+                .and_then(|word| word.strip_suffix(':'))
+                .filter(|shortcode| !shortcode.is_empty())
+                .and_then(|shortcode| {
+                    from_shortcode(&shortcode.to_lowercase(), skin_tone)
+                })
+                .unwrap_or(word)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replace() {
+        let tests = [
+            (":thumbsup: nice", SkinTone::Default, "👍 nice"),
+            ("nice :+1:", SkinTone::Dark, "nice 👍🏿"),
+            (":SMILE:", SkinTone::Default, "😄"),
+            // Not a known shortcode
+            (":halloy:", SkinTone::Default, ":halloy:"),
+            // Within a word
+            ("at 12:30:45", SkinTone::Default, "at 12:30:45"),
+            ("done:smile:", SkinTone::Default, "done:smile:"),
+            ("::", SkinTone::Default, "::"),
+        ];
+
+        for (text, skin_tone, expected) in tests {
+            assert_eq!(replace_shortcodes(text, skin_tone), expected);
+        }
+    }
+
+    #[test]
+    fn variants() {
+        let variants = skin_tones("👍");
+
+        assert_eq!(variants.len(), 6);
+        assert_eq!(variants[0], "👍");
+        assert_eq!(skin_tones("👍🏽"), variants);
+        assert!(skin_tones("🎉").is_empty());
+    }
+}
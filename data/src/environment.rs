@@ -1,5 +1,7 @@
 use std::env;
-use std::path::PathBuf;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 pub const VERSION: &str = env!("VERSION");
 pub const GIT_HASH: Option<&str> = option_env!("GIT_HASH");
@@ -13,6 +15,8 @@ pub const RELEASE_WEBSITE: &str =
     "https://github.com/squidowl/halloy/releases/latest";
 pub const SOURCE_WEBSITE: &str = "https://github.com/squidowl/halloy/";
 
+static PROFILE: OnceLock<String> = OnceLock::new();
+
 pub fn formatted_version() -> String {
     let hash = GIT_HASH
         .map(|hash| format!(" ({hash})"))
@@ -21,16 +25,47 @@ pub fn formatted_version() -> String {
     format!("{VERSION}{hash}")
 }
 
+/// Selects the profile used for this process.
+///
+/// Must be called before any path is resolved, as it can only be set once.
+pub fn set_profile(name: String) {
+    let _ = PROFILE.set(name);
+}
+
+/// The selected profile, or `None` when running the default profile.
+pub fn profile() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
+
 pub fn config_dir() -> PathBuf {
     portable_dir().unwrap_or_else(platform_specific_config_dir)
 }
 
+/// Name of the config file of `profile`, e.g. `config.work.toml`.
+pub fn config_file_name(profile: Option<&str>) -> String {
+    match profile {
+        Some(profile) => format!("config.{profile}.toml"),
+        None => CONFIG_FILE_NAME.to_string(),
+    }
+}
+
 pub fn data_dir() -> PathBuf {
-    portable_dir().unwrap_or_else(|| {
+    profile_data_dir(profile())
+}
+
+/// Data directory of `profile`. Profiles other than the default one keep
+/// their data in a subdirectory, so they can run side by side.
+pub fn profile_data_dir(profile: Option<&str>) -> PathBuf {
+    let dir = portable_dir().unwrap_or_else(|| {
         dirs_next::data_dir()
             .expect("expected valid data dir")
             .join("halloy")
-    })
+    });
+
+    match profile {
+        Some(profile) => dir.join("profiles").join(profile),
+        None => dir,
+    }
 }
 
 pub fn cache_dir() -> PathBuf {
@@ -39,6 +74,34 @@ pub fn cache_dir() -> PathBuf {
         .join("halloy")
 }
 
+/// Where a file that failed to load is set aside for inspection, e.g.
+/// `config.toml.broken`, or `config.toml.broken.2` and so on while earlier
+/// ones are kept
+pub fn broken_path(path: &Path) -> PathBuf {
+    let mut broken = with_suffix(path, "broken");
+    let mut number = 1;
+
+    while broken.exists() {
+        number += 1;
+        broken = with_suffix(path, &format!("broken.{number}"));
+    }
+
+    broken
+}
+
+/// Copy of a file from the last time it loaded, e.g. `config.toml.last-good`
+pub fn last_good_path(path: &Path) -> PathBuf {
+    with_suffix(path, "last-good")
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = OsString::from(path);
+    path.push(".");
+    path.push(suffix);
+
+    PathBuf::from(path)
+}
+
 /// Checks if a config file exists in the same directory as the executable.
 /// If so, it'll use that directory for both config & data dirs.
 fn portable_dir() -> Option<PathBuf> {
@@ -3,11 +3,13 @@ use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 
+pub use self::checksum::Verification;
 pub use self::manager::Manager;
 pub use self::task::Task;
 use crate::user::Nick;
 use crate::{Server, dcc, server};
 
+pub mod checksum;
 pub mod manager;
 pub mod task;
 
@@ -35,6 +37,8 @@ pub struct FileTransfer {
     pub remote_user: Nick,
     pub filename: String,
     pub size: u64,
+    /// Bytes received before the transfer was resumed
+    pub offset: u64,
     pub status: Status,
 }
 
@@ -44,6 +48,7 @@ impl FileTransfer {
             Status::Active { transferred, .. } => {
                 transferred as f64 / self.size as f64
             }
+            Status::Resuming { offset } => offset as f64 / self.size as f64,
             Status::Completed { .. } => 1.0,
             _ => 0.0,
         }
@@ -83,10 +88,17 @@ pub enum Status {
     Queued,
     /// Ready (waiting for remote user to connect)
     Ready,
+    /// Waiting for the remote user to accept resuming at `offset`
+    Resuming { offset: u64 },
     /// Transfer is actively sending / receiving
     Active { transferred: u64, elapsed: Duration },
     /// Transfer is complete
-    Completed { elapsed: Duration, sha256: String },
+    Completed {
+        elapsed: Duration,
+        sha256: String,
+        /// Checked against a `.sha256` file received alongside
+        verification: Option<Verification>,
+    },
     /// An error occurred
     Failed { error: String },
 }
@@ -99,7 +111,7 @@ pub struct ReceiveRequest {
     pub server_handle: server::Handle,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SendRequest {
     pub to: Nick,
     pub path: PathBuf,
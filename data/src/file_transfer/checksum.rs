@@ -0,0 +1,242 @@
+//! Checking received files against `.sha256` checksum files next to them
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use bytes::BytesMut;
+use sha2::{Digest, Sha256};
+use tokio::fs::{self, File};
+use tokio::io::AsyncReadExt;
+
+use super::task::BUFFER_SIZE;
+
+const EXTENSION: &str = ".sha256";
+
+/// Whether a file matches the checksum for it in a `.sha256` file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    /// The file, named, matches its checksum
+    Matched(String),
+    /// The file, named, doesn't match its checksum
+    Mismatched(String),
+}
+
+/// Checks a received file, whose SHA-256 digest is `sha256`, against the
+/// checksum file next to it: `name.ext.sha256` for `name.ext`. If it's a
+/// checksum file itself, the file it's for is checked instead. `None` if
+/// there's nothing to check against or checking failed.
+pub async fn verify(path: &Path, sha256: &str) -> Option<Verification> {
+    let result = match checksum_for(path) {
+        Some(file) => {
+            if !fs::try_exists(&file).await.unwrap_or(false) {
+                return None;
+            }
+
+            verify_against(path, &file, hash(&file).await).await
+        }
+        None => {
+            let checksum_file = with_extension(path);
+
+            if !fs::try_exists(&checksum_file).await.unwrap_or(false) {
+                return None;
+            }
+
+            verify_against(&checksum_file, path, Ok(sha256.to_string())).await
+        }
+    };
+
+    result
+        .inspect_err(|error| {
+            log::warn!("failed to verify checksum of {path:?}: {error}");
+        })
+        .ok()
+        .flatten()
+}
+
+/// Checks `file`, whose digest is `sha256`, against the checksum for it in
+/// `checksum_file`
+async fn verify_against(
+    checksum_file: &Path,
+    file: &Path,
+    sha256: io::Result<String>,
+) -> io::Result<Option<Verification>> {
+    let name = file
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let contents = fs::read_to_string(checksum_file).await?;
+
+    let Some(expected) = expected(&contents, &name) else {
+        return Ok(None);
+    };
+
+    Ok(Some(if expected.eq_ignore_ascii_case(&sha256?) {
+        Verification::Matched(name)
+    } else {
+        Verification::Mismatched(name)
+    }))
+}
+
+/// The file `path` is the checksum file for, if it's one
+fn checksum_for(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?.to_str()?;
+
+    if name.len() <= EXTENSION.len() {
+        return None;
+    }
+
+    let (stem, extension) = name.split_at(name.len() - EXTENSION.len());
+
+    extension
+        .eq_ignore_ascii_case(EXTENSION)
+        .then(|| path.with_file_name(stem))
+}
+
+fn with_extension(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(EXTENSION);
+
+    PathBuf::from(name)
+}
+
+/// Digest for `name` in the contents of a checksum file, as written by
+/// `sha256sum` (`<digest>  <name>`, `*` before the name in binary mode) or
+/// only the digest
+fn expected<'a>(contents: &'a str, name: &str) -> Option<&'a str> {
+    let is_digest = |digest: &str| {
+        digest.len() == 64 && digest.chars().all(|c| c.is_ascii_hexdigit())
+    };
+
+    let mut lines = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+    let entries = lines.clone().filter_map(|line| {
+        let (digest, file) = line.split_once(char::is_whitespace)?;
+        let file = file.trim_start();
+        let file = file.strip_prefix('*').unwrap_or(file);
+
+        is_digest(digest).then_some((digest, file))
+    });
+
+    for (digest, file) in entries {
+        // Paths in the checksum file are relative to where it was made
+        let file = file.rsplit(['/', '\\']).next().unwrap_or(file);
+
+        if file == name {
+            return Some(digest);
+        }
+    }
+
+    let only = lines.next().filter(|line| is_digest(line));
+
+    only.filter(|_| lines.next().is_none())
+}
+
+async fn hash(path: &Path) -> io::Result<String> {
+    let mut file = File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = BytesMut::with_capacity(BUFFER_SIZE);
+
+    while file.read_buf(&mut buffer).await? > 0 {
+        hasher.update(buffer.split());
+        buffer.reserve(BUFFER_SIZE);
+    }
+
+    Ok(hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIGEST: &str =
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+    #[test]
+    fn checksum_files() {
+        assert_eq!(
+            checksum_for(Path::new("/downloads/halloy.iso.sha256")),
+            Some(PathBuf::from("/downloads/halloy.iso"))
+        );
+        assert_eq!(
+            checksum_for(Path::new("/downloads/halloy.iso.SHA256")),
+            Some(PathBuf::from("/downloads/halloy.iso"))
+        );
+        assert_eq!(checksum_for(Path::new("/downloads/halloy.iso")), None);
+        assert_eq!(checksum_for(Path::new("/downloads/.sha256")), None);
+        assert_eq!(
+            with_extension(Path::new("/downloads/halloy.iso")),
+            PathBuf::from("/downloads/halloy.iso.sha256")
+        );
+    }
+
+    #[test]
+    fn expected_digest() {
+        let other = "0".repeat(64);
+
+        for (contents, expected_digest) in [
+            (format!("{DIGEST}\n"), Some(DIGEST)),
+            (format!("{DIGEST}  halloy.iso\n"), Some(DIGEST)),
+            (format!("{DIGEST} *halloy.iso"), Some(DIGEST)),
+            (format!("{DIGEST}  dist/halloy.iso"), Some(DIGEST)),
+            (
+                format!("# sums\n{other}  other.iso\n{DIGEST}  halloy.iso\n"),
+                Some(DIGEST),
+            ),
+            (format!("{other}  other.iso\n"), None),
+            (format!("{DIGEST}\n{other}\n"), None),
+            ("not a digest  halloy.iso".to_string(), None),
+            (String::new(), None),
+        ] {
+            assert_eq!(
+                expected(&contents, "halloy.iso"),
+                expected_digest,
+                "{contents}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn verify_siblings() {
+        let dir = std::env::temp_dir()
+            .join(format!("halloy-checksum-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let file = dir.join("empty.txt");
+        let checksum_file = dir.join("empty.txt.sha256");
+        std::fs::write(&file, b"").unwrap();
+
+        // Nothing to check against yet
+        assert_eq!(verify(&file, DIGEST).await, None);
+
+        std::fs::write(&checksum_file, format!("{DIGEST}  empty.txt\n"))
+            .unwrap();
+
+        let name = "empty.txt".to_string();
+
+        assert_eq!(
+            verify(&file, DIGEST).await,
+            Some(Verification::Matched(name.clone()))
+        );
+        assert_eq!(
+            verify(&file, &"0".repeat(64)).await,
+            Some(Verification::Mismatched(name.clone()))
+        );
+        // The checksum file was received, the file it's for is hashed
+        assert_eq!(
+            verify(&checksum_file, "unused").await,
+            Some(Verification::Matched(name.clone()))
+        );
+
+        std::fs::write(&file, b"changed").unwrap();
+        assert_eq!(
+            verify(&checksum_file, "unused").await,
+            Some(Verification::Mismatched(name))
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::{HashMap, VecDeque};
+use std::net::{SocketAddr, TcpListener};
 use std::num::NonZeroU16;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use chrono::Utc;
@@ -13,12 +14,15 @@ use super::{
     Direction, FileTransfer, Id, ReceiveRequest, SendRequest, Status, Task,
     task,
 };
-use crate::{config, dcc};
+use crate::user::Nick;
+use crate::{Server, config, dcc};
 
 enum Item {
     Working {
         file_transfer: FileTransfer,
         task: task::Handle,
+        /// SEND offer of a received file
+        offer: Option<dcc::Send>,
     },
     Finished(FileTransfer),
 }
@@ -39,13 +43,32 @@ impl Item {
     }
 }
 
+/// What a transfer was started with, to start it over
+enum Request {
+    Send(SendRequest),
+    Receive {
+        request: ReceiveRequest,
+        /// Where the file is saved once approved
+        save_to: Option<PathBuf>,
+    },
+}
+
 pub enum Event {
     NewTransfer(FileTransfer, BoxStream<'static, task::Update>),
 }
 
+impl Event {
+    pub fn file_transfer(&self) -> &FileTransfer {
+        match self {
+            Event::NewTransfer(file_transfer, _) => file_transfer,
+        }
+    }
+}
+
 pub struct Manager {
     config: config::FileTransfer,
     items: HashMap<Id, Item>,
+    requests: HashMap<Id, Request>,
     /// Queued = waiting for port assignment
     queued: VecDeque<Id>,
     used_ports: HashMap<Id, NonZeroU16>,
@@ -56,6 +79,7 @@ impl Manager {
         Self {
             config,
             items: HashMap::new(),
+            requests: HashMap::new(),
             queued: VecDeque::new(),
             used_ports: HashMap::new(),
         }
@@ -90,7 +114,7 @@ impl Manager {
             path,
             server,
             server_handle,
-        } = request;
+        } = request.clone();
 
         let reverse = self.config.passive;
 
@@ -114,6 +138,7 @@ impl Manager {
             filename: filename.clone(),
             // Will be updated by task
             size: 0,
+            offset: 0,
             status: if reverse {
                 Status::PendingReverseConfirmation
             } else {
@@ -122,6 +147,8 @@ impl Manager {
             },
         };
 
+        self.requests.insert(id, Request::Send(request));
+
         let task = Task::send(id, path, filename, to, reverse, server_handle);
         let (handle, stream) = task.spawn(
             self.server(),
@@ -134,6 +161,7 @@ impl Manager {
             Item::Working {
                 file_transfer: file_transfer.clone(),
                 task: handle,
+                offer: None,
             },
         );
 
@@ -150,7 +178,7 @@ impl Manager {
             dcc_send,
             server,
             server_handle,
-        } = request;
+        } = request.clone();
 
         // Check if this is the response to a reverse send we sent
         if let Some(id) = dcc_send.token().and_then(|s| s.parse().ok().map(Id))
@@ -165,6 +193,7 @@ impl Manager {
                 if let Some(Item::Working {
                     file_transfer,
                     task,
+                    ..
                 }) = self.items.get_mut(&id)
                 {
                     if file_transfer.filename == *filename {
@@ -195,10 +224,25 @@ impl Manager {
             remote_user: from.clone(),
             filename: dcc_send.filename().to_string(),
             size: dcc_send.size(),
+            offset: 0,
             status: Status::PendingApproval,
         };
 
-        let task = Task::receive(id, dcc_send, from, server_handle);
+        self.requests.insert(
+            id,
+            Request::Receive {
+                request,
+                save_to: None,
+            },
+        );
+
+        let task = Task::receive(
+            id,
+            dcc_send.clone(),
+            from,
+            self.config.resume,
+            server_handle,
+        );
         let (handle, stream) = task.spawn(
             self.server(),
             Duration::from_secs(self.config.timeout),
@@ -210,6 +254,7 @@ impl Manager {
             Item::Working {
                 file_transfer: file_transfer.clone(),
                 task: handle,
+                offer: Some(dcc_send),
             },
         );
 
@@ -225,10 +270,16 @@ impl Manager {
             }
             task::Update::Queued(id) => {
                 let available_port = self.get_available_port();
+                // Ports of other transfers are freed once they're done
+                let is_exhausted =
+                    available_port.is_none() && self.used_ports.is_empty();
 
-                if let Some(Item::Working {
+                if is_exhausted {
+                    self.fail(id, self.no_free_port());
+                } else if let Some(Item::Working {
                     file_transfer,
                     task,
+                    ..
                 }) = self.items.get_mut(&id)
                 {
                     if let Some(port) = available_port {
@@ -247,6 +298,17 @@ impl Manager {
                     item.file_transfer_mut().status = Status::Ready;
                 }
             }
+            task::Update::Resuming(id, offset) => {
+                if let Some(item) = self.items.get_mut(&id) {
+                    item.file_transfer_mut().status =
+                        Status::Resuming { offset };
+                }
+            }
+            task::Update::Resumed(id, offset) => {
+                if let Some(item) = self.items.get_mut(&id) {
+                    item.file_transfer_mut().offset = offset;
+                }
+            }
             task::Update::Progress {
                 id,
                 transferred,
@@ -274,6 +336,7 @@ impl Manager {
                 id,
                 elapsed,
                 sha256,
+                verification,
             } => {
                 if let Some(Item::Working { file_transfer, .. }) =
                     self.items.remove(&id)
@@ -292,7 +355,11 @@ impl Manager {
                     self.items.insert(
                         id,
                         Item::Finished(FileTransfer {
-                            status: Status::Completed { elapsed, sha256 },
+                            status: Status::Completed {
+                                elapsed,
+                                sha256,
+                                verification,
+                            },
                             ..file_transfer
                         }),
                     );
@@ -320,23 +387,41 @@ impl Manager {
         }
     }
 
+    /// A port of the range which isn't used by another transfer or taken by
+    /// another program
     fn get_available_port(&self) -> Option<NonZeroU16> {
         let server = self.config.server.as_ref()?;
 
         server
             .bind_ports
             .clone()
-            .find(|port| {
+            .filter(|port| {
                 !self.used_ports.values().any(|used| used.get() == *port)
             })
+            .find(|port| {
+                TcpListener::bind(SocketAddr::new(server.bind_address, *port))
+                    .is_ok()
+            })
             .and_then(NonZeroU16::new)
     }
 
+    fn no_free_port(&self) -> String {
+        match &self.config.server {
+            Some(server) => format!(
+                "no free port between {} and {} to accept the transfer on",
+                server.bind_ports.start(),
+                server.bind_ports.end()
+            ),
+            None => "no port to accept the transfer on".to_string(),
+        }
+    }
+
     fn recycle_port(&mut self, id: Id) {
         if let Some(port) = self.used_ports.remove(&id) {
             if let Some(Item::Working {
                 task,
                 file_transfer,
+                ..
             }) = self
                 .queued
                 .pop_front()
@@ -348,15 +433,145 @@ impl Manager {
         }
     }
 
+    /// Fails a transfer which can't go on, ending its task
+    fn fail(&mut self, id: Id, error: String) {
+        if let Some(Item::Working { file_transfer, .. }) =
+            self.items.remove(&id)
+        {
+            log::error!(
+                "File transfer failed {} {} for {:?}: {error}",
+                match file_transfer.direction {
+                    Direction::Sent => "to",
+                    Direction::Received => "from",
+                },
+                &file_transfer.remote_user,
+                &file_transfer.filename,
+            );
+
+            self.items.insert(
+                id,
+                Item::Finished(FileTransfer {
+                    status: Status::Failed { error },
+                    ..file_transfer
+                }),
+            );
+        }
+    }
+
     pub fn remove(&mut self, id: &Id) {
         let _ = self.items.remove(id);
+        let _ = self.requests.remove(id);
         self.queued.retain(|i| i != id);
         self.recycle_port(*id);
     }
 
+    /// Removes the transfers which completed or failed
+    pub fn clear_finished(&mut self) {
+        let finished = self
+            .items
+            .iter()
+            .filter_map(|(id, item)| {
+                matches!(
+                    item.file_transfer().status,
+                    Status::Completed { .. } | Status::Failed { .. }
+                )
+                .then_some(*id)
+            })
+            .collect::<Vec<_>>();
+
+        for id in finished {
+            self.remove(&id);
+        }
+    }
+
+    /// Stops a transfer underway, which is kept as failed so it can be
+    /// retried
+    pub fn cancel(&mut self, id: &Id) {
+        self.fail(*id, "cancelled".to_string());
+        self.queued.retain(|i| i != id);
+        self.recycle_port(*id);
+    }
+
+    /// Starts a failed transfer over as a new one. A send offers the file
+    /// again, a receive is saved where it was going to be, resuming it if
+    /// enabled.
+    pub fn retry(
+        &mut self,
+        id: &Id,
+        proxy: Option<config::Proxy>,
+    ) -> Option<Event> {
+        if !self.get(id).is_some_and(|file_transfer| {
+            matches!(file_transfer.status, Status::Failed { .. })
+        }) {
+            return None;
+        }
+
+        let request = self.requests.remove(id)?;
+        self.remove(id);
+
+        match request {
+            Request::Send(request) => self.send(request, proxy),
+            Request::Receive { request, save_to } => {
+                let event = self.receive(request, proxy.as_ref())?;
+
+                if let Some(save_to) = save_to {
+                    self.approve(&event.file_transfer().id, save_to);
+                }
+
+                Some(event)
+            }
+        }
+    }
+
     pub fn approve(&mut self, id: &Id, save_to: PathBuf) {
         if let Some(Item::Working { task, .. }) = self.items.get_mut(id) {
-            task.approve(save_to);
+            task.approve(save_to.clone());
+        }
+
+        if let Some(Request::Receive { save_to: saved, .. }) =
+            self.requests.get_mut(id)
+        {
+            *saved = Some(save_to);
+        }
+    }
+
+    /// The file sent, or where the received file is saved once approved
+    pub fn path(&self, id: &Id) -> Option<&Path> {
+        match self.requests.get(id)? {
+            Request::Send(request) => Some(&request.path),
+            Request::Receive { save_to, .. } => save_to.as_deref(),
+        }
+    }
+
+    /// Continues the received transfer from `from` which `accept` agrees to
+    /// resume
+    pub fn resume_accepted(
+        &mut self,
+        server: &Server,
+        from: &Nick,
+        accept: dcc::Resume,
+    ) {
+        let task = self.items.values_mut().find_map(|item| match item {
+            Item::Working {
+                file_transfer,
+                task,
+                offer: Some(offer),
+            } if file_transfer.server == *server
+                && file_transfer.remote_user == *from
+                && matches!(file_transfer.status, Status::Resuming { .. })
+                && accept.is_for(offer) =>
+            {
+                Some(task)
+            }
+            _ => None,
+        });
+
+        if let Some(task) = task {
+            log::debug!(
+                "File transfer resume accepted by {from} at {}",
+                accept.position
+            );
+            task.resume_accepted(accept.position);
         }
     }
 
@@ -371,4 +586,47 @@ impl Manager {
     pub fn is_empty(&self) -> bool {
         self.items.values().len() == 0
     }
+
+    /// # of received offers waiting to be approved
+    pub fn pending_approval(&self) -> usize {
+        self.items
+            .values()
+            .filter(|item| {
+                matches!(item.file_transfer().status, Status::PendingApproval)
+            })
+            .count()
+    }
+
+    /// Combined progress of the transfers in progress, `None` if there are
+    /// none
+    pub fn active_progress(&self) -> Option<f64> {
+        let active = self
+            .items
+            .values()
+            .map(Item::file_transfer)
+            .filter(|file_transfer| {
+                matches!(
+                    file_transfer.status,
+                    Status::Active { .. } | Status::Resuming { .. }
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if active.is_empty() {
+            return None;
+        }
+
+        let size = active
+            .iter()
+            .map(|file_transfer| file_transfer.size)
+            .sum::<u64>();
+        let transferred = active
+            .iter()
+            .map(|file_transfer| {
+                file_transfer.progress() * file_transfer.size as f64
+            })
+            .sum::<f64>();
+
+        Some(transferred / size.max(1) as f64)
+    }
 }
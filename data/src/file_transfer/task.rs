@@ -1,7 +1,7 @@
 use std::io;
 use std::net::IpAddr;
 use std::num::NonZeroU16;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::{Duration, Instant};
 
 use bytes::{Bytes, BytesMut};
@@ -11,19 +11,22 @@ use irc::proto::command;
 use irc::{BytesCodec, Connection, connection};
 use sha2::{Digest, Sha256};
 use thiserror::Error;
-use tokio::fs::File;
+use tokio::fs::{File, OpenOptions};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::task::JoinHandle;
 use tokio::time;
 use tokio_stream::StreamExt;
 
-use super::Id;
+use super::{Id, Verification, checksum};
 use crate::user::Nick;
 use crate::{config, dcc, server};
 
 /// 16 KiB
 pub const BUFFER_SIZE: usize = 16 * 1024;
 
+/// How long to wait for the sender to accept resuming before starting over
+const RESUME_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub struct Handle {
     sender: Sender<Action>,
     task: JoinHandle<()>,
@@ -43,6 +46,10 @@ impl Handle {
     pub fn port_available(&mut self, port: NonZeroU16) {
         let _ = self.sender.try_send(Action::PortAvailable { port });
     }
+
+    pub fn resume_accepted(&mut self, position: u64) {
+        let _ = self.sender.try_send(Action::ResumeAccepted { position });
+    }
 }
 
 impl Drop for Handle {
@@ -57,6 +64,7 @@ pub enum Task {
         dcc_send: dcc::Send,
         server_handle: server::Handle,
         remote_user: Nick,
+        resume: bool,
     },
     Send {
         id: Id,
@@ -73,12 +81,14 @@ impl Task {
         id: Id,
         dcc_send: dcc::Send,
         remote_user: Nick,
+        resume: bool,
         server_handle: server::Handle,
     ) -> Self {
         Self::Receive {
             id,
             dcc_send,
             remote_user,
+            resume,
             server_handle,
         }
     }
@@ -118,12 +128,14 @@ impl Task {
                     id,
                     dcc_send,
                     remote_user,
+                    resume,
                     server_handle,
                 } => {
                     if let Err(error) = receive(
                         id,
                         dcc_send,
                         remote_user,
+                        resume,
                         server_handle,
                         action_receiver,
                         update_sender,
@@ -183,6 +195,7 @@ pub enum Action {
     Approve { save_to: PathBuf },
     ReverseConfirmed { host: IpAddr, port: NonZeroU16 },
     PortAvailable { port: NonZeroU16 },
+    ResumeAccepted { position: u64 },
 }
 
 #[derive(Debug)]
@@ -190,6 +203,10 @@ pub enum Update {
     Metadata(Id, u64),
     Queued(Id),
     Ready(Id),
+    /// Asked the sender to resume at an offset
+    Resuming(Id, u64),
+    /// Receiving continues from an offset, 0 if starting over
+    Resumed(Id, u64),
     Progress {
         id: Id,
         transferred: u64,
@@ -199,6 +216,7 @@ pub enum Update {
         id: Id,
         elapsed: Duration,
         sha256: String,
+        verification: Option<Verification>,
     },
     Failed(Id, String),
 }
@@ -212,6 +230,7 @@ async fn receive(
     id: Id,
     dcc_send: dcc::Send,
     remote_user: Nick,
+    resume: bool,
     mut server_handle: server::Handle,
     mut action: Receiver<Action>,
     mut update: Sender<Update>,
@@ -224,6 +243,39 @@ async fn receive(
         return Ok(());
     };
 
+    // Continue a partially received file where it left off
+    let existing = match tokio::fs::metadata(&save_to).await {
+        Ok(metadata) if resume && metadata.len() < dcc_send.size() => {
+            metadata.len()
+        }
+        _ => 0,
+    };
+
+    let offset = if existing > 0 {
+        let _ = server_handle
+            .send(dcc::Resume::new(&dcc_send, existing).encode(&remote_user))
+            .await;
+
+        let _ = update.send(Update::Resuming(id, existing)).await;
+
+        match time::timeout(RESUME_TIMEOUT, action.next()).await {
+            Ok(Some(Action::ResumeAccepted { position })) => {
+                position.min(existing)
+            }
+            _ => {
+                log::debug!(
+                    "File transfer resume not accepted by {remote_user}, starting over"
+                );
+
+                0
+            }
+        }
+    } else {
+        0
+    };
+
+    let _ = update.send(Update::Resumed(id, offset)).await;
+
     let (host, port, filename, size, reverse) = match dcc_send {
         dcc::Send::Direct {
             host,
@@ -287,16 +339,21 @@ async fn receive(
                 port: port.get(),
                 security: connection::Security::Unsecured,
                 proxy: proxy.map(From::from),
+                unix_socket: None,
             },
             BytesCodec::new(),
         )
         .await?
     };
 
-    let mut file = File::create(&save_to).await?;
     let mut hasher = Sha256::new();
+    let mut file = if offset > 0 {
+        open_resumed(&save_to, offset, &mut hasher).await?
+    } else {
+        File::create(&save_to).await?
+    };
 
-    let mut transferred = 0;
+    let mut transferred = offset;
     let mut last_progress = started_at;
 
     while transferred < size {
@@ -342,17 +399,43 @@ async fn receive(
         ))
         .await;
 
+    let elapsed = started_at.elapsed();
+
+    // Hashed here rather than by the UI, and not checking isn't a failure
+    let verification = checksum::verify(&save_to, &sha256).await;
+
     let _ = update
         .send(Update::Finished {
             id,
-            elapsed: started_at.elapsed(),
+            elapsed,
             sha256,
+            verification,
         })
         .await;
 
     Ok(())
 }
 
+/// Opens `path` to continue writing at `offset`, hashing what's before it
+async fn open_resumed(
+    path: &Path,
+    offset: u64,
+    hasher: &mut Sha256,
+) -> io::Result<File> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path).await?;
+
+    file.set_len(offset).await?;
+
+    let mut buffer = BytesMut::with_capacity(BUFFER_SIZE);
+
+    while file.read_buf(&mut buffer).await? > 0 {
+        hasher.update(buffer.split());
+        buffer.reserve(BUFFER_SIZE);
+    }
+
+    Ok(file)
+}
+
 async fn send(
     id: Id,
     path: PathBuf,
@@ -405,6 +488,7 @@ async fn send(
                 port: port.get(),
                 security: connection::Security::Unsecured,
                 proxy: proxy.map(From::from),
+                unix_socket: None,
             },
             BytesCodec::new(),
         )
@@ -512,6 +596,7 @@ async fn send(
             id,
             elapsed: started_at.elapsed(),
             sha256,
+            verification: None,
         })
         .await;
 
@@ -0,0 +1,244 @@
+//! Folding text for accent and case insensitive matching, so "cafe" matches
+//! "Café" and "uber" matches "Über". Nicknames are never folded, they're
+//! matched by the server's casemapping only.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{LazyLock, Mutex, PoisonError};
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+/// Folded texts kept, cleared once full
+const CACHE_CAPACITY: usize = 8192;
+
+/// Texts which aren't ASCII are folded the same way each time their buffer is
+/// searched, so they're kept
+static CACHE: LazyLock<Mutex<HashMap<String, String>>> =
+    LazyLock::new(Mutex::default);
+
+/// `text` decomposed (NFKD), without combining marks and lowercased. ASCII
+/// text is only lowercased.
+pub fn fold(text: &str) -> Cow<'_, str> {
+    if text.is_ascii() {
+        return if text.bytes().any(|byte| byte.is_ascii_uppercase()) {
+            Cow::Owned(text.to_ascii_lowercase())
+        } else {
+            Cow::Borrowed(text)
+        };
+    }
+
+    let mut cache = CACHE.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if let Some(folded) = cache.get(text) {
+        return Cow::Owned(folded.clone());
+    }
+
+    let mut folded = String::with_capacity(text.len());
+    for c in text.chars() {
+        fold_char(c, &mut folded);
+    }
+
+    if cache.len() >= CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(text.to_string(), folded.clone());
+
+    Cow::Owned(folded)
+}
+
+/// Folded text which tells where its matches are in the text it was folded
+/// from
+#[derive(Debug, Clone)]
+pub struct Folded<'a> {
+    original: &'a str,
+    text: String,
+    /// Where each char of the original which folds to something starts, in
+    /// the folded text and in the original. Combining marks belong to the
+    /// char before them, or the first one when they lead.
+    starts: Vec<(usize, usize)>,
+}
+
+impl<'a> Folded<'a> {
+    pub fn new(original: &'a str) -> Self {
+        let mut text = String::with_capacity(original.len());
+        let mut starts = vec![(0, 0)];
+
+        for (index, c) in original.char_indices() {
+            let start = text.len();
+
+            fold_char(c, &mut text);
+
+            if start > 0 && text.len() > start {
+                starts.push((start, index));
+            }
+        }
+
+        Self {
+            original,
+            text,
+            starts,
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// Range of the original covering `range` of the folded text, widened to
+    /// whole chars of the original
+    pub fn original_range(&self, range: Range<usize>) -> Range<usize> {
+        let start = self
+            .starts
+            .partition_point(|(folded, _)| *folded <= range.start)
+            .checked_sub(1)
+            .map_or(0, |index| self.starts[index].1);
+
+        let end = self
+            .starts
+            .get(
+                self.starts
+                    .partition_point(|(folded, _)| *folded < range.end),
+            )
+            .map_or(self.original.len(), |(_, original)| *original);
+
+        start..end.max(start)
+    }
+}
+
+fn fold_char(c: char, folded: &mut String) {
+    if c.is_ascii() {
+        folded.push(c.to_ascii_lowercase());
+        return;
+    }
+
+    for c in c.nfkd().filter(|c| !is_combining_mark(*c)) {
+        folded.extend(c.to_lowercase());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    /// Random text of ASCII, Latin letters with diacritics, Greek, Cyrillic,
+    /// compatibility forms and combining marks
+    fn random_text(rng: &mut ChaCha8Rng, is_ascii: bool) -> String {
+        let ranges: &[Range<u32>] = if is_ascii {
+            &[0x20..0x7f]
+        } else {
+            &[
+                0x20..0x7f,
+                0xa0..0x250,
+                0x300..0x370,
+                0x370..0x500,
+                0x1e00..0x1f00,
+                0x2100..0x2150,
+                0xfb00..0xfb07,
+                0xff01..0xff5f,
+            ]
+        };
+
+        (0..rng.random_range(0..24))
+            .filter_map(|_| {
+                let range = &ranges[rng.random_range(0..ranges.len())];
+
+                char::from_u32(rng.random_range(range.clone()))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn folds() {
+        for (text, expected) in [
+            ("Café", "cafe"),
+            ("Über", "uber"),
+            ("cafe\u{301}", "cafe"),
+            ("ÅNGSTRÖM", "angstrom"),
+            ("ﬁle", "file"),
+            ("ＨＡＬＬＯＹ", "halloy"),
+            ("Ελληνικά", "ελληνικα"),
+            ("Hello", "hello"),
+            ("hello", "hello"),
+            ("", ""),
+        ] {
+            assert_eq!(fold(text), expected, "{text}");
+            assert_eq!(Folded::new(text).as_str(), expected, "{text}");
+        }
+
+        assert!(matches!(fold("hello"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn original_ranges() {
+        let find = |text: &str, needle: &str| {
+            let folded = Folded::new(text);
+            let start = folded.as_str().find(needle).unwrap();
+
+            text[folded.original_range(start..start + needle.len())].to_string()
+        };
+
+        assert_eq!(find("Un café noir", "cafe"), "café");
+        // Combining marks after a match are part of it
+        assert_eq!(find("un cafe\u{301} noir", "cafe"), "cafe\u{301}");
+        assert_eq!(find("Über alles", "uber"), "Über");
+        assert_eq!(find("\u{301}abc", "abc"), "\u{301}abc");
+        // Part of a char which folds to more is all of it
+        assert_eq!(find("a ﬁle", "i"), "ﬁ");
+        assert_eq!(find("a ﬁle", "file"), "ﬁle");
+    }
+
+    #[test]
+    fn idempotent() {
+        for c in (0..0x3_0000).filter_map(char::from_u32) {
+            let once = fold(&String::from(c)).into_owned();
+
+            assert_eq!(fold(&once), once, "{c:?}");
+        }
+
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        for _ in 0..2000 {
+            let text = random_text(&mut rng, false);
+            let once = fold(&text).into_owned();
+
+            assert_eq!(fold(&once), once, "{text:?}");
+            assert_eq!(Folded::new(&text).as_str(), once, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn ascii_unchanged() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+
+        for _ in 0..2000 {
+            let text = random_text(&mut rng, true);
+            let needle = random_text(&mut rng, true);
+            let needle = &needle[..needle.len().min(2)];
+
+            // As matched without folding
+            assert_eq!(fold(&text), text.to_lowercase());
+            assert_eq!(
+                fold(&text).contains(fold(needle).as_ref()),
+                text.to_lowercase().contains(&needle.to_lowercase()),
+                "{text:?} {needle:?}"
+            );
+
+            let folded = Folded::new(&text);
+            assert_eq!(
+                folded.original_range(0..folded.as_str().len()),
+                0..text.len()
+            );
+            for (index, _) in text.char_indices() {
+                assert_eq!(
+                    folded.original_range(index..index + 1),
+                    index..index + 1
+                );
+            }
+        }
+    }
+}
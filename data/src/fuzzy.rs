@@ -0,0 +1,176 @@
+//! Fuzzy matching of what's typed against names, as in the quick switcher
+
+/// Points for a character matched right after the previous one
+const CONSECUTIVE: u32 = 4;
+/// Points for a character matched at the start of a word
+const WORD_START: u32 = 3;
+/// Points for a candidate which was used recently
+const RECENT: u32 = 8;
+
+/// How well a query matched. Prefix matches rank above every other match,
+/// which rank by their points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Score {
+    pub is_prefix: bool,
+    pub points: u32,
+}
+
+/// Scores `candidate` against `query`, ignoring case and whitespace in the
+/// query, or `None` when the characters of `query` aren't all in
+/// `candidate` in the same order. Leading symbols of `candidate`, such as the
+/// `#` of a channel, don't stop it from being a prefix match.
+pub fn score(query: &str, candidate: &str) -> Option<Score> {
+    let query = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect::<Vec<_>>();
+    let candidate = candidate.to_lowercase().chars().collect::<Vec<_>>();
+
+    let Some(first) = query.first() else {
+        return Some(Score {
+            is_prefix: false,
+            points: 0,
+        });
+    };
+
+    // Matching from the first character found can miss a better match
+    // later on, so every start is tried
+    let points = candidate
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| *c == first)
+        .filter_map(|(start, _)| points(&query, &candidate, start))
+        .max()?;
+
+    let symbol = |c: &char| !c.is_alphanumeric();
+    let query = query.iter().skip_while(|c| symbol(c));
+    let candidate = candidate.iter().skip_while(|c| symbol(c));
+    let is_prefix = query.clone().next().is_some()
+        && query.zip(candidate).all(|(q, c)| q == c);
+
+    Some(Score { is_prefix, points })
+}
+
+/// Candidates matching `query` by their name, best first, and otherwise in
+/// the order given. The first `recent` candidates are those used recently,
+/// which get extra points.
+pub fn rank<T>(
+    query: &str,
+    candidates: impl IntoIterator<Item = (T, String)>,
+    recent: usize,
+) -> Vec<T> {
+    let mut matches = candidates
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, (candidate, name))| {
+            let mut score = score(query, &name)?;
+
+            if index < recent {
+                score.points += RECENT;
+            }
+
+            Some((score, index, candidate))
+        })
+        .collect::<Vec<_>>();
+
+    matches.sort_by(|(a, a_index, _), (b, b_index, _)| {
+        b.cmp(a).then(a_index.cmp(b_index))
+    });
+
+    matches
+        .into_iter()
+        .map(|(_, _, candidate)| candidate)
+        .collect()
+}
+
+/// Points of matching `query` in `candidate` from `start` on, taking each
+/// character of `query` where it's first found
+fn points(query: &[char], candidate: &[char], start: usize) -> Option<u32> {
+    let mut remaining = candidate.iter().enumerate().skip(start);
+    let mut previous = None;
+    let mut points = 0;
+
+    for c in query {
+        let (index, _) = remaining.find(|(_, candidate)| *candidate == c)?;
+
+        if previous.is_some_and(|previous| previous + 1 == index) {
+            points += CONSECUTIVE;
+        } else if index == 0 || !candidate[index - 1].is_alphanumeric() {
+            points += WORD_START;
+        }
+
+        points += 1;
+        previous = Some(index);
+    }
+
+    Some(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches() {
+        assert!(score("hal", "#halloy (libera)").unwrap().is_prefix);
+        assert!(score("#Hal", "#halloy (libera)").unwrap().is_prefix);
+        assert!(!score("loy", "#halloy (libera)").unwrap().is_prefix);
+        assert!(score("hy", "#halloy (libera)").is_some());
+        assert!(score("libera", "#halloy (libera)").is_some());
+        assert_eq!(score("yolla", "#halloy (libera)"), None);
+        assert_eq!(score("halloyy", "#halloy"), None);
+        assert_eq!(
+            score("", "#halloy"),
+            Some(Score {
+                is_prefix: false,
+                points: 0
+            })
+        );
+    }
+
+    #[test]
+    fn ranking() {
+        // Prefixes first
+        assert!(
+            score("rust", "#rust-offtopic").unwrap()
+                > score("rust", "#learn-rust").unwrap()
+        );
+        // Then consecutive characters and starts of words
+        assert!(
+            score("lr", "#learn-rust").unwrap()
+                < score("lr", "#l-rust").unwrap()
+        );
+        assert!(
+            score("rust", "#learn-rust").unwrap()
+                > score("rust", "#r-u-s-t").unwrap()
+        );
+    }
+
+    #[test]
+    fn rank() {
+        let names = [
+            "#halloy (libera)",
+            "#rust (libera)",
+            "#learn-rust (oftc)",
+            "#rust-offtopic (libera)",
+            "Highlights",
+        ];
+        let candidates = || names.map(|name| (name, name.to_string()));
+
+        assert_eq!(super::rank("", candidates(), 2), names);
+        // Recently used ones before better matches
+        assert_eq!(super::rank("o", candidates(), 0)[0], "#learn-rust (oftc)");
+        assert_eq!(super::rank("o", candidates(), 1)[0], "#halloy (libera)");
+        // Prefixes before recently used ones
+        assert_eq!(
+            super::rank("rust", candidates(), 3),
+            [
+                "#rust (libera)",
+                "#rust-offtopic (libera)",
+                "#learn-rust (oftc)"
+            ]
+        );
+        assert!(super::rank("xyz", candidates(), 5).is_empty());
+    }
+}
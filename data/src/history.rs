@@ -11,7 +11,7 @@ use tokio::fs;
 use tokio::time::Instant;
 
 pub use self::manager::{Manager, Resource};
-pub use self::metadata::{Metadata, ReadMarker};
+pub use self::metadata::{Metadata, ReadMarker, Unread};
 use crate::message::{self, MessageReferences, Source};
 use crate::target::{self, Target};
 use crate::user::Nick;
@@ -19,6 +19,7 @@ use crate::{
     Buffer, Message, Server, buffer, compression, environment, isupport,
 };
 
+pub mod audit;
 pub mod manager;
 pub mod metadata;
 
@@ -187,6 +188,10 @@ pub async fn overwrite(
 
     let latest = &messages[messages.len().saturating_sub(MAX_MESSAGES)..];
 
+    if messages.len() > MAX_MESSAGES {
+        audit::prune(kind, latest[0].server_time).await?;
+    }
+
     let path = path(kind).await?;
     let compressed = compression::compress(&latest)?;
 
@@ -212,6 +217,49 @@ pub async fn append(
     overwrite(kind, &all_messages, read_marker).await
 }
 
+/// Appends `messages` to the history of `kind` on disk without changing
+/// whether it has unread messages
+pub async fn store(kind: &Kind, messages: Vec<Message>) -> Result<(), Error> {
+    let Loaded {
+        messages: mut all_messages,
+        metadata,
+    } = load(kind.clone()).await?;
+
+    let was_read = match metadata.read_marker {
+        Some(read_marker) => metadata
+            .last_triggers_unread
+            .is_none_or(|last| last <= read_marker.date_time()),
+        None => metadata.last_triggers_unread.is_none(),
+    };
+
+    let read_marker = if was_read {
+        metadata.read_marker.max(ReadMarker::latest(&messages))
+    } else {
+        metadata.read_marker
+    };
+
+    messages.into_iter().for_each(|message| {
+        insert_message(&mut all_messages, message);
+    });
+
+    overwrite(kind, &all_messages, read_marker).await
+}
+
+/// Messages of `kind` on disk, deleting its history
+pub async fn take(kind: &Kind) -> Result<Vec<Message>, Error> {
+    let path = path(kind).await?;
+
+    let messages = read_all(&path).await.unwrap_or_default();
+
+    if fs::try_exists(&path).await? {
+        fs::remove_file(path).await?;
+    }
+
+    metadata::delete(kind).await?;
+
+    Ok(messages)
+}
+
 async fn read_all(path: &PathBuf) -> Result<Vec<Message>, Error> {
     let bytes = fs::read(path).await?;
     Ok(compression::decompress(&bytes)?)
@@ -229,9 +277,53 @@ pub async fn dir_path() -> Result<PathBuf, Error> {
     Ok(history_dir)
 }
 
+/// Files of the histories on disk
+pub async fn paths() -> Result<Vec<PathBuf>, Error> {
+    let mut entries = fs::read_dir(dir_path().await?).await?;
+    let mut paths = vec![];
+
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+
+        if path.to_string_lossy().ends_with(".json.gz") {
+            paths.push(path);
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Messages of the history at `path` along with its kind, if it's of a buffer
+/// of one of `servers`. Only a hash of the kind is in the name of the file, so
+/// it's told from the messages.
+pub async fn read_with_kind(
+    path: &PathBuf,
+    servers: &[Server],
+) -> Result<Option<(Kind, Vec<Message>)>, Error> {
+    let messages = read_all(path).await?;
+
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+
+    let kind = messages.iter().find_map(|message| {
+        servers.iter().find_map(|server| {
+            Kind::from_server_message(server.clone(), message)
+                .filter(|kind| file_name(kind) == name)
+        })
+    });
+
+    Ok(kind.map(|kind| (kind, messages)))
+}
+
 async fn path(kind: &Kind) -> Result<PathBuf, Error> {
     let dir = dir_path().await?;
 
+    Ok(dir.join(file_name(kind)))
+}
+
+fn file_name(kind: &Kind) -> String {
     let name = match kind {
         Kind::Server(server) => format!("{server}"),
         Kind::Channel(server, channel) => {
@@ -246,7 +338,7 @@ async fn path(kind: &Kind) -> Result<PathBuf, Error> {
 
     let hashed_name = seahash::hash(name.as_bytes());
 
-    Ok(dir.join(format!("{hashed_name}.json.gz")))
+    format!("{hashed_name}.json.gz")
 }
 
 #[derive(Debug)]
@@ -259,6 +351,9 @@ pub enum History {
         read_marker: Option<ReadMarker>,
         chathistory_references: Option<MessageReferences>,
         last_seen: HashMap<Nick, DateTime<Utc>>,
+        /// Kept up to date as messages arrive, since most of them are only
+        /// on disk
+        unread: Unread,
     },
     Full {
         kind: Kind,
@@ -279,18 +374,35 @@ impl History {
             read_marker: None,
             chathistory_references: None,
             last_seen: HashMap::new(),
+            unread: Unread::default(),
         }
     }
 
     pub fn update_partial(&mut self, metadata: Metadata) {
         if let Self::Partial {
+            messages,
             max_triggers_unread,
             read_marker,
             chathistory_references,
+            unread,
             ..
         } = self
         {
             *read_marker = (*read_marker).max(metadata.read_marker);
+
+            let stored_unread =
+                if metadata.last_triggers_unread.is_none_or(|last| {
+                    read_marker.is_some_and(|read_marker| {
+                        last <= read_marker.date_time()
+                    })
+                }) {
+                    Unread::default()
+                } else {
+                    metadata.unread
+                };
+
+            // Those received before the metadata loaded aren't on disk yet
+            *unread = Unread::count(messages, *read_marker) + stored_unread;
             *max_triggers_unread =
                 (*max_triggers_unread).max(metadata.last_triggers_unread);
             *chathistory_references = chathistory_references
@@ -324,11 +436,15 @@ impl History {
         if message.triggers_unread() {
             if let History::Partial {
                 max_triggers_unread,
+                read_marker,
+                unread,
                 ..
             } = self
             {
                 *max_triggers_unread =
                     (*max_triggers_unread).max(Some(message.server_time));
+
+                unread.record(&message, *read_marker);
             }
         }
 
@@ -404,12 +520,7 @@ impl History {
                         let read_marker = *read_marker;
                         *last_updated_at = None;
 
-                        if messages.len() > MAX_MESSAGES {
-                            messages.drain(
-                                0..messages.len()
-                                    - (MAX_MESSAGES - TRUNC_COUNT),
-                            );
-                        }
+                        truncate(messages);
 
                         let messages = messages.clone();
 
@@ -456,6 +567,8 @@ impl History {
                 let chathistory_references =
                     metadata::latest_can_reference(&messages);
 
+                let unread = Unread::count(&messages, read_marker);
+
                 *self = Self::Partial {
                     kind: kind.clone(),
                     messages: vec![],
@@ -464,6 +577,7 @@ impl History {
                     max_triggers_unread,
                     chathistory_references,
                     last_seen: last_seen.clone(),
+                    unread,
                 };
 
                 Some(async move {
@@ -529,11 +643,16 @@ impl History {
             History::Partial {
                 max_triggers_unread,
                 read_marker,
+                unread,
                 ..
-            } => (
-                read_marker,
-                max_triggers_unread.map(ReadMarker::from_date_time),
-            ),
+            } => {
+                *unread = Unread::default();
+
+                (
+                    read_marker,
+                    max_triggers_unread.map(ReadMarker::from_date_time),
+                )
+            }
             History::Full {
                 messages,
                 read_marker,
@@ -615,13 +734,36 @@ impl History {
 
     pub fn update_read_marker(&mut self, read_marker: ReadMarker) {
         let stored = match self {
-            History::Partial { read_marker, .. } => read_marker,
+            History::Partial {
+                read_marker: stored,
+                max_triggers_unread,
+                unread,
+                ..
+            } => {
+                // Only counted, so they're all read or none are
+                if max_triggers_unread
+                    .is_none_or(|max| max <= read_marker.date_time())
+                {
+                    *unread = Unread::default();
+                }
+
+                stored
+            }
             History::Full { read_marker, .. } => read_marker,
         };
 
         *stored = (*stored).max(Some(read_marker));
     }
 
+    /// Unread messages, shown for the history of buffers which aren't open
+    /// like `has_unread`
+    pub fn unread(&self) -> Unread {
+        match self {
+            History::Partial { unread, .. } if self.has_unread() => *unread,
+            History::Partial { .. } | History::Full { .. } => Unread::default(),
+        }
+    }
+
     pub fn read_marker(&self) -> Option<ReadMarker> {
         match self {
             History::Partial { read_marker, .. }
@@ -646,12 +788,86 @@ impl History {
         }
     }
 
+    /// Adds a reaction to the message with msgid `id`. Reactions to messages
+    /// that aren't loaded are dropped.
+    pub fn add_reaction(&mut self, id: &str, reaction: message::Reaction) {
+        let (History::Partial {
+            messages,
+            last_updated_at,
+            ..
+        }
+        | History::Full {
+            messages,
+            last_updated_at,
+            ..
+        }) = self;
+
+        let is_added = messages
+            .iter_mut()
+            .rev()
+            .find(|message| message.id.as_deref() == Some(id))
+            .is_some_and(|message| message.add_reaction(reaction));
+
+        if is_added {
+            *last_updated_at = Some(Instant::now());
+        }
+    }
+
     pub fn last_seen(&self) -> HashMap<Nick, DateTime<Utc>> {
         match self {
             History::Partial { last_seen, .. }
             | History::Full { last_seen, .. } => last_seen.clone(),
         }
     }
+
+    pub fn metrics(&self) -> Metrics {
+        match self {
+            History::Partial { messages, .. }
+            | History::Full { messages, .. } => Metrics {
+                messages: messages.len(),
+                bytes: messages.iter().map(Message::approx_size).sum(),
+            },
+        }
+    }
+
+    /// Truncate a full history immediately instead of waiting for the
+    /// next flush, returning the # of messages dropped
+    pub fn trim(&mut self) -> usize {
+        match self {
+            History::Partial { .. } => 0,
+            History::Full { messages, .. } => truncate(messages),
+        }
+    }
+}
+
+/// Approximate in-memory footprint of a history
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Metrics {
+    pub messages: usize,
+    pub bytes: usize,
+}
+
+impl std::ops::Add for Metrics {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            messages: self.messages + other.messages,
+            bytes: self.bytes + other.bytes,
+        }
+    }
+}
+
+/// Drop the oldest messages once [`MAX_MESSAGES`] is exceeded, returning
+/// the # of messages dropped
+fn truncate(messages: &mut Vec<Message>) -> usize {
+    if messages.len() > MAX_MESSAGES {
+        messages
+            .drain(0..messages.len() - (MAX_MESSAGES - TRUNC_COUNT))
+            .count()
+    } else {
+        0
+    }
 }
 
 /// Insert the incoming message into the provided vector, sorted
@@ -790,10 +1006,15 @@ fn has_matching_content(
                 }
                 message::source::server::Kind::ReplyTopic
                 | message::source::server::Kind::ChangeHost
+                | message::source::server::Kind::ChangeNick
+                | message::source::server::Kind::ChangeMode
                 | message::source::server::Kind::MonitoredOnline
                 | message::source::server::Kind::MonitoredOffline
                 | message::source::server::Kind::StandardReply(_)
-                | message::source::server::Kind::Wallops => (),
+                | message::source::server::Kind::Wallops
+                | message::source::server::Kind::Ctcp
+                | message::source::server::Kind::Bouncer
+                | message::source::server::Kind::Notice => (),
             }
         }
 
@@ -843,6 +1064,53 @@ pub struct View<'a> {
     pub new_messages: Vec<&'a Message>,
     pub max_nick_chars: Option<usize>,
     pub max_prefix_chars: Option<usize>,
+    /// Messages replied to in the view, by msgid
+    pub replied_to: HashMap<&'a str, &'a Message>,
+}
+
+impl View<'_> {
+    /// Where highlights and the backlog divider are among the messages of the
+    /// view. Positions come from message indices rather than layout, so
+    /// they're cheap to keep up to date.
+    pub fn markers(&self) -> Markers {
+        let count = self.old_messages.len() + self.new_messages.len();
+
+        if count == 0 {
+            return Markers::default();
+        }
+
+        let position = |index: usize| index as f32 / count as f32;
+
+        let highlights = self
+            .old_messages
+            .iter()
+            .chain(&self.new_messages)
+            .enumerate()
+            .filter(|(_, message)| {
+                !message.is_echo && message.has_highlight_fragment()
+            })
+            .map(|(index, message)| (message.hash, position(index)))
+            .collect();
+
+        // Shown in the same cases as the divider itself
+        let backlog = (!self.old_messages.is_empty()
+            || !self.has_more_older_messages)
+            .then(|| position(self.old_messages.len()));
+
+        Markers {
+            highlights,
+            backlog,
+        }
+    }
+}
+
+/// Positions in a view of messages, from `0.0` at the top to `1.0` at the
+/// bottom
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Markers {
+    pub highlights: Vec<(message::Hash, f32)>,
+    /// The backlog divider, unread messages follow it to the bottom
+    pub backlog: Option<f32>,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -854,3 +1122,98 @@ pub enum Error {
     #[error(transparent)]
     SerdeJson(#[from] serde_json::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{Content, Fragment, plain};
+
+    fn message(seconds: i64, content: Content) -> Message {
+        Message::test(
+            DateTime::from_timestamp(seconds, 0).unwrap(),
+            message::Target::Server {
+                source: Source::Server(None),
+            },
+            content,
+        )
+    }
+
+    #[test]
+    fn markers() {
+        let messages = (0..4)
+            .map(|seconds| {
+                if seconds == 1 {
+                    message(
+                        seconds,
+                        Content::Fragments(vec![Fragment::HighlightMatch(
+                            "halloy".to_string(),
+                        )]),
+                    )
+                } else {
+                    message(seconds, plain(format!("{seconds}")))
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let view = |split: usize, has_more_older_messages: bool| View {
+            total: messages.len(),
+            has_more_older_messages,
+            has_more_newer_messages: false,
+            old_messages: messages[..split].iter().collect(),
+            new_messages: messages[split..].iter().collect(),
+            max_nick_chars: None,
+            max_prefix_chars: None,
+            replied_to: HashMap::new(),
+        };
+
+        assert_eq!(
+            view(2, true).markers(),
+            Markers {
+                highlights: vec![(messages[1].hash, 0.25)],
+                backlog: Some(0.5),
+            }
+        );
+
+        // The divider is above the view
+        assert_eq!(view(0, true).markers().backlog, None);
+        assert_eq!(view(0, false).markers().backlog, Some(0.0));
+    }
+
+    #[test]
+    fn unread() {
+        let action = |seconds: i64, content: Content| Message {
+            target: message::Target::Server {
+                source: Source::Action(None),
+            },
+            ..message(seconds, content)
+        };
+
+        let mut history = History::partial(Kind::Logs);
+        history.update_read_marker(ReadMarker::from_date_time(
+            DateTime::from_timestamp(1, 0).unwrap(),
+        ));
+
+        history.add_message(action(0, plain("read".to_string())));
+        history.add_message(action(2, plain("unread".to_string())));
+        history.add_message(action(
+            3,
+            Content::Fragments(vec![Fragment::HighlightMatch(
+                "halloy".to_string(),
+            )]),
+        ));
+        // Doesn't trigger unread
+        history.add_message(message(4, plain("server".to_string())));
+
+        assert_eq!(
+            history.unread(),
+            Unread {
+                messages: 2,
+                highlights: 1,
+            }
+        );
+
+        history.mark_as_read();
+
+        assert_eq!(history.unread(), Unread::default());
+    }
+}
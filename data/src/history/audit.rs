@@ -0,0 +1,312 @@
+//! Topic changes, mode changes, kicks and bans of a channel, kept apart from
+//! its messages so they can be looked through without scrolling
+use std::fmt;
+use std::path::PathBuf;
+
+use chrono::{DateTime, Utc};
+use irc::proto::Command;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::history::{Error, Kind, dir_path};
+use crate::message::{Encoded, server_time};
+use crate::user::Nick;
+use crate::{isupport, mode, target};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub server_time: DateTime<Utc>,
+    /// Who made the change, `None` when it was the server
+    pub actor: Option<Nick>,
+    pub change: Change,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Change {
+    Topic { topic: String },
+    Mode { modes: String },
+    Ban { mask: String, is_set: bool },
+    Kick { nick: Nick, reason: Option<String> },
+}
+
+/// Types of changes entries can be filtered by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Topic,
+    Mode,
+    Ban,
+    Kick,
+}
+
+impl Category {
+    pub const ALL: &'static [Self] =
+        &[Self::Topic, Self::Mode, Self::Ban, Self::Kick];
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Category::Topic => write!(f, "Topics"),
+            Category::Mode => write!(f, "Modes"),
+            Category::Ban => write!(f, "Bans"),
+            Category::Kick => write!(f, "Kicks"),
+        }
+    }
+}
+
+impl Change {
+    pub fn category(&self) -> Category {
+        match self {
+            Change::Topic { .. } => Category::Topic,
+            Change::Mode { .. } => Category::Mode,
+            Change::Ban { .. } => Category::Ban,
+            Change::Kick { .. } => Category::Kick,
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Topic { topic } if topic.is_empty() => {
+                write!(f, "cleared the topic")
+            }
+            Change::Topic { topic } => write!(f, "set the topic to {topic}"),
+            Change::Mode { modes } => write!(f, "set mode {modes}"),
+            Change::Ban { mask, is_set: true } => write!(f, "banned {mask}"),
+            Change::Ban {
+                mask,
+                is_set: false,
+            } => write!(f, "unbanned {mask}"),
+            Change::Kick { nick, reason } => {
+                write!(f, "kicked {nick}")?;
+
+                if let Some(reason) = reason {
+                    write!(f, " ({reason})")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Entries for the changes made by `message` to a channel, if it's one that's
+/// audited
+pub fn entries(
+    message: &Encoded,
+    chantypes: &[char],
+    statusmsg: &[char],
+    casemapping: isupport::CaseMap,
+) -> Option<(target::Channel, Vec<Entry>)> {
+    let entry = |change| Entry {
+        server_time: server_time(message),
+        actor: message.user().map(|user| user.nickname().to_owned()),
+        change,
+    };
+    let parse_channel = |channel: &str| {
+        target::Channel::parse(channel, chantypes, statusmsg, casemapping).ok()
+    };
+
+    match &message.command {
+        Command::TOPIC(channel, Some(topic)) => Some((
+            parse_channel(channel)?,
+            vec![entry(Change::Topic {
+                topic: topic.clone(),
+            })],
+        )),
+        Command::KICK(channel, nick, reason) => Some((
+            parse_channel(channel)?,
+            vec![entry(Change::Kick {
+                nick: Nick::from(nick.as_str()),
+                reason: reason.clone(),
+            })],
+        )),
+        Command::MODE(channel, Some(modes), args) => {
+            let channel = parse_channel(channel)?;
+            let args = args.as_deref().unwrap_or_default();
+
+            let (bans, others): (Vec<_>, Vec<_>) =
+                mode::parse::<mode::Channel>(modes, args)
+                    .into_iter()
+                    .partition(|mode| *mode.value() == mode::Channel::Ban);
+
+            // The whole mode change is kept, bans are also listed on their own
+            let changes = (!others.is_empty())
+                .then(|| Change::Mode {
+                    modes: std::iter::once(modes.as_str())
+                        .chain(args.iter().map(String::as_str))
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                })
+                .into_iter()
+                .chain(bans.into_iter().filter_map(|ban| {
+                    Some(Change::Ban {
+                        mask: ban.arg()?.to_string(),
+                        is_set: matches!(ban, mode::Mode::Add(..)),
+                    })
+                }))
+                .map(entry)
+                .collect::<Vec<_>>();
+
+            (!changes.is_empty()).then_some((channel, changes))
+        }
+        _ => None,
+    }
+}
+
+pub async fn load(kind: &Kind) -> Result<Vec<Entry>, Error> {
+    let Some(path) = path(kind).await? else {
+        return Ok(vec![]);
+    };
+
+    if let Ok(bytes) = fs::read(path).await {
+        Ok(serde_json::from_slice(&bytes).unwrap_or_default())
+    } else {
+        Ok(vec![])
+    }
+}
+
+/// Adds `entries` to the log of `kind` on disk, skipping those it already has
+/// (e.g. from chathistory replaying them)
+pub async fn append(kind: &Kind, entries: Vec<Entry>) -> Result<(), Error> {
+    let mut all_entries = load(kind).await?;
+
+    for entry in entries {
+        if !all_entries.contains(&entry) {
+            all_entries.push(entry);
+        }
+    }
+
+    all_entries.sort_by_key(|entry| entry.server_time);
+
+    save(kind, &all_entries).await
+}
+
+/// Drops entries older than `before`, along with the messages they were
+/// among
+pub async fn prune(kind: &Kind, before: DateTime<Utc>) -> Result<(), Error> {
+    let mut entries = load(kind).await?;
+    let len = entries.len();
+
+    entries.retain(|entry| entry.server_time >= before);
+
+    if entries.len() < len {
+        save(kind, &entries).await?;
+    }
+
+    Ok(())
+}
+
+async fn save(kind: &Kind, entries: &[Entry]) -> Result<(), Error> {
+    let Some(path) = path(kind).await? else {
+        return Ok(());
+    };
+
+    fs::write(path, serde_json::to_vec(entries)?).await?;
+
+    Ok(())
+}
+
+/// Only channels have an audit log
+async fn path(kind: &Kind) -> Result<Option<PathBuf>, Error> {
+    let Kind::Channel(server, channel) = kind else {
+        return Ok(None);
+    };
+
+    let dir = dir_path().await?;
+
+    let name = format!("{server}channel{}-audit", channel.as_normalized_str());
+
+    let hashed_name = seahash::hash(name.as_bytes());
+
+    Ok(Some(dir.join(format!("{hashed_name}.json"))))
+}
+
+#[cfg(test)]
+mod tests {
+    use irc::proto;
+
+    use super::*;
+
+    fn audited(raw: &str) -> Option<(target::Channel, Vec<Entry>)> {
+        let encoded = Encoded::from(
+            proto::parse::message(&format!("{raw}\r\n")).unwrap(),
+        );
+
+        super::entries(
+            &encoded,
+            &['#'],
+            &['@', '+'],
+            isupport::CaseMap::default(),
+        )
+    }
+
+    fn changes(raw: &str) -> Vec<Change> {
+        audited(raw)
+            .map(|(_, entries)| {
+                entries.into_iter().map(|entry| entry.change).collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn audited_changes() {
+        let (channel, entries) =
+            audited(":alice!a@host TOPIC #halloy :new topic").unwrap();
+        assert_eq!(channel.as_str(), "#halloy");
+        assert_eq!(entries[0].actor, Some(Nick::from("alice")));
+        assert_eq!(
+            entries[0].change,
+            Change::Topic {
+                topic: "new topic".to_string()
+            }
+        );
+
+        assert_eq!(
+            changes(":alice!a@host KICK #halloy bob :spam"),
+            vec![Change::Kick {
+                nick: Nick::from("bob"),
+                reason: Some("spam".to_string())
+            }]
+        );
+
+        // Bans are listed on their own
+        assert_eq!(
+            changes(":alice!a@host MODE #halloy +b-b *!*@bad *!*@good"),
+            vec![
+                Change::Ban {
+                    mask: "*!*@bad".to_string(),
+                    is_set: true
+                },
+                Change::Ban {
+                    mask: "*!*@good".to_string(),
+                    is_set: false
+                },
+            ]
+        );
+        assert_eq!(
+            changes(":alice!a@host MODE #halloy +ob bob *!*@bad"),
+            vec![
+                Change::Mode {
+                    modes: "+ob bob *!*@bad".to_string()
+                },
+                Change::Ban {
+                    mask: "*!*@bad".to_string(),
+                    is_set: true
+                },
+            ]
+        );
+        assert_eq!(
+            changes("MODE #halloy +nt"),
+            vec![Change::Mode {
+                modes: "+nt".to_string()
+            }]
+        );
+
+        // Our own modes and messages aren't audited
+        assert!(audited(":kai MODE kai +i").is_none());
+        assert!(audited(":alice!a@host PRIVMSG #halloy :hi").is_none());
+    }
+}
@@ -1,18 +1,32 @@
 use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use futures::future::BoxFuture;
 use futures::{Future, FutureExt, future};
 use tokio::time::Instant;
 
-use crate::history::{self, History, MessageReferences, ReadMarker};
+use crate::client::server_info;
+use crate::config::history::ClosedBuffers;
+use crate::history::{self, History, MessageReferences, ReadMarker, audit};
+use crate::ignore::{self, Ignores};
 use crate::message::{self, Limit};
+use crate::scheduled::{self, Scheduled};
+use crate::stream::disconnect;
 use crate::target::{self, Target};
 use crate::user::Nick;
 use crate::{
-    Config, Input, Server, User, buffer, config, input, isupport, server,
+    Config, Input, Server, User, bouncer, buffer, config, input, isupport,
+    server,
 };
 
+/// How long the read marker of a history which isn't loaded can change
+/// before it's written to disk, so a burst of updates is a single write
+const FLUSH_READ_MARKER_AFTER: Duration = Duration::from_secs(5);
+/// Messages sent from inputs are saved at most this often, since the whole
+/// input history is written each time
+const SAVE_SENT_INPUT_AFTER: Duration = Duration::from_secs(10);
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Resource {
     pub kind: history::Kind,
@@ -46,6 +60,8 @@ pub enum Message {
         Result<Option<history::ReadMarker>, history::Error>,
     ),
     Flushed(history::Kind, Result<(), history::Error>),
+    AuditFlushed(history::Kind, Result<(), history::Error>),
+    Stored(history::Kind, Result<(), history::Error>),
     Exited(
         Vec<(
             history::Kind,
@@ -53,6 +69,9 @@ pub enum Message {
         )>,
     ),
     SentMessageUpdated(history::Kind, history::ReadMarker),
+    Merged(history::Kind, Result<Vec<crate::Message>, history::Error>),
+    ScheduledSaved(Result<(), scheduled::Error>),
+    SentInputSaved(Result<(), input::SentError>),
 }
 
 pub enum Event {
@@ -60,15 +79,118 @@ pub enum Event {
     Closed(history::Kind, Option<history::ReadMarker>),
     Exited(Vec<(history::Kind, Option<history::ReadMarker>)>),
     SentMessageUpdated(history::Kind, history::ReadMarker),
+    /// Messages of a query with a bouncer service, to be recorded in the
+    /// server buffer
+    Merged(Server, Vec<crate::Message>),
 }
 
 #[derive(Debug, Default)]
 pub struct Manager {
     resources: HashSet<Resource>,
     data: Data,
+    ignores: Ignores,
+    /// Audit entries not yet written to disk
+    audit: HashMap<history::Kind, Vec<audit::Entry>>,
+    /// Audit logs being written to disk, which aren't written to again until
+    /// done
+    audit_flushing: HashSet<history::Kind>,
+    scheduled: Scheduled,
+    /// Whether scheduled messages changed since they were last saved
+    scheduled_changed: bool,
+    /// Since when messages sent from inputs changed without being saved
+    sent_input_changed: Option<Instant>,
 }
 
 impl Manager {
+    pub fn new() -> Self {
+        let ignores = Ignores::load().unwrap_or_else(|error| {
+            log::warn!("failed to load ignore list: {error}");
+
+            Ignores::default()
+        });
+        let scheduled = Scheduled::load().unwrap_or_else(|error| {
+            log::warn!("failed to load scheduled messages: {error}");
+
+            Scheduled::default()
+        });
+        let sent_input = input::Sent::load().unwrap_or_else(|error| {
+            log::warn!("failed to load input history: {error}");
+
+            input::Sent::default()
+        });
+
+        Self {
+            data: Data {
+                input: input::Storage::new(sent_input),
+                ..Data::default()
+            },
+            ignores,
+            scheduled,
+            ..Self::default()
+        }
+    }
+
+    pub fn ignores(&self) -> &Ignores {
+        &self.ignores
+    }
+
+    /// Updates the ignore list, returning a future which persists it
+    pub fn ignore(
+        &mut self,
+        server: &Server,
+        user: &User,
+        ignore: bool,
+    ) -> BoxFuture<'static, Result<(), ignore::Error>> {
+        if ignore {
+            self.ignores.ignore(server, user);
+        } else {
+            self.ignores.unignore(server, user);
+        }
+
+        self.ignores.clone().save().boxed()
+    }
+
+    pub fn scheduled(&self) -> &Scheduled {
+        &self.scheduled
+    }
+
+    /// Schedules `text` to be sent to `buffer` at `send_at`, saved on the
+    /// next tick
+    pub fn schedule(
+        &mut self,
+        buffer: buffer::Upstream,
+        send_at: DateTime<Utc>,
+        text: String,
+    ) -> u64 {
+        self.scheduled_changed = true;
+
+        self.scheduled.schedule(buffer, send_at, text)
+    }
+
+    pub fn cancel_scheduled(&mut self, id: u64) -> Option<scheduled::Pending> {
+        let pending = self.scheduled.cancel(id);
+
+        self.scheduled_changed |= pending.is_some();
+
+        pending
+    }
+
+    /// Takes the scheduled messages due at `now`, see [`Scheduled::take_due`]
+    pub fn take_due_scheduled(
+        &mut self,
+        now: DateTime<Utc>,
+        is_connected: impl Fn(&Server) -> bool,
+        when_disconnected: scheduled::WhenDisconnected,
+    ) -> scheduled::Due {
+        let due = self
+            .scheduled
+            .take_due(now, is_connected, when_disconnected);
+
+        self.scheduled_changed |= !due.is_empty();
+
+        due
+    }
+
     pub fn track(
         &mut self,
         new_resources: HashSet<Resource>,
@@ -129,6 +251,21 @@ impl Manager {
             Message::Flushed(kind, Err(error)) => {
                 log::warn!("failed to flush history for {kind}: {error}");
             }
+            Message::AuditFlushed(kind, result) => {
+                if let Err(error) = result {
+                    log::warn!("failed to flush audit log for {kind}: {error}");
+                }
+
+                self.audit_flushing.remove(&kind);
+            }
+            Message::Stored(kind, Ok(())) => {
+                log::debug!("stored message for closed buffer {kind}");
+            }
+            Message::Stored(kind, Err(error)) => {
+                log::warn!(
+                    "failed to store message for closed buffer {kind}: {error}"
+                );
+            }
             Message::UpdatePartial(kind, Ok(metadata)) => {
                 log::debug!("loaded metadata for {kind}");
                 self.data.update_partial(kind, metadata);
@@ -167,13 +304,143 @@ impl Manager {
             Message::SentMessageUpdated(kind, read_marker) => {
                 return Some(Event::SentMessageUpdated(kind, read_marker));
             }
+            Message::Merged(kind, Ok(messages)) => {
+                if let history::Kind::Query(server, query) = kind {
+                    log::debug!(
+                        "merged {} messages of {query} into server on {server}",
+                        messages.len()
+                    );
+
+                    let target = bouncer::target(Nick::from(query.as_str()));
+
+                    return Some(Event::Merged(
+                        server,
+                        messages
+                            .into_iter()
+                            .map(|message| message.with_target(target.clone()))
+                            .collect(),
+                    ));
+                }
+            }
+            Message::Merged(kind, Err(error)) => {
+                log::warn!("failed to merge history for {kind}: {error}");
+            }
+            Message::ScheduledSaved(Ok(())) => {
+                log::debug!("scheduled messages saved");
+            }
+            Message::ScheduledSaved(Err(error)) => {
+                log::warn!("failed to save scheduled messages: {error}");
+            }
+            Message::SentInputSaved(Ok(())) => {
+                log::debug!("input history saved");
+            }
+            Message::SentInputSaved(Err(error)) => {
+                log::warn!("failed to save input history: {error}");
+            }
         }
 
         None
     }
 
     pub fn tick(&mut self, now: Instant) -> Vec<BoxFuture<'static, Message>> {
-        self.data.flush_all(now)
+        let mut tasks = self.data.flush_all(now);
+
+        tasks.extend(self.data.settled_read_markers(now).into_iter().map(
+            |(kind, read_marker)| update_read_marker(kind, read_marker).boxed(),
+        ));
+
+        let flushable = self
+            .audit
+            .keys()
+            .filter(|kind| !self.audit_flushing.contains(*kind))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for kind in flushable {
+            let Some(entries) = self.audit.remove(&kind) else {
+                continue;
+            };
+
+            self.audit_flushing.insert(kind.clone());
+
+            tasks.push(
+                async move {
+                    let result = audit::append(&kind, entries).await;
+
+                    Message::AuditFlushed(kind, result)
+                }
+                .boxed(),
+            );
+        }
+
+        if self.scheduled_changed {
+            self.scheduled_changed = false;
+
+            tasks.push(
+                self.scheduled
+                    .clone()
+                    .save()
+                    .map(Message::ScheduledSaved)
+                    .boxed(),
+            );
+        }
+
+        if self.sent_input_changed.is_some_and(|since| {
+            now.duration_since(since) >= SAVE_SENT_INPUT_AFTER
+        }) {
+            self.sent_input_changed = None;
+
+            tasks.push(
+                self.data
+                    .input
+                    .sent()
+                    .clone()
+                    .save()
+                    .map(Message::SentInputSaved)
+                    .boxed(),
+            );
+        }
+
+        tasks
+    }
+
+    /// Records changes to a channel in its audit log, written to disk on the
+    /// next tick
+    pub fn record_audit(
+        &mut self,
+        server: Server,
+        channel: target::Channel,
+        entries: Vec<audit::Entry>,
+    ) {
+        self.audit
+            .entry(history::Kind::Channel(server, channel))
+            .or_default()
+            .extend(entries);
+    }
+
+    /// Audit log of a channel, including entries not yet written to disk
+    pub fn audit_log(
+        &self,
+        server: Server,
+        channel: target::Channel,
+    ) -> impl Future<Output = Result<Vec<audit::Entry>, history::Error>> + use<>
+    {
+        let kind = history::Kind::Channel(server, channel);
+        let pending = self.audit.get(&kind).cloned().unwrap_or_default();
+
+        async move {
+            let mut entries = audit::load(&kind).await?;
+
+            for entry in pending {
+                if !entries.contains(&entry) {
+                    entries.push(entry);
+                }
+            }
+
+            entries.sort_by_key(|entry| entry.server_time);
+
+            Ok(entries)
+        }
     }
 
     pub fn close(
@@ -190,14 +457,67 @@ impl Manager {
         )
     }
 
+    /// Takes the history of `query`, a bouncer service, to be shown in the
+    /// server buffer instead
+    pub fn merge_into_server(
+        &mut self,
+        server: &Server,
+        query: &target::Query,
+    ) -> impl Future<Output = Message> + use<> {
+        let kind = history::Kind::Query(server.clone(), query.clone());
+        let history = self.data.map.remove(&kind);
+
+        async move {
+            let result = async {
+                if let Some(history) = history {
+                    history.close(false).await?;
+                }
+
+                history::take(&kind).await
+            }
+            .await;
+
+            Message::Merged(kind, result)
+        }
+    }
+
     pub fn exit(
         &mut self,
         mark_partial_as_read: bool,
         mark_full_as_read: bool,
     ) -> impl Future<Output = Message> + use<> {
-        let map = std::mem::take(&mut self.data).map;
+        let Data {
+            map,
+            pending_read_markers,
+            input,
+        } = std::mem::take(&mut self.data);
+        let audit = std::mem::take(&mut self.audit);
+        let sent_input =
+            self.sent_input_changed.take().map(|_| input.sent().clone());
 
         async move {
+            if let Some(sent_input) = sent_input {
+                if let Err(error) = sent_input.save().await {
+                    log::warn!("failed to save input history: {error}");
+                }
+            }
+
+            for (kind, entries) in audit {
+                if let Err(error) = audit::append(&kind, entries).await {
+                    log::warn!("failed to flush audit log for {kind}: {error}");
+                }
+            }
+
+            for (kind, pending) in pending_read_markers {
+                if let Err(error) =
+                    history::metadata::update(&kind, &pending.read_marker).await
+                {
+                    log::warn!(
+                        "failed to update read marker for {kind}: {error}"
+                    );
+                }
+            }
+
             let tasks = map.into_iter().map(|(kind, state)| {
                 match state {
                     History::Partial { .. } => {
@@ -220,6 +540,7 @@ impl Manager {
         chantypes: &[char],
         statusmsg: &[char],
         casemapping: isupport::CaseMap,
+        bouncer_services: &[String],
         config: &Config,
     ) -> Vec<BoxFuture<'static, Message>> {
         let mut tasks = vec![];
@@ -233,19 +554,33 @@ impl Manager {
             config,
         ) {
             for message in messages {
+                // Messages to bouncer services are shown in the server buffer
+                let message = match &message.target {
+                    message::Target::Query { query, .. }
+                        if bouncer::is_service(
+                            query.as_str(),
+                            bouncer_services,
+                            casemapping,
+                        ) =>
+                    {
+                        let target =
+                            bouncer::target(Nick::from(query.as_str()));
+
+                        message.with_target(target)
+                    }
+                    _ => message,
+                };
+
                 if config.buffer.mark_as_read.on_message_sent {
                     if let Some(kind) = history::Kind::from_server_message(
                         input.server().clone(),
                         &message,
                     ) {
-                        tasks.extend(
-                            self.update_read_marker(
-                                kind,
-                                history::ReadMarker::from_date_time(
-                                    message.server_time,
-                                ),
-                            )
-                            .map(futures::FutureExt::boxed),
+                        self.update_read_marker(
+                            kind,
+                            history::ReadMarker::from_date_time(
+                                message.server_time,
+                            ),
                         );
                     }
                 }
@@ -266,6 +601,7 @@ impl Manager {
         text: String,
     ) {
         self.data.input.record(buffer, text);
+        self.sent_input_changed.get_or_insert_with(Instant::now);
     }
 
     pub fn record_draft(&mut self, raw_input: input::RawInput) {
@@ -276,6 +612,21 @@ impl Manager {
         self.data.input.store_text(raw_input);
     }
 
+    pub fn record_compose(
+        &mut self,
+        buffer: &buffer::Upstream,
+        compose: input::Compose,
+    ) {
+        self.data.input.store_compose(buffer, compose);
+    }
+
+    pub fn take_compose(
+        &mut self,
+        buffer: &buffer::Upstream,
+    ) -> Option<input::Compose> {
+        self.data.input.take_compose(buffer)
+    }
+
     pub fn record_message(
         &mut self,
         server: &Server,
@@ -285,6 +636,104 @@ impl Manager {
             .and_then(|kind| self.data.add_message(kind, message))
     }
 
+    /// Records a received `message` like [`Manager::record_message`], unless
+    /// its buffer was closed. What happens then is up to `closed_buffers`.
+    /// PRIVMSGs and NOTICEs look the same once received, so `is_privmsg`
+    /// tells them apart.
+    pub fn record_received(
+        &mut self,
+        server: &Server,
+        message: crate::Message,
+        is_privmsg: bool,
+        is_joined: impl Fn(&target::Channel) -> bool,
+        closed_buffers: ClosedBuffers,
+    ) -> Option<BoxFuture<'static, Message>> {
+        let message = self.route_notice(server, message);
+        let kind =
+            history::Kind::from_server_message(server.clone(), &message)?;
+
+        match self.route(&kind, &message, is_privmsg, is_joined, closed_buffers)
+        {
+            Route::Record => {
+                self.data.add_message(kind, message).map(FutureExt::boxed)
+            }
+            Route::Store => Some(
+                async move {
+                    let result = history::store(&kind, vec![message]).await;
+
+                    Message::Stored(kind, result)
+                }
+                .boxed(),
+            ),
+            Route::Discard => {
+                log::debug!("discarded message for closed buffer {kind}");
+
+                None
+            }
+        }
+    }
+
+    /// Notices of users we don't have a query with are shown in the server
+    /// buffer, rather than opening one
+    fn route_notice(
+        &self,
+        server: &Server,
+        message: crate::Message,
+    ) -> crate::Message {
+        match &message.target {
+            message::Target::Query {
+                query,
+                source: message::Source::User(user),
+            } if message.is_notice
+                && !message.is_echo
+                && !self.data.map.contains_key(&history::Kind::Query(
+                    server.clone(),
+                    query.clone(),
+                )) =>
+            {
+                let target =
+                    message::Target::notice(Some(user.nickname().to_owned()));
+
+                message.with_target(target)
+            }
+            _ => message,
+        }
+    }
+
+    /// A buffer is closed when its history isn't open. Channel buffers stay
+    /// open while we're in the channel, and only our own messages are kept
+    /// out of closed queries so anyone else can still open one.
+    fn route(
+        &self,
+        kind: &history::Kind,
+        message: &crate::Message,
+        is_privmsg: bool,
+        is_joined: impl Fn(&target::Channel) -> bool,
+        closed_buffers: ClosedBuffers,
+    ) -> Route {
+        let is_closed = !self.data.map.contains_key(kind)
+            && match kind {
+                history::Kind::Channel(_, channel) => !is_joined(channel),
+                history::Kind::Query(_, _) => message.is_echo,
+                history::Kind::Server(_)
+                | history::Kind::Logs
+                | history::Kind::Highlights => false,
+            };
+
+        match (is_closed, closed_buffers) {
+            (false, _) => Route::Record,
+            (true, ClosedBuffers::Discard) => Route::Discard,
+            (true, ClosedBuffers::Store) => Route::Store,
+            (true, ClosedBuffers::Reopen) => {
+                if is_privmsg {
+                    Route::Record
+                } else {
+                    Route::Store
+                }
+            }
+        }
+    }
+
     pub fn record_log(
         &mut self,
         record: crate::log::Record,
@@ -300,12 +749,15 @@ impl Manager {
         self.data.add_message(history::Kind::Highlights, message)
     }
 
-    pub fn update_read_marker<T: Into<history::Kind>>(
+    /// Read markers of histories which aren't loaded are written to disk on
+    /// a later tick, once they've settled
+    pub fn update_read_marker(
         &mut self,
-        kind: T,
+        kind: impl Into<history::Kind>,
         read_marker: history::ReadMarker,
-    ) -> Option<impl Future<Output = Message> + use<T>> {
-        self.data.update_read_marker(kind, read_marker)
+    ) {
+        self.data
+            .update_read_marker(kind.into(), read_marker, Instant::now());
     }
 
     pub fn load_metadata(
@@ -342,13 +794,27 @@ impl Manager {
         self.data.can_mark_as_read(kind)
     }
 
+    pub fn metrics(
+        &self,
+    ) -> impl Iterator<Item = (&history::Kind, history::Metrics)> {
+        self.data
+            .map
+            .iter()
+            .map(|(kind, history)| (kind, history.metrics()))
+    }
+
+    pub fn trim(&mut self) -> usize {
+        self.data.map.values_mut().map(History::trim).sum()
+    }
+
     pub fn get_messages(
         &self,
         kind: &history::Kind,
         limit: Option<Limit>,
         buffer_config: &config::Buffer,
     ) -> Option<history::View<'_>> {
-        self.data.history_view(kind, limit, buffer_config)
+        self.data
+            .history_view(kind, limit, buffer_config, &self.ignores)
     }
 
     pub fn get_last_seen(
@@ -377,6 +843,22 @@ impl Manager {
             .collect::<Vec<_>>()
     }
 
+    pub fn get_unique_channels(
+        &self,
+        server: &Server,
+    ) -> Vec<&target::Channel> {
+        self.data
+            .map
+            .keys()
+            .filter_map(|kind| match kind {
+                history::Kind::Channel(s, channel) => {
+                    (s == server).then_some(channel)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+    }
+
     pub fn server_kinds(&self, server: Server) -> Vec<history::Kind> {
         self.data
             .map
@@ -409,6 +891,14 @@ impl Manager {
         self.data.map.get(kind).is_some_and(History::has_unread)
     }
 
+    pub fn unread(&self, kind: &history::Kind) -> history::Unread {
+        self.data
+            .map
+            .get(kind)
+            .map(History::unread)
+            .unwrap_or_default()
+    }
+
     pub fn read_marker(
         &self,
         kind: &history::Kind,
@@ -458,9 +948,9 @@ impl Manager {
             Broadcast::ConnectionFailed { error } => {
                 message::broadcast::connection_failed(error, sent_time)
             }
-            Broadcast::Disconnected { error } => {
+            Broadcast::Disconnected { error, reason } => {
                 message::broadcast::disconnected(
-                    channels, queries, error, sent_time,
+                    channels, queries, error, reason, sent_time,
                 )
             }
             Broadcast::Reconnected => {
@@ -559,6 +1049,29 @@ impl Manager {
                     )
                 }
             }
+            Broadcast::UsersResynced {
+                channel,
+                removed,
+                added,
+            } => message::broadcast::users_resynced(
+                channel, &removed, &added, sent_time,
+            ),
+            Broadcast::ServerInfo { report } => {
+                message::broadcast::server_info(&report, sent_time)
+            }
+            Broadcast::TopicHistory { channel, topics } => {
+                message::broadcast::topic_history(channel, &topics, sent_time)
+            }
+            Broadcast::Monitored { users, online } => {
+                message::broadcast::monitored(
+                    queries, &users, online, sent_time,
+                )
+            }
+            Broadcast::MonitorListTruncated { limit, left_out } => {
+                message::broadcast::monitor_list_truncated(
+                    limit, &left_out, sent_time,
+                )
+            }
         };
 
         messages
@@ -579,6 +1092,41 @@ impl Manager {
     ) {
         self.data.hide_preview(&kind.into(), message, url);
     }
+
+    pub fn add_reaction(
+        &mut self,
+        kind: impl Into<history::Kind>,
+        id: &str,
+        reaction: message::Reaction,
+    ) {
+        if let Some(history) = self.data.map.get_mut(&kind.into()) {
+            history.add_reaction(id, reaction);
+        }
+    }
+}
+
+/// Messages in `all` replied to by `messages`, by msgid. Replies to messages
+/// that aren't found are shown as regular messages.
+fn replied_to<'a>(
+    all: &[&'a crate::Message],
+    messages: &[&'a crate::Message],
+) -> HashMap<&'a str, &'a crate::Message> {
+    let ids = messages
+        .iter()
+        .filter_map(|message| message.reply_to.as_deref())
+        .collect::<HashSet<_>>();
+
+    if ids.is_empty() {
+        return HashMap::new();
+    }
+
+    all.iter()
+        .filter_map(|message| {
+            let id = message.id.as_deref()?;
+
+            ids.contains(id).then_some((id, *message))
+        })
+        .collect()
 }
 
 fn with_limit<'a>(
@@ -599,12 +1147,29 @@ fn with_limit<'a>(
     }
 }
 
+/// Where a received message goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    Record,
+    Store,
+    Discard,
+}
+
 #[derive(Debug, Default)]
 struct Data {
     map: HashMap<history::Kind, History>,
+    /// Read markers of histories which aren't loaded, not yet written to disk
+    pending_read_markers: HashMap<history::Kind, PendingReadMarker>,
     input: input::Storage,
 }
 
+#[derive(Debug, Clone, Copy)]
+struct PendingReadMarker {
+    read_marker: history::ReadMarker,
+    /// When it first changed since it was last written
+    since: Instant,
+}
+
 impl Data {
     fn load_full(&mut self, kind: history::Kind, data: history::Loaded) {
         use std::collections::hash_map;
@@ -660,12 +1225,16 @@ impl Data {
             },
             hash_map::Entry::Vacant(entry) => {
                 let last_seen = history::get_last_seen(&messages);
+                let pending = self
+                    .pending_read_markers
+                    .remove(&kind)
+                    .map(|pending| pending.read_marker);
 
                 entry.insert(History::Full {
                     kind,
                     messages,
                     last_updated_at: None,
-                    read_marker: metadata.read_marker,
+                    read_marker: metadata.read_marker.max(pending),
                     last_seen,
                 });
             }
@@ -683,6 +1252,7 @@ impl Data {
         kind: &history::Kind,
         limit: Option<Limit>,
         buffer_config: &config::Buffer,
+        ignores: &Ignores,
     ) -> Option<history::View> {
         let History::Full {
             messages,
@@ -699,6 +1269,22 @@ impl Data {
             .iter()
             .filter(|message| match message.target.source() {
                 message::Source::Server(Some(source)) => {
+                    // Activity before a nick change counts for the new nick
+                    if let (
+                        message::source::server::Kind::ChangeNick,
+                        Some(seen),
+                        Some(new_nick),
+                    ) = (
+                        source.kind(),
+                        source
+                            .nick()
+                            .and_then(|nick| last_seen.get(nick))
+                            .copied(),
+                        source.new_nick(),
+                    ) {
+                        last_seen.insert(new_nick.clone(), seen);
+                    }
+
                     if let Some(server_message) =
                         buffer_config.server_messages.get(source)
                     {
@@ -739,6 +1325,20 @@ impl Data {
                     true
                 }
                 crate::message::Source::User(message_user) => {
+                    let server = match &message.target {
+                        message::Target::Highlights { server, .. } => {
+                            Some(server)
+                        }
+                        _ => kind.server(),
+                    };
+
+                    // Hide messages already received from ignored users
+                    if server.is_some_and(|server| {
+                        ignores.is_ignored(server, message_user)
+                    }) {
+                        return false;
+                    }
+
                     last_seen.insert(
                         message_user.nickname().to_owned(),
                         message.server_time,
@@ -830,7 +1430,8 @@ impl Data {
         let first_without_limit = filtered.first().copied();
         let last_without_limit = filtered.last().copied();
 
-        let limited = with_limit(limit, filtered.into_iter());
+        let limited = with_limit(limit, filtered.iter().copied());
+        let replied_to = replied_to(&filtered, &limited);
 
         let first_with_limit = limited.first();
         let last_with_limit = limited.last();
@@ -872,6 +1473,7 @@ impl Data {
             new_messages: new.to_vec(),
             max_nick_chars,
             max_prefix_chars,
+            replied_to,
         })
     }
 
@@ -894,9 +1496,13 @@ impl Data {
                 })
             }
             hash_map::Entry::Vacant(entry) => {
-                let _ = entry
-                    .insert(History::partial(kind.clone()))
-                    .add_message(message);
+                let history = entry.insert(History::partial(kind.clone()));
+
+                if let Some(pending) = self.pending_read_markers.remove(&kind) {
+                    history.update_read_marker(pending.read_marker);
+                }
+
+                let _ = history.add_message(message);
 
                 Some(
                     async move {
@@ -911,31 +1517,49 @@ impl Data {
         }
     }
 
-    fn update_read_marker<T: Into<history::Kind>>(
+    fn update_read_marker(
         &mut self,
-        kind: T,
+        kind: history::Kind,
         read_marker: history::ReadMarker,
-    ) -> Option<impl Future<Output = Message> + use<T>> {
-        use std::collections::hash_map;
-
-        let kind = kind.into();
+        now: Instant,
+    ) {
+        if let Some(history) = self.map.get_mut(&kind) {
+            history.update_read_marker(read_marker);
+        } else {
+            self.pending_read_markers
+                .entry(kind)
+                .and_modify(|pending| {
+                    pending.read_marker = pending.read_marker.max(read_marker);
+                })
+                .or_insert(PendingReadMarker {
+                    read_marker,
+                    since: now,
+                });
+        }
+    }
 
-        match self.map.entry(kind.clone()) {
-            hash_map::Entry::Occupied(mut entry) => {
-                entry.get_mut().update_read_marker(read_marker);
+    /// Pending read markers which are due to be written to disk
+    fn settled_read_markers(
+        &mut self,
+        now: Instant,
+    ) -> Vec<(history::Kind, history::ReadMarker)> {
+        let settled = self
+            .pending_read_markers
+            .iter()
+            .filter(|(_, pending)| {
+                now.duration_since(pending.since) >= FLUSH_READ_MARKER_AFTER
+            })
+            .map(|(kind, _)| kind.clone())
+            .collect::<Vec<_>>();
 
-                None
-            }
-            hash_map::Entry::Vacant(_) => Some(
-                async move {
-                    let updated =
-                        history::metadata::update(&kind, &read_marker).await;
+        settled
+            .into_iter()
+            .filter_map(|kind| {
+                let pending = self.pending_read_markers.remove(&kind)?;
 
-                    Message::UpdateReadMarker(kind, read_marker, updated)
-                }
-                .boxed(),
-            ),
-        }
+                Some((kind, pending.read_marker))
+            })
+            .collect()
     }
 
     fn load_metadata(
@@ -950,7 +1574,11 @@ impl Data {
         match self.map.entry(kind.clone()) {
             hash_map::Entry::Occupied(_) => None,
             hash_map::Entry::Vacant(entry) => {
-                entry.insert(History::partial(kind.clone()));
+                let history = entry.insert(History::partial(kind.clone()));
+
+                if let Some(pending) = self.pending_read_markers.remove(&kind) {
+                    history.update_read_marker(pending.read_marker);
+                }
 
                 Some(
                     async move {
@@ -1040,6 +1668,15 @@ impl Data {
     }
 }
 
+async fn update_read_marker(
+    kind: history::Kind,
+    read_marker: history::ReadMarker,
+) -> Message {
+    let updated = history::metadata::update(&kind, &read_marker).await;
+
+    Message::UpdateReadMarker(kind, read_marker, updated)
+}
+
 fn smart_filter_message(
     message: &crate::Message,
     seconds: &i64,
@@ -1079,6 +1716,7 @@ pub enum Broadcast {
     },
     Disconnected {
         error: Option<String>,
+        reason: Option<disconnect::Reason>,
     },
     Reconnected,
     Quit {
@@ -1105,4 +1743,308 @@ pub enum Broadcast {
         logged_in: bool,
         user_channels: Vec<target::Channel>,
     },
+    UsersResynced {
+        channel: target::Channel,
+        removed: Vec<Nick>,
+        added: Vec<Nick>,
+    },
+    ServerInfo {
+        report: server_info::Report,
+    },
+    TopicHistory {
+        channel: target::Channel,
+        topics: Vec<audit::Entry>,
+    },
+    Monitored {
+        users: Vec<Nick>,
+        online: bool,
+    },
+    MonitorListTruncated {
+        limit: usize,
+        left_out: Vec<String>,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use irc::proto;
+
+    use super::*;
+    use crate::isupport::CaseMap;
+    use crate::message::Encoded;
+    use crate::user::HighlightNicks;
+
+    fn received(raw: &str) -> (crate::Message, bool) {
+        let encoded = Encoded::from(
+            proto::parse::message(&format!("{raw}\r\n")).unwrap(),
+        );
+        let is_privmsg = encoded.is_privmsg();
+
+        let message = crate::Message::received(
+            encoded,
+            Nick::from("kai"),
+            &Server::from("libera"),
+            &HighlightNicks::default(),
+            &Config::default(),
+            |_, _| None,
+            |_| &[],
+            &['#'],
+            &['@', '+'],
+            CaseMap::default(),
+        )
+        .expect(raw);
+
+        (message, is_privmsg)
+    }
+
+    #[test]
+    fn closed_buffers() {
+        use Route::*;
+
+        let manager = Manager::default();
+        let server = Server::from("libera");
+
+        let tests = [
+            (":kai!k@host PRIVMSG #left :hi", [Discard, Store, Record]),
+            (":kai!k@host NOTICE #left :hi", [Discard, Store, Store]),
+            (":alice!a@host PRIVMSG @#left :hi", [Discard, Store, Record]),
+            (":kai!k@host MODE #left +o alice", [Discard, Store, Store]),
+            (":kai!k@host PART #left", [Discard, Store, Store]),
+            (":kai!k@host PRIVMSG alice :hi", [Discard, Store, Record]),
+            (":kai!k@host NOTICE alice :hi", [Discard, Store, Store]),
+            // Only our own messages are kept out of closed queries
+            (":alice!a@host PRIVMSG kai :hi", [Record, Record, Record]),
+            (":alice!a@host NOTICE kai :hi", [Record, Record, Record]),
+            // Still in the channel
+            (
+                ":alice!a@host PRIVMSG #joined :hi",
+                [Record, Record, Record],
+            ),
+            (
+                ":kai!k@host MODE #joined +o alice",
+                [Record, Record, Record],
+            ),
+        ];
+
+        for (raw, expected) in tests {
+            let (message, is_privmsg) = received(raw);
+            let kind =
+                history::Kind::from_server_message(server.clone(), &message)
+                    .expect(raw);
+
+            for (closed_buffers, expected) in [
+                ClosedBuffers::Discard,
+                ClosedBuffers::Store,
+                ClosedBuffers::Reopen,
+            ]
+            .into_iter()
+            .zip(expected)
+            {
+                assert_eq!(
+                    manager.route(
+                        &kind,
+                        &message,
+                        is_privmsg,
+                        |channel| channel.as_str() == "#joined",
+                        closed_buffers,
+                    ),
+                    expected,
+                    "{raw} with {closed_buffers:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn notices() {
+        let mut manager = Manager::default();
+        let server = Server::from("libera");
+        let kind = |message: &crate::Message| {
+            history::Kind::from_server_message(server.clone(), message).unwrap()
+        };
+
+        let route = |manager: &Manager, raw| {
+            kind(&manager.route_notice(&server, received(raw).0))
+        };
+
+        // Without a query
+        assert_eq!(
+            route(&manager, ":NickServ!n@services NOTICE kai :hi"),
+            history::Kind::Server(server.clone())
+        );
+        assert!(matches!(
+            route(&manager, ":NickServ!n@services PRIVMSG kai :hi"),
+            history::Kind::Query(..)
+        ));
+        assert!(matches!(
+            route(&manager, ":kai!k@host NOTICE NickServ :hi"),
+            history::Kind::Query(..)
+        ));
+        assert!(matches!(
+            route(&manager, ":NickServ!n@services NOTICE #halloy :hi"),
+            history::Kind::Channel(..)
+        ));
+        // CTCP replies
+        assert!(matches!(
+            route(&manager, ":alice!a@host NOTICE kai :\u{1}VERSION 1\u{1}"),
+            history::Kind::Query(..)
+        ));
+
+        let (message, _) = received(":NickServ!n@services NOTICE kai :hi");
+        let _ = manager.data.add_message(kind(&message), message);
+
+        // With a query
+        assert!(matches!(
+            route(&manager, ":NickServ!n@services NOTICE kai :hi"),
+            history::Kind::Query(..)
+        ));
+
+        // Server notices
+        assert_eq!(
+            kind(&received(":irc.example.com NOTICE kai :hi").0),
+            history::Kind::Server(server.clone())
+        );
+        assert_eq!(
+            kind(&received(":irc.example.com NOTICE * :hi").0),
+            history::Kind::Server(server.clone())
+        );
+    }
+
+    #[test]
+    fn smart_filter_follows_nick_changes() {
+        let mut manager = Manager::default();
+        let channel = target::Channel::from_str("#halloy", CaseMap::default());
+        let kind =
+            history::Kind::Channel(Server::from("libera"), channel.clone());
+
+        let (spoke, _) = received(":alice!a@host PRIVMSG #halloy :hi");
+        let renamed = message::broadcast::nickname(
+            [channel],
+            [],
+            &Nick::from("alice"),
+            &Nick::from("alicia"),
+            false,
+            spoke.server_time,
+        );
+        let (left, _) = received(":alicia!a@host PART #halloy");
+        let (lurker_left, _) = received(":bob!b@host PART #halloy");
+
+        manager.data.map.insert(
+            kind.clone(),
+            History::Full {
+                kind: kind.clone(),
+                messages: [spoke]
+                    .into_iter()
+                    .chain(renamed)
+                    .chain([left, lurker_left])
+                    .collect(),
+                last_updated_at: None,
+                read_marker: None,
+                last_seen: HashMap::new(),
+            },
+        );
+
+        let mut buffer_config = config::Buffer::default();
+        buffer_config.server_messages.part.smart = Some(180);
+
+        let view = manager.get_messages(&kind, None, &buffer_config).unwrap();
+
+        // alice spoke before becoming alicia, bob didn't
+        let parted = view
+            .old_messages
+            .iter()
+            .chain(&view.new_messages)
+            .filter_map(|message| match message.target.source() {
+                message::Source::Server(Some(source))
+                    if source.kind() == message::source::server::Kind::Part =>
+                {
+                    source.nick().cloned()
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(parted, [Nick::from("alicia")]);
+    }
+
+    #[test]
+    fn replies_and_reactions() {
+        let (original, _) =
+            received("@msgid=a1 :alice!a@host PRIVMSG #joined :hi");
+        let (reply, _) = received(
+            "@msgid=b2;+draft/reply=a1 :kai!k@host PRIVMSG #joined :hello",
+        );
+        let (orphan, _) =
+            received("@+draft/reply=gone :kai!k@host PRIVMSG #joined :what");
+
+        let all = [&original, &reply, &orphan];
+        let replied = replied_to(&all, &[&reply, &orphan]);
+        assert_eq!(replied.len(), 1);
+        assert_eq!(
+            replied.get("a1").map(|message| message.hash),
+            Some(original.hash)
+        );
+
+        let mut history = History::Full {
+            kind: history::Kind::Channel(
+                Server::from("libera"),
+                target::Channel::from_str("#joined", CaseMap::default()),
+            ),
+            messages: vec![original, reply],
+            last_updated_at: None,
+            read_marker: None,
+            last_seen: HashMap::new(),
+        };
+        let reaction = |nick: &str, text: &str| message::Reaction {
+            nick: Nick::from(nick),
+            text: text.to_string(),
+        };
+
+        history.add_reaction("a1", reaction("kai", "👍"));
+        history.add_reaction("a1", reaction("kai", "👍"));
+        history.add_reaction("a1", reaction("bob", "👍"));
+        history.add_reaction("gone", reaction("kai", "🎉"));
+
+        let History::Full { messages, .. } = &history else {
+            unreachable!()
+        };
+        assert_eq!(messages[0].reactions.len(), 2);
+        assert!(messages[1].reactions.is_empty());
+    }
+
+    #[test]
+    fn read_markers_settle() {
+        let mut data = Data::default();
+        let kind = history::Kind::Channel(
+            Server::from("libera"),
+            target::Channel::from_str("#closed", CaseMap::default()),
+        );
+        let read_marker = |seconds| {
+            ReadMarker::from_date_time(
+                DateTime::from_timestamp(seconds, 0).unwrap(),
+            )
+        };
+        let now = Instant::now();
+
+        // In quick succession, and not always newer
+        for (i, seconds) in [1, 3, 2].into_iter().enumerate() {
+            data.update_read_marker(
+                kind.clone(),
+                read_marker(seconds),
+                now + Duration::from_millis(i as u64 * 100),
+            );
+        }
+
+        assert!(
+            data.settled_read_markers(now + Duration::from_secs(1))
+                .is_empty()
+        );
+        assert_eq!(
+            data.settled_read_markers(now + FLUSH_READ_MARKER_AFTER),
+            vec![(kind.clone(), read_marker(3))]
+        );
+        assert!(
+            data.settled_read_markers(now + FLUSH_READ_MARKER_AFTER * 2)
+                .is_empty()
+        );
+    }
 }
@@ -16,6 +16,63 @@ pub struct Metadata {
     pub read_marker: Option<ReadMarker>,
     pub last_triggers_unread: Option<DateTime<Utc>>,
     pub chathistory_references: Option<MessageReferences>,
+    #[serde(default)]
+    pub unread: Unread,
+}
+
+/// Messages after the read marker which trigger unread, and how many of them
+/// are highlights
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize,
+)]
+pub struct Unread {
+    pub messages: usize,
+    pub highlights: usize,
+}
+
+impl Unread {
+    pub fn count(
+        messages: &[Message],
+        read_marker: Option<ReadMarker>,
+    ) -> Self {
+        let mut unread = Self::default();
+
+        for message in messages {
+            unread.record(message, read_marker);
+        }
+
+        unread
+    }
+
+    /// Counts `message` if it's unread
+    pub fn record(
+        &mut self,
+        message: &Message,
+        read_marker: Option<ReadMarker>,
+    ) {
+        if message.triggers_unread()
+            && read_marker.is_none_or(|read_marker| {
+                message.server_time > read_marker.date_time()
+            })
+        {
+            self.messages += 1;
+
+            if message.has_highlight_fragment() {
+                self.highlights += 1;
+            }
+        }
+    }
+}
+
+impl std::ops::Add for Unread {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            messages: self.messages + other.messages,
+            highlights: self.highlights + other.highlights,
+        }
+    }
 }
 
 #[derive(
@@ -109,6 +166,7 @@ pub async fn save(
         read_marker,
         last_triggers_unread: latest_triggers_unread(messages),
         chathistory_references: latest_can_reference(messages),
+        unread: Unread::count(messages, read_marker),
     })?;
 
     let path = path(kind).await?;
@@ -130,10 +188,22 @@ pub async fn update(
         return Ok(());
     }
 
+    // Messages aren't loaded to recount them, but they're all read when it's
+    // past the last one
+    let unread = if metadata
+        .last_triggers_unread
+        .is_none_or(|last| last <= read_marker.date_time())
+    {
+        Unread::default()
+    } else {
+        metadata.unread
+    };
+
     let bytes = serde_json::to_vec(&Metadata {
         read_marker: Some(*read_marker),
         last_triggers_unread: metadata.last_triggers_unread,
         chathistory_references: metadata.chathistory_references,
+        unread,
     })?;
 
     let path = path(kind).await?;
@@ -143,6 +213,16 @@ pub async fn update(
     Ok(())
 }
 
+pub async fn delete(kind: &Kind) -> Result<(), Error> {
+    let path = path(kind).await?;
+
+    if fs::try_exists(&path).await? {
+        fs::remove_file(path).await?;
+    }
+
+    Ok(())
+}
+
 async fn path(kind: &Kind) -> Result<PathBuf, Error> {
     let dir = dir_path().await?;
 
@@ -0,0 +1,486 @@
+//! Shared client for everything fetched over HTTP, limiting how many requests
+//! are in progress at once, how often each host is requested and how much is
+//! read of each response
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock, Mutex, PoisonError, RwLock};
+use std::time::Duration;
+
+use bytes::Bytes;
+use reqwest::header::{HeaderName, HeaderValue};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::{self, Instant};
+use url::Url;
+
+use crate::config;
+
+pub mod cache;
+
+static CLIENT: LazyLock<RwLock<Arc<Client>>> = LazyLock::new(|| {
+    RwLock::new(Arc::new(
+        Client::new(&config::Http::default(), None)
+            .expect("client without proxy"),
+    ))
+});
+
+static IN_FLIGHT: AtomicUsize = AtomicUsize::new(0);
+static COMPLETED: AtomicUsize = AtomicUsize::new(0);
+static FAILED: AtomicUsize = AtomicUsize::new(0);
+
+/// Replaces the shared client with one for `config`. Requests already in
+/// progress finish with the previous one.
+pub fn configure(config: &config::Config) {
+    let client = match Client::new(&config.http, config.proxy.as_ref()) {
+        Ok(client) => client,
+        Err(error) => {
+            log::error!("HTTP requests are disabled: {error}");
+
+            Client::disabled(&config.http, error)
+        }
+    };
+
+    *CLIENT.write().unwrap_or_else(PoisonError::into_inner) = Arc::new(client);
+}
+
+/// The shared client
+pub fn client() -> Arc<Client> {
+    CLIENT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Sends `request` with the shared client
+pub async fn send(request: Request) -> Result<Response, Error> {
+    client().send(request).await
+}
+
+/// Requests made since launch, for the debug overlay
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stats {
+    pub in_flight: usize,
+    pub completed: usize,
+    pub failed: usize,
+}
+
+pub fn stats() -> Stats {
+    Stats {
+        in_flight: IN_FLIGHT.load(Ordering::Relaxed),
+        completed: COMPLETED.load(Ordering::Relaxed),
+        failed: FAILED.load(Ordering::Relaxed),
+    }
+}
+
+#[derive(Debug)]
+pub struct Client {
+    /// Unset when the configured proxy can't be used, so nothing is requested
+    /// without it
+    inner: Result<reqwest::Client, Error>,
+    permits: Arc<Semaphore>,
+    host_delay: Duration,
+    max_response_size: usize,
+    cache_ttl: Duration,
+    /// When the next request to each host may be sent
+    next_request: Mutex<HashMap<String, Instant>>,
+}
+
+impl Client {
+    pub fn new(
+        config: &config::Http,
+        irc_proxy: Option<&config::Proxy>,
+    ) -> Result<Self, Error> {
+        let mut builder = reqwest::Client::builder();
+
+        builder = match (&config.proxy, config.proxy.resolve(irc_proxy)) {
+            (_, Some(proxy)) => builder.proxy(proxy_for(proxy)?),
+            (config::http::Proxy::None, None) => builder.no_proxy(),
+            // Falls back to the system proxy, if any
+            (_, None) => builder,
+        };
+
+        Ok(Self {
+            inner: Ok(builder.build()?),
+            ..Self::disabled(config, Error::Disabled)
+        })
+    }
+
+    fn disabled(config: &config::Http, error: Error) -> Self {
+        Self {
+            inner: Err(error),
+            permits: Arc::new(Semaphore::new(config.concurrency.max(1))),
+            host_delay: Duration::from_millis(config.host_delay_ms),
+            max_response_size: config.max_response_size,
+            cache_ttl: Duration::from_secs(config.cache_ttl_hours * 60 * 60),
+            next_request: Mutex::default(),
+        }
+    }
+
+    /// How long fetched content is kept in the [`cache`]
+    pub fn cache_ttl(&self) -> Duration {
+        self.cache_ttl
+    }
+
+    /// Sends `request` once a request is allowed for its host and fewer than
+    /// the configured number of requests are in progress
+    pub async fn send(&self, request: Request) -> Result<Response, Error> {
+        let client = self.inner.as_ref().map_err(Clone::clone)?;
+
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|_| Error::Disabled)?;
+
+        self.wait_for_host(&request.url).await;
+
+        let mut tracker = Tracker::start();
+
+        let mut builder = match request.body {
+            Some(body) => client.post(request.url).body(body),
+            None => client.get(request.url),
+        };
+
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+
+        if let Some(timeout) = request.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        let inner = builder.send().await?.error_for_status()?;
+
+        if inner
+            .content_length()
+            .is_some_and(|length| length > self.max_response_size as u64)
+        {
+            return Err(Error::TooLarge);
+        }
+
+        tracker.failed = false;
+
+        Ok(Response {
+            inner,
+            read: 0,
+            max_size: self.max_response_size,
+            _permit: permit,
+            tracker,
+        })
+    }
+
+    async fn wait_for_host(&self, url: &Url) {
+        let Some(host) = url.host_str() else {
+            return;
+        };
+
+        let now = Instant::now();
+
+        let send_at = {
+            let mut next_request = self
+                .next_request
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+
+            let send_at =
+                next_request.get(host).map_or(now, |next| (*next).max(now));
+
+            next_request.insert(host.to_owned(), send_at + self.host_delay);
+
+            send_at
+        };
+
+        time::sleep_until(send_at).await;
+    }
+}
+
+fn proxy_for(proxy: &config::Proxy) -> Result<reqwest::Proxy, Error> {
+    match proxy {
+        config::Proxy::Http {
+            host,
+            port,
+            username,
+            password,
+        } => {
+            let proxy = reqwest::Proxy::all(format!("http://{host}:{port}"))?;
+
+            Ok(match (username, password) {
+                (Some(username), password) => proxy
+                    .basic_auth(username, password.as_deref().unwrap_or("")),
+                (None, _) => proxy,
+            })
+        }
+        config::Proxy::Socks5 { .. } => Err(Error::UnsupportedProxy("socks5")),
+        config::Proxy::Tor => Err(Error::UnsupportedProxy("tor")),
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    url: Url,
+    headers: Vec<(HeaderName, HeaderValue)>,
+    timeout: Option<Duration>,
+    /// Sent with a POST instead of a GET
+    body: Option<Vec<u8>>,
+}
+
+impl Request {
+    pub fn get(url: Url) -> Self {
+        Self {
+            url,
+            headers: vec![],
+            timeout: None,
+            body: None,
+        }
+    }
+
+    /// POST of `body` as JSON
+    pub fn post_json(url: Url, body: &impl Serialize) -> Result<Self, Error> {
+        Ok(Self {
+            body: Some(serde_json::to_vec(body)?),
+            ..Self::get(url)
+        }
+        .header(
+            reqwest::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        ))
+    }
+
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.push((name, value));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// Response whose body is read at most up to the configured size. Counts
+/// towards the requests in progress until dropped.
+#[derive(Debug)]
+pub struct Response {
+    inner: reqwest::Response,
+    read: usize,
+    max_size: usize,
+    _permit: OwnedSemaphorePermit,
+    tracker: Tracker,
+}
+
+impl Response {
+    /// Next chunk of the body, if any is left
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, Error> {
+        // Failed until read without error
+        self.tracker.failed = true;
+
+        let Some(chunk) = self.inner.chunk().await? else {
+            self.tracker.failed = false;
+            return Ok(None);
+        };
+
+        self.read += chunk.len();
+
+        if self.read > self.max_size {
+            return Err(Error::TooLarge);
+        }
+
+        self.tracker.failed = false;
+
+        Ok(Some(chunk))
+    }
+
+    pub async fn bytes(mut self) -> Result<Vec<u8>, Error> {
+        let mut bytes = vec![];
+
+        while let Some(chunk) = self.chunk().await? {
+            bytes.extend(chunk);
+        }
+
+        Ok(bytes)
+    }
+
+    pub async fn json<T: DeserializeOwned>(self) -> Result<T, Error> {
+        Ok(serde_json::from_slice(&self.bytes().await?)?)
+    }
+}
+
+/// Counts a request as in progress until dropped, then as completed or
+/// failed
+#[derive(Debug)]
+struct Tracker {
+    failed: bool,
+}
+
+impl Tracker {
+    fn start() -> Self {
+        IN_FLIGHT.fetch_add(1, Ordering::Relaxed);
+
+        Self { failed: true }
+    }
+}
+
+impl Drop for Tracker {
+    fn drop(&mut self) {
+        IN_FLIGHT.fetch_sub(1, Ordering::Relaxed);
+
+        if self.failed {
+            FAILED.fetch_add(1, Ordering::Relaxed);
+        } else {
+            COMPLETED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error("requests are disabled")]
+    Disabled,
+    #[error("{0} proxies aren't supported for HTTP requests")]
+    UnsupportedProxy(&'static str),
+    #[error("response exceeds max size")]
+    TooLarge,
+    #[error("request failed: {0}")]
+    Reqwest(Arc<reqwest::Error>),
+    #[error("invalid json: {0}")]
+    Json(Arc<serde_json::Error>),
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Reqwest(Arc::new(error))
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(Arc::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use futures::future;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Connections {
+        active: AtomicUsize,
+        max_active: AtomicUsize,
+    }
+
+    /// Serves `body` to every request after `delay`, with a `Content-Length`
+    /// header when `with_length`
+    async fn serve(
+        body: &'static [u8],
+        with_length: bool,
+        delay: Duration,
+    ) -> (Url, Arc<Connections>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let connections = Arc::new(Connections::default());
+
+        tokio::spawn({
+            let connections = connections.clone();
+
+            async move {
+                loop {
+                    let (mut stream, _) = listener.accept().await.unwrap();
+                    let connections = connections.clone();
+
+                    tokio::spawn(async move {
+                        let active =
+                            connections.active.fetch_add(1, Ordering::SeqCst)
+                                + 1;
+                        connections
+                            .max_active
+                            .fetch_max(active, Ordering::SeqCst);
+
+                        let mut request = vec![];
+                        let mut buffer = [0; 1024];
+
+                        while !request.ends_with(b"\r\n\r\n") {
+                            let read = stream.read(&mut buffer).await.unwrap();
+                            request.extend(&buffer[..read]);
+                        }
+
+                        time::sleep(delay).await;
+
+                        let length = if with_length {
+                            format!("Content-Length: {}\r\n", body.len())
+                        } else {
+                            String::new()
+                        };
+                        let head = format!(
+                            "HTTP/1.1 200 OK\r\n{length}Connection: close\r\n\r\n"
+                        );
+
+                        stream.write_all(head.as_bytes()).await.unwrap();
+                        stream.write_all(body).await.unwrap();
+                        stream.shutdown().await.unwrap();
+
+                        connections.active.fetch_sub(1, Ordering::SeqCst);
+                    });
+                }
+            }
+        });
+
+        (url.parse().unwrap(), connections)
+    }
+
+    fn client(concurrency: usize, max_response_size: usize) -> Client {
+        Client::new(
+            &config::Http {
+                proxy: config::http::Proxy::None,
+                concurrency,
+                host_delay_ms: 0,
+                max_response_size,
+                ..config::Http::default()
+            },
+            None,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn size_cap() {
+        const BODY: &[u8] = &[b'a'; 1000];
+
+        let (url, _) = serve(BODY, true, Duration::ZERO).await;
+
+        let response = client(1, 1000).send(Request::get(url.clone())).await;
+        assert_eq!(response.unwrap().bytes().await.unwrap(), BODY);
+
+        // Refused up front when the length is known
+        let response = client(1, 999).send(Request::get(url)).await;
+        assert!(matches!(response, Err(Error::TooLarge)));
+
+        // Otherwise once too much has been read
+        let (url, _) = serve(BODY, false, Duration::ZERO).await;
+
+        let response = client(1, 999).send(Request::get(url)).await.unwrap();
+        assert!(matches!(response.bytes().await, Err(Error::TooLarge)));
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit() {
+        let (url, connections) =
+            serve(b"ok", true, Duration::from_millis(50)).await;
+        let client = client(2, 1000);
+
+        let responses = future::join_all((0..5).map(|_| async {
+            client.send(Request::get(url.clone())).await?.bytes().await
+        }))
+        .await;
+
+        assert!(responses.into_iter().all(|response| response.is_ok()));
+        assert_eq!(connections.max_active.load(Ordering::SeqCst), 2);
+    }
+}
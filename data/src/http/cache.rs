@@ -0,0 +1,99 @@
+//! Fetched content stored on disk until it expires
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::environment;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry<T> {
+    saved_at: DateTime<Utc>,
+    value: T,
+}
+
+/// Where the value for `key` is cached, under the `namespace` directory of
+/// the cache directory
+pub fn path(namespace: &str, key: &str) -> PathBuf {
+    let hash = hex::encode(seahash::hash(key.as_bytes()).to_be_bytes());
+
+    environment::cache_dir()
+        .join(namespace)
+        .join(&hash[..2])
+        .join(&hash[2..4])
+        .join(&hash[4..6])
+        .join(format!("{hash}.json"))
+}
+
+/// Value cached at `path`, unless saved more than `ttl` ago
+pub async fn load<T: DeserializeOwned>(
+    path: &Path,
+    ttl: Duration,
+) -> Option<T> {
+    let entry: Entry<T> =
+        serde_json::from_slice(&fs::read(path).await.ok()?).ok()?;
+
+    if is_expired(entry.saved_at, ttl, Utc::now()) {
+        let _ = fs::remove_file(path).await;
+
+        return None;
+    }
+
+    Some(entry.value)
+}
+
+pub async fn save<T: Serialize>(path: &Path, value: &T) {
+    save_at(path, value, Utc::now()).await;
+}
+
+async fn save_at<T: Serialize>(path: &Path, value: &T, now: DateTime<Utc>) {
+    if let Some(parent) = path.parent().filter(|p| !p.exists()) {
+        let _ = fs::create_dir_all(parent).await;
+    }
+
+    let Ok(bytes) = serde_json::to_vec(&Entry {
+        saved_at: now,
+        value,
+    }) else {
+        return;
+    };
+
+    let _ = fs::write(path, &bytes).await;
+}
+
+fn is_expired(
+    saved_at: DateTime<Utc>,
+    ttl: Duration,
+    now: DateTime<Utc>,
+) -> bool {
+    chrono::Duration::from_std(ttl)
+        .is_ok_and(|ttl| now.signed_duration_since(saved_at) > ttl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn expiry() {
+        let path = std::env::temp_dir()
+            .join(format!("halloy-http-cache-{}", std::process::id()))
+            .join("entry.json");
+        let ttl = Duration::from_secs(60 * 60);
+
+        save(&path, &"fresh").await;
+        assert_eq!(load::<String>(&path, ttl).await.as_deref(), Some("fresh"));
+
+        let two_hours_ago = Utc::now() - chrono::Duration::hours(2);
+        save_at(&path, &"stale", two_hours_ago).await;
+        assert_eq!(load::<String>(&path, ttl).await, None);
+
+        // Expired entries are removed
+        assert!(!path.exists());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap()).await;
+    }
+}
@@ -0,0 +1,198 @@
+use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::isupport::CaseMap;
+use crate::{Server, User, environment};
+
+/// Masks of ignored users per server, e.g. `*!*@host` or `nick!*@*`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Ignores(HashMap<Server, Vec<String>>);
+
+impl Ignores {
+    pub fn load() -> Result<Self, Error> {
+        let path = path()?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let bytes = std::fs::read(path)?;
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    pub async fn save(self) -> Result<(), Error> {
+        let path = path()?;
+
+        let bytes = serde_json::to_vec(&self)?;
+        fs::write(path, &bytes).await?;
+
+        Ok(())
+    }
+
+    pub fn is_ignored(&self, server: &Server, user: &User) -> bool {
+        self.0
+            .get(server)
+            .is_some_and(|masks| masks.iter().any(|mask| matches(mask, user)))
+    }
+
+    /// Ignores the user by their host, or by nickname if it's unknown
+    pub fn ignore(&mut self, server: &Server, user: &User) {
+        let mask = user.ban_mask();
+        let masks = self.0.entry(server.clone()).or_default();
+
+        if !masks.contains(&mask) {
+            masks.push(mask);
+        }
+    }
+
+    /// Removes every mask matching the user
+    pub fn unignore(&mut self, server: &Server, user: &User) {
+        if let Some(masks) = self.0.get_mut(server) {
+            masks.retain(|mask| !matches(mask, user));
+
+            if masks.is_empty() {
+                self.0.remove(server);
+            }
+        }
+    }
+}
+
+fn matches(mask: &str, user: &User) -> bool {
+    wildcard_match(&mask.to_lowercase(), &hostmask(user).to_lowercase())
+}
+
+/// Whether `mask`, e.g. `*!*@host` or `nick!*@*`, matches the user, comparing
+/// as the server does
+pub fn mask_matches(mask: &str, user: &User, casemapping: CaseMap) -> bool {
+    wildcard_match(
+        &casemapping.normalize(mask),
+        &casemapping.normalize(&hostmask(user)),
+    )
+}
+
+fn hostmask(user: &User) -> String {
+    format!(
+        "{}!{}@{}",
+        user.nickname(),
+        user.username().unwrap_or_default(),
+        user.hostname().unwrap_or_default()
+    )
+}
+
+/// `*` matches any number of characters and `?` matches a single character
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let text = text.chars().collect::<Vec<_>>();
+
+    let (mut p, mut t) = (0, 0);
+    // Last `*` seen and the position in `text` it's matched up to
+    let mut star = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match star {
+                Some((star_p, star_t)) => {
+                    star = Some((star_p, star_t + 1));
+                    p = star_p + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn path() -> Result<PathBuf, Error> {
+    let parent = environment::data_dir();
+
+    if !parent.exists() {
+        std::fs::create_dir_all(&parent)?;
+    }
+
+    Ok(parent.join("ignore.json"))
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Serde(Arc<serde_json::Error>),
+    #[error(transparent)]
+    Io(Arc<io::Error>),
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serde(Arc::new(error))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(Arc::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wildcards() {
+        for (pattern, text, expected) in [
+            ("*!*@host", "nick!user@host", true),
+            ("*!*@host", "nick!user@otherhost", false),
+            ("nick!*@*", "nick!@", true),
+            ("nick!*@*", "nickname!user@host", false),
+            ("*!*@*.example.com", "nick!user@a.b.example.com", true),
+            ("*!*@*.example.com", "nick!user@example.com", false),
+            ("n?ck!*@*", "nick!user@host", true),
+            ("n?ck!*@*", "nck!user@host", false),
+            ("*", "", true),
+            ("", "nick", false),
+        ] {
+            assert_eq!(wildcard_match(pattern, text), expected, "{pattern}");
+        }
+    }
+
+    #[test]
+    fn ignore() {
+        let server = Server::from("libera");
+        let other = Server::from("oftc");
+        let user = User::try_from("dan!d@Host.example.com").unwrap();
+        let nick_only = User::try_from("dan").unwrap();
+        let mut ignores = Ignores::default();
+
+        ignores.ignore(&server, &user);
+
+        assert!(ignores.is_ignored(&server, &user));
+        assert!(ignores.is_ignored(
+            &server,
+            &User::try_from("dan_!other@host.example.com").unwrap()
+        ));
+        assert!(!ignores.is_ignored(&other, &user));
+        assert!(!ignores.is_ignored(&server, &nick_only));
+
+        ignores.ignore(&server, &nick_only);
+        assert!(ignores.is_ignored(&server, &nick_only));
+
+        ignores.unignore(&server, &user);
+        assert!(!ignores.is_ignored(&server, &user));
+        assert!(!ignores.is_ignored(&server, &nick_only));
+    }
+}
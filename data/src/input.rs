@@ -1,20 +1,35 @@
 use std::collections::HashMap;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
 use irc::proto;
 use irc::proto::format;
+use serde::{Deserialize, Serialize};
+use tokio::fs;
 
-use crate::buffer::{self, AutoFormat};
+use crate::buffer::{self, AutoFormat, MarkdownLinks};
 use crate::message::formatting;
 use crate::target::Target;
+use crate::user::Nick;
 use crate::{
-    Command, Config, Message, Server, User, command, isupport, message,
+    Command, Config, Message, Server, User, command, config, environment,
+    isupport, message,
 };
 
-const INPUT_HISTORY_LENGTH: usize = 100;
+const INPUT_HISTORY_LENGTH: usize = 200;
+const REPLY_QUOTE_CHARS: usize = 80;
+/// Allowed for our username in the prefix servers add to messages they relay,
+/// when it isn't known, including the `~` added without ident
+const USERNAME_LENGTH: usize = 11;
+/// Allowed for our hostname in the prefix, when it isn't known
+const HOSTNAME_LENGTH: usize = 63;
 
 pub fn parse(
     buffer: buffer::Upstream,
     auto_format: AutoFormat,
+    markdown_links: MarkdownLinks,
     input: &str,
     isupport: &HashMap<isupport::Kind, isupport::Parameter>,
 ) -> Result<Parsed, Error> {
@@ -22,27 +37,39 @@ pub fn parse(
         Ok(Command::Internal(command)) => return Ok(Parsed::Internal(command)),
         Ok(Command::Irc(command)) => Content::Command(command),
         Err(command::Error::MissingSlash) => {
-            let text = match auto_format {
-                AutoFormat::Disabled => input.to_string(),
-                AutoFormat::Markdown => formatting::encode(input, true),
-                AutoFormat::All => formatting::encode(input, false),
-            };
-
-            Content::Text(text)
+            Content::text(input, auto_format, markdown_links)
         }
         Err(error) => return Err(Error::Command(error)),
     };
 
-    if let Some(message_bytes) = content
-        .proto(&buffer)
-        .map(|message| format::message(message).len())
-    {
-        if message_bytes > format::BYTE_LIMIT {
-            return Err(Error::ExceedsByteLimit { message_bytes });
-        }
-    }
+    Input::new(buffer, content).map(Parsed::Input)
+}
+
+/// Parses each of the [`lines`] of pasted `text` as a message, including
+/// those starting with `/`
+pub fn parse_lines(
+    buffer: buffer::Upstream,
+    auto_format: AutoFormat,
+    markdown_links: MarkdownLinks,
+    text: &str,
+) -> Result<Vec<Input>, Error> {
+    lines(text)
+        .into_iter()
+        .map(|line| {
+            Input::new(
+                buffer.clone(),
+                Content::text(line, auto_format, markdown_links),
+            )
+        })
+        .collect()
+}
 
-    Ok(Parsed::Input(Input { buffer, content }))
+/// The lines of `text` to send as messages of their own, as a message can't
+/// contain line breaks. Blank lines are left out.
+pub fn lines(text: &str) -> Vec<&str> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect()
 }
 
 pub enum Parsed {
@@ -54,20 +81,133 @@ pub enum Parsed {
 pub struct Input {
     pub buffer: buffer::Upstream,
     content: Content,
+    /// `msgid` of the message this replies to
+    reply_to: Option<String>,
 }
 
 impl Input {
+    /// Messages over the byte limit are [`split`](Self::split) when sent,
+    /// other commands are rejected
+    fn new(buffer: buffer::Upstream, content: Content) -> Result<Self, Error> {
+        if let Some(message_bytes) = content
+            .proto(&buffer)
+            .filter(|_| content.message().is_none())
+            .map(|message| format::message(message).len())
+        {
+            if message_bytes > format::BYTE_LIMIT {
+                return Err(Error::ExceedsByteLimit { message_bytes });
+            }
+        }
+
+        Ok(Self {
+            buffer,
+            content,
+            reply_to: None,
+        })
+    }
+
     pub fn command(buffer: buffer::Upstream, command: command::Irc) -> Self {
         Self {
             buffer,
             content: Content::Command(command),
+            reply_to: None,
+        }
+    }
+
+    /// Sends the input as a reply to the message `id`, if it's a message
+    /// rather than a command
+    pub fn with_reply(self, id: String) -> Self {
+        match self.content {
+            Content::Text(_) => Self {
+                reply_to: Some(id),
+                ..self
+            },
+            Content::Command(_) => self,
+        }
+    }
+
+    /// Sends the input as a NOTICE to `target` instead, if it's a message
+    /// rather than a command
+    pub fn with_notice(self, target: &Nick) -> Self {
+        match self.content {
+            Content::Text(text) => Self {
+                content: Content::Command(command::Irc::Notice(
+                    target.to_string(),
+                    text,
+                )),
+                ..self
+            },
+            Content::Command(_) => self,
         }
     }
 
+    pub fn is_message(&self) -> bool {
+        matches!(self.content, Content::Text(_))
+    }
+
     pub fn server(&self) -> &Server {
         self.buffer.server()
     }
 
+    /// Command that will be sent, unless it's a message
+    pub fn irc_command(&self) -> Option<&command::Irc> {
+        match &self.content {
+            Content::Command(command) => Some(command),
+            Content::Text(_) => None,
+        }
+    }
+
+    /// Text that will be sent, if it contains any formatting
+    pub fn formatted(&self) -> Option<&str> {
+        let text = self.content.message()?;
+
+        text.chars()
+            .any(|c| formatting::Modifier::try_from(c).is_ok())
+            .then_some(text)
+    }
+
+    /// Splits a message too long for a line into several, leaving room for
+    /// the prefix of `source` which servers add when relaying it, as long as
+    /// allowed for its username and hostname when they aren't known. Only
+    /// the first is sent as a reply.
+    pub fn split(self, source: &User) -> Vec<Self> {
+        let Some(text) = self.content.message() else {
+            return vec![self];
+        };
+
+        // With a space, so the last parameter is always written with a `:`
+        let Some(overhead) = self
+            .content
+            .with_message(" ".to_string())
+            .proto(&self.buffer)
+            .map(|message| format::message(message).len() - 1)
+        else {
+            return vec![self];
+        };
+
+        // `:nick!user@host `
+        let prefix = 4
+            + source.nickname().as_ref().len()
+            + source.username().map_or(USERNAME_LENGTH, str::len)
+            + source.hostname().map_or(HOSTNAME_LENGTH, str::len);
+
+        let max_bytes = format::BYTE_LIMIT.saturating_sub(overhead + prefix);
+
+        if text.len() <= max_bytes {
+            return vec![self];
+        }
+
+        formatting::split(text, max_bytes)
+            .into_iter()
+            .enumerate()
+            .map(|(index, text)| Self {
+                buffer: self.buffer.clone(),
+                content: self.content.with_message(text),
+                reply_to: self.reply_to.clone().filter(|_| index == 0),
+            })
+            .collect()
+    }
+
     pub fn messages(
         &self,
         user: User,
@@ -90,14 +230,17 @@ impl Input {
         };
 
         let command = self.content.command(&self.buffer)?;
+        let is_notice = matches!(command, command::Irc::Notice(..));
 
         match command {
             command::Irc::Msg(targets, text)
             | command::Irc::Notice(targets, text) => Some(
                 targets
                     .split(',')
-                    .map(|target| {
-                        Message::sent(
+                    .map(|target| Message {
+                        is_notice,
+                        reply_to: self.reply_to.clone(),
+                        ..Message::sent(
                             to_target(
                                 target,
                                 message::Source::User(user.clone()),
@@ -106,6 +249,7 @@ impl Input {
                                 text.clone(),
                                 channel_users,
                                 target,
+                                self.buffer.server(),
                                 None,
                                 &config.highlights,
                             ),
@@ -120,6 +264,7 @@ impl Input {
                     Some(&action),
                     channel_users,
                     &target,
+                    self.buffer.server(),
                     None,
                     &config.highlights,
                 ),
@@ -157,7 +302,19 @@ impl Input {
     }
 
     pub fn encoded(&self) -> Option<message::Encoded> {
-        self.content.proto(&self.buffer).map(message::Encoded::from)
+        self.content
+            .proto(&self.buffer)
+            .map(|mut message| {
+                if let Some(id) = &self.reply_to {
+                    message.tags.push(proto::Tag {
+                        key: "+draft/reply".to_string(),
+                        value: Some(id.clone()),
+                    });
+                }
+
+                message
+            })
+            .map(message::Encoded::from)
     }
 }
 
@@ -168,6 +325,50 @@ enum Content {
 }
 
 impl Content {
+    fn text(
+        text: &str,
+        auto_format: AutoFormat,
+        markdown_links: MarkdownLinks,
+    ) -> Self {
+        Self::Text(match auto_format {
+            AutoFormat::Disabled => text.to_string(),
+            AutoFormat::Markdown => {
+                formatting::encode(text, true, markdown_links)
+            }
+            AutoFormat::All => formatting::encode(text, false, markdown_links),
+        })
+    }
+
+    /// Text of a message, as opposed to other commands
+    fn message(&self) -> Option<&str> {
+        match self {
+            Self::Text(text)
+            | Self::Command(
+                command::Irc::Msg(_, text)
+                | command::Irc::Notice(_, text)
+                | command::Irc::Me(_, text),
+            ) => Some(text),
+            Self::Command(_) => None,
+        }
+    }
+
+    /// The same message with `text` instead
+    fn with_message(&self, text: String) -> Self {
+        match self {
+            Self::Text(_) => Self::Text(text),
+            Self::Command(command::Irc::Msg(targets, _)) => {
+                Self::Command(command::Irc::Msg(targets.clone(), text))
+            }
+            Self::Command(command::Irc::Notice(targets, _)) => {
+                Self::Command(command::Irc::Notice(targets.clone(), text))
+            }
+            Self::Command(command::Irc::Me(target, _)) => {
+                Self::Command(command::Irc::Me(target.clone(), text))
+            }
+            Self::Command(command) => Self::Command(command.clone()),
+        }
+    }
+
     fn command(&self, buffer: &buffer::Upstream) -> Option<command::Irc> {
         match self {
             Self::Text(text) => {
@@ -191,18 +392,102 @@ pub struct RawInput {
     pub text: String,
 }
 
+/// Message being replied to from a buffer's input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    /// `msgid` of the message, attached to the reply when the server
+    /// supports message tags
+    pub id: Option<String>,
+    pub nick: Nick,
+    pub text: String,
+    pub server_time: DateTime<Utc>,
+}
+
+impl Reply {
+    /// Only messages from users can be replied to
+    pub fn new(message: &Message) -> Option<Self> {
+        let (message::Source::User(user) | message::Source::Action(Some(user))) =
+            message.target.source()
+        else {
+            return None;
+        };
+
+        Some(Self {
+            id: message.id.clone(),
+            nick: user.nickname().to_owned(),
+            text: message.text(),
+            server_time: message.server_time,
+        })
+    }
+
+    /// Line quoting the message, sent before the reply when it can't be
+    /// tagged as one. Its timestamp is formatted as in `buffer`, where the
+    /// reply is sent
+    pub fn quote(&self, buffer: &config::Buffer) -> String {
+        let line = self.text.lines().next().unwrap_or_default();
+
+        let snippet = if line.chars().count() > REPLY_QUOTE_CHARS {
+            format!(
+                "{}…",
+                line.chars().take(REPLY_QUOTE_CHARS).collect::<String>()
+            )
+        } else {
+            line.to_string()
+        };
+
+        let timestamp = buffer
+            .copy_message
+            .include_timestamp_in_quote
+            .then(|| buffer.copied_timestamp(&self.server_time))
+            .flatten();
+
+        format!(
+            "> {}{}: {snippet}",
+            timestamp.unwrap_or_default(),
+            self.nick
+        )
+    }
+}
+
+/// What a buffer's input composes instead of a plain message, shown as a
+/// chip before it until it's sent or cancelled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Compose {
+    Reply(Reply),
+    /// NOTICE to a user, the polite way to reply to bots
+    Notice(Nick),
+}
+
+impl Compose {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Compose::Reply(_) => "REPLY",
+            Compose::Notice(_) => "NOTICE",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct Storage {
-    sent: HashMap<buffer::Upstream, Vec<String>>,
+    sent: Sent,
     draft: HashMap<buffer::Upstream, String>,
     text: HashMap<buffer::Upstream, String>,
+    compose: HashMap<buffer::Upstream, Compose>,
 }
 
 impl Storage {
+    pub fn new(sent: Sent) -> Self {
+        Self {
+            sent,
+            ..Self::default()
+        }
+    }
+
     pub fn get<'a>(&'a self, buffer: &buffer::Upstream) -> Cache<'a> {
         Cache {
             history: self
                 .sent
+                .0
                 .get(buffer)
                 .map(Vec::as_slice)
                 .unwrap_or_default(),
@@ -212,17 +497,24 @@ impl Storage {
                 .map(AsRef::as_ref)
                 .unwrap_or_default(),
             text: self.text.get(buffer).map(AsRef::as_ref).unwrap_or_default(),
+            compose: self.compose.get(buffer),
         }
     }
 
     pub fn record(&mut self, buffer: &buffer::Upstream, text: String) {
         self.draft.remove(buffer);
         self.text.remove(buffer);
-        let history = self.sent.entry(buffer.clone()).or_default();
+        let history = self.sent.0.entry(buffer.clone()).or_default();
+        // Only the latest of the same messages is recalled
+        history.retain(|sent| *sent != text);
         history.insert(0, text);
         history.truncate(INPUT_HISTORY_LENGTH);
     }
 
+    pub fn sent(&self) -> &Sent {
+        &self.sent
+    }
+
     pub fn store_draft(&mut self, raw_input: RawInput) {
         self.draft.insert(raw_input.buffer, raw_input.text);
     }
@@ -230,6 +522,23 @@ impl Storage {
     pub fn store_text(&mut self, raw_input: RawInput) {
         self.text.insert(raw_input.buffer, raw_input.text);
     }
+
+    /// Kept per buffer, so a reply or notice is sent from the buffer it was
+    /// started in whichever pane is focused meanwhile
+    pub fn store_compose(
+        &mut self,
+        buffer: &buffer::Upstream,
+        compose: Compose,
+    ) {
+        self.compose.insert(buffer.clone(), compose);
+    }
+
+    pub fn take_compose(
+        &mut self,
+        buffer: &buffer::Upstream,
+    ) -> Option<Compose> {
+        self.compose.remove(buffer)
+    }
 }
 
 /// Cached values for a buffers input
@@ -238,6 +547,120 @@ pub struct Cache<'a> {
     pub history: &'a [String],
     pub draft: &'a str,
     pub text: &'a str,
+    pub compose: Option<&'a Compose>,
+}
+
+impl Cache<'_> {
+    /// Sent message older than the one at `index` in `history`, or the
+    /// latest without one, which starts with the draft like a prefix search
+    pub fn older(&self, index: Option<usize>) -> Option<usize> {
+        let start = index.map_or(0, |index| index + 1);
+
+        (start..self.history.len()).find(|index| self.recalls(*index))
+    }
+
+    /// Sent message newer than the one at `index` in `history`, which starts
+    /// with the draft, or `None` when it's back to the draft
+    pub fn newer(&self, index: usize) -> Option<usize> {
+        (0..index.min(self.history.len()))
+            .rev()
+            .find(|index| self.recalls(*index))
+    }
+
+    fn recalls(&self, index: usize) -> bool {
+        let sent = &self.history[index];
+
+        sent.starts_with(self.draft) && sent != self.draft
+    }
+}
+
+/// Messages sent from each buffer, most recent first, which are recalled
+/// across restarts. Commands carrying a password are only recalled until
+/// then, they're never written to disk
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(from = "Vec<(buffer::Upstream, Vec<String>)>")]
+#[serde(into = "Vec<(buffer::Upstream, Vec<String>)>")]
+pub struct Sent(HashMap<buffer::Upstream, Vec<String>>);
+
+impl Sent {
+    pub fn load() -> Result<Self, SentError> {
+        let path = path()?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let bytes = std::fs::read(path)?;
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    pub async fn save(self) -> Result<(), SentError> {
+        let path = path()?;
+
+        let bytes = serde_json::to_vec(&self)?;
+        fs::write(path, &bytes).await?;
+
+        Ok(())
+    }
+}
+
+impl From<Vec<(buffer::Upstream, Vec<String>)>> for Sent {
+    fn from(sent: Vec<(buffer::Upstream, Vec<String>)>) -> Self {
+        Self(sent.into_iter().collect())
+    }
+}
+
+impl From<Sent> for Vec<(buffer::Upstream, Vec<String>)> {
+    fn from(sent: Sent) -> Self {
+        sent.0
+            .into_iter()
+            .map(|(buffer, mut history)| {
+                history.retain(|sent| !has_secret(sent));
+
+                (buffer, history)
+            })
+            .collect()
+    }
+}
+
+/// Whether `text` is a command which can carry a password, such as `/oper`
+/// or a message to NickServ
+fn has_secret(text: &str) -> bool {
+    let text = text.to_lowercase();
+    let mut words = text.split_whitespace();
+
+    let Some(mut command) =
+        words.next().and_then(|word| word.strip_prefix('/'))
+    else {
+        return false;
+    };
+
+    if matches!(command, "raw" | "quote") {
+        let Some(raw) = words.next() else {
+            return false;
+        };
+
+        command = raw;
+    }
+
+    match command {
+        "oper" | "pass" | "authenticate" | "nickserv" | "ns" => true,
+        "msg" | "query" | "privmsg" | "notice" => words
+            .next()
+            .is_some_and(|target| target.split('@').next() == Some("nickserv")),
+        _ => false,
+    }
+}
+
+fn path() -> Result<PathBuf, SentError> {
+    let parent = environment::data_dir();
+
+    if !parent.exists() {
+        std::fs::create_dir_all(&parent)?;
+    }
+
+    Ok(parent.join("input-history.json"))
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -251,3 +674,314 @@ pub enum Error {
     #[error(transparent)]
     Command(#[from] command::Error),
 }
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum SentError {
+    #[error(transparent)]
+    Serde(Arc<serde_json::Error>),
+    #[error(transparent)]
+    Io(Arc<io::Error>),
+}
+
+impl From<serde_json::Error> for SentError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serde(Arc::new(error))
+    }
+}
+
+impl From<io::Error> for SentError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(Arc::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::target;
+
+    #[test]
+    fn reply() {
+        let buffer = buffer::Upstream::Channel(
+            "libera".into(),
+            target::Channel::from_str("#halloy", isupport::CaseMap::default()),
+        );
+        let parse = |input| match parse(
+            buffer.clone(),
+            AutoFormat::Disabled,
+            MarkdownLinks::Parentheses,
+            input,
+            &HashMap::new(),
+        ) {
+            Ok(Parsed::Input(input)) => input.with_reply("abc".into()),
+            _ => panic!("{input} isn't an input"),
+        };
+        let tag = |input: &Input| {
+            input.encoded().and_then(|encoded| {
+                encoded.tags.iter().find_map(|tag| {
+                    (tag.key == "+draft/reply").then(|| tag.value.clone())
+                })
+            })
+        };
+
+        assert_eq!(tag(&parse("hello")), Some(Some("abc".to_string())));
+        // Commands aren't replies
+        assert_eq!(tag(&parse("/mode +i")), None);
+
+        let reply = Reply {
+            id: None,
+            nick: Nick::from("alice"),
+            text: format!("{}\nsecond line", "a".repeat(100)),
+            server_time: DateTime::from_timestamp(45_240, 0).unwrap(),
+        };
+        let mut config = config::Buffer::default();
+        assert_eq!(
+            reply.quote(&config),
+            format!("> alice: {}…", "a".repeat(REPLY_QUOTE_CHARS))
+        );
+
+        config.copy_message.include_timestamp_in_quote = true;
+        config.copy_message.timestamp_zone = Some(crate::time::Zone::Utc);
+        config.timestamp.format = "%R".to_string();
+        assert_eq!(
+            reply.quote(&config),
+            format!("> 12:34 UTC alice: {}…", "a".repeat(REPLY_QUOTE_CHARS))
+        );
+    }
+
+    #[test]
+    fn recall() {
+        let buffer = buffer::Upstream::Channel(
+            "libera".into(),
+            target::Channel::from_str("#halloy", isupport::CaseMap::default()),
+        );
+        let mut storage = Storage::default();
+
+        for sent in ["hello", "/join #rust", "help", "hello", "/me waves"] {
+            storage.record(&buffer, sent.to_string());
+        }
+
+        let cache = storage.get(&buffer);
+        assert_eq!(
+            cache.history,
+            ["/me waves", "hello", "help", "/join #rust"]
+        );
+
+        // Every message without a draft
+        assert_eq!(cache.older(None), Some(0));
+        assert_eq!(cache.older(Some(2)), Some(3));
+        assert_eq!(cache.older(Some(3)), None);
+        assert_eq!(cache.newer(1), Some(0));
+        assert_eq!(cache.newer(0), None);
+
+        // Only those starting with it with one
+        let draft = |text: &str| RawInput {
+            buffer: buffer.clone(),
+            text: text.to_string(),
+        };
+        storage.store_draft(draft("hel"));
+        let cache = storage.get(&buffer);
+        assert_eq!(cache.older(None), Some(1));
+        assert_eq!(cache.older(Some(1)), Some(2));
+        assert_eq!(cache.older(Some(2)), None);
+        assert_eq!(cache.newer(2), Some(1));
+        assert_eq!(cache.newer(1), None);
+
+        storage.store_draft(draft("hello"));
+        assert_eq!(storage.get(&buffer).older(None), None);
+
+        // Sending an edited message keeps the one it was recalled from
+        storage.record(&buffer, "help me".to_string());
+        assert_eq!(
+            storage.get(&buffer).history,
+            ["help me", "/me waves", "hello", "help", "/join #rust"]
+        );
+    }
+
+    #[test]
+    fn secrets_not_saved() {
+        let buffer = buffer::Upstream::Server("libera".into());
+        let mut storage = Storage::default();
+
+        for sent in [
+            "/oper halloy hunter2",
+            "/msg NickServ IDENTIFY hunter2",
+            "/msg nickserv@services.libera.chat identify hunter2",
+            "/ns identify hunter2",
+            "/raw PASS hunter2",
+            "/quote privmsg NickServ :IDENTIFY hunter2",
+            "/msg ChanServ op #halloy",
+            "hello",
+        ] {
+            storage.record(&buffer, sent.to_string());
+        }
+
+        // Recalled until restarting
+        assert_eq!(storage.get(&buffer).history.len(), 8);
+
+        let saved: Sent = serde_json::from_slice(
+            &serde_json::to_vec(storage.sent()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(saved.0[&buffer], ["hello", "/msg ChanServ op #halloy"]);
+    }
+
+    #[test]
+    fn confirm_send() {
+        let paste = "Traceback:\r\n  at main\n\n  at run\n";
+        assert_eq!(lines(paste), ["Traceback:", "  at main", "  at run"]);
+
+        let confirm_send = buffer::ConfirmSend::default();
+        assert!(!confirm_send.is_exceeded_by("hello"));
+        assert!(!confirm_send.is_exceeded_by(paste));
+        assert!(confirm_send.is_exceeded_by(&"at main\n".repeat(4)));
+        assert!(confirm_send.is_exceeded_by(&"a".repeat(1025)));
+
+        let buffer = buffer::Upstream::Channel(
+            "libera".into(),
+            target::Channel::from_str("#halloy", isupport::CaseMap::default()),
+        );
+        let parse = |text: &str| {
+            parse_lines(
+                buffer.clone(),
+                AutoFormat::Disabled,
+                MarkdownLinks::default(),
+                text,
+            )
+        };
+
+        // Lines starting with `/` are sent as they are
+        let inputs = parse("at main\n/usr/lib/halloy\n").unwrap();
+        assert_eq!(inputs.len(), 2);
+        assert!(inputs.iter().all(Input::is_message));
+
+        // Lines over the byte limit are split when sent
+        let inputs = parse(&format!("at main\n{}", "a".repeat(500))).unwrap();
+        assert_eq!(inputs.len(), 2);
+    }
+
+    #[test]
+    fn split() {
+        let buffer = buffer::Upstream::Channel(
+            "libera".into(),
+            target::Channel::from_str("#halloy", isupport::CaseMap::default()),
+        );
+        let source = User::from(Nick::from("kai"));
+        let parse = |input: &str| match parse(
+            buffer.clone(),
+            AutoFormat::All,
+            MarkdownLinks::Parentheses,
+            input,
+            &HashMap::new(),
+        ) {
+            Ok(Parsed::Input(input)) => input.with_reply("abc".to_string()),
+            _ => panic!("{input} isn't an input"),
+        };
+        // Lines as relayed by the server, which adds our prefix
+        let lines = |inputs: &[Input]| {
+            inputs
+                .iter()
+                .map(|input| {
+                    let mut encoded = input.encoded().unwrap();
+                    encoded.tags.clear();
+
+                    format!(
+                        ":kai!{}@{} {}",
+                        "u".repeat(USERNAME_LENGTH),
+                        "h".repeat(HOSTNAME_LENGTH),
+                        format::message(encoded.into())
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let inputs = parse("hello").split(&source);
+        assert_eq!(inputs.len(), 1);
+
+        // Emoji and colors crossing the limit
+        let text = format!("$c4{}$c", "hello 😀 ".repeat(60));
+        let inputs = parse(&text).split(&source);
+        assert_eq!(inputs.len(), 2);
+        assert!(
+            lines(&inputs)
+                .iter()
+                .all(|line| line.len() <= format::BYTE_LIMIT)
+        );
+        assert!(inputs[0].reply_to.is_some());
+        assert!(inputs[1].reply_to.is_none());
+        assert!(inputs[1].formatted().unwrap().starts_with("\u{3}04hello"));
+
+        // Actions leave room for the CTCP delimiters
+        let inputs =
+            parse(&format!("/me {}", "waves ".repeat(100))).split(&source);
+        assert_eq!(inputs.len(), 2);
+        assert!(
+            lines(&inputs)
+                .iter()
+                .all(|line| line.len() <= format::BYTE_LIMIT)
+        );
+
+        // A known mask leaves more room
+        let text = "waves ".repeat(75);
+        let unknown = parse(&text).split(&source);
+        let known = parse(&text)
+            .split(&source.with_username_and_hostname("k".into(), "h".into()));
+        assert_eq!(unknown.len(), 2);
+        assert_eq!(known.len(), 1);
+
+        // Other commands aren't split, but rejected
+        assert!(matches!(
+            super::parse(
+                buffer.clone(),
+                AutoFormat::Disabled,
+                MarkdownLinks::Parentheses,
+                &format!("/topic #halloy {}", "a".repeat(500)),
+                &HashMap::new(),
+            ),
+            Err(Error::ExceedsByteLimit { .. })
+        ));
+    }
+
+    #[test]
+    fn notice() {
+        let buffer = buffer::Upstream::Channel(
+            "libera".into(),
+            target::Channel::from_str("#halloy", isupport::CaseMap::default()),
+        );
+        let Ok(Parsed::Input(input)) = parse(
+            buffer,
+            AutoFormat::Disabled,
+            MarkdownLinks::Parentheses,
+            "thanks",
+            &HashMap::new(),
+        ) else {
+            panic!("thanks isn't an input");
+        };
+        let input = input.with_notice(&Nick::from("bot"));
+
+        assert_eq!(
+            input.encoded().map(|encoded| encoded.command.clone()),
+            Some(proto::Command::NOTICE(
+                "bot".to_string(),
+                "thanks".to_string()
+            ))
+        );
+
+        let messages = input
+            .messages(
+                User::from(Nick::from("kai")),
+                &[],
+                &['#'],
+                &[],
+                isupport::CaseMap::default(),
+                &Config::default(),
+            )
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert!(messages[0].is_notice);
+        assert!(matches!(
+            &messages[0].target,
+            message::Target::Query { query, .. } if query.as_str() == "bot"
+        ));
+    }
+}
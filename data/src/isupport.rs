@@ -17,6 +17,7 @@ pub enum Kind {
     AWAYLEN,
     CASEMAPPING,
     CHANLIMIT,
+    CHANMODES,
     CHANNELLEN,
     CHANTYPES,
     CHATHISTORY,
@@ -609,6 +610,7 @@ impl Operation {
                 "AWAYLEN" => Some(Kind::AWAYLEN),
                 "CASEMAPPING" => Some(Kind::CASEMAPPING),
                 "CHANLIMIT" => Some(Kind::CHANLIMIT),
+                "CHANMODES" => Some(Kind::CHANMODES),
                 "CHANNELLEN" => Some(Kind::CHANNELLEN),
                 "CHANTYPES" => Some(Kind::CHANTYPES),
                 "CHATHISTORY" => Some(Kind::CHATHISTORY),
@@ -711,6 +713,7 @@ impl Parameter {
             Parameter::AWAYLEN(_) => Some(Kind::AWAYLEN),
             Parameter::CASEMAPPING(_) => Some(Kind::CASEMAPPING),
             Parameter::CHANLIMIT(_) => Some(Kind::CHANLIMIT),
+            Parameter::CHANMODES(_) => Some(Kind::CHANMODES),
             Parameter::CHANNELLEN(_) => Some(Kind::CHANNELLEN),
             Parameter::CHANTYPES(_) => Some(Kind::CHANTYPES),
             Parameter::CHATHISTORY(_) => Some(Kind::CHATHISTORY),
@@ -1123,6 +1126,31 @@ pub fn find_target_limit(
     }
 }
 
+/// CHANMODES of servers which don't advertise it, from RFC 2811
+const DEFAULT_CHANMODES: [(char, &str); 4] =
+    [('A', "beI"), ('B', "k"), ('C', "l"), ('D', "imnpst")];
+
+/// Whether the channel mode `mode` is of the CHANMODES type `letter`: `A` for
+/// lists, `B` for modes always taking an argument, `C` for modes taking one
+/// when set and `D` for flags
+pub fn has_channel_mode(
+    isupport: &HashMap<Kind, Parameter>,
+    letter: char,
+    mode: char,
+) -> bool {
+    if let Some(Parameter::CHANMODES(channel_modes)) =
+        isupport.get(&Kind::CHANMODES)
+    {
+        channel_modes.iter().any(|channel_mode| {
+            channel_mode.letter == letter && channel_mode.modes.contains(mode)
+        })
+    } else {
+        DEFAULT_CHANMODES
+            .iter()
+            .any(|(default, modes)| *default == letter && modes.contains(mode))
+    }
+}
+
 pub fn get_casemapping(isupport: &HashMap<Kind, Parameter>) -> CaseMap {
     if let Some(Parameter::CASEMAPPING(casemapping)) =
         isupport.get(&Kind::CASEMAPPING)
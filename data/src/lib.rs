@@ -21,32 +21,46 @@ pub use self::window::Window;
 
 pub mod appearance;
 pub mod audio;
+pub mod bouncer;
 pub mod buffer;
 pub mod channel;
 pub mod client;
 pub mod command;
+pub mod composer;
 mod compression;
 pub mod config;
 pub mod ctcp;
 pub mod dashboard;
 pub mod dcc;
+pub mod emoji;
 pub mod environment;
 pub mod file_transfer;
+pub mod fuzzy;
+pub mod fold;
 pub mod history;
+pub mod http;
+pub mod ignore;
 pub mod input;
 pub mod isupport;
 pub mod log;
 pub mod message;
 pub mod mode;
 pub mod notification;
+pub mod opener;
 pub mod pane;
+pub mod paste;
 pub mod preview;
+pub mod profile;
+pub mod scheduled;
+pub mod scheduler;
+pub mod search;
 pub mod serde;
 pub mod server;
 pub mod shortcut;
 pub mod stream;
 pub mod target;
 pub mod time;
+pub mod translation;
 pub mod url;
 pub mod user;
 pub mod version;
@@ -5,9 +5,14 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::environment;
+use crate::server::Server;
 
 pub fn file() -> Result<fs::File, Error> {
-    let path = path()?;
+    let path = path();
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
 
     Ok(fs::OpenOptions::new()
         .write(true)
@@ -17,23 +22,68 @@ pub fn file() -> Result<fs::File, Error> {
         .open(path)?)
 }
 
-fn path() -> Result<PathBuf, Error> {
-    let parent = environment::data_dir();
-
-    if !parent.exists() {
-        fs::create_dir_all(&parent)?;
-    }
-
-    Ok(parent.join("halloy.log"))
+pub fn path() -> PathBuf {
+    environment::data_dir().join("halloy.log")
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Record {
     pub timestamp: DateTime<Utc>,
     pub level: Level,
+    /// Module which emitted it, e.g. `data::stream`
+    #[serde(default)]
+    pub target: String,
+    /// Server it's about, from the `[server]` prefix of its message
+    #[serde(default)]
+    pub server: Option<Server>,
     pub message: String,
 }
 
+impl Record {
+    pub fn new(level: Level, target: &str, message: String) -> Self {
+        let (server, message) = match split_server(&message) {
+            Some((server, rest)) => {
+                (Some(Server::from(server)), rest.to_string())
+            }
+            None => (None, message),
+        };
+
+        Self {
+            timestamp: Utc::now(),
+            level,
+            target: target.to_string(),
+            server,
+            message,
+        }
+    }
+
+    /// e.g. "data::stream [libera] connected", as its message is searched
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+
+        if !self.target.is_empty() {
+            text.push_str(&self.target);
+            text.push(' ');
+        }
+
+        if let Some(server) = &self.server {
+            text.push_str(&format!("[{server}] "));
+        }
+
+        text.push_str(&self.message);
+
+        text
+    }
+}
+
+/// Splits `[server] ` off the start of `message`, which is how records about
+/// a server are written. Cheap enough to do for every record.
+fn split_server(message: &str) -> Option<(&str, &str)> {
+    let (server, rest) = message.strip_prefix('[')?.split_once("] ")?;
+
+    (!server.is_empty() && !server.contains('[')).then_some((server, rest))
+}
+
 #[derive(
     Clone,
     Copy,
@@ -77,3 +127,34 @@ pub enum Error {
     #[error(transparent)]
     ParseLevel(#[from] log::ParseLevelError),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_server() {
+        let record = Record::new(
+            Level::Info,
+            "data::stream",
+            "[libera] connected".to_string(),
+        );
+
+        assert_eq!(record.server, Some(Server::from("libera")));
+        assert_eq!(record.message, "connected");
+        assert_eq!(record.text(), "data::stream [libera] connected");
+
+        let record = Record::new(
+            Level::Warn,
+            "halloy",
+            "[[nested]] not a server".to_string(),
+        );
+
+        assert_eq!(record.server, None);
+        assert_eq!(record.message, "[[nested]] not a server");
+
+        let record = Record::new(Level::Debug, "", "plain".to_string());
+
+        assert_eq!(record.text(), "plain");
+    }
+}
@@ -21,8 +21,8 @@ use crate::config::buffer::UsernameFormat;
 use crate::serde::fail_as_none;
 use crate::target::Channel;
 use crate::time::Posix;
-use crate::user::{Nick, NickRef};
-use crate::{Config, Server, User, ctcp, isupport, target};
+use crate::user::{HighlightNicks, Nick, NickRef};
+use crate::{Config, Server, User, bouncer, ctcp, isupport, mode, target};
 
 // References:
 // - https://datatracker.ietf.org/doc/html/rfc1738#section-5
@@ -75,8 +75,10 @@ static USER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
 });
 
 pub(crate) mod broadcast;
+pub mod fold;
 pub mod formatting;
 pub mod source;
+pub mod standard_reply;
 
 #[derive(Debug, Clone)]
 pub struct Encoded(proto::Message);
@@ -90,6 +92,10 @@ impl Encoded {
             _ => None,
         }
     }
+
+    pub fn is_privmsg(&self) -> bool {
+        matches!(self.command, Command::PRIVMSG(..))
+    }
 }
 
 impl std::ops::Deref for Encoded {
@@ -165,6 +171,17 @@ impl Target {
             Target::Highlights { source, .. } => source,
         }
     }
+
+    /// Target of notices shown in the server buffer, from `nick` or from the
+    /// server itself
+    pub fn notice(nick: Option<Nick>) -> Self {
+        Target::Server {
+            source: Source::Server(Some(source::Server::new(
+                Kind::Notice,
+                nick,
+            ))),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
@@ -173,6 +190,16 @@ pub enum Direction {
     Received,
 }
 
+/// Categories of messages whose rows can be styled differently
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Category {
+    Own,
+    Highlight,
+    Action,
+    Server,
+    Error,
+}
+
 #[derive(Debug, Clone)]
 pub struct Message {
     pub received_at: Posix,
@@ -184,6 +211,18 @@ pub struct Message {
     pub hash: Hash,
     pub hidden_urls: HashSet<Url>,
     pub is_echo: bool,
+    /// Sent or received as a NOTICE rather than a PRIVMSG
+    pub is_notice: bool,
+    /// msgid of the message this replies to with `+draft/reply`
+    pub reply_to: Option<String>,
+    pub reactions: Vec<Reaction>,
+}
+
+/// A `+draft/react` to a message, shown beneath it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reaction {
+    pub nick: Nick,
+    pub text: String,
 }
 
 impl Message {
@@ -200,6 +239,9 @@ impl Message {
                             | Kind::MonitoredOffline
                             | Kind::StandardReply(_)
                             | Kind::Wallops
+                            | Kind::Ctcp
+                            | Kind::Bouncer
+                            | Kind::Notice
                     )
                 }
                 Source::Internal(source::Internal::Logs) => true,
@@ -237,6 +279,8 @@ impl Message {
     pub fn received<'a>(
         encoded: Encoded,
         our_nick: Nick,
+        server: &Server,
+        highlight_nicks: &HighlightNicks,
         config: &'a Config,
         resolve_attributes: impl Fn(&User, &target::Channel) -> Option<User>,
         channel_users: impl Fn(&target::Channel) -> &'a [User],
@@ -246,12 +290,16 @@ impl Message {
     ) -> Option<Message> {
         let server_time = server_time(&encoded);
         let id = message_id(&encoded);
+        let reply_to = reply_to(&encoded);
         let is_echo = encoded
             .user()
             .is_some_and(|user| user.nickname() == our_nick);
+        let is_notice = matches!(encoded.command, Command::NOTICE(..));
         let content = content(
             &encoded,
             &our_nick,
+            server,
+            highlight_nicks,
             config,
             &resolve_attributes,
             &channel_users,
@@ -280,6 +328,9 @@ impl Message {
             hash,
             hidden_urls: HashSet::default(),
             is_echo,
+            is_notice,
+            reply_to,
+            reactions: vec![],
         })
     }
 
@@ -298,6 +349,9 @@ impl Message {
             hash,
             hidden_urls: HashSet::default(),
             is_echo: false,
+            is_notice: false,
+            reply_to: None,
+            reactions: vec![],
         }
     }
 
@@ -324,6 +378,9 @@ impl Message {
             hash,
             hidden_urls: HashSet::default(),
             is_echo: false,
+            is_notice: false,
+            reply_to: None,
+            reactions: vec![],
         }
     }
 
@@ -350,6 +407,35 @@ impl Message {
             hash,
             hidden_urls: HashSet::default(),
             is_echo: false,
+            is_notice: false,
+            reply_to: None,
+            reactions: vec![],
+        }
+    }
+
+    /// Line for another client attaching to or detaching from the bouncer
+    pub fn bouncer_attachment(
+        service: Nick,
+        attachment: bouncer::Attachment,
+        server_time: DateTime<Utc>,
+    ) -> Message {
+        let received_at = Posix::now();
+        let content = plain(attachment.to_string());
+        let hash = Hash::new(&server_time, &content);
+
+        Message {
+            received_at,
+            server_time,
+            direction: Direction::Received,
+            target: bouncer::target(service),
+            content,
+            id: None,
+            hash,
+            hidden_urls: HashSet::default(),
+            is_echo: false,
+            is_notice: false,
+            reply_to: None,
+            reactions: vec![],
         }
     }
 
@@ -357,6 +443,18 @@ impl Message {
         Self { target, ..self }
     }
 
+    /// Adds a reaction unless `nick` already reacted with it, returning
+    /// whether it was added
+    pub fn add_reaction(&mut self, reaction: Reaction) -> bool {
+        if self.reactions.contains(&reaction) {
+            return false;
+        }
+
+        self.reactions.push(reaction);
+
+        true
+    }
+
     pub fn plain(&self) -> Option<&str> {
         match &self.content {
             Content::Plain(s) => Some(s),
@@ -369,6 +467,34 @@ impl Message {
         self.content.text().to_string()
     }
 
+    /// Approximate # of bytes held in memory by this message
+    pub fn approx_size(&self) -> usize {
+        let content = match &self.content {
+            Content::Plain(s) => s.len(),
+            Content::Fragments(fragments) => fragments
+                .iter()
+                .map(|fragment| fragment.as_str().len())
+                .sum(),
+            Content::Log(record) => {
+                record.target.len()
+                    + record.server.as_ref().map_or(0, |s| s.as_ref().len())
+                    + record.message.len()
+            }
+        };
+
+        std::mem::size_of::<Self>()
+            + content
+            + self.id.as_ref().map_or(0, String::len)
+            + self.reply_to.as_ref().map_or(0, String::len)
+            + self
+                .reactions
+                .iter()
+                .map(|reaction| {
+                    std::mem::size_of::<Reaction>() + reaction.text.len()
+                })
+                .sum::<usize>()
+    }
+
     pub fn log(record: crate::log::Record) -> Self {
         let received_at = Posix::now();
         let server_time = record.timestamp;
@@ -385,15 +511,18 @@ impl Message {
             hash,
             hidden_urls: HashSet::default(),
             is_echo: false,
+            is_notice: false,
+            reply_to: None,
+            reactions: vec![],
         }
     }
 
     pub fn has_highlight_fragment(&self) -> bool {
         if let Content::Fragments(fragments) = &self.content {
             fragments.iter().any(|fragment| match fragment {
-                Fragment::HighlightNick(_, _) | Fragment::HighlightMatch(_) => {
-                    true
-                }
+                Fragment::HighlightNick(_, _)
+                | Fragment::HighlightMatch(_)
+                | Fragment::HighlightMention(_) => true,
                 Fragment::Text(_)
                 | Fragment::Channel(_)
                 | Fragment::User(_, _)
@@ -405,6 +534,34 @@ impl Message {
         }
     }
 
+    /// Category of the message for styling its row, if it has one
+    pub fn category(&self) -> Option<Category> {
+        let source = self.target.source();
+
+        if matches!(
+            source,
+            Source::Internal(source::Internal::Status(source::Status::Error))
+        ) || matches!(
+            source,
+            Source::Server(Some(server))
+                if server.kind() == Kind::StandardReply(StandardReply::Fail)
+        ) {
+            Some(Category::Error)
+        } else if !self.is_echo && self.has_highlight_fragment() {
+            Some(Category::Highlight)
+        } else if matches!(self.direction, Direction::Sent) || self.is_echo {
+            Some(Category::Own)
+        } else {
+            match source {
+                Source::Action(_) => Some(Category::Action),
+                Source::Server(_) | Source::Internal(_) => {
+                    Some(Category::Server)
+                }
+                Source::User(_) => None,
+            }
+        }
+    }
+
     pub fn into_highlight(
         &self,
         server: Server,
@@ -440,6 +597,31 @@ impl Message {
     }
 }
 
+#[cfg(test)]
+impl Message {
+    /// A message received at `server_time`, for tests
+    pub fn test(
+        server_time: DateTime<Utc>,
+        target: Target,
+        content: Content,
+    ) -> Self {
+        Message {
+            received_at: Posix::now(),
+            server_time,
+            direction: Direction::Received,
+            target,
+            hash: Hash::new(&server_time, &content),
+            content,
+            id: None,
+            hidden_urls: HashSet::default(),
+            is_echo: false,
+            is_notice: false,
+            reply_to: None,
+            reactions: vec![],
+        }
+    }
+}
+
 impl Serialize for Message {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -458,6 +640,12 @@ impl Serialize for Message {
             text: Cow<'a, str>,
             hidden_urls: &'a HashSet<url::Url>,
             is_echo: &'a bool,
+            #[serde(skip_serializing_if = "std::ops::Not::not")]
+            is_notice: bool,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            reply_to: &'a Option<String>,
+            #[serde(skip_serializing_if = "<[_]>::is_empty")]
+            reactions: &'a [Reaction],
         }
 
         Data {
@@ -470,6 +658,9 @@ impl Serialize for Message {
             text: self.content.text(),
             hidden_urls: &self.hidden_urls,
             is_echo: &self.is_echo,
+            is_notice: self.is_notice,
+            reply_to: &self.reply_to,
+            reactions: &self.reactions,
         }
         .serialize(serializer)
     }
@@ -497,6 +688,12 @@ impl<'de> Deserialize<'de> for Message {
             // New field, optional for upgrade compatibility
             #[serde(default, deserialize_with = "fail_as_none")]
             is_echo: Option<bool>,
+            #[serde(default)]
+            is_notice: bool,
+            #[serde(default)]
+            reply_to: Option<String>,
+            #[serde(default)]
+            reactions: Vec<Reaction>,
         }
 
         let Data {
@@ -509,6 +706,9 @@ impl<'de> Deserialize<'de> for Message {
             id,
             hidden_urls,
             is_echo,
+            is_notice,
+            reply_to,
+            reactions,
         } = Data::deserialize(deserializer)?;
 
         let content = if let Some(content) = content {
@@ -535,6 +735,9 @@ impl<'de> Deserialize<'de> for Message {
             hash,
             hidden_urls,
             is_echo,
+            is_notice,
+            reply_to,
+            reactions,
         })
     }
 }
@@ -559,37 +762,53 @@ pub fn parse_fragments_with_highlights(
     text: String,
     channel_users: &[User],
     target: &str,
-    our_nick: Option<&Nick>,
+    server: &Server,
+    highlight_nicks: Option<&HighlightNicks>,
     highlights: &Highlights,
 ) -> Content {
+    let highlight_nicks = highlight_nicks
+        .filter(|_| highlights.nickname.is_target_included(target));
+
     let mut fragments = parse_fragments_with_users_inner(text, channel_users)
-        .map(|fragment| match fragment {
-            Fragment::User(user, raw)
-                if highlights.nickname.is_target_included(target)
-                    && our_nick
-                        .is_some_and(|nick| user.nickname() == *nick) =>
+        .flat_map(|fragment| match (fragment, highlight_nicks) {
+            (Fragment::User(user, raw), Some(nicks))
+                if nicks.contains(user.nickname().as_ref()) =>
             {
-                Fragment::HighlightNick(user, raw)
+                Either::Right(iter::once(Fragment::HighlightNick(user, raw)))
             }
-            f => f,
+            // Mentions of nicknames which aren't channel users
+            (Fragment::Text(text), Some(nicks)) => {
+                Either::Left(highlight_mentions(text, nicks).into_iter())
+            }
+            (fragment, _) => Either::Right(iter::once(fragment)),
         })
         .collect::<Vec<_>>();
 
-    for regex in highlights
+    for (regex, is_folded) in highlights
         .matches
         .iter()
-        .filter_map(|m| m.is_target_included(target).then_some(&m.regex))
+        .filter_map(|m| {
+            m.is_target_included(target).then_some((&m.regex, false))
+        })
+        .chain(
+            highlights
+                .patterns(server, target)
+                .map(|pattern| (&pattern.regex, pattern.is_folded)),
+        )
     {
         fragments = fragments
             .into_iter()
             .flat_map(|fragment| {
                 if let Fragment::Text(text) = &fragment {
-                    return Either::Left(
+                    let fragments = if is_folded {
+                        parse_folded_highlight_fragments(regex, text)
+                    } else {
                         parse_regex_fragments(regex, text, |text| {
                             Some(Fragment::HighlightMatch(text.to_owned()))
                         })
-                        .into_iter(),
-                    );
+                    };
+
+                    return Either::Left(fragments.into_iter());
                 }
 
                 Either::Right(iter::once(fragment))
@@ -608,6 +827,35 @@ pub fn parse_fragments_with_highlights(
     }
 }
 
+fn highlight_mentions(text: String, nicks: &HighlightNicks) -> Vec<Fragment> {
+    let mentions = nicks.mentions(&text);
+
+    if mentions.is_empty() {
+        return vec![Fragment::Text(text)];
+    }
+
+    let mut fragments = vec![];
+    let mut i = 0;
+
+    for mention in mentions {
+        if i < mention.start {
+            fragments.push(Fragment::Text(text[i..mention.start].to_string()));
+        }
+
+        fragments.push(Fragment::HighlightMention(
+            text[mention.clone()].to_string(),
+        ));
+
+        i = mention.end;
+    }
+
+    if i < text.len() {
+        fragments.push(Fragment::Text(text[i..].to_string()));
+    }
+
+    fragments
+}
+
 pub fn parse_fragments_with_user(text: String, user: &User) -> Content {
     let users: &[User] = std::slice::from_ref(user);
     parse_fragments_with_users(text, users)
@@ -673,9 +921,7 @@ fn parse_fragments_with_users_inner(
 fn parse_fragments_inner<'a>(
     text: String,
 ) -> impl Iterator<Item = Fragment> + use<'a> {
-    let mut modifiers = HashSet::new();
-    let mut fg = None;
-    let mut bg = None;
+    let mut decoder = formatting::Decoder::default();
 
     parse_regex_fragments(&URL_REGEX, text, |url| {
         let url = if url.starts_with("www") {
@@ -701,46 +947,12 @@ fn parse_fragments_inner<'a>(
     })
     .flat_map(move |fragment| {
         if let Fragment::Text(text) = &fragment {
-            if let Some(fragments) =
-                formatting::parse(text, &mut modifiers, &mut fg, &mut bg)
-            {
-                if fragments.is_empty() {
-                    return Either::Right(Either::Left(iter::empty()));
-                }
-
-                if fragments.iter().any(|fragment| {
-                    matches!(fragment, formatting::Fragment::Formatted(_, _))
-                }) {
-                    return Either::Left(
-                        fragments.into_iter().map(Fragment::from),
-                    );
-                // If there are no Formatted fragments,
-                // then fragments should contain a single Unformatted fragment
-                } else if let Some(text) = fragments
-                    .into_iter()
-                    .next()
-                    .and_then(|fragment| match fragment {
-                        formatting::Fragment::Unformatted(text) => Some(text),
-                        formatting::Fragment::Formatted(_, _) => None,
-                    })
-                {
-                    // Even if the fragment is Unformatted there may have been formatting
-                    // characters in the text input into formatting::parse. They are
-                    // stripped from the text contained in the fragment.
-                    return Either::Right(Either::Right(iter::once(
-                        Fragment::Text(text),
-                    )));
-                }
-            } else if text.is_empty() {
-                return Either::Right(Either::Left(iter::empty()));
-            } else {
-                return Either::Right(Either::Right(iter::once(
-                    Fragment::Text(text.clone()),
-                )));
-            }
+            return Either::Left(
+                decoder.decode(text).into_iter().map(Fragment::from),
+            );
         }
 
-        Either::Right(Either::Right(iter::once(fragment)))
+        Either::Right(iter::once(fragment))
     })
 }
 
@@ -776,6 +988,38 @@ fn parse_regex_fragments<'a>(
     fragments
 }
 
+/// Highlights where `regex` matches `text` once folded, ignoring accents
+fn parse_folded_highlight_fragments(
+    regex: &Regex,
+    text: &str,
+) -> Vec<Fragment> {
+    let folded = crate::fold::Folded::new(text);
+
+    let mut i = 0;
+    let mut fragments = Vec::with_capacity(1);
+
+    for range in regex
+        .find_iter(folded.as_str())
+        .filter_map(Result::ok)
+        .map(|re_match| folded.original_range(re_match.start()..re_match.end()))
+    {
+        // Matches within the same char of `text` are highlighted once
+        if range.start < i || range.is_empty() {
+            continue;
+        }
+
+        if i < range.start {
+            fragments.push(Fragment::Text(text[i..range.start].to_string()));
+        }
+        i = range.end;
+        fragments.push(Fragment::HighlightMatch(text[range].to_string()));
+    }
+
+    fragments.push(Fragment::Text(text[i..].to_string()));
+
+    fragments
+}
+
 #[derive(Debug, Clone, Eq, Serialize, Deserialize)]
 pub enum Content {
     Plain(String),
@@ -784,13 +1028,13 @@ pub enum Content {
 }
 
 impl Content {
-    fn text(&self) -> Cow<str> {
+    pub fn text(&self) -> Cow<str> {
         match self {
             Content::Plain(s) => s.into(),
             Content::Fragments(fragments) => {
                 fragments.iter().map(Fragment::as_str).join("").into()
             }
-            Content::Log(record) => (&record.message).into(),
+            Content::Log(record) => record.text().into(),
         }
     }
 
@@ -821,8 +1065,11 @@ pub enum Fragment {
         text: String,
         formatting: Formatting,
     },
+    /// Mention of a channel user who is us, by one of our nicknames
     HighlightNick(User, String),
     HighlightMatch(String),
+    /// Mention of one of our nicknames, other than a channel user
+    HighlightMention(String),
 }
 
 impl Fragment {
@@ -843,17 +1090,17 @@ impl Fragment {
             Fragment::Formatted { text, .. } => text,
             Fragment::HighlightNick(_, s) => s,
             Fragment::HighlightMatch(s) => s,
+            Fragment::HighlightMention(s) => s,
         }
     }
 }
 
-impl From<formatting::Fragment> for Fragment {
-    fn from(value: formatting::Fragment) -> Self {
-        match value {
-            formatting::Fragment::Unformatted(text) => Self::Text(text),
-            formatting::Fragment::Formatted(text, formatting) => {
-                Self::Formatted { text, formatting }
-            }
+impl From<formatting::Span> for Fragment {
+    fn from(formatting::Span { text, formatting }: formatting::Span) -> Self {
+        if formatting == Formatting::default() {
+            Self::Text(text)
+        } else {
+            Self::Formatted { text, formatting }
         }
     }
 }
@@ -869,10 +1116,11 @@ fn target(
     use proto::command::Numeric::*;
 
     let user = message.user();
+    let is_notice = matches!(message.command, Command::NOTICE(..));
 
     match message.0.command {
         // Channel
-        Command::MODE(target, ..) => {
+        Command::MODE(target, modes, args) => {
             let channel = target::Channel::parse(
                 &target,
                 chantypes,
@@ -881,9 +1129,16 @@ fn target(
             )
             .ok()?;
 
+            let source = modes.map(|modes| {
+                source::Server::mode_change(
+                    user.map(|user| user.nickname().to_owned()),
+                    mode::Change::parse(&modes, &args.unwrap_or_default()),
+                )
+            });
+
             Some(Target::Channel {
                 channel,
-                source: Source::Server(None),
+                source: Source::Server(source),
             })
         }
         Command::TOPIC(channel, _) | Command::KICK(channel, _, _) => {
@@ -950,6 +1205,21 @@ fn target(
                 ))),
             })
         }
+        Command::Numeric(ERR_LINKCHANNEL, params) => {
+            // Shown in the channel we were forwarded to, as that's the buffer
+            // opened in place of the requested one
+            let channel = target::Channel::parse(
+                params.get(2)?,
+                chantypes,
+                statusmsg,
+                casemapping,
+            )
+            .ok()?;
+            Some(Target::Channel {
+                channel,
+                source: Source::Server(None),
+            })
+        }
         Command::Numeric(RPL_CHANNELMODEIS, params) => {
             let channel = target::Channel::parse(
                 params.get(1)?,
@@ -994,7 +1264,10 @@ fn target(
 
                 Some(Target::Query {
                     query: target::Query::from_user(&user, casemapping),
-                    source: Source::Server(None),
+                    source: Source::Server(Some(source::Server::new(
+                        Kind::Ctcp,
+                        Some(user.nickname().to_owned()),
+                    ))),
                 })
             } else {
                 let source = |user| {
@@ -1040,6 +1313,8 @@ fn target(
                             source: source(user),
                         })
                     }
+                    // Server notices
+                    (_, None) if is_notice => Some(Target::notice(None)),
                     _ => None,
                 }
             }
@@ -1126,6 +1401,7 @@ fn target(
         | Command::CPRIVMSG(_, _, _)
         | Command::KNOCK(_, _)
         | Command::MARKREAD(_, _)
+        | Command::METADATA(_, _, _)
         | Command::MONITOR(_, _)
         | Command::SETNAME(_)
         | Command::TAGMSG(_)
@@ -1147,6 +1423,25 @@ pub fn message_id(message: &Encoded) -> Option<String> {
         .and_then(|tag| tag.value.clone())
 }
 
+/// msgid of the message replied or reacted to with `+draft/reply`
+pub fn reply_to(message: &Encoded) -> Option<String> {
+    message
+        .tags
+        .iter()
+        .find(|tag| &tag.key == "+draft/reply")
+        .and_then(|tag| tag.value.clone())
+}
+
+/// Emoji or text reacted with in a `+draft/react` TAGMSG
+pub fn reaction(message: &Encoded) -> Option<String> {
+    message
+        .tags
+        .iter()
+        .find(|tag| &tag.key == "+draft/react")
+        .and_then(|tag| tag.value.clone())
+        .filter(|reaction| !reaction.is_empty())
+}
+
 pub fn server_time(message: &Encoded) -> DateTime<Utc> {
     message
         .tags
@@ -1160,6 +1455,8 @@ pub fn server_time(message: &Encoded) -> DateTime<Utc> {
 fn content<'a>(
     message: &Encoded,
     our_nick: &Nick,
+    server: &Server,
+    highlight_nicks: &HighlightNicks,
     config: &Config,
     resolve_attributes: &dyn Fn(&User, &target::Channel) -> Option<User>,
     channel_users: &dyn Fn(&target::Channel) -> &'a [User],
@@ -1291,18 +1588,10 @@ fn content<'a>(
                     let user = resolve_attributes(&raw_user, &channel)
                         .unwrap_or(raw_user);
 
-                    let modes = modes
-                        .iter()
-                        .map(ToString::to_string)
-                        .collect::<Vec<_>>()
-                        .join(" ");
-
-                    let args = args
-                        .iter()
-                        .flatten()
-                        .map(ToString::to_string)
-                        .collect::<Vec<_>>()
-                        .join(" ");
+                    let change = mode::Change::parse(
+                        modes.as_deref().unwrap_or_default(),
+                        args.as_deref().unwrap_or_default(),
+                    );
 
                     let channel_users = target::Channel::parse(
                         target,
@@ -1314,20 +1603,39 @@ fn content<'a>(
                     .unwrap_or_default();
 
                     parse_fragments_with_users(
-                        format!("{} sets mode {modes} {args}", user.nickname()),
+                        format!(
+                            "{} {}",
+                            user.nickname(),
+                            change.describe(None)
+                        ),
                         channel_users,
                     )
                 })
         }
         Command::PRIVMSG(target, text) | Command::NOTICE(target, text) => {
-            let channel_users = target::Channel::parse(
+            let channel = target::Channel::parse(
                 target,
                 chantypes,
                 statusmsg,
                 casemapping,
             )
-            .map(|channel| channel_users(&channel))
-            .unwrap_or_default();
+            .ok();
+            let channel_users =
+                channel.as_ref().map(channel_users).unwrap_or_default();
+
+            // Highlight settings name channels without STATUSMSG prefixes
+            let highlight_target =
+                channel.as_ref().map_or(target.as_str(), |channel| {
+                    target.trim_start_matches(channel.prefixes())
+                });
+
+            // Echoes are parsed as the messages we send are, so they show the
+            // same as when they're echoed locally
+            let highlight_nicks = Some(highlight_nicks).filter(|_| {
+                message
+                    .user()
+                    .is_none_or(|user| user.nickname() != *our_nick)
+            });
 
             // Check if a synthetic action message
 
@@ -1336,8 +1644,9 @@ fn content<'a>(
                     nick,
                     text,
                     channel_users,
-                    target,
-                    Some(our_nick),
+                    highlight_target,
+                    server,
+                    highlight_nicks,
                     &config.highlights,
                 ) {
                     return Some(action);
@@ -1345,14 +1654,31 @@ fn content<'a>(
             }
 
             if let Some(query) = ctcp::parse_query(text) {
+                let command = query.command.as_ref();
+
+                // Requests sent to us are shown in the server buffer
+                if let Some(user) = message.user().filter(|_| {
+                    matches!(message.command, Command::PRIVMSG(..))
+                        && target == our_nick.as_ref()
+                }) {
+                    let text = if let Some(params) = query.params {
+                        format!(
+                            "{} requested CTCP {command} {params}",
+                            user.nickname()
+                        )
+                    } else {
+                        format!("{} requested CTCP {command}", user.nickname())
+                    };
+
+                    return Some(parse_fragments_with_user(text, &user));
+                }
+
                 let arrow = if target == our_nick.as_ref() {
                     "⟵"
                 } else {
                     "⟶"
                 };
 
-                let command = query.command.as_ref();
-
                 let text = if let Some(params) = query.params {
                     [arrow, command, params].join(" ")
                 } else {
@@ -1365,8 +1691,9 @@ fn content<'a>(
             Some(parse_fragments_with_highlights(
                 text.clone(),
                 channel_users,
-                target,
-                Some(our_nick),
+                highlight_target,
+                server,
+                highlight_nicks,
                 &config.highlights,
             ))
         }
@@ -1496,6 +1823,12 @@ fn content<'a>(
 
             Some(parse_fragments(format!("Channel mode is {mode}")))
         }
+        Command::Numeric(ERR_LINKCHANNEL, params) => {
+            let from = params.get(1)?;
+            let to = params.get(2)?;
+
+            Some(parse_fragments(format!("{from} forwarded you to {to}")))
+        }
         Command::Numeric(RPL_UMODEIS, params) => {
             let mode = params
                 .iter()
@@ -1551,35 +1884,12 @@ fn content<'a>(
                 None
             }
         }
-        Command::FAIL(command, _, context, description) => {
-            if let Some(context) = context {
-                Some(plain(format!(
-                    "{command} ({}) failed: {description}",
-                    context.join(", ")
-                )))
-            } else {
-                Some(plain(format!("{command} failed: {description}")))
-            }
-        }
-        Command::WARN(command, _, context, description) => {
-            if let Some(context) = context {
-                Some(plain(format!(
-                    "{command} ({}) warning: {description}",
-                    context.join(", ")
-                )))
-            } else {
-                Some(plain(format!("{command} warning: {description}")))
-            }
-        }
-        Command::NOTE(command, _, context, description) => {
-            if let Some(context) = context {
-                Some(plain(format!(
-                    "{command} ({}) notice: {description}",
-                    context.join(", ")
-                )))
-            } else {
-                Some(plain(format!("{command} notice: {description}")))
-            }
+        Command::FAIL(_, _, _, _)
+        | Command::WARN(_, _, _, _)
+        | Command::NOTE(_, _, _, _) => {
+            let reply = standard_reply::Reply::parse(&message.command)?;
+
+            Some(plain(reply.text()))
         }
         Command::WALLOPS(text) => {
             let user = message.user()?;
@@ -1636,7 +1946,8 @@ fn parse_action(
     text: &str,
     channel_users: &[User],
     target: &str,
-    our_nick: Option<&Nick>,
+    server: &Server,
+    highlight_nicks: Option<&HighlightNicks>,
     highlights: &Highlights,
 ) -> Option<Content> {
     if !is_action(text) {
@@ -1650,7 +1961,8 @@ fn parse_action(
         query.params,
         channel_users,
         target,
-        our_nick,
+        server,
+        highlight_nicks,
         highlights,
     ))
 }
@@ -1660,7 +1972,8 @@ pub fn action_text(
     action: Option<&str>,
     channel_users: &[User],
     target: &str,
-    our_nick: Option<&Nick>,
+    server: &Server,
+    highlight_nicks: Option<&HighlightNicks>,
     highlights: &Highlights,
 ) -> Content {
     let text = if let Some(action) = action {
@@ -1673,7 +1986,8 @@ pub fn action_text(
         text,
         channel_users,
         target,
-        our_nick,
+        server,
+        highlight_nicks,
         highlights,
     )
 }
@@ -1755,13 +2069,19 @@ impl PartialOrd for MessageReferences {
 
 #[cfg(test)]
 mod tests {
-    use super::{parse_fragments, parse_fragments_with_highlights};
-    use crate::User;
-    use crate::config::Highlights;
+    use irc::proto;
+
+    use super::{
+        Category, Encoded, Message, Source, action_text, parse_fragments,
+        parse_fragments_with_highlights,
+    };
     use crate::config::highlights::Nickname;
+    use crate::config::{Highlights, Matching};
+    use crate::isupport::CaseMap;
     use crate::message::formatting::Color;
     use crate::message::{Content, Formatting, Fragment};
-    use crate::user::Nick;
+    use crate::user::{HighlightNicks, Nick, NickRef};
+    use crate::{Config, Server, User};
 
     #[test]
     fn fragment_parsing() {
@@ -1919,10 +2239,12 @@ mod tests {
                         User::try_from("`Bill`").unwrap(),
                     ]),
                     "#interesting",
-                    Some(Nick::from("Bob")),
+                    HighlightNicks::new(["Bob"], CaseMap::default()),
                     &Highlights {
-                        nickname: Nickname {exclude: vec![], include: vec!["#interesting".into()]},
+                        nickname: Nickname {exclude: vec![], include: vec!["#interesting".into()], grace_period: 300},
                         matches: vec![],
+                        nicknames: vec![],
+                        ..Highlights::default()
                     },
                 ),
                 vec![
@@ -1948,27 +2270,63 @@ mod tests {
                         User::try_from("rx").unwrap(),
                     ]),
                     "#funderscore-sucks",
-                    Some(Nick::from("f_")),
+                    HighlightNicks::new(["f_"], CaseMap::default()),
                     &Highlights {
-                        nickname: Nickname {exclude: vec![], include: vec!["*".into()]},
+                        nickname: Nickname {exclude: vec![], include: vec!["*".into()], grace_period: 300},
                         matches: vec![],
+                        nicknames: vec![],
+                        ..Highlights::default()
                     },
                 ),
                 vec![
-                    Fragment::Text("\u{3}14<\u{3}\u{3}04lurk_\u{3}\u{3}14/rx>\u{3} ".into()),
+                    Fragment::Formatted { text: "<".into(), formatting: Formatting { fg: Some(Color::Grey), ..Formatting::default() } },
+                    Fragment::Formatted { text: "lurk_".into(), formatting: Formatting { fg: Some(Color::Red), ..Formatting::default() } },
+                    Fragment::Formatted { text: "/rx>".into(), formatting: Formatting { fg: Some(Color::Grey), ..Formatting::default() } },
+                    Fragment::Text(" ".into()),
                     Fragment::HighlightNick(User::try_from("f_").unwrap(), "f_".into()),
-                    Fragment::Text("~oftc: > A��\u{1f}qj\u{14}��L�5�g���5�P��yn_?�i3g�1\u{7f}mE�\\X��� Xe�\u{5fa}{d�+�`@�^��NK��~~ޏ\u{7}\u{8}\u{15}\\�\u{4}A� \u{f}\u{1c}�N\u{11}6�r�\u{4}t��Q��\u{1c}�m\u{19}��".into())
+                    Fragment::Text("~oftc: > A��".into()),
+                    Fragment::Formatted { text: "qj\u{14}��L�5�g���5�P��yn_?�i3g�1\u{7f}mE�\\X��� Xe�\u{5fa}{d�+�`@�^��NK��~~ޏ\u{7}\u{8}\u{15}\\�A� ".into(), formatting: Formatting { underline: true, ..Formatting::default() } },
+                    Fragment::Text("\u{1c}�N".into()),
+                    Fragment::Formatted { text: "6�r�t��Q��\u{1c}�m\u{19}��".into(), formatting: Formatting { monospace: true, ..Formatting::default() } },
+                ],
+            ),
+            (
+                (
+                    "robert, ask Bobby or BOB_ about Roberta".to_string(),
+                    &Vec::from([
+                        User::try_from("Bob_").unwrap(),
+                        User::try_from("Bobby").unwrap(),
+                    ]),
+                    "#interesting",
+                    HighlightNicks::new(["Bob", "Bob_", "Robert"], CaseMap::default()),
+                    &Highlights {
+                        nickname: Nickname {exclude: vec![], include: vec!["*".into()], grace_period: 300},
+                        matches: vec![],
+                        nicknames: vec!["Robert".into()],
+                        ..Highlights::default()
+                    },
+                ),
+                vec![
+                    Fragment::HighlightMention("robert".into()),
+                    Fragment::Text(", ask ".into()),
+                    Fragment::User(User::try_from("Bobby").unwrap(), "Bobby".into()),
+                    Fragment::Text(" or ".into()),
+                    Fragment::HighlightNick(User::try_from("Bob_").unwrap(), "BOB_".into()),
+                    Fragment::Text(" about Roberta".into()),
                 ],
             ),
         ];
-        for ((text, channel_users, target, our_nick, highlights), expected) in
-            tests
+        for (
+            (text, channel_users, target, highlight_nicks, highlights),
+            expected,
+        ) in tests
         {
             if let Content::Fragments(actual) = parse_fragments_with_highlights(
                 text,
                 channel_users,
                 target,
-                our_nick.as_ref(),
+                &Server::from("libera"),
+                Some(&highlight_nicks),
                 highlights,
             ) {
                 assert_eq!(expected, actual);
@@ -1977,4 +2335,302 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn highlights_in_actions_and_notices() {
+        let mut config = Config::default();
+        config.highlights.nickname.exclude = vec!["*".into()];
+        config.highlights.nickname.include = vec!["#halloy".into()];
+
+        let highlight_nicks = HighlightNicks::new(["kai"], CaseMap::default());
+        let users = [User::from(Nick::from("alice"))];
+
+        let tests = [
+            (
+                ":alice!a@host PRIVMSG #halloy :\u{1}ACTION pokes kai\u{1}",
+                true,
+            ),
+            (":alice!a@host NOTICE #halloy :kai: wake up", false),
+            (
+                ":alice!a@host NOTICE #halloy :\u{1}ACTION pokes kai\u{1}",
+                true,
+            ),
+            (
+                ":alice!a@host PRIVMSG @#halloy :\u{1}ACTION pokes kai\u{1}",
+                true,
+            ),
+            (":alice!a@host NOTICE +#halloy :kai: wake up", false),
+        ];
+
+        for (raw, is_action) in tests {
+            let encoded = Encoded::from(
+                proto::parse::message(&format!("{raw}\r\n")).unwrap(),
+            );
+
+            let message = Message::received(
+                encoded,
+                Nick::from("kai"),
+                &Server::from("libera"),
+                &highlight_nicks,
+                &config,
+                |_, _| None,
+                |_| &users,
+                &['#'],
+                &['@', '+'],
+                CaseMap::default(),
+            )
+            .unwrap();
+
+            let (highlight, channel, user) =
+                message.into_highlight(Server::from("libera")).expect(raw);
+
+            assert_eq!(channel.as_normalized_str(), "#halloy", "{raw}");
+            assert_eq!(user.nickname(), Nick::from("alice"), "{raw}");
+            assert_eq!(
+                matches!(highlight.target.source(), Source::Action(_)),
+                is_action,
+                "{raw}"
+            );
+        }
+    }
+
+    #[test]
+    fn categories() {
+        let config = Config::default();
+        let highlight_nicks = HighlightNicks::new(["kai"], CaseMap::default());
+        let users = [User::from(Nick::from("alice"))];
+
+        let tests = [
+            (":alice!a@host PRIVMSG #halloy :hello", None),
+            (
+                ":alice!a@host PRIVMSG #halloy :hello kai",
+                Some(Category::Highlight),
+            ),
+            (
+                ":alice!a@host PRIVMSG #halloy :\u{1}ACTION waves\u{1}",
+                Some(Category::Action),
+            ),
+            (":kai!k@host PRIVMSG #halloy :hi kai", Some(Category::Own)),
+            (":alice!a@host JOIN #halloy", Some(Category::Server)),
+            (
+                ":irc.example FAIL CHATHISTORY MESSAGE_ERROR :Oops",
+                Some(Category::Error),
+            ),
+        ];
+
+        for (raw, category) in tests {
+            let encoded = Encoded::from(
+                proto::parse::message(&format!("{raw}\r\n")).unwrap(),
+            );
+
+            let message = Message::received(
+                encoded,
+                Nick::from("kai"),
+                &Server::from("libera"),
+                &highlight_nicks,
+                &config,
+                |_, _| None,
+                |_| &users,
+                &['#'],
+                &['@', '+'],
+                CaseMap::default(),
+            )
+            .expect(raw);
+
+            assert_eq!(message.category(), category, "{raw}");
+        }
+    }
+
+    #[test]
+    fn echoed_actions() {
+        let config = Config::default();
+        let highlight_nicks = HighlightNicks::new(["kai"], CaseMap::default());
+        let users = [
+            User::from(Nick::from("kai")),
+            User::from(Nick::from("alice")),
+        ];
+        let server = Server::from("libera");
+
+        let raw =
+            ":kai!k@host PRIVMSG #halloy :\u{1}ACTION waves at alice\u{1}";
+        let encoded = Encoded::from(
+            proto::parse::message(&format!("{raw}\r\n")).unwrap(),
+        );
+
+        let message = Message::received(
+            encoded,
+            Nick::from("kai"),
+            &server,
+            &highlight_nicks,
+            &config,
+            |_, _| None,
+            |_| &users,
+            &['#'],
+            &['@', '+'],
+            CaseMap::default(),
+        )
+        .unwrap();
+
+        // Shown as the action is when echoed locally, without highlighting
+        // our own nickname
+        assert!(matches!(message.target.source(), Source::Action(Some(_))));
+        assert_eq!(
+            message.content,
+            action_text(
+                NickRef::from("kai"),
+                Some("waves at alice"),
+                &users,
+                "#halloy",
+                &server,
+                None,
+                &config.highlights,
+            )
+        );
+        assert!(!message.has_highlight_fragment());
+
+        // Stored as an action
+        let stored: Message =
+            serde_json::from_str(&serde_json::to_string(&message).unwrap())
+                .unwrap();
+        assert!(matches!(stored.target.source(), Source::Action(Some(_))));
+        assert_eq!(stored.content, message.content);
+    }
+
+    #[test]
+    fn highlight_words_and_regexes() {
+        let highlights = Highlights {
+            words: vec!["Halloy".into()],
+            regexes: vec![r"\bdeploy(ed)?\b".into()],
+            exclude_servers: vec!["oftc".into()],
+            exclude_channels: vec!["#noisy".into()],
+            ..Highlights::default()
+        }
+        .compile(Matching::default())
+        .unwrap();
+
+        let highlight = |text: &str, server: &str, target: &str| {
+            match parse_fragments_with_highlights(
+                text.to_string(),
+                &[],
+                target,
+                &Server::from(server),
+                None,
+                &highlights,
+            ) {
+                Content::Fragments(fragments) => fragments
+                    .into_iter()
+                    .filter_map(|fragment| match fragment {
+                        Fragment::HighlightMatch(text) => Some(text),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+                Content::Plain(_) | Content::Log(_) => vec![],
+            }
+        };
+
+        assert_eq!(
+            highlight("we deployed halloy to HALLOYs", "libera", "#dev"),
+            vec!["deployed", "halloy", "HALLOY"]
+        );
+        assert!(highlight("redeploy", "libera", "#dev").is_empty());
+        assert!(highlight("deployed halloy", "oftc", "#dev").is_empty());
+        assert!(highlight("deployed halloy", "libera", "#noisy").is_empty());
+
+        let invalid = Highlights {
+            regexes: vec!["(unclosed".into()],
+            ..Highlights::default()
+        };
+        assert!(matches!(
+            invalid.compile(Matching::default()),
+            Err(crate::config::Error::InvalidHighlightRegex { .. })
+        ));
+    }
+
+    #[test]
+    fn highlight_words_accent_insensitive() {
+        let highlights = |accent_insensitive| {
+            Highlights {
+                words: vec!["cafe".into(), "Über".into()],
+                ..Highlights::default()
+            }
+            .compile(Matching { accent_insensitive })
+            .unwrap()
+        };
+        let highlight_nicks = HighlightNicks::new(["José"], CaseMap::default());
+
+        let highlight = |text: &str, highlights: &Highlights| {
+            match parse_fragments_with_highlights(
+                text.to_string(),
+                &[],
+                "#dev",
+                &Server::from("libera"),
+                Some(&highlight_nicks),
+                highlights,
+            ) {
+                Content::Fragments(fragments) => fragments
+                    .into_iter()
+                    .filter_map(|fragment| match fragment {
+                        Fragment::HighlightMatch(text)
+                        | Fragment::HighlightMention(text) => Some(text),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+                Content::Plain(_) | Content::Log(_) => vec![],
+            }
+        };
+
+        let text = "un CAFÉ, cafe\u{301} ou uber? jose";
+
+        assert_eq!(
+            highlight(text, &highlights(true)),
+            vec!["CAFÉ", "cafe\u{301}", "uber"]
+        );
+        assert_eq!(highlight(text, &highlights(false)), vec!["cafe"]);
+        // Nicknames follow the casemapping only
+        assert_eq!(highlight("josé or jose", &highlights(true)), vec!["josé"]);
+    }
+
+    #[test]
+    fn highlight_mentions_word_boundaries() {
+        let highlight_nicks = HighlightNicks::new(["john"], CaseMap::default());
+        let fragments = |text: &str| match parse_fragments_with_highlights(
+            text.to_string(),
+            &[],
+            "#dev",
+            &Server::from("libera"),
+            Some(&highlight_nicks),
+            &Highlights::default(),
+        ) {
+            Content::Fragments(fragments) => fragments,
+            Content::Plain(text) => vec![Fragment::Text(text)],
+            Content::Log(_) => vec![],
+        };
+
+        // Part of a longer nickname
+        assert_eq!(
+            fragments("johnny: hi"),
+            vec![Fragment::Text("johnny: hi".into())]
+        );
+        assert_eq!(
+            fragments("ask bigjohn"),
+            vec![Fragment::Text("ask bigjohn".into())]
+        );
+
+        // Next to punctuation
+        assert_eq!(
+            fragments("john: hi"),
+            vec![
+                Fragment::HighlightMention("john".into()),
+                Fragment::Text(": hi".into()),
+            ]
+        );
+        assert_eq!(
+            fragments("thanks @John, and johnny"),
+            vec![
+                Fragment::Text("thanks @".into()),
+                Fragment::HighlightMention("John".into()),
+                Fragment::Text(", and johnny".into()),
+            ]
+        );
+    }
 }
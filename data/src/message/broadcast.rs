@@ -1,13 +1,17 @@
 //! Generate messages that can be broadcast into every buffer
 use std::collections::HashSet;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, Utc};
+use itertools::Itertools;
 
 use super::{
-    Content, Direction, Message, Source, Target, parse_fragments_with_user,
-    parse_fragments_with_users, plain, source,
+    Content, Direction, Fragment, Message, Source, Target, formatting,
+    parse_fragments_with_user, parse_fragments_with_users, plain, source,
 };
+use crate::client::server_info;
 use crate::config::buffer::UsernameFormat;
+use crate::history::audit;
+use crate::stream::disconnect;
 use crate::time::Posix;
 use crate::user::Nick;
 use crate::{Config, User, message, target};
@@ -38,6 +42,9 @@ fn expand(
             hash,
             hidden_urls: HashSet::default(),
             is_echo: false,
+            is_notice: false,
+            reply_to: None,
+            reactions: vec![],
         }
     };
 
@@ -122,10 +129,24 @@ pub fn disconnected(
     channels: impl IntoIterator<Item = target::Channel>,
     queries: impl IntoIterator<Item = target::Query>,
     error: Option<String>,
+    reason: Option<disconnect::Reason>,
     sent_time: DateTime<Utc>,
 ) -> Vec<Message> {
+    let reason = reason
+        .map(|reason| match reason {
+            disconnect::Reason::Banned => {
+                format!(": {reason}, not reconnecting")
+            }
+            disconnect::Reason::Throttled => {
+                format!(": {reason}, waiting longer to reconnect")
+            }
+            disconnect::Reason::PingTimeout | disconnect::Reason::Shutdown => {
+                format!(": {reason}")
+            }
+        })
+        .unwrap_or_default();
     let error = error.map(|error| format!(" ({error})")).unwrap_or_default();
-    let content = plain(format!("connection to server lost{error}"));
+    let content = plain(format!("connection to server lost{reason}{error}"));
     expand(
         channels,
         queries,
@@ -213,7 +234,10 @@ pub fn nickname(
         channels,
         queries,
         false,
-        Cause::Server(None),
+        Cause::Server(Some(source::Server::nick_change(
+            old_nick.clone(),
+            new_nick.clone(),
+        ))),
         content,
         sent_time,
     )
@@ -284,3 +308,189 @@ pub fn change_host(
         )
     }
 }
+
+pub fn users_resynced(
+    channel: target::Channel,
+    removed: &[Nick],
+    added: &[Nick],
+    sent_time: DateTime<Utc>,
+) -> Vec<Message> {
+    let describe = |nicks: &[Nick], verb: &str, adjective: &str| {
+        let plural = if nicks.len() == 1 { "" } else { "s" };
+
+        format!(
+            "{verb} {} {adjective} user{plural} ({})",
+            nicks.len(),
+            nicks.iter().join(", ")
+        )
+    };
+
+    let changes = [
+        (!removed.is_empty()).then(|| describe(removed, "removed", "stale")),
+        (!added.is_empty()).then(|| describe(added, "added", "missing")),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+
+    let content = if changes.is_empty() {
+        plain("user list resynced, no changes".into())
+    } else {
+        plain(format!("user list resynced: {}", changes.join(", ")))
+    };
+
+    expand(
+        [channel],
+        [],
+        false,
+        Cause::Status(source::Status::Success),
+        content,
+        sent_time,
+    )
+}
+
+/// A single message in the channel listing its last topics, oldest first
+pub fn topic_history(
+    channel: target::Channel,
+    topics: &[audit::Entry],
+    sent_time: DateTime<Utc>,
+) -> Vec<Message> {
+    let content = if topics.is_empty() {
+        plain("no topic changes recorded".into())
+    } else {
+        let heading = if topics.len() == 1 {
+            "last topic".to_string()
+        } else {
+            format!("last {} topics", topics.len())
+        };
+
+        let fragments = std::iter::once(Fragment::Text(heading))
+            .chain(topics.iter().flat_map(|entry| {
+                let time = entry
+                    .server_time
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M");
+                let line = match &entry.actor {
+                    Some(actor) => format!("{time} {actor} {}", entry.change),
+                    None => format!("{time} {}", entry.change),
+                };
+
+                [Fragment::Text("\n".into()), Fragment::Text(line)]
+            }))
+            .collect();
+
+        Content::Fragments(fragments)
+    };
+
+    expand(
+        [channel],
+        [],
+        false,
+        Cause::Status(source::Status::Success),
+        content,
+        sent_time,
+    )
+}
+
+/// A single message in the server buffer, with the report's lines under its
+/// title
+pub fn server_info(
+    report: &server_info::Report,
+    sent_time: DateTime<Utc>,
+) -> Vec<Message> {
+    let heading = match report.note() {
+        Some(note) => format!("{} ({note})", report.title()),
+        None => report.title().to_string(),
+    };
+
+    let fragments = std::iter::once(Fragment::Text(heading))
+        .chain(report.lines.iter().flat_map(|line| {
+            let line = if report.is_monospace() {
+                Fragment::Formatted {
+                    text: line.clone(),
+                    formatting: formatting::Formatting {
+                        monospace: true,
+                        ..formatting::Formatting::default()
+                    },
+                }
+            } else {
+                Fragment::Text(line.clone())
+            };
+
+            [Fragment::Text("\n".into()), line]
+        }))
+        .collect();
+
+    let status = if report.timed_out {
+        source::Status::Error
+    } else {
+        source::Status::Success
+    };
+
+    expand(
+        [],
+        [],
+        true,
+        Cause::Status(status),
+        Content::Fragments(fragments),
+        sent_time,
+    )
+}
+
+/// A message in the query of each monitored user that came online or went
+/// offline
+pub fn monitored(
+    queries: impl IntoIterator<Item = target::Query>,
+    users: &[Nick],
+    online: bool,
+    sent_time: DateTime<Utc>,
+) -> Vec<Message> {
+    let (kind, status) = if online {
+        (source::server::Kind::MonitoredOnline, "online")
+    } else {
+        (source::server::Kind::MonitoredOffline, "offline")
+    };
+
+    queries
+        .into_iter()
+        .filter_map(|query| {
+            let nick = users.iter().find(|nick| {
+                nick.as_ref().eq_ignore_ascii_case(query.as_str())
+            })?;
+            let user = User::from(nick.clone());
+
+            Some(expand(
+                [],
+                [query],
+                false,
+                Cause::Server(Some(source::Server::new(
+                    kind,
+                    Some(nick.clone()),
+                ))),
+                parse_fragments_with_user(format!("{nick} is {status}"), &user),
+                sent_time,
+            ))
+        })
+        .flatten()
+        .collect()
+}
+
+pub fn monitor_list_truncated(
+    limit: usize,
+    left_out: &[String],
+    sent_time: DateTime<Utc>,
+) -> Vec<Message> {
+    let content = plain(format!(
+        "server only allows monitoring {limit} users, not monitoring {}",
+        left_out.join(", ")
+    ));
+
+    expand(
+        [],
+        [],
+        true,
+        Cause::Status(source::Status::Error),
+        content,
+        sent_time,
+    )
+}
@@ -0,0 +1,326 @@
+//! Folds runs of joins, parts, quits and nick changes into a single line,
+//! and mode changes made one after another by the same user
+use std::collections::HashMap;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use itertools::Itertools;
+
+use super::{Hash, Kind, Message, Source};
+use crate::mode;
+use crate::user::Nick;
+
+/// Shorter runs are shown as is
+const MIN_RUN: usize = 2;
+
+/// Targets of each mode named in the line of a run of mode changes, the rest
+/// are counted
+const NAMED_TARGETS: usize = 2;
+
+#[derive(Debug)]
+pub enum Item<'a> {
+    Message(&'a Message),
+    Fold(Fold<'a>),
+}
+
+#[derive(Debug)]
+pub struct Fold<'a> {
+    pub messages: Vec<&'a Message>,
+}
+
+impl<'a> Fold<'a> {
+    /// The first event of the run, which stays the same as more events are
+    /// added to its end
+    pub fn id(&self) -> Hash {
+        self.messages[0].hash
+    }
+
+    pub fn first(&self) -> &'a Message {
+        self.messages[0]
+    }
+
+    /// e.g. "12 joins, 3 quits", or "ChanServ gave op to alice, bob, +13
+    /// more" for mode changes
+    pub fn summary(&self) -> String {
+        if let Some((setter, change)) = mode_changes(&self.messages) {
+            return match setter {
+                Some(setter) => {
+                    format!("{setter} {}", change.describe(Some(NAMED_TARGETS)))
+                }
+                None => change.describe(Some(NAMED_TARGETS)),
+            };
+        }
+
+        [
+            (Kind::Join, "join"),
+            (Kind::Part, "part"),
+            (Kind::Quit, "quit"),
+            (Kind::ChangeNick, "nick change"),
+        ]
+        .into_iter()
+        .filter_map(|(kind, noun)| {
+            let count = self
+                .messages
+                .iter()
+                .filter(|message| {
+                    event(message).is_some_and(|(event, _)| event == kind)
+                })
+                .count();
+            let plural = if count == 1 { "" } else { "s" };
+
+            (count > 0).then(|| format!("{count} {noun}{plural}"))
+        })
+        .join(", ")
+    }
+}
+
+/// Folds runs of consecutive mode changes in `messages` and, with
+/// `collapse_events`, runs of consecutive events. With `exempt_active`,
+/// events of users who sent a message within that many seconds before are
+/// left unfolded.
+pub fn fold<'a>(
+    messages: &[&'a Message],
+    collapse_events: bool,
+    exempt_active: Option<i64>,
+) -> Vec<Item<'a>> {
+    let mut last_spoke = HashMap::<Nick, DateTime<Utc>>::new();
+    let mut items = vec![];
+    let mut run = Vec::<&Message>::new();
+
+    let end_run = |run: &mut Vec<&'a Message>, items: &mut Vec<Item<'a>>| {
+        // A single mode change is folded when it names too many to list
+        let is_folded = run.len() >= MIN_RUN
+            || mode_changes(run)
+                .is_some_and(|(_, change)| change.is_truncated(NAMED_TARGETS));
+
+        if is_folded {
+            items.push(Item::Fold(Fold {
+                messages: std::mem::take(run),
+            }));
+        } else {
+            items.extend(run.drain(..).map(Item::Message));
+        }
+    };
+
+    for message in messages {
+        let is_exempt = |nick: &Nick| {
+            exempt_active.is_some_and(|seconds| {
+                last_spoke.get(nick).is_some_and(|spoke| {
+                    message.server_time - *spoke <= TimeDelta::seconds(seconds)
+                })
+            })
+        };
+
+        if let Some((setter, _)) = mode_change(message) {
+            // Runs are of one user's mode changes
+            let continues_run = run.first().is_none_or(|first| {
+                mode_change(first)
+                    .is_some_and(|(first_setter, _)| first_setter == setter)
+            });
+
+            if !continues_run {
+                end_run(&mut run, &mut items);
+            }
+
+            run.push(*message);
+            continue;
+        }
+
+        match event(message).filter(|_| collapse_events) {
+            Some((_, nick)) if !nick.is_some_and(is_exempt) => {
+                if run.first().and_then(|first| mode_change(first)).is_some() {
+                    end_run(&mut run, &mut items);
+                }
+
+                run.push(*message);
+            }
+            _ => {
+                if let Source::User(user) | Source::Action(Some(user)) =
+                    message.target.source()
+                {
+                    last_spoke.insert(
+                        user.nickname().to_owned(),
+                        message.server_time,
+                    );
+                }
+
+                end_run(&mut run, &mut items);
+                items.push(Item::Message(message));
+            }
+        }
+    }
+
+    end_run(&mut run, &mut items);
+
+    items
+}
+
+/// Who changed modes in `message` and how
+fn mode_change(message: &Message) -> Option<(Option<&Nick>, &mode::Change)> {
+    let Source::Server(Some(server)) = message.target.source() else {
+        return None;
+    };
+
+    Some((server.nick(), server.mode()?))
+}
+
+/// The mode changes of a run, if it's one of mode changes
+fn mode_changes(run: &[&Message]) -> Option<(Option<Nick>, mode::Change)> {
+    let (setter, first) = mode_change(run.first()?)?;
+    let mut change = first.clone();
+
+    for (_, next) in run[1..].iter().filter_map(|message| mode_change(message))
+    {
+        change.merge(next);
+    }
+
+    Some((setter.cloned(), change))
+}
+
+/// Kind of a foldable event and who it's about
+fn event(message: &Message) -> Option<(Kind, Option<&Nick>)> {
+    let Source::Server(Some(server)) = message.target.source() else {
+        return None;
+    };
+
+    matches!(
+        server.kind(),
+        Kind::Join | Kind::Part | Kind::Quit | Kind::ChangeNick
+    )
+    .then(|| (server.kind(), server.nick()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::isupport::CaseMap;
+    use crate::message::{Target, plain, source};
+    use crate::{User, target};
+
+    fn message(seconds: i64, source: Source) -> Message {
+        Message::test(
+            DateTime::from_timestamp(seconds, 0).unwrap(),
+            Target::Channel {
+                channel: target::Channel::from_str(
+                    "#halloy",
+                    CaseMap::default(),
+                ),
+                source,
+            },
+            plain(format!("{seconds}")),
+        )
+    }
+
+    fn event_at(seconds: i64, kind: Kind, nick: &str) -> Message {
+        message(
+            seconds,
+            Source::Server(Some(source::Server::new(
+                kind,
+                Some(Nick::from(nick)),
+            ))),
+        )
+    }
+
+    fn mode_at(seconds: i64, nick: &str, modes: &str, args: &str) -> Message {
+        let args = args.split(' ').map(String::from).collect::<Vec<_>>();
+
+        message(
+            seconds,
+            Source::Server(Some(source::Server::mode_change(
+                Some(Nick::from(nick)),
+                mode::Change::parse(modes, &args),
+            ))),
+        )
+    }
+
+    fn said(seconds: i64, nick: &str) -> Message {
+        message(seconds, Source::User(User::from(Nick::from(nick))))
+    }
+
+    fn shape(items: &[Item]) -> Vec<usize> {
+        items
+            .iter()
+            .map(|item| match item {
+                Item::Message(_) => 1,
+                Item::Fold(fold) => fold.messages.len() * 10,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn runs() {
+        let messages = [
+            event_at(0, Kind::Join, "a"),
+            event_at(1, Kind::Join, "b"),
+            event_at(2, Kind::Quit, "c"),
+            said(3, "b"),
+            event_at(4, Kind::Part, "d"),
+            said(5, "d"),
+            event_at(6, Kind::ChangeNick, "e"),
+            event_at(7, Kind::Join, "f"),
+        ];
+        let messages = messages.iter().collect::<Vec<_>>();
+
+        let items = fold(&messages, true, None);
+        assert_eq!(shape(&items), vec![30, 1, 1, 1, 20]);
+
+        let Item::Fold(fold) = &items[0] else {
+            unreachable!()
+        };
+        assert_eq!(fold.id(), messages[0].hash);
+        assert_eq!(fold.summary(), "2 joins, 1 quit");
+    }
+
+    #[test]
+    fn exempt_active() {
+        let messages = [
+            said(0, "a"),
+            event_at(10, Kind::Join, "b"),
+            event_at(20, Kind::Part, "a"),
+            event_at(30, Kind::Join, "c"),
+            event_at(200, Kind::Quit, "a"),
+        ];
+        let messages = messages.iter().collect::<Vec<_>>();
+
+        // "a" spoke 20 seconds before parting, but not 200 before quitting
+        assert_eq!(shape(&fold(&messages, true, Some(60))), vec![1, 1, 1, 20]);
+        assert_eq!(shape(&fold(&messages, true, None)), vec![1, 40]);
+    }
+
+    #[test]
+    fn mode_changes() {
+        let messages = [
+            mode_at(0, "ChanServ", "+ooooo", "a b c d e"),
+            mode_at(0, "ChanServ", "+ooooo", "f g h i j"),
+            mode_at(0, "ChanServ", "+ooooo", "k l m n o"),
+            event_at(1, Kind::Join, "p"),
+            mode_at(2, "alice", "+v", "p"),
+            mode_at(3, "bob", "+ovvv", "q r s t"),
+            mode_at(4, "bob", "+nt", ""),
+        ];
+        let messages = messages.iter().collect::<Vec<_>>();
+
+        // Mode changes are folded by who made them, even when events aren't,
+        // and so is a single one naming too many to list
+        let items = fold(&messages, false, None);
+        assert_eq!(shape(&items), vec![30, 1, 1, 20]);
+
+        let Item::Fold(fold) = &items[0] else {
+            unreachable!()
+        };
+        assert_eq!(fold.summary(), "ChanServ gave op to a, b, +13 more");
+
+        let Item::Fold(fold) = &items[3] else {
+            unreachable!()
+        };
+        assert_eq!(
+            fold.summary(),
+            "bob gave op to q; gave voice to r, s, t; set mode +nt"
+        );
+
+        assert_eq!(shape(&super::fold(&messages[..1], false, None)), vec![10]);
+        assert_eq!(
+            shape(&super::fold(&messages[4..6], false, None)),
+            vec![1, 1]
+        );
+    }
+}
@@ -1,191 +1,141 @@
 use std::collections::HashSet;
-use std::mem;
+use std::str::Chars;
 
 use iced_core::color;
-use itertools::PeekingNext;
 use serde::{Deserialize, Serialize};
 
 pub use self::encode::encode;
+pub use self::split::split;
 use crate::appearance::theme;
 
 pub mod encode;
+pub mod split;
 
-pub fn parse(
-    text: &str,
-    modifiers: &mut HashSet<Modifier>,
-    fg: &mut Option<Color>,
-    bg: &mut Option<Color>,
-) -> Option<Vec<Fragment>> {
-    let mut fragments = vec![];
-
-    let mut current_text = String::new();
-
-    let mut iter = text.chars().peekable();
-
-    while let Some(c) = iter.next() {
-        if let Ok(modifier) = Modifier::try_from(c) {
-            if !current_text.is_empty() {
-                let text = mem::take(&mut current_text);
-
-                if modifiers.is_empty() && fg.is_none() && bg.is_none() {
-                    fragments.push(Fragment::Unformatted(text));
-                } else {
-                    fragments.push(Fragment::Formatted(
-                        text,
-                        Formatting::new(modifiers, *fg, *bg),
-                    ));
+/// Run of text sharing the same formatting, with formatting codes removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub formatting: Formatting,
+}
+
+/// Decodes the formatting codes in `text` into styled spans. Never fails:
+/// malformed codes are dropped and the text around them is kept.
+pub fn decode(text: &str) -> Vec<Span> {
+    Decoder::default().decode(text)
+}
+
+/// Decodes text which was split up (e.g. around URLs), carrying formatting
+/// over from one part to the next
+#[derive(Debug, Default)]
+pub struct Decoder {
+    modifiers: HashSet<Modifier>,
+    fg: Option<Color>,
+    bg: Option<Color>,
+}
+
+impl Decoder {
+    pub fn decode(&mut self, text: &str) -> Vec<Span> {
+        let mut spans = Vec::<Span>::new();
+        let mut chars = text.chars();
+
+        while let Some(c) = chars.next() {
+            let Ok(modifier) = Modifier::try_from(c) else {
+                let formatting =
+                    Formatting::new(&self.modifiers, self.fg, self.bg);
+
+                match spans.last_mut() {
+                    Some(span) if span.formatting == formatting => {
+                        span.text.push(c);
+                    }
+                    _ => spans.push(Span {
+                        text: c.to_string(),
+                        formatting,
+                    }),
                 }
-            }
+
+                continue;
+            };
 
             match modifier {
                 Modifier::Reset => {
-                    modifiers.clear();
-                    *fg = None;
-                    *bg = None;
+                    self.modifiers.clear();
+                    self.fg = None;
+                    self.bg = None;
                 }
-                Modifier::Color => {
-                    // Trailing digit for new color, otherwise resets
-                    if let Some(c) = iter.peeking_next(char::is_ascii_digit) {
-                        // 1-2 digiits
-                        let mut digits = c.to_string();
-                        if let Some(c) = iter.peeking_next(char::is_ascii_digit)
-                        {
-                            digits.push(c);
-                        }
-
-                        let code = digits.parse().ok()?;
-
-                        *fg = Color::code(code);
-
-                        if let Some(comma) = iter.peeking_next(|c| *c == ',') {
-                            // Has background
-                            if let Some(c) =
-                                iter.peeking_next(char::is_ascii_digit)
-                            {
-                                // 1-2 digits
-                                let mut digits = c.to_string();
-                                if let Some(c) =
-                                    iter.peeking_next(char::is_ascii_digit)
-                                {
-                                    digits.push(c);
-                                }
-
-                                let code = digits.parse().ok()?;
-
-                                *bg = Color::code(code);
-                            }
-                            // Nope, just a normal char
-                            else {
-                                current_text.push(comma);
-                            }
-                        }
+                // Without a valid color following, resets colors
+                Modifier::Color | Modifier::HexColor => {
+                    let color = if modifier == Modifier::Color {
+                        color_code
                     } else {
-                        *fg = None;
-                        *bg = None;
-                    }
-                }
-                Modifier::HexColor => {
-                    // Trailing digit for new color, otherwise resets
-                    if let Some(c) = iter.peeking_next(char::is_ascii_hexdigit)
-                    {
-                        // 6 digits (hex)
-                        let mut hex = Vec::from([c]);
-                        for _ in 0..5 {
-                            hex.push(iter.next()?);
-                        }
+                        hex_color
+                    };
+
+                    if let Some(fg) = color(&mut chars) {
+                        self.fg = Some(fg);
 
-                        let r = u8::from_str_radix(
-                            &hex.iter().take(2).collect::<String>(),
-                            16,
-                        )
-                        .ok()?;
-                        let g = u8::from_str_radix(
-                            &hex.iter().skip(2).take(2).collect::<String>(),
-                            16,
-                        )
-                        .ok()?;
-                        let b = u8::from_str_radix(
-                            &hex.iter().skip(4).take(2).collect::<String>(),
-                            16,
-                        )
-                        .ok()?;
-
-                        *fg = Some(Color::Rgb(r, g, b));
-
-                        if let Some(comma) = iter.peeking_next(|c| *c == ',') {
-                            // Has background
-                            if let Some(c) =
-                                iter.peeking_next(char::is_ascii_hexdigit)
-                            {
-                                // 6 digits (hex)
-                                let mut hex = Vec::from([c]);
-                                for _ in 0..5 {
-                                    hex.push(iter.next()?);
-                                }
-
-                                let r = u8::from_str_radix(
-                                    &hex.iter().take(2).collect::<String>(),
-                                    16,
-                                )
-                                .ok()?;
-                                let g = u8::from_str_radix(
-                                    &hex.iter()
-                                        .skip(2)
-                                        .take(2)
-                                        .collect::<String>(),
-                                    16,
-                                )
-                                .ok()?;
-                                let b = u8::from_str_radix(
-                                    &hex.iter()
-                                        .skip(4)
-                                        .take(2)
-                                        .collect::<String>(),
-                                    16,
-                                )
-                                .ok()?;
-
-                                *bg = Some(Color::Rgb(r, g, b));
-                            }
-                            // Nope, just a normal char
-                            else {
-                                current_text.push(comma);
-                            }
+                        if let Some(bg) = background(&mut chars, color) {
+                            self.bg = Some(bg);
                         }
                     } else {
-                        *fg = None;
-                        *bg = None;
+                        self.fg = None;
+                        self.bg = None;
                     }
                 }
                 m => {
-                    if modifiers.contains(&m) {
-                        modifiers.remove(&m);
-                    } else {
-                        modifiers.insert(m);
+                    if !self.modifiers.remove(&m) {
+                        self.modifiers.insert(m);
                     }
                 }
             }
-        } else {
-            current_text.push(c);
         }
+
+        spans
     }
+}
 
-    if !current_text.is_empty() {
-        let text = mem::take(&mut current_text);
+/// Color code of 1-2 digits at the start of `chars`, which are consumed
+fn color_code(chars: &mut Chars) -> Option<Color> {
+    let rest = chars.as_str();
+    let len = rest.bytes().take(2).take_while(u8::is_ascii_digit).count();
 
-        if modifiers.is_empty() && fg.is_none() && bg.is_none() {
-            fragments.push(Fragment::Unformatted(text));
-        } else {
-            fragments.push(Fragment::Formatted(
-                text,
-                Formatting::new(modifiers, *fg, *bg),
-            ));
-        }
+    let color = Color::code(rest[..len].parse().ok()?)?;
+
+    *chars = rest[len..].chars();
+
+    Some(color)
+}
+
+/// Hex color of exactly 6 digits at the start of `chars`, which are consumed
+fn hex_color(chars: &mut Chars) -> Option<Color> {
+    let rest = chars.as_str();
+
+    if rest.len() < 6 || !rest.bytes().take(6).all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let [_, r, g, b] = u32::from_str_radix(&rest[..6], 16).ok()?.to_be_bytes();
+
+    *chars = rest[6..].chars();
+
+    Some(Color::Rgb(r, g, b))
+}
+
+/// Background color following a comma, otherwise the comma is left as text
+fn background(
+    chars: &mut Chars,
+    color: fn(&mut Chars) -> Option<Color>,
+) -> Option<Color> {
+    let mut lookahead = chars.clone();
+
+    if lookahead.next() != Some(',') {
+        return None;
     }
 
-    // Only return None if parsing failed; return an empty fragments if there
-    // are no non-formatting characters after parsing
-    Some(fragments)
+    let bg = color(&mut lookahead)?;
+
+    *chars = lookahead;
+
+    Some(bg)
 }
 
 #[derive(
@@ -197,6 +147,10 @@ pub struct Formatting {
     pub underline: bool,
     pub strikethrough: bool,
     pub monospace: bool,
+    /// Set along with swapping `fg` and `bg`, so text without colors can be
+    /// reversed as well
+    #[serde(default)]
+    pub reverse: bool,
     pub fg: Option<Color>,
     pub bg: Option<Color>,
 }
@@ -219,18 +173,13 @@ impl Formatting {
             underline: modifiers.contains(&Modifier::Underline),
             strikethrough: modifiers.contains(&Modifier::Strikethrough),
             monospace: modifiers.contains(&Modifier::Monospace),
+            reverse: modifiers.contains(&Modifier::ReverseColor),
             fg,
             bg,
         }
     }
 }
 
-#[derive(Debug)]
-pub enum Fragment {
-    Unformatted(String),
-    Formatted(String, Formatting),
-}
-
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[repr(u8)]
 pub enum Modifier {
@@ -705,3 +654,178 @@ impl Color {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::MarkdownLinks;
+
+    /// xorshift64, so failing cases reproduce without a dependency
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+
+            (self.0 % n as u64) as usize
+        }
+
+        fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+            items[self.below(items.len())]
+        }
+    }
+
+    fn visible(spans: &[Span]) -> String {
+        spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    #[test]
+    fn pathological() {
+        let fg = |color| Formatting {
+            fg: Some(color),
+            ..Formatting::default()
+        };
+        let span = |text: &str, formatting| Span {
+            text: text.to_string(),
+            formatting,
+        };
+
+        let tests = [
+            // Color code at the end of the text
+            ("\u{3}", vec![]),
+            ("end\u{3}", vec![span("end", Formatting::default())]),
+            ("end\u{3}12", vec![span("end", Formatting::default())]),
+            (
+                "end\u{3}12,",
+                vec![
+                    span("end", Formatting::default()),
+                    span(",", fg(Color::LightBlue)),
+                ],
+            ),
+            ("\u{4}", vec![]),
+            ("\u{4}ff00", vec![span("ff00", Formatting::default())]),
+            // Unmatched codes
+            ("\u{3}04red\u{3}", vec![span("red", fg(Color::Red))]),
+            (
+                "\u{2}bold",
+                vec![span(
+                    "bold",
+                    Formatting {
+                        bold: true,
+                        ..Formatting::default()
+                    },
+                )],
+            ),
+            ("\u{2}\u{2}\u{f}", vec![]),
+            // At most 2 digits belong to the color
+            ("\u{3}123", vec![span("3", fg(Color::LightBlue))]),
+            (
+                "\u{3}1,234",
+                vec![span(
+                    "4",
+                    Formatting {
+                        fg: Some(Color::Black),
+                        bg: Some(Color::Code23),
+                        ..Formatting::default()
+                    },
+                )],
+            ),
+            // Not a background color
+            ("\u{3}2,x", vec![span(",x", fg(Color::Blue))]),
+            // Hex colors need all 6 digits
+            ("\u{4}abcde", vec![span("abcde", Formatting::default())]),
+            (
+                "\u{4}abcdefg",
+                vec![span("g", fg(Color::Rgb(171, 205, 239)))],
+            ),
+            (
+                "\u{4}00ff00,ff",
+                vec![span(",ff", fg(Color::Rgb(0, 255, 0)))],
+            ),
+            // Multibyte characters right after codes
+            (
+                "\u{3}1é\u{4}é",
+                vec![
+                    span("é", fg(Color::Black)),
+                    span("é", Formatting::default()),
+                ],
+            ),
+            (
+                "\u{16}reversed",
+                vec![span(
+                    "reversed",
+                    Formatting {
+                        reverse: true,
+                        ..Formatting::default()
+                    },
+                )],
+            ),
+        ];
+
+        for (text, expected) in tests {
+            assert_eq!(decode(text), expected, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn arbitrary_input() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let chars = [
+            '\u{2}', '\u{3}', '\u{4}', '\u{f}', '\u{11}', '\u{16}', '\u{1d}',
+            '\u{1e}', '\u{1f}', '\u{0}', '\u{7}', '0', '1', '9', 'a', 'f', 'g',
+            ',', ' ', 'é', '🦀',
+        ];
+
+        for _ in 0..10_000 {
+            let len = rng.below(32);
+            let text = (0..len).map(|_| rng.pick(&chars)).collect::<String>();
+
+            let spans = decode(&text);
+
+            for (i, span) in spans.iter().enumerate() {
+                assert!(!span.text.is_empty(), "{text:?}");
+                assert!(
+                    span.text.chars().all(|c| Modifier::try_from(c).is_err()),
+                    "{text:?}"
+                );
+                assert!(
+                    i == 0 || spans[i - 1].formatting != span.formatting,
+                    "{text:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let words = ["hello", "there", "friend", "42", "1,2", "é", "🦀"];
+        let styles = [("**", "**"), ("_", "_"), ("~~", "~~"), ("`", "`")];
+
+        for _ in 0..1_000 {
+            let words = (0..1 + rng.below(8))
+                .map(|_| (rng.pick(&words), rng.below(styles.len() + 1)))
+                .collect::<Vec<_>>();
+
+            let input = words
+                .iter()
+                .map(|(word, style)| match styles.get(*style) {
+                    Some((start, end)) => format!("{start}{word}{end}"),
+                    None => word.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let expected = words
+                .iter()
+                .map(|(word, _)| *word)
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            let encoded = encode(&input, true, MarkdownLinks::default());
+
+            assert_eq!(visible(&decode(&encoded)), expected, "{input:?}");
+        }
+    }
+}
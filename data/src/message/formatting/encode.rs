@@ -3,7 +3,7 @@ use std::convert::identity;
 use std::fmt::Write;
 
 use nom::branch::alt;
-use nom::bytes::complete::tag;
+use nom::bytes::complete::{tag, take_while1};
 use nom::character::complete::{anychar, char, satisfy};
 use nom::combinator::{
     cond, cut, eof, map, map_opt, not, opt, peek, recognize, value, verify,
@@ -13,8 +13,9 @@ use nom::sequence::{pair, preceded, tuple};
 use nom::{Finish, IResult};
 
 use super::{Color, Modifier};
+use crate::buffer::MarkdownLinks;
 
-pub fn encode(text: &str, markdown_only: bool) -> String {
+pub fn encode(text: &str, markdown_only: bool, links: MarkdownLinks) -> String {
     let Some(tokens) = parse(text, markdown_only) else {
         return text.to_string();
     };
@@ -22,7 +23,7 @@ pub fn encode(text: &str, markdown_only: bool) -> String {
     let mut out = String::with_capacity(irc::proto::format::BYTE_LIMIT);
 
     for token in tokens {
-        token.encode(&mut out);
+        token.encode(&mut out, links);
     }
 
     out
@@ -56,9 +57,12 @@ fn escaped<'a>(
     alt((
         value('*', tag("\\*")),
         value('_', tag("\\_")),
+        value('~', tag("\\~")),
         value('`', tag("\\`")),
         value('`', tag("``")),
         value('|', tag("\\|")),
+        value('[', tag("\\[")),
+        value(']', tag("\\]")),
         skip(markdown_only, value('$', tag("\\$"))),
         skip(markdown_only, value('$', tag("$$"))),
     ))
@@ -251,6 +255,22 @@ fn markdown<'a>(
         )),
         |(_, (tokens, _))| tokens,
     );
+    // [<tokens>](url)
+    let link = map(
+        tuple((
+            char('['),
+            many_till(
+                verify(
+                    move |input| token(source, input, markdown_only),
+                    |token| !matches!(token, Token::Plain(']')),
+                ),
+                tag("]("),
+            ),
+            take_while1(|c: char| c != ')' && !c.is_whitespace()),
+            char(')'),
+        )),
+        |(_, (tokens, _), url, _)| (tokens, url.to_string()),
+    );
 
     alt((
         map(italic_bold, Markdown::ItalicBold),
@@ -259,6 +279,7 @@ fn markdown<'a>(
         map(strikethrough, Markdown::Strikethrough),
         map(spoiler, Markdown::Spoiler),
         map(code, Markdown::Code),
+        map(link, |(tokens, url)| Markdown::Link(tokens, url)),
     ))
 }
 
@@ -303,6 +324,28 @@ fn dollar(input: &str) -> IResult<&str, Dollar> {
         |(_, (fg, bg))| (fg, bg),
     );
 
+    // #RRGGBB -> (r, g, b)
+    let hex = |input| {
+        map_opt(
+            preceded(
+                char('#'),
+                recognize(count(satisfy(|c| c.is_ascii_hexdigit()), 6)),
+            ),
+            |hex: &str| {
+                let [_, r, g, b] =
+                    u32::from_str_radix(hex, 16).ok()?.to_be_bytes();
+
+                Some((r, g, b))
+            },
+        )(input)
+    };
+
+    // $c#RRGGBB[,#RRGGBB]
+    let start_hex_color = map(
+        tuple((tag("$c"), hex, opt(preceded(char(','), hex)))),
+        |(_, fg, bg)| (fg, bg),
+    );
+
     alt((
         map(tag("$b"), |_| Dollar::Bold),
         map(tag("$i"), |_| Dollar::Italics),
@@ -310,6 +353,7 @@ fn dollar(input: &str) -> IResult<&str, Dollar> {
         map(tag("$s"), |_| Dollar::Strikethrough),
         map(tag("$u"), |_| Dollar::Underline),
         map(tag("$r"), |_| Dollar::Reset),
+        map(start_hex_color, |(fg, bg)| Dollar::StartHexColor(fg, bg)),
         map(start_color, |(fg, bg)| Dollar::StartColor(fg, bg)),
         // No valid colors after code == end
         map(tag("$c"), |_| Dollar::EndColor),
@@ -325,7 +369,7 @@ enum Token {
 }
 
 impl Token {
-    fn encode(self, out: &mut String) {
+    fn encode(self, out: &mut String, links: MarkdownLinks) {
         match self {
             Token::Escaped(c) => out.push(c),
             Token::Markdown(markdown) => match markdown {
@@ -333,7 +377,7 @@ impl Token {
                     let b = Modifier::Bold.char();
                     out.push(b);
                     for token in tokens {
-                        token.encode(out);
+                        token.encode(out, links);
                     }
                     out.push(b);
                 }
@@ -341,7 +385,7 @@ impl Token {
                     let i = Modifier::Italics.char();
                     out.push(i);
                     for token in tokens {
-                        token.encode(out);
+                        token.encode(out, links);
                     }
                     out.push(i);
                 }
@@ -351,7 +395,7 @@ impl Token {
                     out.push(b);
                     out.push(i);
                     for token in tokens {
-                        token.encode(out);
+                        token.encode(out, links);
                     }
                     out.push(i);
                     out.push(b);
@@ -360,7 +404,7 @@ impl Token {
                     let m = Modifier::Monospace.char();
                     out.push(m);
                     for token in tokens {
-                        token.encode(out);
+                        token.encode(out, links);
                     }
                     out.push(m);
                 }
@@ -369,7 +413,7 @@ impl Token {
                     let black = Color::Black.digit();
                     let _ = write!(out, "{c}{black},{black}");
                     for token in tokens {
-                        token.encode(out);
+                        token.encode(out, links);
                     }
                     out.push(c);
                 }
@@ -377,10 +421,19 @@ impl Token {
                     let m = Modifier::Strikethrough.char();
                     out.push(m);
                     for token in tokens {
-                        token.encode(out);
+                        token.encode(out, links);
                     }
                     out.push(m);
                 }
+                Markdown::Link(tokens, url) => match links {
+                    MarkdownLinks::Parentheses => {
+                        for token in tokens {
+                            token.encode(out, links);
+                        }
+                        let _ = write!(out, " ({url})");
+                    }
+                    MarkdownLinks::Inline => out.push_str(&url),
+                },
             },
             Token::Dollar(dollar) => match dollar {
                 Dollar::Bold => {
@@ -410,6 +463,14 @@ impl Token {
                         let _ = write!(out, ",{bg}");
                     }
                 }
+                Dollar::StartHexColor((r, g, b), bg) => {
+                    let c = Modifier::HexColor.char();
+                    let _ = write!(out, "{c}{r:02X}{g:02X}{b:02X}");
+
+                    if let Some((r, g, b)) = bg {
+                        let _ = write!(out, ",{r:02X}{g:02X}{b:02X}");
+                    }
+                }
                 Dollar::EndColor => {
                     out.push(Modifier::Color.char());
                 }
@@ -427,6 +488,7 @@ enum Markdown {
     Strikethrough(Vec<Token>),
     Code(Vec<Token>),
     Spoiler(Vec<Token>),
+    Link(Vec<Token>, String),
 }
 
 #[derive(Debug)]
@@ -438,12 +500,15 @@ enum Dollar {
     Underline,
     Reset,
     StartColor(Color, Option<Color>),
+    StartHexColor((u8, u8, u8), Option<(u8, u8, u8)>),
     EndColor,
 }
 
 #[cfg(test)]
 mod test {
+    use crate::buffer::MarkdownLinks;
     use crate::message::formatting::encode;
+
     #[test]
     fn internal_format() {
         let tests = [
@@ -472,6 +537,23 @@ mod test {
                 ("hello there ~~friend~~!!", false),
                 String::from("hello there \u{1e}friend\u{1e}!!"),
             ),
+            (
+                ("~~**bold struck**~~", false),
+                String::from("\u{1e}\u{2}bold struck\u{2}\u{1e}"),
+            ),
+            (
+                ("**~~struck bold~~**", false),
+                String::from("\u{2}\u{1e}struck bold\u{1e}\u{2}"),
+            ),
+            (
+                ("not \\~~struck\\~~", false),
+                String::from("not ~~struck~~"),
+            ),
+            (
+                ("$sstruck$s text", false),
+                String::from("\u{1e}struck\u{1e} text"),
+            ),
+            (("$sstruck$s text", true), String::from("$sstruck$s text")),
             (
                 ("testing__testing__onetwothree", false),
                 String::from("testing__testing__onetwothree"),
@@ -493,9 +575,82 @@ mod test {
                     "\u{3}1,0black on white \u{3}2now blue on white\u{f}\u{2} BOLD \u{1d} BOLD AND ITALIC\u{f} \u{3}code yo",
                 ),
             ),
+            (
+                ("$c#ff00aahex$c", false),
+                String::from("\u{4}FF00AAhex\u{3}"),
+            ),
+            (
+                ("$c#FF00AA,#001122hex on hex$r", false),
+                String::from("\u{4}FF00AA,001122hex on hex\u{f}"),
+            ),
+            (
+                ("$c#ff00aa,redhex", false),
+                String::from("\u{4}FF00AA,redhex"),
+            ),
+            (("$c#ffnot hex", false), String::from("\u{3}#ffnot hex")),
+            (
+                ("$uunderline$u", false),
+                String::from("\u{1f}underline\u{1f}"),
+            ),
+            (
+                ("$b$ibold italic $uand underline$r plain", false),
+                String::from(
+                    "\u{2}\u{1d}bold italic \u{1f}and underline\u{f} plain",
+                ),
+            ),
+            (
+                ("__bold__ $uunderline$u _italic_", false),
+                String::from(
+                    "\u{2}bold\u{2} \u{1f}underline\u{1f} \u{1d}italic\u{1d}",
+                ),
+            ),
+            (("$uunderline$u", true), String::from("$uunderline$u")),
+            (
+                ("[click here](https://example.org)", false),
+                String::from("click here (https://example.org)"),
+            ),
+            (
+                ("see [**bold** _link_](https://example.org)!", true),
+                String::from(
+                    "see \u{2}bold\u{2} \u{1d}link\u{1d} (https://example.org)!",
+                ),
+            ),
+            (
+                ("[$c4red$c link](https://example.org)", false),
+                String::from("\u{3}4red\u{3} link (https://example.org)"),
+            ),
+            (("[not a link", false), String::from("[not a link")),
+            (("[not] a (link)", false), String::from("[not] a (link)")),
+            (("[not](a link)", false), String::from("[not](a link)")),
+            (("[not](", false), String::from("[not](")),
+            (("[[nested]](url)", false), String::from("[[nested]](url)")),
+            (
+                ("\\[escaped\\](https://example.org)", false),
+                String::from("[escaped](https://example.org)"),
+            ),
         ];
         for ((text, markdown_only), expected) in tests {
-            let actual = encode(text, markdown_only);
+            let actual =
+                encode(text, markdown_only, MarkdownLinks::Parentheses);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn inline_links() {
+        let tests = [
+            (
+                "[click here](https://example.org)",
+                String::from("https://example.org"),
+            ),
+            (
+                "**[bold link](https://example.org)**",
+                String::from("\u{2}https://example.org\u{2}"),
+            ),
+            ("[not a link", String::from("[not a link")),
+        ];
+        for (text, expected) in tests {
+            let actual = encode(text, false, MarkdownLinks::Inline);
             assert_eq!(actual, expected);
         }
     }
@@ -0,0 +1,310 @@
+use std::fmt::Write;
+
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::{Color, Decoder, Modifier, background, color_code, hex_color};
+
+/// Modifiers re-opened at the start of a part, in the order they're written
+const TOGGLES: [Modifier; 6] = [
+    Modifier::Bold,
+    Modifier::Italics,
+    Modifier::Underline,
+    Modifier::Strikethrough,
+    Modifier::Monospace,
+    Modifier::ReverseColor,
+];
+
+/// Splits `text` into parts of at most `max_bytes` bytes, preferring to split
+/// on whitespace, which is then left out. Grapheme clusters and formatting
+/// codes are never split, and the formatting active where a part ends is
+/// re-opened at the start of the next one, unless the codes leave no room for
+/// any text.
+pub fn split(text: &str, max_bytes: usize) -> Vec<String> {
+    if text.len() <= max_bytes {
+        return vec![text.to_string()];
+    }
+
+    let tokens = tokens(text);
+    let mut decoder = Decoder::default();
+    let mut parts = vec![];
+    let mut start = 0;
+
+    while start < tokens.len() {
+        let mut part = reopen(&decoder);
+
+        if part.len() + tokens[start].len() > max_bytes {
+            part.clear();
+        }
+
+        let budget = max_bytes.saturating_sub(part.len());
+
+        let mut len = 0;
+        let mut end = start;
+        let mut space = None;
+
+        while end < tokens.len() && len + tokens[end].len() <= budget {
+            if end > start && is_whitespace(tokens[end]) {
+                space = Some(end);
+            }
+
+            len += tokens[end].len();
+            end += 1;
+        }
+
+        let (part_end, next) = if end == tokens.len() {
+            (end, end)
+        } else if is_whitespace(tokens[end]) {
+            (end, end + 1)
+        } else if let Some(space) = space {
+            (space, space + 1)
+        } else {
+            // A part holds at least one token, however long
+            let end = end.max(start + 1);
+
+            (end, end)
+        };
+
+        part.extend(tokens[start..part_end].iter().copied());
+        parts.push(part);
+
+        for token in &tokens[start..next] {
+            decoder.decode(token);
+        }
+
+        start = next;
+    }
+
+    parts
+}
+
+fn is_whitespace(token: &str) -> bool {
+    token.chars().all(char::is_whitespace)
+}
+
+/// Grapheme clusters of `text`, with formatting codes kept together with the
+/// colors following them
+fn tokens(text: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut rest = text;
+
+    while let Some(grapheme) = rest.graphemes(true).next() {
+        let mut chars = rest.chars();
+
+        let len = match chars.next().map(Modifier::try_from) {
+            Some(Ok(modifier @ (Modifier::Color | Modifier::HexColor))) => {
+                let color = if modifier == Modifier::Color {
+                    color_code
+                } else {
+                    hex_color
+                };
+
+                if color(&mut chars).is_some() {
+                    background(&mut chars, color);
+                }
+
+                rest.len() - chars.as_str().len()
+            }
+            _ => grapheme.len(),
+        };
+
+        let (token, tail) = rest.split_at(len);
+        tokens.push(token);
+        rest = tail;
+    }
+
+    tokens
+}
+
+/// Formatting codes setting the formatting active in `decoder`. Colors are
+/// written with 2 digits, so digits following them aren't taken as part of
+/// the color.
+fn reopen(decoder: &Decoder) -> String {
+    let mut codes = TOGGLES
+        .iter()
+        .filter(|modifier| decoder.modifiers.contains(*modifier))
+        .map(Modifier::char)
+        .collect::<String>();
+
+    if let Some(fg) = decoder.fg {
+        let (fg_code, fg) = color(fg);
+
+        match decoder.bg.map(color) {
+            Some((bg_code, bg)) if bg_code == fg_code => {
+                let _ = write!(codes, "{}{fg},{bg}", fg_code.char());
+            }
+            // A code sets colors of its own kind only, so the background is
+            // set first, along with a foreground which is then replaced
+            Some((bg_code, bg)) => {
+                let _ = write!(
+                    codes,
+                    "{}{bg},{bg}{}{fg}",
+                    bg_code.char(),
+                    fg_code.char()
+                );
+            }
+            None => {
+                let _ = write!(codes, "{}{fg}", fg_code.char());
+            }
+        }
+    }
+
+    codes
+}
+
+/// Code setting `color` along with its digits
+fn color(color: Color) -> (Modifier, String) {
+    match color {
+        Color::Rgb(r, g, b) => {
+            (Modifier::HexColor, format!("{r:02X}{g:02X}{b:02X}"))
+        }
+        color => (Modifier::Color, format!("{:02}", color.digit())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::MarkdownLinks;
+    use crate::message::formatting::{Formatting, decode, encode};
+
+    /// Characters other than whitespace with their formatting
+    fn styled(parts: &[String]) -> Vec<(char, Formatting)> {
+        parts
+            .iter()
+            .flat_map(|part| decode(part))
+            .flat_map(|span| {
+                span.text
+                    .chars()
+                    .filter(|c| !c.is_whitespace())
+                    .map(|c| (c, span.formatting))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn whitespace() {
+        assert_eq!(split("hello there", 20), ["hello there"]);
+        assert_eq!(split("hello there friend", 12), ["hello there", "friend"]);
+        // Without whitespace, as much as fits
+        assert_eq!(split("abcdefgh", 3), ["abc", "def", "gh"]);
+    }
+
+    #[test]
+    fn multibyte() {
+        // 4 byte emoji crossing the limit
+        let text = format!("{}😀😀", "a".repeat(8));
+        assert_eq!(split(&text, 10), ["a".repeat(8), "😀😀".to_string()]);
+
+        // Emoji with a skin tone, ending exactly at the limit
+        let text = "ab👍🏽 cd";
+
+        let parts = split(text, 10);
+        assert_eq!(parts, ["ab👍🏽", "cd"]);
+        assert!(parts.iter().all(|part| part.len() <= 10));
+
+        // Flags are pairs of characters which aren't split
+        assert_eq!(split("a🇫🇷🇩🇪", 13), ["a🇫🇷", "🇩🇪"]);
+    }
+
+    #[test]
+    fn formatting() {
+        let text = encode(
+            "$c4,12red on blue$c and $bbold$b",
+            false,
+            MarkdownLinks::default(),
+        );
+        assert_eq!(text, "\u{3}4,12red on blue\u{3} and \u{2}bold\u{2}");
+
+        // Codes aren't split from their colors, so every character keeps its
+        // formatting as long as the re-opened codes (at most "\u{3}04,12")
+        // leave room for a character. Parts only exceed the limit when a
+        // single token does, the longest being "\u{3}4,12"
+        for max_bytes in 1..text.len() {
+            let parts = split(&text, max_bytes);
+
+            if max_bytes > 6 {
+                assert_eq!(
+                    styled(&parts),
+                    styled(&[text.clone()]),
+                    "{parts:?}"
+                );
+            }
+            assert!(
+                parts.iter().all(|part| part.len() <= max_bytes.max(5)),
+                "{parts:?}"
+            );
+        }
+
+        // Color spanning the split is re-opened
+        let parts = split(&text, 12);
+        assert_eq!(
+            parts,
+            [
+                "\u{3}4,12red on",
+                "\u{3}04,12blue\u{3}",
+                "and \u{2}bold\u{2}"
+            ]
+        );
+
+        let red_on_blue = Formatting {
+            fg: Some(Color::Red),
+            bg: Some(Color::LightBlue),
+            ..Formatting::default()
+        };
+        assert_eq!(decode(&parts[1])[0].text, "blue");
+        assert_eq!(decode(&parts[1])[0].formatting, red_on_blue);
+
+        // Digits following a re-opened color stay text
+        let parts = split("\u{3}4ab 42", 5);
+        assert_eq!(parts, ["\u{3}4ab", "\u{3}0442"]);
+        assert_eq!(decode(&parts[1])[0].text, "42");
+
+        // Along with modifiers and hex colors
+        let parts = split("\u{2}\u{1d}\u{4}FF0000,0000FFbold italic red", 22);
+        assert_eq!(
+            parts,
+            [
+                "\u{2}\u{1d}\u{4}FF0000,0000FFbold",
+                "\u{2}\u{1d}\u{4}FF0000,0000FFitalic",
+                "\u{2}\u{1d}\u{4}FF0000,0000FFred",
+            ]
+        );
+
+        // Hex and numbered colors mixed
+        let parts = split("\u{3}4,12\u{4}FF0000ab cd", 15);
+        assert_eq!(
+            parts,
+            ["\u{3}4,12\u{4}FF0000ab", "\u{3}12,12\u{4}FF0000cd"]
+        );
+        assert_eq!(
+            decode(&parts[1])[0].formatting,
+            Formatting {
+                fg: Some(Color::Rgb(255, 0, 0)),
+                bg: Some(Color::LightBlue),
+                ..Formatting::default()
+            }
+        );
+
+        let parts = split("\u{4}00FF00,0000FF\u{3}4ab cd", 19);
+        assert_eq!(
+            parts,
+            ["\u{4}00FF00,0000FF\u{3}4ab", "\u{4}0000FF,0000FF\u{3}04cd"]
+        );
+        assert_eq!(
+            decode(&parts[1])[0].formatting,
+            Formatting {
+                fg: Some(Color::Red),
+                bg: Some(Color::Rgb(0, 0, 255)),
+                ..Formatting::default()
+            }
+        );
+    }
+
+    #[test]
+    fn clamped() {
+        // Re-opening the formatting would leave no room for text
+        let parts = split("ab \u{2}\u{1d}\u{4}FF0000,0000FFcd", 16);
+        assert_eq!(parts, ["ab", "\u{2}\u{1d}\u{4}FF0000,0000FF", "cd"]);
+    }
+}
@@ -27,6 +27,7 @@ pub mod server {
     #![allow(deprecated)]
     use serde::{Deserialize, Serialize};
 
+    use crate::mode;
     use crate::user::Nick;
 
     #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -39,7 +40,32 @@ pub mod server {
 
     impl Server {
         pub fn new(kind: Kind, nick: Option<Nick>) -> Self {
-            Self::Details(Details { kind, nick })
+            Self::Details(Details {
+                kind,
+                nick,
+                mode: None,
+                new_nick: None,
+            })
+        }
+
+        /// Nick `old_nick` changed to `new_nick`
+        pub fn nick_change(old_nick: Nick, new_nick: Nick) -> Self {
+            Self::Details(Details {
+                kind: Kind::ChangeNick,
+                nick: Some(old_nick),
+                mode: None,
+                new_nick: Some(new_nick),
+            })
+        }
+
+        /// Modes changed by `nick`
+        pub fn mode_change(nick: Option<Nick>, change: mode::Change) -> Self {
+            Self::Details(Details {
+                kind: Kind::ChangeMode,
+                nick,
+                mode: Some(change),
+                new_nick: None,
+            })
         }
 
         pub fn kind(&self) -> Kind {
@@ -55,6 +81,21 @@ pub mod server {
                 Server::Details(details) => details.nick.as_ref(),
             }
         }
+
+        pub fn mode(&self) -> Option<&mode::Change> {
+            match self {
+                Server::Kind(_) => None,
+                Server::Details(details) => details.mode.as_ref(),
+            }
+        }
+
+        /// Nick changed to, of a nick change
+        pub fn new_nick(&self) -> Option<&Nick> {
+            match self {
+                Server::Kind(_) => None,
+                Server::Details(details) => details.new_nick.as_ref(),
+            }
+        }
     }
 
     #[derive(
@@ -67,10 +108,17 @@ pub mod server {
         Quit,
         ReplyTopic,
         ChangeHost,
+        ChangeNick,
+        ChangeMode,
         MonitoredOnline,
         MonitoredOffline,
         StandardReply(StandardReply),
         Wallops,
+        Ctcp,
+        /// Messages of bouncer services, e.g. ZNC's `*status`
+        Bouncer,
+        /// Notices from the server, or from users we don't have a query with
+        Notice,
     }
 
     #[derive(
@@ -86,5 +134,9 @@ pub mod server {
     pub struct Details {
         pub kind: Kind,
         pub nick: Option<Nick>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub mode: Option<mode::Change>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pub new_nick: Option<Nick>,
     }
 }
@@ -0,0 +1,335 @@
+//! Standard replies (`FAIL`, `WARN` and `NOTE`)
+//!
+//! Reference: https://ircv3.net/specs/extensions/standard-replies
+use irc::proto::Command;
+
+use super::StandardReply as Kind;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reply {
+    pub kind: Kind,
+    pub command: String,
+    pub code: Code,
+    pub context: Vec<String>,
+    pub description: String,
+}
+
+impl Reply {
+    pub fn parse(command: &Command) -> Option<Self> {
+        let (kind, command, code, context, description) = match command {
+            Command::FAIL(command, code, context, description) => {
+                (Kind::Fail, command, code, context, description)
+            }
+            Command::WARN(command, code, context, description) => {
+                (Kind::Warn, command, code, context, description)
+            }
+            Command::NOTE(command, code, context, description) => {
+                (Kind::Note, command, code, context, description)
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            kind,
+            command: command.clone(),
+            code: Code::from(code.as_str()),
+            context: context.clone().unwrap_or_default(),
+            description: description.clone(),
+        })
+    }
+
+    pub fn text(&self) -> String {
+        let outcome = match self.kind {
+            Kind::Fail => "failed",
+            Kind::Warn => "warning",
+            Kind::Note => "notice",
+        };
+
+        let mut text = if self.context.is_empty() {
+            format!("{} {outcome}: {}", self.command, self.description)
+        } else {
+            format!(
+                "{} ({}) {outcome}: {}",
+                self.command,
+                self.context.join(", "),
+                self.description
+            )
+        };
+
+        if let Some(hint) = self.code.hint() {
+            text.push_str(" (");
+            text.push_str(hint);
+            text.push(')');
+        }
+
+        text
+    }
+}
+
+/// Codes registered in the IRCv3 registry, see
+/// https://ircv3.net/registry#standard-replies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Code {
+    AccountRequired,
+    InvalidUtf8,
+    NeedMoreParams,
+    InvalidParams,
+    InvalidTarget,
+    UnknownCommand,
+    MessageError,
+    InternalError,
+    MultilineMaxBytes,
+    MultilineMaxLines,
+    MultilineInvalidTarget,
+    MultilineInvalid,
+    AccountExists,
+    AccountNameMustBeNick,
+    AlreadyAuthenticated,
+    BadAccountName,
+    CompleteConnectionRequired,
+    InvalidEmail,
+    NeedNick,
+    TemporarilyUnavailable,
+    UnacceptableEmail,
+    UnacceptablePassword,
+    WeakPassword,
+    VerificationRequired,
+    InvalidCode,
+    ChannelNameInUse,
+    CannotRename,
+    CannotChangeRealname,
+    InvalidRealname,
+    RedactForbidden,
+    RedactWindowExpired,
+    UnknownMsgid,
+    Other(String),
+}
+
+impl Code {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Code::AccountRequired => "ACCOUNT_REQUIRED",
+            Code::InvalidUtf8 => "INVALID_UTF8",
+            Code::NeedMoreParams => "NEED_MORE_PARAMS",
+            Code::InvalidParams => "INVALID_PARAMS",
+            Code::InvalidTarget => "INVALID_TARGET",
+            Code::UnknownCommand => "UNKNOWN_COMMAND",
+            Code::MessageError => "MESSAGE_ERROR",
+            Code::InternalError => "INTERNAL_ERROR",
+            Code::MultilineMaxBytes => "MULTILINE_MAX_BYTES",
+            Code::MultilineMaxLines => "MULTILINE_MAX_LINES",
+            Code::MultilineInvalidTarget => "MULTILINE_INVALID_TARGET",
+            Code::MultilineInvalid => "MULTILINE_INVALID",
+            Code::AccountExists => "ACCOUNT_EXISTS",
+            Code::AccountNameMustBeNick => "ACCOUNT_NAME_MUST_BE_NICK",
+            Code::AlreadyAuthenticated => "ALREADY_AUTHENTICATED",
+            Code::BadAccountName => "BAD_ACCOUNT_NAME",
+            Code::CompleteConnectionRequired => "COMPLETE_CONNECTION_REQUIRED",
+            Code::InvalidEmail => "INVALID_EMAIL",
+            Code::NeedNick => "NEED_NICK",
+            Code::TemporarilyUnavailable => "TEMPORARILY_UNAVAILABLE",
+            Code::UnacceptableEmail => "UNACCEPTABLE_EMAIL",
+            Code::UnacceptablePassword => "UNACCEPTABLE_PASSWORD",
+            Code::WeakPassword => "WEAK_PASSWORD",
+            Code::VerificationRequired => "VERIFICATION_REQUIRED",
+            Code::InvalidCode => "INVALID_CODE",
+            Code::ChannelNameInUse => "CHANNEL_NAME_IN_USE",
+            Code::CannotRename => "CANNOT_RENAME",
+            Code::CannotChangeRealname => "CANNOT_CHANGE_REALNAME",
+            Code::InvalidRealname => "INVALID_REALNAME",
+            Code::RedactForbidden => "REDACT_FORBIDDEN",
+            Code::RedactWindowExpired => "REDACT_WINDOW_EXPIRED",
+            Code::UnknownMsgid => "UNKNOWN_MSGID",
+            Code::Other(code) => code,
+        }
+    }
+
+    /// Additional guidance shown alongside the server's description.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Code::AccountRequired => {
+                Some("log in, or configure sasl to log in when connecting")
+            }
+            Code::CompleteConnectionRequired => {
+                Some("retry once connected to the server")
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for Code {
+    fn from(code: &str) -> Self {
+        match code {
+            "ACCOUNT_REQUIRED" => Code::AccountRequired,
+            "INVALID_UTF8" => Code::InvalidUtf8,
+            "NEED_MORE_PARAMS" => Code::NeedMoreParams,
+            "INVALID_PARAMS" => Code::InvalidParams,
+            "INVALID_TARGET" => Code::InvalidTarget,
+            "UNKNOWN_COMMAND" => Code::UnknownCommand,
+            "MESSAGE_ERROR" => Code::MessageError,
+            "INTERNAL_ERROR" => Code::InternalError,
+            "MULTILINE_MAX_BYTES" => Code::MultilineMaxBytes,
+            "MULTILINE_MAX_LINES" => Code::MultilineMaxLines,
+            "MULTILINE_INVALID_TARGET" => Code::MultilineInvalidTarget,
+            "MULTILINE_INVALID" => Code::MultilineInvalid,
+            "ACCOUNT_EXISTS" => Code::AccountExists,
+            "ACCOUNT_NAME_MUST_BE_NICK" => Code::AccountNameMustBeNick,
+            "ALREADY_AUTHENTICATED" => Code::AlreadyAuthenticated,
+            "BAD_ACCOUNT_NAME" => Code::BadAccountName,
+            "COMPLETE_CONNECTION_REQUIRED" => Code::CompleteConnectionRequired,
+            "INVALID_EMAIL" => Code::InvalidEmail,
+            "NEED_NICK" => Code::NeedNick,
+            "TEMPORARILY_UNAVAILABLE" => Code::TemporarilyUnavailable,
+            "UNACCEPTABLE_EMAIL" => Code::UnacceptableEmail,
+            "UNACCEPTABLE_PASSWORD" => Code::UnacceptablePassword,
+            "WEAK_PASSWORD" => Code::WeakPassword,
+            "VERIFICATION_REQUIRED" => Code::VerificationRequired,
+            "INVALID_CODE" => Code::InvalidCode,
+            "CHANNEL_NAME_IN_USE" => Code::ChannelNameInUse,
+            "CANNOT_RENAME" => Code::CannotRename,
+            "CANNOT_CHANGE_REALNAME" => Code::CannotChangeRealname,
+            "INVALID_REALNAME" => Code::InvalidRealname,
+            "REDACT_FORBIDDEN" => Code::RedactForbidden,
+            "REDACT_WINDOW_EXPIRED" => Code::RedactWindowExpired,
+            "UNKNOWN_MSGID" => Code::UnknownMsgid,
+            code => Code::Other(code.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registered_codes() {
+        let tests = [
+            ("ACCOUNT_REQUIRED", Code::AccountRequired),
+            ("INVALID_UTF8", Code::InvalidUtf8),
+            ("NEED_MORE_PARAMS", Code::NeedMoreParams),
+            ("INVALID_PARAMS", Code::InvalidParams),
+            ("INVALID_TARGET", Code::InvalidTarget),
+            ("UNKNOWN_COMMAND", Code::UnknownCommand),
+            ("MESSAGE_ERROR", Code::MessageError),
+            ("INTERNAL_ERROR", Code::InternalError),
+            ("MULTILINE_MAX_BYTES", Code::MultilineMaxBytes),
+            ("MULTILINE_MAX_LINES", Code::MultilineMaxLines),
+            ("MULTILINE_INVALID_TARGET", Code::MultilineInvalidTarget),
+            ("MULTILINE_INVALID", Code::MultilineInvalid),
+            ("ACCOUNT_EXISTS", Code::AccountExists),
+            ("ACCOUNT_NAME_MUST_BE_NICK", Code::AccountNameMustBeNick),
+            ("ALREADY_AUTHENTICATED", Code::AlreadyAuthenticated),
+            ("BAD_ACCOUNT_NAME", Code::BadAccountName),
+            (
+                "COMPLETE_CONNECTION_REQUIRED",
+                Code::CompleteConnectionRequired,
+            ),
+            ("INVALID_EMAIL", Code::InvalidEmail),
+            ("NEED_NICK", Code::NeedNick),
+            ("TEMPORARILY_UNAVAILABLE", Code::TemporarilyUnavailable),
+            ("UNACCEPTABLE_EMAIL", Code::UnacceptableEmail),
+            ("UNACCEPTABLE_PASSWORD", Code::UnacceptablePassword),
+            ("WEAK_PASSWORD", Code::WeakPassword),
+            ("VERIFICATION_REQUIRED", Code::VerificationRequired),
+            ("INVALID_CODE", Code::InvalidCode),
+            ("CHANNEL_NAME_IN_USE", Code::ChannelNameInUse),
+            ("CANNOT_RENAME", Code::CannotRename),
+            ("CANNOT_CHANGE_REALNAME", Code::CannotChangeRealname),
+            ("INVALID_REALNAME", Code::InvalidRealname),
+            ("REDACT_FORBIDDEN", Code::RedactForbidden),
+            ("REDACT_WINDOW_EXPIRED", Code::RedactWindowExpired),
+            ("UNKNOWN_MSGID", Code::UnknownMsgid),
+            ("SOMETHING_ELSE", Code::Other("SOMETHING_ELSE".to_string())),
+        ];
+
+        for (code, expected) in tests {
+            assert_eq!(Code::from(code), expected);
+            assert_eq!(expected.as_str(), code);
+        }
+    }
+
+    #[test]
+    fn parse() {
+        let tests = [
+            (
+                Command::FAIL(
+                    "*".to_string(),
+                    "ACCOUNT_REQUIRED".to_string(),
+                    None,
+                    "Authentication required".to_string(),
+                ),
+                Reply {
+                    kind: Kind::Fail,
+                    command: "*".to_string(),
+                    code: Code::AccountRequired,
+                    context: vec![],
+                    description: "Authentication required".to_string(),
+                },
+            ),
+            (
+                Command::WARN(
+                    "REHASH".to_string(),
+                    "CERTS_EXPIRED".to_string(),
+                    Some(vec!["tls".to_string()]),
+                    "Certificate has expired".to_string(),
+                ),
+                Reply {
+                    kind: Kind::Warn,
+                    command: "REHASH".to_string(),
+                    code: Code::Other("CERTS_EXPIRED".to_string()),
+                    context: vec!["tls".to_string()],
+                    description: "Certificate has expired".to_string(),
+                },
+            ),
+            (
+                Command::NOTE(
+                    "REGISTER".to_string(),
+                    "VERIFICATION_REQUIRED".to_string(),
+                    Some(vec!["halloy".to_string()]),
+                    "Check your email".to_string(),
+                ),
+                Reply {
+                    kind: Kind::Note,
+                    command: "REGISTER".to_string(),
+                    code: Code::VerificationRequired,
+                    context: vec!["halloy".to_string()],
+                    description: "Check your email".to_string(),
+                },
+            ),
+        ];
+
+        for (command, expected) in tests {
+            assert_eq!(Reply::parse(&command), Some(expected));
+        }
+
+        assert_eq!(Reply::parse(&Command::PING("halloy".to_string())), None);
+    }
+
+    #[test]
+    fn text() {
+        let reply = |command| Reply::parse(&command).unwrap().text();
+
+        assert_eq!(
+            reply(Command::FAIL(
+                "JOIN".to_string(),
+                "CHANNEL_RENAMED".to_string(),
+                Some(vec!["#old".to_string(), "#new".to_string()]),
+                "Channel was renamed".to_string(),
+            )),
+            "JOIN (#old, #new) failed: Channel was renamed"
+        );
+        assert_eq!(
+            reply(Command::FAIL(
+                "*".to_string(),
+                "ACCOUNT_REQUIRED".to_string(),
+                None,
+                "Authentication required".to_string(),
+            )),
+            format!(
+                "* failed: Authentication required ({})",
+                Code::AccountRequired.hint().unwrap()
+            )
+        );
+    }
+}
@@ -1,3 +1,10 @@
+use std::fmt;
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::user::AccessLevel;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Mode<T> {
     Add(T, Option<String>),
@@ -31,6 +38,7 @@ impl<T> Mode<T> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Operation {
     Add,
     Remove,
@@ -38,6 +46,11 @@ pub enum Operation {
 
 pub trait Parser: Copy {
     fn takes_arg(self) -> bool;
+    /// Some modes (e.g. a channel's user limit) only take an argument when
+    /// they're set
+    fn takes_arg_when_removed(self) -> bool {
+        self.takes_arg()
+    }
     fn from_char(c: char) -> Self;
 }
 
@@ -149,6 +162,10 @@ impl Parser for Channel {
         )
     }
 
+    fn takes_arg_when_removed(self) -> bool {
+        self.takes_arg() && self != Channel::Limit
+    }
+
     fn from_char(c: char) -> Self {
         Self::from(c)
     }
@@ -227,54 +244,341 @@ pub fn parse<T>(encoded: &str, args: &[String]) -> Vec<Mode<T>>
 where
     T: Parser,
 {
-    enum Mod<'a> {
-        Plus(&'a str),
-        Minus(&'a str),
-        None(&'a str),
-    }
-
     let mut args = args.iter();
+    let mut operation = None;
     let mut parsed = vec![];
 
-    let mods = match (encoded.find('+'), encoded.find('-')) {
-        (None, None) => vec![Mod::None(encoded)],
-        (None, Some(i)) => vec![Mod::Minus(&encoded[i + 1..])],
-        (Some(i), None) => vec![Mod::Plus(&encoded[i + 1..])],
-        (Some(p), Some(m)) => {
-            let end_plus = if p > m { encoded.len() } else { m };
-            let end_minus = if m > p { encoded.len() } else { p };
+    // Arguments are taken in the order their modes are given in
+    for c in encoded.chars() {
+        match c {
+            '+' => operation = Some(Operation::Add),
+            '-' => operation = Some(Operation::Remove),
+            c => {
+                let value = T::from_char(c);
 
-            vec![
-                Mod::Plus(&encoded[p + 1..end_plus]),
-                Mod::Minus(&encoded[m + 1..end_minus]),
-            ]
+                parsed.push(match operation {
+                    Some(Operation::Add) => Mode::Add(
+                        value,
+                        value
+                            .takes_arg()
+                            .then(|| args.next().cloned())
+                            .flatten(),
+                    ),
+                    Some(Operation::Remove) => Mode::Remove(
+                        value,
+                        value
+                            .takes_arg_when_removed()
+                            .then(|| args.next().cloned())
+                            .flatten(),
+                    ),
+                    None => Mode::NoPrefix(value),
+                });
+            }
         }
-    };
+    }
 
-    for _mod in mods {
-        let modes = match _mod {
-            Mod::Plus(s) => s,
-            Mod::Minus(s) => s,
-            Mod::None(s) => s,
-        };
+    // Adds are listed first
+    parsed.sort_by_key(|mode| !matches!(mode, Mode::Add(..)));
 
-        for c in modes.chars() {
-            let value = T::from_char(c);
-            let arg = if value.takes_arg() {
-                args.next().cloned()
-            } else {
-                None
-            };
-            let mode = match _mod {
-                Mod::Plus(_) => Mode::Add(value, arg),
-                Mod::Minus(_) => Mode::Remove(value, arg),
-                Mod::None(_) => Mode::NoPrefix(value),
+    parsed
+}
+
+/// What a channel MODE line did, with access levels and bans grouped by mode
+/// so `+oo-v alice bob carol` reads as op given to alice and bob and voice
+/// taken from carol
+#[derive(
+    Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize,
+)]
+pub struct Change {
+    groups: Vec<Group>,
+    /// Any other modes, kept as they were set
+    other: Vec<(Option<Operation>, char, Option<String>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct Group {
+    operation: Operation,
+    mode: char,
+    targets: Vec<String>,
+}
+
+impl Change {
+    pub fn parse(modes: &str, args: &[String]) -> Self {
+        let mut change = Self::default();
+
+        for mode in parse::<Letter>(modes, args) {
+            let Letter(letter) = *mode.value();
+
+            match (mode.operation(), mode.arg()) {
+                (Some(operation), Some(target)) if is_grouped(letter) => {
+                    change.push(operation, letter, target.to_string());
+                }
+                (operation, arg) => {
+                    change.other.push((
+                        operation,
+                        letter,
+                        arg.map(ToString::to_string),
+                    ));
+                }
+            }
+        }
+
+        change
+    }
+
+    /// Adds the modes of a later line, e.g. when a services bot ops many
+    /// users over a few lines
+    pub fn merge(&mut self, other: &Self) {
+        for group in &other.groups {
+            for target in &group.targets {
+                self.push(group.operation, group.mode, target.clone());
+            }
+        }
+
+        self.other.extend(other.other.iter().cloned());
+    }
+
+    /// Whether `describe` leaves out any targets when it names at most
+    /// `named` of each mode
+    pub fn is_truncated(&self, named: usize) -> bool {
+        self.groups
+            .iter()
+            .any(|group| group.targets.len() > named + 1)
+    }
+
+    /// e.g. "gave op to alice, bob; unbanned *!*@mask". With `named`, at most
+    /// that many targets of each mode are listed and the rest are counted.
+    pub fn describe(&self, named: Option<usize>) -> String {
+        let groups = self.groups.iter().map(|group| {
+            let targets = match named {
+                Some(named) if group.targets.len() > named + 1 => format!(
+                    "{}, +{} more",
+                    group.targets[..named].join(", "),
+                    group.targets.len() - named
+                ),
+                _ => group.targets.join(", "),
             };
-            parsed.push(mode);
+
+            match (Channel::from(group.mode), group.operation) {
+                (Channel::Ban, Operation::Add) => format!("banned {targets}"),
+                (Channel::Ban, Operation::Remove) => {
+                    format!("unbanned {targets}")
+                }
+                (mode, Operation::Add) => {
+                    format!("gave {} to {targets}", access_level_name(mode))
+                }
+                (mode, Operation::Remove) => {
+                    format!("took {} from {targets}", access_level_name(mode))
+                }
+            }
+        });
+
+        let other = (!self.other.is_empty())
+            .then(|| format!("set mode {}", self.other_modes()));
+
+        groups.chain(other).join("; ")
+    }
+
+    fn push(&mut self, operation: Operation, mode: char, target: String) {
+        if let Some(group) = self
+            .groups
+            .iter_mut()
+            .find(|group| group.operation == operation && group.mode == mode)
+        {
+            group.targets.push(target);
+        } else {
+            self.groups.push(Group {
+                operation,
+                mode,
+                targets: vec![target],
+            });
         }
     }
 
-    parsed
+    /// e.g. "+lk-s 10 key"
+    fn other_modes(&self) -> String {
+        let mut modes = String::new();
+        let mut current = None;
+
+        for (operation, letter, _) in &self.other {
+            if *operation != current {
+                modes.extend(operation.map(|operation| match operation {
+                    Operation::Add => '+',
+                    Operation::Remove => '-',
+                }));
+                current = *operation;
+            }
+
+            modes.push(*letter);
+        }
+
+        std::iter::once(modes.as_str())
+            .chain(self.other.iter().filter_map(|(_, _, arg)| arg.as_deref()))
+            .join(" ")
+    }
+}
+
+/// Modes set on a channel with their arguments, e.g. `+ntk key`. Lists, like
+/// bans, and access levels aren't kept.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Modes(Vec<(char, Option<String>)>);
+
+impl Modes {
+    /// Modes of `RPL_CHANNELMODEIS`
+    pub fn parse(modes: &str, args: &[String]) -> Self {
+        let mut parsed = Self::default();
+
+        parsed.apply(modes, args);
+
+        parsed
+    }
+
+    /// Applies the modes set and unset by a MODE line
+    pub fn apply(&mut self, modes: &str, args: &[String]) {
+        for mode in parse::<Letter>(modes, args) {
+            match mode {
+                Mode::Add(Letter(letter), _)
+                | Mode::Remove(Letter(letter), _)
+                    if is_list(letter) => {}
+                Mode::Add(Letter(letter), arg) => self.set(letter, arg),
+                Mode::Remove(Letter(letter), _) => self.unset(letter),
+                Mode::NoPrefix(_) => {}
+            }
+        }
+    }
+
+    pub fn contains(&self, letter: char) -> bool {
+        self.0.iter().any(|(set, _)| *set == letter)
+    }
+
+    pub fn arg(&self, letter: char) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(set, _)| *set == letter)
+            .and_then(|(_, arg)| arg.as_deref())
+    }
+
+    pub fn set(&mut self, letter: char, arg: Option<String>) {
+        if let Some((_, current)) =
+            self.0.iter_mut().find(|(set, _)| *set == letter)
+        {
+            *current = arg;
+        } else {
+            self.0.push((letter, arg));
+        }
+    }
+
+    pub fn unset(&mut self, letter: char) {
+        self.0.retain(|(set, _)| *set != letter);
+    }
+
+    /// Modes and arguments of the MODE line changing these modes into
+    /// `other`, e.g. `+m-k key`
+    pub fn change_to(&self, other: &Self) -> Option<(String, Vec<String>)> {
+        let added = other
+            .0
+            .iter()
+            .filter(|mode| !self.0.contains(mode))
+            .collect::<Vec<_>>();
+        let removed = self
+            .0
+            .iter()
+            .filter(|(letter, _)| !other.contains(*letter))
+            .collect::<Vec<_>>();
+
+        if added.is_empty() && removed.is_empty() {
+            return None;
+        }
+
+        let mut modes = String::new();
+        let mut args = vec![];
+
+        if !added.is_empty() {
+            modes.push('+');
+        }
+
+        for (letter, arg) in added {
+            modes.push(*letter);
+            args.extend(arg.clone());
+        }
+
+        if !removed.is_empty() {
+            modes.push('-');
+        }
+
+        for (letter, arg) in removed {
+            modes.push(*letter);
+
+            // Servers want the key, which we may not know, to remove it
+            if Letter(*letter).takes_arg_when_removed() {
+                args.push(arg.clone().unwrap_or_else(|| "*".to_string()));
+            }
+        }
+
+        Some((modes, args))
+    }
+}
+
+/// e.g. "+ntk key"
+impl fmt::Display for Modes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+")?;
+
+        for (letter, _) in &self.0 {
+            write!(f, "{letter}")?;
+        }
+
+        for arg in self.0.iter().filter_map(|(_, arg)| arg.as_deref()) {
+            write!(f, " {arg}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A channel mode kept as its letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Letter(char);
+
+impl Parser for Letter {
+    fn takes_arg(self) -> bool {
+        Channel::from(self.0).takes_arg()
+    }
+
+    fn takes_arg_when_removed(self) -> bool {
+        Channel::from(self.0).takes_arg_when_removed()
+    }
+
+    fn from_char(c: char) -> Self {
+        Self(c)
+    }
+}
+
+/// Bans and modes setting a user's access level name their targets
+fn is_grouped(letter: char) -> bool {
+    let mode = Channel::from(letter);
+
+    mode == Channel::Ban || AccessLevel::try_from(mode).is_ok()
+}
+
+/// Lists of masks and modes setting a user's access level
+fn is_list(letter: char) -> bool {
+    is_grouped(letter)
+        || matches!(
+            Channel::from(letter),
+            Channel::BanException | Channel::InviteException
+        )
+}
+
+fn access_level_name(mode: Channel) -> &'static str {
+    match AccessLevel::try_from(mode) {
+        Ok(AccessLevel::Owner) => "owner",
+        Ok(AccessLevel::Admin) => "admin",
+        Ok(AccessLevel::Oper) => "op",
+        Ok(AccessLevel::HalfOp) => "halfop",
+        Ok(AccessLevel::Voice) => "voice",
+        Ok(AccessLevel::Member) | Err(()) => "access",
+    }
 }
 
 #[cfg(test)]
@@ -303,4 +607,99 @@ mod test {
             assert_eq!(modes, expected);
         }
     }
+
+    #[test]
+    fn args_in_order() {
+        let args = |args: &[&str]| {
+            args.iter().map(ToString::to_string).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            parse::<Channel>("-b+ov", &args(&["*!*@mask", "alice", "bob"])),
+            vec![
+                Mode::Add(Channel::Oper, Some("alice".into())),
+                Mode::Add(Channel::Voice, Some("bob".into())),
+                Mode::Remove(Channel::Ban, Some("*!*@mask".into())),
+            ]
+        );
+        // Removing the limit takes no argument
+        assert_eq!(
+            parse::<Channel>("-lo", &args(&["alice"])),
+            vec![
+                Mode::Remove(Channel::Limit, None),
+                Mode::Remove(Channel::Oper, Some("alice".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn modes() {
+        let args = |args: &[&str]| {
+            args.iter().map(ToString::to_string).collect::<Vec<_>>()
+        };
+
+        let mut modes = Modes::parse("+ntk", &args(&["key"]));
+        assert_eq!(modes.to_string(), "+ntk key");
+        assert!(modes.contains('t'));
+        assert_eq!(modes.arg('k'), Some("key"));
+
+        // Lists and access levels aren't kept
+        modes.apply("+ml-t+bo", &args(&["10", "*!*@mask", "alice"]));
+        assert_eq!(modes.to_string(), "+nkml key 10");
+        modes.apply("-l+k", &args(&["other"]));
+        assert_eq!(modes.to_string(), "+nkm other");
+
+        let mut wanted = modes.clone();
+        assert_eq!(modes.change_to(&wanted), None);
+
+        wanted.set('i', None);
+        wanted.set('l', Some("5".into()));
+        wanted.unset('k');
+        wanted.unset('m');
+        assert_eq!(
+            modes.change_to(&wanted),
+            Some(("+il-km".into(), args(&["5", "other"])))
+        );
+
+        // A changed key is set again
+        let mut wanted = modes.clone();
+        wanted.set('k', Some("new".into()));
+        assert_eq!(
+            modes.change_to(&wanted),
+            Some(("+k".into(), args(&["new"])))
+        );
+
+        assert_eq!(Modes::parse("+", &[]).to_string(), "+");
+    }
+
+    #[test]
+    fn change() {
+        let change = |modes: &str, args: &[&str]| {
+            Change::parse(
+                modes,
+                &args.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            )
+        };
+
+        assert_eq!(
+            change("+ov-b", &["alice", "bob", "*!*@mask"]).describe(None),
+            "gave op to alice; gave voice to bob; unbanned *!*@mask"
+        );
+        assert_eq!(
+            change("+oo-v+nt", &["alice", "bob", "carol"]).describe(None),
+            "gave op to alice, bob; took voice from carol; set mode +nt"
+        );
+        assert_eq!(change("+l-s", &["10"]).describe(None), "set mode +l-s 10");
+
+        let mut merged = change("+ooooo", &["a", "b", "c", "d", "e"]);
+        merged.merge(&change("+ooooo", &["f", "g", "h", "i", "j"]));
+        merged.merge(&change("+ooooo", &["k", "l", "m", "n", "o"]));
+        assert!(merged.is_truncated(2));
+        assert_eq!(merged.describe(Some(2)), "gave op to a, b, +13 more");
+
+        // Naming the last one takes as much room as counting it
+        let three = change("+ooo", &["a", "b", "c"]);
+        assert!(!three.is_truncated(2));
+        assert_eq!(three.describe(Some(2)), "gave op to a, b, c");
+    }
 }
@@ -1,11 +1,13 @@
 use crate::User;
+use crate::bouncer::Attachment;
+use crate::stream::disconnect;
 use crate::target::Channel;
 use crate::user::Nick;
 
 #[derive(Debug, PartialEq, Eq, Hash, Clone)]
 pub enum Notification {
     Connected,
-    Disconnected,
+    Disconnected(Option<disconnect::Reason>),
     Reconnected,
     DirectMessage {
         user: User,
@@ -16,10 +18,22 @@ pub enum Notification {
         channel: Channel,
         message: String,
     },
+    /// Any message in a channel set to notify on all messages
+    ChannelMessage {
+        user: User,
+        channel: Channel,
+        message: String,
+    },
     FileTransferRequest {
         nick: Nick,
         filename: String,
     },
     MonitoredOnline(Vec<User>),
     MonitoredOffline(Vec<Nick>),
+    BouncerAttachment(Attachment),
+    /// A scheduled message came due while its server was disconnected
+    ScheduledMessage {
+        target: String,
+        dropped: bool,
+    },
 }
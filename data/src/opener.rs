@@ -0,0 +1,116 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{fs, io};
+
+use crate::{Config, environment, log};
+
+/// Files and directories of Halloy the user can open with the platform's
+/// file manager or editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    ConfigFile,
+    ConfigDirectory,
+    ThemesDirectory,
+    DataDirectory,
+    CacheDirectory,
+    LogFile,
+}
+
+impl Target {
+    pub fn path(self) -> PathBuf {
+        match self {
+            Target::ConfigFile => environment::config_dir()
+                .join(environment::config_file_name(environment::profile())),
+            Target::ConfigDirectory => environment::config_dir(),
+            Target::ThemesDirectory => environment::config_dir().join("themes"),
+            Target::DataDirectory => environment::data_dir(),
+            Target::CacheDirectory => environment::cache_dir(),
+            Target::LogFile => log::path(),
+        }
+    }
+
+    /// Creates the missing file or directory at `path`. A missing config file
+    /// is created from the same template as on first launch.
+    fn create(self, path: &Path) -> io::Result<()> {
+        match self {
+            Target::ConfigFile => Config::write_initial_config(path),
+            Target::LogFile => {
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+
+                fs::File::create(path).map(|_| ())
+            }
+            Target::ConfigDirectory
+            | Target::ThemesDirectory
+            | Target::DataDirectory
+            | Target::CacheDirectory => fs::create_dir_all(path),
+        }
+    }
+}
+
+/// Opens `target` with the platform's opener, creating it first if it
+/// doesn't exist. Returns the opened path.
+pub fn open(target: Target) -> Result<PathBuf, Error> {
+    let path = target.path();
+
+    if !path.exists() {
+        target
+            .create(&path)
+            .map_err(|error| Error::Create(path.clone(), Arc::new(error)))?;
+    }
+
+    open_path(&path)?;
+
+    Ok(path)
+}
+
+/// Opens the file or directory at `path` with the platform's opener
+pub fn open_path(path: &Path) -> Result<(), Error> {
+    open::that_detached(path)
+        .map_err(|error| Error::Open(path.to_path_buf(), Arc::new(error)))
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error("failed to create {path}: {error}", path = .0.display(), error = .1)]
+    Create(PathBuf, Arc<io::Error>),
+    #[error("failed to open {path}: {error}", path = .0.display(), error = .1)]
+    Open(PathBuf, Arc<io::Error>),
+}
+
+impl Error {
+    /// Path which couldn't be opened, for the user to open themselves
+    pub fn path(&self) -> &Path {
+        match self {
+            Error::Create(path, _) | Error::Open(path, _) => path,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_missing() {
+        let dir = std::env::temp_dir()
+            .join(format!("halloy-opener-{}", std::process::id()));
+
+        let config = dir.join("config").join("config.toml");
+        Target::ConfigFile.create(&config).unwrap();
+        let template = fs::read_to_string(&config).unwrap();
+        assert!(template.starts_with('#'));
+        assert!(!template.contains("__NICKNAME__"));
+
+        let log = dir.join("data").join("halloy.log");
+        Target::LogFile.create(&log).unwrap();
+        assert!(log.is_file());
+
+        let themes = dir.join("config").join("themes");
+        Target::ThemesDirectory.create(&themes).unwrap();
+        assert!(themes.is_dir());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::Buffer;
+use crate::{Buffer, Server};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum Pane {
@@ -12,6 +12,8 @@ pub enum Pane {
     },
     Buffer {
         buffer: Buffer,
+        #[serde(default)]
+        pinned: bool,
     },
     Empty,
 }
@@ -21,3 +23,73 @@ pub enum Axis {
     Horizontal,
     Vertical,
 }
+
+impl Pane {
+    /// Empties the panes of buffers on servers which aren't `known`, adding
+    /// those servers to `skipped`
+    pub fn skip_servers(
+        self,
+        known: &impl Fn(&Server) -> bool,
+        skipped: &mut Vec<Server>,
+    ) -> Self {
+        match self {
+            Pane::Split { axis, ratio, a, b } => Pane::Split {
+                axis,
+                ratio,
+                a: Box::new(a.skip_servers(known, skipped)),
+                b: Box::new(b.skip_servers(known, skipped)),
+            },
+            Pane::Buffer {
+                buffer: Buffer::Upstream(buffer),
+                ..
+            } if !known(buffer.server()) => {
+                if !skipped.contains(buffer.server()) {
+                    skipped.push(buffer.server().clone());
+                }
+
+                Pane::Empty
+            }
+            pane => pane,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::Upstream;
+
+    #[test]
+    fn skip_servers() {
+        let buffer = |server: &str| Pane::Buffer {
+            buffer: Buffer::Upstream(Upstream::Server(server.into())),
+            pinned: false,
+        };
+        let split = |a, b| Pane::Split {
+            axis: Axis::Vertical,
+            ratio: 0.5,
+            a: Box::new(a),
+            b: Box::new(b),
+        };
+
+        let pane = split(
+            buffer("libera"),
+            split(buffer("oftc"), split(buffer("oftc"), Pane::Empty)),
+        );
+
+        let mut skipped = vec![];
+        let pane = pane
+            .skip_servers(&|server| server.as_ref() == "libera", &mut skipped);
+
+        assert_eq!(skipped, vec![Server::from("oftc")]);
+
+        let Pane::Split { a, b, .. } = pane else {
+            panic!("split was kept");
+        };
+        assert!(matches!(*a, Pane::Buffer { .. }));
+        assert!(matches!(
+            *b,
+            Pane::Split { a, .. } if matches!(*a, Pane::Empty)
+        ));
+    }
+}
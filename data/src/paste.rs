@@ -0,0 +1,103 @@
+//! Long input uploaded through the configured pastebin command, to send
+//! where it can be read instead of the input itself
+use std::io;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time;
+
+/// How long an upload may take
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Runs `command_line` in the shell with `text` on stdin, returning the
+/// first line it printed, e.g. a URL
+pub async fn upload(
+    command_line: String,
+    text: String,
+) -> Result<String, Error> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+
+    let mut child = command
+        .arg(&command_line)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(text.as_bytes()).await;
+    }
+
+    let output = time::timeout(TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| Error::Timeout)??;
+
+    if !output.status.success() {
+        return Err(Error::Command(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(ToString::to_string)
+        .ok_or(Error::Empty)
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error("command failed: {0}")]
+    Command(String),
+    #[error("timed out")]
+    Timeout,
+    #[error("command didn't print where it was uploaded")]
+    Empty,
+    #[error("io error: {0}")]
+    Io(Arc<io::Error>),
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(Arc::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn first_line() {
+        let url = upload(
+            "wc -l | xargs printf '\\nhttps://paste.example/%s\\nexpires in 1d\\n'"
+                .to_string(),
+            "one\ntwo\n".to_string(),
+        )
+        .await;
+
+        assert_eq!(url.unwrap(), "https://paste.example/2");
+        assert!(matches!(
+            upload("true".to_string(), String::new()).await,
+            Err(Error::Empty)
+        ));
+        assert!(matches!(
+            upload("echo nope >&2; false".to_string(), String::new()).await,
+            Err(Error::Command(error)) if error == "nope"
+        ));
+    }
+}
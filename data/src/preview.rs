@@ -17,7 +17,7 @@ use url::Url;
 pub use self::card::Card;
 pub use self::image::Image;
 use crate::target::Target;
-use crate::{config, isupport};
+use crate::{config, http, isupport};
 
 mod cache;
 pub mod card;
@@ -25,8 +25,6 @@ pub mod image;
 
 // Prevent us from rate limiting ourselves
 static RATE_LIMIT: OnceLock<Semaphore> = OnceLock::new();
-static CLIENT: LazyLock<reqwest::Client> =
-    LazyLock::new(|| reqwest::Client::builder().build().expect("build client"));
 static OPENGRAPH_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(
         r#"(?m)<meta[^>]+(name|property|content)=("[^"]+"|'[^']+')[^>]+(name|property|content)=("[^"]+"|'[^']+')[^>]*\/?>"#,
@@ -39,13 +37,14 @@ pub struct Previews<'a> {
     collection: &'a Collection,
     cards_are_visible: bool,
     images_are_visible: bool,
+    image_config: &'a config::preview::Image,
 }
 
 impl<'a> Previews<'a> {
     pub fn new(
         collection: &'a Collection,
         target: &Target,
-        config: &config::Preview,
+        config: &'a config::Preview,
         casemapping: isupport::CaseMap,
     ) -> Previews<'a> {
         Self {
@@ -53,7 +52,9 @@ impl<'a> Previews<'a> {
             cards_are_visible: config.enabled
                 && config.card.visible(target, casemapping),
             images_are_visible: config.enabled
+                && config.image.enabled
                 && config.image.visible(target, casemapping),
+            image_config: &config.image,
         }
     }
 
@@ -62,7 +63,10 @@ impl<'a> Previews<'a> {
             State::Loading => true,
             State::Loaded(preview) => match preview {
                 Preview::Card(_) => self.cards_are_visible,
-                Preview::Image(_) => self.images_are_visible,
+                Preview::Image(_) => {
+                    self.images_are_visible
+                        && self.image_config.domain_visible(url)
+                }
             },
             State::Error(_) => true,
         })
@@ -93,6 +97,14 @@ pub async fn load(
         return Err(LoadError::Disabled);
     }
 
+    // Skip fetching links which are obviously images when they wouldn't be
+    // shown anyway
+    if is_image_url(&url)
+        && !(config.image.enabled && config.image.domain_visible(&url))
+    {
+        return Err(LoadError::Disabled);
+    }
+
     if let Some(state) = cache::load(&url, &config).await {
         match state {
             cache::State::Ok(preview) => return Ok(preview),
@@ -114,6 +126,42 @@ pub async fn load(
     }
 }
 
+/// Image at `url`, loaded the same way as previews but whether or not they're
+/// enabled
+pub async fn load_image(
+    url: Url,
+    config: config::Preview,
+) -> Result<Image, LoadError> {
+    let preview = match cache::load(&url, &config).await {
+        Some(cache::State::Ok(preview)) => preview,
+        Some(cache::State::Error) => return Err(LoadError::CachedFailed),
+        None => match fetch(url.clone(), &config).await {
+            Ok(Fetched::Image(image)) => {
+                let preview = Preview::Image(image);
+
+                cache::save(&url, cache::State::Ok(preview.clone())).await;
+
+                preview
+            }
+            Ok(Fetched::Other(_)) => {
+                cache::save(&url, cache::State::Error).await;
+
+                return Err(LoadError::NotImage);
+            }
+            Err(error) => {
+                cache::save(&url, cache::State::Error).await;
+
+                return Err(error);
+            }
+        },
+    };
+
+    match preview {
+        Preview::Image(image) => Ok(image),
+        Preview::Card(_) => Err(LoadError::NotImage),
+    }
+}
+
 async fn load_uncached(
     url: Url,
     config: &config::Preview,
@@ -209,15 +257,14 @@ async fn fetch(
         .acquire()
         .await;
 
-    let mut req = CLIENT
-        .get(url.clone())
+    let mut request = http::Request::get(url.clone())
         .timeout(Duration::from_millis(config.request.timeout_ms));
 
     if let Ok(user_agent) = HeaderValue::from_str(&config.request.user_agent) {
-        req = req.header(header::USER_AGENT, user_agent);
+        request = request.header(header::USER_AGENT, user_agent);
     }
 
-    let mut resp = req.send().await?.error_for_status()?;
+    let mut resp = http::send(request).await?;
 
     let Some(first_chunk) = resp.chunk().await? else {
         return Err(LoadError::EmptyBody);
@@ -291,6 +338,16 @@ async fn fetch(
     Ok(fetched)
 }
 
+fn is_image_url(url: &Url) -> bool {
+    url.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|file_name| file_name.rsplit_once('.'))
+        .is_some_and(|(_, extension)| {
+            ["png", "jpg", "jpeg", "gif", "webp"]
+                .contains(&extension.to_lowercase().as_str())
+        })
+}
+
 fn decode_html_string(s: &str) -> String {
     html_escape::decode_html_entities(s).to_string()
 }
@@ -315,10 +372,37 @@ pub enum LoadError {
     ParseImage(#[from] image::Error),
     #[error("missing required property {0}")]
     MissingProperty(&'static str),
-    #[error("request failed: {0}")]
-    Reqwest(#[from] reqwest::Error),
+    #[error(transparent)]
+    Http(#[from] http::Error),
     #[error("failed to parse url: {0}")]
     ParseUrl(#[from] url::ParseError),
     #[error("io error: {0}")]
     Io(#[from] io::Error),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn image_urls() {
+        let url = |url: &str| Url::parse(url).unwrap();
+
+        assert!(is_image_url(&url("https://example.com/cat.PNG")));
+        assert!(is_image_url(&url("https://example.com/a/cat.webp?s=1")));
+        assert!(!is_image_url(&url("https://example.com/cat.html")));
+        assert!(!is_image_url(&url("https://example.com/")));
+
+        let config = config::preview::Image {
+            exclude_domains: vec!["example.com".into()],
+            include_domains: vec!["i.example.com".into()],
+            ..Default::default()
+        };
+
+        assert!(config.domain_visible(&url("https://imgur.com/cat.png")));
+        assert!(!config.domain_visible(&url("https://example.com/cat.png")));
+        assert!(!config.domain_visible(&url("https://a.example.com/cat.png")));
+        assert!(config.domain_visible(&url("https://i.example.com/cat.png")));
+        assert!(config.domain_visible(&url("https://notexample.com/cat.png")));
+    }
+}
@@ -2,11 +2,10 @@ use std::path::PathBuf;
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
-use tokio::fs;
 use url::Url;
 
 use super::{Preview, image};
-use crate::{config, environment};
+use crate::{config, environment, http};
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -16,14 +15,8 @@ pub enum State {
 }
 
 pub async fn load(url: &Url, config: &config::Preview) -> Option<State> {
-    let path = state_path(url);
-
-    if !path.exists() {
-        return None;
-    }
-
     let state: State =
-        serde_json::from_slice(&fs::read(&path).await.ok()?).ok()?;
+        http::cache::load(&state_path(url), http::client().cache_ttl()).await?;
 
     // Ensure the actual image is cached
     match &state {
@@ -44,30 +37,11 @@ pub async fn load(url: &Url, config: &config::Preview) -> Option<State> {
 }
 
 pub async fn save(url: &Url, state: State) {
-    let path = state_path(url);
-
-    if let Some(parent) = path.parent().filter(|p| !p.exists()) {
-        let _ = fs::create_dir_all(parent).await;
-    }
-
-    let Ok(bytes) = serde_json::to_vec(&state) else {
-        return;
-    };
-
-    let _ = fs::write(path, &bytes).await;
+    http::cache::save(&state_path(url), &state).await;
 }
 
 fn state_path(url: &Url) -> PathBuf {
-    let hash =
-        hex::encode(seahash::hash(url.as_str().as_bytes()).to_be_bytes());
-
-    environment::cache_dir()
-        .join("previews")
-        .join("state")
-        .join(&hash[..2])
-        .join(&hash[2..4])
-        .join(&hash[4..6])
-        .join(format!("{hash}.json"))
+    http::cache::path("previews/state", url.as_str())
 }
 
 pub(super) fn download_path(url: &Url) -> PathBuf {
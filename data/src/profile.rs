@@ -0,0 +1,210 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::{io, process};
+
+use chrono::{DateTime, Utc};
+use tokio::fs;
+
+use crate::environment::{self, CONFIG_FILE_NAME};
+
+/// Name used on the command line for the profile backed by `config.toml`
+pub const DEFAULT: &str = "default";
+
+/// Files carried over when duplicating a profile. History and logs are left
+/// behind so the copy starts out as a sandbox.
+const DUPLICATED_DATA: &[&str] =
+    &["dashboard.json.gz", "window.json", "ignore.json"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    /// `None` for the default profile
+    pub name: Option<String>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+impl Profile {
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(DEFAULT)
+    }
+}
+
+/// Parses a profile name given on the command line, `default` being the
+/// profile without a name.
+pub fn parse(name: &str) -> Result<Option<String>, Error> {
+    if name == DEFAULT {
+        Ok(None)
+    } else if is_valid_name(name) {
+        Ok(Some(name.to_string()))
+    } else {
+        Err(Error::InvalidName(name.to_string()))
+    }
+}
+
+/// Every profile with a config file, most recently used first.
+pub fn list() -> Vec<Profile> {
+    let Ok(entries) = std::fs::read_dir(environment::config_dir()) else {
+        return vec![];
+    };
+
+    let mut profiles = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            name_from_file(&entry.file_name().to_string_lossy())
+        })
+        .map(|name| Profile {
+            last_used: last_used(name.as_deref()),
+            name,
+        })
+        .collect::<Vec<_>>();
+
+    profiles.sort_by(|a, b| {
+        b.last_used
+            .cmp(&a.last_used)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+
+    profiles
+}
+
+/// Records that the current profile has been used.
+pub async fn touch() -> Result<(), Error> {
+    let path = last_used_path(environment::profile());
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    let bytes = serde_json::to_vec(&Utc::now())?;
+    fs::write(path, &bytes).await?;
+
+    Ok(())
+}
+
+/// Copies the config and layout of `profile` into a new profile, returning
+/// its name.
+pub async fn duplicate(profile: Option<String>) -> Result<String, Error> {
+    let base = profile.as_deref().unwrap_or(DEFAULT);
+    let config_dir = environment::config_dir();
+
+    let name = (1..)
+        .map(|n| match n {
+            1 => format!("{base}-copy"),
+            n => format!("{base}-copy-{n}"),
+        })
+        .find(|name| {
+            !config_dir
+                .join(environment::config_file_name(Some(name)))
+                .exists()
+        })
+        .expect("unbounded range");
+
+    fs::copy(
+        config_dir.join(environment::config_file_name(profile.as_deref())),
+        config_dir.join(environment::config_file_name(Some(&name))),
+    )
+    .await?;
+
+    let from = environment::profile_data_dir(profile.as_deref());
+    let to = environment::profile_data_dir(Some(&name));
+
+    fs::create_dir_all(&to).await?;
+
+    for file in DUPLICATED_DATA {
+        if fs::try_exists(from.join(file)).await? {
+            fs::copy(from.join(file), to.join(file)).await?;
+        }
+    }
+
+    Ok(name)
+}
+
+/// Starts a new Halloy process running `profile`.
+pub fn launch(profile: Option<&str>) -> Result<(), Error> {
+    let exe = std::env::current_exe()?;
+
+    process::Command::new(exe)
+        .arg("--profile")
+        .arg(profile.unwrap_or(DEFAULT))
+        .spawn()?;
+
+    Ok(())
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Profile of a config file name, e.g. `Some(Some("work"))` for
+/// `config.work.toml` and `Some(None)` for `config.toml`.
+fn name_from_file(file_name: &str) -> Option<Option<String>> {
+    if file_name == CONFIG_FILE_NAME {
+        return Some(None);
+    }
+
+    file_name
+        .strip_prefix("config.")?
+        .strip_suffix(".toml")
+        .filter(|name| *name != DEFAULT && is_valid_name(name))
+        .map(|name| Some(name.to_string()))
+}
+
+fn last_used(profile: Option<&str>) -> Option<DateTime<Utc>> {
+    let bytes = std::fs::read(last_used_path(profile)).ok()?;
+
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn last_used_path(profile: Option<&str>) -> PathBuf {
+    environment::profile_data_dir(profile).join("last-used.json")
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error(
+        "invalid profile name {0:?}, only letters, digits, '-' and '_' are allowed"
+    )]
+    InvalidName(String),
+    #[error(transparent)]
+    Serde(Arc<serde_json::Error>),
+    #[error(transparent)]
+    Io(Arc<io::Error>),
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serde(Arc::new(error))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(Arc::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        assert_eq!(parse("default").unwrap(), None);
+        assert_eq!(parse("work_2-test").unwrap(), Some("work_2-test".into()));
+        assert!(parse("").is_err());
+        assert!(parse("../work").is_err());
+        assert!(parse("work.old").is_err());
+
+        assert_eq!(name_from_file("config.toml"), Some(None));
+        assert_eq!(
+            name_from_file("config.work.toml"),
+            Some(Some("work".into()))
+        );
+        assert_eq!(name_from_file("config.default.toml"), None);
+        assert_eq!(name_from_file("config.a.b.toml"), None);
+        assert_eq!(name_from_file("config.yaml"), None);
+        assert_eq!(name_from_file("themes"), None);
+    }
+}
@@ -0,0 +1,540 @@
+//! Messages sent to a buffer later, at a time set with `/schedule` or `/in`
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::{fmt, io};
+
+use chrono::{
+    DateTime, Local, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc,
+};
+use serde::{Deserialize, Serialize};
+use tokio::fs;
+
+use crate::{Server, buffer, environment};
+
+/// How long a message which is due waits for its server to connect before
+/// it's held or dropped, so reconnects and startup don't count
+const GRACE: Duration = Duration::from_secs(30);
+
+/// Times with an offset from UTC, after a trailing `Z` is replaced by one
+const OFFSET_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%:z",
+    "%Y-%m-%dT%H:%M%:z",
+    "%Y-%m-%dT%H:%M:%S%z",
+    "%Y-%m-%dT%H:%M%z",
+];
+
+/// Dates and times in the local timezone
+const LOCAL_FORMATS: &[&str] = &["%Y-%m-%dT%H:%M:%S", "%Y-%m-%dT%H:%M"];
+
+/// Times of day in the local timezone, the next time it's that time
+const TIME_OF_DAY_FORMATS: &[&str] = &["%H:%M:%S", "%H:%M"];
+
+/// Pending messages of every buffer, soonest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Scheduled(Vec<Pending>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pending {
+    pub id: u64,
+    pub buffer: buffer::Upstream,
+    pub send_at: DateTime<Utc>,
+    /// Input as typed, sent as if typed at `send_at`
+    pub text: String,
+    /// Came due while disconnected and is sent once the server connects
+    #[serde(default)]
+    pub is_held: bool,
+}
+
+impl Pending {
+    /// Input which schedules the message again, to be edited
+    pub fn edit_input(&self) -> String {
+        format!(
+            "/schedule {} {}",
+            self.send_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%dT%H:%M:%S"),
+            self.text
+        )
+    }
+}
+
+/// Messages taken from the schedule as they came due
+#[derive(Debug, Default)]
+pub struct Due {
+    /// Due and their server is connected
+    pub send: Vec<Pending>,
+    /// Due while their server is disconnected, now waiting for it to connect
+    pub held: Vec<Pending>,
+    /// Due while their server is disconnected, and won't be sent
+    pub dropped: Vec<Pending>,
+}
+
+impl Due {
+    pub fn is_empty(&self) -> bool {
+        self.send.is_empty() && self.held.is_empty() && self.dropped.is_empty()
+    }
+}
+
+/// What happens to messages which come due while their server is
+/// disconnected
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WhenDisconnected {
+    #[default]
+    SendOnConnect,
+    Drop,
+}
+
+impl Scheduled {
+    pub fn load() -> Result<Self, Error> {
+        let path = path()?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let bytes = std::fs::read(path)?;
+
+        Ok(serde_json::from_slice(&bytes)?)
+    }
+
+    pub async fn save(self) -> Result<(), Error> {
+        let path = path()?;
+
+        let bytes = serde_json::to_vec(&self)?;
+        fs::write(path, &bytes).await?;
+
+        Ok(())
+    }
+
+    /// Schedules `text` to be sent to `buffer` at `send_at`, returning its id
+    pub fn schedule(
+        &mut self,
+        buffer: buffer::Upstream,
+        send_at: DateTime<Utc>,
+        text: String,
+    ) -> u64 {
+        let id = self.0.iter().map(|pending| pending.id + 1).max();
+        let id = id.unwrap_or_default();
+
+        let index =
+            self.0.partition_point(|pending| pending.send_at <= send_at);
+
+        self.0.insert(
+            index,
+            Pending {
+                id,
+                buffer,
+                send_at,
+                text,
+                is_held: false,
+            },
+        );
+
+        id
+    }
+
+    pub fn cancel(&mut self, id: u64) -> Option<Pending> {
+        let index = self.0.iter().position(|pending| pending.id == id)?;
+
+        Some(self.0.remove(index))
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Pending> {
+        self.0.iter().find(|pending| pending.id == id)
+    }
+
+    /// Pending messages of `buffer`, soonest first
+    pub fn in_buffer<'a>(
+        &'a self,
+        buffer: &'a buffer::Upstream,
+    ) -> impl Iterator<Item = &'a Pending> + 'a {
+        self.0
+            .iter()
+            .filter(move |pending| &pending.buffer == buffer)
+    }
+
+    /// Takes the messages due at `now` whose server is connected, and holds
+    /// or drops those whose server isn't
+    pub fn take_due(
+        &mut self,
+        now: DateTime<Utc>,
+        is_connected: impl Fn(&Server) -> bool,
+        when_disconnected: WhenDisconnected,
+    ) -> Due {
+        let mut due = Due::default();
+
+        let grace = TimeDelta::from_std(GRACE).unwrap_or_default();
+
+        self.0.retain_mut(|pending| {
+            if pending.send_at > now {
+                true
+            } else if is_connected(pending.buffer.server()) {
+                due.send.push(pending.clone());
+                false
+            } else if pending.is_held || now - pending.send_at < grace {
+                true
+            } else {
+                match when_disconnected {
+                    WhenDisconnected::SendOnConnect => {
+                        pending.is_held = true;
+                        due.held.push(pending.clone());
+                        true
+                    }
+                    WhenDisconnected::Drop => {
+                        due.dropped.push(pending.clone());
+                        false
+                    }
+                }
+            }
+        });
+
+        due
+    }
+}
+
+/// Parses the time given to `/schedule`. Times without an offset from UTC
+/// are in `timezone`, and a time of day alone is the next time it's that
+/// time.
+pub fn parse_time<Tz: TimeZone>(
+    time: &str,
+    now: DateTime<Utc>,
+    timezone: &Tz,
+) -> Result<DateTime<Utc>, TimeError>
+where
+    Tz::Offset: fmt::Display,
+{
+    let nonexistent = || TimeError::Nonexistent(time.to_string());
+
+    let send_at = if let Some(send_at) = with_offset(time) {
+        send_at
+    } else if let Some(naive) = LOCAL_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(time, format).ok())
+    {
+        local(naive, timezone).ok_or_else(nonexistent)?
+    } else if let Some(time_of_day) = TIME_OF_DAY_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(time, format).ok())
+    {
+        let today = now.with_timezone(timezone).date_naive();
+
+        match local(today.and_time(time_of_day), timezone)
+            .filter(|send_at| *send_at > now)
+        {
+            Some(send_at) => send_at,
+            None => today
+                .succ_opt()
+                .and_then(|tomorrow| {
+                    local(tomorrow.and_time(time_of_day), timezone)
+                })
+                .ok_or_else(nonexistent)?,
+        }
+    } else {
+        return Err(TimeError::InvalidTime(time.to_string()));
+    };
+
+    if send_at <= now {
+        return Err(TimeError::InPast {
+            time: time.to_string(),
+            now: now
+                .with_timezone(timezone)
+                .format("%Y-%m-%dT%H:%M")
+                .to_string(),
+        });
+    }
+
+    Ok(send_at)
+}
+
+/// Parses the delay given to `/in`, e.g. `2h`, `1h30m` or `45s`, into the time
+/// it's over
+pub fn parse_delay(
+    delay: &str,
+    now: DateTime<Utc>,
+) -> Result<DateTime<Utc>, TimeError> {
+    let invalid = || TimeError::InvalidDelay(delay.to_string());
+
+    let mut seconds = 0u64;
+    let mut digits = String::new();
+
+    for c in delay.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let unit = match c.to_ascii_lowercase() {
+            'd' => 24 * 60 * 60,
+            'h' => 60 * 60,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+
+        let count = digits.parse::<u64>().map_err(|_| invalid())?;
+        digits.clear();
+
+        seconds = count
+            .checked_mul(unit)
+            .and_then(|unit_seconds| seconds.checked_add(unit_seconds))
+            .ok_or_else(invalid)?;
+    }
+
+    if !digits.is_empty() || seconds == 0 {
+        return Err(invalid());
+    }
+
+    i64::try_from(seconds)
+        .ok()
+        .and_then(TimeDelta::try_seconds)
+        .and_then(|delay| now.checked_add_signed(delay))
+        .ok_or_else(invalid)
+}
+
+fn with_offset(time: &str) -> Option<DateTime<Utc>> {
+    let time = time
+        .strip_suffix(['Z', 'z'])
+        .map_or_else(|| time.to_string(), |time| format!("{time}+00:00"));
+
+    OFFSET_FORMATS.iter().find_map(|format| {
+        DateTime::parse_from_str(&time, format)
+            .ok()
+            .map(|time| time.with_timezone(&Utc))
+    })
+}
+
+/// The earliest time it's `naive` in `timezone`, `None` if it's skipped over
+fn local<Tz: TimeZone>(
+    naive: NaiveDateTime,
+    timezone: &Tz,
+) -> Option<DateTime<Utc>> {
+    timezone
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+fn path() -> Result<PathBuf, Error> {
+    let parent = environment::data_dir();
+
+    if !parent.exists() {
+        std::fs::create_dir_all(&parent)?;
+    }
+
+    Ok(parent.join("scheduled.json"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TimeError {
+    #[error(
+        "{0} is not a valid time, e.g. 2024-05-01T09:00, 09:00 or 2024-05-01T09:00+02:00"
+    )]
+    InvalidTime(String),
+    #[error("{0} doesn't exist in the local timezone")]
+    Nonexistent(String),
+    #[error("{0} is not a valid delay, e.g. 2h, 1h30m or 45s")]
+    InvalidDelay(String),
+    #[error("{time} has already passed (it's {now} now)")]
+    InPast { time: String, now: String },
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Serde(Arc<serde_json::Error>),
+    #[error(transparent)]
+    Io(Arc<io::Error>),
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Serde(Arc::new(error))
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(Arc::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+    use crate::isupport::CaseMap;
+    use crate::target;
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn times() {
+        let now = utc("2024-05-01T08:00:00Z");
+        let cest = FixedOffset::east_opt(2 * 60 * 60).unwrap();
+
+        for (time, expected) in [
+            ("2024-05-01T12:00", "2024-05-01T10:00:00Z"),
+            ("2024-05-01T12:00:30", "2024-05-01T10:00:30Z"),
+            ("2024-05-01T12:00Z", "2024-05-01T12:00:00Z"),
+            ("2024-05-01T12:00:00z", "2024-05-01T12:00:00Z"),
+            ("2024-05-01T12:00-05:00", "2024-05-01T17:00:00Z"),
+            ("2024-05-01T18:00+0530", "2024-05-01T12:30:00Z"),
+            // Later today, or tomorrow if that's passed
+            ("12:00", "2024-05-01T10:00:00Z"),
+            ("09:30", "2024-05-02T07:30:00Z"),
+        ] {
+            assert_eq!(
+                parse_time(time, now, &cest),
+                Ok(utc(expected)),
+                "{time}"
+            );
+        }
+
+        assert_eq!(
+            parse_time("2024-05-01T09:59", now, &cest),
+            Err(TimeError::InPast {
+                time: "2024-05-01T09:59".to_string(),
+                now: "2024-05-01T10:00".to_string(),
+            })
+        );
+        assert!(matches!(
+            parse_time("2024-05-01T07:00Z", now, &cest),
+            Err(TimeError::InPast { .. })
+        ));
+        for time in ["tomorrow", "2024-05-01", "2024-13-01T09:00", "25:00"] {
+            assert_eq!(
+                parse_time(time, now, &cest),
+                Err(TimeError::InvalidTime(time.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn delays() {
+        let now = utc("2024-05-01T08:00:00Z");
+
+        for (delay, expected) in [
+            ("2h", "2024-05-01T10:00:00Z"),
+            ("1h30m", "2024-05-01T09:30:00Z"),
+            ("45s", "2024-05-01T08:00:45Z"),
+            ("90M", "2024-05-01T09:30:00Z"),
+            ("1d", "2024-05-02T08:00:00Z"),
+        ] {
+            assert_eq!(parse_delay(delay, now), Ok(utc(expected)), "{delay}");
+        }
+
+        for delay in ["", "2", "h", "0m", "2x", "-1h", "99999999999999999999s"]
+        {
+            assert_eq!(
+                parse_delay(delay, now),
+                Err(TimeError::InvalidDelay(delay.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn take_due() {
+        let libera = Server::from("libera");
+        let oftc = Server::from("oftc");
+        let channel = |server: &Server| {
+            buffer::Upstream::Channel(
+                server.clone(),
+                target::Channel::from_str("#halloy", CaseMap::default()),
+            )
+        };
+        let now = utc("2024-05-01T08:00:00Z");
+        let is_connected = |server: &Server| server == &libera;
+
+        let mut scheduled = Scheduled::default();
+        let later = scheduled.schedule(
+            channel(&libera),
+            utc("2024-05-01T09:00:00Z"),
+            "later".to_string(),
+        );
+        let sent = scheduled.schedule(
+            channel(&libera),
+            utc("2024-05-01T07:59:59Z"),
+            "sent".to_string(),
+        );
+        let waiting = scheduled.schedule(
+            channel(&oftc),
+            utc("2024-05-01T07:59:45Z"),
+            "waiting".to_string(),
+        );
+        let held = scheduled.schedule(
+            channel(&oftc),
+            utc("2024-05-01T07:00:00Z"),
+            "held".to_string(),
+        );
+
+        // Soonest first
+        assert_eq!(
+            scheduled
+                .in_buffer(&channel(&libera))
+                .map(|pending| pending.id)
+                .collect::<Vec<_>>(),
+            vec![sent, later]
+        );
+
+        let mut dropping = scheduled.clone();
+
+        let due = scheduled.take_due(
+            now,
+            is_connected,
+            WhenDisconnected::SendOnConnect,
+        );
+        assert_eq!(
+            due.send
+                .iter()
+                .map(|pending| pending.id)
+                .collect::<Vec<_>>(),
+            vec![sent]
+        );
+        assert_eq!(
+            due.held
+                .iter()
+                .map(|pending| pending.id)
+                .collect::<Vec<_>>(),
+            vec![held]
+        );
+        assert!(due.dropped.is_empty());
+        assert!(scheduled.get(held).is_some_and(|pending| pending.is_held));
+        assert!(scheduled.get(waiting).is_some());
+
+        // Held only once, then sent once connected
+        assert!(
+            scheduled
+                .take_due(now, is_connected, WhenDisconnected::SendOnConnect)
+                .is_empty()
+        );
+        let due =
+            scheduled.take_due(now, |_| true, WhenDisconnected::SendOnConnect);
+        assert_eq!(
+            due.send
+                .iter()
+                .map(|pending| pending.id)
+                .collect::<Vec<_>>(),
+            vec![held, waiting]
+        );
+        assert!(scheduled.get(later).is_some());
+
+        let due = dropping.take_due(now, is_connected, WhenDisconnected::Drop);
+        assert_eq!(
+            due.dropped
+                .iter()
+                .map(|pending| pending.id)
+                .collect::<Vec<_>>(),
+            vec![held]
+        );
+        assert!(dropping.get(held).is_none());
+        assert!(dropping.get(waiting).is_some());
+    }
+}
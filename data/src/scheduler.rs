@@ -0,0 +1,423 @@
+//! Background work of the dashboard, started a few at a time for each
+//! category so a burst of it doesn't make typing laggy. Interactive work
+//! isn't scheduled, but holds background work back for a frame once
+//! dispatched.
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long background work is held back after interactive work
+pub const FRAME: Duration = Duration::from_millis(16);
+
+/// Kinds of background work, in order of priority
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
+pub enum Category {
+    Backlog,
+    Who,
+    Preview,
+    Avatar,
+}
+
+impl Category {
+    pub const ALL: &'static [Self] = &[
+        Category::Backlog,
+        Category::Who,
+        Category::Preview,
+        Category::Avatar,
+    ];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Work of a category, as shown in the debug overlay
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Counter {
+    pub queued: usize,
+    pub running: usize,
+    pub completed: usize,
+}
+
+#[derive(Debug)]
+pub struct Scheduler<T> {
+    /// Work of each category allowed to run at the same time
+    concurrency: usize,
+    queues: [VecDeque<T>; 4],
+    counters: [Counter; 4],
+    interactive_at: Option<Instant>,
+    resume_at: Option<Instant>,
+}
+
+impl<T> Scheduler<T> {
+    pub fn new(concurrency: usize) -> Self {
+        Self {
+            concurrency: concurrency.max(1),
+            queues: Default::default(),
+            counters: Default::default(),
+            interactive_at: None,
+            resume_at: None,
+        }
+    }
+
+    pub fn set_concurrency(&mut self, concurrency: usize) {
+        self.concurrency = concurrency.max(1);
+    }
+
+    /// Records that interactive work was dispatched at `now`
+    pub fn interactive(&mut self, now: Instant) {
+        self.interactive_at = Some(now);
+    }
+
+    /// Queues `work`, which starts once polled
+    pub fn submit(&mut self, category: Category, work: T) {
+        self.queues[category.index()].push_back(work);
+        self.counters[category.index()].queued += 1;
+    }
+
+    /// Work which can start at `now`, by priority. Nothing starts within a
+    /// frame of interactive work, see [`Self::resume_at`].
+    pub fn poll(&mut self, now: Instant) -> Vec<(Category, T)> {
+        let held_until = self
+            .interactive_at
+            .map(|interactive_at| interactive_at + FRAME);
+
+        if held_until.is_some_and(|held_until| now < held_until) {
+            // Interactive work since resuming was asked for holds it back
+            // longer, so it's asked for again
+            if self.resume_at < held_until {
+                self.resume_at = None;
+            }
+
+            return vec![];
+        }
+
+        self.resume_at = None;
+
+        let mut started = vec![];
+
+        for &category in Category::ALL {
+            let queue = &mut self.queues[category.index()];
+            let counter = &mut self.counters[category.index()];
+
+            while counter.running < self.concurrency {
+                let Some(work) = queue.pop_front() else {
+                    break;
+                };
+
+                counter.queued -= 1;
+                counter.running += 1;
+                started.push((category, work));
+            }
+        }
+
+        started
+    }
+
+    /// Records that work of `category` finished, letting more of it start
+    pub fn finished(&mut self, category: Category) {
+        let counter = &mut self.counters[category.index()];
+
+        counter.running = counter.running.saturating_sub(1);
+        counter.completed += 1;
+    }
+
+    /// When to poll again for work held back by interactive work, unless
+    /// it was already asked for
+    pub fn resume_at(&mut self) -> Option<Instant> {
+        let interactive_at = self.interactive_at?;
+
+        if self.resume_at.is_some()
+            || self.queues.iter().all(VecDeque::is_empty)
+        {
+            return None;
+        }
+
+        let resume_at = interactive_at + FRAME;
+        self.resume_at = Some(resume_at);
+
+        Some(resume_at)
+    }
+
+    pub fn counter(&self, category: Category) -> Counter {
+        self.counters[category.index()]
+    }
+}
+
+/// Requests sent to a server by background work, which keeps running until
+/// they're answered or time out rather than once they're sent
+#[derive(Debug)]
+pub struct Requests<K>(Vec<(Category, K)>);
+
+impl<K> Default for Requests<K> {
+    fn default() -> Self {
+        Self(vec![])
+    }
+}
+
+impl<K> Requests<K> {
+    /// Records that work of `category` sent `request`
+    pub fn sent(&mut self, category: Category, request: K) {
+        self.0.push((category, request));
+    }
+
+    /// Finishes the work of the requests which are no longer `pending`
+    pub fn finish<T>(
+        &mut self,
+        scheduler: &mut Scheduler<T>,
+        pending: impl Fn(&K) -> bool,
+    ) {
+        self.0.retain(|(category, request)| {
+            if pending(request) {
+                true
+            } else {
+                scheduler.finished(*category);
+                false
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(
+        scheduler: &mut Scheduler<&'static str>,
+        now: Instant,
+    ) -> Vec<&'static str> {
+        scheduler
+            .poll(now)
+            .into_iter()
+            .map(|(_, work)| work)
+            .collect()
+    }
+
+    #[test]
+    fn concurrency() {
+        let now = Instant::now();
+        let mut scheduler = Scheduler::new(2);
+
+        scheduler.submit(Category::Avatar, "avatar");
+        scheduler.submit(Category::Preview, "preview 1");
+        scheduler.submit(Category::Preview, "preview 2");
+        scheduler.submit(Category::Preview, "preview 3");
+        scheduler.submit(Category::Backlog, "backlog");
+
+        // By priority, up to 2 of each category
+        assert_eq!(
+            started(&mut scheduler, now),
+            ["backlog", "preview 1", "preview 2", "avatar"]
+        );
+        assert_eq!(
+            scheduler.counter(Category::Preview),
+            Counter {
+                queued: 1,
+                running: 2,
+                completed: 0
+            }
+        );
+        assert!(started(&mut scheduler, now).is_empty());
+
+        scheduler.finished(Category::Preview);
+
+        assert_eq!(started(&mut scheduler, now), ["preview 3"]);
+        assert_eq!(
+            scheduler.counter(Category::Preview),
+            Counter {
+                queued: 0,
+                running: 2,
+                completed: 1
+            }
+        );
+    }
+
+    #[test]
+    fn interactive() {
+        let now = Instant::now();
+        let mut scheduler = Scheduler::new(4);
+
+        assert_eq!(scheduler.resume_at(), None);
+
+        scheduler.interactive(now);
+        scheduler.submit(Category::Preview, "preview");
+
+        // Held back for a frame
+        assert!(started(&mut scheduler, now).is_empty());
+        assert!(started(&mut scheduler, now + FRAME / 2).is_empty());
+        assert_eq!(scheduler.resume_at(), Some(now + FRAME));
+        // Only asked for once
+        assert_eq!(scheduler.resume_at(), None);
+
+        assert_eq!(started(&mut scheduler, now + FRAME), ["preview"]);
+        assert_eq!(scheduler.resume_at(), None);
+
+        // More interactive work holds it back again
+        scheduler.interactive(now + FRAME * 2);
+        scheduler.submit(Category::Backlog, "backlog");
+
+        assert!(started(&mut scheduler, now + FRAME * 2).is_empty());
+        assert_eq!(scheduler.resume_at(), Some(now + FRAME * 3));
+        assert_eq!(started(&mut scheduler, now + FRAME * 3), ["backlog"]);
+    }
+
+    /// Polls `polls` times with fake tasks, each finishing before the next
+    /// poll while more backlog keeps coming in, returning what started on
+    /// each poll
+    fn run(
+        scheduler: &mut Scheduler<(Category, usize)>,
+        polls: usize,
+    ) -> Vec<Vec<(Category, usize)>> {
+        let now = Instant::now();
+
+        (0..polls)
+            .map(|poll| {
+                scheduler
+                    .submit(Category::Backlog, (Category::Backlog, 100 + poll));
+
+                let started = scheduler
+                    .poll(now)
+                    .into_iter()
+                    .map(|(_, task)| task)
+                    .collect::<Vec<_>>();
+
+                for &(category, _) in &started {
+                    scheduler.finished(category);
+                }
+
+                started
+            })
+            .collect()
+    }
+
+    #[test]
+    fn priority() {
+        let now = Instant::now();
+        let mut scheduler = Scheduler::new(1);
+
+        // Submitted from lowest priority to highest
+        scheduler.submit(Category::Avatar, "avatar");
+        scheduler.submit(Category::Preview, "preview");
+        scheduler.submit(Category::Backlog, "backlog");
+
+        assert_eq!(
+            scheduler.poll(now),
+            [
+                (Category::Backlog, "backlog"),
+                (Category::Preview, "preview"),
+                (Category::Avatar, "avatar"),
+            ]
+        );
+    }
+
+    #[test]
+    fn ties() {
+        let now = Instant::now();
+        let mut scheduler = Scheduler::new(2);
+
+        // Of the same category, in the order submitted
+        for work in ["preview 1", "preview 2", "preview 3", "preview 4"] {
+            scheduler.submit(Category::Preview, work);
+        }
+
+        assert_eq!(started(&mut scheduler, now), ["preview 1", "preview 2"]);
+
+        scheduler.finished(Category::Preview);
+        assert_eq!(started(&mut scheduler, now), ["preview 3"]);
+
+        scheduler.finished(Category::Preview);
+        scheduler.finished(Category::Preview);
+        assert_eq!(started(&mut scheduler, now), ["preview 4"]);
+    }
+
+    #[test]
+    fn starvation() {
+        let mut scheduler = Scheduler::new(1);
+
+        // Busy with backlog, which never runs out
+        for task in 0..100 {
+            scheduler.submit(Category::Backlog, (Category::Backlog, task));
+        }
+        scheduler.submit(Category::Avatar, (Category::Avatar, 0));
+        scheduler.submit(Category::Avatar, (Category::Avatar, 1));
+
+        // Lower priority work still runs alongside it, one at a time
+        assert_eq!(
+            run(&mut scheduler, 3),
+            [
+                vec![(Category::Backlog, 0), (Category::Avatar, 0)],
+                vec![(Category::Backlog, 1), (Category::Avatar, 1)],
+                vec![(Category::Backlog, 2)],
+            ]
+        );
+        assert_eq!(
+            scheduler.counter(Category::Avatar),
+            Counter {
+                queued: 0,
+                running: 0,
+                completed: 2
+            }
+        );
+        assert_eq!(scheduler.counter(Category::Backlog).queued, 100);
+    }
+
+    #[test]
+    fn interactive_again() {
+        let now = Instant::now();
+        let mut scheduler = Scheduler::new(4);
+
+        scheduler.interactive(now);
+        scheduler.submit(Category::Preview, "preview");
+
+        assert!(started(&mut scheduler, now).is_empty());
+        assert_eq!(scheduler.resume_at(), Some(now + FRAME));
+
+        // Interacting again within the frame, before it resumes
+        scheduler.interactive(now + FRAME / 2);
+
+        assert!(started(&mut scheduler, now + FRAME).is_empty());
+        assert_eq!(scheduler.resume_at(), Some(now + FRAME / 2 + FRAME));
+        assert_eq!(
+            started(&mut scheduler, now + FRAME / 2 + FRAME),
+            ["preview"]
+        );
+    }
+
+    #[test]
+    fn outstanding_requests() {
+        let now = Instant::now();
+        let mut scheduler = Scheduler::new(2);
+        let mut requests = Requests::default();
+
+        for target in ["#a", "#b", "#c"] {
+            scheduler.submit(Category::Backlog, target);
+        }
+
+        for (category, target) in scheduler.poll(now) {
+            requests.sent(category, target);
+        }
+
+        // Running while their requests aren't answered
+        requests.finish(&mut scheduler, |_| true);
+        assert!(started(&mut scheduler, now).is_empty());
+        assert_eq!(
+            scheduler.counter(Category::Backlog),
+            Counter {
+                queued: 1,
+                running: 2,
+                completed: 0
+            }
+        );
+
+        // Answered, or timed out
+        requests.finish(&mut scheduler, |&target| target != "#a");
+        assert_eq!(started(&mut scheduler, now), ["#c"]);
+        assert_eq!(
+            scheduler.counter(Category::Backlog),
+            Counter {
+                queued: 0,
+                running: 2,
+                completed: 1
+            }
+        );
+    }
+}
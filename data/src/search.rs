@@ -0,0 +1,314 @@
+//! Finding messages of a buffer by their text
+
+use fancy_regex::{Regex, RegexBuilder};
+use futures::{Stream, StreamExt, stream};
+
+use crate::config::Matching;
+use crate::{Buffer, Message, Server, buffer, fold, history};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pub text: String,
+    pub case_sensitive: bool,
+    pub is_regex: bool,
+}
+
+impl Query {
+    /// Matcher for the query, `None` if there's nothing to search for. Text
+    /// searched for without case sensitivity is folded when `matching` is
+    /// accent insensitive.
+    pub fn matcher(
+        &self,
+        matching: Matching,
+    ) -> Result<Option<Matcher>, Error> {
+        if self.text.is_empty() {
+            return Ok(None);
+        }
+
+        let matcher = if self.is_regex {
+            Matcher::Regex(
+                RegexBuilder::new(&self.text)
+                    .case_insensitive(!self.case_sensitive)
+                    .build()
+                    .map_err(|error| Error::InvalidRegex(error.to_string()))?,
+            )
+        } else if self.case_sensitive {
+            Matcher::Text(self.text.clone())
+        } else if matching.accent_insensitive {
+            Matcher::TextFolded(fold::fold(&self.text).into_owned())
+        } else {
+            Matcher::TextInsensitive(self.text.to_lowercase())
+        };
+
+        Ok(Some(matcher))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    Text(String),
+    TextInsensitive(String),
+    /// Folded by [`fold::fold`], so accents are ignored
+    TextFolded(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    pub fn is_match(&self, message: &Message) -> bool {
+        let text = message.text();
+
+        match self {
+            Matcher::Text(needle) => text.contains(needle.as_str()),
+            Matcher::TextInsensitive(needle) => {
+                text.to_lowercase().contains(needle.as_str())
+            }
+            Matcher::TextFolded(needle) => {
+                fold::fold(&text).contains(needle.as_str())
+            }
+            // Lookarounds can fail at runtime, which isn't a match
+            Matcher::Regex(regex) => regex.is_match(&text).unwrap_or(false),
+        }
+    }
+
+    /// Messages of `messages` which match, in the same order
+    pub fn matches<'a>(
+        &self,
+        messages: impl IntoIterator<Item = &'a Message>,
+    ) -> Vec<&'a Message> {
+        messages
+            .into_iter()
+            .filter(|message| self.is_match(message))
+            .collect()
+    }
+}
+
+/// Matches in the history of a buffer
+#[derive(Debug, Clone)]
+pub struct Found {
+    pub buffer: buffer::Upstream,
+    /// The newest matches, oldest first
+    pub messages: Vec<Message>,
+    /// # of matches, which can be more than were kept
+    pub total: usize,
+}
+
+/// Searches the histories on disk of buffers of `servers`, yielding the
+/// matches of each buffer as it's searched. Up to `limit` matches are kept for
+/// each buffer.
+pub fn history(
+    matcher: Matcher,
+    servers: Vec<Server>,
+    limit: usize,
+) -> impl Stream<Item = Found> {
+    stream::once(history::paths())
+        .flat_map(|paths| {
+            stream::iter(paths.unwrap_or_else(|error| {
+                log::warn!("failed to list history: {error}");
+
+                vec![]
+            }))
+        })
+        .filter_map(move |path| {
+            let matcher = matcher.clone();
+            let servers = servers.clone();
+
+            async move {
+                let (kind, messages) =
+                    history::read_with_kind(&path, &servers).await.ok()??;
+                let buffer = Buffer::from(kind).upstream()?.clone();
+
+                found(buffer, &matcher, &messages, limit)
+            }
+        })
+}
+
+/// Up to the `limit` newest matches among `messages` of `buffer`, if any
+fn found(
+    buffer: buffer::Upstream,
+    matcher: &Matcher,
+    messages: &[Message],
+    limit: usize,
+) -> Option<Found> {
+    let matches = matcher.matches(messages);
+    let total = matches.len();
+
+    (total > 0).then(|| Found {
+        buffer,
+        messages: matches[total.saturating_sub(limit)..]
+            .iter()
+            .map(|message| (*message).clone())
+            .collect(),
+        total,
+    })
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error("invalid regex: {0}")]
+    InvalidRegex(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::message::{Source, Target, plain};
+
+    fn message(text: &str) -> Message {
+        Message::test(
+            Utc::now(),
+            Target::Server {
+                source: Source::Server(None),
+            },
+            plain(text.to_string()),
+        )
+    }
+
+    fn matched(query: Query, texts: &[&str]) -> Vec<String> {
+        matched_with(query, Matching::default(), texts)
+    }
+
+    fn matched_with(
+        query: Query,
+        matching: Matching,
+        texts: &[&str],
+    ) -> Vec<String> {
+        let messages =
+            texts.iter().map(|text| message(text)).collect::<Vec<_>>();
+
+        query
+            .matcher(matching)
+            .unwrap()
+            .map(|matcher| {
+                matcher
+                    .matches(&messages)
+                    .into_iter()
+                    .map(Message::text)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn search() {
+        let texts = ["Hello world", "hello there", "goodbye"];
+
+        assert_eq!(
+            matched(
+                Query {
+                    text: "hello".to_string(),
+                    ..Query::default()
+                },
+                &texts
+            ),
+            vec!["Hello world", "hello there"]
+        );
+        assert_eq!(
+            matched(
+                Query {
+                    text: "hello".to_string(),
+                    case_sensitive: true,
+                    ..Query::default()
+                },
+                &texts
+            ),
+            vec!["hello there"]
+        );
+        assert_eq!(
+            matched(
+                Query {
+                    text: "^(hello|good)".to_string(),
+                    is_regex: true,
+                    ..Query::default()
+                },
+                &texts
+            ),
+            vec!["Hello world", "hello there", "goodbye"]
+        );
+        assert!(
+            matched(
+                Query {
+                    text: "world$".to_string(),
+                    ..Query::default()
+                },
+                &texts
+            )
+            .is_empty()
+        );
+        assert!(matched(Query::default(), &texts).is_empty());
+        assert!(
+            Query {
+                text: "(".to_string(),
+                is_regex: true,
+                ..Query::default()
+            }
+            .matcher(Matching::default())
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn search_accent_insensitive() {
+        let texts = ["Über alles", "un café", "uber", "cafe\u{301} noir"];
+        let matching = Matching {
+            accent_insensitive: true,
+        };
+        let query = |text: &str| Query {
+            text: text.to_string(),
+            ..Query::default()
+        };
+
+        assert_eq!(
+            matched_with(query("uber"), matching, &texts),
+            vec!["Über alles", "uber"]
+        );
+        assert_eq!(
+            matched_with(query("CAFÉ"), matching, &texts),
+            vec!["un café", "cafe\u{301} noir"]
+        );
+        assert_eq!(matched(query("uber"), &texts), vec!["uber"]);
+        // Only text searched for regardless of case is folded
+        assert_eq!(
+            matched_with(
+                Query {
+                    case_sensitive: true,
+                    ..query("uber")
+                },
+                matching,
+                &texts
+            ),
+            vec!["uber"]
+        );
+    }
+
+    #[test]
+    fn found_up_to_limit() {
+        let buffer = buffer::Upstream::Server(Server::from("libera"));
+        let matcher = Query {
+            text: "match".to_string(),
+            ..Query::default()
+        }
+        .matcher(Matching::default())
+        .unwrap()
+        .unwrap();
+        let messages = (0..10)
+            .map(|i| message(&format!("match {i}")))
+            .chain([message("other")])
+            .collect::<Vec<_>>();
+
+        let kept = found(buffer.clone(), &matcher, &messages, 3).unwrap();
+        assert_eq!(kept.total, 10);
+        assert_eq!(
+            kept.messages.iter().map(Message::text).collect::<Vec<_>>(),
+            vec!["match 7", "match 8", "match 9"]
+        );
+
+        // Fewer matches than the limit
+        let kept = found(buffer.clone(), &matcher, &messages[8..], 3).unwrap();
+        assert_eq!(kept.total, 2);
+        assert_eq!(kept.messages.len(), 2);
+
+        assert!(found(buffer, &matcher, &messages[10..], 3).is_none());
+    }
+}
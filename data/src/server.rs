@@ -1,3 +1,4 @@
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::{cmp, fmt, str};
 
@@ -11,7 +12,7 @@ use tokio::process::Command;
 
 use crate::config;
 use crate::config::Error;
-use crate::config::server::Sasl;
+use crate::config::server::{Connection, Sasl};
 
 pub type Handle = Sender<proto::Message>;
 
@@ -99,12 +100,31 @@ async fn read_from_command(pass_command: &str) -> Result<String, Error> {
     }
 }
 
+/// The socket of a server connected to over a Unix domain socket, with a
+/// leading `~` expanded to the home directory
+fn unix_socket_path(path: Option<&Path>) -> Result<PathBuf, Error> {
+    if cfg!(not(unix)) {
+        return Err(Error::UnixSocketUnsupported);
+    }
+
+    let path = path.ok_or(Error::MissingUnixSocketPath)?;
+
+    match (path.strip_prefix("~"), dirs_next::home_dir()) {
+        (Ok(relative), Some(home)) => Ok(home.join(relative)),
+        _ => Ok(path.to_path_buf()),
+    }
+}
+
 impl Map {
     pub async fn new(
         iter: impl IntoIterator<Item = (Server, config::Server)>,
     ) -> Result<Self, Error> {
         let inner = stream::iter(iter)
             .then(|(server, mut config)| async move {
+                if config.connection == Connection::Unix {
+                    config.path =
+                        Some(unix_socket_path(config.path.as_deref())?);
+                }
                 if let Some(pass_file) = &config.password_file {
                     if config.password.is_some()
                         || config.password_command.is_some()
@@ -19,6 +19,10 @@ impl Shortcut {
     pub fn execute(&self, key_bind: &KeyBind) -> Option<Command> {
         (self.key_bind == *key_bind).then_some(self.command)
     }
+
+    pub fn key_bind(&self) -> &KeyBind {
+        &self.key_bind
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -30,11 +34,17 @@ pub enum Command {
     CloseBuffer,
     MaximizeBuffer,
     RestoreBuffer,
+    SplitHorizontal,
+    SplitVertical,
+    MoveBufferUp,
+    MoveBufferDown,
     CycleNextBuffer,
     CyclePreviousBuffer,
     LeaveBuffer,
     ToggleNicklist,
+    ToggleNicklistFilter,
     ToggleTopic,
+    TogglePin,
     ToggleSidebar,
     ToggleFullscreen,
     CommandBar,
@@ -44,12 +54,20 @@ pub enum Command {
     ThemeEditor,
     Highlights,
     QuitApplication,
+    ToggleDebugOverlay,
     ScrollUpPage,
     ScrollDownPage,
     ScrollToTop,
     ScrollToBottom,
+    ScrollToFocusMarker,
+    SearchBuffer,
+    SearchHistory,
+    QuickSwitcher,
     CycleNextUnreadBuffer,
     CyclePreviousUnreadBuffer,
+    CycleRecentBuffer,
+    CyclePreviousRecentBuffer,
+    FocusBuffer(usize),
     MarkAsRead,
 }
 
@@ -138,12 +156,21 @@ impl KeyBind {
     default!(close_buffer, "w", COMMAND);
     default!(maximize_buffer, ArrowUp, COMMAND | SHIFT);
     default!(restore_buffer, ArrowDown, COMMAND | SHIFT);
+    default!(split_horizontal, "h", ALT | SHIFT);
+    default!(split_vertical, "v", ALT | SHIFT);
+    // Command + shift + up / down maximize and restore buffers
+    default!(move_buffer_up, ArrowUp, ALT | SHIFT);
+    default!(move_buffer_down, ArrowDown, ALT | SHIFT);
     default!(cycle_next_buffer, Tab, CTRL);
     default!(cycle_previous_buffer, Tab, CTRL | SHIFT);
+    default!(cycle_recent_buffer, "e", COMMAND);
+    default!(cycle_previous_recent_buffer, "e", COMMAND | SHIFT);
     default!(leave_buffer, "w", COMMAND | SHIFT);
     default!(toggle_nick_list, "m", COMMAND | ALT);
+    default!(toggle_nick_list_filter, "f", COMMAND | ALT);
     default!(toggle_sidebar, "b", COMMAND | ALT);
     default!(toggle_topic, "t", COMMAND | ALT);
+    default!(toggle_pin, "p", COMMAND | ALT);
     #[cfg(target_os = "macos")]
     default!(toggle_fullscreen, "f", COMMAND | CTRL);
     #[cfg(not(target_os = "macos"))]
@@ -159,10 +186,25 @@ impl KeyBind {
     // Don't use HOME / END since text input is always focused
     default!(scroll_to_top, ArrowUp, COMMAND);
     default!(scroll_to_bottom, ArrowDown, COMMAND);
-    default!(cycle_next_unread_buffer, "`", CTRL);
-    default!(cycle_previous_unread_buffer, "`", CTRL | SHIFT);
+    // Command + j is file transfers
+    default!(scroll_to_focus_marker, "j", COMMAND | SHIFT);
+    default!(search_buffer, "f", COMMAND);
+    default!(search_history, "f", COMMAND | SHIFT);
+    default!(quick_switcher, "p", COMMAND);
+    default!(cycle_next_unread_buffer, "a", ALT);
+    default!(cycle_previous_unread_buffer, "a", ALT | SHIFT);
     // Command + m is minimize in macOS
     default!(mark_as_read, "m", COMMAND | SHIFT);
+    default!(focus_buffer_1, "1", ALT);
+    default!(focus_buffer_2, "2", ALT);
+    default!(focus_buffer_3, "3", ALT);
+    default!(focus_buffer_4, "4", ALT);
+    default!(focus_buffer_5, "5", ALT);
+    default!(focus_buffer_6, "6", ALT);
+    default!(focus_buffer_7, "7", ALT);
+    default!(focus_buffer_8, "8", ALT);
+    default!(focus_buffer_9, "9", ALT);
+    default!(focus_buffer_10, "0", ALT);
 
     pub fn is_pressed(
         &self,
@@ -171,6 +213,14 @@ impl KeyBind {
     ) -> bool {
         self.key_code == key_code.into() && self.modifiers == modifiers.into()
     }
+
+    pub fn key(&self) -> &keyboard::Key {
+        &self.key_code.0
+    }
+
+    pub fn modifiers(&self) -> keyboard::Modifiers {
+        self.modifiers.0
+    }
 }
 
 impl From<(keyboard::Key, keyboard::Modifiers)> for KeyBind {
@@ -193,7 +243,7 @@ impl<'de> Deserialize<'de> for KeyBind {
 
         let string = String::deserialize(deserializer)?;
 
-        let parts = string.trim().split('+').collect::<Vec<_>>();
+        let parts = string.split('+').map(str::trim).collect::<Vec<_>>();
 
         let (key_code, modifiers) = match parts.len() {
             0 => return Err(de::Error::custom("empty keybind")),
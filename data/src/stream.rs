@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, OnceLock};
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
@@ -7,15 +7,22 @@ use futures::never::Never;
 use futures::{FutureExt, SinkExt, StreamExt, future, stream};
 use irc::proto::{self, Command, command};
 use irc::{Connection, codec, connection};
+use tokio::sync::{Semaphore, SemaphorePermit};
 use tokio::time::{self, Instant, Interval};
 
+use self::disconnect::Reconnect;
 use crate::client::Client;
 use crate::server::Server;
 use crate::time::Posix;
 use crate::{config, message, server};
 
+pub mod disconnect;
+
 pub type Result<T = Update, E = Error> = std::result::Result<T, E>;
 
+static STARTED_AT: LazyLock<Instant> = LazyLock::new(Instant::now);
+static STARTUP_LIMIT: OnceLock<Semaphore> = OnceLock::new();
+
 #[derive(Debug)]
 pub enum Error {
     Connection(connection::Error),
@@ -33,6 +40,7 @@ pub enum Update {
         server: Server,
         is_initial: bool,
         error: Option<String>,
+        reason: Option<disconnect::Reason>,
         sent_time: DateTime<Utc>,
     },
     ConnectionFailed {
@@ -47,12 +55,16 @@ pub enum Update {
 enum State {
     Disconnected {
         last_retry: Option<Instant>,
+        delay: Duration,
     },
     Connected {
         stream: Stream,
         batch: Batch,
         ping_time: Interval,
         ping_timeout: Option<Interval>,
+        /// The server announced it's shutting down, so losing the
+        /// connection is put down to that
+        shutdown_announced: bool,
     },
     Quit,
 }
@@ -70,16 +82,53 @@ struct Stream {
     receiver: mpsc::Receiver<proto::Message>,
 }
 
+/// Paces the initial connection of a server connected on startup, so
+/// servers are connected in order rather than all at once
+#[derive(Debug, Clone, Copy)]
+pub struct Startup {
+    position: usize,
+    delay: Duration,
+    concurrency: usize,
+}
+
+impl Startup {
+    pub fn new(position: usize, config: &config::Startup) -> Self {
+        LazyLock::force(&STARTED_AT);
+
+        Self {
+            position,
+            delay: Duration::from_millis(config.delay_ms),
+            concurrency: config.concurrency.max(1),
+        }
+    }
+
+    async fn wait(self) -> Option<SemaphorePermit<'static>> {
+        let position = u32::try_from(self.position).unwrap_or(u32::MAX);
+
+        time::sleep_until(*STARTED_AT + self.delay.saturating_mul(position))
+            .await;
+
+        // WARN: `concurrency` changes aren't picked up until app is relaunched
+        STARTUP_LIMIT
+            .get_or_init(|| Semaphore::new(self.concurrency))
+            .acquire()
+            .await
+            .ok()
+    }
+}
+
 pub fn run(
     server: server::Entry,
     proxy: Option<config::Proxy>,
+    startup: Option<Startup>,
 ) -> impl futures::Stream<Item = Update> {
     let (sender, receiver) = mpsc::unbounded();
 
     // Spawn to unblock backend from iced stream which has backpressure
-    let runner =
-        stream::once(async { tokio::spawn(_run(server, proxy, sender)).await })
-            .map(|_| unreachable!());
+    let runner = stream::once(async move {
+        tokio::spawn(_run(server, proxy, startup, sender)).await
+    })
+    .map(|_| unreachable!());
 
     stream::select(receiver, runner)
 }
@@ -87,6 +136,7 @@ pub fn run(
 async fn _run(
     server: server::Entry,
     proxy: Option<config::Proxy>,
+    mut startup: Option<Startup>,
     sender: mpsc::UnboundedSender<Update>,
 ) -> Never {
     let server::Entry { server, config } = server;
@@ -94,31 +144,50 @@ async fn _run(
     let reconnect_delay = Duration::from_secs(config.reconnect_delay);
 
     let mut is_initial = true;
-    let mut state = State::Disconnected { last_retry: None };
+    let mut state = State::Disconnected {
+        last_retry: None,
+        delay: reconnect_delay,
+    };
 
     // Notify app of initial disconnected state
     let _ = sender.unbounded_send(Update::Disconnected {
         server: server.clone(),
         is_initial,
         error: None,
+        reason: None,
         sent_time: Utc::now(),
     });
 
     loop {
         match &mut state {
-            State::Disconnected { last_retry } => {
+            State::Disconnected { last_retry, delay } => {
                 if let Some(last_retry) = last_retry.as_ref() {
-                    let remaining =
-                        reconnect_delay.saturating_sub(last_retry.elapsed());
+                    let remaining = delay.saturating_sub(last_retry.elapsed());
 
                     if !remaining.is_zero() {
                         time::sleep(remaining).await;
                     }
                 }
 
+                // The frontend replaced this stream, e.g. to retry now
+                if sender.is_closed() {
+                    state = State::Quit;
+                    continue;
+                }
+
+                // Only the initial connection attempt is paced, reconnects
+                // are not held back by other servers starting up
+                let _permit = match startup.take() {
+                    Some(startup) => startup.wait().await,
+                    None => None,
+                };
+
                 match connect(server.clone(), config.clone(), proxy.clone())
                     .await
                 {
+                    Ok(_) if sender.is_closed() => {
+                        state = State::Quit;
+                    }
                     Ok((stream, client)) => {
                         log::info!("[{server}] connected");
 
@@ -136,6 +205,7 @@ async fn _run(
                             batch: Batch::new(),
                             ping_timeout: None,
                             ping_time: ping_time_interval(config.ping_time),
+                            shutdown_announced: false,
                         };
                     }
                     Err(e) => {
@@ -157,6 +227,7 @@ async fn _run(
                             });
 
                         *last_retry = Some(Instant::now());
+                        *delay = reconnect_delay;
                     }
                 }
             }
@@ -165,6 +236,7 @@ async fn _run(
                 batch,
                 ping_time,
                 ping_timeout,
+                shutdown_announced,
             } => {
                 let input = {
                     let mut select = stream::select_all([
@@ -207,17 +279,31 @@ async fn _run(
                             *ping_timeout = None;
                         }
                         proto::Command::ERROR(error) => {
+                            let reason = disconnect::Reason::classify(&error)
+                                .or((*shutdown_announced)
+                                    .then_some(disconnect::Reason::Shutdown));
+
                             log::warn!("[{server}] disconnected: {error}");
                             let _ =
                                 sender.unbounded_send(Update::Disconnected {
                                     server: server.clone(),
                                     is_initial,
                                     error: Some(error),
+                                    reason,
                                     sent_time: Utc::now(),
                                 });
-                            state = State::Disconnected {
-                                last_retry: Some(Instant::now()),
-                            };
+                            state = disconnected(reason, reconnect_delay);
+                        }
+                        proto::Command::NOTICE(_, ref text)
+                            if matches!(
+                                message.source,
+                                Some(proto::Source::Server(_))
+                            ) && disconnect::Reason::classify(text)
+                                == Some(disconnect::Reason::Shutdown) =>
+                        {
+                            *shutdown_announced = true;
+
+                            batch.messages.push(message.into());
                         }
                         _ => {
                             batch.messages.push(message.into());
@@ -227,16 +313,18 @@ async fn _run(
                         log::warn!("message decoding failed: {e}");
                     }
                     Input::IrcMessage(Err(e)) => {
+                        let reason = (*shutdown_announced)
+                            .then_some(disconnect::Reason::Shutdown);
+
                         log::warn!("[{server}] disconnected: {e}");
                         let _ = sender.unbounded_send(Update::Disconnected {
                             server: server.clone(),
                             is_initial,
                             error: Some(e.to_string()),
+                            reason,
                             sent_time: Utc::now(),
                         });
-                        state = State::Disconnected {
-                            last_retry: Some(Instant::now()),
-                        };
+                        state = disconnected(reason, reconnect_delay);
                     }
                     Input::Batch(messages) => {
                         let _ = sender.unbounded_send(
@@ -284,11 +372,13 @@ async fn _run(
                             server: server.clone(),
                             is_initial,
                             error: Some("ping timeout".into()),
+                            reason: Some(disconnect::Reason::PingTimeout),
                             sent_time: Utc::now(),
                         });
-                        state = State::Disconnected {
-                            last_retry: Some(Instant::now()),
-                        };
+                        state = disconnected(
+                            Some(disconnect::Reason::PingTimeout),
+                            reconnect_delay,
+                        );
                     }
                 }
             }
@@ -300,6 +390,21 @@ async fn _run(
     }
 }
 
+/// Waits to reconnect as called for by the `reason` for being disconnected,
+/// or until retried by the frontend
+fn disconnected(
+    reason: Option<disconnect::Reason>,
+    reconnect_delay: Duration,
+) -> State {
+    match disconnect::reconnect(reason, reconnect_delay) {
+        Reconnect::After(delay) => State::Disconnected {
+            last_retry: Some(Instant::now()),
+            delay,
+        },
+        Reconnect::Never => State::Quit,
+    }
+}
+
 async fn connect(
     server: Server,
     config: Arc<config::Server>,
@@ -0,0 +1,232 @@
+//! Why a server closed the connection, from the text it sent along
+
+use std::fmt;
+use std::time::Duration;
+
+/// Reconnecting after being throttled waits at least this long
+const THROTTLED_DELAY: Duration = Duration::from_secs(60);
+
+/// Text (lowercase) of the reasons servers give, matched in order. Bans are
+/// only matched by the text of server bans, since a mention of a channel ban
+/// in a kill or quit message mustn't stop reconnecting
+const PATTERNS: &[(Reason, &[&str])] = &[
+    (
+        Reason::Banned,
+        &[
+            "k-lined",
+            "g-lined",
+            "z-lined",
+            "d-lined",
+            "k-line active",
+            "g-line active",
+            "kill line active",
+            // ERR_YOUREBANNEDCREEP
+            "you are banned from this server",
+            "you're banned from this server",
+            // UnrealIRCd's text for server bans
+            "user has been banned from",
+        ],
+    ),
+    (
+        Reason::Throttled,
+        &[
+            "throttled",
+            "reconnect too fast",
+            "connect too fast",
+            "connecting too fast",
+            "too many connections",
+            "too many host connections",
+            "too many user connections",
+        ],
+    ),
+    (
+        Reason::PingTimeout,
+        &[
+            "ping timeout",
+            "registration timeout",
+            "registration timed out",
+        ],
+    ),
+    (
+        Reason::Shutdown,
+        &[
+            "server shutdown",
+            "server shutting down",
+            "server is shutting down",
+            "server restarting",
+            "server is restarting",
+            "restarting server",
+        ],
+    ),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Reason {
+    /// Banned from the server, e.g. by a k-line
+    Banned,
+    /// Connecting too often
+    Throttled,
+    PingTimeout,
+    /// The server shut down or is restarting
+    Shutdown,
+}
+
+impl Reason {
+    /// Reason given in `text`, `None` if it's none of the known ones
+    pub fn classify(text: &str) -> Option<Self> {
+        let text = text.to_lowercase();
+
+        PATTERNS.iter().find_map(|(reason, patterns)| {
+            patterns
+                .iter()
+                .any(|pattern| text.contains(pattern))
+                .then_some(*reason)
+        })
+    }
+
+    /// How to reconnect after being disconnected for this reason, given the
+    /// configured delay
+    pub fn reconnect(self, delay: Duration) -> Reconnect {
+        match self {
+            // Reconnecting would only be refused again
+            Reason::Banned => Reconnect::Never,
+            Reason::Throttled => {
+                Reconnect::After(delay.saturating_mul(4).max(THROTTLED_DELAY))
+            }
+            Reason::PingTimeout | Reason::Shutdown => Reconnect::After(delay),
+        }
+    }
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::Banned => write!(f, "banned from the server"),
+            Reason::Throttled => write!(f, "reconnecting too fast"),
+            Reason::PingTimeout => write!(f, "ping timeout"),
+            Reason::Shutdown => write!(f, "server shutting down"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reconnect {
+    After(Duration),
+    /// Until retried by hand
+    Never,
+}
+
+/// How to reconnect after being disconnected for `reason`, if there is one
+pub fn reconnect(reason: Option<Reason>, delay: Duration) -> Reconnect {
+    reason.map_or(Reconnect::After(delay), |reason| reason.reconnect(delay))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify() {
+        let tests = [
+            // Solanum / Charybdis
+            ("Closing Link: 192.0.2.1 (K-Lined)", Some(Reason::Banned)),
+            (
+                "Closing Link: 192.0.2.1 (You are banned from this server- spam)",
+                Some(Reason::Banned),
+            ),
+            ("Trying to reconnect too fast.", Some(Reason::Throttled)),
+            (
+                "Closing Link: 192.0.2.1 (Ping timeout: 240 seconds)",
+                Some(Reason::PingTimeout),
+            ),
+            (
+                "Closing Link: 192.0.2.1 (Server shutdown)",
+                Some(Reason::Shutdown),
+            ),
+            // InspIRCd
+            (
+                "Closing link: (halloy@192.0.2.1) [G-Lined: spam]",
+                Some(Reason::Banned),
+            ),
+            (
+                "Closing link: (halloy@192.0.2.1) [Z-Lined: Tor exit node]",
+                Some(Reason::Banned),
+            ),
+            (
+                "Closing link: (halloy@192.0.2.1) [Ping timeout: 121 seconds]",
+                Some(Reason::PingTimeout),
+            ),
+            (
+                "Closing link: (halloy@192.0.2.1) [Registration timeout]",
+                Some(Reason::PingTimeout),
+            ),
+            (
+                "Closing link: (halloy@192.0.2.1) [Server shutting down]",
+                Some(Reason::Shutdown),
+            ),
+            // UnrealIRCd
+            (
+                "Closing Link: halloy[192.0.2.1] (User has been banned from irc.example.org (spam))",
+                Some(Reason::Banned),
+            ),
+            (
+                "Throttled: Reconnecting too fast - Email admin@example.org for more information.",
+                Some(Reason::Throttled),
+            ),
+            (
+                "Closing Link: halloy[192.0.2.1] (Server is shutting down)",
+                Some(Reason::Shutdown),
+            ),
+            // ircd-hybrid
+            (
+                "Closing Link: 192.0.2.1 (Too many host connections (global))",
+                Some(Reason::Throttled),
+            ),
+            (
+                "Closing Link: 192.0.2.1 (Server Restarting)",
+                Some(Reason::Shutdown),
+            ),
+            // Reasons which aren't known
+            ("Closing Link: 192.0.2.1 (Quit: bye)", None),
+            ("Closing Link: 192.0.2.1 (Killed (oper (reason)))", None),
+            // Mentions of bans which aren't from the server
+            ("Closing Link: 192.0.2.1 (Banned)", None),
+            (
+                "Closing Link: 192.0.2.1 (Quit: got banned from #halloy)",
+                None,
+            ),
+            (
+                "Closing Link: 192.0.2.1 (Killed (oper (ban evasion, banned in #halloy)))",
+                None,
+            ),
+        ];
+
+        for (text, reason) in tests {
+            assert_eq!(Reason::classify(text), reason, "{text}");
+        }
+    }
+
+    #[test]
+    fn policy() {
+        let delay = Duration::from_secs(10);
+
+        assert_eq!(reconnect(None, delay), Reconnect::After(delay));
+        assert_eq!(
+            reconnect(Some(Reason::PingTimeout), delay),
+            Reconnect::After(delay)
+        );
+        assert_eq!(
+            reconnect(Some(Reason::Shutdown), delay),
+            Reconnect::After(delay)
+        );
+        assert_eq!(reconnect(Some(Reason::Banned), delay), Reconnect::Never);
+        assert_eq!(
+            reconnect(Some(Reason::Throttled), delay),
+            Reconnect::After(Duration::from_secs(60))
+        );
+        assert_eq!(
+            reconnect(Some(Reason::Throttled), Duration::from_secs(30)),
+            Reconnect::After(Duration::from_secs(120))
+        );
+    }
+}
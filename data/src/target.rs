@@ -57,6 +57,22 @@ impl Target {
             }))
         }
     }
+
+    /// Like [`Target::parse`], but rejects a target which is neither a
+    /// channel nor a valid nickname
+    pub fn try_parse(
+        target: &str,
+        chantypes: &[char],
+        statusmsg: &[char],
+        casemapping: isupport::CaseMap,
+    ) -> Result<Self, ParseError> {
+        match Target::parse(target, chantypes, statusmsg, casemapping) {
+            Target::Query(_) if !is_valid_nick(target) => {
+                Err(ParseError::InvalidTarget(target.to_string()))
+            }
+            target => Ok(target),
+        }
+    }
 }
 
 impl PartialEq for Target {
@@ -258,8 +274,8 @@ impl Query {
         statusmsg: &[char],
         casemapping: isupport::CaseMap,
     ) -> Result<Self, ParseError> {
-        if let Some((_, _)) =
-            proto::parse_channel_from_target(target, chantypes, statusmsg)
+        if proto::parse_channel_from_target(target, chantypes, statusmsg)
+            .is_some()
         {
             Err(ParseError::InvalidQuery(target.to_string()))
         } else {
@@ -307,10 +323,111 @@ impl fmt::Display for Query {
     }
 }
 
+/// Whether `nick` can be a nickname. Servers differ in which characters they
+/// allow, so only those which can't be part of one in any are rejected.
+fn is_valid_nick(nick: &str) -> bool {
+    !nick.is_empty()
+        && !nick.starts_with(':')
+        && !nick
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, ',' | '!' | '@' | '\0'))
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ParseError {
     #[error("unable to parse channel from {0}")]
     InvalidChannel(String),
     #[error("unable to parse query from {0}")]
     InvalidQuery(String),
+    #[error("{0} is not a valid channel or nickname")]
+    InvalidTarget(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+    use crate::buffer::Upstream;
+
+    const CHANTYPES: &[char] = &['#', '&'];
+    const STATUSMSG: &[char] = &['@', '+'];
+
+    fn target(target: &str, casemapping: isupport::CaseMap) -> Target {
+        Target::try_parse(target, CHANTYPES, STATUSMSG, casemapping).unwrap()
+    }
+
+    #[test]
+    fn casemapping() {
+        let casemapping = isupport::CaseMap::RFC1459;
+
+        assert_eq!(target("#Foo", casemapping), target("#foo", casemapping));
+        assert_eq!(
+            target("#Foo[]", casemapping),
+            target("#foo{}", casemapping)
+        );
+        assert_eq!(target("Dan", casemapping), target("dAN", casemapping));
+        assert_ne!(target("#dan", casemapping), target("dan", casemapping));
+        assert_ne!(
+            target("#Foo[]", isupport::CaseMap::ASCII),
+            target("#foo{}", isupport::CaseMap::ASCII)
+        );
+
+        // Differently cased channels are the same buffer, keeping how
+        // they were first written
+        let buffers = ["#Foo", "#foo", "#FOO"]
+            .into_iter()
+            .map(|channel| {
+                Upstream::Channel(
+                    "libera".into(),
+                    Channel::parse(channel, CHANTYPES, STATUSMSG, casemapping)
+                        .unwrap(),
+                )
+            })
+            .collect::<HashSet<_>>();
+        assert_eq!(buffers.len(), 1);
+        assert_eq!(target("#Foo", casemapping).to_string(), "#Foo");
+    }
+
+    #[test]
+    fn invalid() {
+        let casemapping = isupport::CaseMap::default();
+
+        for invalid in ["", "dan,bob", "dan!d@host", "dan bob", ":dan"] {
+            assert!(
+                matches!(
+                    Target::try_parse(
+                        invalid,
+                        CHANTYPES,
+                        STATUSMSG,
+                        casemapping
+                    ),
+                    Err(ParseError::InvalidTarget(_))
+                ),
+                "{invalid:?}"
+            );
+        }
+
+        // Nicknames from servers aren't validated, since they can allow
+        // characters users can't type in a target
+        for nick in ["dan bob", "dan!d@host", "matrix,bridge"] {
+            assert!(
+                Query::parse(nick, CHANTYPES, STATUSMSG, casemapping).is_ok(),
+                "{nick:?}"
+            );
+        }
+
+        assert!(matches!(
+            target("@#halloy", casemapping),
+            Target::Channel(channel) if channel.prefixes() == ['@']
+        ));
+        assert!(matches!(target("*status", casemapping), Target::Query(_)));
+        assert!(
+            Channel::parse("halloy", CHANTYPES, STATUSMSG, casemapping)
+                .is_err()
+        );
+        assert!(
+            Query::parse("#halloy", CHANTYPES, STATUSMSG, casemapping).is_err()
+        );
+    }
 }
@@ -1,6 +1,7 @@
+use std::fmt;
 use std::time::SystemTime;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Local, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(
@@ -42,3 +43,99 @@ impl Posix {
         DateTime::from_timestamp(seconds, nanos)
     }
 }
+
+/// Zone of the timestamps in text copied or quoted out of a buffer. It's
+/// written after them, since the text can be read in another zone
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Zone {
+    Local,
+    Utc,
+}
+
+impl Zone {
+    /// Formats `date_time` in the zone with the strftime `format`, followed
+    /// by the zone unless `format` already has it
+    pub fn format(self, date_time: &DateTime<Utc>, format: &str) -> String {
+        match self {
+            Zone::Local => format_with_zone(
+                &date_time.with_timezone(&Local),
+                format,
+                "%:z",
+            ),
+            Zone::Utc => format_with_zone(date_time, format, "UTC"),
+        }
+    }
+}
+
+fn format_with_zone<Tz: TimeZone>(
+    date_time: &DateTime<Tz>,
+    format: &str,
+    zone: &str,
+) -> String
+where
+    Tz::Offset: fmt::Display,
+{
+    if has_zone(format) {
+        date_time.format(format).to_string()
+    } else {
+        date_time.format(&format!("{format} {zone}")).to_string()
+    }
+}
+
+/// Whether the strftime `format` has a zone name or offset, e.g. `%Z` or
+/// `%:z`
+fn has_zone(format: &str) -> bool {
+    let mut chars = format.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+
+        // Skips the padding and colon flags before the specifier
+        if let Some('z' | 'Z') =
+            chars.find(|c| !matches!(c, '-' | '_' | '0' | ':' | '#'))
+        {
+            return true;
+        }
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::FixedOffset;
+
+    use super::*;
+
+    #[test]
+    fn zone_suffix() {
+        let date_time = DateTime::from_timestamp(45_240, 0).unwrap();
+
+        assert_eq!(Zone::Utc.format(&date_time, "%H:%M"), "12:34 UTC");
+        // Not repeated when the format has it
+        assert_eq!(Zone::Utc.format(&date_time, "%H:%M %Z"), "12:34 UTC");
+        assert_eq!(Zone::Utc.format(&date_time, "%R%z"), "12:34+0000");
+
+        let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(
+            format_with_zone(&date_time.with_timezone(&offset), "%R", "%:z"),
+            "14:34 +02:00"
+        );
+        assert_eq!(
+            format_with_zone(
+                &date_time.with_timezone(&offset),
+                "%R %::z",
+                "%:z"
+            ),
+            "14:34 +02:00:00"
+        );
+
+        // A literal percent sign isn't a specifier
+        assert!(!has_zone("%%z"));
+        assert!(has_zone("%H:%M %#z"));
+        assert!(!has_zone("%H:%M"));
+    }
+}
@@ -0,0 +1,200 @@
+//! Translations of messages through the configured command or service, kept
+//! for the session only and never logged
+use std::collections::HashMap;
+use std::io;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::time;
+use url::Url;
+
+use crate::{config, http, message};
+
+/// How long a translation may take
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+pub type Collection = HashMap<message::Hash, State>;
+
+#[derive(Debug)]
+pub enum State {
+    Translating,
+    Translated(String),
+    Failed(Error),
+}
+
+/// Translates `text` into the configured language
+pub async fn translate(
+    text: String,
+    config: config::Translation,
+) -> Result<String, Error> {
+    let translation = if let Some(command) = &config.command {
+        run(&command_line(command, &config.language), &text).await?
+    } else if let Some(url) = config.url.clone() {
+        request(url, &config, &text).await?
+    } else {
+        return Err(Error::Disabled);
+    };
+
+    let translation = translation.trim();
+
+    if translation.is_empty() {
+        Err(Error::Empty)
+    } else {
+        Ok(translation.to_string())
+    }
+}
+
+fn command_line(command: &str, language: &str) -> String {
+    command.replace("{language}", language)
+}
+
+/// Runs `command_line` in the shell with `text` on stdin, returning what it
+/// printed
+async fn run(command_line: &str, text: &str) -> Result<String, Error> {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+
+    let mut child = command
+        .arg(command_line)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    // Closed once written, so the command knows the message ended. Commands
+    // which don't read it may have exited already.
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(text.as_bytes()).await;
+    }
+
+    let output = time::timeout(TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| Error::Timeout)??;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(Error::Command(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// Asks the LibreTranslate compatible service at `url`
+async fn request(
+    url: Url,
+    config: &config::Translation,
+    text: &str,
+) -> Result<String, Error> {
+    #[derive(Serialize)]
+    struct Request<'a> {
+        q: &'a str,
+        source: &'a str,
+        target: &'a str,
+        format: &'a str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        api_key: Option<&'a str>,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Response {
+        translated_text: String,
+    }
+
+    let request = http::Request::post_json(
+        url,
+        &Request {
+            q: text,
+            source: "auto",
+            target: &config.language,
+            format: "text",
+            api_key: config.api_key.as_deref(),
+        },
+    )?
+    .timeout(TIMEOUT);
+
+    let response: Response = http::send(request).await?.json().await?;
+
+    Ok(response.translated_text)
+}
+
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum Error {
+    #[error("translation isn't configured")]
+    Disabled,
+    #[error("command failed: {0}")]
+    Command(String),
+    #[error("timed out")]
+    Timeout,
+    #[error("nothing was translated")]
+    Empty,
+    #[error(transparent)]
+    Http(#[from] http::Error),
+    #[error("io error: {0}")]
+    Io(Arc<io::Error>),
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(Arc::new(error))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(command: &str) -> config::Translation {
+        config::Translation {
+            language: "fr".to_string(),
+            command: Some(command.to_string()),
+            ..config::Translation::default()
+        }
+    }
+
+    #[test]
+    fn language_is_substituted() {
+        assert_eq!(command_line("trans -b :{language}", "fr"), "trans -b :fr");
+        assert_eq!(command_line("translate", "fr"), "translate");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn command() {
+        assert_eq!(
+            translate(" bonjour\n".into(), config("cat")).await.unwrap(),
+            "bonjour"
+        );
+        assert_eq!(
+            translate("hello".into(), config("echo {language}"))
+                .await
+                .unwrap(),
+            "fr"
+        );
+        assert!(matches!(
+            translate("hello".into(), config("cat >/dev/null")).await,
+            Err(Error::Empty)
+        ));
+        assert!(matches!(
+            translate("hello".into(), config("echo nope >&2; exit 1")).await,
+            Err(Error::Command(stderr)) if stderr == "nope"
+        ));
+        assert!(matches!(
+            translate("hello".into(), config::Translation::default()).await,
+            Err(Error::Disabled)
+        ));
+    }
+}
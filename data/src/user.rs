@@ -1,6 +1,8 @@
 use std::collections::HashSet;
 use std::fmt;
 use std::hash::Hash;
+use std::ops::Range;
+use std::time::{Duration, Instant};
 
 use irc::proto;
 use itertools::sorted;
@@ -8,7 +10,10 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::config::buffer::UsernameFormat;
-use crate::mode;
+use crate::{isupport, mode};
+
+/// # of nicknames changed from to keep highlighting
+const PREVIOUS_NICKS_LEN: usize = 4;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(into = "String")]
@@ -18,6 +23,7 @@ pub struct User {
     username: Option<String>,
     hostname: Option<String>,
     accountname: Option<String>,
+    realname: Option<String>,
     access_levels: HashSet<AccessLevel>,
     away: bool,
 }
@@ -121,6 +127,7 @@ impl<'a> TryFrom<&'a str> for User {
             username,
             hostname,
             accountname: None,
+            realname: None,
             access_levels,
             away: false,
         })
@@ -153,6 +160,7 @@ impl From<Nick> for User {
             username: None,
             hostname: None,
             accountname: None,
+            realname: None,
             access_levels: HashSet::default(),
             away: false,
         }
@@ -197,6 +205,10 @@ impl User {
         self.accountname.as_deref()
     }
 
+    pub fn realname(&self) -> Option<&str> {
+        self.realname.as_deref()
+    }
+
     pub fn with_nickname(self, nickname: Nick) -> Self {
         Self { nickname, ..self }
     }
@@ -226,6 +238,26 @@ impl User {
         }
     }
 
+    pub fn with_realname(self, realname: &str) -> Self {
+        Self {
+            realname: Some(realname.to_string()),
+            ..self
+        }
+    }
+
+    /// Fills in what a fresh snapshot of the user, e.g. from `NAMES`, lacks
+    /// with what was already known about them
+    pub fn with_known_details(self, known: &User) -> Self {
+        Self {
+            username: self.username.or_else(|| known.username.clone()),
+            hostname: self.hostname.or_else(|| known.hostname.clone()),
+            accountname: self.accountname.or_else(|| known.accountname.clone()),
+            realname: self.realname.or_else(|| known.realname.clone()),
+            away: known.away,
+            ..self
+        }
+    }
+
     pub fn highest_access_level(&self) -> AccessLevel {
         self.access_levels
             .iter()
@@ -234,6 +266,14 @@ impl User {
             .unwrap_or(AccessLevel::Member)
     }
 
+    /// Mask matching the user's host when known, otherwise their nickname
+    pub fn ban_mask(&self) -> String {
+        match self.hostname() {
+            Some(hostname) => format!("*!*@{hostname}"),
+            None => format!("{}!*@*", self.nickname()),
+        }
+    }
+
     pub fn has_access_level(&self, access_level: AccessLevel) -> bool {
         self.access_levels.contains(&access_level)
     }
@@ -283,6 +323,7 @@ impl From<proto::User> for User {
             username: user.username,
             hostname: user.hostname,
             accountname: None,
+            realname: None,
             access_levels: HashSet::default(),
             away: false,
         }
@@ -435,6 +476,110 @@ impl TryFrom<mode::Channel> for AccessLevel {
     }
 }
 
+/// Nicknames mentions of which are highlighted as if they were our current
+/// nickname, compared using the server's casemapping
+#[derive(Debug, Clone, Default)]
+pub struct HighlightNicks {
+    nicks: Vec<String>,
+    casemapping: isupport::CaseMap,
+}
+
+impl HighlightNicks {
+    pub fn new<'a>(
+        nicks: impl IntoIterator<Item = &'a str>,
+        casemapping: isupport::CaseMap,
+    ) -> Self {
+        let mut normalized = Vec::<String>::new();
+
+        for nick in nicks {
+            let nick = casemapping.normalize(nick.trim());
+
+            if !nick.is_empty() && !normalized.contains(&nick) {
+                normalized.push(nick);
+            }
+        }
+
+        Self {
+            nicks: normalized,
+            casemapping,
+        }
+    }
+
+    pub fn contains(&self, nick: &str) -> bool {
+        self.nicks.contains(&self.casemapping.normalize(nick))
+    }
+
+    /// Byte ranges of mentions in `text` which aren't part of a longer word
+    pub fn mentions(&self, text: &str) -> Vec<Range<usize>> {
+        let is_word = |c: char| c.is_alphanumeric() || c == '_';
+
+        let mut mentions: Vec<Range<usize>> = vec![];
+        let mut prev = None;
+
+        for (start, c) in text.char_indices() {
+            if prev.is_some_and(is_word) {
+                prev = Some(c);
+                continue;
+            }
+
+            prev = Some(c);
+
+            // Longest match wins when nicks share a prefix
+            let end = self
+                .nicks
+                .iter()
+                .filter_map(|nick| {
+                    let end = text[start..]
+                        .char_indices()
+                        .nth(nick.chars().count())
+                        .map_or(text.len(), |(i, _)| start + i);
+
+                    (self.casemapping.normalize(&text[start..end]) == *nick
+                        && !text[end..].starts_with(is_word))
+                    .then_some(end)
+                })
+                .max();
+
+            if let Some(end) = end {
+                if mentions.last().is_none_or(|last| last.end <= start) {
+                    mentions.push(start..end);
+                }
+            }
+        }
+
+        mentions
+    }
+}
+
+/// Nicknames we changed from, which keep being highlighted for a grace
+/// period so mentions from users who haven't noticed the change aren't missed
+#[derive(Debug, Clone, Default)]
+pub struct PreviousNicks(Vec<(Nick, Instant)>);
+
+impl PreviousNicks {
+    pub fn record(&mut self, nick: Nick, changed_at: Instant) {
+        self.0.retain(|(previous, _)| *previous != nick);
+        self.0.push((nick, changed_at));
+
+        if self.0.len() > PREVIOUS_NICKS_LEN {
+            self.0.remove(0);
+        }
+    }
+
+    pub fn active(
+        &self,
+        now: Instant,
+        grace_period: Duration,
+    ) -> impl Iterator<Item = &Nick> {
+        self.0
+            .iter()
+            .filter(move |(_, changed_at)| {
+                now.saturating_duration_since(*changed_at) < grace_period
+            })
+            .map(|(nick, _)| nick)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -448,6 +593,7 @@ mod tests {
                     username: None,
                     hostname: None,
                     accountname: None,
+                    realname: None,
                     access_levels: HashSet::<AccessLevel>::from([
                         AccessLevel::Oper,
                         AccessLevel::Voice,
@@ -462,6 +608,7 @@ mod tests {
                     username: Some("d".into()),
                     hostname: Some("localhost".into()),
                     accountname: None,
+                    realname: None,
                     access_levels: HashSet::<AccessLevel>::from([
                         AccessLevel::Oper,
                     ]),
@@ -475,6 +622,7 @@ mod tests {
                     username: None,
                     hostname: None,
                     accountname: None,
+                    realname: None,
                     access_levels: HashSet::<AccessLevel>::new(),
                     away: false,
                 },
@@ -488,6 +636,7 @@ mod tests {
                         "2201:12f1:2:1162:1242:1fg:he11:abde".into(),
                     ),
                     accountname: None,
+                    realname: None,
                     access_levels: HashSet::<AccessLevel>::new(),
                     away: false,
                 },
@@ -499,6 +648,7 @@ mod tests {
                     username: Some("~foobar".into()),
                     hostname: Some("12.521.212.521".into()),
                     accountname: None,
+                    realname: None,
                     access_levels: HashSet::<AccessLevel>::from([
                         AccessLevel::Oper,
                         AccessLevel::Voice,
@@ -525,6 +675,7 @@ mod tests {
                     username: Some("d".into()),
                     hostname: Some("localhost".into()),
                     accountname: None,
+                    realname: None,
                     access_levels: HashSet::<AccessLevel>::new(),
                     away: false,
                 },
@@ -536,6 +687,7 @@ mod tests {
                     username: Some("the.flu".into()),
                     hostname: Some("in.you".into()),
                     accountname: None,
+                    realname: None,
                     access_levels: HashSet::<AccessLevel>::from([
                         AccessLevel::Oper,
                     ]),
@@ -549,6 +701,7 @@ mod tests {
                     username: Some("d".into()),
                     hostname: Some("localhost".into()),
                     accountname: None,
+                    realname: None,
                     access_levels: HashSet::<AccessLevel>::new(),
                     away: false,
                 },
@@ -560,6 +713,7 @@ mod tests {
                     username: Some("d".into()),
                     hostname: None,
                     accountname: None,
+                    realname: None,
                     access_levels: HashSet::<AccessLevel>::new(),
                     away: false,
                 },
@@ -571,6 +725,7 @@ mod tests {
                     username: None,
                     hostname: None,
                     accountname: None,
+                    realname: None,
                     access_levels: HashSet::<AccessLevel>::new(),
                     away: false,
                 },
@@ -596,4 +751,72 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn ban_mask() {
+        let tests = [
+            ("dan!d@localhost", "*!*@localhost"),
+            ("@H1N5!the.flu@in.you", "*!*@in.you"),
+            ("dan", "dan!*@*"),
+        ];
+
+        for (test, expected) in tests {
+            let user = User::try_from(test).unwrap();
+            assert_eq!(user.ban_mask(), expected);
+        }
+    }
+
+    #[test]
+    fn highlight_nicks() {
+        let nicks = HighlightNicks::new(
+            ["alice", "alice_w", "Alice[m]", "ALICE", "Alice Smith"],
+            isupport::CaseMap::RFC1459,
+        );
+
+        assert_eq!(
+            nicks.nicks,
+            vec!["alice", "alice_w", "alice{m}", "alice smith"]
+        );
+        assert!(nicks.contains("Alice_W"));
+        assert!(nicks.contains("alice{M}"));
+        assert!(!nicks.contains("alicew"));
+
+        let tests = [
+            ("alice: hi", vec![0..5]),
+            ("hi ALICE_W!", vec![3..10]),
+            ("ping alice[M], alice{m}", vec![5..13, 15..23]),
+            ("hello Alice Smith", vec![6..17]),
+            ("malice alices alice_ww", vec![]),
+            ("@alice", vec![1..6]),
+        ];
+
+        for (text, expected) in tests {
+            assert_eq!(nicks.mentions(text), expected, "{text}");
+        }
+    }
+
+    #[test]
+    fn previous_nicks() {
+        let start = Instant::now();
+        let grace_period = Duration::from_secs(300);
+
+        let mut previous = PreviousNicks::default();
+        previous.record("alice".into(), start);
+        previous.record("alice_".into(), start + Duration::from_secs(200));
+
+        let active = |now| {
+            previous
+                .active(now, grace_period)
+                .map(Nick::to_string)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(active(start), vec!["alice", "alice_"]);
+        assert_eq!(
+            active(start + Duration::from_secs(299)),
+            vec!["alice", "alice_"]
+        );
+        assert_eq!(active(start + Duration::from_secs(300)), vec!["alice_"]);
+        assert!(active(start + Duration::from_secs(500)).is_empty());
+    }
 }
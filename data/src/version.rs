@@ -1,4 +1,7 @@
+use reqwest::header::{self, HeaderValue};
+
 use crate::environment::VERSION;
+use crate::http;
 
 const LATEST_REMOTE_RELEASE_URL: &str =
     "https://api.github.com/repos/squidowl/halloy/releases/latest";
@@ -39,19 +42,16 @@ pub async fn latest_remote_version() -> Option<String> {
         tag_name: String,
     }
 
-    let client = reqwest::Client::builder()
-        .user_agent("halloy")
-        .build()
-        .ok()?;
+    let request = http::Request::get(LATEST_REMOTE_RELEASE_URL.parse().ok()?)
+        .header(header::USER_AGENT, HeaderValue::from_static("halloy"))
+        .header(
+            header::ACCEPT,
+            HeaderValue::from_static("application/vnd.github.v3+json"),
+        );
 
-    let response = client
-        .get(LATEST_REMOTE_RELEASE_URL)
-        .header(reqwest::header::ACCEPT, "application/vnd.github.v3+json")
-        .send()
+    http::send(request)
         .await
-        .ok()?;
-
-    response
+        .ok()?
         .json::<Release>()
         .await
         .ok()
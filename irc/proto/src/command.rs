@@ -112,6 +112,9 @@ pub enum Command {
     KNOCK(String, Option<String>),
     /// <target> [<timestamp>]
     MARKREAD(String, Option<String>),
+    /// <target> <subcommand> [<params>] (send)
+    /// <target> <key> <visibility> [<value>] (receive)
+    METADATA(String, String, Vec<String>),
     /// <subcommand> [<targets>]
     MONITOR(String, Option<String>),
     /// <realname>
@@ -222,6 +225,7 @@ impl Command {
             "CPRIVMSG" if len > 2 => CPRIVMSG(req!(), req!(), req!()),
             "KNOCK" if len > 0 => KNOCK(req!(), opt!()),
             "MARKREAD" if len > 0 => MARKREAD(req!(), opt!()),
+            "METADATA" if len > 1 => METADATA(req!(), req!(), params.collect()),
             "MONITOR" if len > 0 => MONITOR(req!(), opt!()),
             "SETNAME" if len > 0 => SETNAME(req!()),
             "TAGMSG" if len > 0 => TAGMSG(req!()),
@@ -326,6 +330,9 @@ impl Command {
             Command::CPRIVMSG(a, b, c) => vec![a, b, c],
             Command::KNOCK(a, b) => std::iter::once(a).chain(b).collect(),
             Command::MARKREAD(a, b) => std::iter::once(a).chain(b).collect(),
+            Command::METADATA(a, b, c) => {
+                std::iter::once(a).chain(Some(b)).chain(c).collect()
+            }
             Command::MONITOR(a, b) => std::iter::once(a).chain(b).collect(),
             Command::SETNAME(a) => vec![a],
             Command::TAGMSG(a) => vec![a],
@@ -403,6 +410,7 @@ impl Command {
             CPRIVMSG(_, _, _) => "CPRIVMSG".to_string(),
             KNOCK(_, _) => "KNOCK".to_string(),
             MARKREAD(_, _) => "MARKREAD".to_string(),
+            METADATA(_, _, _) => "METADATA".to_string(),
             MONITOR(_, _) => "MONITOR".to_string(),
             SETNAME(_) => "SETNAME".to_string(),
             TAGMSG(_) => "TAGMSG".to_string(),
@@ -426,7 +434,14 @@ pub enum Numeric {
     RPL_CREATED = 3,
     RPL_MYINFO = 4,
     RPL_ISUPPORT = 5,
+    RPL_MAP = 6,
+    RPL_MAPEND = 7,
     RPL_BOUNCE = 10,
+    /// `RPL_MAP` of ircu and its descendants
+    RPL_MAPIRCU = 15,
+    RPL_MAPMORE = 16,
+    /// `RPL_MAPEND` of ircu and its descendants
+    RPL_MAPENDIRCU = 17,
     RPL_STATSCOMMANDS = 212,
     RPL_ENDOFSTATS = 219,
     RPL_STATSUPTIME = 242,
@@ -447,6 +462,7 @@ pub enum Numeric {
     RPL_NONE = 300,
     RPL_AWAY = 301,
     RPL_USERHOST = 302,
+    RPL_ISON = 303,
     RPL_UNAWAY = 305,
     RPL_NOWAWAY = 306,
     RPL_WHOREPLY = 352,
@@ -521,6 +537,7 @@ pub enum Numeric {
     ERR_ALREADYREGISTERED = 462,
     ERR_PASSWDMISMATCH = 464,
     ERR_YOUREBANNEDCREEP = 465,
+    ERR_LINKCHANNEL = 470,
     ERR_CHANNELISFULL = 471,
     ERR_UNKNOWNMODE = 472,
     ERR_INVITEONLYCHAN = 473,
@@ -549,6 +566,13 @@ pub enum Numeric {
     RPL_MONLIST = 732,
     RPL_ENDOFMONLIST = 733,
     ERR_MONLISTFULL = 734,
+    RPL_WHOISKEYVALUE = 760,
+    RPL_KEYVALUE = 761,
+    RPL_KEYNOTSET = 766,
+    RPL_METADATASUBOK = 770,
+    RPL_METADATAUNSUBOK = 771,
+    RPL_METADATASUBS = 772,
+    RPL_METADATASYNCLATER = 774,
     RPL_LOGGEDIN = 900,
     RPL_LOGGEDOUT = 901,
     ERR_NICKLOCKED = 902,
@@ -572,7 +596,12 @@ impl TryFrom<u16> for Numeric {
             3 => RPL_CREATED,
             4 => RPL_MYINFO,
             5 => RPL_ISUPPORT,
+            6 => RPL_MAP,
+            7 => RPL_MAPEND,
             10 => RPL_BOUNCE,
+            15 => RPL_MAPIRCU,
+            16 => RPL_MAPMORE,
+            17 => RPL_MAPENDIRCU,
             212 => RPL_STATSCOMMANDS,
             219 => RPL_ENDOFSTATS,
             242 => RPL_STATSUPTIME,
@@ -593,6 +622,7 @@ impl TryFrom<u16> for Numeric {
             300 => RPL_NONE,
             301 => RPL_AWAY,
             302 => RPL_USERHOST,
+            303 => RPL_ISON,
             305 => RPL_UNAWAY,
             306 => RPL_NOWAWAY,
             352 => RPL_WHOREPLY,
@@ -667,6 +697,7 @@ impl TryFrom<u16> for Numeric {
             462 => ERR_ALREADYREGISTERED,
             464 => ERR_PASSWDMISMATCH,
             465 => ERR_YOUREBANNEDCREEP,
+            470 => ERR_LINKCHANNEL,
             471 => ERR_CHANNELISFULL,
             472 => ERR_UNKNOWNMODE,
             473 => ERR_INVITEONLYCHAN,
@@ -695,6 +726,13 @@ impl TryFrom<u16> for Numeric {
             732 => RPL_MONLIST,
             733 => RPL_ENDOFMONLIST,
             734 => ERR_MONLISTFULL,
+            760 => RPL_WHOISKEYVALUE,
+            761 => RPL_KEYVALUE,
+            766 => RPL_KEYNOTSET,
+            770 => RPL_METADATASUBOK,
+            771 => RPL_METADATAUNSUBOK,
+            772 => RPL_METADATASUBS,
+            774 => RPL_METADATASYNCLATER,
             900 => RPL_LOGGEDIN,
             901 => RPL_LOGGEDOUT,
             902 => ERR_NICKLOCKED,
@@ -7,6 +7,73 @@ use crate::{Command, Message, Tag};
 /// Most IRC servers limit messages to 512 bytes in length, including the trailing CR-LF characters.
 pub const BYTE_LIMIT: usize = 512;
 
+/// https://ircv3.net/specs/extensions/message-tags#size-limit
+///
+/// Clients must not send messages with more than 4094 bytes of tag data,
+/// including the leading '@' and trailing space.
+pub const CLIENT_TAGS_BYTE_LIMIT: usize = 4094;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TagError {
+    #[error("tag key is empty")]
+    EmptyKey,
+    #[error("tag vendor is empty")]
+    EmptyVendor,
+    #[error("invalid character {0:?} in tag key")]
+    InvalidKey(char),
+    #[error("invalid character {0:?} in tag vendor")]
+    InvalidVendor(char),
+    #[error("tag value contains NUL")]
+    InvalidValue,
+}
+
+/// https://ircv3.net/specs/extensions/message-tags#format
+///
+/// Validates a tag against the message-tags grammar before it is formatted.
+/// Values are escaped when formatted, so only NUL is rejected in a value.
+pub fn validate_tag(tag: &Tag) -> Result<(), TagError> {
+    // [ <client_prefix> ]
+    let key = tag.key.strip_prefix('+').unwrap_or(&tag.key);
+
+    // [ <vendor> '/' ] <key_name>
+    let (vendor, key_name) = match key.rsplit_once('/') {
+        Some((vendor, key_name)) => (Some(vendor), key_name),
+        None => (None, key),
+    };
+
+    if let Some(vendor) = vendor {
+        if vendor.is_empty() {
+            return Err(TagError::EmptyVendor);
+        }
+
+        // <host>
+        if let Some(c) = vendor
+            .chars()
+            .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '.')))
+        {
+            return Err(TagError::InvalidVendor(c));
+        }
+    }
+
+    if key_name.is_empty() {
+        return Err(TagError::EmptyKey);
+    }
+
+    // <sequence of letters, digits, hyphens (`-`)>
+    if let Some(c) = key_name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '-'))
+    {
+        return Err(TagError::InvalidKey(c));
+    }
+
+    if tag.value.as_ref().is_some_and(|value| value.contains('\0')) {
+        return Err(TagError::InvalidValue);
+    }
+
+    Ok(())
+}
+
 pub fn message(message: Message) -> String {
     let mut output = String::with_capacity(BYTE_LIMIT);
 
@@ -30,7 +97,7 @@ pub fn message(message: Message) -> String {
     output
 }
 
-fn tags(tags: Vec<Tag>) -> String {
+pub fn tags(tags: Vec<Tag>) -> String {
     tags.into_iter().map(tag).join(";")
 }
 
@@ -83,6 +150,7 @@ fn trailing(parameter: String) -> String {
 
 #[cfg(test)]
 mod test {
+    use super::TagError;
     use crate::{Tag, command, format};
 
     #[test]
@@ -139,4 +207,40 @@ mod test {
         let tags = super::tags(test);
         assert_eq!(tags, expected);
     }
+
+    #[test]
+    fn validate_tags() {
+        let tag = |key: &str, value: Option<&str>| Tag {
+            key: key.into(),
+            value: value.map(String::from),
+        };
+
+        let valid = [
+            tag("msgid", Some("abc")),
+            tag("+typing", Some("active")),
+            tag("+draft/reply", Some("123")),
+            tag("example.com/foo-bar", None),
+            tag("+example.com/foo", Some("spa ces;\\")),
+        ];
+
+        for tag in valid {
+            assert_eq!(super::validate_tag(&tag), Ok(()), "{tag:?}");
+        }
+
+        let invalid = [
+            (tag("", None), TagError::EmptyKey),
+            (tag("+", None), TagError::EmptyKey),
+            (tag("example.com/", None), TagError::EmptyKey),
+            (tag("/foo", None), TagError::EmptyVendor),
+            (tag("foo_bar", None), TagError::InvalidKey('_')),
+            (tag("foo bar", None), TagError::InvalidKey(' ')),
+            (tag("+foo=bar", None), TagError::InvalidKey('=')),
+            (tag("exa mple/foo", None), TagError::InvalidVendor(' ')),
+            (tag("foo", Some("nul\0")), TagError::InvalidValue),
+        ];
+
+        for (tag, error) in invalid {
+            assert_eq!(super::validate_tag(&tag), Err(error), "{tag:?}");
+        }
+    }
 }
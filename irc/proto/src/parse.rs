@@ -380,6 +380,35 @@ mod test {
                     ),
                 },
             ),
+            (
+                Vec::from(b":irc.example.com METADATA alice display-name * :Alice Liddell\r\n"),
+                Message {
+                    tags: vec![],
+                    source: Some(Source::Server("irc.example.com".to_string())),
+                    command: Command::METADATA(
+                        "alice".to_string(),
+                        "display-name".to_string(),
+                        vec!["*".to_string(), "Alice Liddell".to_string()],
+                    ),
+                },
+            ),
+            (
+                Vec::from(b":irc.example.com 761 dan alice avatar * :https://example.com/a.png\r\n"),
+                Message {
+                    tags: vec![],
+                    source: Some(Source::Server("irc.example.com".to_string())),
+                    command: Command::Numeric(
+                        RPL_KEYVALUE,
+                        vec![
+                            "dan".to_string(),
+                            "alice".to_string(),
+                            "avatar".to_string(),
+                            "*".to_string(),
+                            "https://example.com/a.png".to_string(),
+                        ],
+                    ),
+                },
+            ),
             // Extra \r sent by digitalirc
             (
                 Vec::from(b"@batch=JQlhpjWY7SYaBPQtXAfUQh;msgid=UGnor4DBoafs6ge0UgsHF7-aVdnYMbjbdTf9eEHQmPKWA;time=2024-11-07T12:04:28.361Z :foo!~foo@F3FF3610.5A633F24.29800D3F.IP JOIN #pixelcove * :foo\r\r\n"),
@@ -1,10 +1,13 @@
+use std::io;
 use std::net::IpAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
 
 use arti_client::DataStream as TorStream;
 use futures::{Sink, SinkExt, Stream, StreamExt};
 use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+#[cfg(unix)]
+use tokio::net::UnixStream;
 use tokio::net::{TcpListener, TcpStream};
 use tokio_rustls::client::TlsStream;
 use tokio_util::codec;
@@ -18,6 +21,8 @@ mod tls;
 pub enum IrcStream {
     Tcp(TcpStream),
     Tor(TorStream),
+    #[cfg(unix)]
+    Unix(UnixStream),
 }
 
 pub enum Connection<Codec> {
@@ -42,15 +47,21 @@ pub struct Config<'a> {
     pub port: u16,
     pub security: Security<'a>,
     pub proxy: Option<Proxy>,
+    /// Connects to this socket instead of `server` and `port`, which only
+    /// name the server for TLS. No proxy is used.
+    pub unix_socket: Option<&'a Path>,
 }
 
 impl<Codec> Connection<Codec> {
     pub async fn new(config: Config<'_>, codec: Codec) -> Result<Self, Error> {
-        let stream = match config.proxy {
-            None => IrcStream::Tcp(
+        let stream = match (config.unix_socket, config.proxy) {
+            (Some(path), _) => connect_unix(path).await?,
+            (None, None) => IrcStream::Tcp(
                 TcpStream::connect((config.server, config.port)).await?,
             ),
-            Some(proxy) => proxy.connect(config.server, config.port).await?,
+            (None, Some(proxy)) => {
+                proxy.connect(config.server, config.port).await?
+            }
         };
 
         if let Security::Secured {
@@ -112,6 +123,25 @@ impl<Codec> Connection<Codec> {
     }
 }
 
+#[cfg(unix)]
+async fn connect_unix(path: &Path) -> Result<IrcStream, Error> {
+    UnixStream::connect(path)
+        .await
+        .map(IrcStream::Unix)
+        .map_err(|source| Error::UnixSocket {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+#[cfg(not(unix))]
+async fn connect_unix(path: &Path) -> Result<IrcStream, Error> {
+    Err(Error::UnixSocket {
+        path: path.to_path_buf(),
+        source: io::ErrorKind::Unsupported.into(),
+    })
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error("tls error: {0}")]
@@ -120,6 +150,24 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("proxy error: {0}")]
     Proxy(#[from] proxy::Error),
+    #[error("unix socket {}: {}", path.display(), unix_socket_reason(source))]
+    UnixSocket { path: PathBuf, source: io::Error },
+}
+
+fn unix_socket_reason(error: &io::Error) -> String {
+    match error.kind() {
+        io::ErrorKind::NotFound => {
+            "doesn't exist, is the server listening on it?".to_string()
+        }
+        io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+        io::ErrorKind::ConnectionRefused => {
+            "connection refused, is the server still running?".to_string()
+        }
+        io::ErrorKind::Unsupported => {
+            "not supported on this platform".to_string()
+        }
+        _ => error.to_string(),
+    }
 }
 
 macro_rules! delegate {
@@ -189,6 +237,8 @@ impl AsyncRead for IrcStream {
         match self.get_mut() {
             IrcStream::Tcp(s) => Pin::new(s).poll_read(cx, buf),
             IrcStream::Tor(s) => Pin::new(s).poll_read(cx, buf),
+            #[cfg(unix)]
+            IrcStream::Unix(s) => Pin::new(s).poll_read(cx, buf),
         }
     }
 }
@@ -198,6 +248,8 @@ impl AsyncWrite for IrcStream {
         match self {
             IrcStream::Tcp(s) => s.is_write_vectored(),
             IrcStream::Tor(s) => s.is_write_vectored(),
+            #[cfg(unix)]
+            IrcStream::Unix(s) => s.is_write_vectored(),
         }
     }
     fn poll_flush(
@@ -207,6 +259,8 @@ impl AsyncWrite for IrcStream {
         match self.get_mut() {
             IrcStream::Tcp(s) => Pin::new(s).poll_flush(cx),
             IrcStream::Tor(s) => Pin::new(s).poll_flush(cx),
+            #[cfg(unix)]
+            IrcStream::Unix(s) => Pin::new(s).poll_flush(cx),
         }
     }
     fn poll_shutdown(
@@ -216,6 +270,8 @@ impl AsyncWrite for IrcStream {
         match self.get_mut() {
             IrcStream::Tcp(s) => Pin::new(s).poll_shutdown(cx),
             IrcStream::Tor(s) => Pin::new(s).poll_shutdown(cx),
+            #[cfg(unix)]
+            IrcStream::Unix(s) => Pin::new(s).poll_shutdown(cx),
         }
     }
     fn poll_write(
@@ -226,6 +282,8 @@ impl AsyncWrite for IrcStream {
         match self.get_mut() {
             IrcStream::Tcp(s) => Pin::new(s).poll_write(cx, buf),
             IrcStream::Tor(s) => Pin::new(s).poll_write(cx, buf),
+            #[cfg(unix)]
+            IrcStream::Unix(s) => Pin::new(s).poll_write(cx, buf),
         }
     }
     fn poll_write_vectored(
@@ -236,6 +294,84 @@ impl AsyncWrite for IrcStream {
         match self.get_mut() {
             IrcStream::Tcp(s) => Pin::new(s).poll_write_vectored(cx, bufs),
             IrcStream::Tor(s) => Pin::new(s).poll_write_vectored(cx, bufs),
+            #[cfg(unix)]
+            IrcStream::Unix(s) => Pin::new(s).poll_write_vectored(cx, bufs),
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::net::UnixListener;
+
+    use super::*;
+    use crate::Codec;
+
+    fn socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("halloy-{name}-{}.sock", std::process::id()))
+    }
+
+    fn config(path: &Path) -> Config<'_> {
+        Config {
+            server: "localhost",
+            port: 6667,
+            security: Security::Unsecured,
+            proxy: None,
+            unix_socket: Some(path),
         }
     }
+
+    #[tokio::test]
+    async fn unix_socket() {
+        let path = socket_path("connect");
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+
+            let mut line = String::new();
+            BufReader::new(reader).read_line(&mut line).await.unwrap();
+
+            writer.write_all(b"PING :halloy\r\n").await.unwrap();
+
+            line
+        });
+
+        let mut connection =
+            Connection::new(config(&path), Codec).await.unwrap();
+
+        connection
+            .send(proto::command("NICK", vec!["halloy".to_string()]))
+            .await
+            .unwrap();
+        let received = connection.next().await.unwrap().unwrap().unwrap();
+
+        assert_eq!(server.await.unwrap(), "NICK halloy\r\n");
+        assert_eq!(
+            received.command,
+            proto::Command::PING("halloy".to_string())
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn missing_unix_socket() {
+        let path = socket_path("missing");
+
+        let Err(error) = Connection::new(config(&path), Codec).await else {
+            panic!("connected to a missing socket");
+        };
+
+        assert!(matches!(
+            &error,
+            Error::UnixSocket { source, .. }
+                if source.kind() == io::ErrorKind::NotFound
+        ));
+        assert!(error.to_string().contains("doesn't exist"));
+    }
 }
@@ -1,3 +1,4 @@
+use data::message::Category;
 use iced::widget::container::{Catalog, Style, StyleFn, transparent};
 use iced::{Background, Border, Color, border};
 
@@ -65,6 +66,122 @@ pub fn table(theme: &Theme, idx: usize) -> Style {
     }
 }
 
+/// Background of a message row. The tint of its category is laid over the
+/// shading of alternate rows, so both stay visible.
+pub fn message_row(
+    theme: &Theme,
+    category: Option<Category>,
+    is_alternate: bool,
+) -> Style {
+    let row_background = theme.colors().buffer.row_background;
+
+    let tint = category.and_then(|category| row_background.category(category));
+    let shading = row_background.alternate.filter(|_| is_alternate);
+
+    let background = match (tint, shading) {
+        (Some(tint), Some(shading)) => Some(over(tint, shading)),
+        (tint, shading) => tint.or(shading),
+    };
+
+    Style {
+        background: background.map(Background::Color),
+        ..Default::default()
+    }
+}
+
+/// Messages selected by dragging across them
+pub fn selected_message(theme: &Theme) -> Style {
+    Style {
+        background: Some(Background::Color(theme.colors().buffer.selection)),
+        ..Default::default()
+    }
+}
+
+/// Messages matching the search of a buffer, outlined when it's the one
+/// scrolled to
+pub fn search_match(theme: &Theme, is_current: bool) -> Style {
+    let colors = theme.colors();
+
+    Style {
+        background: Some(Background::Color(colors.buffer.highlight)),
+        border: if is_current {
+            Border {
+                color: colors.buffer.border_selected,
+                width: 1.0,
+                radius: 0.0.into(),
+            }
+        } else {
+            Border::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Highlights on the scrollbar of a buffer
+pub fn highlight_marker(theme: &Theme) -> Style {
+    Style {
+        background: Some(Background::Color(Color {
+            a: 1.0,
+            ..theme.colors().buffer.highlight
+        })),
+        ..Default::default()
+    }
+}
+
+/// The backlog divider on the scrollbar of a buffer
+pub fn backlog_marker(theme: &Theme) -> Style {
+    Style {
+        background: Some(Background::Color(
+            theme.colors().general.unread_indicator,
+        )),
+        ..Default::default()
+    }
+}
+
+/// Unread messages on the scrollbar of a buffer, faint enough for the
+/// scroller to show through
+pub fn unread_marker(theme: &Theme) -> Style {
+    Style {
+        background: Some(Background::Color(Color {
+            a: 0.3,
+            ..theme.colors().general.unread_indicator
+        })),
+        ..Default::default()
+    }
+}
+
+/// Chip before the input while it composes a reply or notice
+pub fn compose_chip(theme: &Theme) -> Style {
+    Style {
+        background: Some(Background::Color(theme.colors().general.border)),
+        border: Border {
+            radius: 4.0.into(),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Composites `top` over `bottom`
+fn over(top: Color, bottom: Color) -> Color {
+    let a = top.a + bottom.a * (1.0 - top.a);
+
+    if a == 0.0 {
+        return Color::TRANSPARENT;
+    }
+
+    let channel = |top_channel: f32, bottom_channel: f32| {
+        (top_channel * top.a + bottom_channel * bottom.a * (1.0 - top.a)) / a
+    };
+
+    Color {
+        r: channel(top.r, bottom.r),
+        g: channel(top.g, bottom.g),
+        b: channel(top.b, bottom.b),
+        a,
+    }
+}
+
 pub fn none(_theme: &Theme) -> Style {
     Style {
         background: None,
@@ -148,3 +265,18 @@ pub fn transparent_overlay(theme: &Theme) -> Style {
         ..Default::default()
     }
 }
+
+/// A color of another theme, shown when previewing it
+pub fn swatch(theme: &Theme, color: Color) -> Style {
+    let general = theme.colors().general;
+
+    Style {
+        background: Some(Background::Color(color)),
+        border: Border {
+            radius: 2.0.into(),
+            width: 1.0,
+            color: general.border,
+        },
+        ..Default::default()
+    }
+}
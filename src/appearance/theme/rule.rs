@@ -1,3 +1,4 @@
+use data::appearance::theme::alpha_color;
 use iced::widget::rule::{Catalog, FillMode, Style, StyleFn};
 
 use super::Theme;
@@ -22,3 +23,13 @@ pub fn primary(theme: &Theme) -> Style {
         fill_mode: FillMode::Full,
     }
 }
+
+/// Divider where the window lost focus, fainter than the backlog divider
+pub fn focus_marker(theme: &Theme) -> Style {
+    Style {
+        color: alpha_color(theme.colors().text.tertiary, 0.5),
+        width: 1,
+        radius: 0.0.into(),
+        fill_mode: FillMode::Full,
+    }
+}
@@ -1,3 +1,4 @@
+use data::client::metadata;
 use data::config::buffer::away;
 use data::message::source::server::{Kind, StandardReply};
 use data::message::{self};
@@ -74,6 +75,7 @@ pub fn server(
             Kind::Quit => colors.quit,
             Kind::ReplyTopic => colors.reply_topic,
             Kind::ChangeHost => colors.change_host,
+            Kind::ChangeNick | Kind::ChangeMode | Kind::Bouncer => None,
             Kind::MonitoredOnline => colors.monitored_online,
             Kind::MonitoredOffline => colors.monitored_offline,
             Kind::StandardReply(StandardReply::Fail) => colors
@@ -86,6 +88,8 @@ pub fn server(
                 colors.standard_reply_note
             }
             Kind::Wallops => colors.wallops,
+            Kind::Ctcp => colors.ctcp,
+            Kind::Notice => colors.notice,
         })
         .or(Some(colors.default));
 
@@ -95,20 +99,34 @@ pub fn server(
     }
 }
 
-pub fn nicklist_nickname(theme: &Theme, config: &Config, user: &User) -> Style {
+pub fn nicklist_nickname(
+    theme: &Theme,
+    config: &Config,
+    user: &User,
+    profile: Option<&metadata::Profile>,
+) -> Style {
     nickname_style(
         theme,
+        config,
         config.buffer.channel.nicklist.color,
         user,
+        profile,
         config.buffer.away.appearance(user.is_away()),
     )
 }
 
-pub fn nickname(theme: &Theme, config: &Config, user: &User) -> Style {
+pub fn nickname(
+    theme: &Theme,
+    config: &Config,
+    user: &User,
+    profile: Option<&metadata::Profile>,
+) -> Style {
     nickname_style(
         theme,
+        config,
         config.buffer.channel.message.nickname_color,
         user,
+        profile,
         config.buffer.away.appearance(user.is_away()),
     )
 }
@@ -116,24 +134,24 @@ pub fn nickname(theme: &Theme, config: &Config, user: &User) -> Style {
 pub fn topic_nickname(theme: &Theme, config: &Config, user: &User) -> Style {
     nickname_style(
         theme,
+        config,
         config.buffer.channel.message.nickname_color,
         user,
         None,
+        None,
     )
 }
 
 fn nickname_style(
     theme: &Theme,
+    config: &Config,
     kind: data::buffer::Color,
     user: &User,
+    profile: Option<&metadata::Profile>,
     away_appearance: Option<away::Appearance>,
 ) -> Style {
-    let seed = match kind {
-        data::buffer::Color::Solid => None,
-        data::buffer::Color::Unique => Some(user.seed()),
-    };
-
-    let color = text::nickname(theme, seed, away_appearance).color;
+    let color = text::nickname_color(theme, config, kind, user.seed(), profile);
+    let color = text::nickname(theme, color, away_appearance).color;
 
     Style {
         color,
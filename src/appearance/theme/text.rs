@@ -1,7 +1,8 @@
-use data::appearance::theme::{
-    alpha_color, alpha_color_calculate, randomize_color,
-};
+use data::Config;
+use data::appearance::theme::{alpha_color, alpha_color_calculate};
+use data::client::metadata;
 use data::config::buffer::away;
+use iced::Color;
 use iced::widget::text::{Catalog, Style, StyleFn};
 
 use super::Theme;
@@ -40,6 +41,12 @@ pub fn tertiary(theme: &Theme) -> Style {
     }
 }
 
+pub fn focus_marker(theme: &Theme) -> Style {
+    Style {
+        color: Some(alpha_color(theme.colors().text.tertiary, 0.75)),
+    }
+}
+
 pub fn error(theme: &Theme) -> Style {
     Style {
         color: Some(theme.colors().text.error),
@@ -88,12 +95,33 @@ pub fn url(theme: &Theme) -> Style {
     }
 }
 
-pub fn nickname<T: AsRef<str>>(
+/// Color of the nickname with `seed`, the same wherever it's shown with
+/// `kind` of colors. `profile` is the user's, when known, for the color they
+/// shared.
+pub fn nickname_color(
+    theme: &Theme,
+    config: &Config,
+    kind: data::buffer::Color,
+    seed: &str,
+    profile: Option<&metadata::Profile>,
+) -> Color {
+    data::appearance::theme::nickname_color(
+        theme.colors().buffer.nickname,
+        seed,
+        kind,
+        &config.buffer.nickname.palette,
+        profile
+            .filter(|_| config.buffer.nickname.shared_colors)
+            .and_then(metadata::Profile::color),
+    )
+}
+
+/// Nickname in `color`, dimmed when away
+pub fn nickname(
     theme: &Theme,
-    seed: Option<T>,
+    color: Color,
     away_appearance: Option<away::Appearance>,
 ) -> Style {
-    let nickname = theme.colors().buffer.nickname;
     let calculate_alpha_color = |color| {
         if let Some(away::Appearance::Dimmed(alpha)) = away_appearance {
             match alpha {
@@ -112,13 +140,7 @@ pub fn nickname<T: AsRef<str>>(
         }
     };
 
-    // If we have a seed we randomize the color based on the seed before adding any alpha value.
-    let color = match seed {
-        Some(seed) => {
-            calculate_alpha_color(randomize_color(nickname, seed.as_ref()))
-        }
-        None => calculate_alpha_color(nickname),
-    };
-
-    Style { color: Some(color) }
+    Style {
+        color: Some(calculate_alpha_color(color)),
+    }
 }
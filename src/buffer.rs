@@ -1,10 +1,14 @@
 use std::path::PathBuf;
 
 pub use data::buffer::{Autocomplete, Internal, Settings, Upstream};
+use data::channel::ActionStyle;
 use data::dashboard::BufferAction;
 use data::target::{self, Target};
 use data::user::Nick;
-use data::{Config, buffer, file_transfer, history, message, preview};
+use data::{
+    Config, buffer, command, file_transfer, history, message, preview,
+    translation,
+};
 use iced::Task;
 
 pub use self::channel::Channel;
@@ -21,7 +25,7 @@ pub mod channel;
 pub mod empty;
 pub mod file_transfers;
 pub mod highlights;
-mod input_view;
+pub mod input_view;
 pub mod logs;
 pub mod query;
 mod scroll_view;
@@ -61,6 +65,14 @@ pub enum Event {
     MarkAsRead(history::Kind),
     OpenUrl(String),
     ImagePreview(PathBuf, url::Url),
+    Theme(Option<String>),
+    TopicHistory(target::Channel, usize),
+    Layout(command::Layout),
+    ConfirmSend(Upstream, String),
+    SetActionStyle(data::User, ActionStyle),
+    FileTransfer(file_transfer::manager::Event),
+    GoToServer(data::Server),
+    Translate(message::Hash, String),
 }
 
 impl Buffer {
@@ -163,6 +175,20 @@ impl Buffer {
                     channel::Event::ImagePreview(path, url) => {
                         Event::ImagePreview(path, url)
                     }
+                    channel::Event::Theme(name) => Event::Theme(name),
+                    channel::Event::TopicHistory(channel, count) => {
+                        Event::TopicHistory(channel, count)
+                    }
+                    channel::Event::Layout(layout) => Event::Layout(layout),
+                    channel::Event::ConfirmSend(buffer, text) => {
+                        Event::ConfirmSend(buffer, text)
+                    }
+                    channel::Event::SetActionStyle(user, style) => {
+                        Event::SetActionStyle(user, style)
+                    }
+                    channel::Event::Translate(hash, text) => {
+                        Event::Translate(hash, text)
+                    }
                 });
 
                 (command.map(Message::Channel), event)
@@ -184,6 +210,14 @@ impl Buffer {
                     server::Event::ImagePreview(path, url) => {
                         Event::ImagePreview(path, url)
                     }
+                    server::Event::Theme(name) => Event::Theme(name),
+                    server::Event::TopicHistory(channel, count) => {
+                        Event::TopicHistory(channel, count)
+                    }
+                    server::Event::Layout(layout) => Event::Layout(layout),
+                    server::Event::ConfirmSend(buffer, text) => {
+                        Event::ConfirmSend(buffer, text)
+                    }
                 });
 
                 (command.map(Message::Server), event)
@@ -212,14 +246,32 @@ impl Buffer {
                     query::Event::ImagePreview(path, url) => {
                         Event::ImagePreview(path, url)
                     }
+                    query::Event::Theme(name) => Event::Theme(name),
+                    query::Event::TopicHistory(channel, count) => {
+                        Event::TopicHistory(channel, count)
+                    }
+                    query::Event::Layout(layout) => Event::Layout(layout),
+                    query::Event::ConfirmSend(buffer, text) => {
+                        Event::ConfirmSend(buffer, text)
+                    }
+                    query::Event::Translate(hash, text) => {
+                        Event::Translate(hash, text)
+                    }
                 });
 
                 (command.map(Message::Query), event)
             }
             (Buffer::FileTransfers(state), Message::FileTransfers(message)) => {
-                let command = state.update(message, file_transfers, config);
+                let (command, event) =
+                    state.update(message, file_transfers, config);
 
-                (command.map(Message::FileTransfers), None)
+                let event = event.map(|event| match event {
+                    file_transfers::Event::NewTransfer(event) => {
+                        Event::FileTransfer(event)
+                    }
+                });
+
+                (command.map(Message::FileTransfers), event)
             }
             (Buffer::Logs(state), Message::Logs(message)) => {
                 let (command, event) =
@@ -240,6 +292,9 @@ impl Buffer {
                     logs::Event::ImagePreview(path, url) => {
                         Event::ImagePreview(path, url)
                     }
+                    logs::Event::GoToServer(server) => {
+                        Event::GoToServer(server)
+                    }
                 });
 
                 (command.map(Message::Logs), event)
@@ -279,6 +334,8 @@ impl Buffer {
         file_transfers: &'a file_transfer::Manager,
         history: &'a history::Manager,
         previews: &'a preview::Collection,
+        avatars: &'a preview::Collection,
+        translations: &'a translation::Collection,
         settings: Option<&'a buffer::Settings>,
         config: &'a Config,
         theme: &'a Theme,
@@ -288,7 +345,15 @@ impl Buffer {
         match self {
             Buffer::Empty => empty::view(config, sidebar),
             Buffer::Channel(state) => channel::view(
-                state, clients, history, previews, settings, config, theme,
+                state,
+                clients,
+                history,
+                previews,
+                avatars,
+                translations,
+                settings,
+                config,
+                theme,
                 is_focused,
             )
             .map(Message::Channel),
@@ -297,7 +362,15 @@ impl Buffer {
                     .map(Message::Server)
             }
             Buffer::Query(state) => query::view(
-                state, clients, history, previews, config, theme, is_focused,
+                state,
+                clients,
+                history,
+                previews,
+                avatars,
+                translations,
+                config,
+                theme,
+                is_focused,
             )
             .map(Message::Query),
             Buffer::FileTransfers(state) => {
@@ -305,7 +378,8 @@ impl Buffer {
                     .map(Message::FileTransfers)
             }
             Buffer::Logs(state) => {
-                logs::view(state, history, config, theme).map(Message::Logs)
+                logs::view(state, history, config, theme, is_focused)
+                    .map(Message::Logs)
             }
             Buffer::Highlights(state) => {
                 highlights::view(state, clients, history, config, theme)
@@ -363,6 +437,37 @@ impl Buffer {
         }
     }
 
+    pub fn toggle_nick_list_filter(&mut self) -> Task<Message> {
+        match self {
+            Buffer::Empty
+            | Buffer::Server(_)
+            | Buffer::Query(_)
+            | Buffer::FileTransfers(_)
+            | Buffer::Logs(_)
+            | Buffer::Highlights(_) => Task::none(),
+            Buffer::Channel(channel) => {
+                channel.toggle_nick_list_filter().map(Message::Channel)
+            }
+        }
+    }
+
+    pub fn show_ban_list(
+        &mut self,
+        clients: &mut data::client::Map,
+    ) -> Task<Message> {
+        match self {
+            Buffer::Empty
+            | Buffer::Server(_)
+            | Buffer::Query(_)
+            | Buffer::FileTransfers(_)
+            | Buffer::Logs(_)
+            | Buffer::Highlights(_) => Task::none(),
+            Buffer::Channel(channel) => {
+                channel.show_ban_list(clients).map(Message::Channel)
+            }
+        }
+    }
+
     pub fn insert_user_to_input(
         &mut self,
         nick: Nick,
@@ -390,6 +495,38 @@ impl Buffer {
         }
     }
 
+    /// Composes a NOTICE to `nick` from the input of the buffer
+    pub fn notice(
+        &mut self,
+        nick: Nick,
+        history: &mut history::Manager,
+    ) -> Task<Message> {
+        match self {
+            Buffer::Empty
+            | Buffer::FileTransfers(_)
+            | Buffer::Logs(_)
+            | Buffer::Highlights(_) => Task::none(),
+            Buffer::Channel(state) => state
+                .input_view
+                .notice(nick, &state.buffer, history)
+                .map(|message| {
+                    Message::Channel(channel::Message::InputView(message))
+                }),
+            Buffer::Server(state) => state
+                .input_view
+                .notice(nick, &state.buffer, history)
+                .map(|message| {
+                    Message::Server(server::Message::InputView(message))
+                }),
+            Buffer::Query(state) => state
+                .input_view
+                .notice(nick, &state.buffer, history)
+                .map(|message| {
+                    Message::Query(query::Message::InputView(message))
+                }),
+        }
+    }
+
     pub fn scroll_up_page(&mut self) -> Task<Message> {
         match self {
             Buffer::Empty | Buffer::FileTransfers(_) => Task::none(),
@@ -648,6 +785,92 @@ impl Buffer {
         }
     }
 
+    pub fn scroll_to_focus_marker(
+        &mut self,
+        history: &history::Manager,
+        config: &Config,
+    ) -> Task<Message> {
+        match self {
+            Buffer::Empty | Buffer::FileTransfers(_) => Task::none(),
+            Buffer::Channel(state) => state
+                .scroll_view
+                .scroll_to_focus_marker(
+                    scroll_view::Kind::Channel(&state.server, &state.target),
+                    history,
+                    config,
+                )
+                .map(|message| {
+                    Message::Channel(channel::Message::ScrollView(message))
+                }),
+            Buffer::Server(state) => state
+                .scroll_view
+                .scroll_to_focus_marker(
+                    scroll_view::Kind::Server(&state.server),
+                    history,
+                    config,
+                )
+                .map(|message| {
+                    Message::Server(server::Message::ScrollView(message))
+                }),
+            Buffer::Query(state) => state
+                .scroll_view
+                .scroll_to_focus_marker(
+                    scroll_view::Kind::Query(&state.server, &state.target),
+                    history,
+                    config,
+                )
+                .map(|message| {
+                    Message::Query(query::Message::ScrollView(message))
+                }),
+            Buffer::Logs(state) => state
+                .scroll_view
+                .scroll_to_focus_marker(
+                    scroll_view::Kind::Logs,
+                    history,
+                    config,
+                )
+                .map(|message| {
+                    Message::Logs(logs::Message::ScrollView(message))
+                }),
+            Buffer::Highlights(state) => state
+                .scroll_view
+                .scroll_to_focus_marker(
+                    scroll_view::Kind::Highlights,
+                    history,
+                    config,
+                )
+                .map(|message| {
+                    Message::Highlights(highlights::Message::ScrollView(
+                        message,
+                    ))
+                }),
+        }
+    }
+
+    pub fn mark_focus_lost(&mut self) {
+        match self {
+            Buffer::Empty | Buffer::FileTransfers(_) => {}
+            Buffer::Channel(state) => state.scroll_view.mark_focus_lost(),
+            Buffer::Server(state) => state.scroll_view.mark_focus_lost(),
+            Buffer::Query(state) => state.scroll_view.mark_focus_lost(),
+            Buffer::Logs(state) => state.scroll_view.mark_focus_lost(),
+            Buffer::Highlights(state) => state.scroll_view.mark_focus_lost(),
+        }
+    }
+
+    pub fn show_focus_marker(&mut self) {
+        match self {
+            Buffer::Empty | Buffer::FileTransfers(_) => {}
+            Buffer::Channel(state) => state.scroll_view.show_focus_marker(),
+            Buffer::Server(state) => state.scroll_view.show_focus_marker(),
+            Buffer::Query(state) => state.scroll_view.show_focus_marker(),
+            Buffer::Logs(state) => state.scroll_view.show_focus_marker(),
+            Buffer::Highlights(state) => {
+                state.scroll_view.show_focus_marker();
+            }
+        }
+    }
+
     pub fn is_scrolled_to_bottom(&self) -> Option<bool> {
         match self {
             Buffer::Empty | Buffer::FileTransfers(_) => None,
@@ -667,6 +890,81 @@ impl Buffer {
         }
     }
 
+    /// Messages selected by dragging across them, one line each
+    pub fn selected_messages(
+        &self,
+        history: &history::Manager,
+        config: &Config,
+    ) -> Option<String> {
+        match self {
+            Buffer::Empty | Buffer::FileTransfers(_) => None,
+            Buffer::Channel(state) => state.scroll_view.selected_text(
+                scroll_view::Kind::Channel(&state.server, &state.target),
+                history,
+                config,
+            ),
+            Buffer::Server(state) => state.scroll_view.selected_text(
+                scroll_view::Kind::Server(&state.server),
+                history,
+                config,
+            ),
+            Buffer::Query(state) => state.scroll_view.selected_text(
+                scroll_view::Kind::Query(&state.server, &state.target),
+                history,
+                config,
+            ),
+            Buffer::Logs(state) => state.scroll_view.selected_text(
+                scroll_view::Kind::Logs,
+                history,
+                config,
+            ),
+            Buffer::Highlights(state) => state.scroll_view.selected_text(
+                scroll_view::Kind::Highlights,
+                history,
+                config,
+            ),
+        }
+    }
+
+    pub fn clear_selection(&mut self) -> bool {
+        match self {
+            Buffer::Empty | Buffer::FileTransfers(_) => false,
+            Buffer::Channel(state) => state.scroll_view.clear_selection(),
+            Buffer::Server(state) => state.scroll_view.clear_selection(),
+            Buffer::Query(state) => state.scroll_view.clear_selection(),
+            Buffer::Logs(state) => state.scroll_view.clear_selection(),
+            Buffer::Highlights(state) => state.scroll_view.clear_selection(),
+        }
+    }
+
+    pub fn open_search(&mut self) -> Task<Message> {
+        match self {
+            Buffer::Empty
+            | Buffer::FileTransfers(_)
+            | Buffer::Highlights(_) => Task::none(),
+            Buffer::Channel(state) => {
+                state.scroll_view.open_search().map(|message| {
+                    Message::Channel(channel::Message::ScrollView(message))
+                })
+            }
+            Buffer::Server(state) => {
+                state.scroll_view.open_search().map(|message| {
+                    Message::Server(server::Message::ScrollView(message))
+                })
+            }
+            Buffer::Query(state) => {
+                state.scroll_view.open_search().map(|message| {
+                    Message::Query(query::Message::ScrollView(message))
+                })
+            }
+            Buffer::Logs(state) => {
+                state.scroll_view.open_search().map(|message| {
+                    Message::Logs(logs::Message::ScrollView(message))
+                })
+            }
+        }
+    }
+
     pub fn close_picker(&mut self) -> bool {
         match self {
             Buffer::Empty
@@ -1,12 +1,14 @@
 use std::path::PathBuf;
 
+use data::channel::ActionStyle;
 use data::dashboard::BufferAction;
+use data::ignore::Ignores;
 use data::preview::{self, Previews};
 use data::server::Server;
 use data::target::{self, Target};
-use data::user::Nick;
-use data::{Config, User, buffer, history, message};
-use iced::widget::{column, container, row};
+use data::user::{AccessLevel, Nick};
+use data::{Config, User, buffer, command, history, message, translation};
+use iced::widget::{column, container, row, text_input};
 use iced::{Length, Task, padding};
 
 use super::message_view::{ChannelQueryLayout, TargetInfo};
@@ -14,6 +16,8 @@ use super::{input_view, scroll_view, user_context};
 use crate::widget::Element;
 use crate::Theme;
 
+mod ban_list;
+mod modes;
 mod topic;
 
 #[derive(Debug, Clone)]
@@ -22,6 +26,10 @@ pub enum Message {
     InputView(input_view::Message),
     UserContext(user_context::Message),
     Topic(topic::Message),
+    Modes(modes::Message),
+    BanList(ban_list::Message),
+    NickListFilter(String),
+    CloseNickListFilter,
 }
 
 pub enum Event {
@@ -34,6 +42,12 @@ pub enum Event {
     MarkAsRead(history::Kind),
     OpenUrl(String),
     ImagePreview(PathBuf, url::Url),
+    Theme(Option<String>),
+    TopicHistory(target::Channel, usize),
+    Layout(command::Layout),
+    ConfirmSend(buffer::Upstream, String),
+    SetActionStyle(User, ActionStyle),
+    Translate(message::Hash, String),
 }
 
 pub fn view<'a>(
@@ -41,6 +55,8 @@ pub fn view<'a>(
     clients: &'a data::client::Map,
     history: &'a history::Manager,
     previews: &'a preview::Collection,
+    avatars: &'a preview::Collection,
+    translations: &'a translation::Collection,
     settings: Option<&'a buffer::Settings>,
     config: &'a Config,
     theme: &'a Theme,
@@ -51,6 +67,7 @@ pub fn view<'a>(
     let channel = &state.target;
     let buffer = &state.buffer;
     let input = history.input(buffer);
+    let scheduled = history.scheduled().in_buffer(buffer).collect();
     let our_nick = clients.nickname(&state.server);
 
     let our_user = our_nick
@@ -76,11 +93,16 @@ pub fn view<'a>(
         casemapping,
         server,
         theme,
+        ignores: history.ignores(),
         target: TargetInfo::Channel {
             users,
             channel,
             our_user,
+            actions: settings.map(|settings| &settings.channel.actions),
         },
+        metadata: clients.get_metadata(server),
+        avatars,
+        translations,
     };
 
     let messages = container(
@@ -98,14 +120,61 @@ pub fn view<'a>(
     .width(Length::FillPortion(2))
     .height(Length::Fill);
 
-    let nick_list =
-        nick_list::view(server, casemapping, channel, users, our_user, config)
-            .map(Message::UserContext);
+    let search_bar = scroll_view::search_bar(
+        &state.scroll_view,
+        scroll_view::Kind::Channel(&state.server, channel),
+        history,
+        config,
+        is_focused,
+    )
+    .map(|search_bar| search_bar.map(Message::ScrollView));
+
+    let ban_list = state.ban_list.as_ref().map(|panel| {
+        ban_list::view(
+            panel,
+            clients.get_channel_bans(server, channel),
+            our_user.is_some_and(|user| {
+                user.highest_access_level() >= AccessLevel::Oper
+            }),
+            config.tooltips,
+        )
+        .map(Message::BanList)
+    });
+
+    // Appended, so opening them doesn't reset the messages' scroll state
+    let messages = column![messages]
+        .push_maybe(search_bar)
+        .push_maybe(ban_list)
+        .spacing(4)
+        .width(Length::FillPortion(2))
+        .height(Length::Fill);
+
+    let nick_list = nick_list::view(
+        server,
+        casemapping,
+        channel,
+        users,
+        our_user,
+        clients.get_metadata(server),
+        state.nick_list_filter.as_ref(),
+        is_focused,
+        config,
+        history.ignores(),
+    );
 
     // If topic toggles from None to Some then it messes with messages' scroll state,
     // so produce a zero-height placeholder when topic is None.
-    let topic = topic(state, clients, users, our_user, settings, config, theme)
-        .unwrap_or_else(|| column![].into());
+    let topic = topic(
+        state,
+        clients,
+        users,
+        our_user,
+        settings,
+        config,
+        history.ignores(),
+        theme,
+    )
+    .unwrap_or_else(|| column![].into());
 
     let show_text_input = match config.buffer.text_input.visibility {
         data::buffer::TextInputVisibility::Focused => is_focused,
@@ -119,9 +188,12 @@ pub fn view<'a>(
         input_view::view(
             &state.input_view,
             input,
+            scheduled,
             is_focused,
             !is_connected_to_channel,
+            casemapping,
             config,
+            theme,
         )
         .map(Message::InputView)
     });
@@ -161,6 +233,10 @@ pub struct Channel {
     pub target: target::Channel,
     pub scroll_view: scroll_view::State,
     pub input_view: input_view::State,
+    nick_list_filter: Option<nick_list::Filter>,
+    topic_editor: Option<topic::Editor>,
+    modes_editor: Option<modes::Editor>,
+    ban_list: Option<ban_list::Panel>,
 }
 
 impl Channel {
@@ -171,6 +247,10 @@ impl Channel {
             target,
             scroll_view: scroll_view::State::new(),
             input_view: input_view::State::new(),
+            nick_list_filter: None,
+            topic_editor: None,
+            modes_editor: None,
+            ban_list: None,
         }
     }
 
@@ -192,6 +272,24 @@ impl Channel {
                     config,
                 );
 
+                if let Some(scroll_view::Event::Reply(reply)) = event {
+                    let task = self.input_view.reply(
+                        reply,
+                        &self.buffer,
+                        clients,
+                        history,
+                        config,
+                    );
+
+                    return (
+                        Task::batch(vec![
+                            command.map(Message::ScrollView),
+                            task.map(Message::InputView),
+                        ]),
+                        None,
+                    );
+                }
+
                 let event = event.and_then(|event| match event {
                     scroll_view::Event::UserContext(event) => {
                         Some(Event::UserContext(event))
@@ -221,6 +319,16 @@ impl Channel {
                     scroll_view::Event::ImagePreview(path, url) => {
                         Some(Event::ImagePreview(path, url))
                     }
+                    // Started in the input above
+                    scroll_view::Event::Reply(_) => None,
+                    scroll_view::Event::SetActionStyle(user, style) => {
+                        Some(Event::SetActionStyle(user, style))
+                    }
+                    // Only offered in the logs buffer
+                    scroll_view::Event::GoToServer(_) => None,
+                    scroll_view::Event::Translate(hash, text) => {
+                        Some(Event::Translate(hash, text))
+                    }
                 });
 
                 (command.map(Message::ScrollView), event)
@@ -249,6 +357,23 @@ impl Channel {
                     Some(input_view::Event::OpenBuffers { targets }) => {
                         (command, Some(Event::OpenBuffers(targets)))
                     }
+                    Some(input_view::Event::Theme { name }) => {
+                        (command, Some(Event::Theme(name)))
+                    }
+                    Some(input_view::Event::TopicHistory {
+                        channel,
+                        count,
+                    }) => (command, Some(Event::TopicHistory(channel, count))),
+                    Some(input_view::Event::Layout { layout }) => {
+                        (command, Some(Event::Layout(layout)))
+                    }
+                    Some(input_view::Event::BanList { .. }) => (
+                        Task::batch([command, self.show_ban_list(clients)]),
+                        None,
+                    ),
+                    Some(input_view::Event::ConfirmSend { buffer, text }) => {
+                        (command, Some(Event::ConfirmSend(buffer, text)))
+                    }
                     None => (command, None),
                 }
             }
@@ -256,22 +381,145 @@ impl Channel {
                 Task::none(),
                 Some(Event::UserContext(user_context::update(message))),
             ),
-            Message::Topic(message) => (
-                Task::none(),
-                topic::update(message).map(|event| match event {
-                    topic::Event::UserContext(event) => {
-                        Event::UserContext(event)
+            Message::Topic(message) => {
+                let topic = clients
+                    .get_channel_topic(&self.server, &self.target)
+                    .and_then(|topic| topic.content.as_ref())
+                    .map(|content| content.text().into_owned());
+
+                let (task, event) =
+                    topic::update(message, &mut self.topic_editor, topic);
+                let task = task.map(Message::Topic);
+
+                match event {
+                    Some(topic::Event::UserContext(event)) => {
+                        (task, Some(Event::UserContext(event)))
                     }
-                    topic::Event::OpenChannel(channel) => {
-                        Event::OpenBuffers(vec![(
+                    Some(topic::Event::OpenChannel(channel)) => (
+                        task,
+                        Some(Event::OpenBuffers(vec![(
                             Target::Channel(channel),
                             config.actions.buffer.click_channel_name,
-                        )])
+                        )])),
+                    ),
+                    Some(topic::Event::OpenUrl(url)) => {
+                        (task, Some(Event::OpenUrl(url)))
                     }
-                    topic::Event::OpenUrl(url) => Event::OpenUrl(url),
-                }),
-            ),
+                    Some(topic::Event::SetTopic(text)) => {
+                        self.set_topic(text, clients);
+
+                        (
+                            if self.topic_editor.is_none() {
+                                self.focus()
+                            } else {
+                                task
+                            },
+                            None,
+                        )
+                    }
+                    Some(topic::Event::EditorClosed) => (self.focus(), None),
+                    Some(topic::Event::EditModes) => {
+                        self.modes_editor = clients
+                            .get_channel_modes(&self.server, &self.target)
+                            .cloned()
+                            .map(modes::Editor::new);
+
+                        (task, None)
+                    }
+                    None => (task, None),
+                }
+            }
+            Message::Modes(message) => {
+                let current =
+                    clients.get_channel_modes(&self.server, &self.target);
+
+                match modes::update(message, &mut self.modes_editor, current) {
+                    Some(modes::Event::SetModes(modes, args)) => {
+                        let command = command::Irc::Mode(
+                            self.target.to_string(),
+                            Some(modes),
+                            Some(args),
+                        );
+                        let input =
+                            data::Input::command(self.buffer.clone(), command);
+
+                        if let Some(encoded) = input.encoded() {
+                            clients.send(&input.buffer, encoded);
+                        }
+
+                        (self.focus(), None)
+                    }
+                    Some(modes::Event::EditorClosed) => (self.focus(), None),
+                    None => (Task::none(), None),
+                }
+            }
+            Message::BanList(message) => {
+                match ban_list::update(message, &mut self.ban_list) {
+                    Some(ban_list::Event::Refresh) => {
+                        clients.request_bans(&self.server, &self.target);
+
+                        (Task::none(), None)
+                    }
+                    Some(ban_list::Event::Unban(mask)) => {
+                        let command = command::Irc::Mode(
+                            self.target.to_string(),
+                            Some("-b".to_string()),
+                            Some(vec![mask]),
+                        );
+                        let input =
+                            data::Input::command(self.buffer.clone(), command);
+
+                        if let Some(encoded) = input.encoded() {
+                            clients.send(&input.buffer, encoded);
+                        }
+
+                        (Task::none(), None)
+                    }
+                    Some(ban_list::Event::Closed) => (self.focus(), None),
+                    None => (Task::none(), None),
+                }
+            }
+            Message::NickListFilter(text) => {
+                if let Some(filter) = &mut self.nick_list_filter {
+                    filter.text = text;
+                }
+
+                (Task::none(), None)
+            }
+            Message::CloseNickListFilter => {
+                self.nick_list_filter = None;
+
+                (self.focus(), None)
+            }
+        }
+    }
+
+    pub fn toggle_nick_list_filter(&mut self) -> Task<Message> {
+        if self.nick_list_filter.take().is_some() {
+            return self.focus();
         }
+
+        let filter = nick_list::Filter::new();
+        let task = text_input::focus(filter.input_id.clone());
+
+        self.nick_list_filter = Some(filter);
+
+        task
+    }
+
+    /// Opens the ban list, requesting it from the server
+    pub fn show_ban_list(
+        &mut self,
+        clients: &mut data::client::Map,
+    ) -> Task<Message> {
+        clients.request_bans(&self.server, &self.target);
+
+        let panel = ban_list::Panel::new();
+        let task = panel.focus();
+
+        self.ban_list = Some(panel);
+
+        task
     }
 
     pub fn focus(&self) -> Task<Message> {
@@ -280,9 +528,52 @@ impl Channel {
 
     pub fn reset(&mut self) {
         self.input_view.reset();
+        self.topic_editor = None;
+        self.modes_editor = None;
+        self.ban_list = None;
+    }
+
+    /// Sends `text` as the new topic, closing the editor unless it's too
+    /// long for the server
+    fn set_topic(&mut self, text: String, clients: &mut data::client::Map) {
+        let isupport = clients.get_isupport(&self.server);
+
+        if let Err(error) = command::validate_topic(&text, &isupport) {
+            if let Some(editor) = &mut self.topic_editor {
+                editor.set_error(error.to_string());
+            }
+
+            return;
+        }
+
+        let command = command::Irc::Topic(self.target.to_string(), Some(text));
+        let input = data::Input::command(self.buffer.clone(), command);
+
+        if let Some(encoded) = input.encoded() {
+            clients.send(&input.buffer, encoded);
+        }
+
+        self.topic_editor = None;
     }
 }
 
+/// Ops and half-ops can always set the topic, anyone else only if the
+/// channel isn't `+t`. An unknown mode is assumed to be `+t`.
+fn can_edit_topic(
+    clients: &data::client::Map,
+    state: &Channel,
+    our_user: Option<&User>,
+) -> bool {
+    let is_privileged = our_user
+        .is_some_and(|user| user.highest_access_level() >= AccessLevel::HalfOp);
+
+    let is_protected = clients
+        .get_channel_modes(&state.server, &state.target)
+        .is_none_or(|modes| modes.contains('t'));
+
+    is_privileged || !is_protected
+}
+
 fn topic<'a>(
     state: &'a Channel,
     clients: &'a data::client::Map,
@@ -290,6 +581,7 @@ fn topic<'a>(
     our_user: Option<&'a User>,
     settings: Option<&'a buffer::Settings>,
     config: &'a Config,
+    ignores: &'a Ignores,
     theme: &'a Theme,
 ) -> Option<Element<'a, Message>> {
     let topic_enabled = settings
@@ -301,6 +593,13 @@ fn topic<'a>(
         return None;
     }
 
+    if let Some(editor) = &state.modes_editor {
+        return Some(
+            modes::view(editor, &clients.get_isupport(&state.server))
+                .map(Message::Modes),
+        );
+    }
+
     let casemapping = clients.get_casemapping(&state.server);
 
     let topic = clients.get_channel_topic(&state.server, &state.target)?;
@@ -316,7 +615,14 @@ fn topic<'a>(
             config.buffer.channel.topic.max_lines,
             users,
             our_user,
+            can_edit_topic(clients, state, our_user),
+            clients.get_channel_modes(&state.server, &state.target),
+            our_user.is_some_and(|user| {
+                user.highest_access_level() >= AccessLevel::Oper
+            }),
+            state.topic_editor.as_ref(),
             config,
+            ignores,
             theme,
         )
         .map(Message::Topic),
@@ -324,23 +630,57 @@ fn topic<'a>(
 }
 
 mod nick_list {
+    use data::client::metadata::Metadata;
+    use data::ignore::Ignores;
     use data::{Config, Server, User, config, isupport, target};
     use iced::Length;
     use iced::advanced::text;
-    use iced::widget::{Scrollable, column, scrollable};
-    use user_context::Message;
+    use iced::widget::{Scrollable, column, scrollable, text_input};
 
+    use super::Message;
     use crate::buffer::user_context;
-    use crate::widget::{Element, selectable_text};
+    use crate::widget::{Element, key_press, selectable_text};
     use crate::{font, theme};
 
+    #[derive(Debug, Clone)]
+    pub struct Filter {
+        pub input_id: text_input::Id,
+        pub text: String,
+    }
+
+    impl Filter {
+        pub fn new() -> Self {
+            Self {
+                input_id: text_input::Id::unique(),
+                text: String::new(),
+            }
+        }
+    }
+
+    /// Case-insensitive substring match against the nickname, and the
+    /// username and hostname when known
+    fn matches(user: &User, query: &str) -> bool {
+        [
+            Some(user.nickname().as_ref()),
+            user.username(),
+            user.hostname(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|value| value.to_lowercase().contains(query))
+    }
+
     pub fn view<'a>(
         server: &'a Server,
         casemapping: isupport::CaseMap,
         channel: &'a target::Channel,
         users: &'a [User],
         our_user: Option<&'a User>,
+        metadata: Option<&'a Metadata>,
+        filter: Option<&'a Filter>,
+        is_focused: bool,
         config: &'a Config,
+        ignores: &'a Ignores,
     ) -> Element<'a, Message> {
         let nicklist_config = &config.buffer.channel.nicklist;
 
@@ -361,12 +701,26 @@ mod nick_list {
             }
         };
 
-        let content = column(users.iter().map(|user| {
+        let query = filter
+            .map(|filter| filter.text.to_lowercase())
+            .filter(|query| !query.is_empty());
+
+        let users = users.iter().filter(|user| {
+            query.as_ref().is_none_or(|query| matches(user, query))
+        });
+
+        let content = column(users.map(|user| {
+            let profile = metadata.and_then(|metadata| {
+                metadata.profile(user.nickname().as_ref(), casemapping)
+            });
+
             let content = selectable_text(
                 user.display(nicklist_config.show_access_levels),
             )
-            .style(|theme| {
-                theme::selectable_text::nicklist_nickname(theme, config, user)
+            .style(move |theme| {
+                theme::selectable_text::nicklist_nickname(
+                    theme, config, user, profile,
+                )
             })
             .align_x(match nicklist_config.alignment {
                 config::buffer::channel::Alignment::Left => {
@@ -387,16 +741,43 @@ mod nick_list {
                 Some(user),
                 our_user,
                 config,
+                ignores,
                 &config.buffer.channel.nicklist.click,
             )
+            .map(Message::UserContext)
         }));
 
-        Scrollable::new(content)
-            .direction(scrollable::Direction::Vertical(
-                scrollable::Scrollbar::new().width(1).scroller_width(1),
-            ))
-            .width(Length::Shrink)
-            .style(theme::scrollable::hidden)
+        let filter = filter.map(|filter| {
+            let input = text_input("Filter", &filter.text)
+                .id(filter.input_id.clone())
+                .on_input(Message::NickListFilter)
+                .padding([2, 4])
+                .width(Length::Fixed(width))
+                .style(theme::text_input::primary);
+
+            if is_focused {
+                key_press(
+                    input,
+                    key_press::Key::Named(key_press::Named::Escape),
+                    key_press::Modifiers::default(),
+                    Message::CloseNickListFilter,
+                )
+            } else {
+                input.into()
+            }
+        });
+
+        column![]
+            .push_maybe(filter)
+            .push(
+                Scrollable::new(content)
+                    .direction(scrollable::Direction::Vertical(
+                        scrollable::Scrollbar::new().width(1).scroller_width(1),
+                    ))
+                    .width(Length::Shrink)
+                    .style(theme::scrollable::hidden),
+            )
+            .spacing(4)
             .into()
     }
 }
@@ -0,0 +1,189 @@
+use chrono::Local;
+use data::client::Ban;
+use iced::widget::{
+    Scrollable, button, center, column, container, row, scrollable, text,
+    text_input,
+};
+use iced::{Alignment, Length, Task};
+
+use crate::widget::{Element, Text, key_press, selectable_text, tooltip};
+use crate::{icon, theme};
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Refresh,
+    /// Mask of the ban to remove
+    Unban(String),
+    Closed,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    FilterInput(String),
+    Refresh,
+    Unban(String),
+    Close,
+}
+
+/// Ban list shown below the messages
+#[derive(Debug, Clone)]
+pub struct Panel {
+    input_id: text_input::Id,
+    filter: String,
+}
+
+impl Panel {
+    pub fn new() -> Self {
+        Self {
+            input_id: text_input::Id::unique(),
+            filter: String::new(),
+        }
+    }
+
+    pub fn focus<T>(&self) -> Task<T> {
+        text_input::focus(self.input_id.clone())
+    }
+}
+
+pub fn update(message: Message, panel: &mut Option<Panel>) -> Option<Event> {
+    match message {
+        Message::FilterInput(filter) => {
+            if let Some(panel) = panel {
+                panel.filter = filter;
+            }
+
+            None
+        }
+        Message::Refresh => Some(Event::Refresh),
+        Message::Unban(mask) => Some(Event::Unban(mask)),
+        Message::Close => {
+            *panel = None;
+
+            Some(Event::Closed)
+        }
+    }
+}
+
+/// `bans` is `None` until the ban list is received
+pub fn view<'a>(
+    panel: &'a Panel,
+    bans: Option<&'a [Ban]>,
+    can_unban: bool,
+    show_tooltips: bool,
+) -> Element<'a, Message> {
+    let query = panel.filter.to_lowercase();
+
+    let bans = bans.map(|bans| {
+        bans.iter()
+            .filter(|ban| ban.mask.to_lowercase().contains(&query))
+            .collect::<Vec<_>>()
+    });
+
+    let title = match &bans {
+        Some(bans) => format!("Ban list ({})", bans.len()),
+        None => "Ban list".to_string(),
+    };
+
+    let filter = key_press(
+        text_input("Filter by mask", &panel.filter)
+            .id(panel.input_id.clone())
+            .on_input(Message::FilterInput)
+            .padding([2, 4])
+            .style(theme::text_input::primary),
+        key_press::Key::Named(key_press::Named::Escape),
+        key_press::Modifiers::default(),
+        Message::Close,
+    );
+
+    let control = |icon: Text<'a>, message, tooltip_text: &'static str| {
+        tooltip(
+            button(center(icon))
+                .padding(5)
+                .width(22)
+                .height(22)
+                .on_press(message)
+                .style(|theme, status| {
+                    theme::button::secondary(theme, status, false)
+                }),
+            show_tooltips.then_some(tooltip_text),
+            tooltip::Position::Bottom,
+        )
+    };
+
+    let header = row![
+        text(title).style(theme::text::secondary),
+        filter,
+        control(icon::refresh(), Message::Refresh, "Refresh"),
+        control(icon::cancel(), Message::Close, "Close"),
+    ]
+    .spacing(4)
+    .align_y(Alignment::Center);
+
+    let content: Element<'a, Message> = match bans {
+        None => text("Requesting the ban list...")
+            .style(theme::text::secondary)
+            .into(),
+        Some(bans) if bans.is_empty() => text(if query.is_empty() {
+            "No bans"
+        } else {
+            "No bans match the filter"
+        })
+        .style(theme::text::secondary)
+        .into(),
+        Some(bans) => column(bans.into_iter().map(|ban| {
+            let set_by = match (&ban.who, ban.time) {
+                (Some(who), Some(time)) => format!(
+                    "set by {who} at {}",
+                    time.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                ),
+                (Some(who), None) => format!("set by {who}"),
+                (None, Some(time)) => format!(
+                    "set at {}",
+                    time.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                ),
+                (None, None) => String::new(),
+            };
+
+            let unban = tooltip(
+                button(text("-b"))
+                    .padding([2, 5])
+                    .on_press_maybe(
+                        can_unban.then(|| Message::Unban(ban.mask.clone())),
+                    )
+                    .style(|theme, status| {
+                        theme::button::secondary(theme, status, false)
+                    }),
+                show_tooltips.then_some(if can_unban {
+                    "Remove ban"
+                } else {
+                    "Removing bans requires channel operator status"
+                }),
+                tooltip::Position::Left,
+            );
+
+            row![
+                selectable_text(&ban.mask).width(Length::Fill),
+                selectable_text(set_by).style(theme::selectable_text::topic),
+                unban,
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center)
+            .into()
+        }))
+        .spacing(2)
+        .into(),
+    };
+
+    column![
+        header,
+        Scrollable::new(container(content).width(Length::Fill))
+            .direction(scrollable::Direction::Vertical(
+                scrollable::Scrollbar::new().width(1).scroller_width(1),
+            ))
+            .style(theme::scrollable::hidden)
+            .height(Length::Fill),
+    ]
+    .spacing(4)
+    .height(Length::FillPortion(1))
+    .into()
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+
+use data::isupport;
+use data::mode::Modes;
+use iced::widget::{
+    button, checkbox, column, container, horizontal_rule, row, text, text_input,
+};
+use iced::{Alignment, Length};
+
+use crate::theme;
+use crate::widget::{Element, key_press};
+
+/// Modes offered as toggles, when the server supports them
+const FLAGS: [(char, &str); 5] = [
+    ('m', "Moderated"),
+    ('i', "Invite only"),
+    ('t', "Only ops set the topic"),
+    ('n', "No messages from outside"),
+    ('s', "Secret"),
+];
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    /// Modes and arguments of the MODE line to send
+    SetModes(String, Vec<String>),
+    EditorClosed,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Toggle(char, bool),
+    KeyInput(String),
+    LimitInput(String),
+    Submit,
+    Cancel,
+}
+
+/// Modes being changed in place of the topic banner
+#[derive(Debug, Clone)]
+pub struct Editor {
+    modes: Modes,
+    key: String,
+    limit: String,
+    error: Option<&'static str>,
+}
+
+impl Editor {
+    pub fn new(modes: Modes) -> Self {
+        Self {
+            key: modes.arg('k').unwrap_or_default().to_string(),
+            limit: modes.arg('l').unwrap_or_default().to_string(),
+            modes,
+            error: None,
+        }
+    }
+
+    /// Modes set in the editor, or why they can't be
+    fn modes(&self) -> Result<Modes, &'static str> {
+        let mut modes = self.modes.clone();
+
+        let key = self.key.trim();
+
+        if key.is_empty() {
+            modes.unset('k');
+        } else if key.contains(char::is_whitespace) {
+            return Err("The key can't contain spaces");
+        } else {
+            modes.set('k', Some(key.to_string()));
+        }
+
+        let limit = self.limit.trim();
+
+        if limit.is_empty() {
+            modes.unset('l');
+        } else {
+            let limit = limit
+                .parse::<u32>()
+                .ok()
+                .filter(|limit| *limit > 0)
+                .ok_or("The limit must be a positive number")?;
+
+            modes.set('l', Some(limit.to_string()));
+        }
+
+        Ok(modes)
+    }
+}
+
+/// `current` are the modes of the channel, which are changed into the ones of
+/// the editor
+pub fn update(
+    message: Message,
+    editor: &mut Option<Editor>,
+    current: Option<&Modes>,
+) -> Option<Event> {
+    match message {
+        Message::Toggle(letter, is_set) => {
+            if let Some(editor) = editor {
+                if is_set {
+                    editor.modes.set(letter, None);
+                } else {
+                    editor.modes.unset(letter);
+                }
+            }
+
+            None
+        }
+        Message::KeyInput(key) => {
+            if let Some(editor) = editor {
+                editor.key = key;
+                editor.error = None;
+            }
+
+            None
+        }
+        Message::LimitInput(limit) => {
+            if let Some(editor) = editor {
+                editor.limit = limit;
+                editor.error = None;
+            }
+
+            None
+        }
+        Message::Submit => {
+            let modes = match editor.as_ref()?.modes() {
+                Ok(modes) => modes,
+                Err(error) => {
+                    if let Some(editor) = editor {
+                        editor.error = Some(error);
+                    }
+
+                    return None;
+                }
+            };
+
+            *editor = None;
+
+            Some(
+                current
+                    .and_then(|current| current.change_to(&modes))
+                    .map_or(Event::EditorClosed, |(modes, args)| {
+                        Event::SetModes(modes, args)
+                    }),
+            )
+        }
+        Message::Cancel => {
+            *editor = None;
+
+            Some(Event::EditorClosed)
+        }
+    }
+}
+
+/// Only the modes in the server's `CHANMODES` are offered
+pub fn view<'a>(
+    editor: &'a Editor,
+    isupport: &HashMap<isupport::Kind, isupport::Parameter>,
+) -> Element<'a, Message> {
+    let flags = FLAGS
+        .into_iter()
+        .filter(|(letter, _)| {
+            isupport::has_channel_mode(isupport, 'D', *letter)
+        })
+        .map(|(letter, label)| {
+            checkbox(
+                format!("{label} (+{letter})"),
+                editor.modes.contains(letter),
+            )
+            .on_toggle(move |is_set| Message::Toggle(letter, is_set))
+            .into()
+        });
+
+    let key = isupport::has_channel_mode(isupport, 'B', 'k').then(|| {
+        text_input("Key (+k)", &editor.key)
+            .on_input(Message::KeyInput)
+            .on_submit(Message::Submit)
+            .padding([2, 4])
+            .width(Length::Fixed(160.0))
+    });
+
+    let limit = isupport::has_channel_mode(isupport, 'C', 'l').then(|| {
+        text_input("Limit (+l)", &editor.limit)
+            .on_input(Message::LimitInput)
+            .on_submit(Message::Submit)
+            .padding([2, 4])
+            .width(Length::Fixed(100.0))
+    });
+
+    let buttons = row![
+        button(text("Apply"))
+            .padding([2, 8])
+            .style(|theme, status| theme::button::secondary(
+                theme, status, false
+            ))
+            .on_press(Message::Submit),
+        button(text("Cancel"))
+            .padding([2, 8])
+            .style(|theme, status| theme::button::secondary(
+                theme, status, false
+            ))
+            .on_press(Message::Cancel),
+    ]
+    .spacing(4);
+
+    let error = editor
+        .error
+        .map(|error| text(error).style(theme::text::error));
+
+    let panel = column![
+        row(flags).spacing(12).wrap(),
+        row![]
+            .push_maybe(key)
+            .push_maybe(limit)
+            .push(buttons)
+            .spacing(8)
+            .align_y(Alignment::Center),
+    ]
+    .push_maybe(error)
+    .spacing(8);
+
+    column![
+        container(key_press(
+            panel,
+            key_press::Key::Named(key_press::Named::Escape),
+            key_press::Modifiers::default(),
+            Message::Cancel,
+        ))
+        .padding([0, 8]),
+        container(horizontal_rule(1))
+            .width(Length::Fill)
+            .padding([0, 11])
+    ]
+    .spacing(8)
+    .into()
+}
@@ -1,40 +1,110 @@
 use chrono::{DateTime, Utc};
+use data::ignore::Ignores;
+use data::mode::Modes;
 use data::{Config, Server, User, isupport, message, target};
-use iced::Length;
 use iced::widget::{
-    Scrollable, column, container, horizontal_rule, row, scrollable,
+    Scrollable, button, center, column, container, horizontal_rule, row,
+    scrollable, text, text_input,
 };
+use iced::{Length, Task};
 
 use super::user_context;
-use crate::widget::{Element, double_pass, message_content, selectable_text};
-use crate::{Theme, theme};
+use crate::widget::{
+    Element, double_pass, key_press, message_content, selectable_text, tooltip,
+};
+use crate::{Theme, icon, theme};
 
 #[derive(Debug, Clone)]
 pub enum Event {
     UserContext(user_context::Event),
     OpenChannel(target::Channel),
     OpenUrl(String),
+    SetTopic(String),
+    EditorClosed,
+    EditModes,
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     UserContext(user_context::Message),
     Link(message::Link),
+    Edit,
+    EditorInput(String),
+    SubmitEdit,
+    CancelEdit,
+    EditModes,
 }
 
-pub fn update(message: Message) -> Option<Event> {
+/// Topic being edited in place of the banner
+#[derive(Debug, Clone)]
+pub struct Editor {
+    input_id: text_input::Id,
+    text: String,
+    error: Option<String>,
+}
+
+impl Editor {
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+}
+
+/// `topic` is the current topic, used to pre-fill the editor
+pub fn update(
+    message: Message,
+    editor: &mut Option<Editor>,
+    topic: Option<String>,
+) -> (Task<Message>, Option<Event>) {
     match message {
-        Message::UserContext(message) => {
-            Some(Event::UserContext(user_context::update(message)))
-        }
+        Message::UserContext(message) => (
+            Task::none(),
+            Some(Event::UserContext(user_context::update(message))),
+        ),
         Message::Link(message::Link::Channel(channel)) => {
-            Some(Event::OpenChannel(channel))
+            (Task::none(), Some(Event::OpenChannel(channel)))
+        }
+        Message::Link(message::Link::Url(url)) => {
+            (Task::none(), Some(Event::OpenUrl(url)))
+        }
+        Message::Link(message::Link::User(user)) => (
+            Task::none(),
+            Some(Event::UserContext(user_context::Event::InsertNickname(
+                user.nickname().to_owned(),
+            ))),
+        ),
+        Message::Link(message::Link::GoToMessage(..)) => (Task::none(), None),
+        Message::Edit => {
+            let input_id = text_input::Id::unique();
+            let task = text_input::focus(input_id.clone());
+
+            *editor = Some(Editor {
+                input_id,
+                text: topic.unwrap_or_default(),
+                error: None,
+            });
+
+            (task, None)
+        }
+        Message::EditorInput(text) => {
+            if let Some(editor) = editor {
+                editor.text = text;
+                editor.error = None;
+            }
+
+            (Task::none(), None)
+        }
+        Message::SubmitEdit => (
+            Task::none(),
+            editor
+                .as_ref()
+                .map(|editor| Event::SetTopic(editor.text.clone())),
+        ),
+        Message::CancelEdit => {
+            *editor = None;
+
+            (Task::none(), Some(Event::EditorClosed))
         }
-        Message::Link(message::Link::Url(url)) => Some(Event::OpenUrl(url)),
-        Message::Link(message::Link::User(user)) => Some(Event::UserContext(
-            user_context::Event::InsertNickname(user.nickname().to_owned()),
-        )),
-        Message::Link(message::Link::GoToMessage(..)) => None,
+        Message::EditModes => (Task::none(), Some(Event::EditModes)),
     }
 }
 
@@ -48,9 +118,18 @@ pub fn view<'a>(
     max_lines: u16,
     users: &'a [User],
     our_user: Option<&'a User>,
+    can_edit: bool,
+    modes: Option<&'a Modes>,
+    can_edit_modes: bool,
+    editor: Option<&'a Editor>,
     config: &'a Config,
+    ignores: &'a Ignores,
     theme: &'a Theme,
 ) -> Element<'a, Message> {
+    if let Some(editor) = editor {
+        return editor_view(editor);
+    }
+
     let set_by =
         who.and_then(|who| User::try_from(who).ok())
             .and_then(|user| {
@@ -74,6 +153,7 @@ pub fn view<'a>(
                         Some(user),
                         our_user,
                         config,
+                        ignores,
                         &config.buffer.nickname.click,
                     )
                 } else {
@@ -116,14 +196,89 @@ pub fn view<'a>(
     ))
     .style(theme::scrollable::hidden);
 
+    let edit_button = tooltip(
+        button(center(icon::topic()))
+            .padding(5)
+            .width(22)
+            .height(22)
+            .on_press_maybe(can_edit.then_some(Message::Edit))
+            .style(|theme, status| {
+                theme::button::secondary(theme, status, false)
+            }),
+        config.tooltips.then_some(if can_edit {
+            "Edit topic"
+        } else {
+            "Editing the topic requires channel privileges"
+        }),
+        tooltip::Position::Left,
+    );
+
+    let modes_button = modes.map(|modes| {
+        tooltip(
+            button(text(modes.to_string()).style(theme::text::secondary))
+                .padding([2, 5])
+                .height(22)
+                .on_press_maybe(can_edit_modes.then_some(Message::EditModes))
+                .style(|theme, status| {
+                    theme::button::secondary(theme, status, false)
+                }),
+            config.tooltips.then_some(if can_edit_modes {
+                "Change modes"
+            } else {
+                "Changing modes requires channel operator status"
+            }),
+            tooltip::Position::Left,
+        )
+    });
+
     // Use double pass to limit layout to `max_lines` of text
     column![
-        double_pass(
+        row![double_pass(
             container(column((0..max_lines).map(|_| "".into())))
                 .width(Length::Fill)
                 .padding(padding()),
             column![container(scrollable)].width(Length::Fill),
-        ),
+        )]
+        .push_maybe(modes_button)
+        .push(edit_button)
+        .spacing(4),
+        container(horizontal_rule(1))
+            .width(Length::Fill)
+            .padding([0, 11])
+    ]
+    .spacing(8)
+    .into()
+}
+
+fn editor_view(editor: &Editor) -> Element<'_, Message> {
+    let input = text_input("Topic", &editor.text)
+        .id(editor.input_id.clone())
+        .on_input(Message::EditorInput)
+        .on_submit(Message::SubmitEdit)
+        .padding([2, 4])
+        .style(if editor.error.is_some() {
+            theme::text_input::error
+        } else {
+            theme::text_input::primary
+        });
+
+    let error = editor
+        .error
+        .as_ref()
+        .map(|error| text(error).style(theme::text::error));
+
+    column![
+        container(
+            column![key_press(
+                input,
+                key_press::Key::Named(key_press::Named::Escape),
+                key_press::Modifiers::default(),
+                Message::CancelEdit,
+            )]
+            .push_maybe(error)
+            .spacing(4)
+        )
+        .padding(padding()),
         container(horizontal_rule(1))
             .width(Length::Fill)
             .padding([0, 11])
@@ -2,18 +2,31 @@ use std::path::PathBuf;
 
 use data::{Config, file_transfer};
 use iced::widget::{
-    Scrollable, button, center, column, container, scrollable, text,
+    Scrollable, button, center, column, container, horizontal_space, row,
+    scrollable, text,
 };
-use iced::{Length, Task};
+use iced::{Length, Task, clipboard};
 
 use crate::widget::{Element, Text};
-use crate::{icon, theme};
+use crate::{icon, opener, theme};
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Approve(file_transfer::Id),
     SavePathSelected(file_transfer::Id, Option<PathBuf>),
+    /// Removes the transfer from the list, ending it if it's underway
     Clear(file_transfer::Id),
+    ClearFinished,
+    Cancel(file_transfer::Id),
+    Retry(file_transfer::Id),
+    OpenFile(file_transfer::Id),
+    OpenFolder(file_transfer::Id),
+    CopySha256(String),
+}
+
+pub enum Event {
+    /// A transfer was started over
+    NewTransfer(file_transfer::manager::Event),
 }
 
 pub fn view<'a>(
@@ -41,18 +54,44 @@ pub fn view<'a>(
         .spacing(1)
         .padding([0, 2]);
 
+    let header = file_transfers.list().any(is_finished).then(|| {
+        row![
+            horizontal_space(),
+            button(text("Clear finished"))
+                .padding([2, 6])
+                .style(|theme, status| {
+                    theme::button::secondary(theme, status, false)
+                })
+                .on_press(Message::ClearFinished),
+        ]
+        .padding([4, 4])
+    });
+
     container(
-        Scrollable::new(column)
-            .direction(scrollable::Direction::Vertical(
-                scrollable::Scrollbar::new().width(1).scroller_width(1),
-            ))
-            .style(theme::scrollable::hidden),
+        column![]
+            .push_maybe(header)
+            .push(
+                Scrollable::new(column)
+                    .direction(scrollable::Direction::Vertical(
+                        scrollable::Scrollbar::new().width(1).scroller_width(1),
+                    ))
+                    .style(theme::scrollable::hidden),
+            )
+            .spacing(2),
     )
     .width(Length::Fill)
     .height(Length::Fill)
     .into()
 }
 
+fn is_finished(transfer: &file_transfer::FileTransfer) -> bool {
+    matches!(
+        transfer.status,
+        file_transfer::Status::Completed { .. }
+            | file_transfer::Status::Failed { .. }
+    )
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct FileTransfers;
 
@@ -66,7 +105,7 @@ impl FileTransfers {
         message: Message,
         file_transfers: &mut file_transfer::Manager,
         config: &Config,
-    ) -> Task<Message> {
+    ) -> (Task<Message>, Option<Event>) {
         match message {
             Message::Approve(id) => {
                 if let Some(transfer) = file_transfers.get(&id).cloned() {
@@ -74,23 +113,31 @@ impl FileTransfers {
                         Some(save_directory) => {
                             let file_save_directory =
                                 save_directory.join(transfer.filename);
-                            return Task::done(Message::SavePathSelected(
-                                id,
-                                Some(file_save_directory),
-                            ));
+                            return (
+                                Task::done(Message::SavePathSelected(
+                                    id,
+                                    Some(file_save_directory),
+                                )),
+                                None,
+                            );
                         }
                         None => {
-                            return Task::perform(
-                                async move {
-                                    rfd::AsyncFileDialog::new()
-                                        .set_file_name(transfer.filename)
-                                        .save_file()
-                                        .await
-                                        .map(|handle| {
-                                            handle.path().to_path_buf()
-                                        })
-                                },
-                                move |path| Message::SavePathSelected(id, path),
+                            return (
+                                Task::perform(
+                                    async move {
+                                        rfd::AsyncFileDialog::new()
+                                            .set_file_name(transfer.filename)
+                                            .save_file()
+                                            .await
+                                            .map(|handle| {
+                                                handle.path().to_path_buf()
+                                            })
+                                    },
+                                    move |path| {
+                                        Message::SavePathSelected(id, path)
+                                    },
+                                ),
+                                None,
                             );
                         }
                     }
@@ -104,9 +151,38 @@ impl FileTransfers {
             Message::Clear(id) => {
                 file_transfers.remove(&id);
             }
+            Message::ClearFinished => {
+                file_transfers.clear_finished();
+            }
+            Message::Cancel(id) => {
+                file_transfers.cancel(&id);
+            }
+            Message::Retry(id) => {
+                return (
+                    Task::none(),
+                    file_transfers
+                        .retry(&id, config.proxy.clone())
+                        .map(Event::NewTransfer),
+                );
+            }
+            Message::OpenFile(id) => {
+                if let Some(path) = file_transfers.path(&id) {
+                    return (opener::open_path(path.to_path_buf()), None);
+                }
+            }
+            Message::OpenFolder(id) => {
+                if let Some(folder) =
+                    file_transfers.path(&id).and_then(|path| path.parent())
+                {
+                    return (opener::open_path(folder.to_path_buf()), None);
+                }
+            }
+            Message::CopySha256(sha256) => {
+                return (clipboard::write(sha256), None);
+            }
         }
 
-        Task::none()
+        (Task::none(), None)
     }
 }
 
@@ -115,14 +191,42 @@ mod transfer_row {
 
     use bytesize::ByteSize;
     use data::file_transfer::{self, FileTransfer};
-    use iced::widget::{column, container, progress_bar, row, text};
+    use iced::widget::{button, column, container, progress_bar, row, text};
     use iced::{Length, alignment, padding};
 
     use super::Message;
     use crate::buffer::file_transfers::row_button;
-    use crate::widget::Element;
+    use crate::widget::{Element, context_menu};
     use crate::{icon, theme};
 
+    #[derive(Debug, Clone, Copy)]
+    enum Entry {
+        OpenFile,
+        OpenFolder,
+        Cancel,
+        Retry,
+        Remove,
+    }
+
+    impl Entry {
+        fn list(status: &file_transfer::Status) -> Vec<Self> {
+            match status {
+                file_transfer::Status::PendingApproval => vec![Entry::Remove],
+                file_transfer::Status::PendingReverseConfirmation
+                | file_transfer::Status::Queued
+                | file_transfer::Status::Ready
+                | file_transfer::Status::Resuming { .. }
+                | file_transfer::Status::Active { .. } => vec![Entry::Cancel],
+                file_transfer::Status::Completed { .. } => {
+                    vec![Entry::OpenFile, Entry::OpenFolder, Entry::Remove]
+                }
+                file_transfer::Status::Failed { .. } => {
+                    vec![Entry::Retry, Entry::Remove]
+                }
+            }
+        }
+    }
+
     pub fn view<'a>(
         transfer: &FileTransfer,
         idx: usize,
@@ -175,6 +279,14 @@ mod transfer_row {
                     .style(theme::text::secondary),
                 )
             }
+            file_transfer::Status::Resuming { offset } => container(
+                text(format!(
+                    "Resuming at {:.0}%. Waiting for {} to accept.",
+                    *offset as f64 / transfer.size as f64 * 100.0,
+                    transfer.remote_user
+                ))
+                .style(theme::text::secondary),
+            ),
             file_transfer::Status::Active {
                 transferred,
                 elapsed,
@@ -184,7 +296,11 @@ mod transfer_row {
                 {
                     String::default()
                 } else {
-                    let bytes_per_second = *transferred / elapsed.as_secs();
+                    // Bytes received before resuming don't count toward the
+                    // speed
+                    let bytes_per_second = transferred
+                        .saturating_sub(transfer.offset)
+                        / elapsed.as_secs();
                     let transfer_speed = ByteSize::b(bytes_per_second);
 
                     let remaining_bytes =
@@ -226,7 +342,11 @@ mod transfer_row {
                     .spacing(0),
                 )
             }
-            file_transfer::Status::Completed { elapsed, sha256 } => {
+            file_transfer::Status::Completed {
+                elapsed,
+                sha256,
+                verification,
+            } => {
                 let mut formatter = timeago::Formatter::new();
                 formatter
                     .ago("")
@@ -239,12 +359,41 @@ mod transfer_row {
                     file_transfer::Direction::Received => "from",
                 };
 
+                let verification =
+                    verification.as_ref().map(
+                        |verification| match verification {
+                            file_transfer::Verification::Matched(file) => {
+                                text(format!("{file} matches its checksum"))
+                                    .style(theme::text::success)
+                            }
+                            file_transfer::Verification::Mismatched(file) => {
+                                text(format!(
+                                    "{file} doesn't match its checksum"
+                                ))
+                                .style(theme::text::error)
+                            }
+                        },
+                    );
+
                 container(
-                    text(format!(
-                        "Completed {} {} in {elapsed}. sha256: {sha256}",
-                        direction, transfer.remote_user,
-                    ))
-                    .style(theme::text::secondary),
+                    column![
+                        text(format!(
+                            "Completed {} {} in {elapsed}.",
+                            direction, transfer.remote_user,
+                        ))
+                        .style(theme::text::secondary),
+                        row![
+                            text(format!("sha256: {sha256}"))
+                                .style(theme::text::secondary),
+                            button(icon::copy().size(theme::TEXT_SIZE - 2.0))
+                                .padding([0, 4])
+                                .style(theme::button::bare)
+                                .on_press(Message::CopySha256(sha256.clone())),
+                        ]
+                        .spacing(2)
+                        .align_y(iced::Alignment::Center),
+                    ]
+                    .push_maybe(verification),
                 )
             }
             file_transfer::Status::Failed { error } => container(
@@ -277,20 +426,29 @@ mod transfer_row {
             }
             file_transfer::Status::PendingReverseConfirmation
             | file_transfer::Status::Queued
-            | file_transfer::Status::Ready => {
+            | file_transfer::Status::Ready
+            | file_transfer::Status::Resuming { .. }
+            | file_transfer::Status::Active { .. } => {
                 buttons = buttons.push(row_button(
                     icon::cancel(),
-                    Message::Clear(transfer.id),
+                    Message::Cancel(transfer.id),
                 ));
             }
-            file_transfer::Status::Active { .. }
-            | file_transfer::Status::Completed { .. } => {
+            file_transfer::Status::Completed { .. } => {
+                buttons = buttons.push(row_button(
+                    icon::popout(),
+                    Message::OpenFile(transfer.id),
+                ));
                 buttons = buttons.push(row_button(
                     icon::cancel(),
                     Message::Clear(transfer.id),
                 ));
             }
             file_transfer::Status::Failed { .. } => {
+                buttons = buttons.push(row_button(
+                    icon::refresh(),
+                    Message::Retry(transfer.id),
+                ));
                 buttons = buttons.push(row_button(
                     icon::cancel(),
                     Message::Clear(transfer.id),
@@ -302,12 +460,40 @@ mod transfer_row {
             .spacing(6)
             .align_y(iced::Alignment::Center);
 
-        container(row)
+        let base = container(row)
             .padding(padding::top(6).bottom(6).right(4).left(8))
             .width(Length::Fill)
             .align_y(alignment::Vertical::Center)
-            .style(move |theme| theme::container::table(theme, idx))
-            .into()
+            .style(move |theme| theme::container::table(theme, idx));
+
+        let id = transfer.id;
+
+        context_menu(
+            context_menu::MouseButton::default(),
+            base,
+            Entry::list(&transfer.status),
+            move |entry, length| {
+                let (content, message) = match entry {
+                    Entry::OpenFile => ("Open file", Message::OpenFile(id)),
+                    Entry::OpenFolder => {
+                        ("Open containing folder", Message::OpenFolder(id))
+                    }
+                    Entry::Cancel => ("Cancel", Message::Cancel(id)),
+                    Entry::Retry => ("Retry", Message::Retry(id)),
+                    Entry::Remove => ("Remove from list", Message::Clear(id)),
+                };
+
+                button(text(content))
+                    .width(length)
+                    .padding(5)
+                    .style(|theme, status| {
+                        theme::button::primary(theme, status, false)
+                    })
+                    .on_press(message)
+                    .into()
+            },
+        )
+        .into()
     }
 }
 
@@ -83,8 +83,16 @@ pub fn view<'a>(
                             .brackets
                             .format(user.display(with_access_levels)),
                     )
-                    .style(|theme| {
-                        theme::selectable_text::nickname(theme, config, user)
+                    .style(move |theme| {
+                        theme::selectable_text::nickname(
+                            theme,
+                            config,
+                            user,
+                            clients.get_user_profile(
+                                server,
+                                user.nickname().as_ref(),
+                            ),
+                        )
                     });
 
                     let casemapping = clients.get_casemapping(server);
@@ -98,6 +106,7 @@ pub fn view<'a>(
                         current_user,
                         None,
                         config,
+                        history.ignores(),
                         &config.buffer.nickname.click,
                     )
                     .map(scroll_view::Message::UserContext);
@@ -105,6 +114,7 @@ pub fn view<'a>(
                     let text = message_content::with_context(
                         &message.content,
                         casemapping,
+                        clients.get_metadata(server),
                         theme,
                         scroll_view::Message::Link,
                         theme::selectable_text::default,
@@ -124,6 +134,7 @@ pub fn view<'a>(
                                     current_user,
                                     length,
                                     config,
+                                    history.ignores(),
                                 )
                                 .map(scroll_view::Message::UserContext),
                             _ => row![].into(),
@@ -254,6 +265,13 @@ impl Highlights {
                     scroll_view::Event::ImagePreview(path, url) => {
                         Some(Event::ImagePreview(path, url))
                     }
+                    scroll_view::Event::Reply(_) => None,
+                    // Only offered in channels
+                    scroll_view::Event::SetActionStyle(..) => None,
+                    // Only offered in the logs buffer
+                    scroll_view::Event::GoToServer(_) => None,
+                    // Only offered in channels and queries
+                    scroll_view::Event::Translate(..) => None,
                 });
 
                 (command.map(Message::ScrollView), event)
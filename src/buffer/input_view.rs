@@ -1,20 +1,23 @@
 use std::time::Duration;
 
-use data::buffer::{self, Autocomplete, Upstream};
+use chrono::{DateTime, Local, Utc};
+use data::buffer::{self, Autocomplete, ReplyQuote, Upstream};
 use data::dashboard::BufferAction;
 use data::history::{self, ReadMarker};
 use data::input::{self, Cache, RawInput};
-use data::message::server_time;
-use data::target::Target;
+use data::message::{self, server_time};
+use data::target::{self, Target};
 use data::user::Nick;
-use data::{Config, client, command};
-use iced::Task;
-use iced::widget::{column, container, text, text_input};
+use data::{Config, client, command, emoji, isupport, scheduled};
+use iced::widget::{
+    button, column, container, horizontal_space, row, text, text_input,
+};
+use iced::{Length, Task};
 use tokio::time;
 
 use self::completion::Completion;
-use crate::theme;
-use crate::widget::{Element, anchored_overlay, key_press};
+use crate::widget::{Element, anchored_overlay, key_press, message_content};
+use crate::{Theme, icon, theme};
 
 mod completion;
 
@@ -25,6 +28,24 @@ pub enum Event {
     OpenBuffers {
         targets: Vec<(Target, BufferAction)>,
     },
+    Theme {
+        name: Option<String>,
+    },
+    TopicHistory {
+        channel: target::Channel,
+        count: usize,
+    },
+    Layout {
+        layout: command::Layout,
+    },
+    BanList {
+        channel: target::Channel,
+    },
+    /// Asks before sending `text`, which is long or has many lines
+    ConfirmSend {
+        buffer: Upstream,
+        text: String,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -39,14 +60,32 @@ pub enum Message {
         buffer: Upstream,
         command: command::Irc,
     },
+    PreviewLink(message::Link),
+    SendSteps {
+        buffer: Upstream,
+        steps: Vec<Step>,
+    },
+    CancelCompose,
+    EditScheduled(u64),
+    CancelScheduled(u64),
+}
+
+/// What's left to do for a custom command
+#[derive(Debug, Clone)]
+pub enum Step {
+    Send(data::Input),
+    Delay(u64),
 }
 
 pub fn view<'a>(
     state: &'a State,
     cache: Cache<'a>,
+    scheduled: Vec<&'a scheduled::Pending>,
     buffer_focused: bool,
     disabled: bool,
+    casemapping: isupport::CaseMap,
     config: &Config,
+    theme: &'a Theme,
 ) -> Element<'a, Message> {
     let style = if state.error.is_some() {
         theme::text_input::error
@@ -54,7 +93,14 @@ pub fn view<'a>(
         theme::text_input::primary
     };
 
-    let mut text_input = text_input("Send message...", cache.text)
+    let placeholder = match cache.compose {
+        Some(input::Compose::Notice(nick)) => {
+            format!("Send notice to {nick}...")
+        }
+        Some(input::Compose::Reply(_)) | None => "Send message...".to_string(),
+    };
+
+    let mut text_input = text_input(&placeholder, cache.text)
         .on_submit(Message::Send)
         .id(state.input_id.clone())
         .padding(8)
@@ -97,8 +143,120 @@ pub fn view<'a>(
         );
     }
 
+    // Only shown while the input matches what the preview was made from
+    let preview = state
+        .preview
+        .as_ref()
+        .filter(|preview| preview.input == cache.text)
+        .map(|preview| {
+            container(message_content(
+                &preview.content,
+                casemapping,
+                theme,
+                Message::PreviewLink,
+                theme::selectable_text::default,
+                config,
+            ))
+            .padding(8)
+            .width(Length::Fill)
+            .style(theme::container::tooltip)
+        });
+
+    // Always in the row, so the input keeps its state when it's shown
+    let chip: Element<'a, Message> = match cache.compose {
+        Some(compose) => container(
+            text(compose.label())
+                .size(theme::TEXT_SIZE - 2.0)
+                .style(theme::text::secondary),
+        )
+        .padding([2, 6])
+        .style(theme::container::compose_chip)
+        .into(),
+        None => horizontal_space().width(0).into(),
+    };
+    let input = row![chip, input]
+        .spacing(if cache.compose.is_some() { 6 } else { 0 })
+        .align_y(iced::Alignment::Center);
+
+    let reply = cache.compose.and_then(|compose| match compose {
+        input::Compose::Reply(reply) => Some(reply),
+        input::Compose::Notice(_) => None,
+    });
+    let reply = reply.map(|reply| {
+        container(
+            row![
+                text(reply.quote(&config.buffer))
+                    .style(theme::text::secondary)
+                    .shaping(text::Shaping::Advanced)
+                    .width(Length::Fill),
+                button(icon::cancel())
+                    .padding([2, 4])
+                    .style(theme::button::bare)
+                    .on_press(Message::CancelCompose),
+            ]
+            .spacing(8),
+        )
+        .padding(8)
+        .width(Length::Fill)
+        .style(theme::container::tooltip)
+    });
+
+    let scheduled = (!scheduled.is_empty()).then(|| {
+        container(
+            column![text("Scheduled").style(theme::text::tertiary)]
+                .extend(scheduled.into_iter().map(|pending| {
+                    let time = pending
+                        .send_at
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string();
+                    let is_editing =
+                        state.editing_scheduled == Some(pending.id);
+
+                    row![
+                        text(time).style(theme::text::secondary),
+                        text(&pending.text)
+                            .style(move |theme| {
+                                if is_editing {
+                                    theme::text::secondary(theme)
+                                } else {
+                                    theme::text::none(theme)
+                                }
+                            })
+                            .shaping(text::Shaping::Advanced)
+                            .width(Length::Fill),
+                    ]
+                    .push_maybe(pending.is_held.then(|| {
+                        text("waiting to connect").style(theme::text::secondary)
+                    }))
+                    .push(
+                        button(text("Edit"))
+                            .padding([2, 4])
+                            .style(theme::button::bare)
+                            .on_press(Message::EditScheduled(pending.id)),
+                    )
+                    .push(
+                        button(icon::cancel())
+                            .padding([2, 4])
+                            .style(theme::button::bare)
+                            .on_press(Message::CancelScheduled(pending.id)),
+                    )
+                    .spacing(8)
+                    .align_y(iced::Alignment::Center)
+                    .into()
+                }))
+                .spacing(4),
+        )
+        .padding(8)
+        .width(Length::Fill)
+        .style(theme::container::tooltip)
+    });
+
     let overlay = column![]
         .spacing(4)
+        .push_maybe(scheduled)
+        .push_maybe(reply)
+        .push_maybe(preview)
         .push_maybe(state.completion.view(cache.text, config))
         .push_maybe(state.error.as_deref().map(error));
 
@@ -118,6 +276,16 @@ pub struct State {
     error: Option<String>,
     completion: Completion,
     selected_history: Option<usize>,
+    preview: Option<Preview>,
+    /// Scheduled message being edited, replaced once scheduled again
+    editing_scheduled: Option<u64>,
+}
+
+/// Input rendered with its formatting applied
+#[derive(Debug, Clone)]
+struct Preview {
+    input: String,
+    content: message::Content,
 }
 
 impl Default for State {
@@ -133,6 +301,8 @@ impl State {
             error: None,
             completion: Completion::default(),
             selected_history: None,
+            preview: None,
+            editing_scheduled: None,
         }
     }
 
@@ -161,12 +331,15 @@ impl State {
                     .unwrap_or_default();
                 let channels = clients.get_channels(buffer.server());
                 let isupport = clients.get_isupport(buffer.server());
+                let known_channels =
+                    history.get_unique_channels(buffer.server());
 
                 self.completion.process(
                     &input,
                     users,
                     &history.get_last_seen(buffer),
                     channels,
+                    &known_channels,
                     current_channel,
                     &isupport,
                     config,
@@ -175,12 +348,37 @@ impl State {
                 let input =
                     self.completion.complete_emoji(&input).unwrap_or(input);
 
-                if let Err(error) = input::parse(
+                let parsed = input::parse(
                     buffer.clone(),
                     config.buffer.text_input.auto_format,
+                    config.buffer.text_input.markdown_links,
                     &input,
                     &clients.get_isupport(buffer.server()),
-                ) {
+                );
+
+                self.preview = parsed
+                    .as_ref()
+                    .ok()
+                    .filter(|_| config.buffer.text_input.preview)
+                    .and_then(|parsed| match parsed {
+                        input::Parsed::Input(parsed) => parsed.formatted(),
+                        input::Parsed::Internal(_) => None,
+                    })
+                    .map(|formatted| Preview {
+                        input: input.clone(),
+                        content: message::parse_fragments(
+                            formatted.to_string(),
+                        ),
+                    });
+
+                if let Ok(input::Parsed::Internal(command::Internal::Theme(
+                    Some(name),
+                ))) = &parsed
+                {
+                    self.error = unknown_theme(name, config);
+                }
+
+                if let Err(error) = parsed {
                     if match error {
                         input::Error::ExceedsByteLimit { .. } => true,
                         input::Error::Command(
@@ -208,6 +406,16 @@ impl State {
                         input::Error::Command(
                             command::Error::NotPositiveInteger,
                         ) => true,
+                        input::Error::Command(
+                            command::Error::InvalidCtcpTarget(_)
+                            | command::Error::InvalidCtcpCommand(_),
+                        ) => true,
+                        input::Error::Command(
+                            command::Error::InvalidTarget(_),
+                        ) => true,
+                        input::Error::Command(
+                            command::Error::InvalidScheduleTime(_),
+                        ) => true,
                     } {
                         self.error = Some(error.to_string());
                     }
@@ -242,13 +450,117 @@ impl State {
                 } else if !raw_input.is_empty() {
                     self.completion.reset();
 
+                    // Shortcodes written as words of their own are sent as
+                    // their emoji
+                    let replaced =
+                        config.buffer.text_input.emoji_replacement.then(|| {
+                            emoji::replace_shortcodes(
+                                raw_input,
+                                config.buffer.emojis.skin_tone,
+                            )
+                        });
+                    let raw_input = replaced.as_deref().unwrap_or(raw_input);
+
+                    // Pastes are sent a line at a time, as messages can't
+                    // contain line breaks, and asked about first when long
+                    let confirm_send = &config.buffer.text_input.confirm_send;
+
+                    if !raw_input.starts_with('/')
+                        && (input::lines(raw_input).len() > 1
+                            || confirm_send.is_exceeded_by(raw_input))
+                    {
+                        let inputs = match input::parse_lines(
+                            buffer.clone(),
+                            config.buffer.text_input.auto_format,
+                            config.buffer.text_input.markdown_links,
+                            raw_input,
+                        ) {
+                            Ok(inputs) => inputs,
+                            Err(error) => {
+                                self.error = Some(error.to_string());
+                                return (Task::none(), None);
+                            }
+                        };
+
+                        if confirm_send.is_exceeded_by(raw_input) {
+                            return (
+                                Task::none(),
+                                Some(Event::ConfirmSend {
+                                    buffer: buffer.clone(),
+                                    text: raw_input.to_owned(),
+                                }),
+                            );
+                        }
+
+                        history
+                            .record_input_history(buffer, raw_input.to_owned());
+
+                        return Self::send_steps(
+                            buffer,
+                            inputs.into_iter().map(Step::Send).collect(),
+                            clients,
+                            history,
+                            config,
+                        );
+                    }
+
+                    let server = buffer.server().to_string();
+                    let nick = clients.nickname(buffer.server());
+
+                    let expanded = command::custom::expand(
+                        &config.buffer.commands,
+                        raw_input,
+                        command::custom::Context {
+                            server: &server,
+                            channel: buffer
+                                .channel()
+                                .map(|channel| channel.as_str()),
+                            query: match buffer {
+                                Upstream::Query(_, query) => {
+                                    Some(query.as_str())
+                                }
+                                Upstream::Server(_) | Upstream::Channel(..) => {
+                                    None
+                                }
+                            },
+                            nick: nick.as_ref().map(AsRef::as_ref),
+                        },
+                    );
+
+                    match expanded {
+                        Ok(Some(lines)) => {
+                            let raw_input = raw_input.to_owned();
+
+                            return self.send_custom_command(
+                                buffer, raw_input, &lines, clients, history,
+                                config,
+                            );
+                        }
+                        Ok(None) => {}
+                        Err(error) => {
+                            self.error = Some(error.to_string());
+                            return (Task::none(), None);
+                        }
+                    }
+
                     // Parse input
                     let input = match input::parse(
                         buffer.clone(),
                         config.buffer.text_input.auto_format,
+                        config.buffer.text_input.markdown_links,
                         raw_input,
                         &clients.get_isupport(buffer.server()),
                     ) {
+                        Ok(input::Parsed::Internal(
+                            command::Internal::Schedule(send_at, text),
+                        )) => {
+                            let raw_input = raw_input.to_owned();
+
+                            return self.schedule(
+                                buffer, raw_input, send_at, text, clients,
+                                history, config,
+                            );
+                        }
                         Ok(input::Parsed::Internal(command)) => {
                             history.record_input_history(
                                 buffer,
@@ -408,9 +720,50 @@ impl State {
                                 command::Internal::Delay(_) => {
                                     return (Task::none(), None);
                                 }
+                                command::Internal::Theme(name) => {
+                                    return (
+                                        Task::none(),
+                                        Some(Event::Theme { name }),
+                                    );
+                                }
+                                command::Internal::TopicHistory(
+                                    channel,
+                                    count,
+                                ) => {
+                                    return (
+                                        Task::none(),
+                                        Some(Event::TopicHistory {
+                                            channel,
+                                            count,
+                                        }),
+                                    );
+                                }
+                                // Handled before the input is recorded
+                                command::Internal::Schedule(..) => {
+                                    return (Task::none(), None);
+                                }
+                                command::Internal::Layout(layout) => {
+                                    return (
+                                        Task::none(),
+                                        Some(Event::Layout { layout }),
+                                    );
+                                }
+                                command::Internal::BanList(channel) => {
+                                    return (
+                                        Task::none(),
+                                        Some(Event::BanList { channel }),
+                                    );
+                                }
                             }
                         }
-                        Ok(input::Parsed::Input(input)) => input,
+                        Ok(input::Parsed::Input(input)) => {
+                            if let Some(error) = unsupported(&input, clients) {
+                                self.error = Some(error.to_string());
+                                return (Task::none(), None);
+                            }
+
+                            input
+                        }
                         Err(error) => {
                             self.error = Some(error.to_string());
                             return (Task::none(), None);
@@ -419,73 +772,48 @@ impl State {
 
                     history.record_input_history(buffer, raw_input.to_owned());
 
-                    if let Some(encoded) = input.encoded() {
-                        let sent_time = server_time(&encoded);
-
-                        clients.send(buffer, encoded);
-
-                        if config.buffer.mark_as_read.on_message_sent {
-                            let chantypes =
-                                clients.get_chantypes(buffer.server());
-                            let statusmsg =
-                                clients.get_statusmsg(buffer.server());
-                            let casemapping =
-                                clients.get_casemapping(buffer.server());
-
-                            if let Some(targets) =
-                                input.targets(chantypes, statusmsg, casemapping)
-                            {
-                                for target in targets {
-                                    clients.send_markread(
-                                        buffer.server(),
-                                        target,
-                                        ReadMarker::from_date_time(sent_time),
-                                    );
-                                }
-                            }
-                        }
-                    }
-
-                    let mut history_task = Task::none();
-
-                    if let Some(nick) = clients.nickname(buffer.server()) {
-                        let mut user = nick.to_owned().into();
-                        let mut channel_users = &[][..];
-
-                        let chantypes = clients.get_chantypes(buffer.server());
-                        let statusmsg = clients.get_statusmsg(buffer.server());
-                        let casemapping =
-                            clients.get_casemapping(buffer.server());
+                    let compose = if input.is_message() {
+                        history.take_compose(buffer)
+                    } else {
+                        None
+                    };
 
-                        // Resolve our attributes if sending this message in a channel
-                        if let buffer::Upstream::Channel(server, channel) =
-                            &buffer
+                    // Tagged as a reply if possible, otherwise preceded by a
+                    // quote of the message replied to
+                    let (input, quote) = match compose {
+                        Some(input::Compose::Reply(input::Reply {
+                            id: Some(id),
+                            ..
+                        })) if clients
+                            .supports_message_tags(buffer.server()) =>
                         {
-                            channel_users =
-                                clients.get_channel_users(server, channel);
-
-                            if let Some(user_with_attributes) = clients
-                                .resolve_user_attributes(server, channel, &user)
-                            {
-                                user = user_with_attributes.clone();
-                            }
+                            (input.with_reply(id), None)
                         }
-
-                        history_task = Task::batch(
-                            history
-                                .record_input_message(
-                                    input,
-                                    user,
-                                    channel_users,
-                                    chantypes,
-                                    statusmsg,
-                                    casemapping,
-                                    config,
+                        Some(input::Compose::Notice(nick)) => {
+                            (input.with_notice(&nick), None)
+                        }
+                        Some(input::Compose::Reply(reply)) => (
+                            input,
+                            buffer.target().map(|target| {
+                                data::Input::command(
+                                    buffer.clone(),
+                                    command::Irc::Msg(
+                                        target.to_string(),
+                                        reply.quote(&config.buffer),
+                                    ),
                                 )
-                                .into_iter()
-                                .map(Task::future),
-                        );
-                    }
+                            }),
+                        ),
+                        None => (input, None),
+                    };
+
+                    let history_task = Task::batch(
+                        quote.into_iter().chain(Some(input)).map(|input| {
+                            Self::send_input(
+                                buffer, input, clients, history, config,
+                            )
+                        }),
+                    );
 
                     (Task::none(), Some(Event::InputSent { history_task }))
                 } else {
@@ -495,7 +823,7 @@ impl State {
             Message::Tab(reverse) => {
                 let input = history.input(buffer).text;
 
-                if let Some(entry) = self.completion.tab(reverse) {
+                if let Some(entry) = self.completion.tab(reverse, config) {
                     let chantypes = clients.get_chantypes(buffer.server());
                     let new_input =
                         entry.complete_input(input, chantypes, config);
@@ -514,18 +842,12 @@ impl State {
 
                 self.completion.reset();
 
-                if !cache.history.is_empty() {
-                    if let Some(index) = self.selected_history.as_mut() {
-                        *index = (*index + 1).min(cache.history.len() - 1);
-                    } else {
-                        self.selected_history = Some(0);
-                    }
+                // What's typed, kept as the draft, recalls only the sent
+                // messages starting with it
+                if let Some(index) = cache.older(self.selected_history) {
+                    self.selected_history = Some(index);
 
-                    let new_input = cache
-                        .history
-                        .get(self.selected_history.unwrap())
-                        .unwrap()
-                        .clone();
+                    let new_input = cache.history[index].clone();
 
                     let users = buffer
                         .channel()
@@ -535,12 +857,15 @@ impl State {
                         .unwrap_or_default();
                     let channels = clients.get_channels(buffer.server());
                     let isupport = clients.get_isupport(buffer.server());
+                    let known_channels =
+                        history.get_unique_channels(buffer.server());
 
                     self.completion.process(
                         &new_input,
                         users,
                         &history.get_last_seen(buffer),
                         channels,
+                        &known_channels,
                         current_channel,
                         &isupport,
                         config,
@@ -561,14 +886,11 @@ impl State {
 
                 self.completion.reset();
 
-                if let Some(index) = self.selected_history.as_mut() {
-                    let new_input = if *index == 0 {
-                        self.selected_history = None;
-                        cache.draft.to_string()
-                    } else {
-                        *index -= 1;
-                        let new_input =
-                            cache.history.get(*index).unwrap().clone();
+                if let Some(index) = self.selected_history {
+                    let new_input = if let Some(index) = cache.newer(index) {
+                        self.selected_history = Some(index);
+
+                        let new_input = cache.history[index].clone();
 
                         let users = buffer
                             .channel()
@@ -579,17 +901,23 @@ impl State {
                             .unwrap_or_default();
                         let channels = clients.get_channels(buffer.server());
                         let isupport = clients.get_isupport(buffer.server());
+                        let known_channels =
+                            history.get_unique_channels(buffer.server());
 
                         self.completion.process(
                             &new_input,
                             users,
                             &history.get_last_seen(buffer),
                             channels,
+                            &known_channels,
                             current_channel,
                             &isupport,
                             config,
                         );
                         new_input
+                    } else {
+                        self.selected_history = None;
+                        cache.draft.to_string()
                     };
 
                     return self
@@ -599,8 +927,12 @@ impl State {
                 (Task::none(), None)
             }
             // Capture escape so that closing context menu or commands/emojis picker
-            // does not defocus input
-            Message::Escape => (Task::none(), None),
+            // does not defocus input. It also stops composing a reply or notice
+            Message::Escape => {
+                history.take_compose(buffer);
+
+                (Task::none(), None)
+            }
             Message::SendCommand { buffer, command } => {
                 let input =
                     data::Input::command(buffer.clone(), command).encoded();
@@ -612,7 +944,96 @@ impl State {
 
                 (Task::none(), None)
             }
+            // Links aren't opened from the preview
+            Message::PreviewLink(_) => (Task::none(), None),
+            Message::SendSteps { buffer, steps } => {
+                Self::send_steps(&buffer, steps, clients, history, config)
+            }
+            Message::EditScheduled(id) => {
+                let Some(pending) = history.scheduled().get(id) else {
+                    return (Task::none(), None);
+                };
+
+                let text = pending.edit_input();
+                self.editing_scheduled = Some(id);
+
+                let (task, event) =
+                    self.on_completion(buffer, history, text, true);
+
+                (Task::batch(vec![self.focus(), task]), event)
+            }
+            Message::CancelScheduled(id) => {
+                history.cancel_scheduled(id);
+
+                if self.editing_scheduled == Some(id) {
+                    self.editing_scheduled = None;
+                }
+
+                (Task::none(), None)
+            }
+            Message::CancelCompose => {
+                history.take_compose(buffer);
+
+                (Task::none(), None)
+            }
+        }
+    }
+
+    /// Starts a reply to a message from `buffer`. It's sent tagged as a reply
+    /// if the server supports message tags, or quoted as configured otherwise.
+    pub fn reply(
+        &self,
+        reply: input::Reply,
+        buffer: &buffer::Upstream,
+        clients: &client::Map,
+        history: &mut history::Manager,
+        config: &Config,
+    ) -> Task<Message> {
+        let is_tagged = reply.id.is_some()
+            && clients.supports_message_tags(buffer.server());
+
+        match config.buffer.text_input.reply_quote {
+            ReplyQuote::Nickname if !is_tagged => {
+                let text = history.input(buffer).text;
+                let prefix = format!("{}: ", reply.nick);
+
+                if !text.starts_with(&prefix) {
+                    let text = format!("{prefix}{text}");
+
+                    history.record_text(RawInput {
+                        buffer: buffer.clone(),
+                        text: text.clone(),
+                    });
+                    history.record_draft(RawInput {
+                        buffer: buffer.clone(),
+                        text,
+                    });
+                }
+            }
+            ReplyQuote::Nickname | ReplyQuote::Line => {
+                history.record_compose(buffer, input::Compose::Reply(reply));
+            }
         }
+
+        Task::batch(vec![
+            self.focus(),
+            text_input::move_cursor_to_end(self.input_id.clone()),
+        ])
+    }
+
+    /// Starts a NOTICE to `nick` from `buffer`, sent with the next message
+    pub fn notice(
+        &self,
+        nick: Nick,
+        buffer: &buffer::Upstream,
+        history: &mut history::Manager,
+    ) -> Task<Message> {
+        history.record_compose(buffer, input::Compose::Notice(nick));
+
+        Task::batch(vec![
+            self.focus(),
+            text_input::move_cursor_to_end(self.input_id.clone()),
+        ])
     }
 
     fn on_completion(
@@ -637,6 +1058,229 @@ impl State {
         (text_input::move_cursor_to_end(self.input_id.clone()), None)
     }
 
+    /// Schedules `text` to be sent at `send_at`, replacing the scheduled
+    /// message being edited. It's checked as if it were sent now.
+    fn schedule(
+        &mut self,
+        buffer: &buffer::Upstream,
+        raw_input: String,
+        send_at: DateTime<Utc>,
+        text: String,
+        clients: &mut client::Map,
+        history: &mut history::Manager,
+        config: &Config,
+    ) -> (Task<Message>, Option<Event>) {
+        match input::parse(
+            buffer.clone(),
+            config.buffer.text_input.auto_format,
+            config.buffer.text_input.markdown_links,
+            &text,
+            &clients.get_isupport(buffer.server()),
+        ) {
+            Ok(input::Parsed::Input(input)) => {
+                if let Some(error) = unsupported(&input, clients) {
+                    self.error = Some(error.to_string());
+                    return (Task::none(), None);
+                }
+            }
+            Ok(input::Parsed::Internal(_)) => {
+                self.error = Some(format!("{text} can't be scheduled"));
+                return (Task::none(), None);
+            }
+            Err(error) => {
+                self.error = Some(error.to_string());
+                return (Task::none(), None);
+            }
+        }
+
+        if let Some(id) = self.editing_scheduled.take() {
+            history.cancel_scheduled(id);
+        }
+
+        history.record_input_history(buffer, raw_input);
+        history.schedule(buffer.clone(), send_at, text);
+
+        (Task::none(), None)
+    }
+
+    fn send_custom_command(
+        &mut self,
+        buffer: &buffer::Upstream,
+        raw_input: String,
+        lines: &[String],
+        clients: &mut client::Map,
+        history: &mut history::Manager,
+        config: &Config,
+    ) -> (Task<Message>, Option<Event>) {
+        let mut steps = vec![];
+
+        // Validate every line before anything is sent
+        for line in lines {
+            match input::parse(
+                buffer.clone(),
+                config.buffer.text_input.auto_format,
+                config.buffer.text_input.markdown_links,
+                line,
+                &clients.get_isupport(buffer.server()),
+            ) {
+                Ok(input::Parsed::Input(input)) => {
+                    if let Some(error) = unsupported(&input, clients) {
+                        self.error = Some(format!("{line}: {error}"));
+                        return (Task::none(), None);
+                    }
+
+                    steps.push(Step::Send(input));
+                }
+                Ok(input::Parsed::Internal(command::Internal::Delay(
+                    seconds,
+                ))) => steps.push(Step::Delay(seconds)),
+                Ok(input::Parsed::Internal(_)) => {
+                    self.error = Some(format!(
+                        "{line} can't be used in custom commands"
+                    ));
+                    return (Task::none(), None);
+                }
+                Err(error) => {
+                    self.error = Some(format!("{line}: {error}"));
+                    return (Task::none(), None);
+                }
+            }
+        }
+
+        history.record_input_history(buffer, raw_input);
+
+        Self::send_steps(buffer, steps, clients, history, config)
+    }
+
+    /// Sends inputs until a delay, after which the rest are sent
+    fn send_steps(
+        buffer: &buffer::Upstream,
+        steps: Vec<Step>,
+        clients: &mut client::Map,
+        history: &mut history::Manager,
+        config: &Config,
+    ) -> (Task<Message>, Option<Event>) {
+        let mut steps = steps.into_iter();
+        let mut history_tasks = vec![];
+        let mut task = Task::none();
+
+        while let Some(step) = steps.next() {
+            match step {
+                Step::Send(input) => history_tasks.push(Self::send_input(
+                    buffer, input, clients, history, config,
+                )),
+                Step::Delay(seconds) => {
+                    let buffer = buffer.clone();
+                    let steps = steps.by_ref().collect();
+
+                    task = Task::perform(
+                        time::sleep(Duration::from_secs(seconds)),
+                        move |()| Message::SendSteps { buffer, steps },
+                    );
+
+                    break;
+                }
+            }
+        }
+
+        (
+            task,
+            Some(Event::InputSent {
+                history_task: Task::batch(history_tasks),
+            }),
+        )
+    }
+
+    /// Sends `input` to `buffer`'s server, recording it in its history. A
+    /// message too long for a line is sent as several.
+    pub fn send_input(
+        buffer: &buffer::Upstream,
+        input: data::Input,
+        clients: &mut client::Map,
+        history: &mut history::Manager,
+        config: &Config,
+    ) -> Task<history::manager::Message> {
+        // Resolve our attributes if sending this message in a channel
+        let user = clients.nickname(buffer.server()).map(|nick| {
+            let user = data::User::from(nick.to_owned());
+
+            match buffer {
+                buffer::Upstream::Channel(server, channel) => clients
+                    .resolve_user_attributes(server, channel, &user)
+                    .cloned()
+                    .unwrap_or(user),
+                buffer::Upstream::Server(_) | buffer::Upstream::Query(..) => {
+                    user
+                }
+            }
+        });
+
+        let inputs = match clients.source(buffer.server()) {
+            Some(source) => input.split(&source),
+            None => vec![input],
+        };
+
+        let mut history_tasks = vec![];
+
+        for input in inputs {
+            if let Some(encoded) = input.encoded() {
+                let sent_time = server_time(&encoded);
+
+                clients.send(buffer, encoded);
+
+                if config.buffer.mark_as_read.on_message_sent {
+                    let chantypes = clients.get_chantypes(buffer.server());
+                    let statusmsg = clients.get_statusmsg(buffer.server());
+                    let casemapping = clients.get_casemapping(buffer.server());
+
+                    if let Some(targets) =
+                        input.targets(chantypes, statusmsg, casemapping)
+                    {
+                        for target in targets {
+                            clients.send_markread(
+                                buffer.server(),
+                                target,
+                                ReadMarker::from_date_time(sent_time),
+                            );
+                        }
+                    }
+                }
+            }
+
+            if let Some(user) = &user {
+                let channel_users = match buffer {
+                    buffer::Upstream::Channel(server, channel) => {
+                        clients.get_channel_users(server, channel)
+                    }
+                    buffer::Upstream::Server(_)
+                    | buffer::Upstream::Query(..) => &[],
+                };
+
+                let chantypes = clients.get_chantypes(buffer.server());
+                let statusmsg = clients.get_statusmsg(buffer.server());
+                let casemapping = clients.get_casemapping(buffer.server());
+
+                history_tasks.extend(
+                    history
+                        .record_input_message(
+                            input,
+                            user.clone(),
+                            channel_users,
+                            chantypes,
+                            statusmsg,
+                            casemapping,
+                            clients.get_bouncer_services(buffer.server()),
+                            config,
+                        )
+                        .into_iter()
+                        .map(Task::future),
+                );
+            }
+        }
+
+        Task::batch(history_tasks)
+    }
+
     pub fn focus(&self) -> Task<Message> {
         let input_id = self.input_id.clone();
 
@@ -653,6 +1297,7 @@ impl State {
         self.error = None;
         self.completion = Completion::default();
         self.selected_history = None;
+        self.preview = None;
     }
 
     pub fn insert_user(
@@ -693,3 +1338,32 @@ impl State {
         self.completion.close_picker()
     }
 }
+
+/// Why the server won't accept `input`, for commands needing a capability it
+/// hasn't enabled
+fn unsupported(
+    input: &data::Input,
+    clients: &client::Map,
+) -> Option<&'static str> {
+    match input.irc_command() {
+        Some(command::Irc::SetName(_))
+            if !clients.supports_setname(input.server()) =>
+        {
+            Some("server doesn't support changing your real name (setname)")
+        }
+        _ => None,
+    }
+}
+
+/// Error for a theme name which no known theme starts with, checked while
+/// it's typed
+fn unknown_theme(name: &str, config: &Config) -> Option<String> {
+    let prefix = name.to_lowercase();
+
+    (!config
+        .appearance
+        .all
+        .iter()
+        .any(|theme| theme.name.to_lowercase().starts_with(&prefix)))
+    .then(|| format!("no theme named {name}"))
+}
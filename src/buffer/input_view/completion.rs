@@ -7,7 +7,7 @@ use chrono::{DateTime, Utc};
 use data::buffer::{OrderBy, SkinTone, SortDirection};
 use data::isupport::{self, find_target_limit};
 use data::user::{Nick, User};
-use data::{Config, target};
+use data::{Config, emoji, target};
 use iced::Length;
 use iced::widget::{column, container, row, text, tooltip};
 use itertools::{Either, Itertools};
@@ -18,6 +18,8 @@ use crate::widget::{Element, double_pass};
 
 const MAX_SHOWN_COMMAND_ENTRIES: usize = 5;
 const MAX_SHOWN_EMOJI_ENTRIES: usize = 8;
+const CTCP_COMMANDS: &[&str] =
+    &["ACTION", "CLIENTINFO", "PING", "SOURCE", "TIME", "VERSION"];
 
 #[derive(Debug, Clone, Default)]
 pub struct Completion {
@@ -38,6 +40,7 @@ impl Completion {
         users: &[User],
         last_seen: &HashMap<Nick, DateTime<Utc>>,
         channels: &[target::Channel],
+        known_channels: &[&target::Channel],
         current_channel: Option<&target::Channel>,
         isupport: &HashMap<isupport::Kind, isupport::Parameter>,
         config: &Config,
@@ -53,6 +56,15 @@ impl Completion {
                 isupport::CaseMap::default()
             };
 
+        let chanmodes: &[isupport::ChannelMode] =
+            if let Some(isupport::Parameter::CHANMODES(chanmodes)) =
+                isupport.get(&isupport::Kind::CHANMODES)
+            {
+                chanmodes
+            } else {
+                &[]
+            };
+
         if is_command {
             self.commands.process(input, isupport);
 
@@ -66,7 +78,9 @@ impl Completion {
                     users,
                     last_seen,
                     channels,
+                    known_channels,
                     current_channel,
+                    chanmodes,
                     config,
                 );
             }
@@ -93,7 +107,9 @@ impl Completion {
                 users,
                 last_seen,
                 channels,
+                known_channels,
                 current_channel,
+                chanmodes,
                 config,
             );
 
@@ -118,7 +134,21 @@ impl Completion {
         }
     }
 
-    pub fn tab(&mut self, reverse: bool) -> Option<Entry> {
+    pub fn tab(&mut self, reverse: bool, config: &Config) -> Option<Entry> {
+        // Complete the highlighted command, after which its arguments complete
+        if let Some(command) = self.commands.select() {
+            return Some(Entry::Command(command));
+        }
+
+        // Shift+Tab shows the skin tones of the highlighted emoji, if any
+        if reverse && self.emojis.toggle_skin_tones(config) {
+            return None;
+        }
+
+        if let Some(emoji) = self.emojis.select(config) {
+            return Some(Entry::Emoji(emoji));
+        }
+
         if self.commands.tab(reverse) {
             return None;
         }
@@ -179,7 +209,10 @@ impl Completion {
             self.commands = Commands::Idle;
 
             return true;
-        } else if matches!(self.emojis, Emojis::Selecting { .. }) {
+        } else if matches!(
+            self.emojis,
+            Emojis::Selecting { .. } | Emojis::SelectingSkinTone { .. }
+        ) {
             self.emojis = Emojis::Idle;
 
             return true;
@@ -205,7 +238,14 @@ impl Entry {
     ) -> String {
         match self {
             Entry::Command(command) => {
-                format!("/{}", command.title.to_lowercase())
+                let title = command.title.to_lowercase();
+
+                // Ready to type the arguments the command requires
+                if command.args.iter().any(|arg| !arg.optional) {
+                    format!("/{title} ")
+                } else {
+                    format!("/{title}")
+                }
             }
             Entry::Text {
                 next,
@@ -309,6 +349,34 @@ impl Commands {
                     subcommands: None,
                 }
             },
+            // LINKS
+            {
+                Command {
+                    title: "LINKS",
+                    args: vec![],
+                    subcommands: None,
+                }
+            },
+            // MAP
+            {
+                Command {
+                    title: "MAP",
+                    args: vec![],
+                    subcommands: None,
+                }
+            },
+            // ADMIN
+            {
+                Command {
+                    title: "ADMIN",
+                    args: vec![Arg {
+                        text: "server",
+                        optional: true,
+                        tooltip: None,
+                    }],
+                    subcommands: None,
+                }
+            },
             // QUIT
             {
                 Command {
@@ -508,6 +576,90 @@ impl Commands {
                     subcommands: None,
                 }
             },
+            // THEME
+            {
+                Command {
+                    title: "THEME",
+                    args: vec![Arg {
+                        text: "name",
+                        optional: true,
+                        tooltip: Some(String::from(
+                            "picks from a list of themes when left out",
+                        )),
+                    }],
+                    subcommands: None,
+                }
+            },
+            // SCHEDULE
+            {
+                Command {
+                    title: "SCHEDULE",
+                    args: vec![
+                        Arg {
+                            text: "time",
+                            optional: false,
+                            tooltip: Some(String::from(
+                                "e.g. 2024-05-01T09:00 or 09:00 in your timezone,\
+                               \nor 2024-05-01T09:00+02:00 with an offset",
+                            )),
+                        },
+                        Arg {
+                            text: "message",
+                            optional: false,
+                            tooltip: None,
+                        },
+                    ],
+                    subcommands: None,
+                }
+            },
+            // IN
+            {
+                Command {
+                    title: "IN",
+                    args: vec![
+                        Arg {
+                            text: "delay",
+                            optional: false,
+                            tooltip: Some(String::from(
+                                "e.g. 2h, 1h30m or 45s",
+                            )),
+                        },
+                        Arg {
+                            text: "message",
+                            optional: false,
+                            tooltip: None,
+                        },
+                    ],
+                    subcommands: None,
+                }
+            },
+            // LAYOUT
+            {
+                Command {
+                    title: "LAYOUT",
+                    args: vec![
+                        Arg {
+                            text: "action",
+                            optional: false,
+                            tooltip: Some(String::from("save, load or delete")),
+                        },
+                        Arg {
+                            text: "name",
+                            optional: false,
+                            tooltip: None,
+                        },
+                    ],
+                    subcommands: None,
+                }
+            },
+            // BANLIST
+            {
+                Command {
+                    title: "BANLIST",
+                    args: vec![],
+                    subcommands: None,
+                }
+            },
             // HOP
             {
                 Command {
@@ -737,8 +889,16 @@ impl Commands {
                 let content = |width| {
                     column(entries.iter().map(|(index, command)| {
                         let selected = Some(*index) == *highlighted;
-                        let content =
+                        let title =
                             text(format!("/{}", command.title.to_lowercase()));
+                        let summary = command
+                            .summary()
+                            .filter(|_| config.buffer.commands.show_description)
+                            .map(|summary| {
+                                text(summary).style(theme::text::secondary)
+                            });
+                        let content =
+                            row![title].push_maybe(summary).spacing(12);
 
                         Element::from(
                             container(content)
@@ -787,6 +947,12 @@ pub struct Command {
 }
 
 impl Command {
+    /// The first sentence of the description, shown while picking a command
+    fn summary(&self) -> Option<&'static str> {
+        self.description()
+            .and_then(|description| description.split(". ").next())
+    }
+
     fn description(&self) -> Option<&'static str> {
         Some(match self.title.to_lowercase().as_str() {
             "away" => {
@@ -833,6 +999,11 @@ impl Command {
                 "Request the name and version of <nick>'s IRC client"
             }
             "hop" => "Parts the current channel and joins a new one",
+            "theme" => "Switch theme and save it to the config file",
+            "schedule" => "Send a message at a later time",
+            "in" => "Send a message after a delay",
+            "layout" => "Save, load or delete a named layout of the panes",
+            "banlist" => "Show the ban list of the current channel",
 
             _ => return None,
         })
@@ -1007,20 +1178,192 @@ impl Text {
         users: &[User],
         last_seen: &HashMap<Nick, DateTime<Utc>>,
         channels: &[target::Channel],
+        known_channels: &[&target::Channel],
         current_channel: Option<&target::Channel>,
+        chanmodes: &[isupport::ChannelMode],
         config: &Config,
     ) {
-        if !self.process_channels(
-            input,
-            casemapping,
-            channels,
-            current_channel,
-            config,
-        ) {
+        if !self.process_ctcp_commands(input)
+            && !self.process_themes(input, config)
+            && !self.process_join(
+                input,
+                casemapping,
+                channels,
+                known_channels,
+                config,
+            )
+            && !self.process_modes(input, current_channel, chanmodes)
+            && !self.process_channels(
+                input,
+                casemapping,
+                channels,
+                current_channel,
+                config,
+            )
+        {
             self.process_users(input, casemapping, users, last_seen, config);
         }
     }
 
+    /// Completes the command of `/ctcp <nick> <command>`
+    fn process_ctcp_commands(&mut self, input: &str) -> bool {
+        let mut words = input.split(' ');
+
+        let (Some(command), Some(_), Some(rest), None) =
+            (words.next(), words.next(), words.next(), words.next())
+        else {
+            return false;
+        };
+
+        if !command.eq_ignore_ascii_case("/ctcp") || rest.is_empty() {
+            return false;
+        }
+
+        let ctcp_command = rest.to_uppercase();
+
+        self.selected = None;
+        self.prompt = rest.to_string();
+        self.filtered = CTCP_COMMANDS
+            .iter()
+            .filter(|command| command.starts_with(&ctcp_command))
+            .map(ToString::to_string)
+            .collect();
+
+        true
+    }
+
+    /// Completes the name of `/theme <name>`
+    fn process_themes(&mut self, input: &str, config: &Config) -> bool {
+        let mut words = input.split(' ');
+
+        let (Some(command), Some(rest), None) =
+            (words.next(), words.next(), words.next())
+        else {
+            return false;
+        };
+
+        if !command.eq_ignore_ascii_case("/theme") || rest.is_empty() {
+            return false;
+        }
+
+        let name = rest.to_lowercase();
+
+        self.selected = None;
+        self.prompt = rest.to_string();
+        self.filtered = config
+            .appearance
+            .all
+            .iter()
+            .map(|theme| theme.name.clone())
+            .filter(|theme| theme.to_lowercase().starts_with(&name))
+            .sorted_by_key(|theme| theme.to_lowercase())
+            .collect();
+
+        true
+    }
+
+    /// Completes the last of the channels of `/join <channels>`, from channels
+    /// which are joined or have been seen in history
+    fn process_join(
+        &mut self,
+        input: &str,
+        casemapping: isupport::CaseMap,
+        channels: &[target::Channel],
+        known_channels: &[&target::Channel],
+        config: &Config,
+    ) -> bool {
+        let autocomplete = &config.buffer.text_input.autocomplete;
+        let mut words = input.split(' ');
+
+        let (Some(command), Some(rest), None) =
+            (words.next(), words.next(), words.next())
+        else {
+            return false;
+        };
+
+        if !is_command(command, &["join", "j"]) {
+            return false;
+        }
+
+        // Channels before the last one are kept as typed
+        let (joining, last) = rest
+            .rfind(',')
+            .map_or(("", rest), |index| rest.split_at(index + 1));
+        let channel = casemapping.normalize(last);
+
+        self.selected = None;
+        self.prompt = rest.to_string();
+        self.filtered = channels
+            .iter()
+            .chain(known_channels.iter().copied())
+            .unique_by(|channel| channel.as_normalized_str())
+            .filter(|known| known.as_normalized_str().starts_with(&channel))
+            .sorted_by(|a, b| match autocomplete.sort_direction {
+                SortDirection::Asc => {
+                    a.as_normalized_str().cmp(b.as_normalized_str())
+                }
+                SortDirection::Desc => {
+                    b.as_normalized_str().cmp(a.as_normalized_str())
+                }
+            })
+            .map(|channel| format!("{joining}{channel}"))
+            .collect();
+
+        true
+    }
+
+    /// Completes the channel of `/mode <target>` with the current channel,
+    /// and the modes of `/mode <target> <modestring>` with those the server
+    /// lists in CHANMODES
+    fn process_modes(
+        &mut self,
+        input: &str,
+        current_channel: Option<&target::Channel>,
+        chanmodes: &[isupport::ChannelMode],
+    ) -> bool {
+        let mut words = input.split(' ');
+
+        let (Some(command), Some(target), modestring, None) =
+            (words.next(), words.next(), words.next(), words.next())
+        else {
+            return false;
+        };
+
+        if !is_command(command, &["mode", "m"]) {
+            return false;
+        }
+
+        let Some(modestring) = modestring else {
+            let Some(channel) = current_channel.filter(|_| target.is_empty())
+            else {
+                return false;
+            };
+
+            self.selected = None;
+            self.prompt = String::new();
+            self.filtered = vec![channel.to_string()];
+
+            return true;
+        };
+
+        let sign = if modestring.starts_with('-') {
+            '-'
+        } else {
+            '+'
+        };
+
+        self.selected = None;
+        self.prompt = modestring.to_string();
+        self.filtered = chanmodes
+            .iter()
+            .flat_map(|chanmode| chanmode.modes.chars())
+            .map(|mode| format!("{sign}{mode}"))
+            .filter(|mode| mode.starts_with(modestring))
+            .collect();
+
+        true
+    }
+
     fn process_users(
         &mut self,
         input: &str,
@@ -1032,7 +1375,13 @@ impl Text {
         let autocomplete = &config.buffer.text_input.autocomplete;
         let (_, rest) = input.rsplit_once(' ').unwrap_or(("", input));
 
-        if rest.is_empty() {
+        // The target of `/msg` completes before any of it is typed
+        let is_msg_target = matches!(
+            input.split(' ').collect::<Vec<_>>().as_slice(),
+            [command, _] if is_command(command, &["msg", "query"])
+        );
+
+        if rest.is_empty() && !is_msg_target {
             *self = Self::default();
             return;
         }
@@ -2080,6 +2429,12 @@ enum Emojis {
         highlighted: Option<usize>,
         filtered: Vec<&'static str>,
     },
+    /// The highlighted emoji of `previous` in each of its skin tones
+    SelectingSkinTone {
+        highlighted: Option<usize>,
+        skin_tones: Vec<&'static str>,
+        previous: Box<Emojis>,
+    },
     Selected {
         emoji: &'static str,
     },
@@ -2108,9 +2463,10 @@ impl Emojis {
             .flatten()
             .map(str::to_lowercase)
         {
-            if let Some(emoji) =
-                pick_emoji(&shortcode, config.buffer.emojis.skin_tone)
-            {
+            if let Some(emoji) = emoji::from_shortcode(
+                &shortcode,
+                config.buffer.emojis.skin_tone,
+            ) {
                 *self = Emojis::Selected { emoji };
 
                 return;
@@ -2149,33 +2505,103 @@ impl Emojis {
     }
 
     fn select(&mut self, config: &Config) -> Option<String> {
-        if let Self::Selecting {
-            highlighted: Some(index),
-            filtered,
-        } = self
-        {
-            if let Some(shortcode) = filtered.get(*index).copied() {
+        match self {
+            Self::Selecting {
+                highlighted: Some(index),
+                filtered,
+            } => {
+                let shortcode = filtered.get(*index).copied()?;
+
+                *self = Self::Idle;
+
+                emoji::from_shortcode(shortcode, config.buffer.emojis.skin_tone)
+                    .map(ToString::to_string)
+            }
+            Self::SelectingSkinTone {
+                highlighted: Some(index),
+                skin_tones,
+                ..
+            } => {
+                let emoji = skin_tones.get(*index).copied()?;
+
                 *self = Self::Idle;
 
-                return pick_emoji(shortcode, config.buffer.emojis.skin_tone)
-                    .map(ToString::to_string);
+                Some(emoji.to_string())
             }
+            _ => None,
         }
+    }
 
-        None
+    /// Shows the skin tones of the highlighted emoji, or goes back to the
+    /// emojis when they're shown
+    fn toggle_skin_tones(&mut self, config: &Config) -> bool {
+        match self {
+            Self::Selecting {
+                highlighted: Some(index),
+                filtered,
+            } => {
+                let Some(shortcode) = filtered.get(*index).copied() else {
+                    return false;
+                };
+
+                let skin_tones =
+                    emoji::from_shortcode(shortcode, SkinTone::Default)
+                        .map(emoji::skin_tones)
+                        .unwrap_or_default();
+
+                if skin_tones.is_empty() {
+                    return false;
+                }
+
+                // Starting from the configured skin tone
+                let preferred = emoji::from_shortcode(
+                    shortcode,
+                    config.buffer.emojis.skin_tone,
+                );
+                let highlighted = skin_tones
+                    .iter()
+                    .position(|emoji| Some(*emoji) == preferred)
+                    .unwrap_or_default();
+
+                *self = Self::SelectingSkinTone {
+                    highlighted: Some(highlighted),
+                    skin_tones,
+                    previous: Box::new(std::mem::take(self)),
+                };
+
+                true
+            }
+            Self::SelectingSkinTone { previous, .. } => {
+                let previous = std::mem::take(previous.as_mut());
+
+                *self = previous;
+
+                true
+            }
+            _ => false,
+        }
     }
 
     fn tab(&mut self, reverse: bool) -> bool {
-        if let Self::Selecting {
-            highlighted,
-            filtered,
-        } = self
-        {
-            selecting_tab(highlighted, filtered, reverse);
+        match self {
+            Self::Selecting {
+                highlighted,
+                filtered,
+            } => {
+                selecting_tab(highlighted, filtered, reverse);
 
-            true
-        } else {
-            false
+                true
+            }
+            Self::SelectingSkinTone {
+                highlighted,
+                skin_tones,
+                ..
+            } => {
+                selecting_tab(highlighted, skin_tones, reverse);
+
+                true
+            }
+            _ => false,
         }
     }
 
@@ -2185,6 +2611,36 @@ impl Emojis {
     ) -> Option<Element<'a, Message>> {
         match self {
             Self::Idle | Self::Selected { .. } => None,
+            Self::SelectingSkinTone {
+                highlighted,
+                skin_tones,
+                ..
+            } => {
+                let entries =
+                    skin_tones.iter().enumerate().map(|(index, emoji)| {
+                        let selected = Some(index) == *highlighted;
+
+                        Element::from(
+                            container(
+                                text(*emoji).shaping(text::Shaping::Advanced),
+                            )
+                            .style(if selected {
+                                theme::container::primary_background_hover
+                            } else {
+                                theme::container::none
+                            })
+                            .padding(6),
+                        )
+                    });
+
+                Some(
+                    container(row(entries))
+                        .padding(4)
+                        .style(theme::container::tooltip)
+                        .width(Length::Shrink)
+                        .into(),
+                )
+            }
             Self::Selecting {
                 highlighted,
                 filtered,
@@ -2212,7 +2668,7 @@ impl Emojis {
                         let selected = Some(*index) == *highlighted;
                         let content = text(format!(
                             "{} :{}:",
-                            pick_emoji(
+                            emoji::from_shortcode(
                                 shortcode,
                                 config.buffer.emojis.skin_tone
                             )
@@ -2255,19 +2711,6 @@ struct FilteredShortcode {
     shortcode: &'static str,
 }
 
-fn pick_emoji(shortcode: &str, skin_tone: SkinTone) -> Option<&'static str> {
-    emojis::get_by_shortcode(shortcode).map(|emoji| {
-        if let Some(emoji_with_skin_tone) =
-            emoji.with_skin_tone(skin_tone.into())
-        {
-            emoji_with_skin_tone
-        } else {
-            emoji
-        }
-        .as_str()
-    })
-}
-
 fn replace_last_word_with_emoji(input: &str, emoji: &str) -> String {
     let mut words: Vec<_> = input.split(' ').collect();
 
@@ -2278,6 +2721,13 @@ fn replace_last_word_with_emoji(input: &str, emoji: &str) -> String {
     words.join(" ")
 }
 
+/// Whether `word` runs the command with any of `names`
+fn is_command(word: &str, names: &[&str]) -> bool {
+    word.strip_prefix('/').is_some_and(|command| {
+        names.iter().any(|name| command.eq_ignore_ascii_case(name))
+    })
+}
+
 fn selecting_tab<T>(
     highlighted: &mut Option<usize>,
     filtered: &[T],
@@ -2304,3 +2754,153 @@ pub enum Arrow {
     Up,
     Down,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(name: &str) -> target::Channel {
+        target::Channel::from_str(name, isupport::CaseMap::default())
+    }
+
+    fn process(
+        completion: &mut Completion,
+        input: &str,
+        users: &[User],
+        channels: &[target::Channel],
+        known_channels: &[&target::Channel],
+        config: &Config,
+    ) {
+        completion.process(
+            input,
+            users,
+            &HashMap::new(),
+            channels,
+            known_channels,
+            None,
+            &HashMap::new(),
+            config,
+        );
+    }
+
+    /// The text Tab completes the last word with, without the suffix
+    fn tab(completion: &mut Completion, config: &Config) -> Option<String> {
+        match completion.tab(false, config) {
+            Some(Entry::Text { next, .. }) => Some(next),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn command_name() {
+        let config = Config::default();
+        let mut completion = Completion::default();
+
+        process(&mut completion, "/jo", &[], &[], &[], &config);
+        let entry = completion.tab(false, &config);
+        let Some(Entry::Command(command)) = &entry else {
+            panic!("no command completed");
+        };
+        assert_eq!(command.title, "JOIN");
+        // Followed by a space, since it needs channels
+        assert_eq!(
+            entry.unwrap().complete_input("/jo", &['#'], &config),
+            "/join "
+        );
+
+        // Arrows move through the commands starting with what's typed
+        process(&mut completion, "/mo", &[], &[], &[], &config);
+        let Some(Entry::Command(first)) =
+            completion.clone().tab(false, &config)
+        else {
+            panic!("no command completed");
+        };
+        assert!(completion.arrow(Arrow::Down));
+        let Some(Entry::Command(second)) = completion.tab(false, &config)
+        else {
+            panic!("no command completed");
+        };
+        assert!(first.title.starts_with("MO"));
+        assert!(second.title.starts_with("MO"));
+        assert_ne!(first.title, second.title);
+    }
+
+    #[test]
+    fn join_channels() {
+        let config = Config::default();
+        let mut completion = Completion::default();
+        let joined = [channel("#halloy"), channel("#rust")];
+        let seen = channel("#hacking");
+
+        process(
+            &mut completion,
+            "/join #ha",
+            &[],
+            &joined,
+            &[&seen],
+            &config,
+        );
+        assert_eq!(tab(&mut completion, &config).as_deref(), Some("#hacking"));
+        assert_eq!(tab(&mut completion, &config).as_deref(), Some("#halloy"));
+
+        // Channels before the last one are kept
+        process(
+            &mut completion,
+            "/join #rust,#hal",
+            &[],
+            &joined,
+            &[],
+            &config,
+        );
+        assert_eq!(
+            tab(&mut completion, &config).as_deref(),
+            Some("#rust,#halloy")
+        );
+    }
+
+    #[test]
+    fn msg_nicks() {
+        let config = Config::default();
+        let mut completion = Completion::default();
+        let users =
+            ["dan", "cat", "casper"].map(|nick| User::try_from(nick).unwrap());
+
+        process(&mut completion, "/msg ca", &users, &[], &[], &config);
+        assert_eq!(tab(&mut completion, &config).as_deref(), Some("casper"));
+
+        // Before any of the target is typed
+        process(&mut completion, "/msg ", &users, &[], &[], &config);
+        assert_eq!(tab(&mut completion, &config).as_deref(), Some("casper"));
+    }
+
+    #[test]
+    fn cycle_candidates() {
+        let config = Config::default();
+        let mut completion = Completion::default();
+        let users =
+            ["cat", "casper", "dan"].map(|nick| User::try_from(nick).unwrap());
+
+        process(&mut completion, "hi ca", &users, &[], &[], &config);
+        assert_eq!(tab(&mut completion, &config).as_deref(), Some("casper"));
+        assert_eq!(tab(&mut completion, &config).as_deref(), Some("cat"));
+        // Back to what was typed, then around again
+        assert!(matches!(
+            completion.tab(false, &config),
+            Some(Entry::Text { next, append_suffix: false }) if next == "ca"
+        ));
+        assert_eq!(tab(&mut completion, &config).as_deref(), Some("casper"));
+
+        // And backwards
+        assert!(matches!(
+            completion.tab(true, &config),
+            Some(Entry::Text {
+                append_suffix: false,
+                ..
+            })
+        ));
+        assert!(matches!(
+            completion.tab(true, &config),
+            Some(Entry::Text { next, append_suffix: true }) if next == "cat"
+        ));
+    }
+}
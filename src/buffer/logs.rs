@@ -1,12 +1,13 @@
 use std::path::PathBuf;
 
 use data::dashboard::BufferAction;
+use data::server::Server;
 use data::target::Target;
 use data::{Config, client, history, isupport, message};
-use iced::widget::container;
+use iced::widget::{column, container};
 use iced::{Length, Task};
 
-use super::{scroll_view, user_context};
+use super::{message_view, scroll_view, user_context};
 use crate::widget::{Element, message_content};
 use crate::{Theme, theme};
 
@@ -22,6 +23,7 @@ pub enum Event {
     MarkAsRead,
     OpenUrl(String),
     ImagePreview(PathBuf, url::Url),
+    GoToServer(Server),
 }
 
 pub fn view<'a>(
@@ -29,6 +31,7 @@ pub fn view<'a>(
     history: &'a history::Manager,
     config: &'a Config,
     theme: &'a Theme,
+    is_focused: bool,
 ) -> Element<'a, Message> {
     let messages = container(
         scroll_view::view(
@@ -40,7 +43,8 @@ pub fn view<'a>(
             config,
             move |message: &'a data::Message, _, _| match message.target.source() {
                 message::Source::Internal(message::source::Internal::Logs) => {
-                    Some(
+                    Some(message_view::with_context_menu(
+                        message,
                         container(message_content(
                             &message.content,
                             isupport::CaseMap::default(),
@@ -48,9 +52,11 @@ pub fn view<'a>(
                             scroll_view::Message::Link,
                             theme::selectable_text::default,
                             config,
-                        ))
-                        .into(),
-                    )
+                        )),
+                        None,
+                        None,
+                        config,
+                    ))
                 }
                 _ => None,
             },
@@ -59,6 +65,21 @@ pub fn view<'a>(
     )
     .height(Length::Fill);
 
+    let search_bar = scroll_view::search_bar(
+        &state.scroll_view,
+        scroll_view::Kind::Logs,
+        history,
+        config,
+        is_focused,
+    )
+    .map(|search_bar| search_bar.map(Message::ScrollView));
+
+    // Appended, so opening it doesn't reset the messages' scroll state
+    let messages = column![messages]
+        .push_maybe(search_bar)
+        .spacing(4)
+        .height(Length::Fill);
+
     container(messages)
         .width(Length::Fill)
         .height(Length::Fill)
@@ -112,6 +133,14 @@ impl Logs {
                     scroll_view::Event::ImagePreview(path, url) => {
                         Some(Event::ImagePreview(path, url))
                     }
+                    scroll_view::Event::Reply(_) => None,
+                    // Only offered in channels
+                    scroll_view::Event::SetActionStyle(..) => None,
+                    scroll_view::Event::GoToServer(server) => {
+                        Some(Event::GoToServer(server))
+                    }
+                    // Only offered in channels and queries
+                    scroll_view::Event::Translate(..) => None,
                 });
 
                 (command.map(Message::ScrollView), event)
@@ -1,15 +1,21 @@
 use crate::buffer::scroll_view::Message;
+use data::channel::{ActionStyle, Actions};
+use data::client::metadata::{self, Metadata};
+use data::ignore::Ignores;
 use data::isupport::CaseMap;
 use data::server::Server;
 use data::target::{self};
-use data::{Config, User, message};
+use data::{Config, User, input, message, preview, translation};
 use iced::advanced::text;
-use iced::widget::{column, container, row};
+use iced::widget::{
+    Space, button, column, container, image, row, text as label,
+};
+use iced::{ContentFit, Length};
 
 use super::scroll_view::LayoutMessage;
 use super::user_context;
 use crate::widget::{
-    Element, message_content, message_marker, selectable_text,
+    Element, context_menu, message_content, message_marker, selectable_text,
 };
 use crate::{Theme, theme};
 
@@ -19,6 +25,7 @@ pub enum TargetInfo<'a> {
         channel: &'a target::Channel,
         our_user: Option<&'a User>,
         users: &'a [User],
+        actions: Option<&'a Actions>,
     },
     Query,
 }
@@ -47,16 +54,224 @@ impl<'a> TargetInfo<'a> {
     }
 }
 
+/// Entries of the context menu of a message
+#[derive(Debug, Clone, Copy)]
+enum Entry {
+    Reply,
+    CopyMessage,
+    CopySender,
+    CopyLink,
+    StyleActions(ActionStyle),
+    GoToServer,
+    Translate,
+}
+
+impl Entry {
+    /// `action_style` is how actions of the sender are shown, if they can be
+    /// shown differently. Messages can be translated where `translations`
+    /// are shown.
+    fn list(
+        message: &data::Message,
+        action_style: Option<ActionStyle>,
+        translations: Option<&translation::Collection>,
+        config: &Config,
+    ) -> Vec<Self> {
+        let has_sender = sender(message).is_some();
+        let can_translate = has_sender
+            && config.translation.is_enabled()
+            && translations.is_some_and(|translations| {
+                // Failed translations can be tried again
+                matches!(
+                    translations.get(&message.hash),
+                    None | Some(translation::State::Failed(_))
+                )
+            });
+
+        [
+            has_sender.then_some(Entry::Reply),
+            Some(Entry::CopyMessage),
+            has_sender.then_some(Entry::CopySender),
+            link(message).is_some().then_some(Entry::CopyLink),
+            can_translate.then_some(Entry::Translate),
+            log_server(message).is_some().then_some(Entry::GoToServer),
+        ]
+        .into_iter()
+        .flatten()
+        .chain(action_style.into_iter().flat_map(|current| {
+            [ActionStyle::Plain, ActionStyle::Dimmed, ActionStyle::Action]
+                .into_iter()
+                .filter(move |style| *style != current)
+                .map(Entry::StyleActions)
+        }))
+        .collect()
+    }
+
+    fn view<'a>(
+        self,
+        message: &'a data::Message,
+        config: &'a Config,
+        length: Length,
+    ) -> Element<'a, Message> {
+        let (content, on_press) = match self {
+            Entry::Reply => {
+                ("Reply", input::Reply::new(message).map(Message::Reply))
+            }
+            Entry::CopyMessage => (
+                "Copy message",
+                Some(Message::Copy(config.buffer.copied_text(message))),
+            ),
+            Entry::CopySender => (
+                "Copy sender",
+                sender(message)
+                    .map(|user| Message::Copy(user.nickname().to_string())),
+            ),
+            Entry::CopyLink => (
+                "Copy link",
+                link(message).map(|url| Message::Copy(url.to_string())),
+            ),
+            Entry::StyleActions(style) => (
+                match style {
+                    ActionStyle::Action => "Show /me as actions",
+                    ActionStyle::Plain => "Show /me as messages",
+                    ActionStyle::Dimmed => "Dim /me",
+                },
+                sender(message)
+                    .map(|user| Message::SetActionStyle(user.clone(), style)),
+            ),
+            Entry::GoToServer => (
+                "Go to server buffer",
+                log_server(message)
+                    .map(|server| Message::GoToServer(server.clone())),
+            ),
+            Entry::Translate => (
+                "Translate",
+                Some(Message::Translate(message.hash, message.text())),
+            ),
+        };
+
+        button(label(content).style(theme::text::primary))
+            .padding(5)
+            .width(length)
+            .on_press_maybe(on_press)
+            .into()
+    }
+}
+
+fn sender(message: &data::Message) -> Option<&User> {
+    match message.target.source() {
+        message::Source::User(user) | message::Source::Action(Some(user)) => {
+            Some(user)
+        }
+        _ => None,
+    }
+}
+
+/// The server a log record is about
+fn log_server(message: &data::Message) -> Option<&Server> {
+    match &message.content {
+        message::Content::Log(record) => record.server.as_ref(),
+        _ => None,
+    }
+}
+
+/// The first URL in the message
+fn link(message: &data::Message) -> Option<&url::Url> {
+    match &message.content {
+        message::Content::Fragments(fragments) => {
+            fragments.iter().find_map(message::Fragment::url)
+        }
+        _ => None,
+    }
+}
+
+/// Opens a menu for replying to and copying `message` on right click
+pub fn with_context_menu<'a>(
+    message: &'a data::Message,
+    element: impl Into<Element<'a, Message>>,
+    action_style: Option<ActionStyle>,
+    translations: Option<&translation::Collection>,
+    config: &'a Config,
+) -> Element<'a, Message> {
+    context_menu(
+        context_menu::MouseButton::default(),
+        element,
+        Entry::list(message, action_style, translations, config),
+        move |entry, length| entry.view(message, config, length),
+    )
+    .into()
+}
+
 #[derive(Clone, Copy)]
 pub struct ChannelQueryLayout<'a> {
     pub config: &'a Config,
     pub casemapping: CaseMap,
     pub server: &'a Server,
     pub theme: &'a Theme,
+    pub ignores: &'a Ignores,
     pub target: TargetInfo<'a>,
+    /// Avatars and display names, on servers sharing them
+    pub metadata: Option<&'a Metadata>,
+    pub avatars: &'a preview::Collection,
+    pub translations: &'a translation::Collection,
 }
 
 impl<'a> ChannelQueryLayout<'a> {
+    fn profile(&self, user: &User) -> Option<&'a metadata::Profile> {
+        self.metadata?
+            .profile(user.nickname().as_ref(), self.casemapping)
+    }
+
+    /// Avatar of the sender of `message`, when they're shown next to
+    /// messages
+    fn format_avatar(
+        &self,
+        message: &'a data::Message,
+    ) -> Option<Element<'a, Message>> {
+        if self.config.buffer.nickname.avatars
+            != data::buffer::Avatars::Messages
+        {
+            return None;
+        }
+
+        let url = match message.target.source() {
+            message::Source::User(user)
+            | message::Source::Action(Some(user)) => self
+                .profile(user)
+                .and_then(|profile| profile.avatar.as_ref()),
+            _ => None,
+        };
+
+        Some(row![avatar(url, self.avatars, AVATAR_SIZE), label(" ")].into())
+    }
+
+    /// Translation of `message` shown under it, once one was asked for
+    fn format_translation(
+        &self,
+        message: &data::Message,
+    ) -> Option<Element<'a, Message>> {
+        let text = match self.translations.get(&message.hash)? {
+            translation::State::Translating => {
+                selectable_text("Translating...")
+                    .style(theme::selectable_text::tertiary)
+            }
+            translation::State::Translated(translation) => selectable_text(
+                format!("[{}] {translation}", self.config.translation.language),
+            )
+            .style(theme::selectable_text::tertiary),
+            translation::State::Failed(error) => selectable_text(format!(
+                "Translation failed: {error}"
+            ))
+            .style(|theme: &Theme| {
+                theme::selectable_text::status(
+                    theme,
+                    message::source::Status::Error,
+                )
+            }),
+        };
+
+        Some(text.into())
+    }
+
     fn format_timestamp(
         &self,
         message: &'a data::Message,
@@ -108,11 +323,14 @@ impl<'a> ChannelQueryLayout<'a> {
             .map(Element::from)
     }
 
+    /// `is_action` shows an action as a message, without the nickname it
+    /// starts with
     fn format_user_message(
         &self,
         message: &'a data::Message,
         max_nick_width: Option<f32>,
         user: &'a User,
+        is_action: bool,
     ) -> (Element<'a, Message>, Element<'a, Message>) {
         let fm = *self;
         let with_access_levels = self.config.buffer.nickname.show_access_levels;
@@ -122,15 +340,20 @@ impl<'a> ChannelQueryLayout<'a> {
             .iter()
             .find(|current_user| *current_user == user);
 
-        let mut text = selectable_text(
+        // Notices are set apart as `-nick-`, as is tradition
+        let nickname = if message.is_notice {
+            format!("-{}-", user.display(with_access_levels))
+        } else {
             self.config
                 .buffer
                 .nickname
                 .brackets
-                .format(user.display(with_access_levels)),
-        )
-        .style(|theme| {
-            theme::selectable_text::nickname(theme, self.config, user)
+                .format(user.display(with_access_levels))
+        };
+
+        let profile = self.profile(user);
+        let mut text = selectable_text(nickname).style(move |theme| {
+            theme::selectable_text::nickname(theme, self.config, user, profile)
         });
 
         if let Some(width) = max_nick_width {
@@ -146,24 +369,22 @@ impl<'a> ChannelQueryLayout<'a> {
             current_user,
             self.target.our_user(),
             self.config,
+            self.ignores,
             &self.config.buffer.nickname.click,
         )
         .map(Message::UserContext);
 
-        let message_content = message_content::with_context(
-            &message.content,
-            self.casemapping,
-            self.theme,
-            Message::Link,
-            theme::selectable_text::default,
-            move |link| match link {
-                message::Link::User(_) => user_context::Entry::list(
-                    fm.target.is_channel(),
-                    fm.target.our_user(),
-                ),
-                _ => vec![],
-            },
-            move |link, entry, length| match link {
+        let link_entries = move |link: &message::Link| match link {
+            message::Link::User(_) => user_context::Entry::list(
+                fm.target.is_channel(),
+                fm.target.our_user(),
+            ),
+            _ => vec![],
+        };
+        let link_entry =
+            move |link: &message::Link,
+                  entry: user_context::Entry,
+                  length: Length| match link {
                 message::Link::User(user) => entry
                     .view(
                         fm.server,
@@ -173,14 +394,53 @@ impl<'a> ChannelQueryLayout<'a> {
                         current_user,
                         length,
                         fm.config,
+                        fm.ignores,
                     )
                     .map(Message::UserContext),
                 _ => row![].into(),
-            },
-            self.config,
-        );
+            };
+
+        let display_name = profile
+            .filter(|_| self.config.buffer.nickname.show_display_names)
+            .and_then(|profile| profile.display_name(user.nickname().as_ref()))
+            .map(|display_name| {
+                selectable_text(format!("({display_name}) "))
+                    .style(theme::selectable_text::tertiary)
+            });
+
+        let message_content = if is_action {
+            message_content::without_sender(
+                &message.content,
+                user.nickname().as_ref(),
+                self.casemapping,
+                self.metadata,
+                self.theme,
+                Message::Link,
+                theme::selectable_text::default,
+                link_entries,
+                link_entry,
+                self.config,
+            )
+        } else {
+            message_content::with_context(
+                &message.content,
+                self.casemapping,
+                self.metadata,
+                self.theme,
+                Message::Link,
+                theme::selectable_text::default,
+                link_entries,
+                link_entry,
+                self.config,
+            )
+        };
 
-        (nick, Element::from(container(message_content)))
+        (
+            nick,
+            Element::from(container(
+                row![].push_maybe(display_name).push(message_content),
+            )),
+        )
     }
 
     fn format_server_message(
@@ -197,6 +457,7 @@ impl<'a> ChannelQueryLayout<'a> {
         let message_content = message_content::with_context(
             &message.content,
             fm.casemapping,
+            fm.metadata,
             self.theme,
             Message::Link,
             message_style,
@@ -220,6 +481,7 @@ impl<'a> ChannelQueryLayout<'a> {
                             .find(|current_user| *current_user == user),
                         length,
                         fm.config,
+                        fm.ignores,
                     )
                     .map(Message::UserContext),
                 _ => row![].into(),
@@ -230,6 +492,22 @@ impl<'a> ChannelQueryLayout<'a> {
         (marker, container(message_content).into())
     }
 
+    /// How actions of the sender of `message` are shown, if it's an action
+    /// in a channel
+    fn action_style(&self, message: &data::Message) -> Option<ActionStyle> {
+        let TargetInfo::Channel { actions, .. } = self.target else {
+            return None;
+        };
+        let message::Source::Action(Some(user)) = message.target.source()
+        else {
+            return None;
+        };
+
+        Some(actions.map_or(ActionStyle::Action, |actions| {
+            actions.style(user, self.casemapping)
+        }))
+    }
+
     fn content_on_new_line(&self, message: &data::Message) -> bool {
         use data::buffer::Alignment;
         use message::Source;
@@ -255,7 +533,12 @@ impl<'a> LayoutMessage<'a> for ChannelQueryLayout<'a> {
 
         let space = selectable_text(" ");
 
-        let row = row![].push_maybe(timestamp).push_maybe(prefixes);
+        let row = row![]
+            .push_maybe(timestamp)
+            .push_maybe(self.format_avatar(message))
+            .push_maybe(prefixes);
+
+        let action_style = self.action_style(message);
 
         let (middle, content): (Element<'a, Message>, Element<'a, Message>) =
             match message.target.source() {
@@ -263,6 +546,7 @@ impl<'a> LayoutMessage<'a> for ChannelQueryLayout<'a> {
                     message,
                     max_nick_width,
                     user,
+                    false,
                 )),
                 message::Source::Server(server_message) => {
                     Some(self.format_server_message(
@@ -271,18 +555,31 @@ impl<'a> LayoutMessage<'a> for ChannelQueryLayout<'a> {
                         server_message.as_ref(),
                     ))
                 }
-                message::Source::Action(_) => {
-                    let marker = message_marker(
+                message::Source::Action(Some(user))
+                    if action_style == Some(ActionStyle::Plain) =>
+                {
+                    Some(self.format_user_message(
+                        message,
                         max_nick_width,
-                        theme::selectable_text::action,
-                    );
+                        user,
+                        true,
+                    ))
+                }
+                message::Source::Action(_) => {
+                    let style = if action_style == Some(ActionStyle::Dimmed) {
+                        theme::selectable_text::tertiary
+                    } else {
+                        theme::selectable_text::action
+                    };
+
+                    let marker = message_marker(max_nick_width, style);
 
                     let message_content = message_content(
                         &message.content,
                         self.casemapping,
                         self.theme,
                         Message::Link,
-                        theme::selectable_text::action,
+                        style,
                         self.config,
                     );
 
@@ -314,11 +611,44 @@ impl<'a> LayoutMessage<'a> for ChannelQueryLayout<'a> {
                     None
                 }
             }?;
+        let content = match self.format_translation(message) {
+            Some(translation) => column![content, translation].into(),
+            None => content,
+        };
         let row = row.push(middle).push(space);
-        if self.content_on_new_line(message) {
-            Some(container(column![row, content]).into())
+        let element = if self.content_on_new_line(message) {
+            container(column![row, content])
         } else {
-            Some(container(row![row, content]).into())
+            container(row![row, content])
+        };
+
+        Some(with_context_menu(
+            message,
+            element,
+            action_style,
+            Some(self.translations),
+            self.config,
+        ))
+    }
+}
+
+/// Size of avatars next to messages
+pub const AVATAR_SIZE: f32 = 14.0;
+
+/// Avatar loaded from `url`, or the space it takes until there's one
+pub fn avatar<'a, Message: 'a>(
+    url: Option<&url::Url>,
+    avatars: &'a preview::Collection,
+    size: f32,
+) -> Element<'a, Message> {
+    match url.and_then(|url| avatars.get(url)) {
+        Some(preview::State::Loaded(data::Preview::Image(avatar))) => {
+            image(&avatar.path)
+                .width(size)
+                .height(size)
+                .content_fit(ContentFit::Cover)
+                .into()
         }
+        _ => Space::new(size, size).into(),
     }
 }
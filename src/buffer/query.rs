@@ -1,16 +1,20 @@
 use std::path::PathBuf;
 
+use data::client::metadata::Profile;
 use data::dashboard::BufferAction;
 use data::preview::{self, Previews};
 use data::target::{self, Target};
-use data::{Config, Server, buffer, history, message};
-use iced::widget::{column, container, vertical_space};
-use iced::{Length, Task};
+use data::{Config, Server, buffer, command, history, message, translation};
+use iced::widget::{column, container, row, text, vertical_space};
+use iced::{Length, Task, alignment};
 
-use super::message_view::{ChannelQueryLayout, TargetInfo};
+use super::message_view::{self, ChannelQueryLayout, TargetInfo};
 use super::{input_view, scroll_view, user_context};
 use crate::widget::Element;
-use crate::Theme;
+use crate::{Theme, theme};
+
+/// Size of the avatar in the header
+const AVATAR_SIZE: f32 = 32.0;
 
 #[derive(Debug, Clone)]
 pub enum Message {
@@ -28,6 +32,11 @@ pub enum Event {
     MarkAsRead(history::Kind),
     OpenUrl(String),
     ImagePreview(PathBuf, url::Url),
+    Theme(Option<String>),
+    TopicHistory(target::Channel, usize),
+    Layout(command::Layout),
+    ConfirmSend(buffer::Upstream, String),
+    Translate(message::Hash, String),
 }
 
 pub fn view<'a>(
@@ -35,6 +44,8 @@ pub fn view<'a>(
     clients: &'a data::client::Map,
     history: &'a history::Manager,
     previews: &'a preview::Collection,
+    avatars: &'a preview::Collection,
+    translations: &'a translation::Collection,
     config: &'a Config,
     theme: &'a Theme,
     is_focused: bool,
@@ -45,6 +56,7 @@ pub fn view<'a>(
     let status = clients.status(server);
     let buffer = &state.buffer;
     let input = history.input(buffer);
+    let scheduled = history.scheduled().in_buffer(buffer).collect();
 
     let chathistory_state =
         clients.get_chathistory_state(server, &query.to_target());
@@ -61,7 +73,11 @@ pub fn view<'a>(
         casemapping,
         server,
         theme,
+        ignores: history.ignores(),
         target: TargetInfo::Query,
+        metadata: clients.get_metadata(server),
+        avatars,
+        translations,
     };
 
     let messages = container(
@@ -78,6 +94,21 @@ pub fn view<'a>(
     )
     .height(Length::Fill);
 
+    let search_bar = scroll_view::search_bar(
+        &state.scroll_view,
+        scroll_view::Kind::Query(server, query),
+        history,
+        config,
+        is_focused,
+    )
+    .map(|search_bar| search_bar.map(Message::ScrollView));
+
+    // Appended, so opening it doesn't reset the messages' scroll state
+    let messages = column![messages]
+        .push_maybe(search_bar)
+        .spacing(4)
+        .height(Length::Fill);
+
     let show_text_input = match config.buffer.text_input.visibility {
         data::buffer::TextInputVisibility::Focused => is_focused,
         data::buffer::TextInputVisibility::Always => true,
@@ -89,16 +120,26 @@ pub fn view<'a>(
             input_view::view(
                 &state.input_view,
                 input,
+                scheduled,
                 is_focused,
                 !status.connected(),
-                config
+                casemapping,
+                config,
+                theme,
             )
             .map(Message::InputView)
         ]
         .width(Length::Fill)
     });
 
-    let scrollable = column![messages]
+    // A zero-height placeholder without a header, so it showing up doesn't
+    // reset the messages' scroll state
+    let header = clients
+        .get_user_profile(server, query.as_str())
+        .and_then(|profile| header(profile, query, avatars, config))
+        .unwrap_or_else(|| column![].into());
+
+    let scrollable = column![header, messages]
         .push_maybe(text_input)
         .height(Length::Fill);
 
@@ -109,6 +150,46 @@ pub fn view<'a>(
         .into()
 }
 
+/// Avatar and display name of the user, on servers sharing them
+fn header<'a>(
+    profile: &'a Profile,
+    query: &'a target::Query,
+    avatars: &'a preview::Collection,
+    config: &'a Config,
+) -> Option<Element<'a, Message>> {
+    let avatar = profile
+        .avatar
+        .as_ref()
+        .filter(|_| {
+            config.buffer.nickname.avatars != data::buffer::Avatars::Hidden
+        })
+        .map(|url| message_view::avatar(Some(url), avatars, AVATAR_SIZE));
+    let display_name = profile
+        .display_name(query.as_str())
+        .filter(|_| config.buffer.nickname.show_display_names);
+
+    if avatar.is_none() && display_name.is_none() {
+        return None;
+    }
+
+    Some(
+        container(
+            row![]
+                .push_maybe(avatar)
+                .push(text(query.as_str()).style(theme::text::primary))
+                .push_maybe(display_name.map(|display_name| {
+                    text(display_name)
+                        .shaping(text::Shaping::Advanced)
+                        .style(theme::text::secondary)
+                }))
+                .spacing(8)
+                .align_y(alignment::Vertical::Center),
+        )
+        .padding(iced::padding::bottom(8))
+        .into(),
+    )
+}
+
 #[derive(Debug, Clone)]
 pub struct Query {
     pub buffer: buffer::Upstream,
@@ -147,6 +228,24 @@ impl Query {
                     config,
                 );
 
+                if let Some(scroll_view::Event::Reply(reply)) = event {
+                    let task = self.input_view.reply(
+                        reply,
+                        &self.buffer,
+                        clients,
+                        history,
+                        config,
+                    );
+
+                    return (
+                        Task::batch(vec![
+                            command.map(Message::ScrollView),
+                            task.map(Message::InputView),
+                        ]),
+                        None,
+                    );
+                }
+
                 let event = event.and_then(|event| match event {
                     scroll_view::Event::UserContext(event) => {
                         Some(Event::UserContext(event))
@@ -176,6 +275,15 @@ impl Query {
                     scroll_view::Event::ImagePreview(path, url) => {
                         Some(Event::ImagePreview(path, url))
                     }
+                    // Started in the input above
+                    scroll_view::Event::Reply(_) => None,
+                    // Only offered in channels
+                    scroll_view::Event::SetActionStyle(..) => None,
+                    // Only offered in the logs buffer
+                    scroll_view::Event::GoToServer(_) => None,
+                    scroll_view::Event::Translate(hash, text) => {
+                        Some(Event::Translate(hash, text))
+                    }
                 });
 
                 (command.map(Message::ScrollView), event)
@@ -204,6 +312,21 @@ impl Query {
                     Some(input_view::Event::OpenBuffers { targets }) => {
                         (command, Some(Event::OpenBuffers(targets)))
                     }
+                    Some(input_view::Event::Theme { name }) => {
+                        (command, Some(Event::Theme(name)))
+                    }
+                    Some(input_view::Event::TopicHistory {
+                        channel,
+                        count,
+                    }) => (command, Some(Event::TopicHistory(channel, count))),
+                    Some(input_view::Event::Layout { layout }) => {
+                        (command, Some(Event::Layout(layout)))
+                    }
+                    // Only parsed in channels
+                    Some(input_view::Event::BanList { .. }) => (command, None),
+                    Some(input_view::Event::ConfirmSend { buffer, text }) => {
+                        (command, Some(Event::ConfirmSend(buffer, text)))
+                    }
                     None => (command, None),
                 }
             }
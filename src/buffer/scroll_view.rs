@@ -1,28 +1,40 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
 use chrono::{DateTime, Local, NaiveDate, Utc};
+use data::channel::ActionStyle;
 use data::dashboard::BufferAction;
 use data::isupport::ChatHistoryState;
-use data::message::{self, Limit};
+use data::message::{self, Limit, fold};
 use data::preview::{self, Previews};
 use data::server::Server;
 use data::target::{self, Target};
-use data::{Config, Preview, client, history};
+use data::{Config, Preview, client, history, input, search};
+use iced::advanced::{Clipboard, Layout, Shell, widget};
 use iced::widget::{
     Scrollable, button, center, column, container, horizontal_rule,
-    horizontal_space, image, mouse_area, row, scrollable, text,
+    horizontal_space, image, mouse_area, row, scrollable, stack, text,
+    text_input, vertical_space,
+};
+use iced::{
+    ContentFit, Event as IcedEvent, Length, Rectangle, Task, alignment,
+    clipboard, mouse, padding,
 };
-use iced::{ContentFit, Length, Task, alignment, padding};
 
 use self::correct_viewport::correct_viewport;
 use self::keyed::keyed;
-use super::user_context;
+use super::{message_view, user_context};
 use crate::widget::{
-    Element, MESSAGE_MARKER_TEXT, notify_visibility, selectable_text,
+    Element, MESSAGE_MARKER_TEXT, Renderer, decorate, key_press,
+    message_marker, notify_visibility, selectable_text,
 };
 use crate::{font, icon, theme};
 
+const SCROLLBAR_WIDTH: f32 = 5.0;
+const MARKER_HEIGHT: f32 = 2.0;
+/// Resolution of marker positions along the scrollbar track
+const MARKER_PORTIONS: u16 = 1000;
+
 #[derive(Debug, Clone)]
 pub enum Message {
     Scrolled {
@@ -44,6 +56,24 @@ pub enum Message {
     PreviewUnhovered(message::Hash, usize),
     HidePreview(message::Hash, url::Url),
     MarkAsRead,
+    ToggleFold(message::Hash),
+    Reply(input::Reply),
+    Copy(String),
+    SelectFrom(message::Hash),
+    SelectTo(message::Hash),
+    Search(String),
+    SearchOlder,
+    SearchNewer,
+    ToggleSearchCase,
+    ToggleSearchRegex,
+    CloseSearch,
+    GoToHighlight(message::Hash),
+    GoToBacklog,
+    FocusMarkerSeen,
+    FocusMarkerPassed,
+    SetActionStyle(data::User, ActionStyle),
+    GoToServer(Server),
+    Translate(message::Hash, String),
 }
 
 #[derive(Debug, Clone)]
@@ -57,6 +87,10 @@ pub enum Event {
     MarkAsRead,
     OpenUrl(String),
     ImagePreview(PathBuf, url::Url),
+    Reply(input::Reply),
+    SetActionStyle(data::User, ActionStyle),
+    GoToServer(Server),
+    Translate(message::Hash, String),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -134,18 +168,28 @@ pub fn view<'a>(
     let divider_font_size =
         config.font.size.map_or(theme::TEXT_SIZE, f32::from) - 1.0;
 
-    let Some(history::View {
+    let Some(view) =
+        history.get_messages(&kind.into(), Some(state.limit), &config.buffer)
+    else {
+        return column![].into();
+    };
+
+    let markers = config
+        .buffer
+        .scrollbar_markers
+        .enabled
+        .then(|| view.markers());
+
+    let history::View {
         has_more_older_messages,
         has_more_newer_messages,
         old_messages,
         new_messages,
         max_nick_chars,
         max_prefix_chars,
+        replied_to,
         ..
-    }) = history.get_messages(&kind.into(), Some(state.limit), &config.buffer)
-    else {
-        return column![].into();
-    };
+    } = view;
 
     let top_row = if let (false, Some(chathistory_state)) =
         (has_more_older_messages, chathistory_state)
@@ -194,18 +238,187 @@ pub fn view<'a>(
     let max_prefix_width =
         max_prefix_chars.map(|len| font::width_from_chars(len, &config.font));
 
+    // Mode changes are always folded, events only when configured to
+    let is_channel = matches!(kind, Kind::Channel(..));
+
+    let selected = state
+        .selection
+        .and_then(|selection| {
+            selection
+                .range(
+                    &old_messages
+                        .iter()
+                        .chain(&new_messages)
+                        .copied()
+                        .collect::<Vec<_>>(),
+                )
+                .map(|range| {
+                    range
+                        .iter()
+                        .map(|message| message.hash)
+                        .collect::<HashSet<_>>()
+                })
+        })
+        .unwrap_or_default();
+
+    let search = state.search.as_ref().and_then(|search| {
+        let matcher = search.query.matcher(config.matching).ok().flatten()?;
+
+        let matches = matcher
+            .matches(old_messages.iter().chain(&new_messages).copied())
+            .into_iter()
+            .map(|message| message.hash)
+            .collect::<HashSet<_>>();
+
+        Some((matches, search.current))
+    });
+
+    let mark = |hash: message::Hash| {
+        if selected.contains(&hash) {
+            Mark::Selected
+        } else if let Some((matches, current)) = &search {
+            if *current == Some(hash) {
+                Mark::CurrentMatch
+            } else if matches.contains(&hash) {
+                Mark::Match
+            } else {
+                Mark::None
+            }
+        } else {
+            Mark::None
+        }
+    };
+
+    // Messages which arrived while the window was unfocused
+    let focus_marker = state
+        .focus_marker
+        .filter(|focus_marker| focus_marker.is_shown);
+    let is_unfocused = |message: &data::Message| {
+        focus_marker.is_some_and(|focus_marker| {
+            message.server_time > focus_marker.lost_at
+        })
+    };
+    let is_focus_marker_seen =
+        focus_marker.is_some_and(|focus_marker| focus_marker.is_seen);
+
     let message_rows = |last_date: Option<NaiveDate>,
+                        was_unfocused: bool,
+                        first_row: usize,
                         messages: &[&'a data::Message]| {
-        messages
+        let items = if is_channel {
+            fold::fold(
+                messages,
+                config.buffer.server_messages.collapse,
+                config.buffer.server_messages.collapse_exempt_active,
+            )
+        } else {
+            messages.iter().copied().map(fold::Item::Message).collect()
+        };
+
+        // The first row which arrived while the window was unfocused, unless
+        // the rows before these already did
+        let focus_marker_at = items
             .iter()
-            .filter_map(|message| {
-                formatter
+            .map(|item| match item {
+                fold::Item::Message(message) => *message,
+                fold::Item::Fold(fold) => fold.first(),
+            })
+            .find(|message| is_unfocused(message))
+            .filter(|_| !was_unfocused)
+            .map(|message| message.hash);
+
+        items
+            .into_iter()
+            .filter_map(|item| match item {
+                fold::Item::Message(message) => formatter
                     .format(message, max_nick_width, max_prefix_width)
                     .map(|element| {
-                        (message, keyed(keyed::Key::message(message), element))
+                        let element = annotated(
+                            message,
+                            element,
+                            message
+                                .reply_to
+                                .as_deref()
+                                .and_then(|id| replied_to.get(id).copied()),
+                            max_nick_width,
+                            max_prefix_width,
+                            config,
+                        );
+
+                        (
+                            message,
+                            keyed(keyed::Key::message(message), element),
+                            true,
+                        )
+                    }),
+                // Folds are keyed by their first event, so expanding one keeps
+                // it in place when it's at the top of the viewport
+                fold::Item::Fold(fold) => {
+                    let is_expanded = state.expanded_folds.contains(&fold.id());
+
+                    let header = keyed(
+                        keyed::Key::Fold(fold.id()),
+                        fold_row(
+                            &fold,
+                            is_expanded,
+                            max_nick_width,
+                            max_prefix_width,
+                            config,
+                        ),
+                    );
+
+                    let element = if is_expanded {
+                        column![header]
+                            .extend(fold.messages.iter().filter_map(
+                                |message| {
+                                    formatter
+                                        .format(
+                                            message,
+                                            max_nick_width,
+                                            max_prefix_width,
+                                        )
+                                        .map(|element| {
+                                            keyed(
+                                                keyed::Key::message(message),
+                                                element,
+                                            )
+                                        })
+                                },
+                            ))
+                            .into()
+                    } else {
+                        header
+                    };
+
+                    Some((fold.first(), element, false))
+                }
+            })
+            .enumerate()
+            .map(|(row, (message, element, has_previews))| {
+                let element =
+                    selectable_row(message.hash, mark(message.hash), element);
+
+                if !config.buffer.row_styling {
+                    return (message, element, has_previews);
+                }
+
+                let category = message.category();
+                let is_alternate = (first_row + row) % 2 != 0;
+
+                let element = container(element)
+                    .width(Length::Fill)
+                    .style(move |theme| {
+                        theme::container::message_row(
+                            theme,
+                            category,
+                            is_alternate,
+                        )
                     })
+                    .into();
+
+                (message, element, has_previews)
             })
-            .scan(last_date, |last_date, (message, element)| {
+            .scan(last_date, |last_date, (message, element, has_previews)| {
                 let date =
                     message.server_time.with_timezone(&Local).date_naive();
 
@@ -217,9 +430,13 @@ pub fn view<'a>(
                     message::Content::Fragments(fragments),
                     Some(previews),
                     true,
-                ) =
-                    (&message.content, previews, config.preview.enabled)
-                {
+                    true,
+                ) = (
+                    &message.content,
+                    previews,
+                    config.preview.enabled,
+                    has_previews,
+                ) {
                     let urls = fragments
                         .iter()
                         .filter_map(message::Fragment::url)
@@ -288,6 +505,19 @@ pub fn view<'a>(
                     element
                 };
 
+                let content = if focus_marker_at == Some(message.hash) {
+                    column![
+                        focus_marker_divider(
+                            is_focus_marker_seen,
+                            divider_font_size
+                        ),
+                        content
+                    ]
+                    .into()
+                } else {
+                    content
+                };
+
                 if is_new_day && config.buffer.date_separators.show {
                     Some(
                         column![
@@ -320,11 +550,13 @@ pub fn view<'a>(
             .collect::<Vec<_>>()
     };
 
-    let old = message_rows(None, &old_messages);
+    let old = message_rows(None, false, 0, &old_messages);
     let new = message_rows(
         old_messages.last().map(|message| {
             message.server_time.with_timezone(&Local).date_naive()
         }),
+        old_messages.last().copied().is_some_and(is_unfocused),
+        old.len(),
         &new_messages,
     );
 
@@ -361,13 +593,13 @@ pub fn view<'a>(
         .push(keyed(keyed::Key::Divider, divider))
         .push(column(new));
 
-    correct_viewport(
+    let scrollable = correct_viewport(
         Scrollable::new(container(content).width(Length::Fill).padding([0, 8]))
             .direction(scrollable::Direction::Vertical(
                 scrollable::Scrollbar::default()
                     .anchor(status.anchor())
-                    .width(5)
-                    .scroller_width(5),
+                    .width(SCROLLBAR_WIDTH)
+                    .scroller_width(SCROLLBAR_WIDTH),
             ))
             .on_scroll(move |viewport| Message::Scrolled {
                 has_more_older_messages,
@@ -380,9 +612,151 @@ pub fn view<'a>(
             .id(state.scrollable.clone()),
         state.scrollable.clone(),
         matches!(state.status, Status::Unlocked),
+    );
+
+    let Some(markers) = markers else {
+        return scrollable;
+    };
+
+    let mut layers = stack![scrollable];
+
+    if let Some(unread_band) = unread_band(&markers) {
+        layers = layers.push(unread_band);
+    }
+
+    layers.push(scrollbar_markers(&markers)).into()
+}
+
+/// Divider before messages which arrived while the window was unfocused,
+/// fainter than the backlog divider
+fn focus_marker_divider<'a>(
+    is_seen: bool,
+    font_size: f32,
+) -> Element<'a, Message> {
+    let divider = row![
+        container(horizontal_rule(1).style(theme::rule::focus_marker))
+            .width(Length::Fill)
+            .padding(padding::right(6)),
+        text("while unfocused")
+            .size(font_size)
+            .style(theme::text::focus_marker),
+        container(horizontal_rule(1).style(theme::rule::focus_marker))
+            .width(Length::Fill)
+            .padding(padding::left(6))
+    ]
+    .padding(2)
+    .align_y(iced::Alignment::Center);
+
+    if is_seen {
+        notify_visibility(
+            divider,
+            0.0,
+            notify_visibility::When::Above,
+            Message::FocusMarkerPassed,
+        )
+    } else {
+        notify_visibility(
+            divider,
+            0.0,
+            notify_visibility::When::Visible,
+            Message::FocusMarkerSeen,
+        )
+    }
+}
+
+/// Band over the scrollbar track from the backlog divider to the bottom.
+/// It doesn't react to the mouse, so the scrollbar can be dragged through it.
+fn unread_band<'a>(markers: &history::Markers) -> Option<Element<'a, Message>> {
+    let backlog = markers.backlog.filter(|backlog| *backlog < 1.0)?;
+    let (above, band) = portions(backlog);
+
+    Some(
+        container(
+            column![
+                vertical_space().height(Length::FillPortion(above)),
+                container(vertical_space())
+                    .width(Length::Fill)
+                    .height(Length::FillPortion(band))
+                    .style(theme::container::unread_marker),
+            ]
+            .width(SCROLLBAR_WIDTH)
+            .height(Length::Fill),
+        )
+        .align_right(Length::Fill)
+        .height(Length::Fill)
+        .into(),
     )
 }
 
+/// Ticks on the scrollbar track for highlights and the backlog divider, which
+/// scroll to them when clicked
+fn scrollbar_markers<'a>(markers: &history::Markers) -> Element<'a, Message> {
+    let mut ticks =
+        markers
+            .highlights
+            .iter()
+            .map(|(hash, position)| {
+                (*position, Message::GoToHighlight(*hash), Tick::Highlight)
+            })
+            .chain(markers.backlog.map(|position| {
+                (position, Message::GoToBacklog, Tick::Backlog)
+            }))
+            .collect::<Vec<_>>();
+    ticks.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut column = column![].width(SCROLLBAR_WIDTH).height(Length::Fill);
+    let mut last = 0;
+
+    for (position, message, tick) in ticks {
+        let (above, _) = portions(position);
+
+        column = column
+            .push(
+                vertical_space()
+                    .height(Length::FillPortion(above.saturating_sub(last))),
+            )
+            .push(
+                mouse_area(
+                    container(vertical_space())
+                        .width(Length::Fill)
+                        .height(MARKER_HEIGHT)
+                        .style(move |theme| match tick {
+                            Tick::Highlight => {
+                                theme::container::highlight_marker(theme)
+                            }
+                            Tick::Backlog => {
+                                theme::container::backlog_marker(theme)
+                            }
+                        }),
+                )
+                .interaction(mouse::Interaction::Pointer)
+                .on_press(message),
+            );
+
+        last = above;
+    }
+
+    let below = MARKER_PORTIONS - last;
+
+    container(column.push(vertical_space().height(Length::FillPortion(below))))
+        .align_right(Length::Fill)
+        .height(Length::Fill)
+        .into()
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Tick {
+    Highlight,
+    Backlog,
+}
+
+/// Splits the track at `position`, as the fill portions above and below it
+fn portions(position: f32) -> (u16, u16) {
+    let above = (position.clamp(0.0, 1.0) * f32::from(MARKER_PORTIONS)) as u16;
+
+    (above, MARKER_PORTIONS - above)
+}
+
 #[derive(Debug, Clone)]
 pub struct State {
     pub scrollable: scrollable::Id,
@@ -391,6 +765,101 @@ pub struct State {
     pending_scroll_to: Option<message::Hash>,
     visible_url_messages: HashMap<message::Hash, Vec<url::Url>>,
     hovered_preview: Option<(message::Hash, usize)>,
+    expanded_folds: HashSet<message::Hash>,
+    selection: Option<Selection>,
+    search: Option<Search>,
+    focus_marker: Option<FocusMarker>,
+}
+
+/// Where the window lost focus. Its divider is only shown once focus is
+/// regained, and it's cleared when jumped to or scrolled past.
+#[derive(Debug, Clone, Copy)]
+struct FocusMarker {
+    lost_at: DateTime<Utc>,
+    is_shown: bool,
+    /// The divider has been in view, so leaving it above the viewport
+    /// scrolls past it
+    is_seen: bool,
+}
+
+/// Search of the messages loaded in the buffer, opened with
+/// `search_buffer`. Loading more history isn't triggered by it.
+#[derive(Debug, Clone)]
+struct Search {
+    input_id: text_input::Id,
+    query: search::Query,
+    /// The match last scrolled to
+    current: Option<message::Hash>,
+}
+
+impl Search {
+    /// Loaded messages which match, oldest first
+    fn matches<'a>(
+        &self,
+        kind: Kind,
+        history: &'a history::Manager,
+        config: &Config,
+    ) -> Result<Vec<&'a data::Message>, search::Error> {
+        let Some(matcher) = self.query.matcher(config.matching)? else {
+            return Ok(vec![]);
+        };
+
+        let Some(history::View {
+            old_messages,
+            new_messages,
+            ..
+        }) = history.get_messages(&kind.into(), None, &config.buffer)
+        else {
+            return Ok(vec![]);
+        };
+
+        Ok(matcher.matches(old_messages.into_iter().chain(new_messages)))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Jump {
+    Newest,
+    Older,
+    Newer,
+}
+
+/// How a row stands out from the others
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mark {
+    None,
+    Selected,
+    Match,
+    CurrentMatch,
+}
+
+/// Messages selected by dragging across them, kept by hash so it stays on
+/// the same messages as new ones arrive
+#[derive(Debug, Clone, Copy)]
+struct Selection {
+    anchor: message::Hash,
+    head: message::Hash,
+}
+
+impl Selection {
+    /// The selected messages of `messages`, if it spans more than one of
+    /// them. Single messages are selected by their text instead.
+    fn range<'a, 'b>(
+        &self,
+        messages: &'b [&'a data::Message],
+    ) -> Option<&'b [&'a data::Message]> {
+        if self.anchor == self.head {
+            return None;
+        }
+
+        let position =
+            |hash| messages.iter().position(|message| message.hash == hash);
+
+        let anchor = position(self.anchor)?;
+        let head = position(self.head)?;
+
+        Some(&messages[anchor.min(head)..=anchor.max(head)])
+    }
 }
 
 impl Default for State {
@@ -402,6 +871,10 @@ impl Default for State {
             pending_scroll_to: None,
             visible_url_messages: HashMap::new(),
             hovered_preview: None,
+            expanded_folds: HashSet::new(),
+            selection: None,
+            search: None,
+            focus_marker: None,
         }
     }
 }
@@ -669,11 +1142,198 @@ impl State {
             Message::ImagePreview(path, url) => {
                 return (Task::none(), Some(Event::ImagePreview(path, url)));
             }
+            Message::ToggleFold(id) => {
+                if !self.expanded_folds.remove(&id) {
+                    self.expanded_folds.insert(id);
+                }
+            }
+            Message::Reply(reply) => {
+                return (Task::none(), Some(Event::Reply(reply)));
+            }
+            Message::SetActionStyle(user, style) => {
+                return (Task::none(), Some(Event::SetActionStyle(user, style)));
+            }
+            Message::GoToServer(server) => {
+                return (Task::none(), Some(Event::GoToServer(server)));
+            }
+            Message::Translate(hash, text) => {
+                return (Task::none(), Some(Event::Translate(hash, text)));
+            }
+            Message::Copy(contents) => {
+                return (clipboard::write(contents), None);
+            }
+            Message::SelectFrom(hash) => {
+                self.selection = Some(Selection {
+                    anchor: hash,
+                    head: hash,
+                });
+            }
+            Message::SelectTo(hash) => {
+                if let Some(selection) = &mut self.selection {
+                    selection.head = hash;
+                }
+            }
+            Message::Search(text) => {
+                if let Some(search) = &mut self.search {
+                    search.query.text = text;
+                }
+
+                return (
+                    self.jump_to_match(Jump::Newest, kind, history, config),
+                    None,
+                );
+            }
+            Message::SearchOlder => {
+                return (
+                    self.jump_to_match(Jump::Older, kind, history, config),
+                    None,
+                );
+            }
+            Message::SearchNewer => {
+                return (
+                    self.jump_to_match(Jump::Newer, kind, history, config),
+                    None,
+                );
+            }
+            Message::ToggleSearchCase => {
+                if let Some(search) = &mut self.search {
+                    search.query.case_sensitive = !search.query.case_sensitive;
+                }
+
+                return (
+                    self.jump_to_match(Jump::Newest, kind, history, config),
+                    None,
+                );
+            }
+            Message::ToggleSearchRegex => {
+                if let Some(search) = &mut self.search {
+                    search.query.is_regex = !search.query.is_regex;
+                }
+
+                return (
+                    self.jump_to_match(Jump::Newest, kind, history, config),
+                    None,
+                );
+            }
+            Message::CloseSearch => {
+                self.search = None;
+            }
+            Message::GoToHighlight(hash) => {
+                return (
+                    self.scroll_to_message(hash, kind, history, config),
+                    None,
+                );
+            }
+            Message::GoToBacklog => {
+                return (self.scroll_to_backlog(kind, history, config), None);
+            }
+            Message::FocusMarkerSeen => {
+                if let Some(focus_marker) = &mut self.focus_marker {
+                    focus_marker.is_seen = true;
+                }
+            }
+            Message::FocusMarkerPassed => {
+                self.focus_marker = None;
+            }
         }
 
         (Task::none(), None)
     }
 
+    /// Opens the search bar, or focuses it if it's already open
+    pub fn open_search(&mut self) -> Task<Message> {
+        let search = self.search.get_or_insert_with(|| Search {
+            input_id: text_input::Id::unique(),
+            query: search::Query::default(),
+            current: None,
+        });
+
+        text_input::focus(search.input_id.clone())
+    }
+
+    /// Closes the search bar, returning whether it was open
+    pub fn close_search(&mut self) -> bool {
+        self.search.take().is_some()
+    }
+
+    /// Scrolls to a match of the search. Matches wrap around, so going
+    /// older from the oldest one continues at the newest.
+    fn jump_to_match(
+        &mut self,
+        jump: Jump,
+        kind: Kind,
+        history: &history::Manager,
+        config: &Config,
+    ) -> Task<Message> {
+        let Some(search) = &mut self.search else {
+            return Task::none();
+        };
+
+        let matches = search.matches(kind, history, config).unwrap_or_default();
+
+        let position = search.current.and_then(|current| {
+            matches.iter().position(|message| message.hash == current)
+        });
+
+        let next = match (jump, position) {
+            (Jump::Older, Some(position)) => {
+                Some(position.checked_sub(1).unwrap_or(matches.len() - 1))
+            }
+            (Jump::Newer, Some(position)) => {
+                Some((position + 1) % matches.len())
+            }
+            _ => matches.len().checked_sub(1),
+        };
+
+        let current = next.map(|next| matches[next].hash);
+        search.current = current;
+
+        match current {
+            Some(hash) => self.scroll_to_message(hash, kind, history, config),
+            None => Task::none(),
+        }
+    }
+
+    /// Clears the selection of messages, returning whether there was one
+    pub fn clear_selection(&mut self) -> bool {
+        self.selection
+            .take()
+            .is_some_and(|selection| selection.anchor != selection.head)
+    }
+
+    /// The messages selected by dragging across them, one line each
+    pub fn selected_text(
+        &self,
+        kind: Kind,
+        history: &history::Manager,
+        config: &Config,
+    ) -> Option<String> {
+        let selection = self.selection?;
+
+        let history::View {
+            old_messages,
+            new_messages,
+            ..
+        } = history.get_messages(
+            &kind.into(),
+            Some(self.limit),
+            &config.buffer,
+        )?;
+        let messages = old_messages
+            .into_iter()
+            .chain(new_messages)
+            .collect::<Vec<_>>();
+
+        Some(
+            selection
+                .range(&messages)?
+                .iter()
+                .map(|message| config.buffer.copied_line(message))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    }
+
     pub fn scroll_up_page(&mut self) -> Task<Message> {
         correct_viewport::scroll_by(
             self.scrollable.clone(),
@@ -789,6 +1449,58 @@ impl State {
             .map(Message::ScrollTo)
     }
 
+    /// Marks where the window lost focus, replacing an earlier marker
+    pub fn mark_focus_lost(&mut self) {
+        self.focus_marker = Some(FocusMarker {
+            lost_at: Utc::now(),
+            is_shown: false,
+            is_seen: false,
+        });
+    }
+
+    pub fn show_focus_marker(&mut self) {
+        if let Some(focus_marker) = &mut self.focus_marker {
+            focus_marker.is_shown = true;
+        }
+    }
+
+    /// Scrolls to the first message which arrived while the window was
+    /// unfocused, clearing the focus marker
+    pub fn scroll_to_focus_marker(
+        &mut self,
+        kind: Kind,
+        history: &history::Manager,
+        config: &Config,
+    ) -> Task<Message> {
+        let Some(focus_marker) =
+            self.focus_marker.filter(|marker| marker.is_shown)
+        else {
+            return Task::none();
+        };
+
+        let Some(history::View {
+            old_messages,
+            new_messages,
+            ..
+        }) = history.get_messages(&kind.into(), None, &config.buffer)
+        else {
+            return Task::none();
+        };
+
+        let Some(message) = old_messages
+            .iter()
+            .chain(&new_messages)
+            .find(|message| message.server_time > focus_marker.lost_at)
+            .map(|message| message.hash)
+        else {
+            return Task::none();
+        };
+
+        self.focus_marker = None;
+
+        self.scroll_to_message(message, kind, history, config)
+    }
+
     pub fn visible_urls(&self) -> impl Iterator<Item = &url::Url> {
         self.visible_url_messages.values().flatten()
     }
@@ -860,6 +1572,7 @@ mod keyed {
         Divider,
         Message(message::Hash),
         Preview(message::Hash, usize),
+        Fold(message::Hash),
     }
 
     impl Key {
@@ -1096,6 +1809,210 @@ mod keyed {
     }
 }
 
+/// Starts a selection of messages when pressed and extends it to the row
+/// when dragged over. The press still reaches the row, so its text can be
+/// selected as well.
+fn selectable_row<'a>(
+    hash: message::Hash,
+    mark: Mark,
+    element: Element<'a, Message>,
+) -> Element<'a, Message> {
+    // Always wrapped, so marking doesn't reset the state of the row
+    let element =
+        container(element)
+            .width(Length::Fill)
+            .style(move |theme| match mark {
+                Mark::None => container::Style::default(),
+                Mark::Selected => theme::container::selected_message(theme),
+                Mark::Match => theme::container::search_match(theme, false),
+                Mark::CurrentMatch => {
+                    theme::container::search_match(theme, true)
+                }
+            });
+
+    decorate(element)
+        .update(
+            move |drag: &mut Drag,
+                  inner: &mut Element<'a, Message>,
+                  tree: &mut widget::Tree,
+                  event: &IcedEvent,
+                  layout: Layout<'_>,
+                  cursor: mouse::Cursor,
+                  renderer: &Renderer,
+                  clipboard: &mut dyn Clipboard,
+                  shell: &mut Shell<'_, Message>,
+                  viewport: &Rectangle| {
+                inner.as_widget_mut().update(
+                    tree, event, layout, cursor, renderer, clipboard, shell,
+                    viewport,
+                );
+
+                let is_hovered = cursor.is_over(layout.bounds());
+
+                match event {
+                    IcedEvent::Mouse(mouse::Event::ButtonPressed(
+                        mouse::Button::Left,
+                    )) => {
+                        drag.is_pressed = true;
+
+                        if is_hovered && !shell.is_event_captured() {
+                            shell.publish(Message::SelectFrom(hash));
+                        }
+                    }
+                    IcedEvent::Mouse(mouse::Event::ButtonReleased(
+                        mouse::Button::Left,
+                    )) => {
+                        drag.is_pressed = false;
+                    }
+                    IcedEvent::Mouse(mouse::Event::CursorMoved { .. }) => {
+                        if drag.is_pressed && is_hovered && !drag.is_hovered {
+                            shell.publish(Message::SelectTo(hash));
+                        }
+                    }
+                    _ => {}
+                }
+
+                drag.is_hovered = is_hovered;
+            },
+        )
+        .into()
+}
+
+#[derive(Debug, Default)]
+struct Drag {
+    is_pressed: bool,
+    is_hovered: bool,
+}
+
+/// Bar of the search opened with `search_buffer`, `None` when it's closed
+pub fn search_bar<'a>(
+    state: &'a State,
+    kind: Kind,
+    history: &history::Manager,
+    config: &Config,
+    is_focused: bool,
+) -> Option<Element<'a, Message>> {
+    let search = state.search.as_ref()?;
+
+    let status = match search.matches(kind, history, config) {
+        Ok(_) if search.query.text.is_empty() => {
+            text("").style(theme::text::secondary)
+        }
+        Ok(matches) => {
+            let position = search.current.and_then(|current| {
+                matches.iter().position(|message| message.hash == current)
+            });
+
+            text(match position {
+                Some(position) => {
+                    format!("{} of {}", position + 1, matches.len())
+                }
+                None => format!("0 of {}", matches.len()),
+            })
+            .style(theme::text::secondary)
+        }
+        Err(error) => text(error.to_string()).style(theme::text::error),
+    };
+
+    let input = text_input("Search", &search.query.text)
+        .id(search.input_id.clone())
+        .on_input(Message::Search)
+        .on_submit(Message::SearchOlder)
+        .padding([2, 4])
+        .style(theme::text_input::primary);
+
+    let input = if is_focused {
+        key_press(
+            key_press(
+                input,
+                key_press::Key::Named(key_press::Named::Escape),
+                key_press::Modifiers::default(),
+                Message::CloseSearch,
+            ),
+            key_press::Key::Named(key_press::Named::Enter),
+            key_press::Modifiers::SHIFT,
+            Message::SearchNewer,
+        )
+    } else {
+        input.into()
+    };
+
+    let toggle = |label, is_enabled, message| {
+        button(text(label))
+            .padding([2, 4])
+            .style(move |theme, status| {
+                theme::button::secondary(theme, status, is_enabled)
+            })
+            .on_press(message)
+    };
+
+    Some(
+        row![
+            input,
+            status,
+            toggle(
+                "Aa",
+                search.query.case_sensitive,
+                Message::ToggleSearchCase
+            ),
+            toggle(".*", search.query.is_regex, Message::ToggleSearchRegex),
+            button(icon::cancel())
+                .padding([2, 4])
+                .style(theme::button::bare)
+                .on_press(Message::CloseSearch),
+        ]
+        .spacing(4)
+        .align_y(iced::Alignment::Center)
+        .into(),
+    )
+}
+
+fn fold_row<'a>(
+    fold: &fold::Fold<'a>,
+    is_expanded: bool,
+    max_nick_width: Option<f32>,
+    max_prefix_width: Option<f32>,
+    config: &'a Config,
+) -> Element<'a, Message> {
+    let timestamp = config
+        .buffer
+        .format_timestamp(&fold.first().server_time)
+        .map(|timestamp| {
+            selectable_text(timestamp).style(theme::selectable_text::timestamp)
+        });
+
+    let prefixes = max_nick_width
+        .and(max_prefix_width)
+        .map(|width| selectable_text("").width(width));
+
+    let marker: Element<'a, Message> =
+        message_marker(max_nick_width, |message_theme| {
+            theme::selectable_text::server(message_theme, None)
+        });
+
+    let action = if is_expanded {
+        "click to collapse"
+    } else {
+        "click to expand"
+    };
+
+    let summary = button(
+        text(format!("{} ({action})", fold.summary()))
+            .style(theme::text::secondary),
+    )
+    .padding(0)
+    .style(theme::button::bare)
+    .on_press(Message::ToggleFold(fold.id()));
+
+    row![]
+        .push_maybe(timestamp)
+        .push_maybe(prefixes)
+        .push(marker)
+        .push(selectable_text(" "))
+        .push(summary)
+        .into()
+}
+
 fn preview_row<'a>(
     message: &'a data::Message,
     preview: &'a Preview,
@@ -1149,7 +2066,7 @@ fn preview_row<'a>(
             button(
                 container(image(path).content_fit(ContentFit::ScaleDown))
                     .max_width(550)
-                    .max_height(350),
+                    .max_height(config.preview.image.max_height),
             )
             .on_press(match config.preview.image.action {
                 data::config::preview::ImageAction::OpenUrl => {
@@ -1164,6 +2081,140 @@ fn preview_row<'a>(
         ),
     };
 
+    let aligned_content =
+        aligned(message, content, max_nick_width, max_prefix_width, config);
+
+    let hide_button = if is_hovered {
+        Some(
+            button(center(icon::cancel()))
+                .padding(5)
+                .width(22)
+                .height(22)
+                .on_press(Message::HidePreview(message.hash, url.clone()))
+                .style(|theme, status| {
+                    theme::button::secondary(theme, status, false)
+                }),
+        )
+    } else {
+        None
+    };
+
+    mouse_area(
+        row![aligned_content]
+            .push_maybe(hide_button)
+            .align_y(alignment::Vertical::Top)
+            .width(Length::Fill)
+            .spacing(4),
+    )
+    .on_enter(Message::PreviewHovered(message.hash, idx))
+    .on_exit(Message::PreviewUnhovered(message.hash, idx))
+    .into()
+}
+
+/// Adds a quote of the message replied to above `element`, and reactions
+/// beneath it. Replies to messages that aren't loaded only show the reply.
+fn annotated<'a>(
+    message: &'a data::Message,
+    element: Element<'a, Message>,
+    replied_to: Option<&'a data::Message>,
+    max_nick_width: Option<f32>,
+    max_prefix_width: Option<f32>,
+    config: &'a Config,
+) -> Element<'a, Message> {
+    if replied_to.is_none() && message.reactions.is_empty() {
+        return element;
+    }
+
+    let font_size = config.font.size.map_or(theme::TEXT_SIZE, f32::from) - 1.0;
+
+    let quote = replied_to.map(|replied_to| {
+        aligned(
+            message,
+            text(quote(replied_to))
+                .size(font_size)
+                .style(theme::text::secondary)
+                .shaping(text::Shaping::Advanced),
+            max_nick_width,
+            max_prefix_width,
+            config,
+        )
+    });
+
+    let reactions = (!message.reactions.is_empty()).then(|| {
+        aligned(
+            message,
+            text(reactions(&message.reactions))
+                .size(font_size)
+                .style(theme::text::secondary)
+                .shaping(text::Shaping::Advanced),
+            max_nick_width,
+            max_prefix_width,
+            config,
+        )
+    });
+
+    column![]
+        .push_maybe(quote)
+        .push(element)
+        .push_maybe(reactions)
+        .into()
+}
+
+/// First line of `message` with its sender, shortened
+fn quote(message: &data::Message) -> String {
+    const MAX_CHARS: usize = 80;
+
+    let text = message.text();
+    let line = text.lines().next().unwrap_or_default();
+
+    let snippet = if line.chars().count() > MAX_CHARS {
+        format!("{}…", line.chars().take(MAX_CHARS).collect::<String>())
+    } else {
+        line.to_string()
+    };
+
+    match message.target.source() {
+        message::Source::User(user) | message::Source::Action(Some(user)) => {
+            format!("↪ {}: {snippet}", user.nickname())
+        }
+        _ => format!("↪ {snippet}"),
+    }
+}
+
+/// Reactions with how many reacted with each, in the order first reacted
+fn reactions(reactions: &[message::Reaction]) -> String {
+    let mut counts = Vec::<(&str, usize)>::new();
+
+    for reaction in reactions {
+        match counts.iter_mut().find(|(text, _)| *text == reaction.text) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((&reaction.text, 1)),
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|(text, count)| {
+            if count > 1 {
+                format!("{text} {count}")
+            } else {
+                text.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+/// Lines `content` up with the content of `message`, for rows shown beneath
+/// or above it
+fn aligned<'a>(
+    message: &'a data::Message,
+    content: impl Into<Element<'a, Message>>,
+    max_nick_width: Option<f32>,
+    max_prefix_width: Option<f32>,
+    config: &'a Config,
+) -> Element<'a, Message> {
+    let content = content.into();
+
     let timestamp_gap = config
         .buffer
         .format_timestamp(&message.server_time)
@@ -1171,10 +2222,21 @@ fn preview_row<'a>(
             selectable_text(" ".repeat(timestamp.chars().count()))
         });
 
-    let aligned_content = match &config.buffer.nickname.alignment {
-        data::buffer::Alignment::Left => {
-            row![].push_maybe(timestamp_gap).push(content).into()
-        }
+    let avatar_gap = (config.buffer.nickname.avatars
+        == data::buffer::Avatars::Messages)
+        .then(|| {
+            row![
+                horizontal_space().width(message_view::AVATAR_SIZE),
+                selectable_text(" ")
+            ]
+        });
+
+    match &config.buffer.nickname.alignment {
+        data::buffer::Alignment::Left => row![]
+            .push_maybe(timestamp_gap)
+            .push_maybe(avatar_gap)
+            .push(content)
+            .into(),
         data::buffer::Alignment::Right => {
             let prefixes = message.target.prefixes().map_or(
                 max_nick_width.and_then(|_| {
@@ -1232,6 +2294,7 @@ fn preview_row<'a>(
 
             let timestamp_nickname_row = row![]
                 .push_maybe(timestamp_gap)
+                .push_maybe(avatar_gap)
                 .push_maybe(prefixes)
                 .push_maybe(nick)
                 .push(space);
@@ -1239,33 +2302,7 @@ fn preview_row<'a>(
             row![timestamp_nickname_row, content].into()
         }
         data::buffer::Alignment::Top => content,
-    };
-
-    let hide_button = if is_hovered {
-        Some(
-            button(center(icon::cancel()))
-                .padding(5)
-                .width(22)
-                .height(22)
-                .on_press(Message::HidePreview(message.hash, url.clone()))
-                .style(|theme, status| {
-                    theme::button::secondary(theme, status, false)
-                }),
-        )
-    } else {
-        None
-    };
-
-    mouse_area(
-        row![aligned_content]
-            .push_maybe(hide_button)
-            .align_y(alignment::Vertical::Top)
-            .width(Length::Fill)
-            .spacing(4),
-    )
-    .on_enter(Message::PreviewHovered(message.hash, idx))
-    .on_exit(Message::PreviewUnhovered(message.hash, idx))
-    .into()
+    }
 }
 
 mod correct_viewport {
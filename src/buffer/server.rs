@@ -1,12 +1,12 @@
 use std::path::PathBuf;
 
 use data::dashboard::BufferAction;
-use data::target::Target;
-use data::{Config, buffer, history, message};
+use data::target::{self, Target};
+use data::{Config, User, buffer, command, history, message};
 use iced::widget::{column, container, row, vertical_space};
 use iced::{Length, Task};
 
-use super::{input_view, scroll_view, user_context};
+use super::{input_view, message_view, scroll_view, user_context};
 use crate::widget::{Element, message_content, selectable_text};
 use crate::{Theme, theme};
 
@@ -23,6 +23,10 @@ pub enum Event {
     MarkAsRead(history::Kind),
     OpenUrl(String),
     ImagePreview(PathBuf, url::Url),
+    Theme(Option<String>),
+    TopicHistory(target::Channel, usize),
+    Layout(command::Layout),
+    ConfirmSend(buffer::Upstream, String),
 }
 
 pub fn view<'a>(
@@ -37,6 +41,7 @@ pub fn view<'a>(
     let casemapping = clients.get_casemapping(&state.server);
     let buffer = &state.buffer;
     let input = history.input(buffer);
+    let scheduled = history.scheduled().in_buffer(buffer).collect();
 
     let messages = container(
         scroll_view::view(
@@ -57,6 +62,27 @@ pub fn view<'a>(
 
                 match message.target.source() {
                     message::Source::Server(server) => {
+                        // Notices of users are set apart as `-nick-`, as they
+                        // are in queries
+                        let nickname = server
+                            .as_ref()
+                            .filter(|server| {
+                                server.kind()
+                                    == message::source::server::Kind::Notice
+                            })
+                            .and_then(|server| server.nick())
+                            .map(|nick| {
+                                let user = User::from(nick.clone());
+
+                                selectable_text(format!("-{nick}- ")).style(
+                                    move |theme| {
+                                        theme::selectable_text::nickname(
+                                            theme, config, &user, None,
+                                        )
+                                    },
+                                )
+                            });
+
                         let message = message_content(
                             &message.content,
                             casemapping,
@@ -71,12 +97,12 @@ pub fn view<'a>(
                             config,
                         );
 
-                        Some(
-                            container(
-                                row![].push_maybe(timestamp).push(message),
-                            )
-                            .into(),
-                        )
+                        Some(container(
+                            row![]
+                                .push_maybe(timestamp)
+                                .push_maybe(nickname)
+                                .push(message),
+                        ))
                     }
                     message::Source::Internal(
                         message::source::Internal::Status(status),
@@ -92,21 +118,38 @@ pub fn view<'a>(
                             config,
                         );
 
-                        Some(
-                            container(
-                                row![].push_maybe(timestamp).push(message),
-                            )
-                            .into(),
-                        )
+                        Some(container(
+                            row![].push_maybe(timestamp).push(message),
+                        ))
                     }
                     _ => None,
                 }
+                .map(|element| {
+                    message_view::with_context_menu(
+                        message, element, None, None, config,
+                    )
+                })
             },
         )
         .map(Message::ScrollView),
     )
     .height(Length::Fill);
 
+    let search_bar = scroll_view::search_bar(
+        &state.scroll_view,
+        scroll_view::Kind::Server(&state.server),
+        history,
+        config,
+        is_focused,
+    )
+    .map(|search_bar| search_bar.map(Message::ScrollView));
+
+    // Appended, so opening it doesn't reset the messages' scroll state
+    let messages = column![messages]
+        .push_maybe(search_bar)
+        .spacing(4)
+        .height(Length::Fill);
+
     let show_text_input = match config.buffer.text_input.visibility {
         data::buffer::TextInputVisibility::Focused => is_focused,
         data::buffer::TextInputVisibility::Always => true,
@@ -118,9 +161,12 @@ pub fn view<'a>(
             input_view::view(
                 &state.input_view,
                 input,
+                scheduled,
                 is_focused,
                 !status.connected(),
-                config
+                casemapping,
+                config,
+                theme,
             )
             .map(Message::InputView)
         ]
@@ -197,6 +243,13 @@ impl Server {
                     scroll_view::Event::ImagePreview(path, url) => {
                         Some(Event::ImagePreview(path, url))
                     }
+                    scroll_view::Event::Reply(_) => None,
+                    // Only offered in channels
+                    scroll_view::Event::SetActionStyle(..) => None,
+                    // Only offered in the logs buffer
+                    scroll_view::Event::GoToServer(_) => None,
+                    // Only offered in channels and queries
+                    scroll_view::Event::Translate(..) => None,
                 });
 
                 (command.map(Message::ScrollView), event)
@@ -224,6 +277,21 @@ impl Server {
                     Some(input_view::Event::OpenBuffers { targets }) => {
                         (command, Some(Event::OpenBuffers(targets)))
                     }
+                    Some(input_view::Event::Theme { name }) => {
+                        (command, Some(Event::Theme(name)))
+                    }
+                    Some(input_view::Event::TopicHistory {
+                        channel,
+                        count,
+                    }) => (command, Some(Event::TopicHistory(channel, count))),
+                    Some(input_view::Event::Layout { layout }) => {
+                        (command, Some(Event::Layout(layout)))
+                    }
+                    // Only parsed in channels
+                    Some(input_view::Event::BanList { .. }) => (command, None),
+                    Some(input_view::Event::ConfirmSend { buffer, text }) => {
+                        (command, Some(Event::ConfirmSend(buffer, text)))
+                    }
                     None => (command, None),
                 }
             }
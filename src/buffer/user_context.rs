@@ -1,4 +1,5 @@
 use data::dashboard::BufferAction;
+use data::ignore::Ignores;
 use data::user::Nick;
 use data::{Config, Server, User, config, ctcp, isupport, target};
 use iced::widget::{
@@ -7,12 +8,13 @@ use iced::widget::{
 use iced::{Length, Padding, padding};
 
 use crate::widget::{Element, context_menu, double_pass};
-use crate::{theme, widget};
+use crate::{Theme, theme, widget};
 
 #[derive(Debug, Clone, Copy)]
 pub enum Entry {
     Whois,
     Query,
+    Notice,
     ToggleAccessLevelOp,
     ToggleAccessLevelVoice,
     SendFile,
@@ -20,24 +22,34 @@ pub enum Entry {
     HorizontalRule,
     CtcpRequestTime,
     CtcpRequestVersion,
+    Kick,
+    Ban,
+    KickBan,
+    Ignore,
 }
 
 impl Entry {
     pub fn list(is_channel: bool, our_user: Option<&User>) -> Vec<Self> {
         if is_channel {
             if our_user.is_some_and(|u| {
-                u.has_access_level(data::user::AccessLevel::Oper)
+                u.highest_access_level() >= data::user::AccessLevel::Oper
             }) {
                 vec![
                     Entry::UserInfo,
                     Entry::HorizontalRule,
                     Entry::Whois,
                     Entry::Query,
+                    Entry::Notice,
                     Entry::SendFile,
+                    Entry::Ignore,
                     Entry::HorizontalRule,
                     Entry::ToggleAccessLevelOp,
                     Entry::ToggleAccessLevelVoice,
                     Entry::HorizontalRule,
+                    Entry::Kick,
+                    Entry::Ban,
+                    Entry::KickBan,
+                    Entry::HorizontalRule,
                     Entry::CtcpRequestVersion,
                     Entry::CtcpRequestTime,
                 ]
@@ -47,14 +59,16 @@ impl Entry {
                     Entry::HorizontalRule,
                     Entry::Whois,
                     Entry::Query,
+                    Entry::Notice,
                     Entry::SendFile,
+                    Entry::Ignore,
                     Entry::HorizontalRule,
                     Entry::CtcpRequestVersion,
                     Entry::CtcpRequestTime,
                 ]
             }
         } else {
-            vec![Entry::Whois, Entry::SendFile]
+            vec![Entry::Whois, Entry::Notice, Entry::SendFile, Entry::Ignore]
         }
     }
 
@@ -67,6 +81,7 @@ impl Entry {
         current_user: Option<&User>,
         length: Length,
         config: &Config,
+        ignores: &Ignores,
     ) -> Element<'a, Message> {
         let nickname = user.nickname().to_owned();
 
@@ -85,6 +100,11 @@ impl Entry {
                 ),
                 length,
             ),
+            Entry::Notice => menu_button(
+                "Notice",
+                Message::Notice(server.clone(), nickname),
+                length,
+            ),
             Entry::ToggleAccessLevelOp => {
                 if let Some(channel) = channel {
                     if user.has_access_level(data::user::AccessLevel::Oper) {
@@ -177,6 +197,65 @@ impl Entry {
                 ),
                 length,
             ),
+            Entry::Kick => {
+                if let Some(channel) = channel {
+                    menu_button(
+                        "Kick",
+                        Message::Kick(
+                            server.clone(),
+                            channel.clone(),
+                            nickname,
+                            config.buffer.channel.kick_reason.clone(),
+                        ),
+                        length,
+                    )
+                } else {
+                    row![].into()
+                }
+            }
+            Entry::Ban => {
+                if let Some(channel) = channel {
+                    menu_button(
+                        "Ban (+b)",
+                        Message::Ban(
+                            server.clone(),
+                            channel.clone(),
+                            current_user.unwrap_or(user).ban_mask(),
+                        ),
+                        length,
+                    )
+                } else {
+                    row![].into()
+                }
+            }
+            Entry::Ignore => {
+                // Channel users know the hostname matched by the ignore mask
+                let user = current_user.unwrap_or(user);
+                let is_ignored = ignores.is_ignored(server, user);
+
+                menu_button(
+                    if is_ignored { "Unignore" } else { "Ignore" },
+                    Message::Ignore(server.clone(), user.clone(), !is_ignored),
+                    length,
+                )
+            }
+            Entry::KickBan => {
+                if let Some(channel) = channel {
+                    menu_button(
+                        "Kick + Ban",
+                        Message::KickBan(
+                            server.clone(),
+                            channel.clone(),
+                            nickname,
+                            current_user.unwrap_or(user).ban_mask(),
+                            config.buffer.channel.kick_reason.clone(),
+                        ),
+                        length,
+                    )
+                } else {
+                    row![].into()
+                }
+            }
         }
     }
 }
@@ -185,20 +264,31 @@ impl Entry {
 pub enum Message {
     Whois(Server, Nick),
     Query(Server, target::Query, BufferAction),
+    Notice(Server, Nick),
     ToggleAccessLevel(Server, target::Channel, Nick, String),
     SendFile(Server, Nick),
     InsertNickname(Nick),
     CtcpRequest(ctcp::Command, Server, Nick, Option<String>),
+    Kick(Server, target::Channel, Nick, Option<String>),
+    Ban(Server, target::Channel, String),
+    KickBan(Server, target::Channel, Nick, String, Option<String>),
+    Ignore(Server, User, bool),
 }
 
 #[derive(Debug, Clone)]
 pub enum Event {
     SendWhois(Server, Nick),
     OpenQuery(Server, target::Query, BufferAction),
+    /// Composes a NOTICE to the user from the input of the buffer
+    Notice(Server, Nick),
     ToggleAccessLevel(Server, target::Channel, Nick, String),
     SendFile(Server, Nick),
     InsertNickname(Nick),
     CtcpRequest(ctcp::Command, Server, Nick, Option<String>),
+    Kick(Server, target::Channel, Nick, Option<String>),
+    Ban(Server, target::Channel, String),
+    KickBan(Server, target::Channel, Nick, String, Option<String>),
+    Ignore(Server, User, bool),
 }
 
 pub fn update(message: Message) -> Event {
@@ -207,6 +297,7 @@ pub fn update(message: Message) -> Event {
         Message::Query(server, nick, buffer_action) => {
             Event::OpenQuery(server, nick, buffer_action)
         }
+        Message::Notice(server, nick) => Event::Notice(server, nick),
         Message::ToggleAccessLevel(server, target, nick, mode) => {
             Event::ToggleAccessLevel(server, target, nick, mode)
         }
@@ -215,6 +306,18 @@ pub fn update(message: Message) -> Event {
         Message::CtcpRequest(command, server, nick, params) => {
             Event::CtcpRequest(command, server, nick, params)
         }
+        Message::Kick(server, channel, nick, reason) => {
+            Event::Kick(server, channel, nick, reason)
+        }
+        Message::Ban(server, channel, mask) => {
+            Event::Ban(server, channel, mask)
+        }
+        Message::KickBan(server, channel, nick, mask, reason) => {
+            Event::KickBan(server, channel, nick, mask, reason)
+        }
+        Message::Ignore(server, user, ignore) => {
+            Event::Ignore(server, user, ignore)
+        }
     }
 }
 
@@ -227,6 +330,7 @@ pub fn view<'a>(
     current_user: Option<&'a User>,
     our_user: Option<&'a User>,
     config: &'a Config,
+    ignores: &'a Ignores,
     click: &'a config::buffer::NicknameClickAction,
 ) -> Element<'a, Message> {
     let entries = Entry::list(channel.is_some(), our_user);
@@ -257,6 +361,7 @@ pub fn view<'a>(
                 current_user,
                 length,
                 config,
+                ignores,
             )
         },
     )
@@ -301,23 +406,36 @@ fn user_info<'a>(
     // Dimmed if away or offline.
     let is_user_away = current_user.is_some_and(User::is_away);
     let away_appearance = config.buffer.away.appearance(is_user_away);
-    let seed = match config.buffer.nickname.color {
-        data::buffer::Color::Solid => None,
-        data::buffer::Color::Unique => Some(nickname.to_string()),
-    };
+    let seed = nickname.to_string();
+    let kind = config.buffer.nickname.color;
+    let palette = config.buffer.nickname.palette.clone();
 
     column![
         container(
             text(nickname.to_string())
-                .style(move |theme| theme::text::nickname(
+                .style(move |theme: &Theme| theme::text::nickname(
                     theme,
-                    seed.clone(),
+                    data::appearance::theme::nickname_color(
+                        theme.colors().buffer.nickname,
+                        &seed,
+                        kind,
+                        &palette,
+                        None,
+                    ),
                     away_appearance
                 ))
                 .width(length)
         )
         .padding(right_justified_padding()),
     ]
+    .push_maybe(current_user.and_then(User::realname).map(|realname| {
+        container(
+            text(realname.to_string())
+                .style(theme::text::secondary)
+                .width(length),
+        )
+        .padding(right_justified_padding())
+    }))
     .push_maybe(state.map(|s| container(s).padding(right_justified_padding())))
     .into()
 }
@@ -5,6 +5,8 @@ pub enum Event {
     Copy,
     Escape,
     LeftClick,
+    ModifiersReleased,
+    KeyReleased,
 }
 
 pub fn events() -> Subscription<(window::Id, Event)> {
@@ -28,6 +30,16 @@ fn filtered_events(
             modifiers,
             ..
         }) if c.as_str() == "c" && modifiers.command() => Some(Event::Copy),
+        iced::Event::Keyboard(keyboard::Event::ModifiersChanged(modifiers))
+            if !(modifiers.control()
+                || modifiers.alt()
+                || modifiers.logo()) =>
+        {
+            Some(Event::ModifiersReleased)
+        }
+        iced::Event::Keyboard(keyboard::Event::KeyReleased { .. }) => {
+            Some(Event::KeyReleased)
+        }
         iced::Event::Mouse(mouse::Event::ButtonPressed(
             mouse::Button::Left,
         )) if ignored(status) => Some(Event::LeftClick),
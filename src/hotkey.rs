@@ -0,0 +1,272 @@
+//! System-wide hotkey toggling the main window.
+//!
+//! Grabbing keys outside of the application isn't supported everywhere
+//! (e.g. most Wayland compositors refuse it), so this is only available with
+//! the `global-hotkey` feature and failures are never fatal.
+
+use data::shortcut::KeyBind;
+use iced::Subscription;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[cfg(not(feature = "global-hotkey"))]
+    #[error("Halloy was built without the `global-hotkey` feature")]
+    Unsupported,
+    #[cfg(feature = "global-hotkey")]
+    #[error("{0} can't be used as a global hotkey")]
+    InvalidKey(KeyBind),
+    #[cfg(feature = "global-hotkey")]
+    #[error(transparent)]
+    Platform(#[from] global_hotkey::Error),
+}
+
+#[derive(Default)]
+pub struct GlobalHotkey {
+    #[cfg(feature = "global-hotkey")]
+    manager: Option<global_hotkey::GlobalHotKeyManager>,
+    #[cfg(feature = "global-hotkey")]
+    registered: Option<(KeyBind, global_hotkey::hotkey::HotKey)>,
+}
+
+impl GlobalHotkey {
+    /// Registers `key_bind`, replacing the current hotkey if it changed.
+    #[cfg(feature = "global-hotkey")]
+    pub fn register(
+        &mut self,
+        key_bind: Option<&KeyBind>,
+    ) -> Result<(), Error> {
+        use global_hotkey::GlobalHotKeyManager;
+
+        if self.registered.as_ref().map(|(registered, _)| registered)
+            == key_bind
+        {
+            return Ok(());
+        }
+
+        let unregistered = self
+            .registered
+            .take()
+            .zip(self.manager.as_ref())
+            .map(|((_, hotkey), manager)| manager.unregister(hotkey));
+
+        if let Some(Err(error)) = unregistered {
+            log::warn!("failed to unregister global hotkey: {error}");
+        }
+
+        let Some(key_bind) = key_bind else {
+            return Ok(());
+        };
+
+        let hotkey = hotkey(key_bind)
+            .ok_or_else(|| Error::InvalidKey(key_bind.clone()))?;
+
+        // Created on first use, as it may hook into the platform's event loop
+        let manager = match &mut self.manager {
+            Some(manager) => manager,
+            None => self.manager.insert(GlobalHotKeyManager::new()?),
+        };
+
+        manager.register(hotkey)?;
+
+        self.registered = Some((key_bind.clone(), hotkey));
+
+        Ok(())
+    }
+
+    #[cfg(not(feature = "global-hotkey"))]
+    pub fn register(
+        &mut self,
+        key_bind: Option<&KeyBind>,
+    ) -> Result<(), Error> {
+        match key_bind {
+            Some(_) => Err(Error::Unsupported),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Emits when the registered hotkey is pressed.
+#[cfg(feature = "global-hotkey")]
+pub fn listen() -> Subscription<()> {
+    use futures::StreamExt;
+    use futures::stream::BoxStream;
+    use global_hotkey::{GlobalHotKeyEvent, HotKeyState};
+    use iced::advanced::graphics::futures::subscription;
+    use iced::advanced::subscription::Hasher;
+
+    struct Listener;
+
+    impl subscription::Recipe for Listener {
+        type Output = ();
+
+        fn hash(&self, state: &mut Hasher) {
+            use std::hash::Hash;
+
+            struct Marker;
+            std::any::TypeId::of::<Marker>().hash(state);
+        }
+
+        fn stream(
+            self: Box<Self>,
+            _input: subscription::EventStream,
+        ) -> BoxStream<'static, Self::Output> {
+            futures::stream::unfold((), |()| async {
+                loop {
+                    let event = tokio::task::spawn_blocking(|| {
+                        GlobalHotKeyEvent::receiver().recv()
+                    })
+                    .await;
+
+                    match event {
+                        Ok(Ok(event))
+                            if event.state == HotKeyState::Pressed =>
+                        {
+                            return Some(((), ()));
+                        }
+                        Ok(Ok(_)) => {}
+                        Ok(Err(_)) | Err(_) => return None,
+                    }
+                }
+            })
+            .boxed()
+        }
+    }
+
+    subscription::from_recipe(Listener)
+}
+
+#[cfg(not(feature = "global-hotkey"))]
+pub fn listen() -> Subscription<()> {
+    Subscription::none()
+}
+
+#[cfg(feature = "global-hotkey")]
+fn hotkey(key_bind: &KeyBind) -> Option<global_hotkey::hotkey::HotKey> {
+    use global_hotkey::hotkey::{Code, HotKey, Modifiers};
+    use iced::keyboard::Key;
+    use iced::keyboard::key::Named;
+
+    // Hotkeys are grabbed by physical key, so characters are mapped to their
+    // position on a US layout
+    let code = match key_bind.key() {
+        Key::Character(c) => match c.to_lowercase().as_str() {
+            "a" => Code::KeyA,
+            "b" => Code::KeyB,
+            "c" => Code::KeyC,
+            "d" => Code::KeyD,
+            "e" => Code::KeyE,
+            "f" => Code::KeyF,
+            "g" => Code::KeyG,
+            "h" => Code::KeyH,
+            "i" => Code::KeyI,
+            "j" => Code::KeyJ,
+            "k" => Code::KeyK,
+            "l" => Code::KeyL,
+            "m" => Code::KeyM,
+            "n" => Code::KeyN,
+            "o" => Code::KeyO,
+            "p" => Code::KeyP,
+            "q" => Code::KeyQ,
+            "r" => Code::KeyR,
+            "s" => Code::KeyS,
+            "t" => Code::KeyT,
+            "u" => Code::KeyU,
+            "v" => Code::KeyV,
+            "w" => Code::KeyW,
+            "x" => Code::KeyX,
+            "y" => Code::KeyY,
+            "z" => Code::KeyZ,
+            "0" => Code::Digit0,
+            "1" => Code::Digit1,
+            "2" => Code::Digit2,
+            "3" => Code::Digit3,
+            "4" => Code::Digit4,
+            "5" => Code::Digit5,
+            "6" => Code::Digit6,
+            "7" => Code::Digit7,
+            "8" => Code::Digit8,
+            "9" => Code::Digit9,
+            "`" => Code::Backquote,
+            "-" => Code::Minus,
+            "=" => Code::Equal,
+            "[" => Code::BracketLeft,
+            "]" => Code::BracketRight,
+            "\\" => Code::Backslash,
+            ";" => Code::Semicolon,
+            "'" => Code::Quote,
+            "," => Code::Comma,
+            "." => Code::Period,
+            "/" => Code::Slash,
+            _ => return None,
+        },
+        Key::Named(named) => match named {
+            Named::Escape => Code::Escape,
+            Named::F1 => Code::F1,
+            Named::F2 => Code::F2,
+            Named::F3 => Code::F3,
+            Named::F4 => Code::F4,
+            Named::F5 => Code::F5,
+            Named::F6 => Code::F6,
+            Named::F7 => Code::F7,
+            Named::F8 => Code::F8,
+            Named::F9 => Code::F9,
+            Named::F10 => Code::F10,
+            Named::F11 => Code::F11,
+            Named::F12 => Code::F12,
+            Named::F13 => Code::F13,
+            Named::F14 => Code::F14,
+            Named::F15 => Code::F15,
+            Named::F16 => Code::F16,
+            Named::F17 => Code::F17,
+            Named::F18 => Code::F18,
+            Named::F19 => Code::F19,
+            Named::F20 => Code::F20,
+            Named::F21 => Code::F21,
+            Named::F22 => Code::F22,
+            Named::F23 => Code::F23,
+            Named::F24 => Code::F24,
+            Named::Home => Code::Home,
+            Named::Delete => Code::Delete,
+            Named::End => Code::End,
+            Named::PageDown => Code::PageDown,
+            Named::PageUp => Code::PageUp,
+            Named::ArrowLeft => Code::ArrowLeft,
+            Named::ArrowUp => Code::ArrowUp,
+            Named::ArrowRight => Code::ArrowRight,
+            Named::ArrowDown => Code::ArrowDown,
+            Named::Backspace => Code::Backspace,
+            Named::Enter => Code::Enter,
+            Named::Space => Code::Space,
+            Named::Tab => Code::Tab,
+            Named::Pause => Code::Pause,
+            Named::Insert => Code::Insert,
+            Named::AudioVolumeDown => Code::AudioVolumeDown,
+            Named::AudioVolumeUp => Code::AudioVolumeUp,
+            Named::AudioVolumeMute => Code::AudioVolumeMute,
+            Named::MediaStop => Code::MediaStop,
+            Named::MediaPause => Code::MediaPlayPause,
+            Named::MediaTrackNext => Code::MediaTrackNext,
+            Named::MediaTrackPrevious => Code::MediaTrackPrevious,
+            _ => return None,
+        },
+        Key::Unidentified => return None,
+    };
+
+    let modifiers = key_bind.modifiers();
+    let mut mods = Modifiers::empty();
+
+    if modifiers.shift() {
+        mods |= Modifiers::SHIFT;
+    }
+    if modifiers.control() {
+        mods |= Modifiers::CONTROL;
+    }
+    if modifiers.alt() {
+        mods |= Modifiers::ALT;
+    }
+    if modifiers.logo() {
+        mods |= Modifiers::SUPER;
+    }
+
+    Some(HotKey::new(Some(mods), code))
+}
@@ -2,7 +2,6 @@ use std::sync::mpsc;
 use std::time::{Duration, Instant};
 use std::{env, mem, thread};
 
-use chrono::Utc;
 pub use data::log::{Error, Record};
 use log::Log;
 use tokio::sync::mpsc as tokio_mpsc;
@@ -19,9 +18,10 @@ pub fn setup(is_debug: bool) -> Result<ReceiverStream<Vec<Record>>, Error> {
 
     let mut io_sink = fern::Dispatch::new().format(|out, message, record| {
         out.finish(format_args!(
-            "{}:{} -- {}",
+            "{}:{} {} -- {}",
             chrono::Local::now().format("%H:%M:%S%.3f"),
             record.level(),
+            record.target(),
             message
         ));
     });
@@ -60,11 +60,11 @@ fn channel_logger() -> (Box<dyn Log>, ReceiverStream<Vec<Record>>) {
         }
 
         fn log(&self, record: &::log::Record) {
-            let _ = self.sender.send(Record {
-                timestamp: Utc::now(),
-                level: record.level().into(),
-                message: format!("{}", record.args()),
-            });
+            let _ = self.sender.send(Record::new(
+                record.level().into(),
+                record.target(),
+                format!("{}", record.args()),
+            ));
         }
 
         fn flush(&self) {}
@@ -6,17 +6,21 @@ mod audio;
 mod buffer;
 mod event;
 mod font;
+mod hotkey;
 mod icon;
 mod logger;
 mod modal;
 mod notification;
+mod opener;
 mod screen;
 mod stream;
 mod url;
+mod watchdog;
 mod widget;
 mod window;
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 use std::{env, mem};
@@ -33,26 +37,65 @@ use data::{
 };
 use iced::widget::{column, container};
 use iced::{Length, Subscription, Task, padding};
-use screen::{dashboard, help, migration, welcome};
+use screen::{dashboard, help, migration, profiles, recovery, welcome};
 use tokio::runtime;
 use tokio_stream::wrappers::ReceiverStream;
 
 use self::event::{Event, events};
+use self::hotkey::GlobalHotkey;
 use self::modal::Modal;
 use self::notification::Notifications;
+use self::watchdog::Watchdog;
 use self::widget::Element;
 use self::window::Window;
 
 pub fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let mut args = env::args();
+    let mut args = env::args().peekable();
     args.next();
 
-    let version = args.next().is_some_and(|s| s == "--version" || s == "-V");
+    let mut profile = None;
+    let mut check_config = None;
 
-    if version {
-        println!("halloy {}", environment::formatted_version());
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--version" | "-V" => {
+                println!("halloy {}", environment::formatted_version());
 
-        return Ok(());
+                return Ok(());
+            }
+            "--profile" | "-p" => profile = args.next(),
+            // The path is optional, defaulting to the config of the profile
+            "--check-config" => {
+                check_config = Some(
+                    args.next_if(|arg| !arg.starts_with('-'))
+                        .map(PathBuf::from),
+                );
+            }
+            _ => {
+                if let Some(name) = arg.strip_prefix("--profile=") {
+                    profile = Some(name.to_string());
+                }
+            }
+        }
+    }
+
+    let destination = data::Url::find_in(std::env::args());
+
+    // DANGER ZONE - the profile must be set before any path is resolved
+    let pick_profile = match profile {
+        Some(name) => {
+            if let Some(name) = data::profile::parse(&name)? {
+                environment::set_profile(name);
+            }
+
+            false
+        }
+        // URLs are opened in the default profile
+        None => destination.is_none() && data::profile::list().len() > 1,
+    };
+
+    if let Some(path) = check_config {
+        return run_config_check(path);
     }
 
     let is_debug = cfg!(debug_assertions);
@@ -65,6 +108,10 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     log::info!("config dir: {:?}", environment::config_dir());
     log::info!("data dir: {:?}", environment::data_dir());
 
+    if let Some(profile) = environment::profile() {
+        log::info!("profile: {profile}");
+    }
+
     // spin up a single-threaded tokio runtime to run the config loading task to completion
     // we don't want to wrap our whole program with a runtime since iced starts its own.
     let (config_load, window_load) = {
@@ -76,6 +123,12 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
             let config = Config::load().await;
             let window = data::Window::load().await;
 
+            if !pick_profile {
+                if let Err(error) = data::profile::touch().await {
+                    log::warn!("failed to record profile usage: {error}");
+                }
+            }
+
             (config, window)
         })
     };
@@ -83,7 +136,6 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     // before we do any iced related stuff w/ it
     font::set(config_load.as_ref().ok());
 
-    let destination = data::Url::find_in(std::env::args());
     if let Some(loc) = &destination {
         if ipc::connect_and_send(loc.to_string()) {
             return Ok(());
@@ -110,6 +162,7 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
                 // we start with an unspecified mode because we are guaranteed to
                 // receive a message from mundy containing the correct mode on startup.
                 appearance::Mode::Unspecified,
+                pick_profile,
             )
         },
         Halloy::update,
@@ -126,6 +179,34 @@ pub fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// Loads the config at `path`, or that of the profile, without starting the
+/// GUI. Prints what's wrong and exits with 1 if it doesn't load.
+fn run_config_check(
+    path: Option<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.unwrap_or_else(Config::path);
+
+    let result = runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(config::check::file(&path));
+
+    match result {
+        Ok(summary) => {
+            println!("{}: {summary}", path.display());
+
+            Ok(())
+        }
+        Err(errors) => {
+            for error in errors {
+                eprintln!("{}: {error}", path.display());
+            }
+
+            std::process::exit(1);
+        }
+    }
+}
+
 fn settings(config_load: &Result<Config, config::Error>) -> iced::Settings {
     let default_text_size = config_load
         .as_ref()
@@ -154,10 +235,14 @@ struct Halloy {
     config: Config,
     clients: data::client::Map,
     servers: server::Map,
+    /// Times each server was retried from the dashboard
+    retries: HashMap<Server, usize>,
     modal: Option<Modal>,
     main_window: Window,
     pending_logs: Vec<data::log::Record>,
     notifications: Notifications,
+    global_hotkey: GlobalHotkey,
+    watchdog: Watchdog,
 }
 
 impl Halloy {
@@ -169,13 +254,23 @@ impl Halloy {
         let main_window = Window::new(main_window);
 
         let load_dashboard = |config| match data::Dashboard::load() {
-            Ok(dashboard) => {
-                screen::Dashboard::restore(dashboard, config, &main_window)
+            Ok(Some(dashboard)) => {
+                let (screen, command) =
+                    screen::Dashboard::restore(dashboard, config, &main_window);
+
+                (Screen::Dashboard(screen), command.map(Message::Dashboard))
             }
+            Ok(None) => {
+                let (screen, command) =
+                    screen::Dashboard::empty(config, &main_window);
+
+                (Screen::Dashboard(screen), command.map(Message::Dashboard))
+            }
+            // Left to the user rather than silently starting empty
             Err(error) => {
                 log::warn!("failed to load dashboard: {error}");
 
-                screen::Dashboard::empty(config, &main_window)
+                (Screen::Recovery(screen::Recovery::new(error)), Task::none())
             }
         };
 
@@ -183,11 +278,7 @@ impl Halloy {
             Ok(config) => {
                 let (screen, command) = load_dashboard(&config);
 
-                (
-                    Screen::Dashboard(screen),
-                    config,
-                    command.map(Message::Dashboard),
-                )
+                (screen, config, command)
             }
             // If we have a YAML file, but end up in this arm
             // it means the user tried to load Halloy with a YAML configuration, but it expected TOML.
@@ -214,29 +305,46 @@ impl Halloy {
         };
 
         (
-            Halloy {
-                version: Version::new(),
-                screen,
-                current_mode,
-                theme: current_mode.theme(&config.appearance.selected).into(),
-                clients: data::client::Map::default(),
-                servers: config.servers.clone(),
-                config,
-                modal: None,
-                main_window,
-                pending_logs: vec![],
-                notifications: Notifications::new(),
-            },
+            Halloy::with_screen(main_window, screen, config, current_mode),
             command,
         )
     }
+
+    fn with_screen(
+        main_window: Window,
+        screen: Screen,
+        config: Config,
+        current_mode: appearance::Mode,
+    ) -> Halloy {
+        let mut clients = data::client::Map::default();
+        clients.add_idle(&config.servers);
+
+        Halloy {
+            version: Version::new(),
+            screen,
+            current_mode,
+            theme: current_mode.theme(&config.appearance.selected).into(),
+            clients,
+            servers: config.servers.clone(),
+            retries: HashMap::new(),
+            config,
+            modal: None,
+            main_window,
+            pending_logs: vec![],
+            notifications: Notifications::new(),
+            global_hotkey: GlobalHotkey::default(),
+            watchdog: Watchdog::default(),
+        }
+    }
 }
 
 pub enum Screen {
     Dashboard(screen::Dashboard),
     Help(screen::Help),
+    Recovery(screen::Recovery),
     Welcome(screen::Welcome),
     Migration(screen::Migration),
+    Profiles(screen::Profiles),
     Exit { pending_exit: HashSet<Server> },
 }
 
@@ -247,8 +355,10 @@ pub enum Message {
     Dashboard(dashboard::Message),
     Stream(stream::Update),
     Help(help::Message),
+    Recovery(recovery::Message),
     Welcome(welcome::Message),
     Migration(migration::Message),
+    Profiles(profiles::Message),
     Event(window::Id, Event),
     Tick(Instant),
     Version(Option<String>),
@@ -258,7 +368,40 @@ pub enum Message {
     Window(window::Id, window::Event),
     WindowSettingsSaved(Result<(), window::Error>),
     Logging(Vec<logger::Record>),
+    GlobalHotkey,
     OnConnect(Server, client::on_connect::Event),
+    Watchdog(Instant),
+    NotificationClicked(notification::Trigger),
+}
+
+impl Message {
+    /// Variant name, recorded by the watchdog
+    fn name(&self) -> &'static str {
+        match self {
+            Message::AppearanceReloaded(_) => "AppearanceReloaded",
+            Message::ScreenConfigReloaded(_) => "ScreenConfigReloaded",
+            Message::Dashboard(message) => message.name(),
+            Message::Stream(_) => "Stream",
+            Message::Help(_) => "Help",
+            Message::Recovery(_) => "Recovery",
+            Message::Welcome(_) => "Welcome",
+            Message::Migration(_) => "Migration",
+            Message::Profiles(_) => "Profiles",
+            Message::Event(_, _) => "Event",
+            Message::Tick(_) => "Tick",
+            Message::Version(_) => "Version",
+            Message::Modal(_) => "Modal",
+            Message::RouteReceived(_) => "RouteReceived",
+            Message::AppearanceChange(_) => "AppearanceChange",
+            Message::Window(_, _) => "Window",
+            Message::WindowSettingsSaved(_) => "WindowSettingsSaved",
+            Message::Logging(_) => "Logging",
+            Message::GlobalHotkey => "GlobalHotkey",
+            Message::OnConnect(_, _) => "OnConnect",
+            Message::Watchdog(_) => "Watchdog",
+            Message::NotificationClicked(_) => "NotificationClicked",
+        }
+    }
 }
 
 impl Halloy {
@@ -268,6 +411,7 @@ impl Halloy {
         url_received: Option<data::Url>,
         log_stream: ReceiverStream<Vec<logger::Record>>,
         current_mode: appearance::Mode,
+        pick_profile: bool,
     ) -> (Halloy, Task<Message>) {
         let data::Window { size, position } = window_load.unwrap_or_default();
         let position =
@@ -281,8 +425,30 @@ impl Halloy {
             ..window::settings()
         });
 
-        let (mut halloy, command) =
-            Halloy::load_from_state(main_window, config_load, current_mode);
+        let (mut halloy, command) = if pick_profile {
+            // Nothing is restored until a profile has been picked
+            let screen =
+                Screen::Profiles(screen::Profiles::new(data::profile::list()));
+
+            (
+                Halloy::with_screen(
+                    Window::new(main_window),
+                    screen,
+                    config_load.unwrap_or_default(),
+                    current_mode,
+                ),
+                Task::none(),
+            )
+        } else {
+            let (mut halloy, command) =
+                Halloy::load_from_state(main_window, config_load, current_mode);
+            halloy.register_global_hotkey();
+
+            (halloy, command)
+        };
+
+        data::http::configure(&halloy.config);
+
         let latest_remote_version =
             Task::perform(version::latest_remote_version(), Message::Version);
 
@@ -300,6 +466,16 @@ impl Halloy {
         (halloy, Task::batch(commands))
     }
 
+    fn register_global_hotkey(&mut self) {
+        let key_bind = self.config.keyboard.global_toggle_window.as_ref();
+
+        if let Err(error) = self.global_hotkey.register(key_bind) {
+            log::warn!("failed to register global hotkey: {error}");
+
+            notification::toast("Global hotkey unavailable", error);
+        }
+    }
+
     fn handle_url(&mut self, url: Url) -> Task<Message> {
         match url {
             data::Url::ServerConnect {
@@ -333,19 +509,32 @@ impl Halloy {
     }
 
     fn title(&self, _window_id: window::Id) -> String {
-        String::from("Halloy")
+        match environment::profile() {
+            Some(profile) => format!("Halloy ({profile})"),
+            None => String::from("Halloy"),
+        }
     }
 
     fn update(&mut self, message: Message) -> Task<Message> {
+        // Pings are left out so a stall is attributed to what preceded them
+        if !matches!(message, Message::Watchdog(_)) {
+            self.watchdog.handling(message.name());
+        }
+
         match message {
             Message::AppearanceReloaded(appearance) => {
                 self.config.appearance = appearance;
                 Task::none()
             }
             Message::ScreenConfigReloaded(updated) => {
-                let (halloy, command) =
+                let (mut halloy, command) =
                     Halloy::load_from_state(self.main_window.id, updated, self.current_mode);
+                // Keep the registered hotkey, it's only replaced if changed
+                halloy.global_hotkey = mem::take(&mut self.global_hotkey);
+                halloy.watchdog = mem::take(&mut self.watchdog);
                 *self = halloy;
+                self.register_global_hotkey();
+                data::http::configure(&self.config);
                 command
             }
             Message::Dashboard(message) => {
@@ -379,14 +568,21 @@ impl Halloy {
                                     .collect::<Vec<_>>();
 
                                 self.servers = updated.servers.clone();
+                                self.clients.add_idle(&self.servers);
                                 self.theme = self.current_mode.theme(
                                     &updated.appearance.selected,
                                 )
                                 .into();
                                 self.config = updated;
+                                self.register_global_hotkey();
+                                data::http::configure(&self.config);
 
                                 for server in removed_servers {
-                                    self.clients.quit(&server, None);
+                                    if self.clients.is_idle(&server) {
+                                        self.clients.remove(&server);
+                                    } else {
+                                        self.clients.quit(&server, None);
+                                    }
                                 }
                             }
                             Err(error) => {
@@ -403,7 +599,15 @@ impl Halloy {
                             .map(Message::AppearanceReloaded)
                     }
                     Some(dashboard::Event::QuitServer(server)) => {
-                        self.clients.quit(&server, None);
+                        self.modal = Some(Modal::QuitServer(server));
+                        Task::none()
+                    }
+                    Some(dashboard::Event::ConnectServer(server)) => {
+                        self.clients.connect(&server);
+                        Task::none()
+                    }
+                    Some(dashboard::Event::RetryServer(server)) => {
+                        *self.retries.entry(server).or_default() += 1;
                         Task::none()
                     }
                     Some(dashboard::Event::IrcError(e)) => {
@@ -452,6 +656,41 @@ impl Halloy {
                         });
                         Task::none()
                     }
+                    Some(dashboard::Event::OpenRawComposer) => {
+                        let servers = self
+                            .clients
+                            .connected_servers()
+                            .cloned()
+                            .collect::<Vec<_>>();
+
+                        self.modal = Some(Modal::RawComposer {
+                            server: servers.first().cloned(),
+                            servers,
+                            draft: data::composer::Draft::default(),
+                        });
+                        Task::none()
+                    }
+                    Some(dashboard::Event::ConfirmSend(buffer, text)) => {
+                        let Some((id, _, _)) = dashboard.get_focused() else {
+                            return Task::none();
+                        };
+
+                        self.modal = Some(Modal::ConfirmSend {
+                            buffer,
+                            text,
+                            pastebin_command: self
+                                .config
+                                .buffer
+                                .text_input
+                                .confirm_send
+                                .pastebin_command
+                                .clone(),
+                            uploading: false,
+                            error: None,
+                            window: id,
+                        });
+                        Task::none()
+                    }
                     None => Task::none(),
                 };
 
@@ -477,9 +716,66 @@ impl Halloy {
                         Config::load(),
                         Message::ScreenConfigReloaded,
                     ),
+                    Some(help::Event::OpenConfigurationFile) => {
+                        opener::open(opener::Target::ConfigFile)
+                    }
+                    Some(help::Event::OpenConfigurationDirectory) => {
+                        opener::open(opener::Target::ConfigDirectory)
+                    }
+                    Some(help::Event::StartWithLastGood) => Task::perform(
+                        Config::restore_last_good(),
+                        Message::ScreenConfigReloaded,
+                    ),
                     None => Task::none(),
                 }
             }
+            Message::Recovery(message) => {
+                let Screen::Recovery(recovery) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                let (screen, command) = match recovery.update(message) {
+                    Some(recovery::Event::ResetLayout) => {
+                        if let Err(error) = data::Dashboard::set_aside_broken()
+                        {
+                            log::warn!(
+                                "failed to set aside dashboard: {error}"
+                            );
+                        }
+
+                        screen::Dashboard::empty(
+                            &self.config,
+                            &self.main_window,
+                        )
+                    }
+                    Some(recovery::Event::TryLastGood) => {
+                        match data::Dashboard::restore_last_good() {
+                            Ok(dashboard) => screen::Dashboard::restore(
+                                dashboard,
+                                &self.config,
+                                &self.main_window,
+                            ),
+                            Err(error) => {
+                                log::warn!(
+                                    "failed to load last known good dashboard: {error}"
+                                );
+
+                                recovery.last_good_failed(error);
+
+                                return Task::none();
+                            }
+                        }
+                    }
+                    Some(recovery::Event::OpenDataDirectory) => {
+                        return opener::open(opener::Target::DataDirectory);
+                    }
+                    None => return Task::none(),
+                };
+
+                self.screen = Screen::Dashboard(screen);
+
+                command.map(Message::Dashboard)
+            }
             Message::Welcome(message) => {
                 let Screen::Welcome(welcome) = &mut self.screen else {
                     return Task::none();
@@ -492,6 +788,9 @@ impl Halloy {
                             Message::ScreenConfigReloaded,
                         )
                     }
+                    Some(welcome::Event::OpenConfigurationDirectory) => {
+                        opener::open(opener::Target::ConfigDirectory)
+                    }
                     None => Task::none(),
                 }
             }
@@ -507,6 +806,35 @@ impl Halloy {
                             Message::ScreenConfigReloaded,
                         )
                     }
+                    Some(migration::Event::OpenConfigurationDirectory) => {
+                        opener::open(opener::Target::ConfigDirectory)
+                    }
+                    None => Task::none(),
+                }
+            }
+            Message::Profiles(message) => {
+                let Screen::Profiles(profiles) = &mut self.screen else {
+                    return Task::none();
+                };
+
+                match profiles.update(message) {
+                    // Paths are resolved once per process, so the picked
+                    // profile runs in a new one
+                    Some(profiles::Event::Selected(profile)) => {
+                        match data::profile::launch(profile.as_deref()) {
+                            Ok(()) => iced::exit(),
+                            Err(error) => {
+                                log::warn!("failed to launch profile: {error}");
+
+                                notification::toast(
+                                    "Failed to launch profile",
+                                    error,
+                                );
+
+                                Task::none()
+                            }
+                        }
+                    }
                     None => Task::none(),
                 }
             }
@@ -515,6 +843,7 @@ impl Halloy {
                     server,
                     is_initial,
                     error,
+                    reason,
                     sent_time,
                 } => {
                     self.clients.disconnected(server.clone());
@@ -535,9 +864,10 @@ impl Halloy {
                             .map(Message::Dashboard)
                     } else {
                         self.notifications.notify(
-                            &self.config.notifications,
-                            &Notification::Disconnected,
+                            &self.config,
+                            &Notification::Disconnected(reason),
                             &server,
+                            None,
                         );
 
                         dashboard
@@ -545,7 +875,7 @@ impl Halloy {
                                 &server,
                                 &self.config,
                                 sent_time,
-                                Broadcast::Disconnected { error },
+                                Broadcast::Disconnected { error, reason },
                             )
                             .map(Message::Dashboard)
                     }
@@ -558,15 +888,20 @@ impl Halloy {
                 } => {
                     self.clients.ready(server.clone(), connection);
 
+                    if !is_initial {
+                        self.clients.resync_users_on_join(&server);
+                    }
+
                     let Screen::Dashboard(dashboard) = &mut self.screen else {
                         return Task::none();
                     };
 
                     let broadcast = if is_initial {
                         self.notifications.notify(
-                            &self.config.notifications,
+                            &self.config,
                             &Notification::Connected,
                             &server,
+                            None,
                         );
 
                         dashboard
@@ -579,9 +914,10 @@ impl Halloy {
                             .map(Message::Dashboard)
                     } else {
                         self.notifications.notify(
-                            &self.config.notifications,
+                            &self.config,
                             &Notification::Reconnected,
                             &server,
+                            None,
                         );
 
                         dashboard
@@ -640,6 +976,29 @@ impl Halloy {
                             let mut commands = vec![];
 
                             for event in events {
+                                // Client state is still updated, only what's shown is dropped
+                                let source_user = match &event {
+                                    data::client::Event::Single(encoded, ..)
+                                    | data::client::Event::PrivOrNotice(encoded, ..)
+                                    | data::client::Event::WithTarget(encoded, ..)
+                                    | data::client::Event::DirectMessage(encoded, ..) => {
+                                        encoded.user()
+                                    }
+                                    data::client::Event::Broadcast(
+                                        data::client::Broadcast::Quit { user, .. },
+                                    ) => Some(user.clone()),
+                                    data::client::Event::Broadcast(
+                                        data::client::Broadcast::Nickname { old_user, .. },
+                                    ) => Some(old_user.clone()),
+                                    _ => None,
+                                };
+
+                                if source_user
+                                    .is_some_and(|user| dashboard.is_ignored(&server, &user))
+                                {
+                                    continue;
+                                }
+
                                 // Resolve a user using client state which stores attributes
                                 let resolve_user_attributes =
                                     |user: &User, channel: &target::Channel| {
@@ -652,15 +1011,37 @@ impl Halloy {
                                     self.clients.get_channel_users(&server, channel)
                                 };
 
+                                let is_joined = |channel: &target::Channel| {
+                                    self.clients.get_channels(&server).contains(channel)
+                                };
+
                                 let chantypes = self.clients.get_chantypes(&server);
                                 let statusmsg = self.clients.get_statusmsg(&server);
                                 let casemapping = self.clients.get_casemapping(&server);
+                                let highlight_nicks = self
+                                    .clients
+                                    .get_highlight_nicks(&server, &self.config.highlights);
 
                                 match event {
                                     data::client::Event::Single(encoded, our_nick) => {
+                                        let is_privmsg = encoded.is_privmsg();
+
+                                        if let Some((channel, entries)) =
+                                            data::history::audit::entries(
+                                                &encoded,
+                                                chantypes,
+                                                statusmsg,
+                                                casemapping,
+                                            )
+                                        {
+                                            dashboard.record_audit(&server, channel, entries);
+                                        }
+
                                         if let Some(message) = data::Message::received(
                                             encoded,
                                             our_nick,
+                                            &server,
+                                            &highlight_nicks,
                                             &self.config,
                                             resolve_user_attributes,
                                             channel_users,
@@ -670,9 +1051,12 @@ impl Halloy {
                                         ) {
                                             commands.push(
                                                 dashboard
-                                                    .record_message(
+                                                    .record_received(
                                                         &server,
                                                         message,
+                                                        is_privmsg,
+                                                        is_joined,
+                                                        &self.config,
                                                     )
                                                     .map(Message::Dashboard),
                                             );
@@ -683,9 +1067,13 @@ impl Halloy {
                                         our_nick,
                                         highlight_notification_enabled,
                                     ) => {
+                                        let is_privmsg = encoded.is_privmsg();
+
                                         if let Some(message) = data::Message::received(
                                             encoded,
                                             our_nick,
+                                            &server,
+                                            &highlight_nicks,
                                             &self.config,
                                             resolve_user_attributes,
                                             channel_users,
@@ -693,44 +1081,99 @@ impl Halloy {
                                             statusmsg,
                                             casemapping,
                                         ) {
-                                            if let Some((message, channel, user)) =
-                                                message.into_highlight(server.clone())
-                                            {
-                                                let message_text = message.text();
-
-                                                commands.push(
-                                                    dashboard
-                                                        .record_highlight(message)
-                                                        .map(Message::Dashboard),
+                                            let notifications = dashboard
+                                                .notifications(
+                                                    &server,
+                                                    &message.target,
+                                                );
+                                            let is_muted = notifications
+                                                == Some(data::buffer::Notifications::None);
+                                            let trigger = dashboard
+                                                .notification_trigger(
+                                                    &server,
+                                                    &message,
+                                                    self.main_window.focused,
                                                 );
 
-                                                if highlight_notification_enabled {
-                                                    self.notifications.notify(
-                                                        &self.config.notifications,
-                                                        &Notification::Highlight {
-                                                            user,
-                                                            channel,
-                                                            message: message_text,
-                                                        },
-                                                        &server,
+                                            if let Some((highlight, channel, user)) =
+                                                message.into_highlight(server.clone())
+                                            {
+                                                if !is_muted {
+                                                    let message_text =
+                                                        highlight.text();
+
+                                                    commands.push(
+                                                        dashboard
+                                                            .record_highlight(
+                                                                highlight,
+                                                            )
+                                                            .map(Message::Dashboard),
                                                     );
+
+                                                    if highlight_notification_enabled {
+                                                        self.notifications.notify(
+                                                            &self.config,
+                                                            &Notification::Highlight {
+                                                                user,
+                                                                channel,
+                                                                message: message_text,
+                                                            },
+                                                            &server,
+                                                            trigger,
+                                                        );
+                                                    }
                                                 }
+                                            } else if let (
+                                                Some(data::buffer::Notifications::All),
+                                                false,
+                                                true,
+                                                data::message::Target::Channel {
+                                                    channel,
+                                                    source:
+                                                        data::message::Source::User(user)
+                                                        | data::message::Source::Action(
+                                                            Some(user),
+                                                        ),
+                                                },
+                                            ) = (
+                                                notifications,
+                                                message.is_echo,
+                                                highlight_notification_enabled,
+                                                &message.target,
+                                            ) {
+                                                self.notifications.notify(
+                                                    &self.config,
+                                                    &Notification::ChannelMessage {
+                                                        user: user.clone(),
+                                                        channel: channel.clone(),
+                                                        message: message.text(),
+                                                    },
+                                                    &server,
+                                                    trigger,
+                                                );
                                             }
 
                                             commands.push(
                                                 dashboard
-                                                    .record_message(
+                                                    .record_received(
                                                         &server,
                                                         message,
+                                                        is_privmsg,
+                                                        is_joined,
+                                                        &self.config,
                                                     )
                                                     .map(Message::Dashboard),
                                             );
                                         }
                                     }
                                     data::client::Event::WithTarget(encoded, our_nick, target) => {
+                                        let is_privmsg = encoded.is_privmsg();
+
                                         if let Some(message) = data::Message::received(
                                             encoded,
                                             our_nick,
+                                            &server,
+                                            &highlight_nicks,
                                             &self.config,
                                             resolve_user_attributes,
                                             channel_users,
@@ -740,9 +1183,12 @@ impl Halloy {
                                         ) {
                                             commands.push(
                                                 dashboard
-                                                    .record_message(
+                                                    .record_received(
                                                         &server,
                                                         message.with_target(target),
+                                                        is_privmsg,
+                                                        is_joined,
+                                                        &self.config,
                                                     )
                                                     .map(Message::Dashboard),
                                             );
@@ -843,6 +1289,82 @@ impl Halloy {
                                                     .map(Message::Dashboard),
                                             );
                                         }
+                                        data::client::Broadcast::UsersResynced {
+                                            channel,
+                                            removed,
+                                            added,
+                                            sent_time,
+                                        } => {
+                                            commands.push(
+                                                dashboard
+                                                    .broadcast(
+                                                        &server,
+                                                        &self.config,
+                                                        sent_time,
+                                                        Broadcast::UsersResynced {
+                                                            channel,
+                                                            removed,
+                                                            added,
+                                                        },
+                                                    )
+                                                    .map(Message::Dashboard),
+                                            );
+                                        }
+                                        data::client::Broadcast::ServerInfo {
+                                            report,
+                                            sent_time,
+                                        } => {
+                                            commands.push(
+                                                dashboard
+                                                    .broadcast(
+                                                        &server,
+                                                        &self.config,
+                                                        sent_time,
+                                                        Broadcast::ServerInfo {
+                                                            report,
+                                                        },
+                                                    )
+                                                    .map(Message::Dashboard),
+                                            );
+                                        }
+                                        data::client::Broadcast::Monitored {
+                                            users,
+                                            online,
+                                            sent_time,
+                                        } => {
+                                            commands.push(
+                                                dashboard
+                                                    .broadcast(
+                                                        &server,
+                                                        &self.config,
+                                                        sent_time,
+                                                        Broadcast::Monitored {
+                                                            users,
+                                                            online,
+                                                        },
+                                                    )
+                                                    .map(Message::Dashboard),
+                                            );
+                                        }
+                                        data::client::Broadcast::MonitorListTruncated {
+                                            limit,
+                                            left_out,
+                                            sent_time,
+                                        } => {
+                                            commands.push(
+                                                dashboard
+                                                    .broadcast(
+                                                        &server,
+                                                        &self.config,
+                                                        sent_time,
+                                                        Broadcast::MonitorListTruncated {
+                                                            limit,
+                                                            left_out,
+                                                        },
+                                                    )
+                                                    .map(Message::Dashboard),
+                                            );
+                                        }
                                     },
                                     data::client::Event::FileTransferRequest(request) => {
                                         if let Some(command) = dashboard.receive_file_transfer(
@@ -856,17 +1378,26 @@ impl Halloy {
                                             commands.push(command.map(Message::Dashboard));
                                         }
                                     }
+                                    data::client::Event::FileTransferResumeAccepted(from, accept) => {
+                                        dashboard.resume_file_transfer(&server, &from, accept);
+                                    }
                                     data::client::Event::UpdateReadMarker(target, read_marker) => {
-                                        commands.push(
-                                            dashboard
-                                                .update_read_marker(
-                                                    history::Kind::from_target(
-                                                        server.clone(),
-                                                        target,
-                                                    ),
-                                                    read_marker,
-                                                )
-                                                .map(Message::Dashboard),
+                                        dashboard.update_read_marker(
+                                            history::Kind::from_target(
+                                                server.clone(),
+                                                target,
+                                            ),
+                                            read_marker,
+                                        );
+                                    }
+                                    data::client::Event::Reaction(target, id, reaction) => {
+                                        dashboard.add_reaction(
+                                            history::Kind::from_target(
+                                                server.clone(),
+                                                target,
+                                            ),
+                                            &id,
+                                            reaction,
                                         );
                                     }
                                     data::client::Event::JoinedChannel(channel, server_time) => {
@@ -881,7 +1412,30 @@ impl Halloy {
 
                                         commands.push(command);
                                     }
+                                    data::client::Event::ChannelForwarded {
+                                        from,
+                                        to,
+                                        remembered,
+                                    } => {
+                                        let forwards = self
+                                            .clients
+                                            .forwards(&server)
+                                            .filter(|_| remembered)
+                                            .cloned();
+
+                                        commands.push(
+                                            dashboard
+                                                .channel_forwarded(&server, from, to, forwards)
+                                                .map(Message::Dashboard),
+                                        );
+                                    }
                                     data::client::Event::LoggedIn(server_time) => {
+                                        commands.push(
+                                            dashboard
+                                                .merge_bouncer_queries(&server, &self.clients)
+                                                .map(Message::Dashboard),
+                                        );
+
                                         if self.clients.get_server_supports_chathistory(&server) {
                                             if let Some(command) = dashboard
                                                 .load_chathistory_targets_timestamp(
@@ -928,6 +1482,8 @@ impl Halloy {
                                         if let Some(message) = data::Message::received(
                                             encoded,
                                             our_nick,
+                                            &server,
+                                            &highlight_nicks,
                                             &self.config,
                                             resolve_user_attributes,
                                             channel_users,
@@ -941,17 +1497,37 @@ impl Halloy {
                                                 statusmsg,
                                                 casemapping,
                                             ) {
-                                                if dashboard.history().has_unread(
+                                                let is_unseen = dashboard.history().has_unread(
                                                     &history::Kind::Query(server.clone(), query),
-                                                ) || !self.main_window.focused
+                                                ) || !self.main_window.focused;
+                                                let should_notify = match dashboard
+                                                    .notifications(&server, &message.target)
                                                 {
+                                                    None => is_unseen,
+                                                    Some(data::buffer::Notifications::All) => true,
+                                                    Some(data::buffer::Notifications::Highlights) => {
+                                                        is_unseen
+                                                            && message.has_highlight_fragment()
+                                                    }
+                                                    Some(data::buffer::Notifications::None) => false,
+                                                };
+
+                                                if should_notify {
+                                                    let trigger = dashboard
+                                                        .notification_trigger(
+                                                            &server,
+                                                            &message,
+                                                            self.main_window.focused,
+                                                        );
+
                                                     self.notifications.notify(
-                                                        &self.config.notifications,
+                                                        &self.config,
                                                         &Notification::DirectMessage{
                                                             user,
                                                             message: message.text(),
                                                         },
                                                         &server,
+                                                        trigger,
                                                     );
                                                 }
                                             }
@@ -959,16 +1535,43 @@ impl Halloy {
                                     }
                                     data::client::Event::MonitoredOnline(users) => {
                                         self.notifications.notify(
-                                            &self.config.notifications,
+                                            &self.config,
                                             &Notification::MonitoredOnline(users),
                                             &server,
+                                            None,
                                         );
                                     }
                                     data::client::Event::MonitoredOffline(users) => {
                                         self.notifications.notify(
-                                            &self.config.notifications,
+                                            &self.config,
                                             &Notification::MonitoredOffline(users),
                                             &server,
+                                            None,
+                                        );
+                                    }
+                                    data::client::Event::BouncerAttachment(
+                                        service,
+                                        attachment,
+                                        server_time,
+                                    ) => {
+                                        self.notifications.notify(
+                                            &self.config,
+                                            &Notification::BouncerAttachment(attachment),
+                                            &server,
+                                            None,
+                                        );
+
+                                        commands.push(
+                                            dashboard
+                                                .record_message(
+                                                    &server,
+                                                    data::Message::bouncer_attachment(
+                                                        service,
+                                                        attachment,
+                                                        server_time,
+                                                    ),
+                                                )
+                                                .map(Message::Dashboard),
                                         );
                                     }
                                     data::client::Event::OnConnect(
@@ -1058,8 +1661,31 @@ impl Halloy {
                     handle_irc_error(e);
                 }
 
+                let server_info =
+                    self.clients.finish_timed_out_server_info(now);
+
                 if let Screen::Dashboard(dashboard) = &mut self.screen {
-                    dashboard.tick(now).map(Message::Dashboard)
+                    let mut tasks = server_info
+                        .into_iter()
+                        .map(|(server, report)| {
+                            dashboard
+                                .broadcast(
+                                    &server,
+                                    &self.config,
+                                    Utc::now(),
+                                    Broadcast::ServerInfo { report },
+                                )
+                                .map(Message::Dashboard)
+                        })
+                        .collect::<Vec<_>>();
+
+                    tasks.push(
+                        dashboard
+                            .tick(now, &mut self.clients, &self.config)
+                            .map(Message::Dashboard),
+                    );
+
+                    Task::batch(tasks)
                 } else {
                     Task::none()
                 }
@@ -1120,6 +1746,54 @@ impl Halloy {
                                 }
                             }
                         }
+                        modal::Event::SendRawMessage(server, encoded) => {
+                            self.clients.send(
+                                &data::buffer::Upstream::Server(server),
+                                encoded,
+                            );
+                        }
+                        modal::Event::QuitServer(server) => {
+                            self.modal = None;
+
+                            if let Screen::Dashboard(dashboard) =
+                                &mut self.screen
+                            {
+                                return Task::batch([
+                                    command.map(Message::Modal),
+                                    dashboard
+                                        .quit_server(&mut self.clients, server)
+                                        .map(Message::Dashboard),
+                                ]);
+                            }
+                        }
+                        modal::Event::SendPaste(buffer, text) => {
+                            self.modal = None;
+
+                            if let Screen::Dashboard(dashboard) =
+                                &mut self.screen
+                            {
+                                return Task::batch([
+                                    command.map(Message::Modal),
+                                    dashboard
+                                        .send_paste(
+                                            &mut self.clients,
+                                            &buffer,
+                                            text,
+                                            &self.config,
+                                        )
+                                        .map(Message::Dashboard),
+                                ]);
+                            }
+                        }
+                        modal::Event::DiscardInput(buffer) => {
+                            self.modal = None;
+
+                            if let Screen::Dashboard(dashboard) =
+                                &mut self.screen
+                            {
+                                dashboard.discard_input(&buffer);
+                            }
+                        }
                     }
                 }
 
@@ -1145,9 +1819,21 @@ impl Halloy {
                         }
                         window::Event::Focused => {
                             self.main_window.focused = true;
+
+                            if let Screen::Dashboard(dashboard) =
+                                &mut self.screen
+                            {
+                                dashboard.show_focus_markers();
+                            }
                         }
                         window::Event::Unfocused => {
                             self.main_window.focused = false;
+
+                            if let Screen::Dashboard(dashboard) =
+                                &mut self.screen
+                            {
+                                dashboard.mark_focus_lost();
+                            }
                         }
                         window::Event::Opened { position, size } => {
                             self.main_window.opened(position, size);
@@ -1190,6 +1876,39 @@ impl Halloy {
                     Task::none()
                 }
             }
+            Message::GlobalHotkey => window::toggle_visibility(
+                self.main_window.id,
+                self.main_window.focused,
+            ),
+            Message::NotificationClicked(trigger) => {
+                let show = window::show(self.main_window.id);
+
+                if let Screen::Dashboard(dashboard) = &mut self.screen {
+                    show.chain(
+                        dashboard
+                            .go_to_message(
+                                trigger.buffer,
+                                trigger.message,
+                                true,
+                                &self.config,
+                            )
+                            .map(Message::Dashboard),
+                    )
+                } else {
+                    show
+                }
+            }
+            Message::Watchdog(sent_at) => {
+                if let Some(stall) = self.watchdog.pong(sent_at) {
+                    log::warn!(
+                        "update loop stalled for {} ms, last handling {}",
+                        stall.duration.as_millis(),
+                        stall.handling
+                    );
+                }
+
+                Task::none()
+            }
             Message::WindowSettingsSaved(result) => {
                 if let Err(err) = result {
                     log::error!("window settings failed to save: {:?}", err);
@@ -1277,15 +1996,22 @@ impl Halloy {
                         &self.version,
                         &self.config,
                         &self.theme,
+                        self.watchdog.stats(),
                     )
                     .map(Message::Dashboard),
                 Screen::Help(help) => help.view().map(Message::Help),
+                Screen::Recovery(recovery) => {
+                    recovery.view().map(Message::Recovery)
+                }
                 Screen::Welcome(welcome) => {
                     welcome.view().map(Message::Welcome)
                 }
                 Screen::Migration(migration) => {
                     migration.view().map(Message::Migration)
                 }
+                Screen::Profiles(profiles) => {
+                    profiles.view().map(Message::Profiles)
+                }
                 Screen::Exit { .. } => column![].into(),
             };
 
@@ -1347,22 +2073,55 @@ impl Halloy {
     fn subscription(&self) -> Subscription<Message> {
         let tick = iced::time::every(Duration::from_secs(1)).map(Message::Tick);
 
+        let mut position = 0;
+
         let streams = Subscription::batch(
             self.servers
                 .entries()
-                .map(|entry| stream::run(entry, self.config.proxy.clone())),
+                .filter(|entry| !self.clients.is_idle(&entry.server))
+                .map(|entry| {
+                    let startup = entry.config.autoconnect.then(|| {
+                        position += 1;
+
+                        stream::Startup::new(
+                            position - 1,
+                            &self.config.startup,
+                        )
+                    });
+
+                    let retries = self
+                        .retries
+                        .get(&entry.server)
+                        .copied()
+                        .unwrap_or_default();
+
+                    stream::run(
+                        entry,
+                        self.config.proxy.clone(),
+                        startup,
+                        retries,
+                    )
+                }),
         )
         .map(Message::Stream);
 
         let mut subscriptions = vec![
-            url::listen().map(Message::RouteReceived),
             events().map(|(window, event)| Message::Event(window, event)),
             window::events()
                 .map(|(window, event)| Message::Window(window, event)),
+            hotkey::listen().map(|()| Message::GlobalHotkey),
+            notification::clicked().map(Message::NotificationClicked),
+            watchdog::ping().map(Message::Watchdog),
             tick,
             streams,
         ];
 
+        // The profile picker would take over the URL socket of the default
+        // profile, which may already be running.
+        if !matches!(self.screen, Screen::Profiles(_)) {
+            subscriptions.push(url::listen().map(Message::RouteReceived));
+        }
+
         // We only want to listen for appearance changes if user has dynamic themes.
         if self.config.appearance.selected.is_dynamic() {
             subscriptions.push(
@@ -1,15 +1,20 @@
 use std::path::PathBuf;
 use std::time::Instant;
 
-use data::{Server, config};
+use data::buffer::Upstream;
+use data::composer::{self, Draft};
+use data::{Server, config, paste};
 use iced::Task;
 
 use crate::widget::Element;
 use crate::window;
 
+pub mod confirm_send;
 pub mod connect_to_server;
 pub mod image_preview;
 pub mod prompt_before_open_url;
+pub mod quit_server;
+pub mod raw_composer;
 pub mod reload_configuration_error;
 
 #[derive(Debug)]
@@ -30,15 +35,32 @@ pub enum Modal {
         timer: Option<Instant>,
         window: window::Id,
     },
+    RawComposer {
+        servers: Vec<Server>,
+        server: Option<Server>,
+        draft: Draft,
+    },
+    QuitServer(Server),
+    ConfirmSend {
+        buffer: Upstream,
+        text: String,
+        pastebin_command: Option<String>,
+        uploading: bool,
+        error: Option<String>,
+        window: window::Id,
+    },
 }
 
 #[derive(Debug, Clone)]
 pub enum Message {
     Cancel,
     OpenURL(String),
+    QuitServer(Server),
     // Modal specific messages
     ServerConnect(ServerConnect),
     ImagePreview(ImagePreview),
+    RawComposer(RawComposer),
+    ConfirmSend(ConfirmSend),
 }
 
 #[derive(Debug, Clone)]
@@ -53,9 +75,35 @@ pub enum ServerConnect {
     DangerouslyAcceptInvalidCerts(bool),
 }
 
+#[derive(Debug, Clone)]
+pub enum RawComposer {
+    Server(Server),
+    TagKey(usize, String),
+    TagValue(usize, String),
+    AddTag,
+    RemoveTag(usize),
+    Command(String),
+    Params(String),
+    Trailing(String),
+    Send,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConfirmSend {
+    Send,
+    Upload,
+    Uploaded(Result<String, paste::Error>),
+    /// Cancels sending, clearing the input
+    Discard,
+}
+
 pub enum Event {
     CloseModal,
     AcceptNewServer,
+    SendRawMessage(Server, data::message::Encoded),
+    QuitServer(Server),
+    SendPaste(Upstream, String),
+    DiscardInput(Upstream),
 }
 
 impl Modal {
@@ -70,6 +118,9 @@ impl Modal {
                 timer: _,
                 window,
             } => Some(*window),
+            Modal::RawComposer { .. } => None,
+            Modal::QuitServer(_) => None,
+            Modal::ConfirmSend { window, .. } => Some(*window),
         }
     }
 
@@ -95,6 +146,9 @@ impl Modal {
                 let _ = open::that_detached(url);
                 (Task::none(), Some(Event::CloseModal))
             }
+            Message::QuitServer(server) => {
+                (Task::none(), Some(Event::QuitServer(server)))
+            }
             Message::ImagePreview(image_preview) => match image_preview {
                 ImagePreview::SaveImage(source) => (
                     Task::perform(
@@ -136,6 +190,111 @@ impl Modal {
                     (Task::none(), None)
                 }
             },
+            Message::RawComposer(raw_composer) => {
+                let Modal::RawComposer { server, draft, .. } = self else {
+                    return (Task::none(), None);
+                };
+
+                match raw_composer {
+                    RawComposer::Server(selected) => {
+                        *server = Some(selected);
+                    }
+                    RawComposer::TagKey(index, key) => {
+                        if let Some(tag) = draft.tags.get_mut(index) {
+                            tag.key = key;
+                        }
+                    }
+                    RawComposer::TagValue(index, value) => {
+                        if let Some(tag) = draft.tags.get_mut(index) {
+                            tag.value = value;
+                        }
+                    }
+                    RawComposer::AddTag => {
+                        draft.tags.push(composer::Tag::default());
+                    }
+                    RawComposer::RemoveTag(index) => {
+                        if index < draft.tags.len() {
+                            draft.tags.remove(index);
+                        }
+                    }
+                    RawComposer::Command(command) => {
+                        draft.command = command;
+                    }
+                    RawComposer::Params(params) => {
+                        draft.params = params;
+                    }
+                    RawComposer::Trailing(trailing) => {
+                        draft.trailing =
+                            (!trailing.is_empty()).then_some(trailing);
+                    }
+                    RawComposer::Send => {
+                        if let (Some(server), Ok(encoded)) =
+                            (server.clone(), draft.encoded())
+                        {
+                            return (
+                                Task::none(),
+                                Some(Event::SendRawMessage(server, encoded)),
+                            );
+                        }
+                    }
+                }
+
+                (Task::none(), None)
+            }
+            Message::ConfirmSend(confirm_send) => {
+                let Modal::ConfirmSend {
+                    buffer,
+                    text,
+                    pastebin_command,
+                    uploading,
+                    error,
+                    ..
+                } = self
+                else {
+                    return (Task::none(), None);
+                };
+
+                match confirm_send {
+                    ConfirmSend::Send => (
+                        Task::none(),
+                        Some(Event::SendPaste(buffer.clone(), text.clone())),
+                    ),
+                    ConfirmSend::Upload => {
+                        let Some(command) = pastebin_command.clone() else {
+                            return (Task::none(), None);
+                        };
+
+                        *uploading = true;
+                        *error = None;
+
+                        (
+                            Task::perform(
+                                paste::upload(command, text.clone()),
+                                |result| {
+                                    Message::ConfirmSend(ConfirmSend::Uploaded(
+                                        result,
+                                    ))
+                                },
+                            ),
+                            None,
+                        )
+                    }
+                    ConfirmSend::Uploaded(Ok(url)) => (
+                        Task::none(),
+                        Some(Event::SendPaste(buffer.clone(), url)),
+                    ),
+                    ConfirmSend::Uploaded(Err(upload_error)) => {
+                        *uploading = false;
+                        *error = Some(format!("Upload failed: {upload_error}"));
+
+                        (Task::none(), None)
+                    }
+                    ConfirmSend::Discard => (
+                        Task::none(),
+                        Some(Event::DiscardInput(buffer.clone())),
+                    ),
+                }
+            }
         }
     }
 
@@ -156,6 +315,26 @@ impl Modal {
                 timer,
                 window: _,
             } => image_preview::view(source, url, timer),
+            Modal::RawComposer {
+                servers,
+                server,
+                draft,
+            } => raw_composer::view(servers, server.as_ref(), draft),
+            Modal::QuitServer(server) => quit_server::view(server),
+            Modal::ConfirmSend {
+                buffer,
+                text,
+                pastebin_command,
+                uploading,
+                error,
+                ..
+            } => confirm_send::view(
+                buffer,
+                text,
+                pastebin_command.is_some(),
+                *uploading,
+                error.as_deref(),
+            ),
         }
     }
 }
@@ -0,0 +1,81 @@
+use data::buffer::Upstream;
+use data::input;
+use iced::widget::{button, column, container, text};
+use iced::{Length, alignment};
+
+use super::{ConfirmSend, Message};
+use crate::theme;
+use crate::widget::Element;
+
+pub fn view<'a>(
+    buffer: &'a Upstream,
+    paste: &'a str,
+    can_upload: bool,
+    uploading: bool,
+    error: Option<&'a str>,
+) -> Element<'a, Message> {
+    let lines = input::lines(paste).len();
+    let target = buffer.target().map_or_else(
+        || buffer.server().to_string(),
+        |target| target.to_string(),
+    );
+
+    let action = |label: &'static str, message: Option<Message>| {
+        button(
+            container(text(label))
+                .align_x(alignment::Horizontal::Center)
+                .width(Length::Fill),
+        )
+        .padding(5)
+        .width(Length::Fixed(250.0))
+        .style(|theme, status| theme::button::secondary(theme, status, false))
+        .on_press_maybe(message)
+    };
+
+    let upload = can_upload.then(|| {
+        action(
+            if uploading {
+                "Uploading..."
+            } else {
+                "Upload to pastebin"
+            },
+            (!uploading).then_some(Message::ConfirmSend(ConfirmSend::Upload)),
+        )
+    });
+
+    let summary = column![
+        text(format!(
+            "You're about to send {lines} line{} / {} bytes to {target}",
+            if lines == 1 { "" } else { "s" },
+            paste.len(),
+        )),
+        text("Each line is sent as a message of its own")
+            .style(theme::text::secondary),
+    ]
+    .push_maybe(error.map(|error| text(error).style(theme::text::error)))
+    .align_x(iced::Alignment::Center)
+    .spacing(8);
+
+    let actions = column![action(
+        "Send",
+        Some(Message::ConfirmSend(ConfirmSend::Send))
+    ),]
+    .push_maybe(upload)
+    .push(action("Edit", Some(Message::Cancel)))
+    .push(action(
+        "Cancel",
+        Some(Message::ConfirmSend(ConfirmSend::Discard)),
+    ))
+    .spacing(4);
+
+    container(
+        column![summary, actions]
+            .spacing(20)
+            .align_x(iced::Alignment::Center),
+    )
+    .max_width(400)
+    .width(Length::Shrink)
+    .style(theme::container::tooltip)
+    .padding(25)
+    .into()
+}
@@ -0,0 +1,52 @@
+use data::Server;
+use iced::widget::{button, column, container, text};
+use iced::{Length, alignment};
+
+use super::Message;
+use crate::theme;
+use crate::widget::Element;
+
+pub fn view(server: &Server) -> Element<Message> {
+    container(
+        column![
+            column![
+                text(format!("Disconnect from {server}?")),
+                text("Its channels will be left").style(theme::text::secondary),
+            ]
+            .align_x(iced::Alignment::Center)
+            .spacing(8),
+            column![
+                button(
+                    container(text("Disconnect"))
+                        .align_x(alignment::Horizontal::Center)
+                        .width(Length::Fill),
+                )
+                .padding(5)
+                .width(Length::Fixed(250.0))
+                .style(|theme, status| theme::button::secondary(
+                    theme, status, false
+                ))
+                .on_press(Message::QuitServer(server.clone())),
+                button(
+                    container(text("Cancel"))
+                        .align_x(alignment::Horizontal::Center)
+                        .width(Length::Fill),
+                )
+                .padding(5)
+                .width(Length::Fixed(250.0))
+                .style(|theme, status| theme::button::secondary(
+                    theme, status, false
+                ))
+                .on_press(Message::Cancel),
+            ]
+            .spacing(4),
+        ]
+        .spacing(20)
+        .align_x(iced::Alignment::Center),
+    )
+    .max_width(400)
+    .width(Length::Shrink)
+    .style(theme::container::tooltip)
+    .padding(25)
+    .into()
+}
@@ -0,0 +1,161 @@
+use data::Server;
+use data::composer::Draft;
+use iced::widget::{Column, button, column, container, row, text, text_input};
+use iced::{Length, alignment};
+
+use super::{Message, RawComposer};
+use crate::theme;
+use crate::widget::Element;
+
+pub fn view<'a>(
+    servers: &'a [Server],
+    server: Option<&'a Server>,
+    draft: &'a Draft,
+) -> Element<'a, Message> {
+    let message = |message| Message::RawComposer(message);
+
+    let servers = if servers.is_empty() {
+        Element::from(
+            text("Not connected to any servers").style(theme::text::secondary),
+        )
+    } else {
+        row(servers.iter().map(|candidate| {
+            let selected = server == Some(candidate);
+
+            button(text(candidate.to_string()))
+                .padding(5)
+                .style(move |theme, status| {
+                    theme::button::secondary(theme, status, selected)
+                })
+                .on_press(message(RawComposer::Server(candidate.clone())))
+                .into()
+        }))
+        .spacing(4)
+        .wrap()
+        .into()
+    };
+
+    let tags = Column::with_children(draft.tags.iter().enumerate().map(
+        |(index, tag)| {
+            let is_valid = tag.validate().is_ok();
+
+            row![
+                text_input("key", &tag.key)
+                    .on_input(move |key| message(RawComposer::TagKey(
+                        index, key
+                    )))
+                    .style(move |theme, status| {
+                        if is_valid {
+                            theme::text_input::primary(theme, status)
+                        } else {
+                            theme::text_input::error(theme, status)
+                        }
+                    })
+                    .padding(5),
+                text("=").style(theme::text::secondary),
+                text_input("value", &tag.value)
+                    .on_input(move |value| message(RawComposer::TagValue(
+                        index, value
+                    )))
+                    .padding(5),
+                button(text("Remove"))
+                    .padding(5)
+                    .style(|theme, status| theme::button::secondary(
+                        theme, status, false
+                    ))
+                    .on_press(message(RawComposer::RemoveTag(index))),
+            ]
+            .spacing(4)
+            .align_y(alignment::Vertical::Center)
+            .into()
+        },
+    ))
+    .push(
+        button(text("Add tag"))
+            .padding(5)
+            .style(|theme, status| {
+                theme::button::secondary(theme, status, false)
+            })
+            .on_press(message(RawComposer::AddTag)),
+    )
+    .spacing(4);
+
+    let command = text_input("Command", &draft.command)
+        .on_input(move |command| message(RawComposer::Command(command)))
+        .padding(5);
+
+    let params = text_input("Parameters", &draft.params)
+        .on_input(move |params| message(RawComposer::Params(params)))
+        .padding(5);
+
+    let trailing =
+        text_input("Trailing", draft.trailing.as_deref().unwrap_or_default())
+            .on_input(move |trailing| message(RawComposer::Trailing(trailing)))
+            .padding(5);
+
+    let validation = draft.validate();
+    let size = draft.size();
+    let line = draft.line();
+
+    let preview = column![
+        text(format!("{}\\r\\n", line.trim_end_matches("\r\n")))
+            .style(theme::text::tertiary)
+            .wrapping(text::Wrapping::Glyph),
+        text(format!(
+            "{} bytes (tags: {}, message: {})",
+            size.tags + size.message,
+            size.tags,
+            size.message,
+        ))
+        .style(theme::text::secondary),
+    ]
+    .push_maybe(
+        validation
+            .as_ref()
+            .err()
+            .map(|error| text(error.to_string()).style(theme::text::error)),
+    )
+    .spacing(2);
+
+    let can_send = server.is_some() && validation.is_ok();
+
+    container(
+        column![
+            text("Raw message composer"),
+            servers,
+            tags,
+            column![command, params, trailing].spacing(4),
+            preview,
+            row![
+                button(
+                    container(text("Send"))
+                        .align_x(alignment::Horizontal::Center)
+                        .width(Length::Fill),
+                )
+                .padding(5)
+                .width(Length::Fixed(120.0))
+                .style(|theme, status| theme::button::secondary(
+                    theme, status, false
+                ))
+                .on_press_maybe(can_send.then(|| message(RawComposer::Send))),
+                button(
+                    container(text("Close"))
+                        .align_x(alignment::Horizontal::Center)
+                        .width(Length::Fill),
+                )
+                .padding(5)
+                .width(Length::Fixed(120.0))
+                .style(|theme, status| theme::button::secondary(
+                    theme, status, false
+                ))
+                .on_press(Message::Cancel),
+            ]
+            .spacing(4),
+        ]
+        .spacing(12),
+    )
+    .width(Length::Fixed(520.0))
+    .style(theme::container::tooltip)
+    .padding(25)
+    .into()
+}
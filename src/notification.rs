@@ -1,16 +1,25 @@
 use std::collections::HashMap;
 use std::time::Duration;
 
-use chrono::{DateTime, Utc};
-use data::audio::Sound;
-use data::config::{self, notification};
-use data::{Notification, Server};
+use chrono::{DateTime, Local, Utc};
+use data::config::notification;
+use data::{Config, Notification, Server, bouncer, buffer, message};
 
-pub use self::toast::prepare;
+pub use self::toast::{clicked, prepare, show as toast};
 use crate::audio;
 
 mod toast;
 
+/// Message a notification was triggered by, which is scrolled to when the
+/// notification is clicked
+#[derive(Debug, Clone)]
+pub struct Trigger {
+    pub buffer: buffer::Upstream,
+    pub message: message::Hash,
+    /// Whether its buffer is focused
+    pub is_focused: bool,
+}
+
 pub struct Notifications {
     recent_notifications: HashMap<Notification, DateTime<Utc>>,
 }
@@ -22,12 +31,25 @@ impl Notifications {
         }
     }
 
+    /// Notifies as configured for `notification`. No sound is played if the
+    /// buffer of its `trigger` is focused.
     pub fn notify(
         &mut self,
-        config: &config::Notifications<Sound>,
+        config: &Config,
         notification: &Notification,
         server: &Server,
+        trigger: Option<Trigger>,
     ) {
+        let play_sound = config.notifications.sounds
+            && !trigger.as_ref().is_some_and(|trigger| trigger.is_focused);
+        let is_silenced = |target: &str| {
+            config.notification_level(target, Local::now())
+                > notification::Level::Normal
+        };
+        let privacy = |target: &str| config.notification_privacy(target);
+
+        let config = &config.notifications;
+
         match notification {
             Notification::Connected => {
                 self.execute(
@@ -35,14 +57,23 @@ impl Notifications {
                     notification,
                     "Connected",
                     server,
+                    play_sound,
+                    None,
                 );
             }
-            Notification::Disconnected => {
+            Notification::Disconnected(reason) => {
+                let title = match reason {
+                    Some(reason) => format!("Disconnected: {reason}"),
+                    None => String::from("Disconnected"),
+                };
+
                 self.execute(
                     &config.disconnected,
                     notification,
-                    "Disconnected",
+                    &title,
                     server,
+                    play_sound,
+                    None,
                 );
             }
             Notification::Reconnected => {
@@ -51,6 +82,8 @@ impl Notifications {
                     notification,
                     "Reconnected",
                     server,
+                    play_sound,
+                    None,
                 );
             }
             Notification::MonitoredOnline(targets) => {
@@ -60,6 +93,8 @@ impl Notifications {
                         notification,
                         &format!("{} is online", target.nickname()),
                         server,
+                        play_sound,
+                        None,
                     );
                 });
             }
@@ -70,31 +105,76 @@ impl Notifications {
                         notification,
                         &format!("{target} is offline"),
                         server,
+                        play_sound,
+                        None,
                     );
                 });
             }
+            Notification::BouncerAttachment(attachment) => {
+                let title = match attachment {
+                    bouncer::Attachment::Attached => "Another client attached",
+                    bouncer::Attachment::Detached => "Another client detached",
+                };
+
+                self.execute(
+                    &config.bouncer_attachment,
+                    notification,
+                    title,
+                    server,
+                    play_sound,
+                    None,
+                );
+            }
+            Notification::ScheduledMessage { target, dropped } => {
+                let title = if *dropped {
+                    format!("Scheduled message to {target} was dropped")
+                } else {
+                    format!(
+                        "Scheduled message to {target} is waiting to connect"
+                    )
+                };
+
+                self.execute(
+                    &config.scheduled_message,
+                    notification,
+                    &title,
+                    server,
+                    play_sound,
+                    None,
+                );
+            }
             Notification::FileTransferRequest { nick, filename } => {
                 if config
                     .file_transfer_request
                     .should_notify(vec![nick.to_string()])
                 {
-                    let (title, body) = if config
-                        .file_transfer_request
-                        .show_content
-                    {
-                        (
-                            &format!("File transfer from {nick} on {server}"),
-                            filename.as_ref(),
-                        )
-                    } else {
-                        (&format!("File transfer from {nick}"), server.as_ref())
-                    };
+                    let (title, body) = content(
+                        privacy(nick.as_ref()),
+                        || {
+                            if config.file_transfer_request.show_content {
+                                (
+                                    format!(
+                                        "File transfer from {nick} on {server}"
+                                    ),
+                                    filename.clone(),
+                                )
+                            } else {
+                                (
+                                    format!("File transfer from {nick}"),
+                                    server.to_string(),
+                                )
+                            }
+                        },
+                        || format!("New file transfer from {nick}"),
+                    );
 
                     self.execute(
                         &config.file_transfer_request,
                         notification,
-                        title,
+                        &title,
                         body,
+                        play_sound,
+                        None,
                     );
                 }
             }
@@ -102,30 +182,39 @@ impl Notifications {
                 if config
                     .direct_message
                     .should_notify(vec![user.nickname().to_string()])
+                    && !is_silenced(user.nickname().as_ref())
                 {
-                    let (title, body) = if config.direct_message.show_content {
-                        (
-                            &format!(
-                                "{} sent you a direct message on {server}",
-                                user.nickname()
-                            ),
-                            message.as_ref(),
-                        )
-                    } else {
-                        (
-                            &format!(
-                                "{} sent you a direct message",
-                                user.nickname()
-                            ),
-                            server.as_ref(),
-                        )
-                    };
+                    let (title, body) = content(
+                        privacy(user.nickname().as_ref()),
+                        || {
+                            if config.direct_message.show_content {
+                                (
+                                    format!(
+                                        "{} sent you a direct message on {server}",
+                                        user.nickname()
+                                    ),
+                                    message.clone(),
+                                )
+                            } else {
+                                (
+                                    format!(
+                                        "{} sent you a direct message",
+                                        user.nickname()
+                                    ),
+                                    server.to_string(),
+                                )
+                            }
+                        },
+                        || format!("New message from {}", user.nickname()),
+                    );
 
                     self.execute(
                         &config.direct_message,
                         notification,
-                        title,
+                        &title,
                         body,
+                        play_sound,
+                        trigger,
                     );
                 }
             }
@@ -134,29 +223,101 @@ impl Notifications {
                 channel,
                 message,
             } => {
+                // Settings name channels without STATUSMSG prefixes
+                let name =
+                    channel.as_str().trim_start_matches(channel.prefixes());
+
                 if config.highlight.should_notify(vec![
-                    channel.to_string(),
+                    name.to_string(),
                     user.nickname().to_string(),
-                ]) {
-                    let (title, body) = if config.highlight.show_content {
-                        (
-                            &format!(
-                                "{} highlighted you in {channel} on {server}",
+                ]) && !is_silenced(name)
+                {
+                    let (title, body) = content(
+                        privacy(name),
+                        || {
+                            if config.highlight.show_content {
+                                (
+                                    format!(
+                                        "{} highlighted you in {channel} on {server}",
+                                        user.nickname()
+                                    ),
+                                    message.clone(),
+                                )
+                            } else {
+                                (
+                                    format!(
+                                        "{} highlighted you in {channel}",
+                                        user.nickname()
+                                    ),
+                                    server.to_string(),
+                                )
+                            }
+                        },
+                        || {
+                            format!(
+                                "New message from {} in {channel}",
                                 user.nickname()
-                            ),
-                            message.as_ref(),
-                        )
-                    } else {
-                        (
-                            &format!(
-                                "{} highlighted you in {channel}",
+                            )
+                        },
+                    );
+
+                    self.execute(
+                        &config.highlight,
+                        notification,
+                        &title,
+                        body,
+                        play_sound,
+                        trigger,
+                    );
+                }
+            }
+            // Opted into per channel, so only schedules can silence it
+            Notification::ChannelMessage {
+                user,
+                channel,
+                message,
+            } => {
+                let name =
+                    channel.as_str().trim_start_matches(channel.prefixes());
+
+                if !is_silenced(name) {
+                    let (title, body) = content(
+                        privacy(name),
+                        || {
+                            if config.highlight.show_content {
+                                (
+                                    format!(
+                                        "{} sent a message in {channel} on {server}",
+                                        user.nickname()
+                                    ),
+                                    message.clone(),
+                                )
+                            } else {
+                                (
+                                    format!(
+                                        "{} sent a message in {channel}",
+                                        user.nickname()
+                                    ),
+                                    server.to_string(),
+                                )
+                            }
+                        },
+                        || {
+                            format!(
+                                "New message from {} in {channel}",
                                 user.nickname()
-                            ),
-                            server.as_ref(),
-                        )
-                    };
+                            )
+                        },
+                    );
 
-                    self.execute(&config.highlight, notification, title, body);
+                    self.execute(
+                        &config.highlight,
+                        notification,
+                        &title,
+                        body,
+                        play_sound,
+                        trigger,
+                    );
                 }
             }
         }
@@ -168,6 +329,8 @@ impl Notifications {
         notification: &Notification,
         title: &str,
         body: impl ToString,
+        play_sound: bool,
+        trigger: Option<Trigger>,
     ) {
         let last_notification =
             self.recent_notifications.get(notification).copied();
@@ -181,10 +344,10 @@ impl Notifications {
         }
 
         if config.show_toast {
-            toast::show(title, body);
+            toast::show_with_trigger(title, body, trigger);
         }
 
-        if let Some(sound) = &config.sound {
+        if let (true, Some(sound)) = (play_sound, &config.sound) {
             audio::play(sound.clone());
         }
 
@@ -192,3 +355,22 @@ impl Notifications {
             .insert(notification.clone(), Utc::now());
     }
 }
+
+/// Title and body of a notification about a message, revealing no more than
+/// `privacy` allows. Its trigger is kept, so clicking it works the same.
+fn content(
+    privacy: notification::Privacy,
+    full: impl FnOnce() -> (String, String),
+    sender_only: impl FnOnce() -> String,
+) -> (String, String) {
+    match privacy {
+        notification::Privacy::Full => full(),
+        notification::Privacy::SenderOnly => {
+            (String::from("Halloy"), sender_only())
+        }
+        notification::Privacy::CountOnly => (
+            String::from("Halloy"),
+            String::from("New activity in Halloy"),
+        ),
+    }
+}
@@ -1,3 +1,21 @@
+use std::sync::{LazyLock, Mutex};
+
+use futures::channel::mpsc;
+use futures::stream::{self, StreamExt};
+use iced::Subscription;
+
+use super::Trigger;
+
+/// Triggers of the toasts which were clicked
+static CLICKED: LazyLock<(
+    mpsc::UnboundedSender<Trigger>,
+    Mutex<Option<mpsc::UnboundedReceiver<Trigger>>>,
+)> = LazyLock::new(|| {
+    let (sender, receiver) = mpsc::unbounded();
+
+    (sender, Mutex::new(Some(receiver)))
+});
+
 #[cfg(target_os = "macos")]
 pub fn prepare() {
     match notify_rust::set_application(data::environment::APPLICATION_ID) {
@@ -12,6 +30,17 @@ pub fn prepare() {
 pub fn prepare() {}
 
 pub fn show(title: &str, body: impl ToString) {
+    show_with_trigger(title, body, None);
+}
+
+/// Shows a toast, which reports its `trigger` through [`clicked`] when
+/// clicked. Only supported on Linux, elsewhere clicking a toast just brings
+/// Halloy to the front.
+pub fn show_with_trigger(
+    title: &str,
+    body: impl ToString,
+    trigger: Option<Trigger>,
+) {
     let mut notification = notify_rust::Notification::new();
 
     notification.summary(title);
@@ -21,7 +50,27 @@ pub fn show(title: &str, body: impl ToString) {
     {
         notification.appname("Halloy");
         notification.icon(data::environment::APPLICATION_ID);
+
+        if let Some(trigger) = trigger {
+            // Activates the toast itself rather than adding a button
+            notification.action("default", "Open");
+
+            // Waiting for the toast to be clicked or closed blocks
+            std::thread::spawn(move || {
+                if let Ok(handle) = notification.show() {
+                    handle.wait_for_action(|action| {
+                        if action == "default" {
+                            let _ = CLICKED.0.unbounded_send(trigger);
+                        }
+                    });
+                }
+            });
+
+            return;
+        }
     }
+    #[cfg(not(target_os = "linux"))]
+    let _ = trigger;
     #[cfg(target_os = "windows")]
     {
         notification.app_id(data::environment::APPLICATION_ID);
@@ -29,3 +78,16 @@ pub fn show(title: &str, body: impl ToString) {
 
     let _ = notification.show();
 }
+
+/// Emits the trigger of each toast that's clicked
+pub fn clicked() -> Subscription<Trigger> {
+    Subscription::run(|| {
+        let receiver = CLICKED
+            .1
+            .lock()
+            .ok()
+            .and_then(|mut receiver| receiver.take());
+
+        stream::iter(receiver).flatten()
+    })
+}
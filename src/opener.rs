@@ -0,0 +1,37 @@
+use std::convert;
+use std::path::PathBuf;
+
+use data::opener;
+pub use data::opener::Target;
+use iced::{Task, clipboard};
+
+use crate::notification;
+
+/// Opens `target` with the platform's opener. If that fails, its path is
+/// copied to the clipboard and shown in a toast so it can be opened by hand.
+pub fn open<Message>(target: Target) -> Task<Message> {
+    match opener::open(target) {
+        Ok(_) => Task::none(),
+        Err(error) => failed(error),
+    }
+}
+
+/// Opens the file or directory at `path` with the platform's opener, off the
+/// UI thread. Fails the same as [`open`].
+pub fn open_path<Message: Send + 'static>(path: PathBuf) -> Task<Message> {
+    Task::perform(async move { opener::open_path(&path) }, convert::identity)
+        .then(|result| match result {
+            Ok(()) => Task::none(),
+            Err(error) => failed(error),
+        })
+}
+
+fn failed<Message>(error: opener::Error) -> Task<Message> {
+    log::warn!("{error}");
+
+    let path = error.path().to_string_lossy().into_owned();
+
+    notification::toast("Couldn't open, path copied to clipboard", &path);
+
+    clipboard::write(path)
+}
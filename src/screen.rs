@@ -1,9 +1,13 @@
 pub mod dashboard;
 pub mod help;
 pub mod migration;
+pub mod profiles;
+pub mod recovery;
 pub mod welcome;
 
 pub use dashboard::Dashboard;
 pub use help::Help;
 pub use migration::Migration;
+pub use profiles::Profiles;
+pub use recovery::Recovery;
 pub use welcome::Welcome;
@@ -4,16 +4,19 @@ use std::time::{Duration, Instant};
 use std::{convert, slice};
 
 use chrono::{DateTime, Utc};
+use data::config::keys::FocusBufferTarget;
 use data::dashboard::{self, BufferAction};
 use data::environment::{RELEASE_WEBSITE, WIKI_WEBSITE};
 use data::history::ReadMarker;
+use data::history::audit;
 use data::history::manager::Broadcast;
 use data::isupport::{self, ChatHistorySubcommand, MessageReference};
+use data::scheduler::{self, Scheduler};
 use data::target::{self, Target};
 use data::user::Nick;
 use data::{
-    Config, Notification, Server, Version, client, command, config,
-    environment, file_transfer, history, preview,
+    Config, Notification, Server, User, Version, client, command, config, dcc,
+    environment, file_transfer, history, input, message, preview, translation,
 };
 use iced::widget::pane_grid::{self, PaneGrid};
 use iced::widget::{Space, column, container, row};
@@ -21,39 +24,81 @@ use iced::window::get_position;
 use iced::{Length, Task, Vector, clipboard};
 use log::{debug, error};
 
+use self::audit_log::AuditLog;
+use self::buffer_switcher::BufferSwitcher;
 use self::command_bar::CommandBar;
+use self::connection_report::ConnectionReport;
+use self::debug_overlay::DebugOverlay;
+use self::history_search::HistorySearch;
 use self::pane::Pane;
+use self::quick_switcher::QuickSwitcher;
 use self::sidebar::Sidebar;
 use self::theme_editor::ThemeEditor;
+use self::theme_picker::ThemePicker;
 use crate::buffer::{self, Buffer};
 use crate::widget::{
     Column, Element, Row, anchored_overlay, context_menu, selectable_text,
     shortcut,
 };
 use crate::window::Window;
-use crate::{Theme, event, notification, theme, window};
+use crate::{Theme, event, notification, opener, theme, watchdog, window};
 
+mod audit_log;
+mod buffer_switcher;
 mod command_bar;
+mod connection_report;
+mod debug_overlay;
+mod history_search;
 pub mod pane;
+mod quick_switcher;
 pub mod sidebar;
 mod theme_editor;
+mod theme_picker;
 
 const FOCUS_HISTORY_LEN: usize = 8;
+const RECENT_BUFFERS_LEN: usize = 16;
 const SAVE_AFTER: Duration = Duration::from_secs(3);
+/// How long after launch servers and channels which weren't connected or
+/// joined are reported
+const CONNECTION_REPORT_AFTER: Duration = Duration::from_secs(60);
 
 pub struct Dashboard {
     panes: Panes,
     focus: Focus,
     focus_history: VecDeque<pane_grid::Pane>,
+    recent_buffers: VecDeque<data::Buffer>,
+    buffer_switcher: Option<BufferSwitcher>,
     side_menu: Sidebar,
     history: history::Manager,
     last_changed: Option<Instant>,
     command_bar: Option<CommandBar>,
     file_transfers: file_transfer::Manager,
     theme_editor: Option<ThemeEditor>,
+    theme_picker: Option<ThemePicker>,
+    audit_log: Option<AuditLog>,
+    history_search: Option<HistorySearch>,
+    quick_switcher: Option<QuickSwitcher>,
     notifications: notification::Notifications,
     previews: preview::Collection,
+    /// Avatars of users, keyed by their URL
+    avatars: preview::Collection,
+    /// Translations of messages asked for this session
+    translations: translation::Collection,
     buffer_settings: dashboard::BufferSettings,
+    highlights: dashboard::Highlights,
+    sidebar_order: dashboard::SidebarOrder,
+    collapsed_servers: dashboard::CollapsedServers,
+    layouts: dashboard::Layouts,
+    /// Background work, held back while interacting
+    scheduler: Scheduler<Task<Message>>,
+    /// Requests of background work yet to be answered
+    requests: scheduler::Requests<Request>,
+    /// WHO polls queued as background work
+    queued_who_polls: HashSet<(Server, target::Channel)>,
+    debug_overlay: Option<DebugOverlay>,
+    connection_report: Option<ConnectionReport>,
+    connection_report_at: Option<Instant>,
+    connection_errors: HashMap<Server, String>,
 }
 
 #[derive(Debug)]
@@ -63,6 +108,9 @@ pub enum Message {
     SelectedText(Vec<(f32, String)>),
     History(history::manager::Message),
     DashboardSaved(Result<(), data::dashboard::Error>),
+    IgnoresSaved(Result<(), data::ignore::Error>),
+    ForwardsSaved(Result<(), client::forward::Error>),
+    ProfileDuplicated(Result<String, data::profile::Error>),
     Task(command_bar::Message),
     Shortcut(shortcut::Command),
     FileTransfer(file_transfer::task::Update),
@@ -72,7 +120,88 @@ pub enum Message {
     ConfigReloaded(Result<Config, config::Error>),
     Client(client::Message),
     LoadPreview((url::Url, Result<data::Preview, data::preview::LoadError>)),
+    LoadAvatar((url::Url, Result<preview::Image, data::preview::LoadError>)),
+    RequestOlderChatHistory(data::Buffer),
+    SendWhoPoll(Server, target::Channel),
+    TaskFinished(scheduler::Category),
+    RunScheduled,
+    Translated(message::Hash, Result<String, translation::Error>),
     NewWindow(window::Id, Pane),
+    TrimHistory,
+    ConnectionReport(connection_report::Message),
+    ThemePicker(theme_picker::Message),
+    ThemePreviews(
+        Vec<
+            Result<
+                data::appearance::theme::Preview,
+                (String, data::appearance::theme::Error),
+            >,
+        >,
+    ),
+    ThemeLoaded(Result<data::Theme, data::appearance::theme::Error>),
+    ThemeSaved(Result<(), config::Error>),
+    AuditLog(audit_log::Message),
+    HistorySearch(history_search::Message),
+    QuickSwitcher(quick_switcher::Message),
+    AuditLogLoaded(
+        Server,
+        target::Channel,
+        Result<Vec<audit::Entry>, history::Error>,
+    ),
+    TopicHistoryLoaded(
+        Server,
+        target::Channel,
+        Result<Vec<audit::Entry>, history::Error>,
+    ),
+    HighlightsLoaded(Result<history::Loaded, history::Error>),
+}
+
+impl Message {
+    /// Variant name, recorded by the watchdog
+    pub fn name(&self) -> &'static str {
+        match self {
+            Message::Pane(_, _) => "Dashboard::Pane",
+            Message::Sidebar(_) => "Dashboard::Sidebar",
+            Message::SelectedText(_) => "Dashboard::SelectedText",
+            Message::History(_) => "Dashboard::History",
+            Message::DashboardSaved(_) => "Dashboard::DashboardSaved",
+            Message::IgnoresSaved(_) => "Dashboard::IgnoresSaved",
+            Message::ForwardsSaved(_) => "Dashboard::ForwardsSaved",
+            Message::ProfileDuplicated(_) => "Dashboard::ProfileDuplicated",
+            Message::Task(_) => "Dashboard::Task",
+            Message::Shortcut(_) => "Dashboard::Shortcut",
+            Message::FileTransfer(_) => "Dashboard::FileTransfer",
+            Message::SendFileSelected(_, _, _) => "Dashboard::SendFileSelected",
+            Message::CloseContextMenu(_, _) => "Dashboard::CloseContextMenu",
+            Message::ThemeEditor(_) => "Dashboard::ThemeEditor",
+            Message::ConfigReloaded(_) => "Dashboard::ConfigReloaded",
+            Message::Client(_) => "Dashboard::Client",
+            Message::LoadPreview(_) => "Dashboard::LoadPreview",
+            Message::LoadAvatar(_) => "Dashboard::LoadAvatar",
+            Message::RequestOlderChatHistory(_) => {
+                "Dashboard::RequestOlderChatHistory"
+            }
+            Message::SendWhoPoll(_, _) => "Dashboard::SendWhoPoll",
+            Message::TaskFinished(_) => "Dashboard::TaskFinished",
+            Message::RunScheduled => "Dashboard::RunScheduled",
+            Message::Translated(_, _) => "Dashboard::Translated",
+            Message::NewWindow(_, _) => "Dashboard::NewWindow",
+            Message::TrimHistory => "Dashboard::TrimHistory",
+            Message::ConnectionReport(_) => "Dashboard::ConnectionReport",
+            Message::ThemePicker(_) => "Dashboard::ThemePicker",
+            Message::ThemePreviews(_) => "Dashboard::ThemePreviews",
+            Message::ThemeLoaded(_) => "Dashboard::ThemeLoaded",
+            Message::ThemeSaved(_) => "Dashboard::ThemeSaved",
+            Message::AuditLog(_) => "Dashboard::AuditLog",
+            Message::HistorySearch(_) => "Dashboard::HistorySearch",
+            Message::QuickSwitcher(_) => "Dashboard::QuickSwitcher",
+            Message::AuditLogLoaded(_, _, _) => "Dashboard::AuditLogLoaded",
+            Message::TopicHistoryLoaded(_, _, _) => {
+                "Dashboard::TopicHistoryLoaded"
+            }
+            Message::HighlightsLoaded(_) => "Dashboard::HighlightsLoaded",
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -80,10 +209,36 @@ pub enum Event {
     ConfigReloaded(Result<Config, config::Error>),
     ReloadThemes,
     QuitServer(Server),
+    ConnectServer(Server),
+    RetryServer(Server),
     IrcError(anyhow::Error),
     Exit,
     OpenUrl(String, bool),
     ImagePreview(PathBuf, url::Url),
+    OpenRawComposer,
+    ConfirmSend(buffer::Upstream, String),
+}
+
+/// Request sent to a server by background work, which runs until it's
+/// answered
+#[derive(Debug)]
+enum Request {
+    ChatHistory(Server, Target),
+    Who(Server, target::Channel),
+}
+
+impl Request {
+    /// Whether it's yet to be answered, or time out
+    fn pending(&self, clients: &client::Map, now: Instant) -> bool {
+        match self {
+            Request::ChatHistory(server, target) => {
+                clients.get_chathistory_request(server, target).is_some()
+            }
+            Request::Who(server, channel) => {
+                clients.who_poll_pending(server, channel, now)
+            }
+        }
+    }
 }
 
 impl Dashboard {
@@ -105,17 +260,38 @@ impl Dashboard {
                 pane,
             },
             focus_history: VecDeque::new(),
+            recent_buffers: VecDeque::new(),
+            buffer_switcher: None,
             side_menu: Sidebar::new(),
-            history: history::Manager::default(),
+            history: history::Manager::new(),
             last_changed: None,
             command_bar: None,
             file_transfers: file_transfer::Manager::new(
                 config.file_transfer.clone(),
             ),
             theme_editor: None,
+            theme_picker: None,
+            audit_log: None,
+            history_search: None,
+            quick_switcher: None,
             notifications: notification::Notifications::new(),
             previews: preview::Collection::default(),
+            avatars: preview::Collection::default(),
+            translations: translation::Collection::default(),
             buffer_settings: dashboard::BufferSettings::default(),
+            highlights: dashboard::Highlights::default(),
+            sidebar_order: dashboard::SidebarOrder::default(),
+            collapsed_servers: dashboard::CollapsedServers::default(),
+            layouts: dashboard::Layouts::default(),
+            scheduler: Scheduler::new(config.background.concurrency),
+            requests: scheduler::Requests::default(),
+            queued_who_polls: HashSet::new(),
+            debug_overlay: None,
+            connection_report: None,
+            connection_report_at: Some(
+                Instant::now() + CONNECTION_REPORT_AFTER,
+            ),
+            connection_errors: HashMap::new(),
         };
 
         let command = dashboard.track(config);
@@ -131,7 +307,14 @@ impl Dashboard {
         let (mut dashboard, task) =
             Dashboard::from_data(dashboard, config, main_window);
 
-        let tasks = Task::batch(vec![task, dashboard.track(config)]);
+        let tasks = Task::batch(vec![
+            task,
+            dashboard.track(config),
+            Task::perform(
+                history::load(history::Kind::Highlights),
+                Message::HighlightsLoaded,
+            ),
+        ]);
 
         (dashboard, tasks)
     }
@@ -145,6 +328,13 @@ impl Dashboard {
         config: &Config,
         main_window: &Window,
     ) -> (Task<Message>, Option<Event>) {
+        if matches!(
+            message,
+            Message::Pane(..) | Message::Sidebar(_) | Message::Shortcut(_)
+        ) {
+            self.scheduler.interactive(Instant::now());
+        }
+
         match message {
             Message::Pane(window, message) => {
                 match message {
@@ -173,8 +363,23 @@ impl Dashboard {
                             None,
                         );
                     }
-                    pane::Message::SplitPane(axis) => {
-                        return (self.split_pane(axis), None);
+                    pane::Message::SplitPane(pane, axis) => {
+                        return (self.split_pane(window, pane, axis), None);
+                    }
+                    pane::Message::ShowBanList(id) => {
+                        if let Some(pane) = self.panes.get_mut(window, id) {
+                            return (
+                                pane.buffer.show_ban_list(clients).map(
+                                    move |message| {
+                                        Message::Pane(
+                                            window,
+                                            pane::Message::Buffer(id, message),
+                                        )
+                                    },
+                                ),
+                                None,
+                            );
+                        }
                     }
                     pane::Message::Buffer(id, message) => {
                         if let Some(pane) = self.panes.get_mut(window, id) {
@@ -222,6 +427,99 @@ impl Dashboard {
                                                 clients.send(&input.buffer, encoded);
                                             }
                                         }
+                                        buffer::user_context::Event::Kick(
+                                            server,
+                                            channel,
+                                            nick,
+                                            reason,
+                                        ) => {
+                                            let buffer = buffer::Upstream::Channel(
+                                                server,
+                                                channel.clone(),
+                                            );
+
+                                            let command = command::Irc::Kick(
+                                                channel.to_string(),
+                                                nick.to_string(),
+                                                reason,
+                                            );
+                                            let input = data::Input::command(buffer, command);
+
+                                            if let Some(encoded) = input.encoded() {
+                                                clients.send(&input.buffer, encoded);
+                                            }
+                                        }
+                                        buffer::user_context::Event::Ban(
+                                            server,
+                                            channel,
+                                            mask,
+                                        ) => {
+                                            let buffer = buffer::Upstream::Channel(
+                                                server,
+                                                channel.clone(),
+                                            );
+
+                                            let command = command::Irc::Mode(
+                                                channel.to_string(),
+                                                Some("+b".to_owned()),
+                                                Some(vec![mask]),
+                                            );
+                                            let input = data::Input::command(buffer, command);
+
+                                            if let Some(encoded) = input.encoded() {
+                                                clients.send(&input.buffer, encoded);
+                                            }
+                                        }
+                                        buffer::user_context::Event::KickBan(
+                                            server,
+                                            channel,
+                                            nick,
+                                            mask,
+                                            reason,
+                                        ) => {
+                                            let buffer = buffer::Upstream::Channel(
+                                                server,
+                                                channel.clone(),
+                                            );
+
+                                            // Ban before kicking so the user can't rejoin
+                                            let commands = [
+                                                command::Irc::Mode(
+                                                    channel.to_string(),
+                                                    Some("+b".to_owned()),
+                                                    Some(vec![mask]),
+                                                ),
+                                                command::Irc::Kick(
+                                                    channel.to_string(),
+                                                    nick.to_string(),
+                                                    reason,
+                                                ),
+                                            ];
+
+                                            for command in commands {
+                                                let input =
+                                                    data::Input::command(buffer.clone(), command);
+
+                                                if let Some(encoded) = input.encoded() {
+                                                    clients.send(&input.buffer, encoded);
+                                                }
+                                            }
+                                        }
+                                        buffer::user_context::Event::Ignore(
+                                            server,
+                                            user,
+                                            ignore,
+                                        ) => {
+                                            let save = self.history.ignore(&server, &user, ignore);
+
+                                            return (
+                                                Task::batch(vec![
+                                                    task,
+                                                    Task::perform(save, Message::IgnoresSaved),
+                                                ]),
+                                                None,
+                                            );
+                                        }
                                         buffer::user_context::Event::SendWhois(server, nick) => {
                                             let buffer =
                                                 pane.buffer.upstream().cloned().unwrap_or_else(
@@ -310,6 +608,38 @@ impl Dashboard {
                                                 None,
                                             );
                                         }
+                                        buffer::user_context::Event::Notice(server, nick) => {
+                                            let Some((_, pane, history)) =
+                                                self.get_focused_with_history_mut()
+                                            else {
+                                                return (task, None);
+                                            };
+
+                                            // Composed where the menu was opened,
+                                            // if it's on the same server
+                                            if pane
+                                                .buffer
+                                                .upstream()
+                                                .is_none_or(|buffer| buffer.server() != &server)
+                                            {
+                                                return (task, None);
+                                            }
+
+                                            return (
+                                                Task::batch(vec![
+                                                    task,
+                                                    pane.buffer.notice(nick, history).map(
+                                                        move |message| {
+                                                            Message::Pane(
+                                                                window,
+                                                                pane::Message::Buffer(id, message),
+                                                            )
+                                                        },
+                                                    ),
+                                                ]),
+                                                None,
+                                            );
+                                        }
                                         buffer::user_context::Event::InsertNickname(nick) => {
                                             let Some((_, pane, history)) =
                                                 self.get_focused_with_history_mut()
@@ -421,59 +751,28 @@ impl Dashboard {
                                     channel,
                                     message,
                                 ) => {
-                                    let buffer = data::Buffer::Upstream(
-                                        buffer::Upstream::Channel(
-                                            server, channel,
+                                    return (
+                                        self.go_to_message(
+                                            buffer::Upstream::Channel(
+                                                server, channel,
+                                            ),
+                                            message,
+                                            false,
+                                            config,
                                         ),
+                                        None,
                                     );
-
-                                    let mut tasks = vec![];
-
-                                    if self
-                                        .panes
-                                        .get_mut_by_buffer(&buffer)
-                                        .is_none()
-                                    {
-                                        tasks.push(
-                                            self.open_buffer(
-                                                buffer.clone(),
-                                                config
-                                                    .actions
-                                                    .buffer
-                                                    .click_highlight,
-                                                config,
-                                            ),
-                                        );
-                                    }
-
-                                    if let Some((window, pane, state)) =
-                                        self.panes.get_mut_by_buffer(&buffer)
-                                    {
-                                        tasks.push(
-                                            state
-                                                .buffer
-                                                .scroll_to_message(
-                                                    message,
-                                                    &self.history,
-                                                    config,
-                                                )
-                                                .map(move |message| {
-                                                    Message::Pane(
-                                                        window,
-                                                        pane::Message::Buffer(
-                                                            pane, message,
-                                                        ),
-                                                    )
-                                                }),
-                                        );
-                                    }
-
-                                    return (Task::batch(tasks), None);
                                 }
                                 buffer::Event::RequestOlderChatHistory => {
                                     if let Some(buffer) = pane.buffer.data() {
-                                        self.request_older_chathistory(
-                                            clients, &buffer,
+                                        return (
+                                            Task::batch([
+                                                task,
+                                                self.schedule_older_chathistory(
+                                                    buffer,
+                                                ),
+                                            ]),
+                                            None,
                                         );
                                     }
                                 }
@@ -501,25 +800,25 @@ impl Dashboard {
                                         self.previews.remove(url);
                                     }
 
-                                    return (
-                                        Task::batch(missing.into_iter().map(
-                                            |url| {
-                                                Task::perform(
-                                                    data::preview::load(
+                                    for url in missing {
+                                        self.scheduler.submit(
+                                            scheduler::Category::Preview,
+                                            Task::perform(
+                                                data::preview::load(
+                                                    url.clone(),
+                                                    config.preview.clone(),
+                                                ),
+                                                move |result| {
+                                                    Message::LoadPreview((
                                                         url.clone(),
-                                                        config.preview.clone(),
-                                                    ),
-                                                    move |result| {
-                                                        Message::LoadPreview((
-                                                            url.clone(),
-                                                            result,
-                                                        ))
-                                                    },
-                                                )
-                                            },
-                                        )),
-                                        None,
-                                    );
+                                                        result,
+                                                    ))
+                                                },
+                                            ),
+                                        );
+                                    }
+
+                                    return (self.run_scheduled(), None);
                                 }
                                 buffer::Event::HidePreview(kind, hash, url) => {
                                     self.history.hide_preview(kind, hash, url);
@@ -545,6 +844,175 @@ impl Dashboard {
                                         Some(Event::ImagePreview(path, url)),
                                     );
                                 }
+                                buffer::Event::ConfirmSend(buffer, text) => {
+                                    return (
+                                        task,
+                                        Some(Event::ConfirmSend(buffer, text)),
+                                    );
+                                }
+                                buffer::Event::Theme(Some(name)) => {
+                                    return (
+                                        Task::batch(vec![
+                                            task,
+                                            load_theme(name, config),
+                                        ]),
+                                        None,
+                                    );
+                                }
+                                buffer::Event::Theme(None) => {
+                                    return (
+                                        Task::batch(vec![
+                                            task,
+                                            Task::perform(
+                                                Config::preview_themes(),
+                                                Message::ThemePreviews,
+                                            ),
+                                        ]),
+                                        None,
+                                    );
+                                }
+                                buffer::Event::Layout(layout) => {
+                                    return (
+                                        Task::batch(vec![
+                                            task,
+                                            self.layout(layout, config),
+                                        ]),
+                                        None,
+                                    );
+                                }
+                                buffer::Event::TopicHistory(channel, count) => {
+                                    let Some(server) = pane
+                                        .buffer
+                                        .upstream()
+                                        .map(buffer::Upstream::server)
+                                        .cloned()
+                                    else {
+                                        return (task, None);
+                                    };
+
+                                    let audit_log = self.history.audit_log(
+                                        server.clone(),
+                                        channel.clone(),
+                                    );
+
+                                    return (
+                                        Task::batch(vec![
+                                            task,
+                                            Task::perform(
+                                                async move {
+                                                    audit_log.await.map(
+                                                        |entries| {
+                                                            last_topics(
+                                                                entries, count,
+                                                            )
+                                                        },
+                                                    )
+                                                },
+                                                move |result| {
+                                                    Message::TopicHistoryLoaded(
+                                                        server.clone(),
+                                                        channel.clone(),
+                                                        result,
+                                                    )
+                                                },
+                                            ),
+                                        ]),
+                                        None,
+                                    );
+                                }
+                                buffer::Event::SetActionStyle(user, style) => {
+                                    if let Some(buffer) =
+                                        pane.buffer.upstream().cloned()
+                                    {
+                                        let casemapping = clients
+                                            .get_casemapping(buffer.server());
+
+                                        self.buffer_settings
+                                            .entry(
+                                                &data::Buffer::Upstream(buffer),
+                                                Some(
+                                                    config
+                                                        .buffer
+                                                        .clone()
+                                                        .into(),
+                                                ),
+                                            )
+                                            .channel
+                                            .actions
+                                            .set_style(
+                                                &user,
+                                                style,
+                                                casemapping,
+                                            );
+                                        self.last_changed =
+                                            Some(Instant::now());
+                                    }
+                                }
+                                buffer::Event::FileTransfer(event) => {
+                                    let transfer = event.file_transfer();
+                                    let server = transfer.server.clone();
+
+                                    if let Ok(query) = target::Query::parse(
+                                        transfer.remote_user.as_ref(),
+                                        clients.get_chantypes(&server),
+                                        clients.get_statusmsg(&server),
+                                        clients.get_casemapping(&server),
+                                    ) {
+                                        return (
+                                            Task::batch(vec![
+                                                task,
+                                                self.handle_file_transfer_event(
+                                                    &server, &query, event,
+                                                ),
+                                            ]),
+                                            None,
+                                        );
+                                    }
+                                }
+                                buffer::Event::GoToServer(server) => {
+                                    return (
+                                        Task::batch(vec![
+                                            task,
+                                            self.open_buffer(
+                                                data::Buffer::Upstream(
+                                                    buffer::Upstream::Server(
+                                                        server,
+                                                    ),
+                                                ),
+                                                config
+                                                    .actions
+                                                    .buffer
+                                                    .click_highlight,
+                                                config,
+                                            ),
+                                        ]),
+                                        None,
+                                    );
+                                }
+                                buffer::Event::Translate(hash, text) => {
+                                    self.translations.insert(
+                                        hash,
+                                        translation::State::Translating,
+                                    );
+
+                                    return (
+                                        Task::batch(vec![
+                                            task,
+                                            Task::perform(
+                                                translation::translate(
+                                                    text,
+                                                    config.translation.clone(),
+                                                ),
+                                                move |result| {
+                                                    Message::Translated(
+                                                        hash, result,
+                                                    )
+                                                },
+                                            ),
+                                        ]),
+                                        None,
+                                    );
+                                }
                             }
 
                             return (task, None);
@@ -579,8 +1047,9 @@ impl Dashboard {
                         }
                     }
                     pane::Message::MaximizePane => self.maximize_pane(),
+                    pane::Message::TogglePin => self.toggle_pin(),
                     pane::Message::Popout => {
-                        return (self.popout_pane(config), None);
+                        return (self.popout_pane(), None);
                     }
                     pane::Message::Merge => {
                         return (self.merge_pane(config), None);
@@ -693,6 +1162,12 @@ impl Dashboard {
                         let _ = open::that_detached(WIKI_WEBSITE);
                         (Task::none(), None)
                     }
+                    sidebar::Event::Connect(server) => {
+                        (Task::none(), Some(Event::ConnectServer(server)))
+                    }
+                    sidebar::Event::Retry(server) => {
+                        (Task::none(), Some(Event::RetryServer(server)))
+                    }
                     sidebar::Event::MarkServerAsRead(server) => {
                         self.mark_server_as_read(server, clients);
 
@@ -707,13 +1182,90 @@ impl Dashboard {
 
                         (Task::none(), None)
                     }
+                    sidebar::Event::ForgetForwards(server, channel) => {
+                        let task = clients
+                            .forget_forwards(&server, &channel)
+                            .map_or_else(Task::none, |forwards| {
+                                Task::perform(
+                                    forwards.save(server),
+                                    Message::ForwardsSaved,
+                                )
+                            });
+
+                        (task, None)
+                    }
+                    sidebar::Event::ResyncUsers(server, channel) => {
+                        clients.resync_users(&server, &channel);
+                        (Task::none(), None)
+                    }
+                    sidebar::Event::AuditLog(server, channel) => {
+                        let audit_log = self
+                            .history
+                            .audit_log(server.clone(), channel.clone());
+
+                        (
+                            Task::perform(audit_log, move |result| {
+                                Message::AuditLogLoaded(
+                                    server.clone(),
+                                    channel.clone(),
+                                    result,
+                                )
+                            }),
+                            None,
+                        )
+                    }
+                    sidebar::Event::SetNotifications(buffer, notifications) => {
+                        self.buffer_settings
+                            .entry(
+                                &data::Buffer::Upstream(buffer),
+                                Some(config.buffer.clone().into()),
+                            )
+                            .notifications = notifications;
+                        self.last_changed = Some(Instant::now());
+
+                        (Task::none(), None)
+                    }
+                    sidebar::Event::RemoveActionStyle(buffer, mask) => {
+                        self.buffer_settings
+                            .entry(
+                                &data::Buffer::Upstream(buffer),
+                                Some(config.buffer.clone().into()),
+                            )
+                            .channel
+                            .actions
+                            .remove(&mask);
+                        self.last_changed = Some(Instant::now());
+
+                        (Task::none(), None)
+                    }
                     sidebar::Event::OpenConfigFile => {
-                        let _ = open::that_detached(Config::path());
+                        (opener::open(opener::Target::ConfigFile), None)
+                    }
+                    sidebar::Event::Place(buffer, target, placement) => {
+                        if self.sidebar_order.place(
+                            &all_buffers(
+                                clients,
+                                &self.history,
+                                &self.sidebar_order,
+                            ),
+                            &buffer,
+                            &target,
+                            placement,
+                        ) {
+                            self.last_changed = Some(Instant::now());
+                        }
+
                         (Task::none(), None)
                     }
-                };
+                    sidebar::Event::ToggleCollapsed(server) => {
+                        self.collapsed_servers.toggle(&server);
+                        self.last_changed = Some(Instant::now());
 
-                return (
+                        (Task::none(), None)
+                    }
+                };
+
+                return (
                     Task::batch(vec![
                         event_task,
                         command.map(Message::Sidebar),
@@ -804,6 +1356,16 @@ impl Dashboard {
 
                             return (Task::none(), Some(Event::Exit));
                         }
+                        history::manager::Event::Merged(server, messages) => {
+                            return (
+                                Task::batch(messages.into_iter().map(
+                                    |message| {
+                                        self.record_message(&server, message)
+                                    },
+                                )),
+                                None,
+                            );
+                        }
                         history::manager::Event::SentMessageUpdated(
                             kind,
                             read_marker,
@@ -829,6 +1391,30 @@ impl Dashboard {
             Message::DashboardSaved(Err(error)) => {
                 log::warn!("error saving dashboard: {error}");
             }
+            Message::IgnoresSaved(Ok(())) => {
+                log::info!("ignore list saved");
+            }
+            Message::IgnoresSaved(Err(error)) => {
+                log::warn!("error saving ignore list: {error}");
+            }
+            Message::ForwardsSaved(Ok(())) => {
+                log::info!("channel forwards saved");
+            }
+            Message::ForwardsSaved(Err(error)) => {
+                log::warn!("error saving channel forwards: {error}");
+            }
+            Message::ProfileDuplicated(Ok(profile)) => {
+                log::info!("profile duplicated as {profile}");
+
+                if let Err(error) = data::profile::launch(Some(&profile)) {
+                    log::warn!("failed to launch profile {profile}: {error}");
+                }
+            }
+            Message::ProfileDuplicated(Err(error)) => {
+                log::warn!("error duplicating profile: {error}");
+
+                notification::toast("Failed to duplicate profile", error);
+            }
             Message::Task(message) => {
                 let Some(command_bar) = &mut self.command_bar else {
                     return (Task::none(), None);
@@ -862,14 +1448,10 @@ impl Dashboard {
                                     (self.close_pane(window, pane), None)
                                 }
                                 command_bar::Buffer::Replace(buffer) => (
-                                    self.open_buffer(
-                                        data::Buffer::Upstream(buffer),
-                                        BufferAction::ReplacePane,
-                                        config,
-                                    ),
+                                    self.replace_focused_pane(data::Buffer::Upstream(buffer)),
                                     None,
                                 ),
-                                command_bar::Buffer::Popout => (self.popout_pane(config), None),
+                                command_bar::Buffer::Popout => (self.popout_pane(), None),
                                 command_bar::Buffer::Merge => (self.merge_pane(config), None),
                                 command_bar::Buffer::ToggleInternal(buffer) => {
                                     (self.toggle_internal_buffer(config, buffer), None)
@@ -877,16 +1459,19 @@ impl Dashboard {
                             },
                             command_bar::Command::Configuration(command) => match command {
                                 command_bar::Configuration::OpenConfigDirectory => {
-                                    let _ = open::that_detached(Config::config_dir());
-                                    (Task::none(), None)
+                                    (opener::open(opener::Target::ConfigDirectory), None)
                                 }
                                 command_bar::Configuration::OpenCacheDirectory => {
-                                    let _ = open::that_detached(environment::cache_dir());
-                                    (Task::none(), None)
+                                    (opener::open(opener::Target::CacheDirectory), None)
                                 }
                                 command_bar::Configuration::OpenDataDirectory => {
-                                    let _ = open::that_detached(environment::data_dir());
-                                    (Task::none(), None)
+                                    (opener::open(opener::Target::DataDirectory), None)
+                                }
+                                command_bar::Configuration::OpenThemesDirectory => {
+                                    (opener::open(opener::Target::ThemesDirectory), None)
+                                }
+                                command_bar::Configuration::OpenLogFile => {
+                                    (opener::open(opener::Target::LogFile), None)
                                 }
                                 command_bar::Configuration::OpenWebsite => {
                                     let _ = open::that_detached(environment::WIKI_WEBSITE);
@@ -896,9 +1481,26 @@ impl Dashboard {
                                     (Task::perform(Config::load(), Message::ConfigReloaded), None)
                                 }
                                 command_bar::Configuration::OpenConfigFile => {
-                                    let _ = open::that_detached(Config::path());
+                                    (opener::open(opener::Target::ConfigFile), None)
+                                }
+                                command_bar::Configuration::DuplicateProfile => (
+                                    Task::perform(
+                                        data::profile::duplicate(
+                                            environment::profile().map(String::from),
+                                        ),
+                                        Message::ProfileDuplicated,
+                                    ),
+                                    None,
+                                ),
+                            },
+                            command_bar::Command::Developer(command) => match command {
+                                command_bar::Developer::OpenRawComposer => {
+                                    (Task::none(), Some(Event::OpenRawComposer))
+                                }
+                                command_bar::Developer::ToggleDebugOverlay => {
+                                    self.toggle_debug_overlay();
                                     (Task::none(), None)
-                                },
+                                }
                             },
                             command_bar::Command::UI(command) => match command {
                                 command_bar::Ui::ToggleSidebarVisibility => {
@@ -934,6 +1536,13 @@ impl Dashboard {
                             },
                             command_bar::Command::Application(application) => match application {
                                 command_bar::Application::Quit => (self.exit(config), None),
+                                command_bar::Application::CheckConnections => {
+                                    self.connection_report = Some(ConnectionReport::new(
+                                        clients,
+                                        &self.connection_errors,
+                                    ));
+                                    (Task::none(), None)
+                                }
                             },
                         };
 
@@ -1009,8 +1618,46 @@ impl Dashboard {
                     RestoreBuffer => {
                         self.panes.main.restore();
                     }
+                    SplitHorizontal => {
+                        let Focus { window, pane } = self.focus;
+                        return (
+                            self.split_pane(
+                                window,
+                                pane,
+                                pane_grid::Axis::Horizontal,
+                            ),
+                            None,
+                        );
+                    }
+                    SplitVertical => {
+                        let Focus { window, pane } = self.focus;
+                        return (
+                            self.split_pane(
+                                window,
+                                pane,
+                                pane_grid::Axis::Vertical,
+                            ),
+                            None,
+                        );
+                    }
+                    MoveBufferUp => {
+                        self.move_focused_buffer(
+                            clients,
+                            dashboard::Direction::Up,
+                        );
+                    }
+                    MoveBufferDown => {
+                        self.move_focused_buffer(
+                            clients,
+                            dashboard::Direction::Down,
+                        );
+                    }
                     CycleNextBuffer => {
-                        let all_buffers = all_buffers(clients, &self.history);
+                        let all_buffers = all_buffers(
+                            clients,
+                            &self.history,
+                            &self.sidebar_order,
+                        );
                         let open_buffers = open_buffers(self);
 
                         if let Some((window, pane, state)) =
@@ -1030,7 +1677,11 @@ impl Dashboard {
                         }
                     }
                     CyclePreviousBuffer => {
-                        let all_buffers = all_buffers(clients, &self.history);
+                        let all_buffers = all_buffers(
+                            clients,
+                            &self.history,
+                            &self.sidebar_order,
+                        );
                         let open_buffers = open_buffers(self);
 
                         if let Some((window, pane, state)) =
@@ -1049,6 +1700,54 @@ impl Dashboard {
                             }
                         }
                     }
+                    CycleRecentBuffer => {
+                        if let Some(switcher) = &mut self.buffer_switcher {
+                            switcher.next();
+                        } else {
+                            self.buffer_switcher = BufferSwitcher::new(
+                                self.recent_buffers(clients),
+                                false,
+                                &config.keyboard.cycle_recent_buffer,
+                            );
+                        }
+                    }
+                    CyclePreviousRecentBuffer => {
+                        if let Some(switcher) = &mut self.buffer_switcher {
+                            switcher.previous();
+                        } else {
+                            self.buffer_switcher = BufferSwitcher::new(
+                                self.recent_buffers(clients),
+                                true,
+                                &config.keyboard.cycle_previous_recent_buffer,
+                            );
+                        }
+                    }
+                    FocusBuffer(index) => {
+                        let target = match config.keyboard.focus_buffer_target
+                        {
+                            FocusBufferTarget::Sidebar => all_buffers(
+                                clients,
+                                &self.history,
+                                &self.sidebar_order,
+                            )
+                            .into_iter()
+                            .nth(index)
+                            .map(data::Buffer::Upstream),
+                            FocusBufferTarget::Pane => self
+                                .panes
+                                .ordered()
+                                .into_iter()
+                                .nth(index)
+                                .and_then(|(window, pane)| {
+                                    self.panes.get(window, pane)
+                                })
+                                .and_then(|pane| pane.buffer.data()),
+                        };
+
+                        if let Some(buffer) = target {
+                            return (self.focus_buffer(buffer, config), None);
+                        }
+                    }
                     LeaveBuffer => {
                         if let Some((_, _, state)) = self.get_focused_mut() {
                             if let Some(buffer) =
@@ -1076,6 +1775,26 @@ impl Dashboard {
                             return (Task::none(), None);
                         }
                     }
+                    ToggleNicklistFilter => {
+                        return (
+                            self.get_focused_mut().map_or_else(
+                                Task::none,
+                                |(window, pane, state)| {
+                                    state.buffer.toggle_nick_list_filter().map(
+                                        move |message| {
+                                            Message::Pane(
+                                                window,
+                                                pane::Message::Buffer(
+                                                    pane, message,
+                                                ),
+                                            )
+                                        },
+                                    )
+                                },
+                            ),
+                            None,
+                        );
+                    }
                     ToggleTopic => {
                         if let Some((_, _, pane)) = self.get_focused_mut() {
                             if let Some(buffer) = pane.buffer.data() {
@@ -1090,6 +1809,9 @@ impl Dashboard {
                             return (Task::none(), None);
                         }
                     }
+                    TogglePin => {
+                        self.toggle_pin();
+                    }
                     ToggleSidebar => {
                         self.side_menu.toggle_visibility();
                     }
@@ -1150,6 +1872,11 @@ impl Dashboard {
                         return (window::toggle_fullscreen(), None);
                     }
                     QuitApplication => return (self.exit(config), None),
+                    ToggleDebugOverlay => {
+                        if config.developer.debug_overlay {
+                            self.toggle_debug_overlay();
+                        }
+                    }
                     ScrollUpPage => {
                         return (
                             self.get_focused_mut().map_or_else(
@@ -1191,32 +1918,33 @@ impl Dashboard {
                         );
                     }
                     ScrollToTop => {
+                        let mut older_chathistory = Task::none();
+
                         if config.buffer.chathistory.infinite_scroll {
                             if let Some((_, _, state)) = self.get_focused() {
                                 if let Some(buffer) = state.buffer.data() {
-                                    self.request_older_chathistory(
-                                        clients, &buffer,
-                                    );
+                                    older_chathistory =
+                                        self.schedule_older_chathistory(buffer);
                                 }
                             }
                         }
 
+                        let scroll = self.get_focused_mut().map_or_else(
+                            Task::none,
+                            |(window, id, pane)| {
+                                pane.buffer.scroll_to_start().map(
+                                    move |message| {
+                                        Message::Pane(
+                                            window,
+                                            pane::Message::Buffer(id, message),
+                                        )
+                                    },
+                                )
+                            },
+                        );
+
                         return (
-                            self.get_focused_mut().map_or_else(
-                                Task::none,
-                                |(window, id, pane)| {
-                                    pane.buffer.scroll_to_start().map(
-                                        move |message| {
-                                            Message::Pane(
-                                                window,
-                                                pane::Message::Buffer(
-                                                    id, message,
-                                                ),
-                                            )
-                                        },
-                                    )
-                                },
-                            ),
+                            Task::batch([older_chathistory, scroll]),
                             None,
                         );
                     }
@@ -1254,9 +1982,103 @@ impl Dashboard {
 
                         return (task, None);
                     }
+                    ScrollToFocusMarker => {
+                        let Focus { window, pane } = self.focus;
+
+                        if let Some(state) = self.panes.get_mut(window, pane) {
+                            return (
+                                state
+                                    .buffer
+                                    .scroll_to_focus_marker(
+                                        &self.history,
+                                        config,
+                                    )
+                                    .map(move |message| {
+                                        Message::Pane(
+                                            window,
+                                            pane::Message::Buffer(
+                                                pane, message,
+                                            ),
+                                        )
+                                    }),
+                                None,
+                            );
+                        }
+                    }
+                    SearchBuffer => {
+                        return (
+                            self.get_focused_mut().map_or_else(
+                                Task::none,
+                                |(window, pane, state)| {
+                                    state.buffer.open_search().map(
+                                        move |message| {
+                                            Message::Pane(
+                                                window,
+                                                pane::Message::Buffer(
+                                                    pane, message,
+                                                ),
+                                            )
+                                        },
+                                    )
+                                },
+                            ),
+                            None,
+                        );
+                    }
+                    SearchHistory => {
+                        let (history_search, task) = HistorySearch::new(
+                            clients
+                                .iter()
+                                .map(|(server, _)| server.clone())
+                                .collect(),
+                            config.matching,
+                        );
+
+                        self.history_search = Some(history_search);
+
+                        return (task.map(Message::HistorySearch), None);
+                    }
+                    QuickSwitcher => {
+                        let servers = clients
+                            .connected_servers()
+                            .map(|server| quick_switcher::ChannelRules {
+                                server: server.clone(),
+                                chantypes: clients
+                                    .get_chantypes(server)
+                                    .to_vec(),
+                                casemapping: clients.get_casemapping(server),
+                            })
+                            .collect();
+
+                        let (quick_switcher, task) =
+                            quick_switcher::QuickSwitcher::new(
+                                self.recent_buffers(clients),
+                                all_buffers(
+                                    clients,
+                                    &self.history,
+                                    &self.sidebar_order,
+                                )
+                                .into_iter()
+                                .map(data::Buffer::Upstream)
+                                .chain(
+                                    buffer::Internal::ALL
+                                        .iter()
+                                        .copied()
+                                        .map(data::Buffer::Internal),
+                                ),
+                                servers,
+                            );
+
+                        self.quick_switcher = Some(quick_switcher);
+
+                        return (task.map(Message::QuickSwitcher), None);
+                    }
                     CycleNextUnreadBuffer => {
-                        let all_buffers =
-                            all_buffers_with_has_unread(clients, &self.history);
+                        let all_buffers = all_buffers_with_activity(
+                            clients,
+                            &self.history,
+                            &self.sidebar_order,
+                        );
                         let open_buffers = open_buffers(self);
 
                         if let Some((window, pane, state)) =
@@ -1276,8 +2098,11 @@ impl Dashboard {
                         }
                     }
                     CyclePreviousUnreadBuffer => {
-                        let all_buffers =
-                            all_buffers_with_has_unread(clients, &self.history);
+                        let all_buffers = all_buffers_with_activity(
+                            clients,
+                            &self.history,
+                            &self.sidebar_order,
+                        );
                         let open_buffers = open_buffers(self);
 
                         if let Some((window, pane, state)) =
@@ -1348,6 +2173,10 @@ impl Dashboard {
                         if state.buffer.close_picker() {
                             return (Task::none(), None);
                         }
+
+                        if state.buffer.clear_selection() {
+                            return (Task::none(), None);
+                        }
                     }
 
                     if self.is_pane_maximized() && window == self.main_window()
@@ -1431,6 +2260,8 @@ impl Dashboard {
                         });
 
                     let limit = clients.get_server_chathistory_limit(&server);
+                    let request =
+                        Request::ChatHistory(server.clone(), target.clone());
 
                     clients.send_chathistory_request(
                         &server,
@@ -1440,6 +2271,15 @@ impl Dashboard {
                             limit,
                         ),
                     );
+
+                    return (
+                        self.sent(
+                            clients,
+                            scheduler::Category::Backlog,
+                            Some(request),
+                        ),
+                        None,
+                    );
                 }
                 client::Message::RequestChatHistoryTargets(
                     server,
@@ -1480,74 +2320,326 @@ impl Dashboard {
                     self.previews.insert(url, preview::State::Error(error));
                 }
             }
+            Message::RequestOlderChatHistory(buffer) => {
+                self.request_older_chathistory(clients, &buffer);
+
+                let request = buffer.upstream().and_then(|upstream| {
+                    upstream.target().map(|target| {
+                        Request::ChatHistory(upstream.server().clone(), target)
+                    })
+                });
+
+                return (
+                    self.sent(clients, scheduler::Category::Backlog, request),
+                    None,
+                );
+            }
+            Message::SendWhoPoll(server, channel) => {
+                self.queued_who_polls
+                    .remove(&(server.clone(), channel.clone()));
+
+                let sent =
+                    clients.send_who_poll(&server, &channel, Instant::now());
+                let task = self.sent(
+                    clients,
+                    scheduler::Category::Who,
+                    Some(Request::Who(server, channel)),
+                );
+
+                return (task, sent.err().map(Event::IrcError));
+            }
+            Message::TaskFinished(category) => {
+                self.scheduler.finished(category);
+
+                return (self.run_scheduled(), None);
+            }
+            Message::RunScheduled => {
+                return (self.run_scheduled(), None);
+            }
+            Message::LoadAvatar((url, result)) => {
+                if let Err(error) = &result {
+                    debug!("Failed to load avatar {url}: {error}");
+                }
+
+                if let Some(state) = self.avatars.get_mut(&url) {
+                    *state = match result {
+                        Ok(image) => {
+                            preview::State::Loaded(data::Preview::Image(image))
+                        }
+                        Err(error) => preview::State::Error(error),
+                    };
+                }
+            }
+            Message::Translated(hash, result) => {
+                if let Err(error) = &result {
+                    debug!("Failed to translate message: {error}");
+                }
+
+                self.translations.insert(
+                    hash,
+                    match result {
+                        Ok(translation) => {
+                            translation::State::Translated(translation)
+                        }
+                        Err(error) => translation::State::Failed(error),
+                    },
+                );
+            }
             Message::NewWindow(window, pane) => {
                 let (state, pane) = pane_grid::State::new(pane);
                 self.panes.popout.insert(window, state);
 
                 return (self.focus_pane(window, pane), None);
             }
-        }
-
-        (Task::none(), None)
-    }
+            Message::TrimHistory => {
+                let trimmed = self.history.trim();
 
-    pub fn view_window<'a>(
-        &'a self,
-        window: window::Id,
-        clients: &'a client::Map,
-        config: &'a Config,
-        theme: &'a Theme,
-    ) -> Element<'a, Message> {
-        if let Some(state) = self.panes.popout.get(&window) {
-            let content = container(
-                PaneGrid::new(state, |id, pane, _maximized| {
-                    let is_focused = self.focus == Focus { window, pane: id };
-                    let buffer = pane.buffer.data();
-                    let settings = buffer
-                        .as_ref()
-                        .and_then(|b| self.buffer_settings.get(b));
+                debug!("Trimmed {trimmed} messages from history");
 
-                    pane.view(
-                        id,
-                        1,
-                        is_focused,
-                        false,
-                        clients,
-                        &self.file_transfers,
+                if let Some(debug_overlay) = &mut self.debug_overlay {
+                    debug_overlay.refresh(
                         &self.history,
                         &self.previews,
-                        &self.side_menu,
-                        config,
-                        theme,
-                        settings,
-                        window != self.main_window(),
-                    )
-                })
-                .spacing(4)
-                .on_click(pane::Message::PaneClicked),
-            )
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .padding(8);
+                        &self.scheduler,
+                    );
+                }
+            }
+            Message::ConnectionReport(message) => {
+                let Some(report) = &mut self.connection_report else {
+                    return (Task::none(), None);
+                };
 
-            return Element::new(content)
-                .map(move |message| Message::Pane(window, message));
-        } else if let Some(editor) = self.theme_editor.as_ref() {
-            if editor.window == window {
-                return editor.view(theme).map(Message::ThemeEditor);
+                let event = report.update(message);
+
+                if report.is_empty() {
+                    self.connection_report = None;
+                }
+
+                match event {
+                    Some(connection_report::Event::RetryServer(server)) => {
+                        return (Task::none(), Some(Event::RetryServer(server)));
+                    }
+                    Some(connection_report::Event::RetryChannel(
+                        server,
+                        channel,
+                    )) => {
+                        clients.join(&server, &[channel]);
+                    }
+                    Some(connection_report::Event::Dismissed) => {
+                        self.connection_report = None;
+                    }
+                    None => {}
+                }
             }
-        }
+            Message::ThemePreviews(previews) => {
+                self.theme_picker = Some(ThemePicker::new(previews));
+            }
+            Message::ThemePicker(message) => {
+                let Some(picker) = &mut self.theme_picker else {
+                    return (Task::none(), None);
+                };
 
-        column![].into()
-    }
+                let task = match picker.update(message) {
+                    theme_picker::Event::Picked(name) => {
+                        load_theme(name, config)
+                    }
+                    theme_picker::Event::Closed => Task::none(),
+                };
 
-    pub fn view<'a>(
+                self.theme_picker = None;
+
+                return (task, None);
+            }
+            Message::ThemeLoaded(Ok(loaded)) => {
+                let name = loaded.name.clone();
+
+                *theme = Theme::from(loaded);
+
+                return (
+                    Task::perform(
+                        Config::save_theme(name),
+                        Message::ThemeSaved,
+                    ),
+                    None,
+                );
+            }
+            Message::ThemeLoaded(Err(error)) => {
+                // The current theme is kept
+                notification::toast("Couldn't switch theme", error);
+            }
+            Message::ThemeSaved(Ok(())) => {
+                // Picks up the saved theme as the selected one
+                return (Task::none(), Some(Event::ReloadThemes));
+            }
+            Message::ThemeSaved(Err(error)) => {
+                notification::toast("Couldn't save theme to config", error);
+            }
+            Message::AuditLog(message) => {
+                let Some(audit_log) = &mut self.audit_log else {
+                    return (Task::none(), None);
+                };
+
+                if let Some(audit_log::Event::Closed) = audit_log.update(message)
+                {
+                    self.audit_log = None;
+                }
+            }
+            Message::HistorySearch(message) => {
+                let Some(history_search) = &mut self.history_search else {
+                    return (Task::none(), None);
+                };
+
+                let (task, event) = history_search.update(message);
+
+                match event {
+                    Some(history_search::Event::GoToMessage(
+                        buffer,
+                        message,
+                    )) => {
+                        self.history_search = None;
+
+                        return (
+                            self.go_to_message(buffer, message, true, config),
+                            None,
+                        );
+                    }
+                    Some(history_search::Event::Closed) => {
+                        self.history_search = None;
+                    }
+                    None => {
+                        return (task.map(Message::HistorySearch), None);
+                    }
+                }
+            }
+            Message::QuickSwitcher(message) => {
+                let Some(quick_switcher) = &mut self.quick_switcher else {
+                    return (Task::none(), None);
+                };
+
+                if let Some(quick_switcher::Event::Open(entry, new_pane)) =
+                    quick_switcher.update(message)
+                {
+                    self.quick_switcher = None;
+
+                    let buffer_action = if new_pane {
+                        BufferAction::NewPane
+                    } else {
+                        BufferAction::ReplacePane
+                    };
+
+                    return (
+                        match entry {
+                            quick_switcher::Entry::Buffer(buffer) => {
+                                self.open_buffer(buffer, buffer_action, config)
+                            }
+                            quick_switcher::Entry::Join(server, channel) => {
+                                self.open_channel(
+                                    server,
+                                    channel,
+                                    clients,
+                                    buffer_action,
+                                    config,
+                                )
+                            }
+                        },
+                        None,
+                    );
+                }
+            }
+            Message::AuditLogLoaded(server, channel, Ok(entries)) => {
+                self.audit_log = Some(AuditLog::new(server, channel, entries));
+            }
+            Message::TopicHistoryLoaded(server, channel, Ok(topics)) => {
+                return (
+                    self.broadcast(
+                        &server,
+                        config,
+                        Utc::now(),
+                        Broadcast::TopicHistory { channel, topics },
+                    ),
+                    None,
+                );
+            }
+            Message::AuditLogLoaded(_, channel, Err(error))
+            | Message::TopicHistoryLoaded(_, channel, Err(error)) => {
+                notification::toast(
+                    &format!("Couldn't load audit log of {channel}"),
+                    error,
+                );
+            }
+            Message::HighlightsLoaded(Ok(loaded)) => {
+                self.highlights.loaded(&loaded.messages);
+            }
+            Message::HighlightsLoaded(Err(error)) => {
+                log::warn!("failed to load highlights: {error}");
+            }
+        }
+
+        (Task::none(), None)
+    }
+
+    pub fn view_window<'a>(
+        &'a self,
+        window: window::Id,
+        clients: &'a client::Map,
+        config: &'a Config,
+        theme: &'a Theme,
+    ) -> Element<'a, Message> {
+        if let Some(state) = self.panes.popout.get(&window) {
+            let content = container(
+                PaneGrid::new(state, |id, pane, _maximized| {
+                    let is_focused = self.focus == Focus { window, pane: id };
+                    let buffer = pane.buffer.data();
+                    let settings = buffer
+                        .as_ref()
+                        .and_then(|b| self.buffer_settings.get(b));
+
+                    pane.view(
+                        id,
+                        1,
+                        is_focused,
+                        false,
+                        clients,
+                        &self.file_transfers,
+                        &self.history,
+                        &self.previews,
+                        &self.avatars,
+                        &self.translations,
+                        &self.side_menu,
+                        config,
+                        theme,
+                        settings,
+                        window != self.main_window(),
+                    )
+                })
+                .spacing(4)
+                .on_click(pane::Message::PaneClicked),
+            )
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(8);
+
+            return Element::new(content)
+                .map(move |message| Message::Pane(window, message));
+        } else if let Some(editor) = self.theme_editor.as_ref() {
+            if editor.window == window {
+                return editor.view(theme).map(Message::ThemeEditor);
+            }
+        }
+
+        column![].into()
+    }
+
+    pub fn view<'a>(
         &'a self,
         clients: &'a client::Map,
         version: &'a Version,
         config: &'a Config,
         theme: &'a Theme,
+        stalls: Option<watchdog::Stats>,
     ) -> Element<'a, Message> {
+        let started_at = Instant::now();
+
         let pane_grid: Element<_> =
             PaneGrid::new(&self.panes.main, |id, pane, maximized| {
                 let is_focused = self.focus
@@ -1569,6 +2661,8 @@ impl Dashboard {
                     &self.file_transfers,
                     &self.history,
                     &self.previews,
+                    &self.avatars,
+                    &self.translations,
                     &self.side_menu,
                     config,
                     theme,
@@ -1597,8 +2691,12 @@ impl Dashboard {
                 &self.history,
                 &self.panes,
                 self.focus,
+                &self.buffer_settings,
+                &self.sidebar_order,
+                &self.collapsed_servers,
                 config,
                 &self.file_transfers,
+                &self.highlights,
                 version,
             )
             .map(|e| e.map(Message::Sidebar));
@@ -1633,6 +2731,77 @@ impl Dashboard {
                 .into()
         };
 
+        let debug_overlay = self
+            .debug_overlay
+            .as_ref()
+            .filter(|_| config.developer.debug_overlay);
+
+        let base = if let Some(debug_overlay) = debug_overlay {
+            anchored_overlay(
+                base,
+                debug_overlay.view(stalls, Message::TrimHistory),
+                anchored_overlay::Anchor::BelowTopRight,
+                10.0,
+            )
+        } else {
+            base
+        };
+
+        let base = if let Some(picker) = &self.theme_picker {
+            anchored_overlay(
+                base,
+                picker.view().map(Message::ThemePicker),
+                anchored_overlay::Anchor::BelowTopCentered,
+                10.0,
+            )
+        } else {
+            base
+        };
+
+        let base = if let Some(audit_log) = &self.audit_log {
+            anchored_overlay(
+                base,
+                audit_log.view().map(Message::AuditLog),
+                anchored_overlay::Anchor::BelowTopCentered,
+                10.0,
+            )
+        } else {
+            base
+        };
+
+        let base = if let Some(history_search) = &self.history_search {
+            anchored_overlay(
+                base,
+                history_search.view().map(Message::HistorySearch),
+                anchored_overlay::Anchor::BelowTopCentered,
+                10.0,
+            )
+        } else {
+            base
+        };
+
+        let base = if let Some(quick_switcher) = &self.quick_switcher {
+            anchored_overlay(
+                base,
+                quick_switcher.view().map(Message::QuickSwitcher),
+                anchored_overlay::Anchor::BelowTopCentered,
+                10.0,
+            )
+        } else {
+            base
+        };
+
+        let base = if let Some(report) = &self.connection_report {
+            anchored_overlay(
+                base,
+                report.view().map(Message::ConnectionReport),
+                anchored_overlay::Anchor::BelowTopCentered,
+                10.0,
+            )
+        } else {
+            base
+        };
+
         let base = if let Some(command_bar) = self.command_bar.as_ref() {
             let background = anchored_overlay(
                 base,
@@ -1649,7 +2818,11 @@ impl Dashboard {
                 background,
                 command_bar
                     .view(
-                        &all_buffers(clients, &self.history),
+                        &all_buffers(
+                            clients,
+                            &self.history,
+                            &self.sidebar_order,
+                        ),
                         self.focus,
                         self.buffer_resize_action(),
                         version,
@@ -1660,6 +2833,13 @@ impl Dashboard {
                 anchored_overlay::Anchor::BelowTopCentered,
                 10.0,
             )
+        } else if let Some(buffer_switcher) = self.buffer_switcher.as_ref() {
+            anchored_overlay(
+                column![base],
+                buffer_switcher.view(),
+                anchored_overlay::Anchor::BelowTopCentered,
+                10.0,
+            )
         } else {
             // Align `base` into same view tree shape
             // as `anchored_overlay` to prevent diff
@@ -1667,6 +2847,10 @@ impl Dashboard {
             column![column![base]].into()
         };
 
+        if let Some(debug_overlay) = debug_overlay {
+            debug_overlay.record_frame_time(started_at.elapsed());
+        }
+
         shortcut(base, config.keyboard.shortcuts(), Message::Shortcut)
     }
 
@@ -1685,11 +2869,21 @@ impl Dashboard {
             Escape => {
                 // Order of operations
                 //
+                // - Close buffer switcher
+                // - Close history search and quick switcher
                 // - Close command bar (if main window)
                 // - Close context menu
                 // - Close command/emoji picker
+                // - Clear selected messages
                 // - Restore maximized pane (if main window)
-                if self.command_bar.is_some() && window == self.main_window() {
+                if self.buffer_switcher.take().is_some()
+                    || self.history_search.take().is_some()
+                    || self.quick_switcher.take().is_some()
+                {
+                    Task::none()
+                } else if self.command_bar.is_some()
+                    && window == self.main_window()
+                {
                     self.toggle_command_bar(
                         &closed_buffers(self, clients),
                         version,
@@ -1704,8 +2898,37 @@ impl Dashboard {
                     )
                 }
             }
-            Copy => selectable_text::selected(Message::SelectedText),
+            Copy => {
+                // Whole messages selected by dragging across them take
+                // precedence over selected text
+                if let Some(text) =
+                    self.get_focused().and_then(|(_, _, state)| {
+                        state.buffer.selected_messages(&self.history, config)
+                    })
+                {
+                    clipboard::write(text)
+                } else {
+                    selectable_text::selected(Message::SelectedText)
+                }
+            }
             LeftClick => self.refocus_pane(),
+            ModifiersReleased => {
+                if let Some(switcher) = self.buffer_switcher.take() {
+                    self.focus_buffer(switcher.selected(), config)
+                } else {
+                    Task::none()
+                }
+            }
+            KeyReleased => {
+                if let Some(switcher) = self
+                    .buffer_switcher
+                    .take_if(|switcher| switcher.selects_on_key_release())
+                {
+                    self.focus_buffer(switcher.selected(), config)
+                } else {
+                    Task::none()
+                }
+            }
         }
     }
 
@@ -1759,33 +2982,32 @@ impl Dashboard {
 
         self.last_changed = Some(Instant::now());
 
+        if buffer == data::Buffer::Internal(buffer::Internal::Highlights) {
+            self.highlights.mark_as_read();
+        }
+
         match buffer_action {
             BufferAction::ReplacePane => {
-                // If buffer already is open, we swap it with focused pane.
-                for (window, id, pane) in panes.iter() {
-                    if pane.buffer.data().as_ref() == Some(&buffer) {
-                        if window != self.focus.window || id != self.focus.pane
-                        {
-                            return self.swap_pane_with_focus(window, id);
-                        } else {
-                            return Task::none();
-                        }
-                    }
-                }
-
                 let Focus { window, pane } = self.focus;
 
-                if let Some(state) = self.panes.get_mut(window, pane) {
-                    state.buffer = Buffer::from(buffer);
-                    self.last_changed = Some(Instant::now());
+                let is_open = panes.iter().any(|(_, _, state)| {
+                    state.buffer.data().as_ref() == Some(&buffer)
+                });
 
-                    Task::batch(vec![
-                        self.reset_pane(window, pane),
-                        self.focus_pane(window, pane),
-                    ])
+                if is_open || !self.panes.is_pinned(window, pane) {
+                    return self.replace_focused_pane(buffer);
+                }
+
+                // Focused pane is pinned, so use the first unpinned pane
+                // instead or open the buffer in a new pane.
+                if let Some((window, pane)) =
+                    panes.iter().find_map(|(window, pane, state)| {
+                        (!state.pinned).then_some((window, pane))
+                    })
+                {
+                    self.replace_pane(window, pane, buffer)
                 } else {
-                    log::error!("Didn't find any panes to replace");
-                    Task::none()
+                    self.open_buffer(buffer, BufferAction::NewPane, config)
                 }
             }
             BufferAction::NewPane => {
@@ -1801,7 +3023,8 @@ impl Dashboard {
                 // If we only have one pane, and its empty, we replace it.
                 if self.panes.len() == 1 {
                     for (id, pane) in panes.main.iter() {
-                        if matches!(pane.buffer, Buffer::Empty) {
+                        if matches!(pane.buffer, Buffer::Empty) && !pane.pinned
+                        {
                             self.panes.main.panes.entry(*id).and_modify(|p| {
                                 *p = Pane::new(Buffer::from(buffer));
                             });
@@ -1845,29 +3068,211 @@ impl Dashboard {
                 Task::none()
             }
             BufferAction::NewWindow => {
-                get_position(self.main_window()).then(
-                    move |main_window_position| {
-                        let (_, task) = window::open(window::Settings {
-                            // Just big enough to show all components in combobox
-                            position: main_window_position
-                                .map(|point| {
-                                    window::Position::Specific(
-                                        point + Vector::new(20.0, 20.0),
-                                    )
-                                })
-                                .unwrap_or_default(),
-                            exit_on_close_request: false,
-                            ..window::settings()
-                        });
+                self.open_popout(Pane::new(Buffer::from(buffer)))
+            }
+        }
+    }
 
-                        task.map({
-                            let pane = Pane::new(Buffer::from(buffer.clone()));
-                            move |id| Message::NewWindow(id, pane.clone())
-                        })
-                    },
+    fn open_popout(&self, pane: Pane) -> Task<Message> {
+        get_position(self.main_window()).then(move |main_window_position| {
+            let (_, task) = window::open(window::Settings {
+                // Just big enough to show all components in combobox
+                position: main_window_position
+                    .map(|point| {
+                        window::Position::Specific(
+                            point + Vector::new(20.0, 20.0),
+                        )
+                    })
+                    .unwrap_or_default(),
+                exit_on_close_request: false,
+                ..window::settings()
+            });
+
+            task.map({
+                let pane = pane.clone();
+                move |id| Message::NewWindow(id, pane.clone())
+            })
+        })
+    }
+
+    /// Replaces the buffer of the focused pane, regardless of it being
+    /// pinned. If the buffer is already open, it's swapped with the focused
+    /// pane, or focused if either of the panes is pinned.
+    fn replace_focused_pane(&mut self, buffer: data::Buffer) -> Task<Message> {
+        let Focus { window, pane } = self.focus;
+
+        let open = self.panes.iter().find_map(|(window, pane, state)| {
+            (state.buffer.data().as_ref() == Some(&buffer))
+                .then_some((window, pane, state.pinned))
+        });
+
+        if let Some((open_window, open_pane, is_pinned)) = open {
+            if open_window == window && open_pane == pane {
+                return Task::none();
+            } else if is_pinned || self.panes.is_pinned(window, pane) {
+                return self.focus_pane(open_window, open_pane);
+            } else {
+                return self.swap_pane_with_focus(open_window, open_pane);
+            }
+        }
+
+        self.replace_pane(window, pane, buffer)
+    }
+
+    fn replace_pane(
+        &mut self,
+        window: window::Id,
+        pane: pane_grid::Pane,
+        buffer: data::Buffer,
+    ) -> Task<Message> {
+        if let Some(state) = self.panes.get_mut(window, pane) {
+            state.buffer = Buffer::from(buffer);
+            self.last_changed = Some(Instant::now());
+
+            Task::batch(vec![
+                self.reset_pane(window, pane),
+                self.focus_pane(window, pane),
+            ])
+        } else {
+            log::error!("Didn't find any panes to replace");
+            Task::none()
+        }
+    }
+
+    fn toggle_pin(&mut self) {
+        if let Some((_, _, pane)) = self.get_focused_mut() {
+            pane.pinned = !pane.pinned;
+            self.last_changed = Some(Instant::now());
+        }
+    }
+
+    /// Shows `to` in place of `from`, as that's where joining `from` ended
+    /// up, saving `forwards` when the forward was remembered.
+    pub fn channel_forwarded(
+        &mut self,
+        server: &Server,
+        from: target::Channel,
+        to: target::Channel,
+        forwards: Option<client::Forwards>,
+    ) -> Task<Message> {
+        let from = buffer::Upstream::Channel(server.clone(), from);
+        let to = buffer::Upstream::Channel(server.clone(), to);
+
+        let mut is_open = self
+            .panes
+            .iter()
+            .any(|(_, _, state)| state.buffer.upstream() == Some(&to));
+
+        let forwarded = self
+            .panes
+            .iter()
+            .filter_map(|(window, pane, state)| {
+                (state.buffer.upstream() == Some(&from))
+                    .then_some((window, pane))
+            })
+            .collect::<Vec<_>>();
+
+        let mut tasks = forwarded
+            .into_iter()
+            .map(|(window, pane)| {
+                if is_open {
+                    self.close_pane(window, pane)
+                } else {
+                    is_open = true;
+
+                    self.replace_pane(
+                        window,
+                        pane,
+                        data::Buffer::Upstream(to.clone()),
+                    )
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(forwards) = forwards {
+            tasks.push(Task::perform(
+                forwards.save(server.clone()),
+                Message::ForwardsSaved,
+            ));
+        }
+
+        Task::batch(tasks)
+    }
+
+    /// Shows the server buffer in place of queries with bouncer services,
+    /// moving their history into it
+    pub fn merge_bouncer_queries(
+        &mut self,
+        server: &Server,
+        clients: &data::client::Map,
+    ) -> Task<Message> {
+        let bouncer_services = clients.get_bouncer_services(server);
+        let casemapping = clients.get_casemapping(server);
+
+        let queries = self
+            .history
+            .get_unique_queries(server)
+            .into_iter()
+            .cloned()
+            .chain(self.panes.iter().filter_map(|(_, _, state)| {
+                match state.buffer.upstream() {
+                    Some(buffer::Upstream::Query(s, query)) if s == server => {
+                        Some(query.clone())
+                    }
+                    _ => None,
+                }
+            }))
+            .filter(|query| {
+                data::bouncer::is_service(
+                    query.as_str(),
+                    bouncer_services,
+                    casemapping,
                 )
+            })
+            .collect::<HashSet<_>>();
+
+        let to = buffer::Upstream::Server(server.clone());
+
+        let mut is_open = self
+            .panes
+            .iter()
+            .any(|(_, _, state)| state.buffer.upstream() == Some(&to));
+
+        let mut tasks = vec![];
+
+        for query in queries {
+            let from = buffer::Upstream::Query(server.clone(), query.clone());
+
+            let panes = self
+                .panes
+                .iter()
+                .filter_map(|(window, pane, state)| {
+                    (state.buffer.upstream() == Some(&from))
+                        .then_some((window, pane))
+                })
+                .collect::<Vec<_>>();
+
+            for (window, pane) in panes {
+                if is_open {
+                    tasks.push(self.close_pane(window, pane));
+                } else {
+                    is_open = true;
+
+                    tasks.push(self.replace_pane(
+                        window,
+                        pane,
+                        data::Buffer::Upstream(to.clone()),
+                    ));
+                }
             }
+
+            tasks.push(Task::perform(
+                self.history.merge_into_server(server, &query),
+                Message::History,
+            ));
         }
+
+        Task::batch(tasks)
     }
 
     pub fn leave_buffer(
@@ -1876,23 +3281,52 @@ impl Dashboard {
         buffer: buffer::Upstream,
         mark_as_read: bool,
     ) -> (Task<Message>, Option<Event>) {
+        // Asks for confirmation before disconnecting, see `quit_server`
+        if let buffer::Upstream::Server(server) = &buffer {
+            return (Task::none(), Some(Event::QuitServer(server.clone())));
+        }
+
         let open = self.panes.iter().find_map(|(window, pane, state)| {
             (state.buffer.upstream() == Some(&buffer)).then_some((window, pane))
         });
 
         let mut tasks = vec![];
 
-        // Close pane
         if let Some((window, pane)) = open {
-            tasks.push(self.close_pane(window, pane));
+            // The buffer above it in the sidebar takes the place of one
+            // focused in the only pane, rather than leaving it empty
+            let neighbor = (Focus { window, pane } == self.focus
+                && window == self.main_window()
+                && self.panes.main.len() == 1)
+                .then(|| {
+                    cycle_previous_buffer(
+                        Some(&buffer),
+                        all_buffers(
+                            clients,
+                            &self.history,
+                            &self.sidebar_order,
+                        ),
+                        &open_buffers(self),
+                    )
+                })
+                .flatten();
+
+            if let Some(neighbor) = neighbor {
+                if let Some(state) = self.panes.get_mut(window, pane) {
+                    state.buffer =
+                        Buffer::from(data::Buffer::Upstream(neighbor));
+                }
+
+                tasks.push(self.focus_pane(window, pane));
+            } else {
+                tasks.push(self.close_pane(window, pane));
+            }
 
             self.last_changed = Some(Instant::now());
         }
 
         match buffer.clone() {
-            buffer::Upstream::Server(server) => {
-                (Task::batch(tasks), Some(Event::QuitServer(server)))
-            }
+            buffer::Upstream::Server(_) => (Task::batch(tasks), None),
             buffer::Upstream::Channel(server, channel) => {
                 // Send part & close history file
                 let command = command::Irc::Part(channel.to_string(), None);
@@ -1930,6 +3364,154 @@ impl Dashboard {
         }
     }
 
+    /// Disconnects from `server` once it's confirmed, closing its buffer
+    pub fn quit_server(
+        &mut self,
+        clients: &mut data::client::Map,
+        server: Server,
+    ) -> Task<Message> {
+        let buffer = buffer::Upstream::Server(server.clone());
+        let open = self.panes.iter().find_map(|(window, pane, state)| {
+            (state.buffer.upstream() == Some(&buffer)).then_some((window, pane))
+        });
+
+        clients.quit(&server, None);
+
+        if let Some((window, pane)) = open {
+            self.close_pane(window, pane)
+        } else {
+            Task::none()
+        }
+    }
+
+    /// Sends the lines of pasted `text` to `buffer` once it's confirmed, or
+    /// where it was uploaded to
+    pub fn send_paste(
+        &mut self,
+        clients: &mut data::client::Map,
+        buffer: &buffer::Upstream,
+        text: String,
+        config: &Config,
+    ) -> Task<Message> {
+        let inputs = match input::parse_lines(
+            buffer.clone(),
+            config.buffer.text_input.auto_format,
+            config.buffer.text_input.markdown_links,
+            &text,
+        ) {
+            Ok(inputs) => inputs,
+            Err(error) => {
+                log::warn!("failed to send paste: {error}");
+                return Task::none();
+            }
+        };
+
+        self.history.record_input_history(buffer, text);
+
+        Task::batch(inputs.into_iter().map(|input| {
+            buffer::input_view::State::send_input(
+                buffer,
+                input,
+                clients,
+                &mut self.history,
+                config,
+            )
+            .map(Message::History)
+        }))
+    }
+
+    /// Clears the input of `buffer`, when sending a paste was cancelled
+    pub fn discard_input(&mut self, buffer: &buffer::Upstream) {
+        let raw_input = |text: &str| input::RawInput {
+            buffer: buffer.clone(),
+            text: text.to_string(),
+        };
+
+        self.history.record_text(raw_input(""));
+        self.history.record_draft(raw_input(""));
+    }
+
+    pub fn is_ignored(&self, server: &Server, user: &User) -> bool {
+        self.history.ignores().is_ignored(server, user)
+    }
+
+    /// Notification override of the channel or query `target` is shown in
+    pub fn notifications(
+        &self,
+        server: &Server,
+        target: &data::message::Target,
+    ) -> Option<data::buffer::Notifications> {
+        self.buffer_settings
+            .notifications(&upstream_buffer(server, target)?)
+    }
+
+    /// Trigger of a notification for `message`, if it was received in a
+    /// channel or query
+    pub fn notification_trigger(
+        &self,
+        server: &Server,
+        message: &data::Message,
+        is_window_focused: bool,
+    ) -> Option<notification::Trigger> {
+        let buffer = upstream_buffer(server, &message.target)?;
+        let is_focused = is_window_focused
+            && self.get_focused().is_some_and(|(_, _, state)| {
+                state.buffer.upstream() == Some(&buffer)
+            });
+
+        Some(notification::Trigger {
+            buffer,
+            message: message.hash,
+            is_focused,
+        })
+    }
+
+    /// Scrolls to `message` in `buffer`, opening it if needed. Pinned panes
+    /// are always focused, others only with `focus`.
+    pub fn go_to_message(
+        &mut self,
+        buffer: buffer::Upstream,
+        message: data::message::Hash,
+        focus: bool,
+        config: &Config,
+    ) -> Task<Message> {
+        let buffer = data::Buffer::Upstream(buffer);
+
+        let mut tasks = vec![];
+
+        if self.panes.get_mut_by_buffer(&buffer).is_none() {
+            tasks.push(self.open_buffer(
+                buffer.clone(),
+                config.actions.buffer.click_highlight,
+                config,
+            ));
+        }
+
+        if let Some((window, pane, state)) =
+            self.panes.get_mut_by_buffer(&buffer)
+        {
+            let focus = focus || state.pinned;
+
+            tasks.push(
+                state
+                    .buffer
+                    .scroll_to_message(message, &self.history, config)
+                    .map(move |message| {
+                        Message::Pane(
+                            window,
+                            pane::Message::Buffer(pane, message),
+                        )
+                    }),
+            );
+
+            if focus {
+                tasks.push(self.focus_pane(window, pane));
+            }
+        }
+
+        Task::batch(tasks)
+    }
+
     pub fn record_message(
         &mut self,
         server: &Server,
@@ -1942,6 +3524,27 @@ impl Dashboard {
         }
     }
 
+    pub fn record_received(
+        &mut self,
+        server: &Server,
+        message: data::Message,
+        is_privmsg: bool,
+        is_joined: impl Fn(&target::Channel) -> bool,
+        config: &Config,
+    ) -> Task<Message> {
+        if let Some(task) = self.history.record_received(
+            server,
+            message,
+            is_privmsg,
+            is_joined,
+            config.history.closed_buffers,
+        ) {
+            Task::perform(task, Message::History)
+        } else {
+            Task::none()
+        }
+    }
+
     pub fn record_log(&mut self, record: data::log::Record) -> Task<Message> {
         if let Some(task) = self.history.record_log(record) {
             Task::perform(task, Message::History)
@@ -1954,6 +3557,16 @@ impl Dashboard {
         &mut self,
         message: data::Message,
     ) -> Task<Message> {
+        // Seen as they arrive while the Highlights buffer is open
+        if self.panes.iter().any(|(_, _, state)| {
+            state.buffer.internal() == Some(buffer::Internal::Highlights)
+        }) {
+            self.highlights.mark_as_read();
+            self.last_changed = Some(Instant::now());
+        } else {
+            self.highlights.record(&message);
+        }
+
         if let Some(task) = self.history.record_highlight(message) {
             Task::perform(task, Message::History)
         } else {
@@ -1990,6 +3603,59 @@ impl Dashboard {
         MessageReference::None
     }
 
+    /// Requests older messages of `buffer` once background work allows
+    fn schedule_older_chathistory(
+        &mut self,
+        buffer: data::Buffer,
+    ) -> Task<Message> {
+        self.scheduler.submit(
+            scheduler::Category::Backlog,
+            Task::done(Message::RequestOlderChatHistory(buffer)),
+        );
+
+        self.run_scheduled()
+    }
+
+    /// Starts the background work which can start now. When held back by
+    /// interactive work, it's run again once that frame passed.
+    fn run_scheduled(&mut self) -> Task<Message> {
+        let started = self.scheduler.poll(Instant::now()).into_iter().map(
+            |(category, task)| match category {
+                // Finished once their requests are answered, see `Self::sent`
+                scheduler::Category::Backlog | scheduler::Category::Who => task,
+                scheduler::Category::Preview | scheduler::Category::Avatar => {
+                    task.chain(Task::done(Message::TaskFinished(category)))
+                }
+            },
+        );
+
+        let resume = self.scheduler.resume_at().map(|resume_at| {
+            Task::perform(tokio::time::sleep_until(resume_at.into()), |()| {
+                Message::RunScheduled
+            })
+        });
+
+        Task::batch(started.chain(resume).collect::<Vec<_>>())
+    }
+
+    /// Keeps the background work of `category` running until `request` is
+    /// answered, or finishes it if nothing is waited on
+    fn sent(
+        &mut self,
+        clients: &client::Map,
+        category: scheduler::Category,
+        request: Option<Request>,
+    ) -> Task<Message> {
+        match request
+            .filter(|request| request.pending(clients, Instant::now()))
+        {
+            Some(request) => self.requests.sent(category, request),
+            None => self.scheduler.finished(category),
+        }
+
+        self.run_scheduled()
+    }
+
     pub fn request_older_chathistory(
         &self,
         clients: &mut data::client::Map,
@@ -2043,6 +3709,19 @@ impl Dashboard {
         sent_time: DateTime<Utc>,
         broadcast: Broadcast,
     ) -> Task<Message> {
+        match &broadcast {
+            Broadcast::ConnectionFailed { error }
+            | Broadcast::Disconnected {
+                error: Some(error), ..
+            } => {
+                self.connection_errors.insert(server.clone(), error.clone());
+            }
+            Broadcast::Connected | Broadcast::Reconnected => {
+                self.connection_errors.remove(server);
+            }
+            _ => {}
+        }
+
         Task::batch(
             self.history
                 .broadcast(server, broadcast, config, sent_time)
@@ -2053,14 +3732,28 @@ impl Dashboard {
 
     pub fn update_read_marker(
         &mut self,
-        kind: impl Into<history::Kind> + 'static,
+        kind: impl Into<history::Kind>,
         read_marker: ReadMarker,
-    ) -> Task<Message> {
-        if let Some(task) = self.history.update_read_marker(kind, read_marker) {
-            Task::perform(task, Message::History)
-        } else {
-            Task::none()
-        }
+    ) {
+        self.history.update_read_marker(kind, read_marker);
+    }
+
+    pub fn record_audit(
+        &mut self,
+        server: &Server,
+        channel: target::Channel,
+        entries: Vec<audit::Entry>,
+    ) {
+        self.history.record_audit(server.clone(), channel, entries);
+    }
+
+    pub fn add_reaction(
+        &mut self,
+        kind: impl Into<history::Kind>,
+        id: &str,
+        reaction: data::message::Reaction,
+    ) {
+        self.history.add_reaction(kind, id, reaction);
     }
 
     pub fn load_metadata(
@@ -2075,7 +3768,7 @@ impl Dashboard {
             .load_metadata(server.clone(), target.clone())
             .map_or(Task::none(), |task| Task::perform(task, Message::History));
 
-        if clients.get_server_supports_chathistory(&server) {
+        let command = if clients.get_server_supports_chathistory(&server) {
             command.chain(Task::done(Message::Client(
                 data::client::Message::RequestNewerChatHistory(
                     server,
@@ -2084,8 +3777,14 @@ impl Dashboard {
                 ),
             )))
         } else {
-            command
-        }
+            command.chain(Task::done(Message::TaskFinished(
+                scheduler::Category::Backlog,
+            )))
+        };
+
+        self.scheduler.submit(scheduler::Category::Backlog, command);
+
+        self.run_scheduled()
     }
 
     pub fn load_chathistory_targets_timestamp(
@@ -2126,6 +3825,29 @@ impl Dashboard {
             .map(|state| (window, pane, state))
     }
 
+    /// Moves the focused channel or query one place up or down in the
+    /// sidebar
+    fn move_focused_buffer(
+        &mut self,
+        clients: &data::client::Map,
+        direction: dashboard::Direction,
+    ) {
+        let Some(buffer) = self
+            .get_focused()
+            .and_then(|(_, _, pane)| pane.buffer.upstream())
+            .cloned()
+        else {
+            return;
+        };
+
+        let all_buffers =
+            all_buffers(clients, &self.history, &self.sidebar_order);
+
+        if self.sidebar_order.shift(&all_buffers, &buffer, direction) {
+            self.last_changed = Some(Instant::now());
+        }
+    }
+
     fn get_focused_with_history_mut(
         &mut self,
     ) -> Option<(pane_grid::Pane, &mut Pane, &mut history::Manager)> {
@@ -2164,6 +3886,10 @@ impl Dashboard {
         pane: pane_grid::Pane,
     ) -> Task<Message> {
         if (self.focus != Focus { window, pane }) {
+            if let Some((_, _, state)) = self.get_focused_mut() {
+                state.buffer.clear_selection();
+            }
+
             self.focus = Focus { window, pane };
 
             self.last_changed = Some(Instant::now());
@@ -2180,9 +3906,79 @@ impl Dashboard {
             }
         }
 
+        self.record_recent_buffer();
+        self.expand_focused_server();
+
         self.refocus_pane()
     }
 
+    fn record_recent_buffer(&mut self) {
+        let Some(buffer) = self
+            .panes
+            .get(self.focus.window, self.focus.pane)
+            .and_then(|pane| pane.buffer.data())
+        else {
+            return;
+        };
+
+        self.recent_buffers.retain(|recent| *recent != buffer);
+        self.recent_buffers.push_front(buffer);
+        self.recent_buffers.truncate(RECENT_BUFFERS_LEN);
+    }
+
+    /// Expands the server of the focused channel or query in the sidebar, if
+    /// it's collapsed
+    fn expand_focused_server(&mut self) {
+        let Some(server) = self
+            .get_focused()
+            .and_then(|(_, _, pane)| pane.buffer.upstream())
+            .filter(|buffer| !matches!(buffer, buffer::Upstream::Server(_)))
+            .map(|buffer| buffer.server().clone())
+        else {
+            return;
+        };
+
+        if self.collapsed_servers.expand(&server) {
+            self.last_changed = Some(Instant::now());
+        }
+    }
+
+    /// Recently focused buffers which can still be opened, most recent first.
+    fn recent_buffers(&self, clients: &client::Map) -> Vec<data::Buffer> {
+        let all_buffers =
+            all_buffers(clients, &self.history, &self.sidebar_order);
+
+        self.recent_buffers
+            .iter()
+            .filter(|buffer| match buffer {
+                data::Buffer::Upstream(upstream) => {
+                    all_buffers.contains(upstream)
+                }
+                data::Buffer::Internal(_) => true,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Focuses the pane showing `buffer`, or opens it the same way as
+    /// selecting it in the sidebar.
+    fn focus_buffer(
+        &mut self,
+        buffer: data::Buffer,
+        config: &Config,
+    ) -> Task<Message> {
+        if let Some((window, pane, _)) = self.panes.get_mut_by_buffer(&buffer)
+        {
+            if window == self.focus.window {
+                self.focus_pane(window, pane)
+            } else {
+                window::gain_focus(window).chain(self.focus_pane(window, pane))
+            }
+        } else {
+            self.open_buffer(buffer, config.actions.sidebar.buffer, config)
+        }
+    }
+
     fn focus_first_pane(&mut self, window: window::Id) -> Task<Message> {
         let pane = self
             .panes
@@ -2212,6 +4008,21 @@ impl Dashboard {
         window::gain_focus(window).chain(task)
     }
 
+    /// Marks where the main window lost focus in each of its buffers
+    pub fn mark_focus_lost(&mut self) {
+        for (_, state) in self.panes.main.iter_mut() {
+            state.buffer.mark_focus_lost();
+        }
+    }
+
+    /// Shows the focus markers of the main window's buffers, once it regains
+    /// focus
+    pub fn show_focus_markers(&mut self) {
+        for (_, state) in self.panes.main.iter_mut() {
+            state.buffer.show_focus_marker();
+        }
+    }
+
     fn maximize_pane(&mut self) {
         if self.is_pane_maximized() {
             self.panes.main.restore();
@@ -2227,46 +4038,128 @@ impl Dashboard {
     fn new_pane(&mut self, axis: pane_grid::Axis) -> Task<Message> {
         if self.focus.window == self.main_window() {
             // If there is any focused pane on main window, split it
-            return self.split_pane(axis);
+            let Focus { window, pane } = self.focus;
+            return self.split_pane(window, pane, axis);
         } else {
             // If there is no focused pane, split the last pane or create a new empty grid
             let pane =
                 self.panes.main.iter().last().map(|(pane, _)| pane).copied();
 
-            if let Some(pane) = pane {
-                let result =
-                    self.panes.main.split(axis, pane, Pane::new(Buffer::Empty));
-                self.last_changed = Some(Instant::now());
+            if let Some(pane) = pane {
+                let result =
+                    self.panes.main.split(axis, pane, Pane::new(Buffer::Empty));
+                self.last_changed = Some(Instant::now());
+
+                if let Some((pane, _)) = result {
+                    return self.focus_pane(self.main_window(), pane);
+                }
+            } else {
+                let (state, pane) =
+                    pane_grid::State::new(Pane::new(Buffer::Empty));
+                self.panes.main = state;
+                self.last_changed = Some(Instant::now());
+                return self.focus_pane(self.main_window(), pane);
+            }
+        }
+
+        Task::none()
+    }
+
+    /// Splits `pane` along `axis`, focusing the new empty pane. Only panes
+    /// of the main window can be split.
+    fn split_pane(
+        &mut self,
+        window: window::Id,
+        pane: pane_grid::Pane,
+        axis: pane_grid::Axis,
+    ) -> Task<Message> {
+        if window == self.main_window() {
+            // Restore first, so the new pane isn't hidden behind the
+            // maximized one
+            self.panes.main.restore();
+
+            let result =
+                self.panes.main.split(axis, pane, Pane::new(Buffer::Empty));
+            self.last_changed = Some(Instant::now());
+            if let Some((pane, _)) = result {
+                return self.focus_pane(self.main_window(), pane);
+            }
+        }
+
+        Task::none()
+    }
+
+    /// Saves the panes of the main window as a named layout, or replaces them
+    /// with one. Buffers of servers which are no longer configured are left
+    /// out of a loaded layout.
+    fn layout(
+        &mut self,
+        layout: command::Layout,
+        config: &Config,
+    ) -> Task<Message> {
+        self.last_changed = Some(Instant::now());
+
+        match layout {
+            command::Layout::Save(name) => {
+                let pane = from_layout(
+                    &self.panes.main,
+                    self.panes.main.layout().clone(),
+                );
+
+                self.layouts.save(name, pane);
+
+                Task::none()
+            }
+            command::Layout::Load(name) => {
+                let Some(pane) = self.layouts.get(&name).cloned() else {
+                    notification::toast(
+                        "Couldn't load layout",
+                        format!("There's no layout named {name}"),
+                    );
+                    return Task::none();
+                };
+
+                let mut skipped = vec![];
+                let pane = pane.skip_servers(
+                    &|server| config.servers.contains(server),
+                    &mut skipped,
+                );
+
+                if !skipped.is_empty() {
+                    notification::toast(
+                        &format!("Loaded layout {name}"),
+                        format!(
+                            "Skipped buffers of unknown servers: {}",
+                            skipped
+                                .iter()
+                                .map(ToString::to_string)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    );
+                }
+
+                self.panes.main =
+                    pane_grid::State::with_configuration(configuration(pane));
+                self.focus_history.clear();
 
-                if let Some((pane, _)) = result {
-                    return self.focus_pane(self.main_window(), pane);
+                if self.focus.window == self.main_window() {
+                    self.focus_first_pane(self.main_window())
+                } else {
+                    Task::none()
                 }
-            } else {
-                let (state, pane) =
-                    pane_grid::State::new(Pane::new(Buffer::Empty));
-                self.panes.main = state;
-                self.last_changed = Some(Instant::now());
-                return self.focus_pane(self.main_window(), pane);
             }
-        }
-
-        Task::none()
-    }
+            command::Layout::Delete(name) => {
+                if !self.layouts.delete(&name) {
+                    notification::toast(
+                        "Couldn't delete layout",
+                        format!("There's no layout named {name}"),
+                    );
+                }
 
-    fn split_pane(&mut self, axis: pane_grid::Axis) -> Task<Message> {
-        if self.focus.window == self.main_window() {
-            let result = self.panes.main.split(
-                axis,
-                self.focus.pane,
-                Pane::new(Buffer::Empty),
-            );
-            self.last_changed = Some(Instant::now());
-            if let Some((pane, _)) = result {
-                return self.focus_pane(self.main_window(), pane);
+                Task::none()
             }
         }
-
-        Task::none()
     }
 
     fn reset_pane(
@@ -2298,10 +4191,19 @@ impl Dashboard {
 
             if let Some((_, sibling)) = self.panes.main.close(pane) {
                 if (Focus { window, pane } == self.focus) {
-                    return self.focus_pane(self.main_window(), sibling);
+                    // The pane focused before it, if it's still open
+                    let next = self
+                        .focus_history
+                        .front()
+                        .copied()
+                        .filter(|pane| self.panes.main.get(*pane).is_some())
+                        .unwrap_or(sibling);
+
+                    return self.focus_pane(self.main_window(), next);
                 }
             } else if let Some(pane) = self.panes.main.get_mut(pane) {
                 pane.buffer = Buffer::Empty;
+                pane.pinned = false;
             }
         } else if self.panes.popout.remove(&window).is_some() {
             return window::close(window)
@@ -2311,7 +4213,7 @@ impl Dashboard {
         Task::none()
     }
 
-    fn popout_pane(&mut self, config: &Config) -> Task<Message> {
+    fn popout_pane(&mut self) -> Task<Message> {
         let Focus { pane, .. } = self.focus;
 
         self.focus_history = self
@@ -2323,11 +4225,12 @@ impl Dashboard {
 
         if let Some((pane, _)) = self.panes.main.close(pane) {
             if let Some(buffer) = pane.buffer.data() {
-                return self.open_buffer(
-                    buffer,
-                    BufferAction::NewWindow,
-                    config,
-                );
+                self.last_changed = Some(Instant::now());
+
+                let mut popout = Pane::new(Buffer::from(buffer));
+                popout.pinned = pane.pinned;
+
+                return self.open_popout(popout);
             }
         }
 
@@ -2345,7 +4248,19 @@ impl Dashboard {
         {
             let task = match pane.buffer.data() {
                 Some(buffer) => {
-                    self.open_buffer(buffer, BufferAction::NewPane, config)
+                    let task = self.open_buffer(
+                        buffer.clone(),
+                        BufferAction::NewPane,
+                        config,
+                    );
+
+                    if let Some((_, _, state)) =
+                        self.panes.get_mut_by_buffer(&buffer)
+                    {
+                        state.pinned = pane.pinned;
+                    }
+
+                    task
                 }
                 None => self.new_pane(pane_grid::Axis::Horizontal),
             };
@@ -2408,12 +4323,59 @@ impl Dashboard {
         )
     }
 
-    pub fn tick(&mut self, now: Instant) -> Task<Message> {
+    pub fn tick(
+        &mut self,
+        now: Instant,
+        clients: &mut client::Map,
+        config: &Config,
+    ) -> Task<Message> {
+        if let Some(debug_overlay) = &mut self.debug_overlay {
+            debug_overlay.refresh(
+                &self.history,
+                &self.previews,
+                &self.scheduler,
+            );
+        }
+
+        // Only once after startup, reconnects are reported in their buffers
+        if self.connection_report_at.is_some_and(|at| now >= at) {
+            let report =
+                ConnectionReport::new(clients, &self.connection_errors);
+
+            self.connection_report_at = None;
+            self.connection_report =
+                Some(report).filter(|report| !report.is_empty());
+        }
+
+        let scheduled = self.send_scheduled(clients, config);
+
+        self.sync_focused_metadata(clients);
+        self.load_avatars(clients, config);
+
+        self.requests
+            .finish(&mut self.scheduler, |request| request.pending(clients, now));
+
+        for (server, channel) in clients.who_polls_due(now) {
+            if self.queued_who_polls.insert((server.clone(), channel.clone()))
+            {
+                self.scheduler.submit(
+                    scheduler::Category::Who,
+                    Task::done(Message::SendWhoPoll(server, channel)),
+                );
+            }
+        }
+
+        // Picks up a reloaded concurrency
+        self.scheduler
+            .set_concurrency(config.background.concurrency);
+        let background = self.run_scheduled();
+
         let history = Task::batch(
             self.history
                 .tick(now.into())
                 .into_iter()
                 .map(|task| Task::perform(task, Message::History))
+                .chain([scheduled, background])
                 .collect::<Vec<_>>(),
         );
 
@@ -2433,6 +4395,170 @@ impl Dashboard {
         history
     }
 
+    /// Syncs the avatars and display names of the focused channel or query,
+    /// on servers sharing them
+    fn sync_focused_metadata(&self, clients: &mut client::Map) {
+        let Some(buffer) = self
+            .get_focused()
+            .and_then(|(_, _, pane)| pane.buffer.upstream())
+        else {
+            return;
+        };
+
+        if let Some(target) = buffer.target() {
+            clients.sync_metadata(buffer.server(), &target);
+        }
+    }
+
+    /// Loads the avatars which are shown, forgetting those which aren't, so
+    /// changed avatars are loaded anew
+    fn load_avatars(&mut self, clients: &client::Map, config: &Config) {
+        let shown = self.shown_avatars(clients, config);
+
+        self.avatars.retain(|url, _| shown.contains(url));
+
+        let missing = shown
+            .into_iter()
+            .filter(|url| !self.avatars.contains_key(url))
+            .collect::<Vec<_>>();
+
+        for url in missing {
+            self.avatars.insert(url.clone(), preview::State::Loading);
+            self.scheduler.submit(
+                scheduler::Category::Avatar,
+                Task::perform(
+                    data::preview::load_image(
+                        url.clone(),
+                        config.preview.clone(),
+                    ),
+                    move |result| Message::LoadAvatar((url.clone(), result)),
+                ),
+            );
+        }
+    }
+
+    fn shown_avatars(
+        &self,
+        clients: &client::Map,
+        config: &Config,
+    ) -> HashSet<url::Url> {
+        let buffers = self
+            .panes
+            .iter()
+            .filter_map(|(_, _, pane)| pane.buffer.upstream());
+
+        match config.buffer.nickname.avatars {
+            data::buffer::Avatars::Hidden => HashSet::new(),
+            data::buffer::Avatars::Query => buffers
+                .filter_map(|buffer| match buffer {
+                    buffer::Upstream::Query(server, query) => clients
+                        .get_user_profile(server, query.as_str())?
+                        .avatar
+                        .clone(),
+                    _ => None,
+                })
+                .collect(),
+            data::buffer::Avatars::Messages => buffers
+                .filter(|buffer| buffer.target().is_some())
+                .map(buffer::Upstream::server)
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .filter_map(|server| clients.get_metadata(server))
+                .flat_map(|metadata| metadata.avatars().cloned())
+                .collect(),
+        }
+    }
+
+    /// Sends the scheduled messages which are due, notifying of those which
+    /// came due while their server was disconnected
+    fn send_scheduled(
+        &mut self,
+        clients: &mut client::Map,
+        config: &Config,
+    ) -> Task<Message> {
+        let connected =
+            clients.connected_servers().cloned().collect::<HashSet<_>>();
+
+        let due = self.history.take_due_scheduled(
+            Utc::now(),
+            |server| connected.contains(server),
+            config.buffer.commands.schedule.when_disconnected,
+        );
+
+        let disconnected = due
+            .held
+            .iter()
+            .map(|pending| (pending, false))
+            .chain(due.dropped.iter().map(|pending| (pending, true)));
+
+        for (pending, dropped) in disconnected {
+            self.notifications.notify(
+                config,
+                &Notification::ScheduledMessage {
+                    target: pending
+                        .buffer
+                        .target()
+                        .map(|target| target.to_string())
+                        .unwrap_or_default(),
+                    dropped,
+                },
+                pending.buffer.server(),
+                None,
+            );
+        }
+
+        let mut tasks = vec![];
+
+        for pending in due.send {
+            let buffer = &pending.buffer;
+
+            match input::parse(
+                buffer.clone(),
+                config.buffer.text_input.auto_format,
+                config.buffer.text_input.markdown_links,
+                &pending.text,
+                &clients.get_isupport(buffer.server()),
+            ) {
+                Ok(input::Parsed::Input(input)) => {
+                    tasks.push(
+                        buffer::input_view::State::send_input(
+                            buffer,
+                            input,
+                            clients,
+                            &mut self.history,
+                            config,
+                        )
+                        .map(Message::History),
+                    );
+                }
+                Ok(input::Parsed::Internal(_)) => {
+                    log::warn!(
+                        "scheduled message {} isn't a message",
+                        pending.id
+                    );
+                }
+                Err(error) => {
+                    log::warn!(
+                        "failed to send scheduled message {}: {error}",
+                        pending.id
+                    );
+                }
+            }
+        }
+
+        Task::batch(tasks)
+    }
+
+    fn toggle_debug_overlay(&mut self) {
+        if self.debug_overlay.take().is_none() {
+            self.debug_overlay = Some(DebugOverlay::new(
+                &self.history,
+                &self.previews,
+                &self.scheduler,
+            ));
+        }
+    }
+
     pub fn toggle_command_bar(
         &mut self,
         buffers: &[buffer::Upstream],
@@ -2497,7 +4623,7 @@ impl Dashboard {
             .receive(request.clone(), config.proxy.as_ref())?;
 
         self.notifications.notify(
-            &config.notifications,
+            config,
             &Notification::FileTransferRequest {
                 nick: request.from.clone(),
                 filename: match event {
@@ -2508,6 +4634,7 @@ impl Dashboard {
                 },
             },
             server,
+            None,
         );
 
         let query = target::Query::parse(
@@ -2521,6 +4648,15 @@ impl Dashboard {
         Some(self.handle_file_transfer_event(server, &query, event))
     }
 
+    pub fn resume_file_transfer(
+        &mut self,
+        server: &Server,
+        from: &Nick,
+        accept: dcc::Resume,
+    ) {
+        self.file_transfers.resume_accepted(server, from, accept);
+    }
+
     pub fn handle_file_transfer_event(
         &mut self,
         server: &Server,
@@ -2566,34 +4702,6 @@ impl Dashboard {
         config: &Config,
         main_window: &Window,
     ) -> (Self, Task<Message>) {
-        use pane_grid::Configuration;
-
-        fn configuration(pane: data::Pane) -> Configuration<Pane> {
-            match pane {
-                data::Pane::Split { axis, ratio, a, b } => {
-                    Configuration::Split {
-                        axis: match axis {
-                            data::pane::Axis::Horizontal => {
-                                pane_grid::Axis::Horizontal
-                            }
-                            data::pane::Axis::Vertical => {
-                                pane_grid::Axis::Vertical
-                            }
-                        },
-                        ratio,
-                        a: Box::new(configuration(*a)),
-                        b: Box::new(configuration(*b)),
-                    }
-                }
-                data::Pane::Buffer { buffer } => {
-                    Configuration::Pane(Pane::new(Buffer::from(buffer)))
-                }
-                data::Pane::Empty => {
-                    Configuration::Pane(Pane::new(Buffer::empty()))
-                }
-            }
-        }
-
         let panes = Panes {
             main_window: main_window.id,
             main: pane_grid::State::with_configuration(configuration(
@@ -2623,33 +4731,51 @@ impl Dashboard {
             panes,
             focus,
             focus_history: VecDeque::from([focus.pane]),
+            recent_buffers: VecDeque::new(),
+            buffer_switcher: None,
             side_menu: Sidebar::new(),
-            history: history::Manager::default(),
+            history: history::Manager::new(),
             last_changed: None,
             command_bar: None,
             file_transfers: file_transfer::Manager::new(
                 config.file_transfer.clone(),
             ),
             theme_editor: None,
+            theme_picker: None,
+            audit_log: None,
+            history_search: None,
+            quick_switcher: None,
             notifications: notification::Notifications::new(),
             previews: preview::Collection::default(),
+            avatars: preview::Collection::default(),
+            translations: translation::Collection::default(),
             buffer_settings: data.buffer_settings.clone(),
+            highlights: data.highlights.clone(),
+            sidebar_order: data.sidebar_order.clone(),
+            collapsed_servers: data.collapsed_servers.clone(),
+            layouts: data.layouts.clone(),
+            scheduler: Scheduler::new(config.background.concurrency),
+            requests: scheduler::Requests::default(),
+            queued_who_polls: HashSet::new(),
+            debug_overlay: None,
+            connection_report: None,
+            connection_report_at: Some(
+                Instant::now() + CONNECTION_REPORT_AFTER,
+            ),
+            connection_errors: HashMap::new(),
         };
 
         let mut tasks = vec![];
 
         for pane in data.popout_panes {
             // Popouts are only a single pane
-            let Configuration::Pane(pane) = configuration(pane) else {
+            let pane_grid::Configuration::Pane(pane) = configuration(pane)
+            else {
                 continue;
             };
 
-            if let Some(buffer) = pane.buffer.data() {
-                tasks.push(dashboard.open_buffer(
-                    buffer,
-                    BufferAction::NewWindow,
-                    config,
-                ));
+            if pane.buffer.data().is_some() {
+                tasks.push(dashboard.open_popout(pane));
             }
         }
 
@@ -2830,33 +4956,6 @@ pub struct Focus {
 
 impl<'a> From<&'a Dashboard> for data::Dashboard {
     fn from(dashboard: &'a Dashboard) -> Self {
-        use pane_grid::Node;
-
-        fn from_layout(
-            panes: &pane_grid::State<Pane>,
-            node: pane_grid::Node,
-        ) -> data::Pane {
-            match node {
-                Node::Split {
-                    axis, ratio, a, b, ..
-                } => data::Pane::Split {
-                    axis: match axis {
-                        pane_grid::Axis::Horizontal => {
-                            data::pane::Axis::Horizontal
-                        }
-                        pane_grid::Axis::Vertical => data::pane::Axis::Vertical,
-                    },
-                    ratio,
-                    a: Box::new(from_layout(panes, *a)),
-                    b: Box::new(from_layout(panes, *b)),
-                },
-                Node::Pane(pane) => panes
-                    .get(pane)
-                    .cloned()
-                    .map_or(data::Pane::Empty, data::Pane::from),
-            }
-        }
-
         let layout = dashboard.panes.main.layout().clone();
         let focus = dashboard.focus;
 
@@ -2869,6 +4968,10 @@ impl<'a> From<&'a Dashboard> for data::Dashboard {
                 .map(|state| from_layout(state, state.layout().clone()))
                 .collect(),
             buffer_settings: dashboard.buffer_settings.clone(),
+            highlights: dashboard.highlights.clone(),
+            sidebar_order: dashboard.sidebar_order.clone(),
+            collapsed_servers: dashboard.collapsed_servers.clone(),
+            layouts: dashboard.layouts.clone(),
             focus_buffer: dashboard.panes.iter().find_map(|(w, p, state)| {
                 (w == focus.window && p == focus.pane)
                     .then_some(state.buffer.data())
@@ -2912,6 +5015,41 @@ impl Panes {
         }
     }
 
+    /// Panes of the main window in layout order, followed by popout panes
+    /// in the order their windows were opened.
+    fn ordered(&self) -> Vec<(window::Id, pane_grid::Pane)> {
+        fn collect(node: &pane_grid::Node, panes: &mut Vec<pane_grid::Pane>) {
+            match node {
+                pane_grid::Node::Split { a, b, .. } => {
+                    collect(a, panes);
+                    collect(b, panes);
+                }
+                pane_grid::Node::Pane(pane) => panes.push(*pane),
+            }
+        }
+
+        let mut main = vec![];
+        collect(self.main.layout(), &mut main);
+
+        // Window ids increase as windows are opened
+        let mut popouts = self.popout.iter().collect::<Vec<_>>();
+        popouts.sort_by_key(|(window, _)| **window);
+
+        main.into_iter()
+            .map(|pane| (self.main_window, pane))
+            .chain(popouts.into_iter().flat_map(|(window, state)| {
+                let mut panes = vec![];
+                collect(state.layout(), &mut panes);
+
+                panes.into_iter().map(|pane| (*window, pane))
+            }))
+            .collect()
+    }
+
+    fn is_pinned(&self, window: window::Id, pane: pane_grid::Pane) -> bool {
+        self.get(window, pane).is_some_and(|state| state.pinned)
+    }
+
     fn get_mut_by_buffer(
         &mut self,
         buffer: &data::Buffer,
@@ -2968,57 +5106,154 @@ impl Panes {
     }
 }
 
+/// Buffer of a channel or query message `target`
+fn configuration(pane: data::Pane) -> pane_grid::Configuration<Pane> {
+    use pane_grid::Configuration;
+
+    match pane {
+        data::Pane::Split { axis, ratio, a, b } => Configuration::Split {
+            axis: match axis {
+                data::pane::Axis::Horizontal => pane_grid::Axis::Horizontal,
+                data::pane::Axis::Vertical => pane_grid::Axis::Vertical,
+            },
+            ratio,
+            a: Box::new(configuration(*a)),
+            b: Box::new(configuration(*b)),
+        },
+        data::Pane::Buffer { buffer, pinned } => {
+            let mut pane = Pane::new(Buffer::from(buffer));
+            pane.pinned = pinned;
+
+            Configuration::Pane(pane)
+        }
+        data::Pane::Empty => Configuration::Pane(Pane::new(Buffer::empty())),
+    }
+}
+
+fn from_layout(
+    panes: &pane_grid::State<Pane>,
+    node: pane_grid::Node,
+) -> data::Pane {
+    use pane_grid::Node;
+
+    match node {
+        Node::Split {
+            axis, ratio, a, b, ..
+        } => data::Pane::Split {
+            axis: match axis {
+                pane_grid::Axis::Horizontal => data::pane::Axis::Horizontal,
+                pane_grid::Axis::Vertical => data::pane::Axis::Vertical,
+            },
+            ratio,
+            a: Box::new(from_layout(panes, *a)),
+            b: Box::new(from_layout(panes, *b)),
+        },
+        Node::Pane(pane) => panes
+            .get(pane)
+            .cloned()
+            .map_or(data::Pane::Empty, data::Pane::from),
+    }
+}
+
+fn upstream_buffer(
+    server: &Server,
+    target: &data::message::Target,
+) -> Option<buffer::Upstream> {
+    match target {
+        data::message::Target::Channel { channel, .. } => {
+            Some(buffer::Upstream::Channel(server.clone(), channel.clone()))
+        }
+        data::message::Target::Query { query, .. } => {
+            Some(buffer::Upstream::Query(server.clone(), query.clone()))
+        }
+        _ => None,
+    }
+}
+
+/// The last `count` topics set in an audit log, oldest first
+fn last_topics(entries: Vec<audit::Entry>, count: usize) -> Vec<audit::Entry> {
+    let mut topics = entries
+        .into_iter()
+        .filter(|entry| entry.change.category() == audit::Category::Topic)
+        .collect::<Vec<_>>();
+
+    topics.drain(..topics.len().saturating_sub(count));
+
+    topics
+}
+
+/// Loads the theme `name`, which may have been typed in another case than
+/// the theme's
+fn load_theme(name: String, config: &Config) -> Task<Message> {
+    let name = config
+        .appearance
+        .all
+        .iter()
+        .find(|theme| theme.name.eq_ignore_ascii_case(&name))
+        .map_or(name, |theme| theme.name.clone());
+
+    Task::perform(Config::load_theme(name), Message::ThemeLoaded)
+}
+
 fn all_buffers(
     clients: &client::Map,
     history: &history::Manager,
+    sidebar_order: &dashboard::SidebarOrder,
 ) -> Vec<buffer::Upstream> {
     clients
         .connected_servers()
         .flat_map(|server| {
-            std::iter::once(buffer::Upstream::Server(server.clone()))
-                .chain(clients.get_channels(server).iter().map(|channel| {
+            let mut buffers = clients
+                .get_channels(server)
+                .iter()
+                .map(|channel| {
                     buffer::Upstream::Channel(server.clone(), channel.clone())
-                }))
+                })
                 .chain(history.get_unique_queries(server).into_iter().map(
                     |nick| {
                         buffer::Upstream::Query(server.clone(), nick.clone())
                     },
                 ))
+                .collect::<Vec<_>>();
+
+            sidebar_order.sort(server, &mut buffers);
+
+            std::iter::once(buffer::Upstream::Server(server.clone()))
+                .chain(buffers)
         })
         .collect()
 }
 
-fn all_buffers_with_has_unread(
+/// Messages of a buffer since it was last read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Activity {
+    None,
+    Unread,
+    Highlight,
+}
+
+fn all_buffers_with_activity(
     clients: &client::Map,
     history: &history::Manager,
-) -> Vec<(buffer::Upstream, bool)> {
-    clients
-        .connected_servers()
-        .flat_map(|server| {
-            std::iter::once((
-                buffer::Upstream::Server(server.clone()),
-                history.has_unread(&history::Kind::Server(server.clone())),
-            ))
-            .chain(clients.get_channels(server).iter().map(|channel| {
-                (
-                    buffer::Upstream::Channel(server.clone(), channel.clone()),
-                    history.has_unread(&history::Kind::Channel(
-                        server.clone(),
-                        channel.clone(),
-                    )),
-                )
-            }))
-            .chain(
-                history.get_unique_queries(server).into_iter().map(|nick| {
-                    (
-                        buffer::Upstream::Query(server.clone(), nick.clone()),
-                        history.has_unread(&history::Kind::Query(
-                            server.clone(),
-                            nick.clone(),
-                        )),
-                    )
-                }),
-            )
+    sidebar_order: &dashboard::SidebarOrder,
+) -> Vec<(buffer::Upstream, Activity)> {
+    all_buffers(clients, history, sidebar_order)
+        .into_iter()
+        .map(|buffer| {
+            let activity = match history::Kind::from_buffer(
+                data::Buffer::Upstream(buffer.clone()),
+            ) {
+                Some(kind) if history.has_unread(&kind) => {
+                    if history.unread(&kind).highlights > 0 {
+                        Activity::Highlight
+                    } else {
+                        Activity::Unread
+                    }
+                }
+                _ => Activity::None,
+            };
+
+            (buffer, activity)
         })
         .collect()
 }
@@ -3038,7 +5273,7 @@ fn closed_buffers(
 ) -> Vec<buffer::Upstream> {
     let open_buffers = open_buffers(dashboard);
 
-    all_buffers(clients, &dashboard.history)
+    all_buffers(clients, &dashboard.history, &dashboard.sidebar_order)
         .into_iter()
         .filter(|buffer| !open_buffers.contains(buffer))
         .collect()
@@ -3079,60 +5314,160 @@ fn cycle_previous_buffer(
 
 fn cycle_next_unread_buffer(
     current: Option<&buffer::Upstream>,
-    mut all: Vec<(buffer::Upstream, bool)>,
+    mut all: Vec<(buffer::Upstream, Activity)>,
     opened: &[buffer::Upstream],
 ) -> Option<buffer::Upstream> {
     all.retain(|(buffer, _)| {
         Some(buffer) == current || !opened.contains(buffer)
     });
 
-    let buffer = current?;
-
-    let index = all.iter().position(|(b, _)| b == buffer)?;
-
-    let next_after = || {
-        all.iter()
-            .skip(index + 1)
-            .find_map(|(b, has_unread)| has_unread.then_some(b))
-    };
-
-    let next_before = || {
-        all.iter()
-            .take(index)
-            .find_map(|(b, has_unread)| has_unread.then_some(b))
-    };
-
-    next_after().or_else(|| next_before().or(current)).cloned()
+    most_active_after(current, &all)
 }
 
 fn cycle_previous_unread_buffer(
     current: Option<&buffer::Upstream>,
-    mut all: Vec<(buffer::Upstream, bool)>,
+    mut all: Vec<(buffer::Upstream, Activity)>,
     opened: &[buffer::Upstream],
 ) -> Option<buffer::Upstream> {
     all.retain(|(buffer, _)| {
         Some(buffer) == current || !opened.contains(buffer)
     });
+    all.reverse();
 
-    let buffer = current?;
+    most_active_after(current, &all)
+}
 
-    let index = all.iter().rev().position(|(b, _)| b == buffer)?;
+/// First buffer after `current` with the most activity, wrapping around, so
+/// highlights are shown before other unread messages. `None` when no other
+/// buffer has any.
+fn most_active_after(
+    current: Option<&buffer::Upstream>,
+    all: &[(buffer::Upstream, Activity)],
+) -> Option<buffer::Upstream> {
+    let start = current
+        .and_then(|current| all.iter().position(|(b, _)| b == current))
+        .map_or(0, |index| index + 1);
 
-    let previous_before = || {
+    let others = || {
         all.iter()
-            .rev()
-            .skip(index + 1)
-            .find_map(|(b, has_unread)| has_unread.then_some(b))
+            .cycle()
+            .skip(start)
+            .take(all.len())
+            .filter(move |(buffer, _)| Some(buffer) != current)
     };
 
-    let previous_after = || {
-        all.iter()
-            .rev()
-            .take(index)
-            .find_map(|(b, has_unread)| has_unread.then_some(b))
-    };
+    let most = others()
+        .map(|(_, activity)| *activity)
+        .max()
+        .filter(|activity| *activity > Activity::None)?;
 
-    previous_before()
-        .or_else(|| previous_after().or(current))
-        .cloned()
+    others()
+        .find(|(_, activity)| *activity == most)
+        .map(|(buffer, _)| buffer.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use data::isupport::CaseMap;
+
+    use super::*;
+
+    fn channel(name: &str) -> buffer::Upstream {
+        buffer::Upstream::Channel(
+            Server::from("libera"),
+            target::Channel::from_str(name, CaseMap::default()),
+        )
+    }
+
+    fn buffers(
+        activities: &[(&str, Activity)],
+    ) -> Vec<(buffer::Upstream, Activity)> {
+        activities
+            .iter()
+            .map(|(name, activity)| (channel(name), *activity))
+            .collect()
+    }
+
+    #[test]
+    fn unread_highlights_first() {
+        let all = buffers(&[
+            ("#a", Activity::None),
+            ("#b", Activity::Unread),
+            ("#c", Activity::Highlight),
+            ("#d", Activity::Unread),
+        ]);
+        let next = |current: &str| {
+            cycle_next_unread_buffer(Some(&channel(current)), all.clone(), &[])
+        };
+
+        assert_eq!(next("#a"), Some(channel("#c")));
+        // The current buffer isn't shown again, even with a highlight
+        assert_eq!(next("#c"), Some(channel("#d")));
+    }
+
+    #[test]
+    fn unread_in_sidebar_order() {
+        let all = buffers(&[
+            ("#a", Activity::Unread),
+            ("#b", Activity::None),
+            ("#c", Activity::Unread),
+            ("#d", Activity::Unread),
+        ]);
+
+        assert_eq!(
+            cycle_next_unread_buffer(Some(&channel("#a")), all.clone(), &[]),
+            Some(channel("#c"))
+        );
+        assert_eq!(
+            cycle_previous_unread_buffer(
+                Some(&channel("#d")),
+                all.clone(),
+                &[]
+            ),
+            Some(channel("#c"))
+        );
+        // From a pane without a buffer, the first one in the sidebar
+        assert_eq!(
+            cycle_next_unread_buffer(None, all.clone(), &[]),
+            Some(channel("#a"))
+        );
+        // Buffers open in other panes are skipped
+        assert_eq!(
+            cycle_next_unread_buffer(
+                Some(&channel("#a")),
+                all,
+                &[channel("#c")]
+            ),
+            Some(channel("#d"))
+        );
+    }
+
+    #[test]
+    fn unread_wraps_around() {
+        let all = buffers(&[
+            ("#a", Activity::Unread),
+            ("#b", Activity::None),
+            ("#c", Activity::Unread),
+        ]);
+
+        assert_eq!(
+            cycle_next_unread_buffer(Some(&channel("#c")), all.clone(), &[]),
+            Some(channel("#a"))
+        );
+        assert_eq!(
+            cycle_previous_unread_buffer(
+                Some(&channel("#a")),
+                all.clone(),
+                &[]
+            ),
+            Some(channel("#c"))
+        );
+
+        // Nothing else is unread
+        let all = buffers(&[("#a", Activity::Unread), ("#b", Activity::None)]);
+        assert_eq!(
+            cycle_next_unread_buffer(Some(&channel("#a")), all, &[]),
+            None
+        );
+    }
 }
@@ -0,0 +1,155 @@
+use chrono::Local;
+use data::history::audit::{Category, Entry};
+use data::{Server, target};
+use iced::widget::{
+    Column, Row, button, column, container, row, scrollable, text,
+};
+use iced::{Length, padding};
+
+use crate::widget::Element;
+use crate::{icon, theme};
+
+/// Topic changes, mode changes, kicks and bans recorded for a channel,
+/// opened from the channel's menu in the sidebar
+#[derive(Debug)]
+pub struct AuditLog {
+    server: Server,
+    channel: target::Channel,
+    entries: Vec<Entry>,
+    filter: Option<Category>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Filter(Option<Category>),
+    Close,
+}
+
+pub enum Event {
+    Closed,
+}
+
+impl AuditLog {
+    pub fn new(
+        server: Server,
+        channel: target::Channel,
+        entries: Vec<Entry>,
+    ) -> Self {
+        Self {
+            server,
+            channel,
+            entries,
+            filter: None,
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Option<Event> {
+        match message {
+            Message::Filter(filter) => {
+                self.filter = filter;
+                None
+            }
+            Message::Close => Some(Event::Closed),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let filters = Row::with_children(
+            std::iter::once(filter_button(
+                "All".to_string(),
+                None,
+                self.filter,
+            ))
+            .chain(Category::ALL.iter().map(|category| {
+                filter_button(
+                    category.to_string(),
+                    Some(*category),
+                    self.filter,
+                )
+            })),
+        )
+        .spacing(4);
+
+        // Newest first
+        let entries = self
+            .entries
+            .iter()
+            .rev()
+            .filter(|entry| {
+                self.filter
+                    .is_none_or(|category| entry.change.category() == category)
+            })
+            .map(|entry| {
+                let time = entry
+                    .server_time
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string();
+                let change = match &entry.actor {
+                    Some(actor) => format!("{actor} {}", entry.change),
+                    None => entry.change.to_string(),
+                };
+
+                row![
+                    text(time).style(theme::text::secondary),
+                    text(change)
+                        .shaping(text::Shaping::Advanced)
+                        .width(Length::Fill),
+                ]
+                .spacing(8)
+                .into()
+            })
+            .collect::<Vec<Element<'_, Message>>>();
+
+        let entries: Element<'_, Message> = if entries.is_empty() {
+            text("Nothing recorded")
+                .style(theme::text::secondary)
+                .into()
+        } else {
+            container(
+                scrollable(Column::with_children(entries).spacing(4))
+                    .style(theme::scrollable::hidden),
+            )
+            .max_height(360)
+            .into()
+        };
+
+        container(
+            column![
+                row![
+                    text(format!("{} ({})", self.channel, self.server))
+                        .style(theme::text::tertiary)
+                        .width(Length::Fill),
+                    button(icon::cancel())
+                        .padding([2, 4])
+                        .style(theme::button::bare)
+                        .on_press(Message::Close),
+                ]
+                .padding(padding::bottom(4)),
+                filters,
+                entries,
+            ]
+            .spacing(6)
+            .width(Length::Fixed(480.0)),
+        )
+        .padding(8)
+        .style(theme::container::tooltip)
+        .into()
+    }
+}
+
+fn filter_button<'a>(
+    label: String,
+    filter: Option<Category>,
+    current: Option<Category>,
+) -> Element<'a, Message> {
+    let selected = filter == current;
+
+    button(text(label))
+        .padding([2, 8])
+        .style(move |theme, status| {
+            theme::button::secondary(theme, status, selected)
+        })
+        .on_press(Message::Filter(filter))
+        .into()
+}
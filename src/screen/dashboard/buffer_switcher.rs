@@ -0,0 +1,156 @@
+use data::buffer;
+use data::shortcut::KeyBind;
+use iced::widget::{Column, column, container, text};
+use iced::{Length, padding};
+
+use crate::theme;
+use crate::widget::Element;
+
+/// Switches between buffers in most recently used order while the
+/// modifiers of the cycle shortcut are held, or until its key is released
+/// when it has none.
+#[derive(Debug, Clone)]
+pub struct BufferSwitcher {
+    buffers: Vec<data::Buffer>,
+    index: usize,
+    held: bool,
+}
+
+impl BufferSwitcher {
+    /// Starts on the buffer used before the current one, unless cycling
+    /// backwards which starts on the least recently used buffer.
+    pub fn new(
+        buffers: Vec<data::Buffer>,
+        backwards: bool,
+        key_bind: &KeyBind,
+    ) -> Option<Self> {
+        if buffers.len() < 2 {
+            return None;
+        }
+
+        let index = if backwards { buffers.len() - 1 } else { 1 };
+        // Releasing shift alone isn't reported as releasing the modifiers
+        let modifiers = key_bind.modifiers();
+        let held = modifiers.control() || modifiers.alt() || modifiers.logo();
+
+        Some(Self {
+            buffers,
+            index,
+            held,
+        })
+    }
+
+    /// Whether releasing any key selects the buffer, since the shortcut
+    /// which opened the switcher has no modifiers to hold.
+    pub fn selects_on_key_release(&self) -> bool {
+        !self.held
+    }
+
+    pub fn next(&mut self) {
+        self.index = (self.index + 1) % self.buffers.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.index = (self.index + self.buffers.len() - 1) % self.buffers.len();
+    }
+
+    pub fn selected(self) -> data::Buffer {
+        self.buffers
+            .into_iter()
+            .nth(self.index)
+            .expect("index in bounds")
+    }
+
+    pub fn view<'a, Message: 'a>(&'a self) -> Element<'a, Message> {
+        let buffers = Column::with_children(
+            self.buffers.iter().enumerate().map(|(index, buffer)| {
+                let selected = index == self.index;
+
+                container(
+                    text(name(buffer))
+                        .style(if selected {
+                            theme::text::primary
+                        } else {
+                            theme::text::secondary
+                        })
+                        .shaping(text::Shaping::Advanced),
+                )
+                .padding([2, 8])
+                .width(Length::Fill)
+                .style(if selected {
+                    theme::container::primary_background_hover
+                } else {
+                    theme::container::none
+                })
+                .into()
+            }),
+        );
+
+        container(
+            column![
+                container(text("Recent buffers").style(theme::text::tertiary))
+                    .padding(padding::left(8).right(8).bottom(4)),
+                buffers,
+            ]
+            .width(Length::Fixed(300.0)),
+        )
+        .padding(8)
+        .style(theme::container::tooltip)
+        .into()
+    }
+}
+
+pub fn name(buffer: &data::Buffer) -> String {
+    match buffer {
+        data::Buffer::Upstream(buffer::Upstream::Server(server)) => {
+            server.to_string()
+        }
+        data::Buffer::Upstream(buffer::Upstream::Channel(server, channel)) => {
+            format!("{channel} ({server})")
+        }
+        data::Buffer::Upstream(buffer::Upstream::Query(server, nick)) => {
+            format!("{nick} ({server})")
+        }
+        data::Buffer::Internal(internal) => internal.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use data::Server;
+    use iced::keyboard::{self, key};
+
+    use super::*;
+
+    fn buffers() -> Vec<data::Buffer> {
+        ["libera", "oftc"]
+            .into_iter()
+            .map(|server| {
+                data::Buffer::Upstream(buffer::Upstream::Server(Server::from(
+                    server,
+                )))
+            })
+            .collect()
+    }
+
+    fn key_bind(modifiers: keyboard::Modifiers) -> KeyBind {
+        KeyBind::from((keyboard::Key::Named(key::Named::Tab), modifiers))
+    }
+
+    #[test]
+    fn selects_on_key_release() {
+        let without_modifiers = |modifiers| {
+            BufferSwitcher::new(buffers(), false, &key_bind(modifiers))
+                .unwrap()
+                .selects_on_key_release()
+        };
+
+        assert!(without_modifiers(keyboard::Modifiers::empty()));
+        assert!(without_modifiers(keyboard::Modifiers::SHIFT));
+        assert!(!without_modifiers(keyboard::Modifiers::CTRL));
+        assert!(!without_modifiers(
+            keyboard::Modifiers::CTRL | keyboard::Modifiers::SHIFT
+        ));
+        assert!(!without_modifiers(keyboard::Modifiers::ALT));
+    }
+}
@@ -124,6 +124,7 @@ pub enum Command {
     Version(Version),
     Buffer(Buffer),
     Configuration(Configuration),
+    Developer(Developer),
     UI(Ui),
     Theme(Theme),
     Window(Window),
@@ -131,6 +132,7 @@ pub enum Command {
 
 #[derive(Debug, Clone)]
 pub enum Application {
+    CheckConnections,
     Quit,
 }
 
@@ -158,6 +160,15 @@ pub enum Configuration {
     OpenWebsite,
     OpenCacheDirectory,
     OpenDataDirectory,
+    OpenThemesDirectory,
+    OpenLogFile,
+    DuplicateProfile,
+}
+
+#[derive(Debug, Clone)]
+pub enum Developer {
+    OpenRawComposer,
+    ToggleDebugOverlay,
 }
 
 #[derive(Debug, Clone)]
@@ -194,6 +205,9 @@ impl Command {
             .into_iter()
             .map(Command::Configuration);
 
+        let developer =
+            Developer::list(config).into_iter().map(Command::Developer);
+
         let uis = Ui::list().into_iter().map(Command::UI);
 
         let windows = Window::list().into_iter().map(Command::Window);
@@ -212,6 +226,7 @@ impl Command {
             .chain(themes)
             .chain(uis)
             .chain(windows)
+            .chain(developer)
             .collect()
     }
 }
@@ -223,6 +238,9 @@ impl std::fmt::Display for Command {
             Command::Configuration(config) => {
                 write!(f, "Configuration: {config}")
             }
+            Command::Developer(developer) => {
+                write!(f, "Developer: {developer}")
+            }
             Command::UI(ui) => write!(f, "UI: {ui}"),
             Command::Theme(theme) => write!(f, "Theme: {theme}"),
             Command::Version(application) => {
@@ -273,7 +291,7 @@ impl Buffer {
 
 impl Application {
     fn list() -> Vec<Self> {
-        vec![Application::Quit]
+        vec![Application::CheckConnections, Application::Quit]
     }
 }
 
@@ -290,12 +308,31 @@ impl Configuration {
             Configuration::OpenConfigDirectory,
             Configuration::OpenDataDirectory,
             Configuration::OpenCacheDirectory,
+            Configuration::OpenThemesDirectory,
+            Configuration::OpenLogFile,
             Configuration::OpenWebsite,
             Configuration::Reload,
+            Configuration::DuplicateProfile,
         ]
     }
 }
 
+impl Developer {
+    fn list(config: &Config) -> Vec<Self> {
+        let mut list = vec![];
+
+        if config.developer.raw_composer {
+            list.push(Developer::OpenRawComposer);
+        }
+
+        if config.developer.debug_overlay {
+            list.push(Developer::ToggleDebugOverlay);
+        }
+
+        list
+    }
+}
+
 impl Ui {
     fn list() -> Vec<Self> {
         vec![Ui::ToggleSidebarVisibility]
@@ -321,6 +358,7 @@ impl Theme {
 impl std::fmt::Display for Application {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            Application::CheckConnections => write!(f, "Check connections"),
             Application::Quit => write!(f, "Quit"),
         }
     }
@@ -402,9 +440,29 @@ impl std::fmt::Display for Configuration {
             Configuration::OpenDataDirectory => {
                 write!(f, "Open data directory")
             }
+            Configuration::OpenThemesDirectory => {
+                write!(f, "Open themes directory")
+            }
+            Configuration::OpenLogFile => write!(f, "Open log file"),
             Configuration::OpenConfigFile => {
                 write!(f, "Open config file in default editor")
             }
+            Configuration::DuplicateProfile => {
+                write!(f, "Duplicate current profile")
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Developer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Developer::OpenRawComposer => {
+                write!(f, "Open raw message composer")
+            }
+            Developer::ToggleDebugOverlay => {
+                write!(f, "Toggle debug overlay")
+            }
         }
     }
 }
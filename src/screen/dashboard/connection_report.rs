@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use data::client::{self, State};
+use data::{Server, target};
+use iced::widget::{Column, button, column, container, row, text};
+use iced::{Length, padding};
+
+use crate::widget::Element;
+use crate::{icon, theme};
+
+/// Servers and channels from the configuration which weren't connected to
+/// or joined. Checked once startup settles and when asked for, so it isn't
+/// shown again on every reconnect.
+#[derive(Debug)]
+pub struct ConnectionReport {
+    failures: Vec<Failure>,
+}
+
+#[derive(Debug, Clone)]
+enum Failure {
+    Server {
+        server: Server,
+        error: Option<String>,
+    },
+    Channel {
+        server: Server,
+        channel: target::Channel,
+        reason: Option<&'static str>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Retry(usize),
+    Dismiss,
+}
+
+pub enum Event {
+    RetryServer(Server),
+    RetryChannel(Server, target::Channel),
+    Dismissed,
+}
+
+impl ConnectionReport {
+    /// `errors` are the last connection errors of each server
+    pub fn new(
+        clients: &client::Map,
+        errors: &HashMap<Server, String>,
+    ) -> Self {
+        let failures = clients
+            .iter()
+            .flat_map(|(server, state)| match state {
+                // Left disconnected on purpose
+                State::Idle => vec![],
+                State::Disconnected => vec![Failure::Server {
+                    server: server.clone(),
+                    error: errors.get(server).cloned(),
+                }],
+                State::Ready(client) => client
+                    .configured_channels()
+                    .into_iter()
+                    .filter(|channel| !client.channels().contains(channel))
+                    .map(|channel| Failure::Channel {
+                        server: server.clone(),
+                        reason: client.join_failure(&channel),
+                        channel,
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+
+        Self { failures }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    pub fn update(&mut self, message: Message) -> Option<Event> {
+        match message {
+            Message::Retry(index) => {
+                if index >= self.failures.len() {
+                    return None;
+                }
+
+                // Whether it works out is shown in the server or channel
+                Some(match self.failures.remove(index) {
+                    Failure::Server { server, .. } => {
+                        Event::RetryServer(server)
+                    }
+                    Failure::Channel {
+                        server, channel, ..
+                    } => Event::RetryChannel(server, channel),
+                })
+            }
+            Message::Dismiss => Some(Event::Dismissed),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let failures = Column::with_children(
+            self.failures.iter().enumerate().map(|(index, failure)| {
+                row![
+                    text(failure.to_string())
+                        .shaping(text::Shaping::Advanced)
+                        .width(Length::Fill),
+                    button(text("Retry"))
+                        .padding([2, 8])
+                        .style(|theme, status| {
+                            theme::button::secondary(theme, status, false)
+                        })
+                        .on_press(Message::Retry(index)),
+                ]
+                .spacing(8)
+                .into()
+            }),
+        )
+        .spacing(4);
+
+        container(
+            column![
+                row![
+                    if self.failures.is_empty() {
+                        text("Everything is connected and joined")
+                            .style(theme::text::success)
+                    } else {
+                        text("Not connected or joined")
+                            .style(theme::text::error)
+                    }
+                    .width(Length::Fill),
+                    button(icon::cancel())
+                        .padding([2, 4])
+                        .style(theme::button::bare)
+                        .on_press(Message::Dismiss),
+                ]
+                .padding(padding::bottom(4)),
+                failures,
+            ]
+            .spacing(2)
+            .width(Length::Fixed(420.0)),
+        )
+        .padding(8)
+        .style(theme::container::tooltip)
+        .into()
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Failure::Server { server, error } => write!(
+                f,
+                "{server}: {}",
+                error.as_deref().unwrap_or("not connected")
+            ),
+            Failure::Channel {
+                server,
+                channel,
+                reason: Some(reason),
+            } => write!(f, "{server}: {channel} join failed ({reason})"),
+            Failure::Channel {
+                server,
+                channel,
+                reason: None,
+            } => write!(f, "{server}: {channel} not joined"),
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use std::cell::Cell;
+use std::time::Duration;
+
+use bytesize::ByteSize;
+use data::scheduler::{self, Scheduler};
+use data::{history, http, preview};
+use iced::widget::{Column, button, column, container, row, scrollable, text};
+use iced::{Length, padding};
+
+use crate::widget::Element;
+use crate::{theme, watchdog};
+
+/// Shows memory used by loaded histories. Metrics are collected when
+/// refreshed on tick instead of on every frame.
+#[derive(Debug, Default)]
+pub struct DebugOverlay {
+    buffers: Vec<(history::Kind, history::Metrics)>,
+    previews: usize,
+    requests: http::Stats,
+    background: Vec<(scheduler::Category, scheduler::Counter)>,
+    frame_time: Cell<Duration>,
+}
+
+impl DebugOverlay {
+    pub fn new<T>(
+        history: &history::Manager,
+        previews: &preview::Collection,
+        scheduler: &Scheduler<T>,
+    ) -> Self {
+        let mut overlay = Self::default();
+
+        overlay.refresh(history, previews, scheduler);
+
+        overlay
+    }
+
+    pub fn refresh<T>(
+        &mut self,
+        history: &history::Manager,
+        previews: &preview::Collection,
+        scheduler: &Scheduler<T>,
+    ) {
+        let mut buffers = history
+            .metrics()
+            .map(|(kind, metrics)| (kind.clone(), metrics))
+            .collect::<Vec<_>>();
+
+        buffers.sort_by(|(_, a), (_, b)| b.bytes.cmp(&a.bytes));
+
+        self.buffers = buffers;
+        self.previews = previews.len();
+        self.requests = http::stats();
+        self.background = scheduler::Category::ALL
+            .iter()
+            .map(|&category| (category, scheduler.counter(category)))
+            .collect();
+    }
+
+    /// Records how long the last frame took to build, shown on the next
+    /// frame
+    pub fn record_frame_time(&self, frame_time: Duration) {
+        self.frame_time.set(frame_time);
+    }
+
+    /// `stalls` are shown when the watchdog is available
+    pub fn view<'a, Message: Clone + 'a>(
+        &'a self,
+        stalls: Option<watchdog::Stats>,
+        on_trim: Message,
+    ) -> Element<'a, Message> {
+        let total = self
+            .buffers
+            .iter()
+            .fold(history::Metrics::default(), |total, (_, metrics)| {
+                total + *metrics
+            });
+
+        let buffers = Column::with_children(self.buffers.iter().map(
+            |(kind, metrics)| line(kind.to_string(), metrics_text(*metrics)),
+        ));
+
+        let background = Column::with_children(self.background.iter().map(
+            |(category, counter)| {
+                line(format!("{category} tasks"), counter_text(*counter))
+            },
+        ))
+        .spacing(2);
+
+        container(
+            column![
+                container(text("Debug").style(theme::text::tertiary))
+                    .padding(padding::bottom(4)),
+                line("Total history".to_string(), metrics_text(total)),
+                line("Cached previews".to_string(), self.previews.to_string()),
+                line("HTTP requests".to_string(), requests_text(self.requests)),
+                background,
+                line(
+                    "Frame build time".to_string(),
+                    format!(
+                        "{:.2} ms",
+                        self.frame_time.get().as_secs_f64() * 1000.0
+                    ),
+                ),
+            ]
+            .push_maybe(stalls.map(|stalls| {
+                line("Update loop stalls".to_string(), stalls_text(stalls))
+            }))
+            .push_maybe(stalls.and_then(|stalls| stalls.longest).map(|stall| {
+                line("Longest stall".to_string(), stall_text(stall))
+            }))
+            .push_maybe(
+                stalls.and_then(|stalls| stalls.last).map(|stall| {
+                    line("Last stall".to_string(), stall_text(stall))
+                }),
+            )
+            .push(
+                container(scrollable(buffers).style(theme::scrollable::hidden))
+                    .max_height(240)
+                    .padding(padding::top(4).bottom(4)),
+            )
+            .push(
+                button(text("Trim now"))
+                    .padding([2, 8])
+                    .style(|theme, status| {
+                        theme::button::secondary(theme, status, false)
+                    })
+                    .on_press(on_trim),
+            )
+            .spacing(2)
+            .width(Length::Fixed(360.0)),
+        )
+        .padding(8)
+        .style(theme::container::tooltip)
+        .into()
+    }
+}
+
+fn line<'a, Message: 'a>(label: String, value: String) -> Element<'a, Message> {
+    row![
+        text(label)
+            .style(theme::text::secondary)
+            .shaping(text::Shaping::Advanced)
+            .width(Length::Fill),
+        text(value),
+    ]
+    .spacing(8)
+    .into()
+}
+
+fn metrics_text(metrics: history::Metrics) -> String {
+    format!(
+        "{} messages, {}",
+        metrics.messages,
+        ByteSize::b(metrics.bytes as u64)
+    )
+}
+
+fn requests_text(requests: http::Stats) -> String {
+    format!(
+        "{} in flight, {} completed, {} failed",
+        requests.in_flight, requests.completed, requests.failed
+    )
+}
+
+fn counter_text(counter: scheduler::Counter) -> String {
+    format!(
+        "{} queued, {} running, {} completed",
+        counter.queued, counter.running, counter.completed
+    )
+}
+
+fn stalls_text(stalls: watchdog::Stats) -> String {
+    format!("{}, {} ms in total", stalls.count, stalls.total.as_millis())
+}
+
+fn stall_text(stall: watchdog::Stall) -> String {
+    format!("{} ms, {}", stall.duration.as_millis(), stall.handling)
+}
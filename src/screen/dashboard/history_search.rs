@@ -0,0 +1,312 @@
+use chrono::Local;
+use data::search::{self, Found};
+use data::{Server, buffer, config, message};
+use iced::widget::{
+    Column, button, column, container, row, scrollable, text, text_input,
+};
+use iced::{Length, Task, padding, task};
+
+use super::buffer_switcher;
+use crate::widget::Element;
+use crate::{icon, theme};
+
+/// Matches kept for each buffer
+const MATCHES_PER_BUFFER: usize = 50;
+
+/// Searches the history saved on disk of every buffer, listing the matches
+/// by buffer as each is searched
+pub struct HistorySearch {
+    input_id: text_input::Id,
+    query: search::Query,
+    matching: config::Matching,
+    servers: Vec<Server>,
+    found: Vec<Found>,
+    error: Option<String>,
+    is_searching: bool,
+    /// Aborts the search in progress once dropped
+    handle: Option<task::Handle>,
+    /// Tells the results of the current search apart from those of previous
+    /// ones still on their way
+    generation: usize,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Search(String),
+    ToggleCase,
+    ToggleRegex,
+    Found(usize, Found),
+    Done(usize),
+    GoTo(buffer::Upstream, message::Hash),
+    Close,
+}
+
+pub enum Event {
+    GoToMessage(buffer::Upstream, message::Hash),
+    Closed,
+}
+
+impl HistorySearch {
+    pub fn new(
+        servers: Vec<Server>,
+        matching: config::Matching,
+    ) -> (Self, Task<Message>) {
+        let input_id = text_input::Id::unique();
+
+        (
+            Self {
+                input_id: input_id.clone(),
+                query: search::Query::default(),
+                matching,
+                servers,
+                found: vec![],
+                error: None,
+                is_searching: false,
+                handle: None,
+                generation: 0,
+            },
+            text_input::focus(input_id),
+        )
+    }
+
+    pub fn update(
+        &mut self,
+        message: Message,
+    ) -> (Task<Message>, Option<Event>) {
+        match message {
+            Message::Search(text) => {
+                self.query.text = text;
+
+                (self.search(), None)
+            }
+            Message::ToggleCase => {
+                self.query.case_sensitive = !self.query.case_sensitive;
+
+                (self.search(), None)
+            }
+            Message::ToggleRegex => {
+                self.query.is_regex = !self.query.is_regex;
+
+                (self.search(), None)
+            }
+            Message::Found(generation, found) => {
+                if generation == self.generation {
+                    self.found.push(found);
+                }
+
+                (Task::none(), None)
+            }
+            Message::Done(generation) => {
+                if generation == self.generation {
+                    self.is_searching = false;
+                    self.handle = None;
+                }
+
+                (Task::none(), None)
+            }
+            Message::GoTo(buffer, message) => {
+                (Task::none(), Some(Event::GoToMessage(buffer, message)))
+            }
+            Message::Close => (Task::none(), Some(Event::Closed)),
+        }
+    }
+
+    /// Starts searching for the query, aborting the search in progress
+    fn search(&mut self) -> Task<Message> {
+        self.generation += 1;
+        self.found.clear();
+        self.error = None;
+        self.is_searching = false;
+        self.handle = None;
+
+        let matcher = match self.query.matcher(self.matching) {
+            Ok(Some(matcher)) => matcher,
+            Ok(None) => return Task::none(),
+            Err(error) => {
+                self.error = Some(error.to_string());
+
+                return Task::none();
+            }
+        };
+
+        let generation = self.generation;
+
+        let (task, handle) = Task::run(
+            search::history(matcher, self.servers.clone(), MATCHES_PER_BUFFER),
+            move |found| Message::Found(generation, found),
+        )
+        .chain(Task::done(Message::Done(generation)))
+        .abortable();
+
+        self.is_searching = true;
+        self.handle = Some(handle.abort_on_drop());
+
+        task
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let toggle = |label: &'static str, is_enabled: bool, message| {
+            button(text(label))
+                .padding([2, 4])
+                .style(move |theme, status| {
+                    theme::button::secondary(theme, status, is_enabled)
+                })
+                .on_press(message)
+        };
+
+        let input = row![
+            text_input("Search all buffers", &self.query.text)
+                .id(self.input_id.clone())
+                .on_input(Message::Search)
+                .padding([2, 4])
+                .style(theme::text_input::primary),
+            toggle("Aa", self.query.case_sensitive, Message::ToggleCase),
+            toggle(".*", self.query.is_regex, Message::ToggleRegex),
+            button(icon::cancel())
+                .padding([2, 4])
+                .style(theme::button::bare)
+                .on_press(Message::Close),
+        ]
+        .spacing(4)
+        .align_y(iced::Alignment::Center);
+
+        let total = self.found.iter().map(|found| found.total).sum::<usize>();
+
+        let status = if let Some(error) = &self.error {
+            Some(text(error.clone()).style(theme::text::error))
+        } else if self.is_searching {
+            Some(
+                text(format!("Searching... {total} matches so far"))
+                    .style(theme::text::secondary),
+            )
+        } else if !self.query.text.is_empty() {
+            Some(
+                text(format!(
+                    "{total} matches in {} buffers",
+                    self.found.len()
+                ))
+                .style(theme::text::secondary),
+            )
+        } else {
+            None
+        };
+
+        let results = (!self.found.is_empty()).then(|| {
+            container(
+                scrollable(
+                    Column::with_children(self.found.iter().map(found))
+                        .spacing(8),
+                )
+                .style(theme::scrollable::hidden),
+            )
+            .max_height(480)
+        });
+
+        container(
+            column![input]
+                .push_maybe(status)
+                .push_maybe(results)
+                .spacing(6)
+                .width(Length::Fixed(600.0)),
+        )
+        .padding(8)
+        .style(theme::container::tooltip)
+        .into()
+    }
+}
+
+/// Matches of a buffer, newest first
+fn found(found: &Found) -> Element<'_, Message> {
+    let name =
+        buffer_switcher::name(&data::Buffer::Upstream(found.buffer.clone()));
+
+    let header = if found.total > found.messages.len() {
+        format!(
+            "{name} ({} of {} matches)",
+            found.messages.len(),
+            found.total
+        )
+    } else {
+        format!("{name} ({} matches)", found.total)
+    };
+
+    let messages = found.messages.iter().rev().map(|message| {
+        let time = message
+            .server_time
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string();
+
+        let content = match message.target.source() {
+            message::Source::User(user) => {
+                format!("{}: {}", user.nickname(), message.text())
+            }
+            _ => message.text(),
+        };
+
+        button(
+            row![
+                text(time).style(theme::text::secondary),
+                text(content)
+                    .shaping(text::Shaping::Advanced)
+                    .width(Length::Fill),
+            ]
+            .spacing(8),
+        )
+        .padding(padding::left(8))
+        .width(Length::Fill)
+        .style(|theme, status| theme::button::secondary(theme, status, false))
+        .on_press(Message::GoTo(found.buffer.clone(), message.hash))
+        .into()
+    });
+
+    column![text(header).style(theme::text::tertiary)]
+        .extend(messages)
+        .spacing(2)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(server: &str) -> Found {
+        Found {
+            buffer: buffer::Upstream::Server(Server::from(server)),
+            messages: vec![],
+            total: 1,
+        }
+    }
+
+    #[test]
+    fn stale_results_are_dropped() {
+        let (mut search, _) = HistorySearch::new(
+            vec![Server::from("libera")],
+            config::Matching::default(),
+        );
+
+        let _ = search.update(Message::Search("hello".to_string()));
+        let first = search.generation;
+        let _ = search.update(Message::Found(first, found("libera")));
+        assert_eq!(search.found.len(), 1);
+
+        // Typing more cancels the search in progress
+        let _ = search.update(Message::Search("hello world".to_string()));
+        assert!(search.found.is_empty());
+        assert!(search.is_searching);
+
+        let _ = search.update(Message::Found(first, found("oftc")));
+        let _ = search.update(Message::Done(first));
+        assert!(search.found.is_empty());
+        assert!(search.is_searching);
+
+        let _ = search.update(Message::Found(search.generation, found("oftc")));
+        let _ = search.update(Message::Done(search.generation));
+        assert_eq!(search.found.len(), 1);
+        assert_eq!(
+            search.found[0].buffer,
+            buffer::Upstream::Server(Server::from("oftc"))
+        );
+        assert!(!search.is_searching);
+    }
+}
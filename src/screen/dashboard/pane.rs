@@ -1,9 +1,10 @@
-use data::{Config, file_transfer, history, preview};
+use data::{Config, file_transfer, history, preview, translation};
+use iced::Length;
 use iced::widget::{button, center, container, pane_grid, row, text};
 
 use super::sidebar;
 use crate::buffer::{self, Buffer};
-use crate::widget::tooltip;
+use crate::widget::{Element, context_menu, tooltip};
 use crate::{Theme, icon, theme, widget};
 
 #[derive(Debug, Clone)]
@@ -13,7 +14,7 @@ pub enum Message {
     PaneDragged(pane_grid::DragEvent),
     Buffer(pane_grid::Pane, buffer::Message),
     ClosePane,
-    SplitPane(pane_grid::Axis),
+    SplitPane(pane_grid::Pane, pane_grid::Axis),
     MaximizePane,
     ToggleShowUserList,
     ToggleShowTopic,
@@ -21,11 +22,16 @@ pub enum Message {
     Merge,
     ScrollToBottom,
     MarkAsRead,
+    TogglePin,
+    ShowBanList(pane_grid::Pane),
 }
 
 #[derive(Clone, Debug)]
 pub struct Pane {
     pub buffer: Buffer,
+    /// Pinned panes keep their buffer when buffers are opened from the
+    /// sidebar or links, and only change through the command bar.
+    pub pinned: bool,
     title_bar: TitleBar,
 }
 
@@ -36,6 +42,7 @@ impl Pane {
     pub fn new(buffer: Buffer) -> Self {
         Self {
             buffer,
+            pinned: false,
             title_bar: TitleBar::default(),
         }
     }
@@ -50,6 +57,8 @@ impl Pane {
         file_transfers: &'a file_transfer::Manager,
         history: &'a history::Manager,
         previews: &'a preview::Collection,
+        avatars: &'a preview::Collection,
+        translations: &'a translation::Collection,
         sidebar: &'a sidebar::Sidebar,
         config: &'a Config,
         theme: &'a Theme,
@@ -61,16 +70,22 @@ impl Pane {
             Buffer::Channel(state) => {
                 let channel = state.target.as_str();
                 let server = &state.server;
-                let users = clients
-                    .get_channel_users(&state.server, &state.target)
-                    .len();
 
-                let mode = clients
-                    .get_channel_mode(&state.server, &state.target)
-                    .map(|mode| format!(" ({mode})"))
-                    .unwrap_or_default();
+                // Layouts open channels which may not be joined
+                if clients.get_channels(server).contains(&state.target) {
+                    let users = clients
+                        .get_channel_users(&state.server, &state.target)
+                        .len();
 
-                format!("{channel}{mode} @ {server} - {users} users")
+                    let modes = clients
+                        .get_channel_modes(&state.server, &state.target)
+                        .map(|modes| format!(" ({modes})"))
+                        .unwrap_or_default();
+
+                    format!("{channel}{modes} @ {server} - {users} users")
+                } else {
+                    format!("{channel} @ {server} - offline")
+                }
             }
             Buffer::Server(state) => state.server.to_string(),
             Buffer::Query(state) => {
@@ -86,6 +101,7 @@ impl Pane {
 
         let title_bar = self.title_bar.view(
             &self.buffer,
+            self.pinned,
             history,
             title_bar_text,
             id,
@@ -106,6 +122,8 @@ impl Pane {
                 file_transfers,
                 history,
                 previews,
+                avatars,
+                translations,
                 settings,
                 config,
                 theme,
@@ -162,9 +180,10 @@ impl TitleBar {
     fn view<'a>(
         &'a self,
         buffer: &Buffer,
+        pinned: bool,
         history: &'a history::Manager,
         value: String,
-        _id: pane_grid::Pane,
+        id: pane_grid::Pane,
         panes: usize,
         _is_focused: bool,
         maximized: bool,
@@ -291,6 +310,25 @@ impl TitleBar {
             controls = controls.push(nicklist_button_with_tooltip);
         }
 
+        if !matches!(buffer, Buffer::Empty) {
+            let pin_button = button(center(icon::dot()))
+                .padding(5)
+                .width(22)
+                .height(22)
+                .on_press(Message::TogglePin)
+                .style(move |theme, status| {
+                    theme::button::secondary(theme, status, pinned)
+                });
+
+            let pin_button_with_tooltip = tooltip(
+                pin_button,
+                show_tooltips.then_some(if pinned { "Unpin" } else { "Pin" }),
+                tooltip::Position::Bottom,
+            );
+
+            controls = controls.push(pin_button_with_tooltip);
+        }
+
         // If we have more than one pane open, show maximize button.
         if panes > 1 {
             let maximize_button = button(center(if maximized {
@@ -387,12 +425,71 @@ impl TitleBar {
         .padding([0, 10])
         .align_y(iced::alignment::Vertical::Center);
 
+        // Only panes of the main window can be split
+        let title: Element<'a, Message> = if is_popout {
+            title.into()
+        } else {
+            context_menu(
+                context_menu::MouseButton::default(),
+                title,
+                Entry::list(buffer),
+                move |entry, length| entry.view(id, length),
+            )
+            .into()
+        };
+
         widget::TitleBar::new(title)
             .controls(pane_grid::Controls::new(controls))
             .padding(6)
     }
 }
 
+/// Entries of the menu opened from the title bar
+#[derive(Debug, Clone, Copy)]
+enum Entry {
+    SplitHorizontally,
+    SplitVertically,
+    BanList,
+}
+
+impl Entry {
+    fn list(buffer: &Buffer) -> Vec<Self> {
+        let mut entries =
+            vec![Entry::SplitHorizontally, Entry::SplitVertically];
+
+        if matches!(buffer, Buffer::Channel(_)) {
+            entries.push(Entry::BanList);
+        }
+
+        entries
+    }
+
+    fn view<'a>(
+        self,
+        id: pane_grid::Pane,
+        length: Length,
+    ) -> Element<'a, Message> {
+        let (content, message) = match self {
+            Entry::SplitHorizontally => (
+                "Split horizontally",
+                Message::SplitPane(id, pane_grid::Axis::Horizontal),
+            ),
+            Entry::SplitVertically => (
+                "Split vertically",
+                Message::SplitPane(id, pane_grid::Axis::Vertical),
+            ),
+            Entry::BanList => ("Ban list", Message::ShowBanList(id)),
+        };
+
+        button(text(content))
+            .width(length)
+            .padding(5)
+            .style(|theme, status| theme::button::primary(theme, status, false))
+            .on_press(message)
+            .into()
+    }
+}
+
 impl From<Pane> for data::Pane {
     fn from(pane: Pane) -> Self {
         let buffer = match pane.buffer {
@@ -415,6 +512,9 @@ impl From<Pane> for data::Pane {
             }
         };
 
-        data::Pane::Buffer { buffer }
+        data::Pane::Buffer {
+            buffer,
+            pinned: pane.pinned,
+        }
     }
 }
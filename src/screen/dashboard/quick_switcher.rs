@@ -0,0 +1,223 @@
+use data::buffer::Upstream;
+use data::{Server, fuzzy, isupport, target};
+use iced::widget::{Column, button, column, container, text, text_input};
+use iced::{Length, Task};
+
+use super::buffer_switcher;
+use crate::theme;
+use crate::widget::{Element, key_press};
+
+/// Entries shown at once
+const MAX_ENTRIES: usize = 20;
+
+/// Switches to any buffer by typing part of its name
+pub struct QuickSwitcher {
+    input_id: text_input::Id,
+    query: String,
+    /// Recently focused buffers first, most recent first
+    buffers: Vec<data::Buffer>,
+    recent: usize,
+    servers: Vec<ChannelRules>,
+    entries: Vec<Entry>,
+    selected: usize,
+}
+
+/// How channel names are told apart on a server, to offer joining them
+pub struct ChannelRules {
+    pub server: Server,
+    pub chantypes: Vec<char>,
+    pub casemapping: isupport::CaseMap,
+}
+
+#[derive(Debug, Clone)]
+pub enum Entry {
+    Buffer(data::Buffer),
+    Join(Server, target::Channel),
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Query(String),
+    Up,
+    Down,
+    /// Opens the selected entry, in a new pane when `true`
+    Submit(bool),
+    Open(usize),
+}
+
+pub enum Event {
+    /// Opens the entry, in a new pane when `true`
+    Open(Entry, bool),
+}
+
+impl QuickSwitcher {
+    /// Lists `recent` buffers first, followed by the rest of `buffers`
+    pub fn new(
+        recent: Vec<data::Buffer>,
+        buffers: impl IntoIterator<Item = data::Buffer>,
+        servers: Vec<ChannelRules>,
+    ) -> (Self, Task<Message>) {
+        let input_id = text_input::Id::unique();
+
+        let recent_len = recent.len();
+        let mut all = recent;
+
+        for buffer in buffers {
+            if !all.contains(&buffer) {
+                all.push(buffer);
+            }
+        }
+
+        let mut switcher = Self {
+            input_id: input_id.clone(),
+            query: String::new(),
+            buffers: all,
+            recent: recent_len,
+            servers,
+            entries: vec![],
+            selected: 0,
+        };
+        switcher.filter();
+
+        (switcher, text_input::focus(input_id))
+    }
+
+    pub fn update(&mut self, message: Message) -> Option<Event> {
+        match message {
+            Message::Query(query) => {
+                self.query = query;
+                self.filter();
+
+                None
+            }
+            Message::Up => {
+                if !self.entries.is_empty() {
+                    self.selected = (self.selected + self.entries.len() - 1)
+                        % self.entries.len();
+                }
+
+                None
+            }
+            Message::Down => {
+                if !self.entries.is_empty() {
+                    self.selected = (self.selected + 1) % self.entries.len();
+                }
+
+                None
+            }
+            Message::Submit(new_pane) => self
+                .entries
+                .get(self.selected)
+                .cloned()
+                .map(|entry| Event::Open(entry, new_pane)),
+            Message::Open(index) => self
+                .entries
+                .get(index)
+                .cloned()
+                .map(|entry| Event::Open(entry, false)),
+        }
+    }
+
+    /// Ranks the buffers matching the query, and offers to join the channel
+    /// named by it on servers where it isn't joined
+    fn filter(&mut self) {
+        let buffers = fuzzy::rank(
+            &self.query,
+            self.buffers
+                .iter()
+                .map(|buffer| (buffer, buffer_switcher::name(buffer))),
+            self.recent,
+        );
+
+        let channel = self.query.trim();
+
+        let joins = self
+            .servers
+            .iter()
+            .filter(|_| !channel.is_empty())
+            .filter(|_| !channel.contains(char::is_whitespace))
+            .filter_map(|rules| {
+                let channel = target::Channel::parse(
+                    channel,
+                    &rules.chantypes,
+                    &[],
+                    rules.casemapping,
+                )
+                .ok()?;
+
+                let buffer = data::Buffer::Upstream(Upstream::Channel(
+                    rules.server.clone(),
+                    channel.clone(),
+                ));
+
+                (!self.buffers.contains(&buffer))
+                    .then(|| Entry::Join(rules.server.clone(), channel))
+            });
+
+        self.entries = buffers
+            .into_iter()
+            .cloned()
+            .map(Entry::Buffer)
+            .take(MAX_ENTRIES)
+            .chain(joins)
+            .collect();
+        self.selected = 0;
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let input = text_input("Switch to a buffer...", &self.query)
+            .id(self.input_id.clone())
+            .on_input(Message::Query)
+            .on_submit(Message::Submit(false))
+            .padding([2, 4])
+            .style(theme::text_input::primary);
+
+        let input = key_press(
+            key_press(
+                key_press(
+                    input,
+                    key_press::Key::Named(key_press::Named::ArrowUp),
+                    key_press::Modifiers::default(),
+                    Message::Up,
+                ),
+                key_press::Key::Named(key_press::Named::ArrowDown),
+                key_press::Modifiers::default(),
+                Message::Down,
+            ),
+            key_press::Key::Named(key_press::Named::Enter),
+            key_press::Modifiers::COMMAND,
+            Message::Submit(true),
+        );
+
+        let entries = Column::with_children(
+            self.entries.iter().enumerate().map(|(index, entry)| {
+                let selected = index == self.selected;
+
+                let label = match entry {
+                    Entry::Buffer(buffer) => buffer_switcher::name(buffer),
+                    Entry::Join(server, channel) => {
+                        format!("Join {channel} on {server}")
+                    }
+                };
+
+                button(text(label).shaping(text::Shaping::Advanced))
+                    .padding([2, 8])
+                    .width(Length::Fill)
+                    .style(move |theme, status| {
+                        theme::button::secondary(theme, status, selected)
+                    })
+                    .on_press(Message::Open(index))
+                    .into()
+            }),
+        );
+
+        container(
+            column![input, entries]
+                .spacing(6)
+                .width(Length::Fixed(400.0)),
+        )
+        .padding(8)
+        .style(theme::container::tooltip)
+        .into()
+    }
+}
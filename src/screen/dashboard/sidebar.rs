@@ -1,18 +1,27 @@
 use std::time::Duration;
 
+use chrono::Local;
+use data::channel::ActionStyle;
+use data::client::Status;
+use data::client::monitor::{Monitored, Presence};
 use data::config::{self, Config, sidebar};
-use data::dashboard::{BufferAction, BufferFocusedAction};
-use data::{Version, buffer, file_transfer, history};
+use data::dashboard::{
+    BufferAction, BufferFocusedAction, BufferSettings, CollapsedServers,
+    Highlights, Placement, SidebarOrder,
+};
+use data::{Version, buffer, file_transfer, history, target};
 use iced::widget::{
     Column, Row, Scrollable, Space, button, column, container, horizontal_rule,
     horizontal_space, pane_grid, row, scrollable, text, vertical_rule,
     vertical_space,
 };
 use iced::{Alignment, Length, Task, padding};
+use itertools::Itertools;
 use tokio::time;
 
 use super::{Focus, Panes, Server};
-use crate::widget::{Element, Text, context_menu, double_pass};
+use crate::widget::draggable::{self, draggable};
+use crate::widget::{Element, Text, context_menu, double_pass, tooltip};
 use crate::{icon, theme, window};
 
 const CONFIG_RELOAD_DELAY: Duration = Duration::from_secs(1);
@@ -37,6 +46,17 @@ pub enum Message {
     ReloadComplete,
     MarkAsRead(buffer::Upstream),
     MarkServerAsRead(Server),
+    Connect(Server),
+    Retry(Server),
+    ForgetForwards(Server, target::Channel),
+    ResyncUsers(Server, target::Channel),
+    AuditLog(Server, target::Channel),
+    SetNotifications(buffer::Upstream, Option<buffer::Notifications>),
+    RemoveActionStyle(buffer::Upstream, String),
+    Drag(buffer::Upstream),
+    DragOver(buffer::Upstream, Option<Placement>),
+    Drop,
+    ToggleCollapsed(Server),
 }
 
 #[derive(Debug, Clone)]
@@ -57,12 +77,31 @@ pub enum Event {
     ConfigReloaded(Result<Config, config::Error>),
     MarkAsRead(buffer::Upstream),
     MarkServerAsRead(Server),
+    Connect(Server),
+    /// Reconnect now, e.g. after being banned when it won't be on its own
+    Retry(Server),
+    ForgetForwards(Server, target::Channel),
+    ResyncUsers(Server, target::Channel),
+    AuditLog(Server, target::Channel),
+    SetNotifications(buffer::Upstream, Option<buffer::Notifications>),
+    RemoveActionStyle(buffer::Upstream, String),
+    Place(buffer::Upstream, buffer::Upstream, Placement),
+    ToggleCollapsed(Server),
 }
 
 #[derive(Clone)]
 pub struct Sidebar {
     pub hidden: bool,
     reloading_config: bool,
+    drag: Option<Drag>,
+}
+
+/// A channel or query being dragged to another place among those of its
+/// server
+#[derive(Clone)]
+struct Drag {
+    buffer: buffer::Upstream,
+    target: Option<(buffer::Upstream, Placement)>,
 }
 
 impl Default for Sidebar {
@@ -76,6 +115,7 @@ impl Sidebar {
         Self {
             hidden: false,
             reloading_config: false,
+            drag: None,
         }
     }
 
@@ -142,9 +182,72 @@ impl Sidebar {
             Message::MarkServerAsRead(server) => {
                 (Task::none(), Some(Event::MarkServerAsRead(server)))
             }
+            Message::Connect(server) => {
+                (Task::none(), Some(Event::Connect(server)))
+            }
+            Message::Retry(server) => {
+                (Task::none(), Some(Event::Retry(server)))
+            }
+            Message::ForgetForwards(server, channel) => {
+                (Task::none(), Some(Event::ForgetForwards(server, channel)))
+            }
+            Message::ResyncUsers(server, channel) => {
+                (Task::none(), Some(Event::ResyncUsers(server, channel)))
+            }
+            Message::AuditLog(server, channel) => {
+                (Task::none(), Some(Event::AuditLog(server, channel)))
+            }
+            Message::SetNotifications(buffer, notifications) => (
+                Task::none(),
+                Some(Event::SetNotifications(buffer, notifications)),
+            ),
+            Message::RemoveActionStyle(buffer, mask) => {
+                (Task::none(), Some(Event::RemoveActionStyle(buffer, mask)))
+            }
             Message::OpenConfigFile => {
                 (Task::none(), Some(Event::OpenConfigFile))
             }
+            Message::Drag(buffer) => {
+                self.drag = Some(Drag {
+                    buffer,
+                    target: None,
+                });
+
+                (Task::none(), None)
+            }
+            Message::DragOver(buffer, placement) => {
+                if let Some(drag) = &mut self.drag {
+                    match placement {
+                        Some(placement) => {
+                            drag.target = Some((buffer, placement));
+                        }
+                        // Left the buffer it would've been placed next to
+                        None => {
+                            if drag
+                                .target
+                                .as_ref()
+                                .is_some_and(|(target, _)| *target == buffer)
+                            {
+                                drag.target = None;
+                            }
+                        }
+                    }
+                }
+
+                (Task::none(), None)
+            }
+            Message::Drop => {
+                let event = self.drag.take().and_then(|drag| {
+                    let (target, placement) = drag.target?;
+
+                    Some(Event::Place(drag.buffer, target, placement))
+                });
+
+                (Task::none(), event)
+            }
+            Message::ToggleCollapsed(server) => {
+                (Task::none(), Some(Event::ToggleCollapsed(server)))
+            }
         }
     }
 
@@ -152,9 +255,24 @@ impl Sidebar {
         &self,
         keyboard: &'a data::config::Keyboard,
         file_transfers: &'a file_transfer::Manager,
+        unread_highlights: usize,
         version: &'a Version,
     ) -> Element<'a, Message> {
-        let base = button(icon::menu()).padding(5).width(Length::Shrink);
+        let pending_file_transfers = file_transfers.pending_approval();
+        let file_transfers_progress = file_transfers.active_progress();
+
+        // Badges are on entries of the menu, so its button hints at them
+        let has_badge = pending_file_transfers > 0
+            || file_transfers_progress.is_some()
+            || unread_highlights > 0;
+
+        let base = button(icon::menu().style(if has_badge {
+            theme::text::tertiary
+        } else {
+            theme::text::primary
+        }))
+        .padding(5)
+        .width(Length::Shrink);
 
         let menu = Menu::list();
 
@@ -168,11 +286,17 @@ impl Sidebar {
                 move |menu, length| {
                     let context_button =
                         |title: Text<'a>,
+                         badge: Option<String>,
                          keybind: Option<&data::shortcut::KeyBind>,
                          icon: Text<'a>,
                          message: Message| {
                             button(
                                 row![icon.width(Length::Fixed(12.0)), title]
+                                    .push_maybe(badge.map(|badge| {
+                                        text(badge)
+                                            .size(theme::TEXT_SIZE - 2.0)
+                                            .style(theme::text::tertiary)
+                                    }))
                                     .push_maybe(keybind.map(|kb| {
                                         text(format!("({kb})"))
                                             .shaping(text::Shaping::Advanced)
@@ -191,12 +315,14 @@ impl Sidebar {
                     match menu {
                         Menu::RefreshConfig => context_button(
                             text("Reload config file"),
+                            None,
                             Some(&keyboard.reload_configuration),
                             icon::refresh(),
                             Message::ReloadConfigFile,
                         ),
                         Menu::CommandBar => context_button(
                             text("Command Bar"),
+                            None,
                             Some(&keyboard.command_bar),
                             icon::search(),
                             Message::ToggleCommandBar,
@@ -209,6 +335,10 @@ impl Sidebar {
                                     theme::text::tertiary
                                 },
                             ),
+                            file_transfers_badge(
+                                pending_file_transfers,
+                                file_transfers_progress,
+                            ),
                             Some(&keyboard.file_transfers),
                             icon::file_transfer().style(
                                 if file_transfers.is_empty() {
@@ -223,6 +353,8 @@ impl Sidebar {
                         ),
                         Menu::Highlights => context_button(
                             text("Highlights"),
+                            (unread_highlights > 0)
+                                .then(|| unread_highlights.to_string()),
                             Some(&keyboard.highlights),
                             icon::highlights(),
                             Message::ToggleInternalBuffer(
@@ -231,6 +363,7 @@ impl Sidebar {
                         ),
                         Menu::Logs => context_button(
                             text("Logs"),
+                            None,
                             Some(&keyboard.logs),
                             icon::logs(),
                             Message::ToggleInternalBuffer(
@@ -239,6 +372,7 @@ impl Sidebar {
                         ),
                         Menu::ThemeEditor => context_button(
                             text("Theme Editor"),
+                            None,
                             Some(&keyboard.theme_editor),
                             icon::theme_editor(),
                             Message::ToggleThemeEditor,
@@ -254,6 +388,7 @@ impl Sidebar {
                                 text("New version available")
                                     .style(theme::text::tertiary),
                                 None,
+                                None,
                                 icon::megaphone().style(theme::text::tertiary),
                                 Message::OpenReleaseWebsite,
                             ),
@@ -267,12 +402,14 @@ impl Sidebar {
                         Menu::Documentation => context_button(
                             text("Documentation"),
                             None,
+                            None,
                             icon::documentation(),
                             Message::OpenDocumentation,
                         ),
                         Menu::OpenConfigFile => context_button(
                             text("Open config file"),
                             None,
+                            None,
                             icon::config(),
                             Message::OpenConfigFile,
                         ),
@@ -289,38 +426,95 @@ impl Sidebar {
         history: &'a history::Manager,
         panes: &'a Panes,
         focus: Focus,
+        buffer_settings: &'a BufferSettings,
+        sidebar_order: &SidebarOrder,
+        collapsed_servers: &CollapsedServers,
         config: &'a Config,
         file_transfers: &'a file_transfer::Manager,
+        highlights: &Highlights,
         version: &'a Version,
     ) -> Option<Element<'a, Message>> {
         if self.hidden {
             return None;
         }
 
+        let now = Local::now();
+
+        // Unread indicators are hidden for buffers muted by a schedule
+        let is_muted = |target: &str| {
+            config.notification_level(target, now)
+                == config::notification::Level::Muted
+        };
+
         let content = |width| {
             let user_menu_button = config.sidebar.show_user_menu.then(|| {
-                self.user_menu_button(&config.keyboard, file_transfers, version)
+                self.user_menu_button(
+                    &config.keyboard,
+                    file_transfers,
+                    highlights.unread(),
+                    version,
+                )
             });
 
+            let schedules = active_schedules(config, now);
+
             let mut buffers = vec![];
             let mut client_enumeration = 0;
 
             for server in config.servers.keys() {
+                let is_collapsed = collapsed_servers.contains(server);
+
                 let button = |buffer: buffer::Upstream,
-                              connected: bool,
+                              status: Status,
                               server_has_unread: bool,
                               has_unread: bool| {
+                    let is_header =
+                        matches!(buffer, buffer::Upstream::Server(_));
+                    // A collapsed server stands in for its buffers
+                    let collapsed = (is_header && is_collapsed)
+                        .then(|| highlights.unread_on(buffer.server()));
+                    let unread = if has_unread && collapsed.is_none() {
+                        history.unread(&history::Kind::from_input_buffer(
+                            buffer.clone(),
+                        ))
+                    } else {
+                        history::Unread::default()
+                    };
+                    let is_forward_target = match &buffer {
+                        buffer::Upstream::Channel(server, channel) => {
+                            clients.forwards(server).is_some_and(|forwards| {
+                                forwards.is_target(channel)
+                            })
+                        }
+                        _ => false,
+                    };
+                    let notifications = buffer_settings.notifications(&buffer);
+                    let action_masks = buffer_settings
+                        .actions(&buffer)
+                        .map(|actions| {
+                            actions
+                                .masks()
+                                .map(|(mask, style)| (mask.to_string(), style))
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
                     upstream_buffer_button(
                         panes,
                         focus,
                         buffer,
-                        connected,
+                        status,
                         config.actions.sidebar.buffer,
                         config.actions.sidebar.focused_buffer,
                         config.sidebar.position,
                         config.sidebar.unread_indicator,
                         server_has_unread,
                         has_unread,
+                        unread,
+                        collapsed,
+                        is_forward_target,
+                        notifications,
+                        action_masks,
                         width,
                     )
                 };
@@ -329,11 +523,13 @@ impl Sidebar {
                     client_enumeration += 1;
 
                     match state {
-                        data::client::State::Disconnected => {
-                            // Disconnected server.
+                        data::client::State::Idle
+                        | data::client::State::Disconnected => {
+                            // Disconnected server, or one waiting to be
+                            // connected by the user.
                             buffers.push(button(
                                 buffer::Upstream::Server(server.clone()),
-                                false,
+                                clients.status(server),
                                 history.server_has_unread(server.clone()),
                                 history.has_unread(&history::Kind::Server(
                                     server.clone(),
@@ -341,52 +537,128 @@ impl Sidebar {
                             ));
                         }
                         data::client::State::Ready(connection) => {
-                            // Connected server.
-                            buffers.push(button(
-                                buffer::Upstream::Server(server.clone()),
-                                true,
-                                history.server_has_unread(server.clone()),
-                                history.has_unread(&history::Kind::Server(
-                                    server.clone(),
-                                )),
+                            // Connected server, with the presence of the
+                            // users it monitors in a tooltip.
+                            buffers.push(tooltip(
+                                button(
+                                    buffer::Upstream::Server(server.clone()),
+                                    Status::Connected,
+                                    history.server_has_unread(server.clone()),
+                                    history.has_unread(&history::Kind::Server(
+                                        server.clone(),
+                                    )),
+                                ),
+                                monitored_tooltip(clients.monitored(server))
+                                    .filter(|_| config.tooltips),
+                                match config.sidebar.position {
+                                    sidebar::Position::Left => {
+                                        tooltip::Position::Right
+                                    }
+                                    sidebar::Position::Right => {
+                                        tooltip::Position::Left
+                                    }
+                                    sidebar::Position::Top => {
+                                        tooltip::Position::Bottom
+                                    }
+                                    sidebar::Position::Bottom => {
+                                        tooltip::Position::Top
+                                    }
+                                },
                             ));
 
-                            // Channels from the connected server.
-                            for channel in connection.channels() {
-                                buffers.push(button(
+                            // Channels and queries from the connected
+                            // server, in the order they were dragged to,
+                            // unless it's collapsed.
+                            let channels =
+                                connection.channels().iter().map(|channel| {
                                     buffer::Upstream::Channel(
                                         server.clone(),
                                         channel.clone(),
-                                    ),
-                                    true,
-                                    history.server_has_unread(server.clone()),
-                                    history.has_unread(
-                                        &history::Kind::Channel(
-                                            server.clone(),
-                                            channel.clone(),
-                                        ),
-                                    ),
-                                ));
-                            }
+                                    )
+                                });
+                            let queries = history
+                                .get_unique_queries(server)
+                                .into_iter()
+                                .map(|query| {
+                                    let query = clients
+                                        .resolve_query(server, query)
+                                        .unwrap_or(query);
 
-                            // Queries from the connected server.
-                            let queries = history.get_unique_queries(server);
-                            for query in queries {
-                                let query = clients
-                                    .resolve_query(server, query)
-                                    .unwrap_or(query);
-
-                                buffers.push(button(
                                     buffer::Upstream::Query(
                                         server.clone(),
                                         query.clone(),
+                                    )
+                                });
+
+                            let mut server_buffers = if is_collapsed {
+                                vec![]
+                            } else {
+                                channels.chain(queries).collect::<Vec<_>>()
+                            };
+                            sidebar_order.sort(server, &mut server_buffers);
+
+                            let axis =
+                                if config.sidebar.position.is_horizontal() {
+                                    draggable::Axis::Horizontal
+                                } else {
+                                    draggable::Axis::Vertical
+                                };
+
+                            for buffer in server_buffers {
+                                let has_unread = match &buffer {
+                                    buffer::Upstream::Channel(
+                                        server,
+                                        channel,
+                                    ) => {
+                                        history.has_unread(
+                                            &history::Kind::Channel(
+                                                server.clone(),
+                                                channel.clone(),
+                                            ),
+                                        ) && !is_muted(channel.as_str())
+                                    }
+                                    buffer::Upstream::Query(server, query) => {
+                                        history.has_unread(
+                                            &history::Kind::Query(
+                                                server.clone(),
+                                                query.clone(),
+                                            ),
+                                        ) && !is_muted(query.as_str())
+                                    }
+                                    buffer::Upstream::Server(_) => continue,
+                                };
+
+                                let is_drop_target =
+                                    self.drag.as_ref().is_some_and(|drag| {
+                                        drag.buffer != buffer
+                                            && drag.buffer.server() == server
+                                    });
+                                let indicator = self
+                                    .drag
+                                    .as_ref()
+                                    .and_then(|drag| drag.target.as_ref())
+                                    .filter(|(target, _)| *target == buffer)
+                                    .map(|(_, placement)| *placement);
+
+                                buffers.push(draggable(
+                                    button(
+                                        buffer.clone(),
+                                        Status::Connected,
+                                        history
+                                            .server_has_unread(server.clone()),
+                                        has_unread,
                                     ),
-                                    true,
-                                    history.server_has_unread(server.clone()),
-                                    history.has_unread(&history::Kind::Query(
-                                        server.clone(),
-                                        query.clone(),
-                                    )),
+                                    axis,
+                                    is_drop_target,
+                                    indicator,
+                                    Message::Drag(buffer.clone()),
+                                    move |placement| {
+                                        Message::DragOver(
+                                            buffer.clone(),
+                                            placement,
+                                        )
+                                    },
+                                    Message::Drop,
                                 ));
                             }
 
@@ -430,6 +702,7 @@ impl Sidebar {
                     // Wrap buffers in a column with user_menu_button
                     let content =
                         column![container(buffers).height(Length::Fill)]
+                            .push_maybe(schedules)
                             .push_maybe(user_menu_button);
 
                     container(content)
@@ -447,6 +720,7 @@ impl Sidebar {
 
                     // Wrap buffers in a row with user_menu_button
                     let content = row![container(buffers).width(Length::Fill)]
+                        .push_maybe(schedules)
                         .push_maybe(user_menu_button)
                         .align_y(Alignment::Center);
 
@@ -484,6 +758,30 @@ impl Sidebar {
     }
 }
 
+/// Notification schedules active for tagged buffers
+fn active_schedules<'a, Message: 'a>(
+    config: &'a Config,
+    now: chrono::DateTime<Local>,
+) -> Option<Element<'a, Message>> {
+    let schedules = config
+        .notifications
+        .active_schedules(now)
+        .map(|(tag, level)| {
+            text(format!("{tag}: {level}"))
+                .size(theme::TEXT_SIZE - 2.0)
+                .style(theme::text::secondary)
+                .shaping(text::Shaping::Advanced)
+                .into()
+        })
+        .collect::<Vec<Element<'a, Message>>>();
+
+    (!schedules.is_empty()).then(|| {
+        container(Column::with_children(schedules).spacing(2))
+            .padding(5)
+            .into()
+    })
+}
+
 #[derive(Debug, Clone, Copy)]
 enum Menu {
     RefreshConfig,
@@ -517,6 +815,10 @@ impl Menu {
 
 #[derive(Debug, Clone, Copy)]
 enum Entry {
+    Connect,
+    Retry,
+    Collapse,
+    Expand,
     MarkServerAsRead,
     MarkAsRead,
     NewPane,
@@ -524,6 +826,12 @@ enum Entry {
     Replace,
     Close(window::Id, pane_grid::Pane),
     Swap(window::Id, pane_grid::Pane),
+    ResyncUsers,
+    AuditLog,
+    ForgetForwards,
+    Notifications(Option<buffer::Notifications>),
+    /// Index of a mask of users whose actions are shown differently
+    RemoveActionStyle(usize),
     Leave,
 }
 
@@ -533,12 +841,20 @@ impl Entry {
         num_panes: usize,
         open: Option<(window::Id, pane_grid::Pane)>,
         focus: Focus,
+        is_collapsed: bool,
+        is_forward_target: bool,
+        action_masks: usize,
     ) -> Vec<Self> {
         [
             match buffer {
-                buffer::Upstream::Server(_) => {
-                    vec![Entry::MarkServerAsRead]
-                }
+                buffer::Upstream::Server(_) => vec![
+                    if is_collapsed {
+                        Entry::Expand
+                    } else {
+                        Entry::Collapse
+                    },
+                    Entry::MarkServerAsRead,
+                ],
                 buffer::Upstream::Channel(_, _) => vec![],
                 buffer::Upstream::Query(_, _) => vec![],
             },
@@ -560,22 +876,104 @@ impl Entry {
                     .chain(Some(Entry::Leave))
                     .collect(),
             },
+            match buffer {
+                buffer::Upstream::Channel(_, _) => {
+                    [Entry::ResyncUsers, Entry::AuditLog]
+                        .into_iter()
+                        .chain(
+                            is_forward_target.then_some(Entry::ForgetForwards),
+                        )
+                        .collect()
+                }
+                _ => vec![],
+            },
+            match buffer {
+                buffer::Upstream::Server(_) => vec![],
+                buffer::Upstream::Channel(_, _)
+                | buffer::Upstream::Query(_, _) => Some(None)
+                    .into_iter()
+                    .chain(buffer::Notifications::ALL.iter().copied().map(Some))
+                    .map(Entry::Notifications)
+                    .collect(),
+            },
+            match buffer {
+                buffer::Upstream::Channel(_, _) => {
+                    (0..action_masks).map(Entry::RemoveActionStyle).collect()
+                }
+                _ => vec![],
+            },
         ]
         .concat()
     }
 }
 
+/// Offers waiting for approval and the progress of transfers underway, which
+/// moves along as they do
+fn file_transfers_badge(
+    pending: usize,
+    progress: Option<f64>,
+) -> Option<String> {
+    let pending = (pending > 0).then(|| pending.to_string());
+    let progress = progress.map(|progress| format!("{:.0}%", progress * 100.0));
+
+    match (pending, progress) {
+        (Some(pending), Some(progress)) => {
+            Some(format!("{pending} · {progress}"))
+        }
+        (pending, progress) => pending.or(progress),
+    }
+}
+
+fn monitored_tooltip(monitored: &[Monitored]) -> Option<String> {
+    (!monitored.is_empty()).then(|| {
+        std::iter::once("Monitored users".to_string())
+            .chain(monitored.iter().map(|user| {
+                let presence = match user.presence {
+                    Presence::Online => "online",
+                    Presence::Offline => "offline",
+                    Presence::Unknown => "unknown",
+                };
+
+                format!("{}: {presence}", user.nick)
+            }))
+            .join("\n")
+    })
+}
+
+/// Unread messages, followed by the unread highlights among them
+fn unread_count<'a>(unread: history::Unread) -> Element<'a, Message> {
+    row![
+        text(unread.messages.to_string())
+            .size(theme::TEXT_SIZE - 2.0)
+            .style(theme::text::tertiary)
+    ]
+    .push_maybe((unread.highlights > 0).then(|| {
+        text(format!("@{}", unread.highlights))
+            .size(theme::TEXT_SIZE - 2.0)
+            .style(theme::text::unread_indicator)
+    }))
+    .spacing(4)
+    .align_y(iced::Alignment::Center)
+    .into()
+}
+
 fn upstream_buffer_button(
     panes: &Panes,
     focus: Focus,
     buffer: buffer::Upstream,
-    connected: bool,
+    status: Status,
     buffer_action: BufferAction,
     focused_buffer_action: Option<BufferFocusedAction>,
     position: sidebar::Position,
     unread_indicator: sidebar::UnreadIndicator,
     server_has_unread: bool,
     has_unread: bool,
+    unread: history::Unread,
+    // Unread highlights of a collapsed server
+    collapsed: Option<usize>,
+    is_forward_target: bool,
+    notifications: Option<buffer::Notifications>,
+    action_masks: Vec<(String, ActionStyle)>,
     width: Length,
 ) -> Element<Message> {
     let open = panes.iter().find_map(|(window_id, pane, state)| {
@@ -590,9 +988,21 @@ fn upstream_buffer_button(
         .then_some((window_id, pane))
     });
 
-    let show_unread_indicator =
-        has_unread && matches!(unread_indicator, sidebar::UnreadIndicator::Dot);
-    let show_title_indicator = has_unread
+    // A collapsed server shows the unread of its hidden buffers too
+    let is_unread = has_unread || (collapsed.is_some() && server_has_unread);
+    let show_unread_count =
+        matches!(unread_indicator, sidebar::UnreadIndicator::Count)
+            && unread.messages > 0;
+    // Counts fall back to the dot when there are none, as for a collapsed
+    // server
+    let show_unread_indicator = is_unread
+        && match unread_indicator {
+            sidebar::UnreadIndicator::Dot => true,
+            sidebar::UnreadIndicator::Count => !show_unread_count,
+            sidebar::UnreadIndicator::Title
+            | sidebar::UnreadIndicator::None => false,
+        };
+    let show_title_indicator = is_unread
         && matches!(unread_indicator, sidebar::UnreadIndicator::Title);
 
     let unread_dot_indicator_spacing =
@@ -620,19 +1030,37 @@ fn upstream_buffer_button(
 
     let row = match &buffer {
         buffer::Upstream::Server(server) => row![
-            icon::connected().style(if connected {
-                if show_unread_indicator {
-                    theme::text::unread_indicator
-                } else {
-                    theme::text::primary
+            // Collapses or expands the channels and queries of the server
+            button(icon::connected().style(match status {
+                Status::Connected => {
+                    if show_unread_indicator {
+                        theme::text::unread_indicator
+                    } else {
+                        theme::text::primary
+                    }
+                }
+                Status::Idle => theme::text::tertiary,
+                Status::Disconnected | Status::Unavailable => {
+                    theme::text::error
                 }
-            } else {
-                theme::text::error
-            }),
+            }))
+            .padding(0)
+            .style(theme::button::bare)
+            .on_press(Message::ToggleCollapsed(server.clone())),
             text(server.to_string())
-                .style(buffer_title_style)
+                .style(if matches!(status, Status::Idle) {
+                    theme::text::secondary
+                } else {
+                    buffer_title_style
+                })
                 .shaping(text::Shaping::Advanced)
         ]
+        .push_maybe(show_unread_count.then(|| unread_count(unread)))
+        .push_maybe(collapsed.filter(|unread| *unread > 0).map(|unread| {
+            text(unread.to_string())
+                .size(theme::TEXT_SIZE - 2.0)
+                .style(theme::text::tertiary)
+        }))
         .spacing(8)
         .align_y(iced::Alignment::Center),
         buffer::Upstream::Channel(_, channel) => row![]
@@ -646,6 +1074,9 @@ fn upstream_buffer_button(
                     .style(buffer_title_style)
                     .shaping(text::Shaping::Advanced),
             )
+            .push_maybe(show_unread_count.then(|| {
+                row![horizontal_space().width(8), unread_count(unread)]
+            }))
             .push(horizontal_space().width(3))
             .align_y(iced::Alignment::Center),
         buffer::Upstream::Query(_, query) => row![]
@@ -659,6 +1090,9 @@ fn upstream_buffer_button(
                     .style(buffer_title_style)
                     .shaping(text::Shaping::Advanced),
             )
+            .push_maybe(show_unread_count.then(|| {
+                row![horizontal_space().width(8), unread_count(unread)]
+            }))
             .push(horizontal_space().width(3))
             .align_y(iced::Alignment::Center),
     };
@@ -708,9 +1142,22 @@ fn upstream_buffer_button(
             }
         });
 
-    let entries = Entry::list(&buffer, panes.len(), open, focus);
+    let entries = match status {
+        Status::Connected => Entry::list(
+            &buffer,
+            panes.len(),
+            open,
+            focus,
+            collapsed.is_some(),
+            is_forward_target,
+            action_masks.len(),
+        ),
+        Status::Idle => vec![Entry::Connect],
+        Status::Disconnected => vec![Entry::Retry],
+        Status::Unavailable => vec![],
+    };
 
-    if entries.is_empty() || !connected {
+    if entries.is_empty() {
         base.into()
     } else {
         context_menu(
@@ -719,6 +1166,21 @@ fn upstream_buffer_button(
             entries,
             move |entry, length| {
                 let (content, message) = match entry {
+                    Entry::Connect => (
+                        "Connect",
+                        Some(Message::Connect(buffer.server().clone())),
+                    ),
+                    Entry::Retry => {
+                        ("Retry", Some(Message::Retry(buffer.server().clone())))
+                    }
+                    Entry::Collapse => (
+                        "Collapse",
+                        Some(Message::ToggleCollapsed(buffer.server().clone())),
+                    ),
+                    Entry::Expand => (
+                        "Expand",
+                        Some(Message::ToggleCollapsed(buffer.server().clone())),
+                    ),
                     Entry::MarkServerAsRead => (
                         "Mark entire server as read",
                         if server_has_unread {
@@ -759,9 +1221,82 @@ fn upstream_buffer_button(
                         "Swap with current pane",
                         Some(Message::Swap(window, pane)),
                     ),
+                    Entry::ResyncUsers => (
+                        "Resync users",
+                        match &buffer {
+                            buffer::Upstream::Channel(server, channel) => {
+                                Some(Message::ResyncUsers(
+                                    server.clone(),
+                                    channel.clone(),
+                                ))
+                            }
+                            _ => None,
+                        },
+                    ),
+                    Entry::AuditLog => (
+                        "Audit log",
+                        match &buffer {
+                            buffer::Upstream::Channel(server, channel) => {
+                                Some(Message::AuditLog(
+                                    server.clone(),
+                                    channel.clone(),
+                                ))
+                            }
+                            _ => None,
+                        },
+                    ),
+                    Entry::ForgetForwards => (
+                        "Forget channel forward",
+                        match &buffer {
+                            buffer::Upstream::Channel(server, channel) => {
+                                Some(Message::ForgetForwards(
+                                    server.clone(),
+                                    channel.clone(),
+                                ))
+                            }
+                            _ => None,
+                        },
+                    ),
+                    Entry::Notifications(entry) => (
+                        match entry {
+                            None => "Notify: default",
+                            Some(buffer::Notifications::All) => {
+                                "Notify: all messages"
+                            }
+                            Some(buffer::Notifications::Highlights) => {
+                                "Notify: highlights only"
+                            }
+                            Some(buffer::Notifications::None) => {
+                                "Notify: never"
+                            }
+                        },
+                        (entry != notifications).then(|| {
+                            Message::SetNotifications(buffer.clone(), entry)
+                        }),
+                    ),
+                    Entry::RemoveActionStyle(index) => {
+                        let (mask, style) = &action_masks[index];
+                        let content = match style {
+                            ActionStyle::Plain => {
+                                format!("Show /me of {mask} as actions")
+                            }
+                            ActionStyle::Dimmed | ActionStyle::Action => {
+                                format!("Stop dimming /me of {mask}")
+                            }
+                        };
+
+                        return entry_button(
+                            content,
+                            Some(Message::RemoveActionStyle(
+                                buffer.clone(),
+                                mask.clone(),
+                            )),
+                            length,
+                        );
+                    }
                     Entry::Leave => (
                         match &buffer {
-                            buffer::Upstream::Server(_) => "Leave server",
+                            buffer::Upstream::Server(_) => "Disconnect",
                             buffer::Upstream::Channel(_, _) => "Leave channel",
                             buffer::Upstream::Query(_, _) => "Close query",
                         },
@@ -769,16 +1304,22 @@ fn upstream_buffer_button(
                     ),
                 };
 
-                button(text(content))
-                    .width(length)
-                    .padding(5)
-                    .style(|theme, status| {
-                        theme::button::primary(theme, status, false)
-                    })
-                    .on_press_maybe(message)
-                    .into()
+                entry_button(content, message, length)
             },
         )
         .into()
     }
 }
+
+fn entry_button<'a>(
+    content: impl text::IntoFragment<'a>,
+    message: Option<Message>,
+    length: Length,
+) -> Element<'a, Message> {
+    button(text(content))
+        .width(length)
+        .padding(5)
+        .style(|theme, status| theme::button::primary(theme, status, false))
+        .on_press_maybe(message)
+        .into()
+}
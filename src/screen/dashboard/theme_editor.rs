@@ -372,7 +372,12 @@ fn components() -> impl Iterator<Item = Component> {
         .chain(Text::iter().map(Component::Text))
         .chain(
             Buffer::iter()
-                .filter(|buffer| !matches!(buffer, Buffer::ServerMessages(_)))
+                .filter(|buffer| {
+                    !matches!(
+                        buffer,
+                        Buffer::ServerMessages(_) | Buffer::RowBackground(_)
+                    )
+                })
                 .map(Component::Buffer),
         )
         .chain(
@@ -380,6 +385,11 @@ fn components() -> impl Iterator<Item = Component> {
                 .map(Buffer::ServerMessages)
                 .map(Component::Buffer),
         )
+        .chain(
+            RowBackground::iter()
+                .map(Buffer::RowBackground)
+                .map(Component::Buffer),
+        )
         .chain(Button::iter().map(Buttons::Primary).map(Component::Buttons))
         .chain(
             Button::iter()
@@ -521,6 +531,8 @@ pub enum Buffer {
     Code,
     Highlight,
     Nickname,
+    #[strum(to_string = "row-background-{0}")]
+    RowBackground(RowBackground),
     Selection,
     #[strum(to_string = "server-message-{0}")]
     ServerMessages(ServerMessages),
@@ -541,6 +553,9 @@ impl Buffer {
             Buffer::Code => Some(colors.code),
             Buffer::Highlight => Some(colors.highlight),
             Buffer::Nickname => Some(colors.nickname),
+            Buffer::RowBackground(row_background) => {
+                row_background.color(&colors.row_background)
+            }
             Buffer::Selection => Some(colors.selection),
             Buffer::ServerMessages(server_messages) => {
                 server_messages.color(&colors.server_messages)
@@ -580,6 +595,9 @@ impl Buffer {
             Buffer::Nickname => {
                 colors.nickname = color.unwrap_or(Color::TRANSPARENT);
             }
+            Buffer::RowBackground(row_background) => {
+                row_background.update(&mut colors.row_background, color);
+            }
             Buffer::Selection => {
                 colors.selection = color.unwrap_or(Color::TRANSPARENT);
             }
@@ -612,6 +630,7 @@ pub enum ServerMessages {
     StandardReplyWarn,
     StandardReplyNote,
     Wallops,
+    Ctcp,
     Default,
 }
 
@@ -629,6 +648,7 @@ impl ServerMessages {
             ServerMessages::StandardReplyWarn => colors.standard_reply_warn,
             ServerMessages::StandardReplyNote => colors.standard_reply_note,
             ServerMessages::Wallops => colors.wallops,
+            ServerMessages::Ctcp => colors.ctcp,
             ServerMessages::Default => Some(colors.default),
         }
     }
@@ -654,6 +674,7 @@ impl ServerMessages {
                 colors.standard_reply_note = color;
             }
             ServerMessages::Wallops => colors.wallops = color,
+            ServerMessages::Ctcp => colors.ctcp = color,
             ServerMessages::Default => {
                 colors.default = color.unwrap_or(Color::TRANSPARENT);
             }
@@ -661,6 +682,44 @@ impl ServerMessages {
     }
 }
 
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Default, strum::Display, strum::EnumIter,
+)]
+#[strum(serialize_all = "kebab-case")]
+pub enum RowBackground {
+    #[default]
+    Own,
+    Highlight,
+    Action,
+    Server,
+    Error,
+    Alternate,
+}
+
+impl RowBackground {
+    fn color(&self, colors: &theme::RowBackground) -> Option<Color> {
+        match self {
+            RowBackground::Own => colors.own,
+            RowBackground::Highlight => colors.highlight,
+            RowBackground::Action => colors.action,
+            RowBackground::Server => colors.server,
+            RowBackground::Error => colors.error,
+            RowBackground::Alternate => colors.alternate,
+        }
+    }
+
+    fn update(&self, colors: &mut theme::RowBackground, color: Option<Color>) {
+        match self {
+            RowBackground::Own => colors.own = color,
+            RowBackground::Highlight => colors.highlight = color,
+            RowBackground::Action => colors.action = color,
+            RowBackground::Server => colors.server = color,
+            RowBackground::Error => colors.error = color,
+            RowBackground::Alternate => colors.alternate = color,
+        }
+    }
+}
+
 #[derive(
     Debug, Clone, Copy, PartialEq, Eq, strum::Display, strum::EnumIter,
 )]
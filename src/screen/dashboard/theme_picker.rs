@@ -0,0 +1,120 @@
+use data::appearance::theme::Preview;
+use iced::widget::{
+    Column, Row, Space, button, column, container, row, scrollable, text,
+};
+use iced::{Alignment, Color, Length, padding};
+
+use crate::widget::Element;
+use crate::{icon, theme};
+
+const SWATCH_SIZE: f32 = 12.0;
+
+/// Lists themes with a few of their colors, opened by `/theme` without a
+/// name. Themes are only peeked at, they're loaded once picked.
+#[derive(Debug)]
+pub struct ThemePicker {
+    themes: Vec<Result<Preview, (String, String)>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Pick(String),
+    Close,
+}
+
+pub enum Event {
+    Picked(String),
+    Closed,
+}
+
+impl ThemePicker {
+    pub fn new(
+        previews: Vec<
+            Result<Preview, (String, data::appearance::theme::Error)>,
+        >,
+    ) -> Self {
+        Self {
+            themes: previews
+                .into_iter()
+                .map(|preview| {
+                    preview.map_err(|(name, error)| (name, error.to_string()))
+                })
+                .collect(),
+        }
+    }
+
+    pub fn update(&mut self, message: Message) -> Event {
+        match message {
+            Message::Pick(name) => Event::Picked(name),
+            Message::Close => Event::Closed,
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let themes = Column::with_children(self.themes.iter().map(|entry| {
+            match entry {
+                Ok(preview) => button(
+                    row![preview_swatches(preview), text(&preview.name)]
+                        .spacing(8)
+                        .align_y(Alignment::Center),
+                )
+                .padding([4, 6])
+                .width(Length::Fill)
+                .style(|theme, status| {
+                    theme::button::secondary(theme, status, false)
+                })
+                .on_press(Message::Pick(preview.name.clone()))
+                .into(),
+                Err((name, error)) => column![
+                    text(name),
+                    text(error)
+                        .style(theme::text::error)
+                        .shaping(text::Shaping::Advanced),
+                ]
+                .padding([4, 6])
+                .into(),
+            }
+        }))
+        .spacing(2);
+
+        container(
+            column![
+                row![
+                    text("Themes")
+                        .style(theme::text::tertiary)
+                        .width(Length::Fill),
+                    button(icon::cancel())
+                        .padding([2, 4])
+                        .style(theme::button::bare)
+                        .on_press(Message::Close),
+                ]
+                .padding(padding::bottom(4)),
+                container(scrollable(themes).style(theme::scrollable::hidden))
+                    .max_height(360),
+            ]
+            .spacing(2)
+            .width(Length::Fixed(320.0)),
+        )
+        .padding(8)
+        .style(theme::container::tooltip)
+        .into()
+    }
+}
+
+fn preview_swatches<'a>(preview: &Preview) -> Element<'a, Message> {
+    let background = preview.background;
+
+    container(
+        Row::with_children(preview.swatches.iter().copied().map(swatch))
+            .spacing(2)
+            .padding(3),
+    )
+    .style(move |theme| theme::container::swatch(theme, background))
+    .into()
+}
+
+fn swatch<'a>(color: Color) -> Element<'a, Message> {
+    container(Space::new(SWATCH_SIZE, SWATCH_SIZE))
+        .style(move |theme| theme::container::swatch(theme, color))
+        .into()
+}
@@ -9,42 +9,64 @@ use crate::{icon, theme};
 #[derive(Debug, Clone)]
 pub enum Message {
     RefreshConfiguration,
+    OpenConfigurationFile,
     OpenConfigurationDirectory,
     OpenWikiWebsite,
+    StartWithLastGood,
 }
 
 #[derive(Debug, Clone)]
 pub enum Event {
     RefreshConfiguration,
+    OpenConfigurationFile,
+    OpenConfigurationDirectory,
+    StartWithLastGood,
 }
 
 #[derive(Debug, Clone)]
 pub struct Help {
     error: config::Error,
+    /// Whether the config from the last time it loaded can be started with
+    has_last_good: bool,
 }
 
 impl Help {
     pub fn new(error: config::Error) -> Self {
-        Help { error }
+        Help {
+            error,
+            has_last_good: Config::has_last_good(),
+        }
     }
 
     pub fn update(&mut self, message: Message) -> Option<Event> {
         match message {
             Message::RefreshConfiguration => Some(Event::RefreshConfiguration),
+            Message::OpenConfigurationFile => {
+                Some(Event::OpenConfigurationFile)
+            }
             Message::OpenConfigurationDirectory => {
-                let _ = open::that_detached(Config::config_dir());
-
-                None
+                Some(Event::OpenConfigurationDirectory)
             }
             Message::OpenWikiWebsite => {
                 let _ = open::that_detached(WIKI_WEBSITE);
 
                 None
             }
+            Message::StartWithLastGood => Some(Event::StartWithLastGood),
         }
     }
 
     pub fn view<'a>(&self) -> Element<'a, Message> {
+        let config_file_button = button(
+            container(text("Open Config File"))
+                .align_x(alignment::Horizontal::Center)
+                .width(Length::Fill),
+        )
+        .padding(5)
+        .width(Length::Fill)
+        .style(|theme, status| theme::button::secondary(theme, status, false))
+        .on_press(Message::OpenConfigurationFile);
+
         let config_button = button(
             container(text("Open Config Directory"))
                 .align_x(alignment::Horizontal::Center)
@@ -75,6 +97,21 @@ impl Help {
         .style(|theme, status| theme::button::secondary(theme, status, false))
         .on_press(Message::RefreshConfiguration);
 
+        // The broken config is set aside, not overwritten
+        let last_good_button = self.has_last_good.then(|| {
+            button(
+                container(text("Start With Last Known Good Config"))
+                    .align_x(alignment::Horizontal::Center)
+                    .width(Length::Fill),
+            )
+            .padding(5)
+            .width(Length::Fill)
+            .style(|theme, status| {
+                theme::button::secondary(theme, status, false)
+            })
+            .on_press(Message::StartWithLastGood)
+        });
+
         let content = column![]
             .push(icon::error().style(theme::text::error).size(35))
             .push(vertical_space().height(10))
@@ -86,9 +123,11 @@ impl Help {
                 column![]
                     .width(250)
                     .spacing(4)
+                    .push(config_file_button)
                     .push(config_button)
                     .push(wiki_button)
-                    .push(refresh_button),
+                    .push(refresh_button)
+                    .push_maybe(last_good_button),
             )
             .align_x(iced::Alignment::Center);
 
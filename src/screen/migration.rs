@@ -16,6 +16,7 @@ pub enum Message {
 #[derive(Debug, Clone)]
 pub enum Event {
     RefreshConfiguration,
+    OpenConfigurationDirectory,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -33,9 +34,7 @@ impl Migration {
         match message {
             Message::RefreshConfiguration => Some(Event::RefreshConfiguration),
             Message::OpenConfigurationDirectory => {
-                let _ = open::that_detached(Config::config_dir());
-
-                None
+                Some(Event::OpenConfigurationDirectory)
             }
             Message::OpenMigrationWebsite => {
                 let _ = open::that_detached(MIGRATION_WEBSITE);
@@ -0,0 +1,79 @@
+use chrono::Local;
+use data::profile::Profile;
+use iced::widget::{
+    button, column, container, horizontal_space, row, scrollable, text,
+    vertical_space,
+};
+use iced::{Length, alignment};
+
+use crate::widget::Element;
+use crate::{font, theme};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    Select(Option<String>),
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    Selected(Option<String>),
+}
+
+/// Shown on startup when several profiles exist and none was given with
+/// `--profile`.
+#[derive(Debug, Clone)]
+pub struct Profiles {
+    profiles: Vec<Profile>,
+}
+
+impl Profiles {
+    pub fn new(profiles: Vec<Profile>) -> Self {
+        Self { profiles }
+    }
+
+    pub fn update(&mut self, message: Message) -> Option<Event> {
+        match message {
+            Message::Select(profile) => Some(Event::Selected(profile)),
+        }
+    }
+
+    pub fn view<'a>(&'a self) -> Element<'a, Message> {
+        let profiles = column(self.profiles.iter().map(|profile| {
+            let last_used = match profile.last_used {
+                Some(last_used) => last_used
+                    .with_timezone(&Local)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+                None => "never used".to_string(),
+            };
+
+            button(row![
+                text(profile.label()),
+                horizontal_space(),
+                text(last_used).style(theme::text::secondary),
+            ])
+            .padding(5)
+            .width(Length::Fill)
+            .style(|theme, status| {
+                theme::button::secondary(theme, status, false)
+            })
+            .on_press(Message::Select(profile.name.clone()))
+            .into()
+        }))
+        .spacing(4);
+
+        let content = column![]
+            .spacing(1)
+            .push(text("Choose a profile").font(font::MONO_BOLD.clone()))
+            .push(vertical_space().height(10))
+            .push(container(scrollable(profiles)).width(350).max_height(400))
+            .align_x(iced::Alignment::Center);
+
+        container(content)
+            .align_x(alignment::Horizontal::Center)
+            .align_y(alignment::Vertical::Center)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
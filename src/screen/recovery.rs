@@ -0,0 +1,116 @@
+use iced::widget::{button, column, container, text, vertical_space};
+use iced::{Length, alignment};
+
+use crate::widget::Element;
+use crate::{icon, theme};
+
+#[derive(Debug, Clone)]
+pub enum Message {
+    ResetLayout,
+    TryLastGood,
+    OpenDataDirectory,
+}
+
+#[derive(Debug, Clone)]
+pub enum Event {
+    ResetLayout,
+    TryLastGood,
+    OpenDataDirectory,
+}
+
+/// Shown when the saved dashboard can't be loaded, rather than silently
+/// starting with an empty one. Either way the broken file is set aside.
+#[derive(Debug, Clone)]
+pub struct Recovery {
+    error: String,
+    /// Whether the dashboard from the last time it loaded can be tried
+    has_last_good: bool,
+}
+
+impl Recovery {
+    pub fn new(error: data::dashboard::Error) -> Self {
+        Recovery {
+            error: error.to_string(),
+            has_last_good: data::Dashboard::has_last_good(),
+        }
+    }
+
+    /// Trying the last known good dashboard failed as well
+    pub fn last_good_failed(&mut self, error: data::dashboard::Error) {
+        self.error = error.to_string();
+        self.has_last_good = false;
+    }
+
+    pub fn update(&mut self, message: Message) -> Option<Event> {
+        match message {
+            Message::ResetLayout => Some(Event::ResetLayout),
+            Message::TryLastGood => Some(Event::TryLastGood),
+            Message::OpenDataDirectory => Some(Event::OpenDataDirectory),
+        }
+    }
+
+    pub fn view<'a>(&self) -> Element<'a, Message> {
+        let reset_button = button(
+            container(text("Reset Layout"))
+                .align_x(alignment::Horizontal::Center)
+                .width(Length::Fill),
+        )
+        .padding(5)
+        .width(Length::Fill)
+        .style(|theme, status| theme::button::secondary(theme, status, false))
+        .on_press(Message::ResetLayout);
+
+        let last_good_button = self.has_last_good.then(|| {
+            button(
+                container(text("Try Last Known Good Layout"))
+                    .align_x(alignment::Horizontal::Center)
+                    .width(Length::Fill),
+            )
+            .padding(5)
+            .width(Length::Fill)
+            .style(|theme, status| {
+                theme::button::secondary(theme, status, false)
+            })
+            .on_press(Message::TryLastGood)
+        });
+
+        let data_button = button(
+            container(text("Open Data Directory"))
+                .align_x(alignment::Horizontal::Center)
+                .width(Length::Fill),
+        )
+        .padding(5)
+        .width(Length::Fill)
+        .style(|theme, status| theme::button::secondary(theme, status, false))
+        .on_press(Message::OpenDataDirectory);
+
+        let content = column![]
+            .push(icon::error().style(theme::text::error).size(35))
+            .push(vertical_space().height(10))
+            .push(text("Error reading saved layout"))
+            .push(vertical_space().height(10))
+            .push(text(self.error.clone()).style(theme::text::error))
+            .push(vertical_space().height(10))
+            .push(
+                text("The broken layout is kept with a .broken suffix")
+                    .style(theme::text::secondary),
+            )
+            .push(vertical_space().height(10))
+            .push(
+                column![]
+                    .width(250)
+                    .spacing(4)
+                    .push(reset_button)
+                    .push_maybe(last_good_button)
+                    .push(data_button),
+            )
+            .align_x(iced::Alignment::Center);
+
+        container(content)
+            .align_x(alignment::Horizontal::Center)
+            .align_y(alignment::Vertical::Center)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
+    }
+}
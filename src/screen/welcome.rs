@@ -1,5 +1,5 @@
 use data::Config;
-use data::environment::WIKI_WEBSITE;
+use data::environment::{self, WIKI_WEBSITE};
 use iced::widget::{
     button, column, container, image, row, text, vertical_space,
 };
@@ -18,6 +18,7 @@ pub enum Message {
 #[derive(Debug, Clone)]
 pub enum Event {
     RefreshConfiguration,
+    OpenConfigurationDirectory,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -35,9 +36,7 @@ impl Welcome {
         match message {
             Message::RefreshConfiguration => Some(Event::RefreshConfiguration),
             Message::OpenConfigurationDirectory => {
-                let _ = open::that_detached(Config::config_dir());
-
-                None
+                Some(Event::OpenConfigurationDirectory)
             }
             Message::OpenWikiWebsite => {
                 let _ = open::that_detached(WIKI_WEBSITE);
@@ -90,7 +89,8 @@ impl Welcome {
             .push(text("Halloy is configured through a config file."))
             .push(row![
                 text("You can find the "),
-                text("config.toml").style(theme::text::action),
+                text(environment::config_file_name(environment::profile()))
+                    .style(theme::text::action),
                 text(" file at the following path:"),
             ])
             .push(vertical_space().height(8))
@@ -5,32 +5,48 @@ use data::{config, server};
 use futures::Stream;
 use iced::Subscription;
 
+/// Bumping `retries` replaces the server's stream with a new one, which
+/// connects right away
 pub fn run(
     entry: server::Entry,
     proxy: Option<config::Proxy>,
+    startup: Option<stream::Startup>,
+    retries: usize,
 ) -> Subscription<stream::Update> {
     struct State {
         entry: server::Entry,
         proxy: Option<config::Proxy>,
+        startup: Option<stream::Startup>,
+        retries: usize,
     }
 
     impl State {
         fn run(&self) -> impl Stream<Item = stream::Update> + use<> {
-            stream::run(self.entry.clone(), self.proxy.clone())
+            stream::run(self.entry.clone(), self.proxy.clone(), self.startup)
         }
     }
 
     impl PartialEq for State {
         fn eq(&self, other: &Self) -> bool {
             self.entry.server.eq(&other.entry.server)
+                && self.retries == other.retries
         }
     }
 
     impl Hash for State {
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
             self.entry.server.hash(state);
+            self.retries.hash(state);
         }
     }
 
-    Subscription::run_with(State { entry, proxy }, State::run)
+    Subscription::run_with(
+        State {
+            entry,
+            proxy,
+            startup,
+            retries,
+        },
+        State::run,
+    )
 }
@@ -0,0 +1,109 @@
+//! Detects stalls of the update loop.
+//!
+//! A ping carrying the time it was sent is delivered to `update` on an
+//! interval. If it arrives late, whatever ran before it held up the loop, so
+//! the stall is logged along with the last message handled. Only available
+//! with the `watchdog` feature.
+
+use std::time::{Duration, Instant};
+
+use iced::Subscription;
+
+/// Stalls shorter than this aren't reported
+#[cfg(feature = "watchdog")]
+const THRESHOLD: Duration = Duration::from_millis(250);
+/// Stalls longer than this are always caught, shorter ones only when a ping
+/// happens to be sent during them
+#[cfg(feature = "watchdog")]
+const PING_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Stall {
+    pub duration: Duration,
+    /// Message handled before the late ping arrived
+    pub handling: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stats {
+    pub count: usize,
+    pub total: Duration,
+    pub longest: Option<Stall>,
+    pub last: Option<Stall>,
+}
+
+#[derive(Debug, Default)]
+pub struct Watchdog {
+    #[cfg(feature = "watchdog")]
+    handling: &'static str,
+    #[cfg(feature = "watchdog")]
+    stats: Stats,
+}
+
+impl Watchdog {
+    /// Marks `message` as the one currently being handled.
+    #[cfg(feature = "watchdog")]
+    pub fn handling(&mut self, message: &'static str) {
+        self.handling = message;
+    }
+
+    #[cfg(not(feature = "watchdog"))]
+    pub fn handling(&mut self, _message: &'static str) {}
+
+    /// Checks the round trip of a ping sent at `sent_at`, returning the stall
+    /// if it took too long.
+    #[cfg(feature = "watchdog")]
+    pub fn pong(&mut self, sent_at: Instant) -> Option<Stall> {
+        let duration = sent_at.elapsed();
+
+        if duration < THRESHOLD {
+            return None;
+        }
+
+        let stall = Stall {
+            duration,
+            handling: self.handling,
+        };
+
+        self.stats.count += 1;
+        self.stats.total += duration;
+        self.stats.last = Some(stall);
+
+        if self
+            .stats
+            .longest
+            .is_none_or(|longest| longest.duration < duration)
+        {
+            self.stats.longest = Some(stall);
+        }
+
+        Some(stall)
+    }
+
+    #[cfg(not(feature = "watchdog"))]
+    pub fn pong(&mut self, _sent_at: Instant) -> Option<Stall> {
+        None
+    }
+
+    /// `None` when built without the `watchdog` feature
+    #[cfg(feature = "watchdog")]
+    pub fn stats(&self) -> Option<Stats> {
+        Some(self.stats)
+    }
+
+    #[cfg(not(feature = "watchdog"))]
+    pub fn stats(&self) -> Option<Stats> {
+        None
+    }
+}
+
+/// Emits the time each ping was sent at.
+#[cfg(feature = "watchdog")]
+pub fn ping() -> Subscription<Instant> {
+    iced::time::every(PING_INTERVAL)
+}
+
+#[cfg(not(feature = "watchdog"))]
+pub fn ping() -> Subscription<Instant> {
+    Subscription::none()
+}
@@ -7,6 +7,7 @@ pub use self::combo_box::combo_box;
 pub use self::context_menu::context_menu;
 pub use self::decorate::decorate;
 pub use self::double_pass::double_pass;
+pub use self::key_chord::key_chord;
 pub use self::key_press::key_press;
 pub use self::message_content::message_content;
 pub use self::modal::modal;
@@ -25,6 +26,8 @@ pub mod context_menu;
 pub mod decorate;
 pub mod double_click;
 pub mod double_pass;
+pub mod draggable;
+pub mod key_chord;
 pub mod key_press;
 pub mod message_content;
 pub mod modal;
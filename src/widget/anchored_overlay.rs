@@ -25,6 +25,7 @@ pub fn anchored_overlay<'a, Message: 'a>(
 pub enum Anchor {
     AboveTop,
     BelowTopCentered,
+    BelowTopRight,
 }
 
 struct AnchoredOverlay<'a, Message> {
@@ -207,7 +208,9 @@ impl<Message> overlay::Overlay<Message, Theme, Renderer>
             // From top of base to top of viewport
             Anchor::AboveTop => self.position.y,
             // From top of base to bottom of viewport
-            Anchor::BelowTopCentered => bounds.height - self.position.y,
+            Anchor::BelowTopCentered | Anchor::BelowTopRight => {
+                bounds.height - self.position.y
+            }
         };
 
         let limits = layout::Limits::new(
@@ -235,6 +238,11 @@ impl<Message> overlay::Overlay<Message, Theme, Renderer>
                 self.base_layout.width / 2.0 - node.size().width / 2.0,
                 self.offset,
             ),
+            // Offset below the top and from the right
+            Anchor::BelowTopRight => Vector::new(
+                self.base_layout.width - node.size().width - self.offset,
+                self.offset,
+            ),
         };
 
         node.move_to(self.position + translation)
@@ -177,6 +177,22 @@ where
         shell: &mut Shell<'_, Message>,
         viewport: &Rectangle,
     ) {
+        // Menus nested in the base open instead of this one
+        self.base.as_widget_mut().update(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        );
+
+        if shell.is_event_captured() {
+            return;
+        }
+
         let state = tree.state.downcast_mut::<State>();
         let prev_status = state.status;
 
@@ -206,6 +222,7 @@ where
 
         if let Some(position) = position {
             state.status = Status::Open(position);
+            shell.capture_event();
         }
 
         match (state.status, prev_status) {
@@ -215,17 +232,6 @@ where
             }
             _ => {}
         }
-
-        self.base.as_widget_mut().update(
-            &mut tree.children[0],
-            event,
-            layout,
-            cursor,
-            renderer,
-            clipboard,
-            shell,
-            viewport,
-        );
     }
 
     fn mouse_interaction(
@@ -0,0 +1,202 @@
+use data::dashboard::Placement;
+use iced::advanced::renderer::{Quad, Renderer as _};
+use iced::advanced::widget::Tree;
+use iced::advanced::{Clipboard, Layout, Shell, mouse};
+use iced::{Point, Rectangle, event};
+
+use super::{Element, Renderer, decorate};
+use crate::theme::Theme;
+
+/// How far the cursor moves while pressed before it's a drag, not a click
+const THRESHOLD: f32 = 4.0;
+const INDICATOR_WIDTH: f32 = 2.0;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Axis {
+    Vertical,
+    Horizontal,
+}
+
+/// Content which can be dragged onto others along `axis`. While something
+/// else is dragged and `is_drop_target`, hovering it tells where it'd be
+/// placed, or `None` once the cursor leaves. `indicator` draws where the
+/// dragged content would be placed next to this one.
+pub fn draggable<'a, Message>(
+    content: impl Into<Element<'a, Message>>,
+    axis: Axis,
+    is_drop_target: bool,
+    indicator: Option<Placement>,
+    on_drag: Message,
+    on_hover: impl Fn(Option<Placement>) -> Message + 'a,
+    on_drop: Message,
+) -> Element<'a, Message>
+where
+    Message: Clone + 'a,
+{
+    decorate(content)
+        .update(
+            move |state: &mut Internal,
+                  inner: &mut Element<'a, Message>,
+                  tree: &mut Tree,
+                  event: &iced::Event,
+                  layout: Layout<'_>,
+                  cursor: mouse::Cursor,
+                  renderer: &Renderer,
+                  clipboard: &mut dyn Clipboard,
+                  shell: &mut Shell<'_, Message>,
+                  viewport: &Rectangle| {
+                let bounds = layout.bounds();
+
+                match event {
+                    event::Event::Mouse(mouse::Event::ButtonPressed(
+                        mouse::Button::Left,
+                    )) => {
+                        state.pressed_at = cursor.position_over(bounds);
+                    }
+                    event::Event::Mouse(mouse::Event::CursorMoved {
+                        position,
+                    }) => {
+                        if let Some(pressed_at) = state.pressed_at {
+                            if !state.is_dragging
+                                && pressed_at.distance(*position) > THRESHOLD
+                            {
+                                state.is_dragging = true;
+                                shell.publish(on_drag.clone());
+                            }
+                        }
+
+                        let hovered = cursor
+                            .position_over(bounds)
+                            .filter(|_| is_drop_target)
+                            .map(|position| placement(axis, bounds, position));
+
+                        if hovered != state.hovered {
+                            state.hovered = hovered;
+                            shell.publish(on_hover(hovered));
+                        }
+                    }
+                    event::Event::Mouse(mouse::Event::ButtonReleased(
+                        mouse::Button::Left,
+                    )) => {
+                        state.pressed_at = None;
+
+                        if state.is_dragging {
+                            state.is_dragging = false;
+                            shell.publish(on_drop.clone());
+
+                            // Ends the press without clicking the content
+                            inner.as_widget_mut().update(
+                                tree,
+                                event,
+                                layout,
+                                mouse::Cursor::Unavailable,
+                                renderer,
+                                clipboard,
+                                shell,
+                                viewport,
+                            );
+                            shell.capture_event();
+
+                            return;
+                        }
+                    }
+                    _ => {}
+                }
+
+                inner.as_widget_mut().update(
+                    tree, event, layout, cursor, renderer, clipboard, shell,
+                    viewport,
+                );
+            },
+        )
+        .draw(
+            move |_state: &Internal,
+                  inner: &Element<'a, Message>,
+                  tree: &Tree,
+                  renderer: &mut Renderer,
+                  theme: &Theme,
+                  style: &iced::advanced::renderer::Style,
+                  layout: Layout,
+                  cursor: mouse::Cursor,
+                  viewport: &Rectangle| {
+                inner.as_widget().draw(
+                    tree, renderer, theme, style, layout, cursor, viewport,
+                );
+
+                let Some(placement) = indicator else {
+                    return;
+                };
+
+                let bounds = layout.bounds();
+
+                let line = match (axis, placement) {
+                    (Axis::Vertical, Placement::Before) => Rectangle {
+                        height: INDICATOR_WIDTH,
+                        ..bounds
+                    },
+                    (Axis::Vertical, Placement::After) => Rectangle {
+                        y: bounds.y + bounds.height - INDICATOR_WIDTH,
+                        height: INDICATOR_WIDTH,
+                        ..bounds
+                    },
+                    (Axis::Horizontal, Placement::Before) => Rectangle {
+                        width: INDICATOR_WIDTH,
+                        ..bounds
+                    },
+                    (Axis::Horizontal, Placement::After) => Rectangle {
+                        x: bounds.x + bounds.width - INDICATOR_WIDTH,
+                        width: INDICATOR_WIDTH,
+                        ..bounds
+                    },
+                };
+
+                renderer.fill_quad(
+                    Quad {
+                        bounds: line,
+                        border: iced::Border::default(),
+                        shadow: iced::Shadow::default(),
+                    },
+                    theme.colors().buffer.border_selected,
+                );
+            },
+        )
+        .mouse_interaction(
+            move |state: &Internal,
+                  inner: &Element<'a, Message>,
+                  tree: &Tree,
+                  layout: Layout<'_>,
+                  cursor: mouse::Cursor,
+                  viewport: &Rectangle,
+                  renderer: &Renderer| {
+                if state.is_dragging {
+                    mouse::Interaction::Grabbing
+                } else {
+                    inner.as_widget().mouse_interaction(
+                        tree, layout, cursor, viewport, renderer,
+                    )
+                }
+            },
+        )
+        .into()
+}
+
+/// Whether `position` is in the first or second half of `bounds`
+fn placement(axis: Axis, bounds: Rectangle, position: Point) -> Placement {
+    let is_before = match axis {
+        Axis::Vertical => position.y < bounds.center_y(),
+        Axis::Horizontal => position.x < bounds.center_x(),
+    };
+
+    if is_before {
+        Placement::Before
+    } else {
+        Placement::After
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Internal {
+    pressed_at: Option<Point>,
+    is_dragging: bool,
+    hovered: Option<Placement>,
+}
@@ -0,0 +1,441 @@
+use std::time::{Duration, Instant};
+
+use iced::advanced::{Clipboard, Layout, Shell, widget};
+use iced::keyboard::key::Named;
+use iced::keyboard::{Key, Modifiers};
+use iced::{Event, Rectangle, keyboard, mouse, window};
+
+use super::{Element, Renderer, decorate};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A key combination, optionally followed by a second one which has to be
+/// pressed within the timeout, e.g. <kbd>ctrl</kbd> + <kbd>x</kbd> then
+/// <kbd>ctrl</kbd> + <kbd>o</kbd>.
+#[derive(Debug, Clone)]
+pub struct Chord {
+    first: (Key, Modifiers),
+    second: Option<(Key, Modifiers)>,
+    timeout: Duration,
+}
+
+impl Chord {
+    pub fn new(key: Key, modifiers: Modifiers) -> Self {
+        Self {
+            first: (key, modifiers),
+            second: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    pub fn then(mut self, key: Key, modifiers: Modifiers) -> Self {
+        self.second = Some((key, modifiers));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+}
+
+/// Publishes `on_press` when the chord is pressed, and `on_release` when the
+/// last key of the chord is released afterwards.
+///
+/// The first key combination of a two key chord is held back from `base`
+/// until the chord completes. If it doesn't, the key press is replayed to
+/// `base` before the interrupting event.
+pub fn key_chord<'a, Message>(
+    base: impl Into<Element<'a, Message>>,
+    chord: Chord,
+    on_press: Message,
+    on_release: Option<Message>,
+) -> Element<'a, Message>
+where
+    Message: 'a + Clone,
+{
+    decorate(base)
+        .update(
+            move |state: &mut Internal,
+                  inner: &mut Element<'a, Message>,
+                  tree: &mut widget::Tree,
+                  event: &Event,
+                  layout: Layout<'_>,
+                  cursor: mouse::Cursor,
+                  renderer: &Renderer,
+                  clipboard: &mut dyn Clipboard,
+                  shell: &mut Shell<'_, Message>,
+                  viewport: &Rectangle| {
+                let mut forward =
+                    |event: &Event, shell: &mut Shell<'_, Message>| {
+                        inner.as_widget_mut().update(
+                            tree, event, layout, cursor, renderer, clipboard,
+                            shell, viewport,
+                        );
+                    };
+
+                let outcome = match event {
+                    Event::Keyboard(keyboard::Event::KeyPressed {
+                        key,
+                        modifiers,
+                        ..
+                    }) => {
+                        let now = Instant::now();
+
+                        let press = |progress: &mut Progress| {
+                            progress.press(
+                                &chord,
+                                key,
+                                *modifiers,
+                                on_release.is_some(),
+                                now,
+                            )
+                        };
+
+                        match press(&mut state.progress) {
+                            Outcome::Interrupted => {
+                                if let Some(pending) = state.pending.take() {
+                                    forward(&pending, shell);
+                                }
+
+                                // The interrupting key press may start the
+                                // chord again
+                                press(&mut state.progress)
+                            }
+                            outcome => outcome,
+                        }
+                    }
+                    Event::Keyboard(keyboard::Event::KeyReleased {
+                        key,
+                        ..
+                    }) => state.progress.release(key),
+                    Event::Window(window::Event::RedrawRequested(now)) => {
+                        state.progress.expire(&chord, *now)
+                    }
+                    Event::Window(window::Event::Unfocused) => {
+                        state.pending = None;
+                        state.progress.reset()
+                    }
+                    _ => Outcome::Ignored,
+                };
+
+                match outcome {
+                    Outcome::Ignored => forward(event, shell),
+                    Outcome::Started => {
+                        state.pending = Some(event.clone());
+                        shell.request_redraw_at(Instant::now() + chord.timeout);
+                        shell.capture_event();
+                    }
+                    Outcome::Held => shell.capture_event(),
+                    Outcome::Pressed => {
+                        state.pending = None;
+                        shell.publish(on_press.clone());
+                        shell.capture_event();
+                    }
+                    Outcome::Released => {
+                        if let Some(on_release) = &on_release {
+                            shell.publish(on_release.clone());
+                        }
+
+                        forward(event, shell);
+                    }
+                    Outcome::Interrupted => {
+                        if let Some(pending) = state.pending.take() {
+                            forward(&pending, shell);
+                        }
+
+                        forward(event, shell);
+                    }
+                }
+            },
+        )
+        .into()
+}
+
+#[derive(Debug, Default)]
+struct Internal {
+    progress: Progress,
+    /// First key press of a chord in progress, replayed if the chord is
+    /// interrupted
+    pending: Option<Event>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    /// The event isn't part of the chord
+    Ignored,
+    /// The first key combination was pressed and the second is awaited
+    Started,
+    /// The chord is complete
+    Pressed,
+    /// A repeat of the last key of a pressed chord
+    Held,
+    /// The last key of a pressed chord was released
+    Released,
+    /// The chord timed out or another key was pressed
+    Interrupted,
+}
+
+/// Matches key events against a chord
+#[derive(Debug, Default)]
+struct Progress {
+    started_at: Option<Instant>,
+    /// Last key of the pressed chord, only tracked for `on_release`
+    held: Option<Key>,
+}
+
+impl Progress {
+    fn press(
+        &mut self,
+        chord: &Chord,
+        key: &Key,
+        modifiers: Modifiers,
+        track_release: bool,
+        now: Instant,
+    ) -> Outcome {
+        let matches = |(k, m): &(Key, Modifiers)| k == key && *m == modifiers;
+
+        if self.held.as_ref() == Some(key) {
+            return Outcome::Held;
+        }
+
+        if let Some(started_at) = self.started_at {
+            // Modifiers may be pressed again between the two combinations
+            if is_modifier(key) {
+                return Outcome::Ignored;
+            }
+
+            self.started_at = None;
+
+            return match &chord.second {
+                Some(second)
+                    if matches(second)
+                        && now.duration_since(started_at) < chord.timeout =>
+                {
+                    self.press_chord(key, track_release)
+                }
+                _ => Outcome::Interrupted,
+            };
+        }
+
+        if !matches(&chord.first) {
+            return Outcome::Ignored;
+        }
+
+        if chord.second.is_some() {
+            self.started_at = Some(now);
+            Outcome::Started
+        } else {
+            self.press_chord(key, track_release)
+        }
+    }
+
+    fn press_chord(&mut self, key: &Key, track_release: bool) -> Outcome {
+        if track_release {
+            self.held = Some(key.clone());
+        }
+
+        Outcome::Pressed
+    }
+
+    fn release(&mut self, key: &Key) -> Outcome {
+        if self.held.as_ref() == Some(key) {
+            self.held = None;
+            Outcome::Released
+        } else {
+            Outcome::Ignored
+        }
+    }
+
+    fn expire(&mut self, chord: &Chord, now: Instant) -> Outcome {
+        match self.started_at {
+            Some(started_at)
+                if now.duration_since(started_at) >= chord.timeout =>
+            {
+                self.started_at = None;
+                Outcome::Interrupted
+            }
+            _ => Outcome::Ignored,
+        }
+    }
+
+    /// Forgets any chord in progress, releasing a held chord
+    fn reset(&mut self) -> Outcome {
+        self.started_at = None;
+
+        if self.held.take().is_some() {
+            Outcome::Released
+        } else {
+            Outcome::Ignored
+        }
+    }
+}
+
+fn is_modifier(key: &Key) -> bool {
+    matches!(
+        key,
+        Key::Named(
+            Named::Control
+                | Named::Shift
+                | Named::Alt
+                | Named::Super
+                | Named::Meta
+                | Named::Hyper
+        )
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(c: &str) -> Key {
+        Key::Character(c.into())
+    }
+
+    fn ctrl_x_ctrl_o() -> Chord {
+        Chord::new(key("x"), Modifiers::CTRL)
+            .then(key("o"), Modifiers::CTRL)
+            .timeout(Duration::from_millis(500))
+    }
+
+    #[test]
+    fn single() {
+        let chord = Chord::new(Key::Named(Named::Alt), Modifiers::ALT);
+        let alt = Key::Named(Named::Alt);
+        let now = Instant::now();
+        let mut progress = Progress::default();
+
+        assert_eq!(
+            progress.press(&chord, &key("a"), Modifiers::ALT, true, now),
+            Outcome::Ignored
+        );
+        assert_eq!(
+            progress.press(&chord, &alt, Modifiers::ALT, true, now),
+            Outcome::Pressed
+        );
+        // Key repeat while holding
+        assert_eq!(
+            progress.press(&chord, &alt, Modifiers::ALT, true, now),
+            Outcome::Held
+        );
+        assert_eq!(progress.release(&key("a")), Outcome::Ignored);
+        assert_eq!(progress.release(&alt), Outcome::Released);
+        assert_eq!(progress.release(&alt), Outcome::Ignored);
+
+        // Without `on_release` every press is published
+        assert_eq!(
+            progress.press(&chord, &alt, Modifiers::ALT, false, now),
+            Outcome::Pressed
+        );
+        assert_eq!(
+            progress.press(&chord, &alt, Modifiers::ALT, false, now),
+            Outcome::Pressed
+        );
+        assert_eq!(progress.release(&alt), Outcome::Ignored);
+    }
+
+    #[test]
+    fn sequence() {
+        let chord = ctrl_x_ctrl_o();
+        let now = Instant::now();
+        let later = |millis| now + Duration::from_millis(millis);
+        let mut progress = Progress::default();
+
+        assert_eq!(
+            progress.press(&chord, &key("x"), Modifiers::CTRL, true, now),
+            Outcome::Started
+        );
+        // Control pressed again between the combinations
+        assert_eq!(
+            progress.press(
+                &chord,
+                &Key::Named(Named::Control),
+                Modifiers::CTRL,
+                true,
+                later(100),
+            ),
+            Outcome::Ignored
+        );
+        assert_eq!(progress.expire(&chord, later(200)), Outcome::Ignored);
+        assert_eq!(
+            progress.press(
+                &chord,
+                &key("o"),
+                Modifiers::CTRL,
+                true,
+                later(300)
+            ),
+            Outcome::Pressed
+        );
+        assert_eq!(progress.release(&key("o")), Outcome::Released);
+
+        // The second combination alone does nothing
+        assert_eq!(
+            progress.press(&chord, &key("o"), Modifiers::CTRL, true, now),
+            Outcome::Ignored
+        );
+    }
+
+    #[test]
+    fn interrupted() {
+        let chord = ctrl_x_ctrl_o();
+        let now = Instant::now();
+        let later = |millis| now + Duration::from_millis(millis);
+        let mut progress = Progress::default();
+
+        // Another key
+        progress.press(&chord, &key("x"), Modifiers::CTRL, false, now);
+        assert_eq!(
+            progress.press(&chord, &key("p"), Modifiers::CTRL, false, now),
+            Outcome::Interrupted
+        );
+        assert_eq!(
+            progress.press(&chord, &key("o"), Modifiers::CTRL, false, now),
+            Outcome::Ignored
+        );
+
+        // Second key without modifiers
+        progress.press(&chord, &key("x"), Modifiers::CTRL, false, now);
+        assert_eq!(
+            progress.press(&chord, &key("o"), Modifiers::empty(), false, now),
+            Outcome::Interrupted
+        );
+
+        // Second key after the timeout
+        progress.press(&chord, &key("x"), Modifiers::CTRL, false, now);
+        assert_eq!(
+            progress.press(
+                &chord,
+                &key("o"),
+                Modifiers::CTRL,
+                false,
+                later(600)
+            ),
+            Outcome::Interrupted
+        );
+
+        // Timeout without further key presses
+        progress.press(&chord, &key("x"), Modifiers::CTRL, false, now);
+        assert_eq!(progress.expire(&chord, later(500)), Outcome::Interrupted);
+        assert_eq!(progress.expire(&chord, later(700)), Outcome::Ignored);
+    }
+
+    #[test]
+    fn reset() {
+        let chord = ctrl_x_ctrl_o();
+        let now = Instant::now();
+        let mut progress = Progress::default();
+
+        progress.press(&chord, &key("x"), Modifiers::CTRL, true, now);
+        assert_eq!(progress.reset(), Outcome::Ignored);
+        assert_eq!(
+            progress.press(&chord, &key("o"), Modifiers::CTRL, true, now),
+            Outcome::Ignored
+        );
+
+        progress.press(&chord, &key("x"), Modifiers::CTRL, true, now);
+        progress.press(&chord, &key("o"), Modifiers::CTRL, true, now);
+        assert_eq!(progress.reset(), Outcome::Released);
+    }
+}
@@ -1,11 +1,13 @@
-use data::appearance::theme::randomize_color;
-use data::{Config, isupport, message, target};
+use std::borrow::Cow;
+
+use data::client::metadata::Metadata;
+use data::{Config, User, isupport, message, target};
 use iced::widget::span;
 use iced::widget::text::Span;
 use iced::{Length, border};
 
 use super::{Element, Renderer, selectable_rich_text, selectable_text};
-use crate::{Theme, font};
+use crate::{Theme, font, theme};
 
 pub fn message_content<'a, M: 'a>(
     content: &'a message::Content,
@@ -18,10 +20,12 @@ pub fn message_content<'a, M: 'a>(
     message_content_impl::<(), M>(
         content,
         casemapping,
+        None,
         theme,
         on_link,
         style,
         Option::<(fn(&message::Link) -> _, fn(&message::Link, _, _) -> _)>::None,
+        None,
         config,
     )
 }
@@ -29,6 +33,34 @@ pub fn message_content<'a, M: 'a>(
 pub fn with_context<'a, T: Copy + 'a, M: 'a>(
     content: &'a message::Content,
     casemapping: isupport::CaseMap,
+    metadata: Option<&Metadata>,
+    theme: &'a Theme,
+    on_link: impl Fn(message::Link) -> M + 'a,
+    style: impl Fn(&Theme) -> selectable_text::Style + 'a,
+    link_entries: impl Fn(&message::Link) -> Vec<T> + 'a,
+    entry: impl Fn(&message::Link, T, Length) -> Element<'a, M> + 'a,
+    config: &Config,
+) -> Element<'a, M> {
+    message_content_impl(
+        content,
+        casemapping,
+        metadata,
+        theme,
+        on_link,
+        style,
+        Some((link_entries, entry)),
+        None,
+        config,
+    )
+}
+
+/// Content of an action without the nickname of its `sender` it starts with,
+/// to show it as a message from them
+pub fn without_sender<'a, T: Copy + 'a, M: 'a>(
+    content: &'a message::Content,
+    sender: &str,
+    casemapping: isupport::CaseMap,
+    metadata: Option<&Metadata>,
     theme: &'a Theme,
     on_link: impl Fn(message::Link) -> M + 'a,
     style: impl Fn(&Theme) -> selectable_text::Style + 'a,
@@ -39,10 +71,12 @@ pub fn with_context<'a, T: Copy + 'a, M: 'a>(
     message_content_impl(
         content,
         casemapping,
+        metadata,
         theme,
         on_link,
         style,
         Some((link_entries, entry)),
+        Some(sender),
         config,
     )
 }
@@ -51,6 +85,7 @@ pub fn with_context<'a, T: Copy + 'a, M: 'a>(
 fn message_content_impl<'a, T: Copy + 'a, M: 'a>(
     content: &'a message::Content,
     casemapping: isupport::CaseMap,
+    metadata: Option<&Metadata>,
     theme: &'a Theme,
     on_link: impl Fn(message::Link) -> M + 'a,
     style: impl Fn(&Theme) -> selectable_text::Style + 'a,
@@ -58,10 +93,31 @@ fn message_content_impl<'a, T: Copy + 'a, M: 'a>(
         impl Fn(&message::Link) -> Vec<T> + 'a,
         impl Fn(&message::Link, T, Length) -> Element<'a, M> + 'a,
     )>,
+    sender: Option<&str>,
     config: &Config,
 ) -> Element<'a, M> {
+    // Nickname of the sender, stripped from the start of the content
+    let mut prefix = sender.map(|sender| format!("{sender} "));
+    // Mentions are colored as the nickname is next to messages
+    let nickname_color = |user: &User| {
+        theme::text::nickname_color(
+            theme,
+            config,
+            config.buffer.channel.message.nickname_color,
+            user.seed(),
+            metadata.and_then(|metadata| {
+                metadata.profile(user.nickname().as_ref(), casemapping)
+            }),
+        )
+    };
+
     match content {
         data::message::Content::Plain(text) => {
+            let text = prefix
+                .as_deref()
+                .and_then(|prefix| text.strip_prefix(prefix))
+                .unwrap_or(text);
+
             selectable_text(text).style(style).into()
         }
         data::message::Content::Fragments(fragments) => {
@@ -84,53 +140,48 @@ fn message_content_impl<'a, T: Copy + 'a, M: 'a>(
                                     casemapping,
                                 ),
                             )),
-                        data::message::Fragment::User(user, text) => {
-                            let color = theme.colors().buffer.nickname;
-                            let seed = match &config
-                                .buffer
-                                .channel
-                                .message
-                                .nickname_color
-                            {
-                                data::buffer::Color::Solid => None,
-                                data::buffer::Color::Unique => {
-                                    Some(user.seed())
-                                }
-                            };
-
-                            let color = match seed {
-                                Some(seed) => randomize_color(color, seed),
-                                None => theme.colors().text.primary,
-                            };
-
-                            span(text)
-                                .color(color)
-                                .link(message::Link::User(user.clone()))
-                        }
+                        data::message::Fragment::User(user, text) => span(text)
+                            .color(nickname_color(user))
+                            .link(message::Link::User(user.clone())),
                         data::message::Fragment::HighlightNick(user, text) => {
-                            let color = theme.colors().buffer.nickname;
-                            let seed = match &config
-                                .buffer
-                                .channel
-                                .message
-                                .nickname_color
-                            {
-                                data::buffer::Color::Solid => None,
-                                data::buffer::Color::Unique => {
-                                    Some(user.seed())
-                                }
-                            };
-
-                            let color = match seed {
-                                Some(seed) => randomize_color(color, seed),
-                                None => theme.colors().text.primary,
-                            };
-
                             span(text)
-                                .color(color)
+                                .color(
+                                    theme
+                                        .colors()
+                                        .buffer
+                                        .mention
+                                        .unwrap_or_else(|| {
+                                            nickname_color(user)
+                                        }),
+                                )
+                                .font(font::MONO_BOLD.clone())
                                 .background(theme.colors().buffer.highlight)
                                 .link(message::Link::User(user.clone()))
                         }
+                        data::message::Fragment::HighlightMention(text) => {
+                            span(text.as_str())
+                                .color(
+                                    theme
+                                        .colors()
+                                        .buffer
+                                        .mention
+                                        .unwrap_or_else(|| {
+                                            theme::text::nickname_color(
+                                                theme,
+                                                config,
+                                                config
+                                                    .buffer
+                                                    .channel
+                                                    .message
+                                                    .nickname_color,
+                                                text,
+                                                None,
+                                            )
+                                        }),
+                                )
+                                .font(font::MONO_BOLD.clone())
+                                .background(theme.colors().buffer.highlight)
+                        }
                         data::message::Fragment::HighlightMatch(text) => {
                             span(text.as_str())
                                 .color(theme.colors().text.primary)
@@ -153,6 +204,16 @@ fn message_content_impl<'a, T: Copy + 'a, M: 'a>(
                                 .underline(formatting.underline)
                                 .strikethrough(formatting.strikethrough);
 
+                            // Reversed colors are already swapped
+                            if formatting.reverse
+                                && formatting.fg.is_none()
+                                && formatting.bg.is_none()
+                            {
+                                span = span
+                                    .color(theme.colors().buffer.background)
+                                    .background(theme.colors().text.primary);
+                            }
+
                             if formatting.monospace {
                                 span = span
                                     .padding([0, 4])
@@ -184,6 +245,7 @@ fn message_content_impl<'a, T: Copy + 'a, M: 'a>(
                             span
                         }
                     })
+                    .filter_map(|span| strip_prefix(span, &mut prefix))
                     .collect::<Vec<_>>(),
             )
             .on_link(on_link)
@@ -209,12 +271,58 @@ fn message_content_impl<'a, T: Copy + 'a, M: 'a>(
                 span(format!("{: <5}", record.level))
                     .color(theme.colors().text.secondary),
                 span(" "),
-                span(&record.message),
             ]);
 
+            // Where it's from, dimmed so the message stands out
+            spans.extend(
+                (!record.target.is_empty())
+                    .then(|| format!("{} ", record.target))
+                    .into_iter()
+                    .chain(
+                        record
+                            .server
+                            .as_ref()
+                            .map(|server| format!("[{server}] ")),
+                    )
+                    .map(|origin| {
+                        span(origin).color(theme.colors().text.tertiary)
+                    }),
+            );
+
+            spans.push(span(&record.message));
+
             selectable_rich_text::<M, message::Link, T, Theme, Renderer>(spans)
                 .style(style)
                 .into()
         }
     }
 }
+
+/// Strips what's left of `prefix` from the start of `span`, dropping it if
+/// nothing is left of it
+fn strip_prefix<'a, Link>(
+    mut span: Span<'a, Link>,
+    prefix: &mut Option<String>,
+) -> Option<Span<'a, Link>> {
+    let Some(remaining) = prefix.take() else {
+        return Some(span);
+    };
+
+    if let Some(rest) = remaining.strip_prefix(span.text.as_ref()) {
+        // The whole span is part of the prefix
+        if !rest.is_empty() {
+            *prefix = Some(rest.to_string());
+        }
+
+        return None;
+    }
+
+    if span.text.starts_with(&remaining) {
+        span.text = match span.text {
+            Cow::Borrowed(text) => Cow::Borrowed(&text[remaining.len()..]),
+            Cow::Owned(text) => Cow::Owned(text[remaining.len()..].to_string()),
+        };
+    }
+
+    Some(span)
+}
@@ -9,6 +9,8 @@ use super::{Element, Renderer, decorate};
 pub enum When {
     Visible,
     NotVisible,
+    /// Scrolled up out of the viewport
+    Above,
 }
 
 pub fn notify_visibility<'a, Message>(
@@ -39,12 +41,15 @@ where
                 {
                     let mut sent = sent.borrow_mut();
 
-                    let is_visible =
-                        viewport.expand(margin).intersects(&layout.bounds());
+                    let viewport = viewport.expand(margin);
+                    let bounds = layout.bounds();
+
+                    let is_visible = viewport.intersects(&bounds);
 
                     let should_notify = match when {
                         When::Visible => is_visible,
                         When::NotVisible => !is_visible,
+                        When::Above => bounds.y + bounds.height < viewport.y,
                     };
 
                     if should_notify && !*sent {
@@ -540,6 +540,8 @@ where
                                 );
                             state.context_menu_link = Some(link);
                             self.cached_entries = entries;
+                            // Menus around the text don't open as well
+                            shell.capture_event();
                         }
                     }
                 }
@@ -6,7 +6,7 @@ use crate::theme;
 
 pub fn tooltip<'a, Message: 'a>(
     content: impl Into<Element<'a, Message>>,
-    tooltip: Option<&'a str>,
+    tooltip: Option<impl text::IntoFragment<'a>>,
     position: Position,
 ) -> Element<'a, Message> {
     match tooltip {
@@ -61,6 +61,36 @@ pub fn toggle_fullscreen<Message: 'static + Send>() -> Task<Message> {
     })
 }
 
+/// Hides the window if it's focused, otherwise shows and focuses it
+pub fn toggle_visibility<Message: 'static + Send>(
+    window: Id,
+    focused: bool,
+) -> Task<Message> {
+    if focused {
+        iced::window::set_mode(window, iced::window::Mode::Hidden)
+    } else {
+        show(window)
+    }
+}
+
+/// Restores the window if it's hidden or minimized and focuses it
+pub fn show<Message: 'static + Send>(window: Id) -> Task<Message> {
+    iced::window::get_mode(window).then(move |mode| {
+        let restore = match mode {
+            iced::window::Mode::Hidden => {
+                iced::window::set_mode(window, iced::window::Mode::Windowed)
+            }
+            iced::window::Mode::Windowed | iced::window::Mode::Fullscreen => {
+                Task::none()
+            }
+        };
+
+        restore
+            .chain(iced::window::minimize(window, false))
+            .chain(gain_focus(window))
+    })
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum Event {
     Moved(Point),